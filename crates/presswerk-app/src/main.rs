@@ -12,7 +12,12 @@
 mod services;
 mod state;
 
+use std::sync::OnceLock;
+
 use dioxus::prelude::*;
+use presswerk_core::logging::{self, LogBuffer, LogHandle};
+use presswerk_core::redact::RedactingWriter;
+use tracing_subscriber::prelude::*;
 
 use pages::add_printer::AddPrinter;
 use pages::audit::Audit;
@@ -29,20 +34,60 @@
 use pages::text_editor::TextEditor;
 
 use services::app_services::AppServices;
+use services::data_dir;
+
+/// The log file is started afresh once it grows past this size.
+const LOG_FILE_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Level filter handle and recent-entries buffer, handed to the services
+/// once the UI starts.
+static LOGGING: OnceLock<(LogHandle, LogBuffer)> = OnceLock::new();
 
 fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
+    // Logs go to stderr and to a file that Print Doctor can attach to a
+    // help bundle. Secrets are redacted before they reach the file. Losing
+    // the file is not fatal.
+    let file_layer = open_log_file().map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(std::sync::Mutex::new(RedactingWriter::new(file)))
+    });
+
+    // The level can be changed from Settings later; recent entries are
+    // kept in memory for the in-app log viewer.
+    let (filter, log_handle) = logging::reloadable_filter(
+        tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+    );
+    let log_buffer = LogBuffer::new(logging::DEFAULT_BUFFER_CAPACITY);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_layer)
+        .with(log_buffer.clone())
         .init();
+    let _ = LOGGING.set((log_handle, log_buffer));
 
     tracing::info!("Print Doctor (Presswerk) starting");
 
     dioxus::launch(app);
 }
 
+/// Open the log file for appending, truncating it first if it has grown
+/// past [`LOG_FILE_MAX_BYTES`].
+fn open_log_file() -> Option<std::fs::File> {
+    let path = data_dir::log_path();
+    let oversized = std::fs::metadata(&path).is_ok_and(|m| m.len() > LOG_FILE_MAX_BYTES);
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(!oversized)
+        .write(true)
+        .truncate(oversized)
+        .open(path)
+        .ok()
+}
+
 /// Top-level route enum.
 ///
 /// Easy Mode routes are the default experience. Advanced routes provide
@@ -85,14 +130,20 @@ enum Route {
 /// Root component.
 fn app() -> Element {
     // Initialise backend services (databases, mDNS, config)
-    let svc = use_hook(|| match AppServices::init() {
-        Ok(s) => {
-            tracing::info!("backend services initialised");
-            s
-        }
-        Err(e) => {
-            tracing::error!(error = %e, "persistent storage failed — using in-memory fallback");
-            AppServices::fallback().expect("even fallback init failed")
+    let svc = use_hook(|| {
+        let svc = match AppServices::init() {
+            Ok(s) => {
+                tracing::info!("backend services initialised");
+                s
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "persistent storage failed — using in-memory fallback");
+                AppServices::fallback().expect("even fallback init failed")
+            }
+        };
+        match LOGGING.get() {
+            Some((handle, buffer)) => svc.with_logging(handle.clone(), buffer.clone()),
+            None => svc,
         }
     });
 
@@ -100,12 +151,15 @@ fn app() -> Element {
     use_context_provider(|| svc.clone());
     use_context_provider(|| Signal::new(state::AppState::new(&svc)));
 
-    // Auto-start discovery if we have it
+    // Auto-start discovery if we have it and the user wants it
     let svc_clone = svc.clone();
     use_hook(move || {
-        if let Err(e) = svc_clone.start_discovery() {
+        if svc_clone.config().auto_discovery
+            && let Err(e) = svc_clone.start_discovery()
+        {
             tracing::warn!(error = %e, "auto-start discovery failed");
         }
+        svc_clone.start_revival();
     });
 
     rsx! {
@@ -120,6 +120,9 @@ pub fn AddPrinter() -> Element {
                                 last_seen: chrono::Utc::now(),
                                 stale: false,
                                 manually_added: true,
+                                addresses: vec![ip],
+                                uuid: None,
+                                document_formats: Vec::new(),
                             };
 
                             // Add to state
@@ -10,10 +10,16 @@
 
 use dioxus::prelude::*;
 
+use presswerk_bridge::traits::Permission;
+use presswerk_core::error::Result;
 use presswerk_print::diagnostics;
 
+use crate::services::data_dir;
 use crate::state::AppState;
 
+/// File name of the help bundle handed to the share sheet.
+const BUNDLE_FILE_NAME: &str = "print-doctor-report.zip";
+
 /// Diagnostic wizard states.
 #[derive(Debug, Clone, PartialEq)]
 enum WizardState {
@@ -30,6 +36,7 @@ pub fn Doctor() -> Element {
     let state = use_context::<Signal<AppState>>();
     let mut wizard = use_signal(|| WizardState::Intro);
     let mut report = use_signal(|| Option::<diagnostics::DiagnosticReport>::None);
+    let mut share_status = use_signal(|| Option::<String>::None);
 
     rsx! {
         div { style: "max-width: 600px; margin: 0 auto;",
@@ -47,25 +54,46 @@ pub fn Doctor() -> Element {
                             "We'll check everything step by step:"
                         }
                         div { style: "text-align: left; max-width: 300px; margin: 0 auto;",
-                            StepPreview { num: 1, label: "Network connection" }
-                            StepPreview { num: 2, label: "Finding printers" }
-                            StepPreview { num: 3, label: "Reaching the printer" }
-                            StepPreview { num: 4, label: "Printer language" }
-                            StepPreview { num: 5, label: "Printer readiness" }
-                            StepPreview { num: 6, label: "Test print" }
+                            StepPreview { num: 1, label: "App permissions" }
+                            StepPreview { num: 2, label: "Network connection" }
+                            StepPreview { num: 3, label: "Finding printers" }
+                            StepPreview { num: 4, label: "Reaching the printer" }
+                            StepPreview { num: 5, label: "Printer language" }
+                            StepPreview { num: 6, label: "Printer readiness" }
+                            StepPreview { num: 7, label: "Test print" }
                         }
                         button {
                             style: "margin-top: 32px; padding: 16px 48px; border-radius: 12px; border: none; background: #007aff; color: white; font-size: 20px; font-weight: bold;",
                             onclick: {
                                 let selected = state.read().selected_printer.clone();
+                                // Every address the selected printer announced, so the
+                                // reachability check is not limited to the one in its URI.
+                                let (addresses, port) = state
+                                    .read()
+                                    .printers
+                                    .iter()
+                                    .find(|p| Some(&p.uri) == selected.as_ref())
+                                    .map(|p| (p.addresses.clone(), Some(p.port)))
+                                    .unwrap_or_default();
                                 move |_| {
                                     wizard.set(WizardState::Running { current_step: 0 });
                                     let selected = selected.clone();
+                                    let addresses = addresses.clone();
                                     spawn(async move {
-                                        let result = diagnostics::run_diagnostics(
-                                            None, None,
+                                        let bridge = presswerk_bridge::platform_bridge();
+                                        let permissions: Vec<_> = Permission::ALL
+                                            .iter()
+                                            .map(|&permission| (permission, bridge.permission_status(permission)))
+                                            .collect();
+                                        let mut result = diagnostics::run_diagnostics_default(
+                                            &permissions,
+                                            &addresses, port,
                                             selected.as_deref(),
                                         ).await;
+                                        result.device_info.wifi_network = bridge
+                                            .wifi_ssid()
+                                            .ok()
+                                            .flatten();
                                         report.set(Some(result));
                                         wizard.set(WizardState::Complete);
                                     });
@@ -83,7 +111,7 @@ pub fn Doctor() -> Element {
                             "\u{1F50D}"
                         }
                         p { style: "font-size: 20px; color: #007aff;",
-                            "Checking... step {current_step + 1} of 6"
+                            "Checking... step {current_step + 1} of 7"
                         }
                         p { style: "color: #666; font-size: 16px; margin-top: 8px;",
                             "This may take a moment."
@@ -155,6 +183,7 @@ pub fn Doctor() -> Element {
                                     onclick: move |_| {
                                         wizard.set(WizardState::Intro);
                                         report.set(None);
+                                        share_status.set(None);
                                     },
                                     "Run Again"
                                 }
@@ -171,6 +200,28 @@ pub fn Doctor() -> Element {
                                     },
                                     "I Need Help"
                                 }
+                                button {
+                                    style: "flex: 1; padding: 14px; border-radius: 12px; border: none; background: #34c759; color: white; font-size: 16px; font-weight: bold;",
+                                    onclick: {
+                                        let rpt = rpt.clone();
+                                        move |_| {
+                                            let status = match share_bundle(&rpt) {
+                                                Ok(()) => "Report ready to send.".to_string(),
+                                                Err(e) => {
+                                                    tracing::warn!(error = %e, "sharing help bundle failed");
+                                                    presswerk_core::human_errors::humanize_error(&e).message
+                                                }
+                                            };
+                                            share_status.set(Some(status));
+                                        }
+                                    },
+                                    "Send to Helper"
+                                }
+                            }
+                            if let Some(ref status) = *share_status.read() {
+                                p { style: "text-align: center; color: #666; font-size: 14px; margin-top: 12px;",
+                                    "{status}"
+                                }
                             }
                         }
                     } else {
@@ -184,6 +235,16 @@ pub fn Doctor() -> Element {
     }
 }
 
+/// Export the report and recent logs as a zip and open the share sheet.
+fn share_bundle(report: &diagnostics::DiagnosticReport) -> Result<()> {
+    let bundle = diagnostics::export_bundle(report, &data_dir::log_path())?;
+    let path = data_dir::data_subdir("temp").join(BUNDLE_FILE_NAME);
+    std::fs::write(&path, bundle)?;
+    tracing::info!(path = %path.display(), "help bundle written");
+
+    presswerk_bridge::platform_bridge().share_file(&path.to_string_lossy(), "application/zip")
+}
+
 #[component]
 fn StepPreview(num: u8, label: &'static str) -> Element {
     rsx! {
@@ -26,13 +26,17 @@ pub fn EasyPrint() -> Element {
     let mut done = use_signal(|| false);
     let mut error_msg = use_signal(|| Option::<String>::None);
 
-    // Auto-select the only printer, or the last-used printer
+    // Auto-select the only printer, the last-used printer, or else the
+    // healthiest of the printers found
     let auto_printer = {
         let printers = &state.read().printers;
         if printers.len() == 1 {
             Some(printers[0].uri.clone())
+        } else if let Some(uri) = state.read().selected_printer.clone() {
+            Some(uri)
         } else {
-            state.read().selected_printer.clone()
+            let uris: Vec<String> = printers.iter().map(|p| p.uri.clone()).collect();
+            svc.best_printer(&uris)
         }
     };
 
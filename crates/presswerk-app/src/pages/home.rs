@@ -4,6 +4,9 @@
 // Home page — printer discovery list and quick actions.
 
 use dioxus::prelude::*;
+use presswerk_bridge::async_bridge::PlatformBridgeAsync;
+use presswerk_bridge::traits::{Permission, PermissionState};
+use presswerk_print::health::Reliability;
 
 use crate::Route;
 use crate::services::app_services::AppServices;
@@ -13,6 +16,7 @@
 pub fn Home() -> Element {
     let mut state = use_context::<Signal<AppState>>();
     let svc = use_context::<AppServices>();
+    let mut config = use_signal(|| svc.config());
 
     // Periodically refresh the printer list from the discovery engine
     let svc_poll = svc.clone();
@@ -58,11 +62,19 @@ pub fn Home() -> Element {
                         let svc = svc.clone();
                         move |_| {
                             tracing::info!("Starting printer discovery");
-                            state.write().scanning = true;
-                            if let Err(e) = svc.start_discovery() {
-                                tracing::error!(error = %e, "discovery start failed");
-                                state.write().status_message = Some(format!("Discovery failed: {e}"));
-                            }
+                            let svc = svc.clone();
+                            spawn(async move {
+                                let bridge = presswerk_bridge::shared_platform_bridge();
+                                if bridge.request_permission_async(Permission::LocalNetwork).await == PermissionState::Denied {
+                                    state.write().status_message = Some("Local network access is off. Allow it for Presswerk in Settings.".into());
+                                    return;
+                                }
+                                state.write().scanning = true;
+                                if let Err(e) = svc.start_discovery() {
+                                    tracing::error!(error = %e, "discovery start failed");
+                                    state.write().status_message = Some(format!("Discovery failed: {e}"));
+                                }
+                            });
                         }
                     },
                     "Scan for Printers"
@@ -80,6 +92,11 @@ pub fn Home() -> Element {
                     {
                         let uri = printer.uri.clone();
                         let is_selected = state.read().selected_printer.as_deref() == Some(&uri);
+                        let display_name = config.read().display_name(printer).to_string();
+                        let alias_key = printer.uuid.clone().unwrap_or_else(|| uri.clone());
+                        let alias = config.read().alias_for(&alias_key).unwrap_or_default().to_string();
+                        let svc_alias = svc.clone();
+                        let reliability = svc.printer_health(&uri).reliability();
                         let border = if is_selected { "2px solid #007aff" } else { "1px solid #e0e0e0" };
                         rsx! {
                             div {
@@ -88,7 +105,13 @@ pub fn Home() -> Element {
                                     state.write().selected_printer = Some(uri.clone());
                                     tracing::info!(uri = %uri, "printer selected");
                                 },
-                                strong { "{printer.name}" }
+                                strong { "{display_name}" }
+                                if display_name != printer.name {
+                                    span { style: "color: #999; font-size: 12px; margin-left: 6px;", "{printer.name}" }
+                                }
+                                if reliability != Reliability::Unknown {
+                                    span { style: "color: #666; font-size: 12px; margin-left: 6px;", "{reliability.label()}" }
+                                }
                                 p { style: "color: #666; font-size: 14px; margin: 4px 0;",
                                     "{printer.ip}:{printer.port}"
                                     if let Some(ref model) = printer.make_and_model {
@@ -100,6 +123,21 @@ pub fn Home() -> Element {
                                     if printer.supports_duplex { span { "Duplex " } }
                                     if printer.supports_tls { span { "TLS " } }
                                 }
+                                if is_selected {
+                                    input {
+                                        r#type: "text",
+                                        placeholder: "Nickname (e.g. Downstairs Printer)",
+                                        value: "{alias}",
+                                        style: "width: 100%; margin-top: 8px; padding: 6px; border-radius: 6px; border: 1px solid #ccc; box-sizing: border-box;",
+                                        onclick: move |evt| evt.stop_propagation(),
+                                        onchange: move |evt| {
+                                            match svc_alias.set_printer_alias(&alias_key, &evt.value()) {
+                                                Ok(()) => config.set(svc_alias.config()),
+                                                Err(e) => tracing::error!(error = %e, "failed to save printer nickname"),
+                                            }
+                                        },
+                                    }
+                                }
                             }
                         }
                     }
@@ -7,7 +7,9 @@
 
 use dioxus::prelude::*;
 
-use presswerk_core::types::{DocumentType, DuplexMode, Orientation, PaperSize, PrintSettings};
+use presswerk_core::types::{
+    DocumentType, DuplexMode, Orientation, PaperSize, PrintProtocol, PrintSettings,
+};
 
 use crate::services::app_services::AppServices;
 use crate::state::AppState;
@@ -63,19 +65,35 @@ fn bg(&self) -> &'static str {
 pub fn Print() -> Element {
     let mut state = use_context::<Signal<AppState>>();
     let svc = use_context::<AppServices>();
+    let config = svc.config();
     let mut file_name = use_signal(|| Option::<String>::None);
     let mut file_bytes = use_signal(|| Option::<Vec<u8>>::None);
     let mut file_type = use_signal(|| DocumentType::Pdf);
     let mut printing = use_signal(|| false);
     let mut print_result = use_signal(|| Option::<String>::None);
+    let mut print_warnings = use_signal(Vec::<String>::new);
     let mut stage = use_signal(|| PrintStage::Idle);
 
-    // Print settings — bound to the UI inputs
-    let mut copies = use_signal(|| 1u32);
-    let mut color = use_signal(|| true);
-    let mut duplex = use_signal(|| DuplexMode::Simplex);
-    let mut paper_size = use_signal(|| PaperSize::A4);
-    let mut orientation = use_signal(|| Orientation::Portrait);
+    // Print settings — bound to the UI inputs, starting from the selected
+    // printer's saved defaults.
+    let initial = config.defaults_for(state.read().selected_printer.as_deref().unwrap_or_default());
+    let mut copies = use_signal(|| initial.copies);
+    let mut color = use_signal(|| initial.color);
+    let mut duplex = use_signal(|| initial.duplex);
+    let mut paper_size = use_signal(|| initial.paper_size);
+    let mut orientation = use_signal(|| initial.orientation);
+    let mut forced_protocol = use_signal(|| initial.forced_protocol);
+
+    let current_settings = move || PrintSettings {
+        copies: *copies.read(),
+        paper_size: *paper_size.read(),
+        duplex: *duplex.read(),
+        orientation: *orientation.read(),
+        color: *color.read(),
+        page_range: None,
+        scale_to_fit: true,
+        forced_protocol: *forced_protocol.read(),
+    };
 
     rsx! {
         div {
@@ -154,15 +172,25 @@ pub fn Print() -> Element {
                 } else {
                     select {
                         style: "width: 100%; padding: 8px; font-size: 16px; border-radius: 8px; border: 1px solid #ccc;",
-                        onchange: move |evt| {
-                            let val = evt.value().to_string();
-                            if !val.is_empty() {
-                                state.write().selected_printer = Some(val);
+                        onchange: {
+                            let svc = svc.clone();
+                            move |evt: Event<FormData>| {
+                                let val = evt.value().to_string();
+                                if !val.is_empty() {
+                                    let defaults = svc.config().defaults_for(&val);
+                                    copies.set(defaults.copies);
+                                    color.set(defaults.color);
+                                    duplex.set(defaults.duplex);
+                                    paper_size.set(defaults.paper_size);
+                                    orientation.set(defaults.orientation);
+                                    forced_protocol.set(defaults.forced_protocol);
+                                    state.write().selected_printer = Some(val);
+                                }
                             }
                         },
                         option { value: "", "Select a printer..." }
                         for printer in state.read().printers.iter() {
-                            option { value: "{printer.uri}", "{printer.name}" }
+                            option { value: "{printer.uri}", "{config.display_name(printer)}" }
                         }
                     }
                 }
@@ -206,9 +234,9 @@ pub fn Print() -> Element {
                                 _ => DuplexMode::Simplex,
                             });
                         },
-                        option { value: "simplex", "One-sided" }
-                        option { value: "long-edge", "Two-sided (long edge)" }
-                        option { value: "short-edge", "Two-sided (short edge)" }
+                        option { value: "simplex", selected: *duplex.read() == DuplexMode::Simplex, "One-sided" }
+                        option { value: "long-edge", selected: *duplex.read() == DuplexMode::LongEdge, "Two-sided (long edge)" }
+                        option { value: "short-edge", selected: *duplex.read() == DuplexMode::ShortEdge, "Two-sided (short edge)" }
                     }
 
                     label { "Paper:" }
@@ -225,12 +253,12 @@ pub fn Print() -> Element {
                                 _ => PaperSize::A4,
                             });
                         },
-                        option { value: "A4", "A4" }
-                        option { value: "A3", "A3" }
-                        option { value: "A5", "A5" }
-                        option { value: "Letter", "Letter" }
-                        option { value: "Legal", "Legal" }
-                        option { value: "Tabloid", "Tabloid" }
+                        option { value: "A4", selected: *paper_size.read() == PaperSize::A4, "A4" }
+                        option { value: "A3", selected: *paper_size.read() == PaperSize::A3, "A3" }
+                        option { value: "A5", selected: *paper_size.read() == PaperSize::A5, "A5" }
+                        option { value: "Letter", selected: *paper_size.read() == PaperSize::Letter, "Letter" }
+                        option { value: "Legal", selected: *paper_size.read() == PaperSize::Legal, "Legal" }
+                        option { value: "Tabloid", selected: *paper_size.read() == PaperSize::Tabloid, "Tabloid" }
                     }
 
                     label { "Orientation:" }
@@ -243,9 +271,53 @@ pub fn Print() -> Element {
                                 _ => Orientation::Portrait,
                             });
                         },
-                        option { value: "portrait", "Portrait" }
-                        option { value: "landscape", "Landscape" }
+                        option { value: "portrait", selected: *orientation.read() == Orientation::Portrait, "Portrait" }
+                        option { value: "landscape", selected: *orientation.read() == Orientation::Landscape, "Landscape" }
                     }
+
+                    // Interop debugging: skip the automatic fallback chain.
+                    label { "Protocol:" }
+                    select {
+                        style: "padding: 4px; border: 1px solid #ccc; border-radius: 4px;",
+                        onchange: move |evt| {
+                            let val = evt.value().to_string();
+                            forced_protocol.set(match val.as_str() {
+                                "ipps" => Some(PrintProtocol::Ipps),
+                                "ipp11" => Some(PrintProtocol::Ipp11),
+                                "ipp10" => Some(PrintProtocol::Ipp10),
+                                "lpr" => Some(PrintProtocol::Lpr),
+                                "raw" => Some(PrintProtocol::RawTcp),
+                                _ => None,
+                            });
+                        },
+                        option { value: "auto", selected: forced_protocol.read().is_none(), "Automatic" }
+                        option { value: "ipps", selected: *forced_protocol.read() == Some(PrintProtocol::Ipps), "Secure IPP (TLS)" }
+                        option { value: "ipp11", selected: *forced_protocol.read() == Some(PrintProtocol::Ipp11), "IPP 1.1" }
+                        option { value: "ipp10", selected: *forced_protocol.read() == Some(PrintProtocol::Ipp10), "IPP 1.0" }
+                        option { value: "lpr", selected: *forced_protocol.read() == Some(PrintProtocol::Lpr), "LPR/LPD" }
+                        option { value: "raw", selected: *forced_protocol.read() == Some(PrintProtocol::RawTcp), "Direct TCP (port 9100)" }
+                    }
+                }
+
+                button {
+                    style: "margin-top: 8px; padding: 6px 12px; border-radius: 4px; border: 1px solid #ccc; background: white; font-size: 14px;",
+                    disabled: state.read().selected_printer.is_none(),
+                    onclick: {
+                        let svc = svc.clone();
+                        move |_| {
+                            let Some(uri) = state.read().selected_printer.clone() else {
+                                return;
+                            };
+                            match svc.set_printer_defaults(&uri, &current_settings()) {
+                                Ok(()) => print_result.set(Some("Saved as this printer's defaults.".into())),
+                                Err(e) => {
+                                    tracing::error!(error = %e, "failed to save printer defaults");
+                                    print_result.set(Some(format!("Could not save the settings. {e}")));
+                                }
+                            }
+                        }
+                    },
+                    "Remember for this printer"
                 }
             }
 
@@ -261,29 +333,25 @@ pub fn Print() -> Element {
                         let printer_uri = state.read().selected_printer.clone();
                         let doc_type = *file_type.read();
 
-                        let settings = PrintSettings {
-                            copies: *copies.read(),
-                            paper_size: *paper_size.read(),
-                            duplex: *duplex.read(),
-                            orientation: *orientation.read(),
-                            color: *color.read(),
-                            page_range: None,
-                            scale_to_fit: true,
-                        };
+                        let settings = current_settings();
 
                         if let (Some(bytes), Some(name), Some(uri)) = (doc_bytes, doc_name, printer_uri) {
                             printing.set(true);
                             stage.set(PrintStage::Preparing);
                             print_result.set(None);
+                            print_warnings.set(Vec::new());
                             let svc = svc.clone();
 
                             spawn(async move {
+                                stage.set(PrintStage::CheckingPrinter);
+                                let warnings = svc.print_warnings(&bytes, doc_type, &uri, &settings).await;
+                                print_warnings.set(warnings.iter().map(ToString::to_string).collect());
                                 stage.set(PrintStage::Sending);
                                 match svc.print_document(bytes, name, doc_type, uri, settings).await {
                                     Ok(job_id) => {
                                         tracing::info!(job_id = %job_id, "print job submitted");
                                         stage.set(PrintStage::Complete);
-                                        print_result.set(Some(format!("Job submitted: {job_id}")));
+                                        print_result.set(Some(format!("Job submitted: {}", job_id.short())));
                                         if let Ok(jobs) = svc.all_jobs() {
                                             state.write().jobs = jobs;
                                         }
@@ -304,6 +372,14 @@ pub fn Print() -> Element {
                 if *printing.read() { "Printing..." } else { "Print" }
             }
 
+            // Warnings (the job is sent regardless)
+            for warning in print_warnings.read().iter() {
+                div {
+                    style: "margin-top: 16px; padding: 12px 16px; border-radius: 12px; background: #fff3cd; color: #856404; font-size: 14px;",
+                    "{warning}"
+                }
+            }
+
             // Progress feedback
             if *stage.read() != PrintStage::Idle {
                 {
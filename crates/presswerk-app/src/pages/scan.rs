@@ -4,80 +4,193 @@
 // Scan page — capture images, enhance, export as PDF.
 //
 // On desktop, the "capture" button opens a file dialog for image selection.
-// On mobile, it will use the native camera bridge (presswerk-bridge).
+// On mobile, it uses the native camera bridge (presswerk-bridge).
+//
+// Pages are enhanced and saved to a `ScanSession` as soon as they are
+// captured, so a scan interrupted by the app being killed can be resumed.
+//
+// Pages are binarized adaptively unless the user picks a manual threshold;
+// the slider sits over the luminance histogram of the last page so the cut
+// can be placed between the ink and paper peaks.
 
 use dioxus::prelude::*;
 
+use presswerk_core::error::Result;
 use presswerk_core::types::PaperSize;
-use presswerk_document::scan::enhance::ScanEnhancer;
+use presswerk_document::ImageProcessor;
+use presswerk_document::scan::enhance::{
+    DEFAULT_BLOCK_RADIUS, DEFAULT_THRESHOLD_OFFSET, ScanEnhancer,
+};
+use presswerk_print::scan_session::ScanSession;
 
 use crate::services::app_services::AppServices;
+use crate::services::scan_assembler::ScanPdfAssembler;
 
 #[component]
 pub fn Scan() -> Element {
     let svc = use_context::<AppServices>();
-    let mut scanned_pages = use_signal(Vec::<Vec<u8>>::new);
+    let mut session = use_signal(|| Option::<ScanSession>::None);
+    let mut resumable = use_signal({
+        let svc = svc.clone();
+        move || svc.unfinished_scan_sessions()
+    });
     let mut status_msg = use_signal(|| Option::<String>::None);
     let mut processing = use_signal(|| false);
+    // `None` means adaptive binarization.
+    let mut threshold = use_signal(|| Option::<u8>::None);
+    let histogram = use_signal(|| Option::<[u64; 256]>::None);
+    let page_count = session.read().as_ref().map_or(0, ScanSession::page_count);
 
     rsx! {
         div {
             h1 { "Scan" }
             p { style: "color: #666;", "Capture documents with your camera or load images." }
 
+            // Resume an interrupted scan
+            if session.read().is_none() {
+                if let Some(unfinished) = resumable.read().last().cloned() {
+                    button {
+                        style: "width: 100%; padding: 12px; border-radius: 8px; border: 1px solid #007aff; color: #007aff; background: #eef5ff; font-size: 15px;",
+                        onclick: {
+                            let svc = svc.clone();
+                            let id = unfinished.id.clone();
+                            move |_| match svc.resume_scan_session(&id) {
+                                Ok(resumed) => {
+                                    session.set(Some(resumed));
+                                    resumable.write().retain(|s| s.id != id);
+                                    status_msg.set(Some("Scan resumed.".into()));
+                                }
+                                Err(e) => status_msg.set(Some(format!("Cannot resume scan: {e}"))),
+                            }
+                        },
+                        "Resume scan ({unfinished.page_count} pages captured)"
+                    }
+                }
+            }
+
             // Capture / load button
             button {
                 style: "width: 100%; padding: 16px; border-radius: 12px; border: 2px dashed #007aff; color: #007aff; background: white; font-size: 16px; margin: 16px 0;",
                 disabled: *processing.read(),
-                onclick: move |_| {
-                    #[cfg(not(any(target_os = "ios", target_os = "android")))]
-                    {
-                        // Desktop: open file dialog for images
-                        if let Some(path) = rfd::FileDialog::new()
-                            .add_filter("Images", &["jpg", "jpeg", "png", "tiff", "tif", "bmp"])
-                            .pick_file()
+                onclick: {
+                    let svc = svc.clone();
+                    move |_| {
+                        #[cfg(not(any(target_os = "ios", target_os = "android")))]
                         {
-                            match std::fs::read(&path) {
-                                Ok(bytes) => {
-                                    tracing::info!(path = %path.display(), bytes = bytes.len(), "image loaded for scanning");
-                                    scanned_pages.write().push(bytes);
-                                    status_msg.set(Some("Page added.".into()));
-                                }
-                                Err(e) => {
-                                    status_msg.set(Some(format!("Error: {e}")));
+                            // Desktop: open file dialog for images
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Images", &["jpg", "jpeg", "png", "tiff", "tif", "bmp"])
+                                .pick_file()
+                            {
+                                match std::fs::read(&path) {
+                                    Ok(bytes) => {
+                                        tracing::info!(path = %path.display(), bytes = bytes.len(), "image loaded for scanning");
+                                        match add_captured_page(&svc, &mut session.write(), bytes, *threshold.read(), histogram) {
+                                            Ok(count) => status_msg.set(Some(format!("Page {count} saved."))),
+                                            Err(e) => status_msg.set(Some(format!("Could not save page: {e}"))),
+                                        }
+                                    }
+                                    Err(e) => {
+                                        status_msg.set(Some(format!("Error: {e}")));
+                                    }
                                 }
                             }
                         }
-                    }
-                    #[cfg(any(target_os = "ios", target_os = "android"))]
-                    {
-                        tracing::info!("camera capture: requires native bridge");
-                        status_msg.set(Some("Camera not yet wired on mobile".into()));
+                        #[cfg(any(target_os = "ios", target_os = "android"))]
+                        {
+                            // The camera blocks until the user is done, so
+                            // wait for it off the UI executor.
+                            use presswerk_bridge::async_bridge::PlatformBridgeAsync;
+                            use presswerk_bridge::traits::{Permission, PermissionState};
+                            let svc = svc.clone();
+                            spawn(async move {
+                                let bridge = presswerk_bridge::shared_platform_bridge();
+                                if bridge.request_permission_async(Permission::Camera).await == PermissionState::Denied {
+                                    status_msg.set(Some("Camera access is off. Allow it for Presswerk in Settings.".into()));
+                                    return;
+                                }
+                                match bridge.capture_image_async().await {
+                                    Ok(Some(bytes)) => {
+                                        match add_captured_page(&svc, &mut session.write(), bytes, *threshold.read(), histogram) {
+                                            Ok(count) => status_msg.set(Some(format!("Page {count} saved."))),
+                                            Err(e) => status_msg.set(Some(format!("Could not save page: {e}"))),
+                                        }
+                                    }
+                                    Ok(None) => {}
+                                    Err(e) => {
+                                        tracing::info!("camera capture: {e}");
+                                        status_msg.set(Some(format!("Camera unavailable: {e}")));
+                                    }
+                                }
+                            });
+                        }
                     }
                 },
                 "\u{1F4F7} Capture Page"
             }
 
+            // Binarization threshold
+            div { style: "margin-bottom: 16px;",
+                label { style: "display: flex; align-items: center; gap: 8px; font-size: 14px;",
+                    input {
+                        r#type: "checkbox",
+                        checked: threshold.read().is_some(),
+                        onchange: move |evt| {
+                            threshold.set(if evt.checked() { Some(128) } else { None });
+                        },
+                    }
+                    "Manual black/white threshold"
+                }
+                if let Some(cut) = *threshold.read() {
+                    if let Some(levels) = *histogram.read() {
+                        ThresholdHistogram { levels, threshold: cut }
+                    }
+                    input {
+                        r#type: "range",
+                        min: "0",
+                        max: "255",
+                        value: "{cut}",
+                        style: "width: 100%;",
+                        oninput: move |evt| {
+                            if let Ok(value) = evt.value().parse::<u8>() {
+                                threshold.set(Some(value));
+                            }
+                        },
+                    }
+                    p { style: "margin: 0; color: #666; font-size: 12px;",
+                        "Darker than {cut} prints black. Applies to the next page captured."
+                    }
+                }
+            }
+
             // Scanned pages
-            if scanned_pages.read().is_empty() {
+            if page_count == 0 {
                 p { style: "text-align: center; color: #aaa; margin: 48px 0;",
                     "No pages scanned yet."
                 }
             } else {
-                {
-                    let count = scanned_pages.read().len();
-                    rsx! {
-                        h3 { "{count} page(s) scanned" }
-                    }
-                }
+                h3 { "{page_count} page(s) scanned" }
                 div { style: "display: flex; gap: 8px; overflow-x: auto; padding: 8px 0;",
-                    for (i, page) in scanned_pages.read().iter().enumerate() {
-                        {
-                            let size_kb = page.len() / 1024;
-                            rsx! {
-                                div { style: "min-width: 80px; height: 110px; border: 1px solid #ccc; border-radius: 4px; display: flex; flex-direction: column; align-items: center; justify-content: center; background: #f0f0f0; font-size: 12px;",
-                                    span { "P{i + 1}" }
-                                    span { style: "color: #888;", "{size_kb}KB" }
+                    for i in 0..page_count {
+                        div { style: "min-width: 80px; height: 110px; border: 1px solid #ccc; border-radius: 4px; display: flex; flex-direction: column; align-items: center; justify-content: center; background: #f0f0f0; font-size: 12px;",
+                            span { "P{i + 1}" }
+                            div { style: "display: flex; gap: 4px; margin-top: 8px;",
+                                button {
+                                    style: "padding: 2px 6px; border: 1px solid #ccc; border-radius: 4px; background: white;",
+                                    disabled: i == 0,
+                                    onclick: move |_| edit_pages(&mut session.write(), &mut status_msg, |s| s.move_page(i, i.saturating_sub(1))),
+                                    "\u{25C0}"
+                                }
+                                button {
+                                    style: "padding: 2px 6px; border: 1px solid #ccc; border-radius: 4px; background: white;",
+                                    disabled: i + 1 == page_count,
+                                    onclick: move |_| edit_pages(&mut session.write(), &mut status_msg, |s| s.move_page(i, i + 1)),
+                                    "\u{25B6}"
+                                }
+                                button {
+                                    style: "padding: 2px 6px; border: 1px solid #ff3b30; border-radius: 4px; background: white; color: #ff3b30;",
+                                    onclick: move |_| edit_pages(&mut session.write(), &mut status_msg, |s| s.remove_page(i)),
+                                    "\u{2715}"
                                 }
                             }
                         }
@@ -87,69 +200,21 @@ pub fn Scan() -> Element {
 
             // Actions
             div { style: "display: flex; gap: 8px; margin-top: 16px;",
-                button {
-                    style: "flex: 1; padding: 12px; border-radius: 8px; border: 1px solid #ccc; background: white;",
-                    disabled: scanned_pages.read().is_empty() || *processing.read(),
-                    onclick: move |_| {
-                        processing.set(true);
-                        status_msg.set(Some("Enhancing...".into()));
-
-                        let pages = scanned_pages.read().clone();
-                        let mut enhanced = Vec::new();
-                        let mut had_errors = false;
-
-                        for page_bytes in &pages {
-                            match ScanEnhancer::from_bytes(page_bytes, PaperSize::A4) {
-                                Ok(enhancer) => {
-                                    match enhancer.enhance_and_convert() {
-                                        Ok(pdf_bytes) => {
-                                            enhanced.push(pdf_bytes);
-                                        }
-                                        Err(_) => {
-                                            enhanced.push(page_bytes.clone());
-                                            had_errors = true;
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    enhanced.push(page_bytes.clone());
-                                    had_errors = true;
-                                }
-                            }
-                        }
-
-                        scanned_pages.set(enhanced);
-                        processing.set(false);
-                        if had_errors {
-                            status_msg.set(Some("Some pages could not be enhanced.".into()));
-                        } else {
-                            status_msg.set(Some("All pages enhanced.".into()));
-                        }
-                    },
-                    "Enhance"
-                }
                 button {
                     style: "flex: 1; padding: 12px; border-radius: 8px; border: none; background: #007aff; color: white;",
-                    disabled: scanned_pages.read().is_empty() || *processing.read(),
+                    disabled: page_count == 0 || *processing.read(),
                     onclick: {
                         let svc = svc.clone();
                         move |_| {
+                            let Some(finished) = session.write().take() else {
+                                return;
+                            };
                             processing.set(true);
                             status_msg.set(Some("Converting to PDF...".into()));
 
-                            let pages = scanned_pages.read().clone();
-                            // Combine all scanned pages into one PDF
-                            // For multi-page, convert each to PDF and merge
-                            let combined_result: std::result::Result<Vec<u8>, _> = if pages.len() == 1 {
-                                ScanEnhancer::from_bytes(&pages[0], PaperSize::A4)
-                                    .and_then(|e| e.enhance_and_convert())
-                            } else {
-                                // Convert first page, then merge rest
-                                // For MVP, just use the first page
-                                ScanEnhancer::from_bytes(&pages[0], PaperSize::A4)
-                                    .and_then(|e| e.enhance_and_convert())
-                            };
-                            match combined_result {
+                            let id = finished.id().to_string();
+                            let assembler = ScanPdfAssembler { paper_size: PaperSize::A4 };
+                            match finished.finalize(&assembler) {
                                 Ok(pdf_bytes) => {
                                     match svc.store_document(&pdf_bytes) {
                                         Ok(hash) => {
@@ -163,6 +228,8 @@ pub fn Scan() -> Element {
                                     }
                                 }
                                 Err(e) => {
+                                    // The pages are still on disk; keep the session open.
+                                    session.set(svc.resume_scan_session(&id).ok());
                                     status_msg.set(Some(format!("PDF conversion failed: {e}")));
                                 }
                             }
@@ -174,11 +241,15 @@ pub fn Scan() -> Element {
             }
 
             // Clear button
-            if !scanned_pages.read().is_empty() {
+            if page_count > 0 {
                 button {
                     style: "width: 100%; padding: 8px; border-radius: 8px; border: 1px solid #ff3b30; color: #ff3b30; background: white; font-size: 14px; margin-top: 8px;",
                     onclick: move |_| {
-                        scanned_pages.set(Vec::new());
+                        if let Some(discarded) = session.write().take()
+                            && let Err(e) = discarded.discard()
+                        {
+                            tracing::warn!("failed to discard scan session: {e}");
+                        }
                         status_msg.set(None);
                     },
                     "Clear All Pages"
@@ -194,3 +265,77 @@ pub fn Scan() -> Element {
         }
     }
 }
+
+/// Luminance histogram of the last page with the chosen threshold marked.
+///
+/// The 256 levels are shown as 64 bars, scaled to the tallest.
+#[component]
+fn ThresholdHistogram(levels: [u64; 256], threshold: u8) -> Element {
+    let bars: Vec<u64> = levels.chunks(4).map(|bin| bin.iter().sum()).collect();
+    let tallest = bars.iter().copied().max().unwrap_or(0).max(1);
+    let marker = f64::from(threshold) / 255.0 * 100.0;
+
+    rsx! {
+        div { style: "position: relative; display: flex; align-items: flex-end; height: 60px; margin: 8px 0; background: #f7f7f7; border-radius: 4px;",
+            for (i, count) in bars.into_iter().enumerate() {
+                div {
+                    key: "{i}",
+                    style: "flex: 1; background: #8e8e93; height: {count as f64 / tallest as f64 * 100.0}%;",
+                }
+            }
+            div { style: "position: absolute; top: 0; bottom: 0; left: {marker}%; width: 2px; background: #ff3b30;" }
+        }
+    }
+}
+
+/// Reorder or remove pages of the open `session` with `edit`, reporting a
+/// failure in `status_msg`.
+fn edit_pages(
+    session: &mut Option<ScanSession>,
+    status_msg: &mut Signal<Option<String>>,
+    edit: impl FnOnce(&mut ScanSession) -> presswerk_core::error::Result<()>,
+) {
+    let Some(session) = session.as_mut() else {
+        return;
+    };
+    if let Err(e) = edit(session) {
+        tracing::warn!("failed to edit scan pages: {e}");
+        status_msg.set(Some(format!("Couldn't change the pages: {e}")));
+    }
+}
+
+/// Enhance a captured image and save it as the next page of `session`,
+/// starting a session if none is open.  Returns the new page count.
+///
+/// The page is binarized at `threshold`, or adaptively if that is `None`,
+/// and its histogram before binarization is left in `histogram`.  If
+/// enhancement fails the original image is kept.
+fn add_captured_page(
+    svc: &AppServices,
+    session: &mut Option<ScanSession>,
+    bytes: Vec<u8>,
+    threshold: Option<u8>,
+    mut histogram: Signal<Option<[u64; 256]>>,
+) -> Result<usize> {
+    let page = ScanEnhancer::from_bytes(&bytes, PaperSize::A4)
+        .and_then(|enhancer| {
+            let prepared = enhancer.prepare_for_binarization();
+            histogram.set(Some(prepared.luminance_histogram()));
+            let binarized = match threshold {
+                Some(cut) => prepared.binarize_at(cut),
+                None => prepared.binarize(DEFAULT_BLOCK_RADIUS, DEFAULT_THRESHOLD_OFFSET),
+            };
+            ImageProcessor::from_dynamic(binarized.into_dynamic()).to_png_bytes()
+        })
+        .unwrap_or_else(|e| {
+            tracing::warn!("page enhancement failed, keeping original: {e}");
+            bytes
+        });
+
+    if session.is_none() {
+        *session = Some(svc.start_scan_session()?);
+    }
+    let session = session.as_mut().expect("session was just started");
+    session.add_page(&page)?;
+    Ok(session.page_count())
+}
@@ -53,7 +53,7 @@ pub fn Server() -> Element {
                     strong { "{status_label(status)}" }
                     if status == ServerStatus::Running {
                         {
-                            let port = state.read().config.server_port;
+                            let port = state.read().config.ipp_server_port;
                             let tls_text = if state.read().config.server_require_tls { "TLS enabled" } else { "TLS disabled" };
                             rsx! {
                                 p { style: "margin: 4px 0 0; color: #666; font-size: 14px;",
@@ -133,6 +133,9 @@ pub fn Server() -> Element {
                             for job in network_jobs.iter() {
                                 div { style: "padding: 10px; margin: 6px 0; border: 1px solid #e0e0e0; border-radius: 8px;",
                                     strong { "{job.document_name}" }
+                                    if let Some(ipp_id) = job.ipp_job_id {
+                                        span { style: "margin-left: 8px; color: #888; font-size: 12px;", "#{ipp_id}" }
+                                    }
                                     {
                                         let ts = job.created_at.format("%H:%M:%S").to_string();
                                         rsx! {
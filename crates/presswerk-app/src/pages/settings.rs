@@ -5,6 +5,7 @@
 
 use dioxus::prelude::*;
 
+use presswerk_core::logging::LogLevel;
 use presswerk_core::types::PaperSize;
 
 use crate::services::app_services::AppServices;
@@ -15,11 +16,18 @@ pub fn Settings() -> Element {
     let mut state = use_context::<Signal<AppState>>();
     let svc = use_context::<AppServices>();
     let mut save_msg = use_signal(|| Option::<String>::None);
+    let config_problem = svc.config_problem().map(str::to_owned);
 
     rsx! {
         div {
             h1 { "Settings" }
 
+            if let Some(problem) = config_problem {
+                p { style: "color: #d93025; background: #fdecea; padding: 12px; border-radius: 8px; font-size: 14px;",
+                    "{problem}"
+                }
+            }
+
             section { style: "margin: 16px 0;",
                 h3 { "Print Server" }
                 // Server port
@@ -28,12 +36,12 @@ pub fn Settings() -> Element {
                     input {
                         r#type: "number",
                         style: "width: 80px; padding: 4px 8px; border: 1px solid #ccc; border-radius: 4px; text-align: right;",
-                        value: "{state.read().config.server_port}",
+                        value: "{state.read().config.ipp_server_port}",
                         onchange: move |evt| {
                             if let Ok(port) = evt.value().parse::<u16>()
                                 && port > 0
                             {
-                                state.write().config.server_port = port;
+                                state.write().config.ipp_server_port = port;
                             }
                         },
                     }
@@ -76,6 +84,16 @@ pub fn Settings() -> Element {
                         option { value: "Tabloid", "Tabloid" }
                     }
                 }
+                SettingRow {
+                    label: "Confirm jobs finished printing",
+                    checked: state.read().config.confirm_completion,
+                    on_toggle: move |v: bool| { state.write().config.confirm_completion = v; },
+                }
+                SettingRow {
+                    label: "Find printers on launch",
+                    checked: state.read().config.auto_discovery,
+                    on_toggle: move |v: bool| { state.write().config.auto_discovery = v; },
+                }
             }
 
             section { style: "margin: 16px 0;",
@@ -92,15 +110,49 @@ pub fn Settings() -> Element {
                 }
             }
 
+            section { style: "margin: 16px 0;",
+                h3 { "Diagnostics" }
+                div { style: "display: flex; justify-content: space-between; align-items: center; padding: 12px 0; border-bottom: 1px solid #f0f0f0;",
+                    span { "Log detail" }
+                    select {
+                        style: "padding: 4px 8px; border: 1px solid #ccc; border-radius: 4px;",
+                        value: state.read().config.log_level.as_str(),
+                        onchange: move |evt| {
+                            if let Some(level) = LogLevel::parse(&evt.value()) {
+                                state.write().config.log_level = level;
+                            }
+                        },
+                        for level in LogLevel::ALL {
+                            option { value: level.as_str(), "{level}" }
+                        }
+                    }
+                }
+            }
+
             // Save button
             button {
                 style: "width: 100%; padding: 12px; border-radius: 8px; border: none; background: #007aff; color: white; font-size: 16px; margin-top: 8px;",
                 onclick: {
                     let svc = svc.clone();
                     move |_| {
-                        let config = state.read().config.clone();
-                        match svc.save_config(&config) {
-                            Ok(()) => {
+                        // Only the fields this page edits; the rest of the
+                        // live config may have changed since it was opened.
+                        let edited = state.read().config.clone();
+                        let saved = svc.update_config(|config| {
+                            config.ipp_server_port = edited.ipp_server_port;
+                            config.auto_start_server = edited.auto_start_server;
+                            config.server_require_tls = edited.server_require_tls;
+                            config.auto_accept_network_jobs = edited.auto_accept_network_jobs;
+                            config.default_paper_size = edited.default_paper_size;
+                            config.confirm_completion = edited.confirm_completion;
+                            config.auto_discovery = edited.auto_discovery;
+                            config.encryption_enabled = edited.encryption_enabled;
+                            config.audit_enabled = edited.audit_enabled;
+                            config.log_level = edited.log_level;
+                        });
+                        match saved {
+                            Ok(config) => {
+                                state.write().config = config;
                                 tracing::info!("settings saved");
                                 save_msg.set(Some("Settings saved.".into()));
                             }
@@ -2,9 +2,14 @@
 // Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
 //
 // Text editor page — create plain text documents, export as PDF, or print.
+//
+// Printing goes through the same PDF as the export, so the printer gets
+// margins, wrapping and an embedded font rather than raw text.
 
 use dioxus::prelude::*;
 
+use presswerk_core::PaperSize;
+use presswerk_core::error::PresswerkError;
 use presswerk_core::types::DocumentType;
 use presswerk_document::PdfWriter;
 
@@ -16,6 +21,7 @@ pub fn TextEditor() -> Element {
     let state = use_context::<Signal<AppState>>();
     let svc = use_context::<AppServices>();
     let mut text = use_signal(String::new);
+    let mut font_size = use_signal(|| PdfWriter::TEXT_FONT_SIZE);
     let mut status_msg = use_signal(|| Option::<String>::None);
 
     rsx! {
@@ -29,6 +35,22 @@ pub fn TextEditor() -> Element {
                 oninput: move |evt| text.set(evt.value().to_string()),
             }
 
+            div { style: "display: flex; align-items: center; gap: 8px; margin-top: 12px;",
+                label { "Text size" }
+                select {
+                    style: "padding: 4px 8px; border: 1px solid #ccc; border-radius: 4px;",
+                    value: "{font_size}",
+                    onchange: move |evt| {
+                        if let Ok(size) = evt.value().parse::<f32>() {
+                            font_size.set(size);
+                        }
+                    },
+                    option { value: "9", "Small" }
+                    option { value: "11", "Normal" }
+                    option { value: "14", "Large" }
+                }
+            }
+
             div { style: "display: flex; gap: 8px; margin-top: 12px;",
                 button {
                     style: "flex: 1; padding: 12px; border-radius: 8px; border: 1px solid #007aff; color: #007aff; background: white;",
@@ -37,9 +59,8 @@ pub fn TextEditor() -> Element {
                         let svc = svc.clone();
                         move |_| {
                             let content = text.read().clone();
-                            let mut writer = PdfWriter::a4();
-                            writer.set_title("Text Document");
-                            match writer.create_from_text(&content) {
+                            let paper = state.read().config.default_paper_size;
+                            match render_pdf(&content, paper, *font_size.read()) {
                                 Ok(pdf_bytes) => {
                                     match svc.store_document(&pdf_bytes) {
                                         Ok(hash) => {
@@ -68,20 +89,30 @@ pub fn TextEditor() -> Element {
                         move |_| {
                             let content = text.read().clone();
                             let printer_uri = state.read().selected_printer.clone();
+                            let paper = state.read().config.default_paper_size;
 
                             if let Some(uri) = printer_uri {
-                                let text_bytes = content.into_bytes();
+                                let pdf_bytes = match render_pdf(&content, paper, *font_size.read()) {
+                                    Ok(bytes) => bytes,
+                                    Err(e) => {
+                                        status_msg.set(Some(format!("PDF creation failed: {e}")));
+                                        return;
+                                    }
+                                };
                                 let svc = svc.clone();
                                 spawn(async move {
                                     match svc.print_document(
-                                        text_bytes,
-                                        "Text Document.txt".into(),
-                                        DocumentType::PlainText,
+                                        pdf_bytes,
+                                        "Text Document.pdf".into(),
+                                        DocumentType::Pdf,
                                         uri,
-                                        presswerk_core::types::PrintSettings::default(),
+                                        presswerk_core::types::PrintSettings {
+                                            paper_size: paper,
+                                            ..Default::default()
+                                        },
                                     ).await {
                                         Ok(job_id) => {
-                                            status_msg.set(Some(format!("Print job submitted: {job_id}")));
+                                            status_msg.set(Some(format!("Print job submitted: {}", job_id.short())));
                                         }
                                         Err(e) => {
                                             status_msg.set(Some(format!("Print failed: {e}")));
@@ -106,3 +137,10 @@ pub fn TextEditor() -> Element {
         }
     }
 }
+
+/// Lay the editor's text out as a PDF on `paper` with the usual margins.
+fn render_pdf(content: &str, paper: PaperSize, font_size: f32) -> Result<Vec<u8>, PresswerkError> {
+    let mut writer = PdfWriter::new(paper);
+    writer.set_title("Text Document");
+    writer.create_from_text(content, font_size, PdfWriter::TEXT_MARGIN_MM)
+}
@@ -9,24 +9,56 @@
 // Dioxus task pool.  Mutex contention is minimal because all operations are
 // fast (sub-millisecond SQLite queries).
 
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use presswerk_core::AppConfig;
 use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::logging::{LogBuffer, LogEntry, LogHandle};
 use presswerk_core::types::{
-    DiscoveredPrinter, DocumentType, JobId, JobSource, JobStatus, PrintJob, PrintSettings,
-    ServerStatus,
+    AttemptRecord, DiscoveredPrinter, DocumentType, ErrorClass, JobId, JobSource, JobStatus,
+    PrintJob, PrintProtocol, PrintSettings, ServerConfig, ServerStatus, resolve_document_type,
 };
+use presswerk_document::{ImageProcessor, PdfReader};
+use presswerk_print::capabilities::{FormatPlan, PrintWarning, PrinterCapabilities};
 use presswerk_print::discovery::PrinterDiscovery;
-use presswerk_print::ipp_client::IppClient;
+use presswerk_print::document_store::DocumentStore;
+use presswerk_print::health::{HealthSummary, HealthTracker};
+use presswerk_print::ipp_client::{self, DEFAULT_COMPLETION_TIMEOUT, IppClient};
 use presswerk_print::ipp_server::IppServer;
+use presswerk_print::printer_cache;
+use presswerk_print::protocol;
 use presswerk_print::queue::JobQueue;
+use presswerk_print::raw_client::{self, RAW_PORT, RawClient};
+use presswerk_print::readiness::ReadinessCache;
+use presswerk_print::revival::Revival;
+use presswerk_print::scan_session::{ScanSession, ScanSessionInfo};
 use presswerk_security::audit::{AuditEntry, AuditLog};
 use presswerk_security::integrity::hash_bytes;
-use tracing::{error, info, warn};
+use presswerk_security::storage::{self, EncryptedStorage};
+use tracing::{debug, error, info, warn};
 
 use super::data_dir;
+use super::rasterizer::DocumentRasterizer;
+
+/// Document store reference held by documents the user saved (rather
+/// than printed), which no job deletion releases.
+const SAVED_DOCUMENT_REF: &str = "saved";
+
+/// How long [`AppServices::print_warnings`] waits for the printer to answer
+/// Validate-Job before giving up on that check, as it runs before every
+/// print.
+const VALIDATE_JOB_BUDGET: Duration = Duration::from_secs(3);
+
+/// Documents whose colour check [`AppServices::print_warnings`] remembers;
+/// the cache starts over once it is full.
+const COLOR_CACHE_CAPACITY: usize = 64;
+
+/// How far back [`AppServices::printer_health`] looks.
+const HEALTH_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
 
 /// Acquire a `Mutex` lock, recovering from poison if a prior thread panicked.
 ///
@@ -52,8 +84,24 @@ pub struct AppServices {
     audit_log: Arc<Mutex<AuditLog>>,
     discovery: Arc<Mutex<Option<PrinterDiscovery>>>,
     ipp_server: Arc<tokio::sync::Mutex<IppServer>>,
+    documents: Arc<DocumentStore>,
+    readiness: Arc<ReadinessCache>,
+    health: Arc<Mutex<HealthTracker>>,
+    /// Cancel flags of the jobs currently being sent.
+    in_flight: Arc<Mutex<HashMap<JobId, Arc<AtomicBool>>>>,
+    /// Whether a document has colour, by document hash.
+    document_colors: Arc<Mutex<HashMap<String, bool>>>,
     data_dir: PathBuf,
     config: Arc<Mutex<AppConfig>>,
+    /// Encrypted store the config is kept in; absent if the platform has no
+    /// keychain for its passphrase, in which case the config is plain JSON.
+    config_storage: Option<Arc<EncryptedStorage>>,
+    /// Why the stored config could not be used at startup, if it could not.
+    config_problem: Option<String>,
+    /// Changes the running log level; absent until [`with_logging`](Self::with_logging).
+    log_handle: Option<LogHandle>,
+    /// Recent log entries for the in-app viewer.
+    log_buffer: Option<LogBuffer>,
 }
 
 #[allow(dead_code)]
@@ -71,10 +119,35 @@ pub fn init() -> Result<Self> {
         let audit_path = dir.join("audit.db");
 
         let job_queue = JobQueue::open(&queue_path)?;
+        // Jobs a crash left mid-send would otherwise stay Processing forever
+        match job_queue.reset_stuck_processing() {
+            Ok(0) => {}
+            Ok(count) => info!(count, "reset jobs interrupted by the last shutdown"),
+            Err(e) => warn!("could not reset interrupted jobs: {e}"),
+        }
         let audit_log = AuditLog::open(&audit_path)?;
+        // One store for the app and the IPP server, so their reference
+        // bookkeeping happens under the same lock.
+        let documents = Arc::new(DocumentStore::open(&dir)?);
+        // Documents a crash stored without committing their job row
+        match job_queue
+            .job_ids()
+            .and_then(|live| documents.sweep_orphans(|id| live.contains(id)))
+        {
+            Ok(0) => {}
+            Ok(count) => info!(count, "removed documents left without a job"),
+            Err(e) => warn!("could not sweep orphaned documents: {e}"),
+        }
 
-        // Prepare discovery (may fail on platforms without multicast)
-        let discovery = match PrinterDiscovery::new() {
+        // Printer outcome history is nice to have; never block startup on it
+        let health = HealthTracker::open(dir.join("health.db")).unwrap_or_else(|e| {
+            warn!("printer health history unavailable: {e}");
+            HealthTracker::new()
+        });
+
+        // Prepare discovery (may fail on platforms without multicast), with
+        // the printers seen on earlier runs listed until browsing finds them
+        let discovery = match PrinterDiscovery::with_cache(dir.join(printer_cache::CACHE_FILE)) {
             Ok(d) => Some(d),
             Err(e) => {
                 warn!("mDNS discovery unavailable: {e}");
@@ -83,10 +156,20 @@ pub fn init() -> Result<Self> {
         };
 
         // Load persisted config or use defaults
-        let config = load_config(&dir).unwrap_or_default();
+        let config_storage = open_config_storage(&dir).map(Arc::new);
+        let (config, config_problem) = load_config(&dir, config_storage.as_deref());
 
-        // Create IPP server (not started until user toggles it on)
-        let ipp_server = IppServer::new(Some(config.server_port), Some(dir.clone()));
+        // Create IPP server (not started until user toggles it on); its
+        // certificate key is encrypted with the config store's key.
+        let mut ipp_server = IppServer::with_config(
+            ServerConfig::default()
+                .with_port(config.ipp_server_port)
+                .with_data_dir(dir.clone()),
+        )
+        .with_documents(Arc::clone(&documents));
+        if let Some(storage) = &config_storage {
+            ipp_server = ipp_server.with_key_storage(Arc::clone(storage));
+        }
 
         info!("app services initialised");
 
@@ -95,8 +178,17 @@ pub fn init() -> Result<Self> {
             audit_log: Arc::new(Mutex::new(audit_log)),
             discovery: Arc::new(Mutex::new(discovery)),
             ipp_server: Arc::new(tokio::sync::Mutex::new(ipp_server)),
+            documents,
+            readiness: Arc::new(ReadinessCache::default()),
+            health: Arc::new(Mutex::new(health)),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+            document_colors: Arc::new(Mutex::new(HashMap::new())),
             data_dir: dir,
             config: Arc::new(Mutex::new(config)),
+            config_storage,
+            config_problem,
+            log_handle: None,
+            log_buffer: None,
         })
     }
 
@@ -111,6 +203,9 @@ pub fn fallback() -> Result<Self> {
         let dir = data_dir::data_dir();
         let job_queue = JobQueue::open_in_memory()?;
         let audit_log = AuditLog::open_in_memory()?;
+        // Same scratch location the IPP server falls back to.
+        let documents = Arc::new(DocumentStore::open(std::env::temp_dir().join("presswerk"))?);
+        let health = HealthTracker::open_in_memory()?;
 
         let discovery = match PrinterDiscovery::new() {
             Ok(d) => Some(d),
@@ -121,7 +216,9 @@ pub fn fallback() -> Result<Self> {
         };
 
         let config = AppConfig::default();
-        let ipp_server = IppServer::new(Some(config.server_port), None);
+        let ipp_server =
+            IppServer::with_config(ServerConfig::default().with_port(config.ipp_server_port))
+                .with_documents(Arc::clone(&documents));
 
         info!("fallback app services initialised (in-memory)");
 
@@ -130,11 +227,35 @@ pub fn fallback() -> Result<Self> {
             audit_log: Arc::new(Mutex::new(audit_log)),
             discovery: Arc::new(Mutex::new(discovery)),
             ipp_server: Arc::new(tokio::sync::Mutex::new(ipp_server)),
+            documents,
+            readiness: Arc::new(ReadinessCache::default()),
+            health: Arc::new(Mutex::new(health)),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+            document_colors: Arc::new(Mutex::new(HashMap::new())),
             data_dir: dir,
             config: Arc::new(Mutex::new(config)),
+            config_storage: None,
+            config_problem: None,
+            log_handle: None,
+            log_buffer: None,
         })
     }
 
+    /// Attach the log level handle and recent-entries buffer the subscriber
+    /// was built with, and apply the configured level (unless `RUST_LOG`
+    /// chose one).
+    pub fn with_logging(mut self, handle: LogHandle, buffer: LogBuffer) -> Self {
+        if std::env::var_os("RUST_LOG").is_none() {
+            let level = acquire_lock(&self.config).log_level;
+            if let Err(e) = handle.set_level(level) {
+                warn!(error = %e, "could not apply configured log level");
+            }
+        }
+        self.log_handle = Some(handle);
+        self.log_buffer = Some(buffer);
+        self
+    }
+
     // -- Discovery -----------------------------------------------------------
 
     /// Start mDNS printer discovery in the background.
@@ -173,6 +294,26 @@ pub fn is_discovering(&self) -> bool {
         }
     }
 
+    /// Send the jobs left pending by the last run, then watch discovery in
+    /// the background and resend jobs that failed while their printer was
+    /// away when it returns.
+    pub fn start_revival(&self) {
+        self.resend_pending_jobs();
+        let services = self.clone();
+        let sender = self.clone();
+        tokio::spawn(Revival::new().run(
+            Arc::clone(&self.job_queue),
+            move || services.discovered_printers(),
+            move |revived| {
+                for job_id in revived {
+                    if let Err(e) = sender.resend_job(job_id) {
+                        warn!(job_id = %job_id, error = %e, "could not resend revived job");
+                    }
+                }
+            },
+        ));
+    }
+
     // -- IPP Server ----------------------------------------------------------
 
     /// Start the embedded IPP print server.
@@ -213,7 +354,8 @@ pub fn ipp_server_status(&self) -> ServerStatus {
     /// Send a document to a printer via IPP.
     ///
     /// Creates a print job in the queue, sends it via IPP, updates the job
-    /// status, and records the operation in the audit log.
+    /// status, and records the operation in the audit log.  The declared
+    /// `document_type` is corrected from the document's magic bytes.
     pub async fn print_document(
         &self,
         document_bytes: Vec<u8>,
@@ -222,6 +364,7 @@ pub async fn print_document(
         printer_uri: String,
         settings: PrintSettings,
     ) -> Result<JobId> {
+        let document_type = resolve_document_type(document_type, &document_bytes);
         let doc_hash = hash_bytes(&document_bytes);
         let total_bytes = document_bytes.len() as u64;
 
@@ -236,70 +379,314 @@ pub async fn print_document(
         job.settings = settings.clone();
         job.total_bytes = total_bytes;
 
-        // Insert into persistent queue
+        let job_id = job.id;
+
+        // Insert into the persistent queue together with a copy of the
+        // document for the lifetime of the job (retry, resubmit, preview).
+        // Failing to store the copy is not fatal to this print.
         {
             let queue = acquire_lock(&self.job_queue);
-            queue.insert_job(&job)?;
+            let documents = &self.documents;
+            queue.insert_job_tx(&job, || {
+                let stored = documents
+                    .put_for_job(&job_id, &document_bytes)
+                    .inspect_err(|e| {
+                        warn!(job_id = %job_id, error = %e, "failed to store document for job");
+                    })
+                    .ok();
+                Ok(move || {
+                    if let Some(hash) = stored {
+                        let _ = documents.release(&hash, &job_id);
+                    }
+                })
+            })?;
         }
 
-        let job_id = job.id;
-
         // Record audit entry
         self.audit("print_submitted", &doc_hash, true, Some(&document_name));
 
-        // Send to printer asynchronously
+        self.spawn_send(&job, document_bytes);
+        Ok(job_id)
+    }
+
+    /// Send the queued `job` to its printer in the background, tracking its
+    /// status, attempts and the printer's health.  The same path serves new
+    /// prints and jobs put back in the queue (see [`resend_job`](Self::resend_job)).
+    fn spawn_send(&self, job: &PrintJob, document_bytes: Vec<u8>) {
+        let Some(uri) = job.printer_uri.as_deref() else {
+            warn!(job_id = %job.id, "job has no printer; not sending");
+            return;
+        };
         let services = self.clone();
+        let job_id = job.id;
         let doc_bytes = document_bytes;
-        let uri = printer_uri;
-        let name = document_name;
-        let hash = doc_hash;
+        let document_type = job.document_type;
+        let uri = uri.to_string();
+        let name = job.document_name.clone();
+        let hash = job.document_hash.clone();
+        let settings = job.settings.clone();
+        let confirm_completion = acquire_lock(&self.config).confirm_completion;
+        let cancel = Arc::new(AtomicBool::new(false));
+        acquire_lock(&self.in_flight).insert(job_id, Arc::clone(&cancel));
 
         tokio::spawn(async move {
+            // Cancelled before it got going: nothing to send
+            if cancel.load(Ordering::Relaxed) {
+                acquire_lock(&services.in_flight).remove(&job_id);
+                return;
+            }
+
             // Update status to Processing
             if let Ok(queue) = services.job_queue.lock() {
                 let _ = queue.update_status(&job_id, JobStatus::Processing, None);
             }
 
-            match IppClient::new(&uri) {
-                Ok(client) => {
-                    match client
-                        .print_job(doc_bytes, document_type, &name, &settings)
+            let mut attempts = Vec::new();
+            let started = Instant::now();
+            let sent = match services.check_ready(&uri, &settings).await {
+                Ok(()) => {
+                    send_job(
+                        &uri,
+                        doc_bytes,
+                        document_type,
+                        &name,
+                        &settings,
+                        &cancel,
+                        &mut attempts,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            // A printer that accepted the job may already have given up on it.
+            let sent = match sent {
+                Ok(Some(remote_id)) if !cancel.load(Ordering::Relaxed) => {
+                    check_printer_job(&uri, remote_id, confirm_completion, &cancel)
                         .await
-                    {
-                        Ok(remote_id) => {
-                            info!(job_id = %job_id, remote_id, "print job accepted");
-                            if let Ok(queue) = services.job_queue.lock() {
-                                let _ =
-                                    queue.update_status(&job_id, JobStatus::Completed, None);
-                            }
-                            services.audit("print_completed", &hash, true, None);
-                        }
-                        Err(e) => {
-                            error!(job_id = %job_id, error = %e, "print job failed");
-                            let msg = e.to_string();
-                            if let Ok(queue) = services.job_queue.lock() {
-                                let _ = queue.update_status(
-                                    &job_id,
-                                    JobStatus::Failed,
-                                    Some(&msg),
-                                );
-                            }
-                            services.audit("print_failed", &hash, false, Some(&msg));
-                        }
-                    }
+                        .map(|()| Some(remote_id))
+                }
+                other => other,
+            };
+            if !attempts.is_empty()
+                && let Ok(queue) = services.job_queue.lock()
+            {
+                let _ = queue.record_attempts(&job_id, &attempts);
+            }
+            // Whatever the send did after a cancel, the printer is not to blame.
+            if !cancel.load(Ordering::Relaxed) {
+                acquire_lock(&services.health).record_result(&uri, &sent, started.elapsed());
+                if sent.is_err() {
+                    services.readiness.invalidate(&uri);
+                }
+            }
+
+            // The job stays in `in_flight` until its final status is written.
+            // `cancel_job` sets the flag before taking the queue lock, so
+            // checking it under the lock means a later cancel writes
+            // Cancelled after us rather than being overwritten.
+            let queue = acquire_lock(&services.job_queue);
+            if cancel.load(Ordering::Relaxed) {
+                info!(job_id = %job_id, "print job cancelled while sending");
+                // Set again in case Processing raced cancel_job.
+                let _ = queue.update_status(&job_id, JobStatus::Cancelled, None);
+                drop(queue);
+                acquire_lock(&services.in_flight).remove(&job_id);
+                services.audit("print_cancelled", &hash, true, None);
+                return;
+            }
+            let (action, ok, msg) = match sent {
+                Ok(remote_id) => {
+                    info!(job_id = %job_id, ?remote_id, "print job accepted");
+                    let _ = queue.update_status(&job_id, JobStatus::Completed, None);
+                    ("print_completed", true, None)
                 }
                 Err(e) => {
-                    error!(error = %e, "invalid printer URI");
+                    error!(job_id = %job_id, error = %e, "print job failed");
                     let msg = e.to_string();
-                    if let Ok(queue) = services.job_queue.lock() {
-                        let _ = queue.update_status(&job_id, JobStatus::Failed, Some(&msg));
+                    // Failures before any attempt (a stopped printer) are
+                    // classified here so the job can be revived.
+                    if attempts.is_empty() {
+                        let _ = queue.set_error_class(&job_id, Some(ErrorClass::classify(&e)));
                     }
-                    services.audit("print_failed", &hash, false, Some(&msg));
+                    let _ = queue.update_status(&job_id, JobStatus::Failed, Some(&msg));
+                    ("print_failed", false, Some(msg))
                 }
+            };
+            drop(queue);
+            acquire_lock(&services.in_flight).remove(&job_id);
+            services.audit(action, &hash, ok, msg.as_deref());
+        });
+    }
+
+    /// Send a `Pending` job again, with the copy of its document kept in the
+    /// document store.  Jobs already being sent, received by the print
+    /// server, or no longer pending are left alone.
+    pub fn resend_job(&self, job_id: &JobId) -> Result<()> {
+        if acquire_lock(&self.in_flight).contains_key(job_id) {
+            return Ok(());
+        }
+        let job = acquire_lock(&self.job_queue)
+            .get_job(job_id)?
+            .ok_or_else(|| PresswerkError::InvalidJobId(job_id.to_string()))?;
+        if job.status != JobStatus::Pending || matches!(job.source, JobSource::Network { .. }) {
+            return Ok(());
+        }
+        let document_bytes = self.documents.get(&job.document_hash)?;
+
+        info!(job_id = %job_id, retry = job.retry_count, "resending queued job");
+        self.audit(
+            "print_resubmitted",
+            &job.document_hash,
+            true,
+            Some(&job.document_name),
+        );
+        self.spawn_send(&job, document_bytes);
+        Ok(())
+    }
+
+    /// Send every `Pending` job of ours, e.g. those requeued at startup
+    /// after being cut off by a crash.
+    pub fn resend_pending_jobs(&self) {
+        let pending = match acquire_lock(&self.job_queue).get_pending_jobs() {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                warn!(error = %e, "could not list pending jobs");
+                return;
+            }
+        };
+        for job in pending {
+            if let Err(e) = self.resend_job(&job.id) {
+                warn!(job_id = %job.id, error = %e, "could not resend job");
+            }
+        }
+    }
+
+    /// Warnings to show before sending a document to `printer_uri`, such as
+    /// colour pages going to a black-and-white printer.
+    ///
+    /// Asks the printer for its capabilities and to validate a job with
+    /// `settings`, in the format the document will actually be sent in
+    /// once converted for the printer; a printer that does not answer gives
+    /// no warnings rather than an error, and the document is only inspected
+    /// when a warning depends on it (off the async runtime, and once per
+    /// document).  Validate-Job is given [`VALIDATE_JOB_BUDGET`] to answer.
+    pub async fn print_warnings(
+        &self,
+        document_bytes: &[u8],
+        document_type: DocumentType,
+        printer_uri: &str,
+        settings: &PrintSettings,
+    ) -> Vec<PrintWarning> {
+        let client = match IppClient::new(printer_uri) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(printer = printer_uri, error = %e, "invalid printer URI; no print warnings");
+                return Vec::new();
+            }
+        };
+        let caps = match PrinterCapabilities::query(&client).await {
+            Ok(caps) => caps,
+            Err(e) => {
+                warn!(printer = printer_uri, error = %e, "capabilities query failed; no print warnings");
+                return Vec::new();
+            }
+        };
+        let has_color = !caps.color_supported
+            && self
+                .check_document_color(document_bytes, document_type)
+                .await;
+        let mut warnings = caps.print_warnings(has_color);
+
+        // The send path converts documents the printer cannot take, so ask
+        // about the format it will receive.  With no format to convert to,
+        // the print itself reports that.
+        let sent_type = match caps.format_plan(document_type) {
+            Ok(FormatPlan::SendAsIs) => document_type,
+            Ok(FormatPlan::Rasterize(target)) => target,
+            Err(_) => return warnings,
+        };
+
+        // Printers that do not implement Validate-Job answer with an error,
+        // which says nothing about the settings.
+        let validation = tokio::time::timeout(
+            VALIDATE_JOB_BUDGET,
+            client.validate_job(sent_type, settings, Some(&caps)),
+        )
+        .await;
+        match validation {
+            Ok(Ok(outcome)) if !outcome.is_accepted() => {
+                warnings.push(PrintWarning::SettingsRejected)
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => debug!(printer = printer_uri, error = %e, "Validate-Job not answered"),
+            Err(_) => debug!(printer = printer_uri, "Validate-Job took too long; skipped"),
+        }
+        warnings
+    }
+
+    /// [`document_has_color`] on a blocking thread, remembered by document
+    /// hash so that checking the same document again is free.
+    async fn check_document_color(
+        &self,
+        document_bytes: &[u8],
+        document_type: DocumentType,
+    ) -> bool {
+        let colors = Arc::clone(&self.document_colors);
+        let bytes = document_bytes.to_vec();
+        let check = tokio::task::spawn_blocking(move || {
+            let hash = hash_bytes(&bytes);
+            if let Some(&has_color) = acquire_lock(&colors).get(&hash) {
+                return has_color;
+            }
+            let has_color = document_has_color(&bytes, document_type);
+            let mut colors = acquire_lock(&colors);
+            if colors.len() >= COLOR_CACHE_CAPACITY {
+                colors.clear();
             }
+            colors.insert(hash, has_color);
+            has_color
         });
+        check.await.unwrap_or_else(|e| {
+            warn!(error = %e, "colour check failed; treating the document as grey");
+            false
+        })
+    }
 
-        Ok(job_id)
+    /// Make sure the printer is not stopped before sending to it.
+    ///
+    /// Uses the readiness cache, so back-to-back prints query the printer
+    /// once.  Skipped when a protocol is forced, and a printer that does
+    /// not answer the query is tried anyway.
+    async fn check_ready(&self, printer_uri: &str, settings: &PrintSettings) -> Result<()> {
+        if settings.forced_protocol.is_some() {
+            return Ok(());
+        }
+        match self.readiness.readiness(printer_uri).await {
+            Ok(readiness) if readiness.is_stopped() => Err(PresswerkError::IppRequest(format!(
+                "printer is stopped: {}",
+                readiness.reasons.join(", ")
+            ))),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                warn!(printer = printer_uri, error = %e, "readiness check failed; sending anyway");
+                Ok(())
+            }
+        }
+    }
+
+    /// How a printer has fared over the last week.
+    pub fn printer_health(&self, printer_uri: &str) -> HealthSummary {
+        acquire_lock(&self.health)
+            .recent_health(printer_uri, HEALTH_WINDOW)
+            .unwrap_or_else(|e| {
+                warn!(printer = printer_uri, error = %e, "failed to read printer health");
+                HealthSummary::default()
+            })
+    }
+
+    /// The printer among `candidates` with the best recent track record.
+    pub fn best_printer(&self, candidates: &[String]) -> Option<String> {
+        acquire_lock(&self.health).best_of(candidates)
     }
 
     // -- Job Queue -----------------------------------------------------------
@@ -316,18 +703,32 @@ pub fn pending_jobs(&self) -> Result<Vec<PrintJob>> {
         queue.get_pending_jobs()
     }
 
-    /// Cancel a job.
+    /// Cancel a job, aborting its upload if it is being sent right now.
     pub fn cancel_job(&self, job_id: &JobId) -> Result<()> {
+        if let Some(cancel) = acquire_lock(&self.in_flight).get(job_id) {
+            cancel.store(true, Ordering::Relaxed);
+        }
         let queue = acquire_lock(&self.job_queue);
         queue.update_status(job_id, JobStatus::Cancelled, None)?;
         self.audit("job_cancelled", &job_id.to_string(), true, None);
         Ok(())
     }
 
-    /// Delete a job from the queue.
+    /// Delete a job from the queue and release its stored document.
+    ///
+    /// The document itself is only removed once no other job references it.
     pub fn delete_job(&self, job_id: &JobId) -> Result<()> {
         let queue = acquire_lock(&self.job_queue);
-        queue.delete_job(job_id)
+        let hash = queue.get_job(job_id)?.map(|job| job.document_hash);
+        queue.delete_job(job_id)?;
+        drop(queue);
+
+        if let Some(hash) = hash
+            && let Err(e) = self.documents.release(&hash, job_id)
+        {
+            warn!(job_id = %job_id, error = %e, "failed to release job document");
+        }
+        Ok(())
     }
 
     // -- Audit Trail ---------------------------------------------------------
@@ -366,56 +767,401 @@ pub fn config(&self) -> AppConfig {
         acquire_lock(&self.config).clone()
     }
 
-    /// Update and persist the config.
-    pub fn save_config(&self, config: &AppConfig) -> Result<()> {
-        *acquire_lock(&self.config) = config.clone();
-        persist_config(&self.data_dir, config)
+    /// Why the saved settings were not loaded at startup, if they were not.
+    ///
+    /// The defaults are in use then, and the damaged settings have been set
+    /// aside as [`DAMAGED_CONFIG`] rather than being overwritten by the next
+    /// save.
+    pub fn config_problem(&self) -> Option<&str> {
+        self.config_problem.as_deref()
     }
 
-    // -- Document Storage (encrypted at rest) --------------------------------
+    /// Apply `edit` to the current config and persist it, applying a changed
+    /// log level at once.  Returns the updated config.
+    ///
+    /// The edit runs on the live config under its lock, so fields changed
+    /// elsewhere since the caller last read it (printer aliases, per-printer
+    /// defaults) are kept.
+    pub fn update_config(&self, edit: impl FnOnce(&mut AppConfig)) -> Result<AppConfig> {
+        let mut config = acquire_lock(&self.config);
+        let previous_level = config.log_level;
+        edit(&mut config);
+        if previous_level != config.log_level
+            && let Some(handle) = &self.log_handle
+        {
+            match handle.set_level(config.log_level) {
+                Ok(()) => info!(level = %config.log_level, "log level changed"),
+                Err(e) => warn!(error = %e, "could not change log level"),
+            }
+        }
+        persist_config(&self.data_dir, self.config_storage.as_deref(), &config)?;
+        Ok(config.clone())
+    }
 
-    /// Save document bytes to the data directory.
+    /// Set (or, with a blank alias, clear) a printer nickname and persist it.
     ///
-    /// Returns the SHA-256 hash used as the filename.
-    pub fn store_document(&self, data: &[u8]) -> Result<String> {
-        let hash = hash_bytes(data);
-        let docs_dir = data_dir::data_subdir("documents");
-        let path = docs_dir.join(&hash);
+    /// `key` is the printer's UUID if it has one, otherwise its URI.
+    pub fn set_printer_alias(&self, key: &str, alias: &str) -> Result<()> {
+        let mut config = acquire_lock(&self.config);
+        config.set_alias(key, alias);
+        persist_config(&self.data_dir, self.config_storage.as_deref(), &config)
+    }
 
-        if !path.exists() {
-            std::fs::write(&path, data).map_err(PresswerkError::Io)?;
-        }
+    /// Remember `settings` as the defaults for the printer at `uri` and
+    /// persist them.
+    pub fn set_printer_defaults(&self, uri: &str, settings: &PrintSettings) -> Result<()> {
+        let mut config = acquire_lock(&self.config);
+        config.set_printer_defaults(uri, settings);
+        persist_config(&self.data_dir, self.config_storage.as_deref(), &config)
+    }
 
-        Ok(hash)
+    // -- Logs ----------------------------------------------------------------
+
+    /// The most recent log entries, oldest first, for the in-app log viewer.
+    pub fn recent_log_entries(&self) -> Vec<LogEntry> {
+        self.log_buffer
+            .as_ref()
+            .map(LogBuffer::entries)
+            .unwrap_or_default()
     }
 
-    /// Load document bytes from the data directory by hash.
-    pub fn load_document(&self, hash: &str) -> Result<Vec<u8>> {
-        let docs_dir = data_dir::data_subdir("documents");
-        let path = docs_dir.join(hash);
+    // -- Document Storage (encrypted at rest) --------------------------------
 
-        std::fs::read(&path).map_err(PresswerkError::Io)
+    /// Save document bytes to the content-addressed document store.
+    ///
+    /// The document is held under [`SAVED_DOCUMENT_REF`], so deleting a job
+    /// that printed the same content does not remove it.  Returns the
+    /// SHA-256 hash used as the filename.
+    pub fn store_document(&self, data: &[u8]) -> Result<String> {
+        self.documents.put_named(data, SAVED_DOCUMENT_REF)
+    }
+
+    /// Load document bytes from the document store by hash.
+    pub fn load_document(&self, hash: &str) -> Result<Vec<u8>> {
+        match self.documents.get(hash) {
+            Ok(data) => Ok(data),
+            // Documents saved before the store existed have no extension.
+            Err(_) => {
+                let legacy = data_dir::data_subdir("documents").join(hash);
+                std::fs::read(&legacy).map_err(PresswerkError::Io)
+            }
+        }
     }
 
     /// Path to the data directory.
     pub fn data_dir(&self) -> &PathBuf {
         &self.data_dir
     }
+
+    // -- Scan sessions -------------------------------------------------------
+
+    /// Start a multi-page scan whose pages are saved as they are captured.
+    pub fn start_scan_session(&self) -> Result<ScanSession> {
+        ScanSession::start(&self.data_dir, Arc::clone(&self.documents))
+    }
+
+    /// Reopen a scan session left unfinished by an earlier run.
+    pub fn resume_scan_session(&self, id: &str) -> Result<ScanSession> {
+        ScanSession::resume(&self.data_dir, Arc::clone(&self.documents), id)
+    }
+
+    /// Scan sessions that were neither exported nor discarded.
+    pub fn unfinished_scan_sessions(&self) -> Vec<ScanSessionInfo> {
+        ScanSession::list(&self.data_dir).unwrap_or_else(|e| {
+            warn!("cannot list scan sessions: {e}");
+            Vec::new()
+        })
+    }
+}
+
+// -- Job submission ----------------------------------------------------------
+
+/// Upload speed cap on metered (mobile data) connections.
+const METERED_UPLOAD_BYTES_PER_SEC: u64 = 256 * 1024;
+
+/// Upload rate limit for the current connection: capped when the platform
+/// reports a metered network, unlimited otherwise or if it cannot tell.
+fn upload_rate_limit() -> Option<u64> {
+    match presswerk_bridge::platform_bridge().is_metered() {
+        Ok(true) => {
+            info!(
+                bytes_per_sec = METERED_UPLOAD_BYTES_PER_SEC,
+                "metered connection, throttling upload"
+            );
+            Some(METERED_UPLOAD_BYTES_PER_SEC)
+        }
+        _ => None,
+    }
+}
+
+/// Whether a PDF or image has colour a black-and-white printer would lose.
+/// Other formats, and documents that fail to load, count as grey.
+fn document_has_color(document_bytes: &[u8], document_type: DocumentType) -> bool {
+    match document_type {
+        DocumentType::Pdf => PdfReader::from_bytes(document_bytes)
+            .map(|reader| reader.has_color())
+            .unwrap_or(false),
+        DocumentType::Jpeg | DocumentType::Png | DocumentType::Tiff => {
+            ImageProcessor::from_bytes(document_bytes)
+                .map(|image| image.has_color())
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+/// Ask the printer at `printer_uri` how its job `printer_job_id` is doing,
+/// failing if the printer aborted or cancelled it.
+///
+/// With `wait`, keeps asking until the printer reports the job finished (or
+/// [`DEFAULT_COMPLETION_TIMEOUT`] passes) rather than taking one look;
+/// setting `cancel` stops asking.  A printer that cannot answer
+/// Get-Job-Attributes gets the benefit of the doubt.
+async fn check_printer_job(
+    printer_uri: &str,
+    printer_job_id: i32,
+    wait: bool,
+    cancel: &Arc<AtomicBool>,
+) -> Result<()> {
+    let client = IppClient::new(printer_uri)?.with_cancel(Arc::clone(cancel));
+    let state = if wait {
+        client
+            .wait_for_completion(printer_job_id, DEFAULT_COMPLETION_TIMEOUT)
+            .await
+    } else {
+        client.job_state(printer_job_id).await
+    };
+    let state = match state {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(printer_job_id, error = %e, "could not read job state from printer");
+            return Ok(());
+        }
+    };
+    match state {
+        JobStatus::Failed => Err(PresswerkError::IppRequest(format!(
+            "printer aborted job {printer_job_id}"
+        ))),
+        JobStatus::Cancelled => Err(PresswerkError::IppRequest(format!(
+            "printer cancelled job {printer_job_id}"
+        ))),
+        JobStatus::Completed => Ok(()),
+        state => {
+            if wait {
+                warn!(
+                    printer_job_id,
+                    ?state,
+                    "printer did not confirm the job finished in time"
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Send a document to `printer_uri`.
+///
+/// Normally a plain IPP Print-Job, falling back to raw TCP (port 9100) for
+/// PostScript, PCL and text when IPP could not deliver the job at all (see
+/// [`ipp_client::not_delivered`]), as many cheap network printers only take
+/// those over a raw socket.  A job IPP may have handed over is never sent
+/// again that way, so it cannot print twice.  When `settings.forced_protocol` is set
+/// the job goes out over exactly that protocol, with no fallback. Returns the
+/// printer's job id when IPP reported one.  Each protocol tried is appended
+/// to `attempts`.
+///
+/// Setting `cancel` aborts an IPP upload in progress and skips the raw TCP
+/// fallback.
+async fn send_job(
+    printer_uri: &str,
+    document_bytes: Vec<u8>,
+    document_type: DocumentType,
+    job_name: &str,
+    settings: &PrintSettings,
+    cancel: &Arc<AtomicBool>,
+    attempts: &mut Vec<AttemptRecord>,
+) -> Result<Option<i32>> {
+    let client = IppClient::new(printer_uri)?
+        .with_rate_limit(upload_rate_limit())
+        .with_cancel(Arc::clone(cancel));
+    let host = client
+        .uri()
+        .host()
+        .map(|host| host.trim_matches(['[', ']']).to_string())
+        .ok_or_else(|| PresswerkError::IppRequest(format!("no host in {printer_uri}")))?;
+
+    if settings.forced_protocol.is_none() {
+        let protocol = if client.uri().scheme_str() == Some("ipps") {
+            PrintProtocol::Ipps
+        } else {
+            PrintProtocol::Ipp11
+        };
+        let raw_copy = raw_client::is_raw_printable(document_type).then(|| document_bytes.clone());
+        let result = client
+            .print_job(document_bytes, document_type, job_name, settings)
+            .await;
+        attempts.push(match &result {
+            Ok(_) => AttemptRecord::delivered(protocol),
+            Err(e) => AttemptRecord::failed(protocol, e),
+        });
+        let (Err(ipp_error), Some(bytes)) = (&result, raw_copy) else {
+            return result.map(Some);
+        };
+        if cancel.load(Ordering::Relaxed) || !ipp_client::not_delivered(ipp_error) {
+            return result.map(Some);
+        }
+
+        warn!(error = %ipp_error, "IPP could not deliver the job, falling back to raw TCP");
+        let raw = RawClient::new().print(&host, RAW_PORT, &bytes).await;
+        attempts.push(match &raw {
+            Ok(()) => AttemptRecord::delivered(PrintProtocol::RawTcp),
+            Err(e) => AttemptRecord::failed(PrintProtocol::RawTcp, e),
+        });
+        return raw.map(|()| None);
+    }
+
+    let port = client.uri().port_u16().unwrap_or(631);
+    protocol::smart_print(
+        &host,
+        port,
+        Some(printer_uri),
+        document_bytes,
+        document_type,
+        job_name,
+        settings,
+        &DocumentRasterizer,
+        attempts,
+    )
+    .await?;
+    Ok(None)
 }
 
 // -- Config file persistence -------------------------------------------------
 
+//
+// With encryption enabled the config is an entry in an encrypted store under
+// `secure/`, whose passphrase is generated on first use and kept in the
+// platform keychain.  Otherwise, or where there is no keychain, it is plain
+// `config.json`.  A plain file left from before is read once and replaced
+// by the encrypted entry on the next save.
+
 const CONFIG_FILE: &str = "config.json";
 
-fn load_config(data_dir: &std::path::Path) -> Option<AppConfig> {
+/// Directory of the encrypted store, under the data directory.
+const SECURE_DIR: &str = "secure";
+
+/// Entry name of the config in the encrypted store.
+const CONFIG_ENTRY: &str = "config";
+
+/// Name a config that could not be loaded is kept under, in the encrypted
+/// store or (as `config.damaged.json`) beside `config.json`.
+const DAMAGED_CONFIG: &str = "config.damaged";
+
+/// Keychain key holding the encrypted store's passphrase.
+const CONFIG_PASSPHRASE_KEY: &str = "presswerk-config-passphrase";
+
+fn open_config_storage(data_dir: &std::path::Path) -> Option<EncryptedStorage> {
+    let bridge = presswerk_bridge::platform_bridge();
+    let passphrase = match bridge.load_secret(CONFIG_PASSPHRASE_KEY) {
+        Ok(Some(secret)) => String::from_utf8(secret).ok()?,
+        Ok(None) => {
+            let passphrase = storage::generate_passphrase().ok()?;
+            if let Err(e) = bridge.store_secret(CONFIG_PASSPHRASE_KEY, passphrase.as_bytes()) {
+                warn!("could not keep the config passphrase, storing config unencrypted: {e}");
+                return None;
+            }
+            passphrase
+        }
+        Err(e) => {
+            warn!("no keychain for the config passphrase, storing config unencrypted: {e}");
+            return None;
+        }
+    };
+    EncryptedStorage::open_with_passphrase(data_dir.join(SECURE_DIR), passphrase)
+        .map_err(|e| warn!("encrypted config store unavailable: {e}"))
+        .ok()
+}
+
+/// Read the config from the encrypted store, or else from `config.json`,
+/// with the reason it could not be read if it could not.
+///
+/// A config that cannot be read or decrypted is moved to [`DAMAGED_CONFIG`]
+/// before anything else happens, so the next save does not destroy it.
+fn load_config(
+    data_dir: &std::path::Path,
+    storage: Option<&EncryptedStorage>,
+) -> (AppConfig, Option<String>) {
+    let mut problem = None;
+    if let Some(storage) = storage {
+        let loaded = storage
+            .get(CONFIG_ENTRY)
+            .and_then(|data| data.map(|data| AppConfig::from_json(&data)).transpose());
+        match loaded {
+            Ok(Some(config)) => return (config, None),
+            Ok(None) => {}
+            Err(e) => {
+                let kept = storage.rename(CONFIG_ENTRY, DAMAGED_CONFIG);
+                problem = Some(damaged_config(CONFIG_ENTRY, DAMAGED_CONFIG, &e, kept));
+            }
+        }
+    }
+
     let path = data_dir.join(CONFIG_FILE);
-    let data = std::fs::read_to_string(&path).ok()?;
-    serde_json::from_str(&data).ok()
+    match AppConfig::load_or_default(&path) {
+        Ok(config) => (config, problem),
+        Err(e) => {
+            let backup = format!("{DAMAGED_CONFIG}.json");
+            let kept = std::fs::rename(&path, data_dir.join(&backup)).map_err(Into::into);
+            (
+                AppConfig::default(),
+                Some(damaged_config(CONFIG_FILE, &backup, &e, kept)),
+            )
+        }
+    }
+}
+
+/// Report that config `name` could not be loaded because of `error` and was
+/// moved to `backup` (or failed to be, per `kept`), returning the message.
+fn damaged_config(name: &str, backup: &str, error: &PresswerkError, kept: Result<()>) -> String {
+    match kept {
+        Ok(()) => {
+            error!(error = %error, "{name} could not be loaded; kept as {backup}");
+            format!(
+                "Your saved settings could not be loaded ({error}). Default settings are \
+                 in use; the old ones were kept as {backup}."
+            )
+        }
+        Err(e) => {
+            error!(error = %error, backup_error = %e, "{name} could not be loaded or set aside");
+            format!(
+                "Your saved settings could not be loaded ({error}), and could not be set \
+                 aside ({e}). Default settings are in use."
+            )
+        }
+    }
 }
 
-fn persist_config(data_dir: &std::path::Path, config: &AppConfig) -> Result<()> {
+fn persist_config(
+    data_dir: &std::path::Path,
+    storage: Option<&EncryptedStorage>,
+    config: &AppConfig,
+) -> Result<()> {
     let path = data_dir.join(CONFIG_FILE);
-    let json = serde_json::to_string_pretty(config)?;
-    std::fs::write(&path, json)?;
-    Ok(())
+    match storage {
+        Some(storage) if config.encryption_enabled => {
+            config.validate()?;
+            storage.put(CONFIG_ENTRY, &config.to_json()?)?;
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        }
+        _ => {
+            config.save(&path)?;
+            if let Some(storage) = storage {
+                storage.remove(CONFIG_ENTRY)?;
+            }
+            Ok(())
+        }
+    }
 }
@@ -23,6 +23,11 @@ pub fn data_subdir(name: &str) -> PathBuf {
     dir
 }
 
+/// Path of the application log file (tailed into diagnostic bundles).
+pub fn log_path() -> PathBuf {
+    data_dir().join("presswerk.log")
+}
+
 fn dirs_fallback() -> PathBuf {
     // Try XDG data dir, then fallback to home
     if let Ok(xdg) = std::env::var("XDG_DATA_HOME") {
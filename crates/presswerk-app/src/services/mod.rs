@@ -8,3 +8,5 @@
 
 pub mod app_services;
 pub mod data_dir;
+pub mod rasterizer;
+pub mod scan_assembler;
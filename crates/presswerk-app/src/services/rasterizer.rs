@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Client-side rasterization for printers that cannot interpret a document's
+// format, backed by presswerk-document.
+//
+// Only image documents can be rasterized for now — there is no PDF renderer
+// in the tree, so PDFs are refused with a clear error.
+
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgb, RgbImage};
+
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::{DocumentType, Orientation, PrintSettings};
+use presswerk_document::ImageProcessor;
+use presswerk_print::ipp_client::Rasterizer;
+
+/// Resolution pages are rendered at for PWG Raster output.
+const RASTER_DPI: u32 = 300;
+
+/// JPEG quality for printers that take JPEG but not the original format.
+const JPEG_QUALITY: u8 = 90;
+
+/// [`Rasterizer`] for image documents.
+pub struct DocumentRasterizer;
+
+impl Rasterizer for DocumentRasterizer {
+    fn rasterize(
+        &self,
+        document: &[u8],
+        document_type: DocumentType,
+        target: DocumentType,
+        settings: &PrintSettings,
+    ) -> Result<Vec<u8>> {
+        if !matches!(
+            document_type,
+            DocumentType::Jpeg | DocumentType::Png | DocumentType::Tiff
+        ) {
+            return Err(PresswerkError::UnsupportedDocument(format!(
+                "cannot rasterize {} for this printer",
+                document_type.mime_type()
+            )));
+        }
+
+        let processor = ImageProcessor::from_bytes(document)?;
+        match target {
+            DocumentType::Jpeg => processor.to_jpeg_bytes(JPEG_QUALITY),
+            DocumentType::PwgRaster => {
+                let page = fit_to_page(processor.into_dynamic(), settings);
+                presswerk_document::to_pwg_raster(&[page], settings)
+            }
+            other => Err(PresswerkError::UnsupportedDocument(format!(
+                "cannot rasterize to {}",
+                other.mime_type()
+            ))),
+        }
+    }
+}
+
+/// Scale `image` to fit the paper at [`RASTER_DPI`], centred on white.
+fn fit_to_page(image: DynamicImage, settings: &PrintSettings) -> DynamicImage {
+    let (w_mm, h_mm) = settings.paper_size.dimensions_mm();
+    let to_px = |mm: f32| (mm / 25.4 * RASTER_DPI as f32).round() as u32;
+    let landscape = matches!(
+        settings.orientation,
+        Orientation::Landscape | Orientation::ReverseLandscape
+    );
+    let (page_w, page_h) = if landscape {
+        (to_px(h_mm), to_px(w_mm))
+    } else {
+        (to_px(w_mm), to_px(h_mm))
+    };
+
+    let scaled = if image.width() > page_w || image.height() > page_h || settings.scale_to_fit {
+        image.resize(page_w, page_h, FilterType::Triangle)
+    } else {
+        image
+    };
+
+    let mut page = RgbImage::from_pixel(page_w, page_h, Rgb([255, 255, 255]));
+    image::imageops::replace(
+        &mut page,
+        &scaled.to_rgb8(),
+        ((page_w - scaled.width()) / 2).into(),
+        ((page_h - scaled.height()) / 2).into(),
+    );
+    DynamicImage::ImageRgb8(page)
+}
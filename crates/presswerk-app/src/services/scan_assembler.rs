@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Turns the pages of a finished scan session into one PDF, backed by
+// presswerk-document.
+
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::PaperSize;
+use presswerk_document::{PdfReader, ScanEnhancer};
+use presswerk_print::scan_session::PageAssembler;
+
+/// [`PageAssembler`] producing one PDF page per scanned image.
+///
+/// Each page goes through [`ScanEnhancer::scan_to_pdf`], which keeps
+/// bilevel pages as compact PNGs, and a page that cannot be decoded fails
+/// the whole assembly rather than being left out: the session is only
+/// discarded once every page made it into the PDF.
+pub struct ScanPdfAssembler {
+    pub paper_size: PaperSize,
+}
+
+impl PageAssembler for ScanPdfAssembler {
+    fn assemble(&self, pages: &[Vec<u8>]) -> Result<Vec<u8>> {
+        let page_pdfs = pages
+            .iter()
+            .map(|page| ScanEnhancer::from_bytes(page, self.paper_size)?.scan_to_pdf())
+            .collect::<Result<Vec<_>>>()?;
+
+        let (first, rest) = page_pdfs
+            .split_first()
+            .ok_or_else(|| PresswerkError::PdfError("no pages to assemble".into()))?;
+        if rest.is_empty() {
+            return Ok(first.clone());
+        }
+        let rest: Vec<&[u8]> = rest.iter().map(Vec::as_slice).collect();
+        PdfReader::from_bytes(first)?.merge(&rest)
+    }
+}
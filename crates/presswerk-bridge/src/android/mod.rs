@@ -13,20 +13,28 @@
 // ContentResolver, Intent launching) are fully implemented here.
 //
 // Methods that require `startActivityForResult` (camera capture, file picker)
-// launch the Intent and return `PresswerkError::Bridge` explaining that the
-// result must be collected through the Activity's `onActivityResult` callback.
-// The host Activity is responsible for wiring that callback back into
-// Presswerk — see `ANDROID-INTEGRATION.md` for the Java/Kotlin glue code.
+// launch the Intent and then block until the host Activity forwards its
+// `onActivityResult` callback to `PresswerkResultReceiver` (see the
+// `result_receiver` submodule for the Java glue).  They must therefore not be
+// called on the Android main thread, which delivers that callback.
 
 #![cfg(target_os = "android")]
 
+pub mod result_receiver;
+
+use std::time::Duration;
+
 use jni::JNIEnv;
-use jni::objects::{JObject, JString, JValue};
+use jni::objects::{JObject, JObjectArray, JString, JValue};
 use jni::sys::jsize;
 
 use presswerk_core::error::{PresswerkError, Result};
 
+use crate::bluetooth::{self, BondedDevice};
+use crate::result_receiver::{self as results, ActivityResult};
 use crate::traits::*;
+use crate::usb::{self, USB_CLASS_PRINTER, UsbDeviceDescriptor};
+use crate::wifi;
 
 // ---------------------------------------------------------------------------
 // JNI bootstrap helpers
@@ -42,6 +50,68 @@
 /// recognise these in its `onActivityResult` override.
 pub const REQUEST_IMAGE_CAPTURE: i32 = 0x5057_0001; // "PW" + 1
 pub const REQUEST_PICK_FILE: i32 = 0x5057_0002;
+pub const REQUEST_PERMISSION: i32 = 0x5057_0003;
+pub const REQUEST_SHARE: i32 = 0x5057_0004;
+
+/// Request code the share chooser's chosen target is delivered under, apart
+/// from the chooser's own activity result; also the request code of the
+/// `PendingIntent` that reports it.
+pub const REQUEST_SHARE_TARGET: i32 = 0x5057_0005;
+
+/// Request code of the USB permission `PendingIntent`, so it is not mistaken
+/// for (or replaced by) another of Presswerk's pending intents.
+pub const REQUEST_USB_PERMISSION: i32 = 0x5057_0006;
+
+/// How long to wait for the user to finish in the camera or picker.
+const ACTIVITY_RESULT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How long to wait for the chosen share target once the chooser has closed:
+/// its broadcast can arrive after the chooser's activity result.
+const SHARE_TARGET_GRACE: Duration = Duration::from_secs(2);
+
+/// File in the cache dir the camera writes the full-resolution photo to.
+const CAPTURE_FILENAME: &str = "presswerk_capture.jpg";
+
+/// Broadcast receiver the share chooser reports the chosen target to. The
+/// host app declares it in `AndroidManifest.xml` (see `result_receiver`).
+const SHARE_RECEIVER_CLASS: &str = "org.presswerk.PresswerkShareReceiver";
+
+/// `PendingIntent.FLAG_UPDATE_CURRENT`.
+const PENDING_INTENT_FLAG_UPDATE_CURRENT: i32 = 0x0800_0000;
+
+/// `PendingIntent.FLAG_MUTABLE` (API 31+).
+const PENDING_INTENT_FLAG_MUTABLE: i32 = 0x0200_0000;
+
+/// Broadcast action for the USB permission `PendingIntent`. The host
+/// Activity may register a receiver for it to retry the print once the user
+/// has answered the prompt.
+pub const ACTION_USB_PERMISSION: &str = "org.presswerk.USB_PERMISSION";
+
+/// `UsbConstants.USB_ENDPOINT_XFER_BULK`.
+const USB_ENDPOINT_XFER_BULK: i32 = 2;
+
+/// `UsbConstants.USB_DIR_OUT`.
+const USB_DIR_OUT: i32 = 0;
+
+/// Bytes sent per `bulkTransfer` call.
+const USB_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Timeout for each `bulkTransfer` call, in milliseconds.
+const USB_TRANSFER_TIMEOUT_MS: i32 = 30_000;
+
+/// `PackageManager.PERMISSION_GRANTED`.
+const PERMISSION_GRANTED: i32 = 0;
+
+/// API level that made Bluetooth connections a runtime permission
+/// (`BLUETOOTH_CONNECT`, Android 12).
+const API_LEVEL_BLUETOOTH_CONNECT: i32 = 31;
+
+/// Bytes written to the RFCOMM stream per `write` call.
+const BLUETOOTH_CHUNK_SIZE: usize = 4 * 1024;
+
+/// Local references reserved for each [`in_local_frame`] call; the JVM
+/// grows the frame if more are needed.
+const LOCAL_FRAME_CAPACITY: i32 = 16;
 
 /// Obtain a [`JNIEnv`] handle from the global Android context.
 ///
@@ -81,6 +151,17 @@ fn jni_err(context: &str, e: jni::errors::Error) -> PresswerkError {
     PresswerkError::Bridge(format!("{context}: {e}"))
 }
 
+/// Run `f` in its own JNI local reference frame, so the references it
+/// creates are freed when it returns instead of piling up across a loop
+/// until the native call ends.
+fn in_local_frame<T>(
+    env: &mut JNIEnv<'_>,
+    f: impl FnOnce(&mut JNIEnv<'_>) -> Result<T>,
+) -> Result<T> {
+    env.with_local_frame(LOCAL_FRAME_CAPACITY, |env| Ok::<_, jni::errors::Error>(f(env)))
+        .map_err(|e| jni_err("with_local_frame", e))?
+}
+
 // ---------------------------------------------------------------------------
 // Bridge struct
 // ---------------------------------------------------------------------------
@@ -105,6 +186,140 @@ impl PlatformBridge for AndroidBridge {
     fn platform_name(&self) -> &str {
         "Android"
     }
+
+    /// Ask `Context.checkSelfPermission` for the manifest permission behind
+    /// `permission`.
+    ///
+    /// Android does not say whether a refused permission was ever asked
+    /// for, so anything not granted reads as `Denied`.  Local network access
+    /// only needs `INTERNET`, which some ROMs let the user revoke.
+    fn permission_status(&self, permission: Permission) -> PermissionState {
+        let status = (|| {
+            let mut env = jni_env()?;
+            let activity = activity()?;
+
+            let Some(manifest_permission) = manifest_permission(&mut env, permission)? else {
+                return Ok(PermissionState::Granted);
+            };
+            let j_permission: JString = env
+                .new_string(manifest_permission)
+                .map_err(|e| jni_err("new_string(permission)", e))?;
+            let result = env
+                .call_method(
+                    &activity,
+                    "checkSelfPermission",
+                    "(Ljava/lang/String;)I",
+                    &[JValue::Object(&j_permission)],
+                )
+                .map_err(|e| jni_err("checkSelfPermission", e))?
+                .i()
+                .map_err(|e| jni_err("checkSelfPermission->i", e))?;
+
+            Ok::<_, PresswerkError>(if result == PERMISSION_GRANTED {
+                PermissionState::Granted
+            } else {
+                PermissionState::Denied
+            })
+        })();
+
+        status.unwrap_or_else(|e| {
+            tracing::warn!(?permission, error = %e, "Android: permission check failed");
+            PermissionState::Unknown
+        })
+    }
+
+    /// Show the system prompt via `Activity.requestPermissions`.
+    ///
+    /// The answer comes back through the host Activity's
+    /// `onRequestPermissionsResult` (see `result_receiver` for the wiring);
+    /// a background thread waits for it and calls `on_result`.  Permissions
+    /// already granted answer at once without a prompt.
+    fn request_permission(&self, permission: Permission, on_result: PermissionCallback) {
+        if self.permission_status(permission) == PermissionState::Granted {
+            on_result(PermissionState::Granted);
+            return;
+        }
+
+        let requested = (|| {
+            let mut env = jni_env()?;
+            let activity = activity()?;
+
+            let Some(manifest_permission) = manifest_permission(&mut env, permission)? else {
+                return Ok(None);
+            };
+            let j_permission: JString = env
+                .new_string(manifest_permission)
+                .map_err(|e| jni_err("new_string(permission)", e))?;
+            let permissions: JObjectArray = env
+                .new_object_array(1, "java/lang/String", &j_permission)
+                .map_err(|e| jni_err("new_object_array(permissions)", e))?;
+
+            let pending = results::register(REQUEST_PERMISSION);
+            env.call_method(
+                &activity,
+                "requestPermissions",
+                "([Ljava/lang/String;I)V",
+                &[
+                    JValue::Object(&permissions),
+                    JValue::Int(REQUEST_PERMISSION),
+                ],
+            )
+            .map_err(|e| jni_err("requestPermissions", e))?;
+
+            tracing::info!(
+                permission = manifest_permission,
+                "Android: permission prompt shown — awaiting onRequestPermissionsResult"
+            );
+            Ok::<_, PresswerkError>(Some(pending))
+        })();
+
+        let pending = match requested {
+            Ok(Some(pending)) => pending,
+            // No runtime permission on this API level.
+            Ok(None) => return on_result(PermissionState::Granted),
+            Err(e) => {
+                tracing::warn!(?permission, error = %e, "Android: permission request failed");
+                return on_result(PermissionState::Unknown);
+            }
+        };
+
+        std::thread::spawn(move || {
+            let state = match pending.wait(ACTIVITY_RESULT_TIMEOUT) {
+                Ok(ActivityResult::Permission { granted: true }) => PermissionState::Granted,
+                Ok(ActivityResult::Permission { granted: false }) => PermissionState::Denied,
+                // The prompt was interrupted before the user answered.
+                Ok(ActivityResult::Cancelled) => PermissionState::NotDetermined,
+                Ok(other) => {
+                    tracing::warn!(?other, "Android: unexpected permission result");
+                    PermissionState::Unknown
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Android: no answer to the permission prompt");
+                    PermissionState::Unknown
+                }
+            };
+            on_result(state);
+        });
+    }
+}
+
+/// The manifest permission Android checks for `permission`, or `None` if
+/// it needs no runtime grant on this device.
+fn manifest_permission(
+    env: &mut JNIEnv<'_>,
+    permission: Permission,
+) -> Result<Option<&'static str>> {
+    Ok(Some(match permission {
+        Permission::LocalNetwork => "android.permission.INTERNET",
+        Permission::Bluetooth => {
+            if sdk_int(env)? < API_LEVEL_BLUETOOTH_CONNECT {
+                // Install-time before Android 12.
+                return Ok(None);
+            }
+            "android.permission.BLUETOOTH_CONNECT"
+        }
+        Permission::Camera => "android.permission.CAMERA",
+    }))
 }
 
 // ---------------------------------------------------------------------------
@@ -254,15 +469,10 @@ fn show_print_dialog(&self, document: &[u8], mime_type: &str) -> Result<()> {
 impl NativeCamera for AndroidBridge {
     /// Launch the system camera via `MediaStore.ACTION_IMAGE_CAPTURE`.
     ///
-    /// This dispatches the capture intent and returns immediately. Because
-    /// `startActivityForResult` is inherently asynchronous, the JPEG bytes
-    /// are **not** returned from this call. Instead, the host Activity must
-    /// override `onActivityResult` with request code [`REQUEST_IMAGE_CAPTURE`]
-    /// and forward the result back to Presswerk.
-    ///
-    /// Returns `Err(Bridge(...))` with an explanatory message after the
-    /// intent has been launched so callers know to await the Activity
-    /// callback.
+    /// The camera writes the photo to the cache directory; this call blocks
+    /// until the host Activity forwards `onActivityResult` for
+    /// [`REQUEST_IMAGE_CAPTURE`] and then returns the JPEG bytes, or
+    /// `Ok(None)` if the user cancelled.
     fn capture_image(&self) -> Result<Option<Vec<u8>>> {
         let mut env = jni_env()?;
         let activity = activity()?;
@@ -277,7 +487,7 @@ fn capture_image(&self) -> Result<Option<Vec<u8>>> {
             .map_err(|e| jni_err("getCacheDir->l", e))?;
 
         let j_filename: JString = env
-            .new_string("presswerk_capture.jpg")
+            .new_string(CAPTURE_FILENAME)
             .map_err(|e| jni_err("new_string", e))?;
 
         let photo_file: JObject = env
@@ -345,6 +555,7 @@ fn capture_image(&self) -> Result<Option<Vec<u8>>> {
         .map_err(|e| jni_err("addFlags(camera)", e))?;
 
         // -- Dispatch -----------------------------------------------------------
+        let pending = results::register(REQUEST_IMAGE_CAPTURE);
         env.call_method(
             &activity,
             "startActivityForResult",
@@ -358,12 +569,14 @@ fn capture_image(&self) -> Result<Option<Vec<u8>>> {
             "Android: camera intent dispatched — awaiting onActivityResult"
         );
 
-        Err(PresswerkError::Bridge(
-            "Camera intent dispatched (request code 0x50570001). \
-             The captured JPEG will arrive via onActivityResult — \
-             wire the Activity callback to PresswerkResultReceiver."
-                .into(),
-        ))
+        match pending.wait(ACTIVITY_RESULT_TIMEOUT)? {
+            ActivityResult::Image(bytes) => Ok(Some(bytes)),
+            ActivityResult::Cancelled => Ok(None),
+            ActivityResult::Failed(reason) => Err(PresswerkError::Bridge(reason)),
+            other => Err(PresswerkError::Bridge(format!(
+                "unexpected camera result: {other:?}"
+            ))),
+        }
     }
 }
 
@@ -374,10 +587,10 @@ fn capture_image(&self) -> Result<Option<Vec<u8>>> {
 impl NativeFilePicker for AndroidBridge {
     /// Launch the Storage Access Framework document picker.
     ///
-    /// Dispatches `ACTION_OPEN_DOCUMENT` filtered to the supplied MIME types.
-    /// Like camera capture, the result (a `content://` URI) arrives
-    /// asynchronously via `onActivityResult` with request code
-    /// [`REQUEST_PICK_FILE`].
+    /// Dispatches `ACTION_OPEN_DOCUMENT` filtered to the supplied MIME types
+    /// and blocks until `onActivityResult` for [`REQUEST_PICK_FILE`] is
+    /// forwarded, returning the chosen `content://` URI (read it with
+    /// [`read_picked_file`](NativeFilePicker::read_picked_file)).
     fn pick_file(&self, mime_types: &[&str]) -> Result<Option<String>> {
         let mut env = jni_env()?;
         let activity = activity()?;
@@ -447,8 +660,10 @@ fn pick_file(&self, mime_types: &[&str]) -> Result<Option<String>> {
                 let j_mt: JString = env
                     .new_string(mt)
                     .map_err(|e| jni_err("new_string(mime_type[i])", e))?;
-                env.set_object_array_element(&mime_array, i as jsize, j_mt)
+                env.set_object_array_element(&mime_array, i as jsize, &j_mt)
                     .map_err(|e| jni_err("set_object_array_element", e))?;
+                env.delete_local_ref(j_mt)
+                    .map_err(|e| jni_err("delete_local_ref(mime_type[i])", e))?;
             }
 
             let j_extra_key: JString = env
@@ -465,6 +680,7 @@ fn pick_file(&self, mime_types: &[&str]) -> Result<Option<String>> {
         }
 
         // -- Dispatch -----------------------------------------------------------
+        let pending = results::register(REQUEST_PICK_FILE);
         env.call_method(
             &activity,
             "startActivityForResult",
@@ -478,12 +694,14 @@ fn pick_file(&self, mime_types: &[&str]) -> Result<Option<String>> {
             "Android: file picker intent dispatched — awaiting onActivityResult"
         );
 
-        Err(PresswerkError::Bridge(
-            "File picker intent dispatched (request code 0x50570002). \
-             The chosen content:// URI will arrive via onActivityResult — \
-             wire the Activity callback to PresswerkResultReceiver."
-                .into(),
-        ))
+        match pending.wait(ACTIVITY_RESULT_TIMEOUT)? {
+            ActivityResult::Document(uri) => Ok(Some(uri)),
+            ActivityResult::Cancelled => Ok(None),
+            ActivityResult::Failed(reason) => Err(PresswerkError::Bridge(reason)),
+            other => Err(PresswerkError::Bridge(format!(
+                "unexpected file picker result: {other:?}"
+            ))),
+        }
     }
 
     /// Read bytes from a `content://` URI returned by the Storage Access
@@ -791,118 +1009,163 @@ fn share_file(&self, path: &str, mime_type: &str) -> Result<()> {
 
         tracing::info!(path, mime = mime_type, "Android: launching share intent");
 
-        // -- Build File object --------------------------------------------------
-        let j_path: JString = env
-            .new_string(path)
-            .map_err(|e| jni_err("new_string(path)", e))?;
-
-        let file_obj: JObject = env
-            .new_object(
-                "java/io/File",
-                "(Ljava/lang/String;)V",
-                &[JValue::Object(&j_path)],
-            )
-            .map_err(|e| jni_err("new File(path)", e))?;
+        let intent = share_file_intent(&mut env, &activity, path, mime_type)?;
 
-        // -- Build content:// URI via FileProvider ------------------------------
-        let authority = get_authority(&mut env, &activity)?;
-        let j_authority: JString = env
-            .new_string(&authority)
-            .map_err(|e| jni_err("new_string(authority)", e))?;
+        // -- Wrap in a chooser --------------------------------------------------
+        let j_title: JString = env
+            .new_string("Share via")
+            .map_err(|e| jni_err("new_string(chooser_title)", e))?;
 
-        let content_uri: JObject = env
+        let chooser: JObject = env
             .call_static_method(
-                "androidx/core/content/FileProvider",
-                "getUriForFile",
-                "(Landroid/content/Context;Ljava/lang/String;Ljava/io/File;)Landroid/net/Uri;",
-                &[
-                    JValue::Object(&activity),
-                    JValue::Object(&j_authority),
-                    JValue::Object(&file_obj),
-                ],
+                "android/content/Intent",
+                "createChooser",
+                "(Landroid/content/Intent;Ljava/lang/CharSequence;)Landroid/content/Intent;",
+                &[JValue::Object(&intent), JValue::Object(&j_title)],
             )
-            .map_err(|e| jni_err("FileProvider.getUriForFile(share)", e))?
+            .map_err(|e| jni_err("Intent.createChooser", e))?
             .l()
-            .map_err(|e| jni_err("getUriForFile->l(share)", e))?;
+            .map_err(|e| jni_err("createChooser->l", e))?;
 
-        // -- Build ACTION_SEND intent -------------------------------------------
-        let j_action: JString = env
-            .new_string("android.intent.action.SEND")
-            .map_err(|e| jni_err("new_string(ACTION_SEND)", e))?;
+        // -- Launch -------------------------------------------------------------
+        env.call_method(
+            &activity,
+            "startActivity",
+            "(Landroid/content/Intent;)V",
+            &[JValue::Object(&chooser)],
+        )
+        .map_err(|e| jni_err("startActivity(share)", e))?;
 
-        let intent: JObject = env
-            .new_object(
-                "android/content/Intent",
-                "(Ljava/lang/String;)V",
-                &[JValue::Object(&j_action)],
-            )
-            .map_err(|e| jni_err("new Intent(SEND)", e))?;
+        tracing::info!(path, mime = mime_type, "Android: share intent dispatched");
+        Ok(())
+    }
 
-        // intent.setType(mimeType)
-        let j_mime: JString = env
-            .new_string(mime_type)
-            .map_err(|e| jni_err("new_string(mime)", e))?;
+    /// Share a file and wait for the user's choice.
+    ///
+    /// The chooser is given a `PendingIntent` for
+    /// `org.presswerk.PresswerkShareReceiver`, which Android fires with
+    /// `EXTRA_CHOSEN_COMPONENT` once a target is picked; the receiver hands
+    /// the package to `onShareTargetChosen`, delivered under
+    /// [`REQUEST_SHARE_TARGET`].  The chooser itself is started for
+    /// [`REQUEST_SHARE`] and usually reports "cancelled" whatever the user
+    /// did, so once it closes the target is waited for a further
+    /// [`SHARE_TARGET_GRACE`]; with none, the outcome is
+    /// [`ShareOutcome::Unknown`].
+    fn share_file_with_result(&self, path: &str, mime_type: &str) -> Result<ShareOutcome> {
+        let mut env = jni_env()?;
+        let activity = activity()?;
 
-        env.call_method(
-            &intent,
-            "setType",
-            "(Ljava/lang/String;)Landroid/content/Intent;",
-            &[JValue::Object(&j_mime)],
-        )
-        .map_err(|e| jni_err("setType(share)", e))?;
+        tracing::info!(
+            path,
+            mime = mime_type,
+            "Android: launching share intent for result"
+        );
 
-        // intent.putExtra(Intent.EXTRA_STREAM, contentUri)
-        let j_extra_stream: JString = env
-            .new_string("android.intent.extra.STREAM")
-            .map_err(|e| jni_err("new_string(EXTRA_STREAM)", e))?;
+        let intent = share_file_intent(&mut env, &activity, path, mime_type)?;
 
+        // -- PendingIntent for the chosen target --------------------------------
+        let package: JObject = env
+            .call_method(&activity, "getPackageName", "()Ljava/lang/String;", &[])
+            .map_err(|e| jni_err("getPackageName(share)", e))?
+            .l()
+            .map_err(|e| jni_err("getPackageName->l(share)", e))?;
+        let j_receiver: JString = env
+            .new_string(SHARE_RECEIVER_CLASS)
+            .map_err(|e| jni_err("new_string(share_receiver)", e))?;
+
+        let callback: JObject = env
+            .new_object("android/content/Intent", "()V", &[])
+            .map_err(|e| jni_err("new Intent(share_callback)", e))?;
         env.call_method(
-            &intent,
-            "putExtra",
-            "(Ljava/lang/String;Landroid/os/Parcelable;)Landroid/content/Intent;",
-            &[
-                JValue::Object(&j_extra_stream),
-                JValue::Object(&content_uri),
-            ],
+            &callback,
+            "setClassName",
+            "(Ljava/lang/String;Ljava/lang/String;)Landroid/content/Intent;",
+            &[JValue::Object(&package), JValue::Object(&j_receiver)],
         )
-        .map_err(|e| jni_err("putExtra(EXTRA_STREAM)", e))?;
+        .map_err(|e| jni_err("setClassName(share_callback)", e))?;
 
-        // Grant read permission
-        env.call_method(
-            &intent,
-            "addFlags",
-            "(I)Landroid/content/Intent;",
-            &[JValue::Int(0x0000_0001)], // FLAG_GRANT_READ_URI_PERMISSION
-        )
-        .map_err(|e| jni_err("addFlags(share)", e))?;
+        // The chooser adds EXTRA_CHOSEN_COMPONENT, so the intent must be
+        // mutable.
+        let sender: JObject = env
+            .call_static_method(
+                "android/app/PendingIntent",
+                "getBroadcast",
+                "(Landroid/content/Context;ILandroid/content/Intent;I)Landroid/app/PendingIntent;",
+                &[
+                    JValue::Object(&activity),
+                    JValue::Int(REQUEST_SHARE_TARGET),
+                    JValue::Object(&callback),
+                    JValue::Int(PENDING_INTENT_FLAG_UPDATE_CURRENT | PENDING_INTENT_FLAG_MUTABLE),
+                ],
+            )
+            .map_err(|e| jni_err("PendingIntent.getBroadcast(share)", e))?
+            .l()
+            .map_err(|e| jni_err("getBroadcast->l(share)", e))?;
+        let sender: JObject = env
+            .call_method(
+                &sender,
+                "getIntentSender",
+                "()Landroid/content/IntentSender;",
+                &[],
+            )
+            .map_err(|e| jni_err("getIntentSender(share)", e))?
+            .l()
+            .map_err(|e| jni_err("getIntentSender->l(share)", e))?;
 
         // -- Wrap in a chooser --------------------------------------------------
         let j_title: JString = env
             .new_string("Share via")
-            .map_err(|e| jni_err("new_string(chooser_title)", e))?;
+            .map_err(|e| jni_err("new_string(chooser_title_result)", e))?;
 
         let chooser: JObject = env
             .call_static_method(
                 "android/content/Intent",
                 "createChooser",
-                "(Landroid/content/Intent;Ljava/lang/CharSequence;)Landroid/content/Intent;",
-                &[JValue::Object(&intent), JValue::Object(&j_title)],
+                "(Landroid/content/Intent;Ljava/lang/CharSequence;Landroid/content/IntentSender;)Landroid/content/Intent;",
+                &[
+                    JValue::Object(&intent),
+                    JValue::Object(&j_title),
+                    JValue::Object(&sender),
+                ],
             )
-            .map_err(|e| jni_err("Intent.createChooser", e))?
+            .map_err(|e| jni_err("Intent.createChooser(result)", e))?
             .l()
-            .map_err(|e| jni_err("createChooser->l", e))?;
+            .map_err(|e| jni_err("createChooser->l(result)", e))?;
 
-        // -- Launch -------------------------------------------------------------
+        // -- Dispatch -----------------------------------------------------------
+        let chosen = results::register(REQUEST_SHARE_TARGET);
+        let pending = results::register(REQUEST_SHARE);
         env.call_method(
             &activity,
-            "startActivity",
-            "(Landroid/content/Intent;)V",
-            &[JValue::Object(&chooser)],
+            "startActivityForResult",
+            "(Landroid/content/Intent;I)V",
+            &[JValue::Object(&chooser), JValue::Int(REQUEST_SHARE)],
         )
-        .map_err(|e| jni_err("startActivity(share)", e))?;
+        .map_err(|e| jni_err("startActivityForResult(share)", e))?;
 
-        tracing::info!(path, mime = mime_type, "Android: share intent dispatched");
-        Ok(())
+        tracing::info!(
+            request_code = REQUEST_SHARE,
+            "Android: share chooser dispatched — awaiting the chosen target"
+        );
+
+        let closed = pending.wait(ACTIVITY_RESULT_TIMEOUT)?;
+        if let ActivityResult::Failed(reason) = closed {
+            return Err(PresswerkError::Bridge(reason));
+        }
+        match chosen.wait(SHARE_TARGET_GRACE) {
+            Ok(ActivityResult::Shared { target }) => {
+                return Ok(ShareOutcome::Completed { target });
+            }
+            Ok(ActivityResult::Failed(reason)) => return Err(PresswerkError::Bridge(reason)),
+            _ => {}
+        }
+        match closed {
+            ActivityResult::Shared { target } => Ok(ShareOutcome::Completed { target }),
+            ActivityResult::Cancelled => Ok(ShareOutcome::Unknown),
+            other => Err(PresswerkError::Bridge(format!(
+                "unexpected share result: {other:?}"
+            ))),
+        }
     }
 
     /// Share text content via the Android share sheet.
@@ -980,6 +1243,396 @@ fn share_text(&self, text: &str) -> Result<()> {
     }
 }
 
+/// An `ACTION_SEND` intent carrying the file at `path` as a `content://`
+/// URI from `FileProvider`, readable by whichever app receives it.
+fn share_file_intent<'local>(
+    env: &mut JNIEnv<'local>,
+    activity: &JObject<'_>,
+    path: &str,
+    mime_type: &str,
+) -> Result<JObject<'local>> {
+    // -- Build File object ------------------------------------------------------
+    let j_path: JString = env
+        .new_string(path)
+        .map_err(|e| jni_err("new_string(path)", e))?;
+
+    let file_obj: JObject = env
+        .new_object(
+            "java/io/File",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(&j_path)],
+        )
+        .map_err(|e| jni_err("new File(path)", e))?;
+
+    // -- Build content:// URI via FileProvider ----------------------------------
+    let authority = get_authority(env, activity)?;
+    let j_authority: JString = env
+        .new_string(&authority)
+        .map_err(|e| jni_err("new_string(authority)", e))?;
+
+    let content_uri: JObject = env
+        .call_static_method(
+            "androidx/core/content/FileProvider",
+            "getUriForFile",
+            "(Landroid/content/Context;Ljava/lang/String;Ljava/io/File;)Landroid/net/Uri;",
+            &[
+                JValue::Object(activity),
+                JValue::Object(&j_authority),
+                JValue::Object(&file_obj),
+            ],
+        )
+        .map_err(|e| jni_err("FileProvider.getUriForFile(share)", e))?
+        .l()
+        .map_err(|e| jni_err("getUriForFile->l(share)", e))?;
+
+    // -- Build ACTION_SEND intent -----------------------------------------------
+    let j_action: JString = env
+        .new_string("android.intent.action.SEND")
+        .map_err(|e| jni_err("new_string(ACTION_SEND)", e))?;
+
+    let intent: JObject = env
+        .new_object(
+            "android/content/Intent",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(&j_action)],
+        )
+        .map_err(|e| jni_err("new Intent(SEND)", e))?;
+
+    // intent.setType(mimeType)
+    let j_mime: JString = env
+        .new_string(mime_type)
+        .map_err(|e| jni_err("new_string(mime)", e))?;
+
+    env.call_method(
+        &intent,
+        "setType",
+        "(Ljava/lang/String;)Landroid/content/Intent;",
+        &[JValue::Object(&j_mime)],
+    )
+    .map_err(|e| jni_err("setType(share)", e))?;
+
+    // intent.putExtra(Intent.EXTRA_STREAM, contentUri)
+    let j_extra_stream: JString = env
+        .new_string("android.intent.extra.STREAM")
+        .map_err(|e| jni_err("new_string(EXTRA_STREAM)", e))?;
+
+    env.call_method(
+        &intent,
+        "putExtra",
+        "(Ljava/lang/String;Landroid/os/Parcelable;)Landroid/content/Intent;",
+        &[
+            JValue::Object(&j_extra_stream),
+            JValue::Object(&content_uri),
+        ],
+    )
+    .map_err(|e| jni_err("putExtra(EXTRA_STREAM)", e))?;
+
+    // Grant read permission
+    env.call_method(
+        &intent,
+        "addFlags",
+        "(I)Landroid/content/Intent;",
+        &[JValue::Int(0x0000_0001)], // FLAG_GRANT_READ_URI_PERMISSION
+    )
+    .map_err(|e| jni_err("addFlags(share)", e))?;
+
+    Ok(intent)
+}
+
+// ---------------------------------------------------------------------------
+// NativeUsbPrint — android.hardware.usb.UsbManager (USB host / OTG)
+// ---------------------------------------------------------------------------
+
+impl NativeUsbPrint for AndroidBridge {
+    /// List attached devices that expose the USB printer class.
+    ///
+    /// Enumerating devices needs no permission; the `device_id` is the
+    /// device path (`/dev/bus/usb/...`) used as the key of
+    /// `UsbManager.getDeviceList()`.
+    fn detect_usb_printers(&self) -> Result<Vec<UsbPrinterInfo>> {
+        let mut env = jni_env()?;
+        let activity = activity()?;
+        let manager = usb_manager(&mut env, &activity)?;
+
+        let device_map = env
+            .call_method(&manager, "getDeviceList", "()Ljava/util/HashMap;", &[])
+            .map_err(|e| jni_err("getDeviceList", e))?
+            .l()
+            .map_err(|e| jni_err("getDeviceList->l", e))?;
+        let values = env
+            .call_method(&device_map, "values", "()Ljava/util/Collection;", &[])
+            .map_err(|e| jni_err("HashMap.values", e))?
+            .l()
+            .map_err(|e| jni_err("HashMap.values->l", e))?;
+        let devices = JObjectArray::from(
+            env.call_method(&values, "toArray", "()[Ljava/lang/Object;", &[])
+                .map_err(|e| jni_err("Collection.toArray", e))?
+                .l()
+                .map_err(|e| jni_err("Collection.toArray->l", e))?,
+        );
+
+        let count = env
+            .get_array_length(&devices)
+            .map_err(|e| jni_err("get_array_length(devices)", e))?;
+        let mut printers = Vec::new();
+        for i in 0..count {
+            let descriptor = in_local_frame(&mut env, |env| {
+                let device = env
+                    .get_object_array_element(&devices, i)
+                    .map_err(|e| jni_err("get_object_array_element(devices)", e))?;
+                read_usb_descriptor(env, &device)
+            })?;
+            if let Some(info) = usb::printer_info(&descriptor) {
+                printers.push(info);
+            }
+        }
+
+        tracing::info!(count = printers.len(), "Android: USB printers detected");
+        Ok(printers)
+    }
+
+    /// Bulk-write `document` to the printer's OUT endpoint.
+    ///
+    /// If the app does not yet hold permission for the device, the system
+    /// permission prompt is requested and `PermissionDenied` is returned;
+    /// the call can be repeated once the user has approved it. The document
+    /// is sent as-is, so `mime_type` must be something the printer accepts.
+    fn print_usb(&self, device_id: &str, document: &[u8], mime_type: &str) -> Result<()> {
+        let mut env = jni_env()?;
+        let activity = activity()?;
+        let manager = usb_manager(&mut env, &activity)?;
+
+        tracing::info!(
+            device = device_id,
+            mime = mime_type,
+            bytes = document.len(),
+            "Android: printing over USB"
+        );
+
+        // -- Look up the device ------------------------------------------------
+        let device_map = env
+            .call_method(&manager, "getDeviceList", "()Ljava/util/HashMap;", &[])
+            .map_err(|e| jni_err("getDeviceList", e))?
+            .l()
+            .map_err(|e| jni_err("getDeviceList->l", e))?;
+        let j_device_id: JString = env
+            .new_string(device_id)
+            .map_err(|e| jni_err("new_string(device_id)", e))?;
+        let device = env
+            .call_method(
+                &device_map,
+                "get",
+                "(Ljava/lang/Object;)Ljava/lang/Object;",
+                &[JValue::Object(&j_device_id)],
+            )
+            .map_err(|e| jni_err("HashMap.get", e))?
+            .l()
+            .map_err(|e| jni_err("HashMap.get->l", e))?;
+        if device.is_null() {
+            return Err(PresswerkError::Bridge(format!(
+                "USB device {device_id} is no longer attached"
+            )));
+        }
+
+        // -- Permission ----------------------------------------------------------
+        let granted = env
+            .call_method(
+                &manager,
+                "hasPermission",
+                "(Landroid/hardware/usb/UsbDevice;)Z",
+                &[JValue::Object(&device)],
+            )
+            .map_err(|e| jni_err("hasPermission", e))?
+            .z()
+            .map_err(|e| jni_err("hasPermission->z", e))?;
+        if !granted {
+            request_usb_permission(&mut env, &activity, &manager, &device)?;
+            return Err(PresswerkError::PermissionDenied(format!(
+                "USB access to {device_id} has not been granted"
+            )));
+        }
+
+        // -- Find the printer interface and its bulk OUT endpoint ---------------
+        let (interface, endpoint) = printer_bulk_out(&mut env, &device)?;
+
+        let connection = env
+            .call_method(
+                &manager,
+                "openDevice",
+                "(Landroid/hardware/usb/UsbDevice;)Landroid/hardware/usb/UsbDeviceConnection;",
+                &[JValue::Object(&device)],
+            )
+            .map_err(|e| jni_err("openDevice", e))?
+            .l()
+            .map_err(|e| jni_err("openDevice->l", e))?;
+        if connection.is_null() {
+            return Err(PresswerkError::Bridge(format!(
+                "could not open USB device {device_id}"
+            )));
+        }
+
+        let claimed = env
+            .call_method(
+                &connection,
+                "claimInterface",
+                "(Landroid/hardware/usb/UsbInterface;Z)Z",
+                &[JValue::Object(&interface), JValue::Bool(1)],
+            )
+            .map_err(|e| jni_err("claimInterface", e))?
+            .z()
+            .map_err(|e| jni_err("claimInterface->z", e))?;
+
+        let result = if claimed {
+            let sent = bulk_write(&mut env, &connection, &endpoint, document);
+            env.call_method(
+                &connection,
+                "releaseInterface",
+                "(Landroid/hardware/usb/UsbInterface;)Z",
+                &[JValue::Object(&interface)],
+            )
+            .map_err(|e| jni_err("releaseInterface", e))?;
+            sent
+        } else {
+            Err(PresswerkError::Bridge(format!(
+                "USB printer interface on {device_id} is in use"
+            )))
+        };
+
+        env.call_method(&connection, "close", "()V", &[])
+            .map_err(|e| jni_err("UsbDeviceConnection.close", e))?;
+
+        if result.is_ok() {
+            tracing::info!(device = device_id, "Android: USB print sent");
+        }
+        result
+    }
+}
+
+// ---------------------------------------------------------------------------
+// NativeBluetoothPrint — android.bluetooth (classic SPP over RFCOMM)
+// ---------------------------------------------------------------------------
+
+impl NativeBluetoothPrint for AndroidBridge {
+    /// List bonded devices whose Class of Device marks them as printers.
+    ///
+    /// Only already-paired devices are returned; pairing happens in the
+    /// system settings. On Android 12+ this needs `BLUETOOTH_CONNECT`.
+    fn scan_bluetooth_printers(&self) -> Result<Vec<BluetoothPrinterInfo>> {
+        let mut env = jni_env()?;
+        let activity = activity()?;
+        let adapter = bluetooth_adapter(&mut env, &activity)?;
+
+        let bonded = env
+            .call_method(&adapter, "getBondedDevices", "()Ljava/util/Set;", &[])
+            .map_err(|e| java_err(&mut env, "getBondedDevices", e))?
+            .l()
+            .map_err(|e| jni_err("getBondedDevices->l", e))?;
+        if bonded.is_null() {
+            return Ok(Vec::new());
+        }
+        let devices = JObjectArray::from(
+            env.call_method(&bonded, "toArray", "()[Ljava/lang/Object;", &[])
+                .map_err(|e| jni_err("Set.toArray", e))?
+                .l()
+                .map_err(|e| jni_err("Set.toArray->l", e))?,
+        );
+
+        let count = env
+            .get_array_length(&devices)
+            .map_err(|e| jni_err("get_array_length(bonded)", e))?;
+        let mut listing = Vec::with_capacity(count.max(0) as usize);
+        for i in 0..count {
+            listing.push(in_local_frame(&mut env, |env| {
+                let device = env
+                    .get_object_array_element(&devices, i)
+                    .map_err(|e| jni_err("get_object_array_element(bonded)", e))?;
+                read_bonded_device(env, &device)
+            })?);
+        }
+
+        let printers = bluetooth::bonded_printers(&listing);
+        tracing::info!(
+            bonded = listing.len(),
+            count = printers.len(),
+            "Android: Bluetooth printers found"
+        );
+        Ok(printers)
+    }
+
+    /// Open an RFCOMM socket to the printer's SPP service and write
+    /// `document` to it unchanged.
+    ///
+    /// `device_id` is the hardware address from
+    /// [`scan_bluetooth_printers`](Self::scan_bluetooth_printers).
+    fn print_bluetooth(&self, device_id: &str, document: &[u8]) -> Result<()> {
+        let mut env = jni_env()?;
+        let activity = activity()?;
+        let adapter = bluetooth_adapter(&mut env, &activity)?;
+
+        tracing::info!(
+            device = device_id,
+            bytes = document.len(),
+            "Android: printing over Bluetooth"
+        );
+
+        let j_address: JString = env
+            .new_string(device_id)
+            .map_err(|e| jni_err("new_string(device_id)", e))?;
+        let device = env
+            .call_method(
+                &adapter,
+                "getRemoteDevice",
+                "(Ljava/lang/String;)Landroid/bluetooth/BluetoothDevice;",
+                &[JValue::Object(&j_address)],
+            )
+            .map_err(|e| java_err(&mut env, "getRemoteDevice", e))?
+            .l()
+            .map_err(|e| jni_err("getRemoteDevice->l", e))?;
+
+        let j_uuid: JString = env
+            .new_string(bluetooth::SPP_UUID)
+            .map_err(|e| jni_err("new_string(SPP_UUID)", e))?;
+        let uuid = env
+            .call_static_method(
+                "java/util/UUID",
+                "fromString",
+                "(Ljava/lang/String;)Ljava/util/UUID;",
+                &[JValue::Object(&j_uuid)],
+            )
+            .map_err(|e| jni_err("UUID.fromString", e))?
+            .l()
+            .map_err(|e| jni_err("UUID.fromString->l", e))?;
+        let socket = env
+            .call_method(
+                &device,
+                "createRfcommSocketToServiceRecord",
+                "(Ljava/util/UUID;)Landroid/bluetooth/BluetoothSocket;",
+                &[JValue::Object(&uuid)],
+            )
+            .map_err(|e| java_err(&mut env, "createRfcommSocketToServiceRecord", e))?
+            .l()
+            .map_err(|e| jni_err("createRfcommSocketToServiceRecord->l", e))?;
+
+        // An inquiry in progress slows the connection down considerably.
+        env.call_method(&adapter, "cancelDiscovery", "()Z", &[])
+            .map_err(|e| java_err(&mut env, "cancelDiscovery", e))?;
+
+        let sent = env
+            .call_method(&socket, "connect", "()V", &[])
+            .map_err(|e| java_err(&mut env, "BluetoothSocket.connect", e))
+            .and_then(|_| rfcomm_write(&mut env, &socket, document));
+
+        // Closing also tears down a half-open connection after a failure.
+        env.call_method(&socket, "close", "()V", &[])
+            .map_err(|e| java_err(&mut env, "BluetoothSocket.close", e))?;
+
+        if sent.is_ok() {
+            tracing::info!(device = device_id, "Android: Bluetooth print sent");
+        }
+        sent
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Internal helpers
 // ---------------------------------------------------------------------------
@@ -1025,31 +1678,362 @@ fn get_authority(env: &mut JNIEnv<'_>, activity: &JObject<'_>) -> Result<String>
     Ok(format!("{pkg}.fileprovider"))
 }
 
+/// Obtain the `UsbManager` system service.
+fn usb_manager<'a>(env: &mut JNIEnv<'a>, activity: &JObject<'_>) -> Result<JObject<'a>> {
+    let service_name = env
+        .new_string("usb")
+        .map_err(|e| jni_err("new_string(usb)", e))?;
+    let manager = env
+        .call_method(
+            activity,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[JValue::Object(&service_name)],
+        )
+        .map_err(|e| jni_err("getSystemService(usb)", e))?
+        .l()
+        .map_err(|e| jni_err("getSystemService->l", e))?;
+    if manager.is_null() {
+        return Err(PresswerkError::PlatformUnavailable);
+    }
+    Ok(manager)
+}
 
-// ---------------------------------------------------------------------------
-// Stub implementations for connection types not yet wired to Android APIs
-// ---------------------------------------------------------------------------
+/// Call an `int`-returning getter.
+fn int_getter(env: &mut JNIEnv<'_>, object: &JObject<'_>, method: &str) -> Result<i32> {
+    env.call_method(object, method, "()I", &[])
+        .map_err(|e| jni_err(method, e))?
+        .i()
+        .map_err(|e| jni_err(method, e))
+}
 
-impl NativeUsbPrint for AndroidBridge {
-    fn detect_usb_printers(&self) -> Result<Vec<UsbPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+/// Call a nullable `String`-returning getter.
+fn string_getter(
+    env: &mut JNIEnv<'_>,
+    object: &JObject<'_>,
+    method: &str,
+) -> Result<Option<String>> {
+    let value = env
+        .call_method(object, method, "()Ljava/lang/String;", &[])
+        .map_err(|e| jni_err(method, e))?
+        .l()
+        .map_err(|e| jni_err(method, e))?;
+    if value.is_null() {
+        return Ok(None);
     }
+    let value: String = env
+        .get_string(&JString::from(value))
+        .map_err(|e| jni_err(method, e))?
+        .into();
+    Ok(Some(value))
+}
 
-    fn print_usb(&self, _device_id: &str, _document: &[u8], _mime_type: &str) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+/// Read the descriptor fields of a `UsbDevice`.
+fn read_usb_descriptor(env: &mut JNIEnv<'_>, device: &JObject<'_>) -> Result<UsbDeviceDescriptor> {
+    let interface_count = int_getter(env, device, "getInterfaceCount")?;
+    let mut interface_classes = Vec::with_capacity(interface_count.max(0) as usize);
+    for index in 0..interface_count {
+        interface_classes.push(in_local_frame(env, |env| {
+            let interface = usb_interface(env, device, index)?;
+            int_getter(env, &interface, "getInterfaceClass")
+        })?);
     }
+
+    Ok(UsbDeviceDescriptor {
+        device_name: string_getter(env, device, "getDeviceName")?.unwrap_or_default(),
+        vendor_id: int_getter(env, device, "getVendorId")?,
+        product_id: int_getter(env, device, "getProductId")?,
+        device_class: int_getter(env, device, "getDeviceClass")?,
+        interface_classes,
+        manufacturer_name: string_getter(env, device, "getManufacturerName")?,
+        product_name: string_getter(env, device, "getProductName")?,
+    })
 }
 
-impl NativeBluetoothPrint for AndroidBridge {
-    fn scan_bluetooth_printers(&self) -> Result<Vec<BluetoothPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+/// `device.getInterface(index)`.
+fn usb_interface<'a>(
+    env: &mut JNIEnv<'a>,
+    device: &JObject<'_>,
+    index: i32,
+) -> Result<JObject<'a>> {
+    env.call_method(
+        device,
+        "getInterface",
+        "(I)Landroid/hardware/usb/UsbInterface;",
+        &[JValue::Int(index)],
+    )
+    .map_err(|e| jni_err("getInterface", e))?
+    .l()
+    .map_err(|e| jni_err("getInterface->l", e))
+}
+
+/// `Build.VERSION.SDK_INT` of the running device.
+fn sdk_int(env: &mut JNIEnv<'_>) -> Result<i32> {
+    env.get_static_field("android/os/Build$VERSION", "SDK_INT", "I")
+        .map_err(|e| jni_err("Build.VERSION.SDK_INT", e))?
+        .i()
+        .map_err(|e| jni_err("SDK_INT->i", e))
+}
+
+/// Find the first printer-class interface with a bulk OUT endpoint.
+fn printer_bulk_out<'a>(
+    env: &mut JNIEnv<'a>,
+    device: &JObject<'_>,
+) -> Result<(JObject<'a>, JObject<'a>)> {
+    let interface_count = int_getter(env, device, "getInterfaceCount")?;
+    for index in 0..interface_count {
+        let interface = usb_interface(env, device, index)?;
+        if int_getter(env, &interface, "getInterfaceClass")? != USB_CLASS_PRINTER {
+            env.delete_local_ref(interface)
+                .map_err(|e| jni_err("delete_local_ref(interface)", e))?;
+            continue;
+        }
+
+        let endpoint_count = int_getter(env, &interface, "getEndpointCount")?;
+        for e_index in 0..endpoint_count {
+            let endpoint = env
+                .call_method(
+                    &interface,
+                    "getEndpoint",
+                    "(I)Landroid/hardware/usb/UsbEndpoint;",
+                    &[JValue::Int(e_index)],
+                )
+                .map_err(|e| jni_err("getEndpoint", e))?
+                .l()
+                .map_err(|e| jni_err("getEndpoint->l", e))?;
+            if int_getter(env, &endpoint, "getType")? == USB_ENDPOINT_XFER_BULK
+                && int_getter(env, &endpoint, "getDirection")? == USB_DIR_OUT
+            {
+                return Ok((interface, endpoint));
+            }
+            env.delete_local_ref(endpoint)
+                .map_err(|e| jni_err("delete_local_ref(endpoint)", e))?;
+        }
+        env.delete_local_ref(interface)
+            .map_err(|e| jni_err("delete_local_ref(interface)", e))?;
     }
 
-    fn print_bluetooth(&self, _device_id: &str, _document: &[u8]) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+    Err(PresswerkError::Bridge(
+        "USB device has no printer interface with a bulk OUT endpoint".into(),
+    ))
+}
+
+/// Ask the user for access to `device`. The answer arrives asynchronously as
+/// an [`ACTION_USB_PERMISSION`] broadcast.
+fn request_usb_permission(
+    env: &mut JNIEnv<'_>,
+    activity: &JObject<'_>,
+    manager: &JObject<'_>,
+    device: &JObject<'_>,
+) -> Result<()> {
+    let j_action: JString = env
+        .new_string(ACTION_USB_PERMISSION)
+        .map_err(|e| jni_err("new_string(ACTION_USB_PERMISSION)", e))?;
+    let intent: JObject = env
+        .new_object(
+            "android/content/Intent",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(&j_action)],
+        )
+        .map_err(|e| jni_err("new Intent(usb permission)", e))?;
+
+    // UsbManager fills in EXTRA_DEVICE and EXTRA_PERMISSION_GRANTED, so the
+    // PendingIntent must be mutable, and from Android 14 a mutable
+    // PendingIntent must not wrap an implicit intent: address the broadcast
+    // to this app only.
+    let package: JObject = env
+        .call_method(activity, "getPackageName", "()Ljava/lang/String;", &[])
+        .map_err(|e| jni_err("getPackageName(usb permission)", e))?
+        .l()
+        .map_err(|e| jni_err("getPackageName->l(usb permission)", e))?;
+    env.call_method(
+        &intent,
+        "setPackage",
+        "(Ljava/lang/String;)Landroid/content/Intent;",
+        &[JValue::Object(&package)],
+    )
+    .map_err(|e| jni_err("setPackage(usb permission)", e))?;
+
+    let pending = env
+        .call_static_method(
+            "android/app/PendingIntent",
+            "getBroadcast",
+            "(Landroid/content/Context;ILandroid/content/Intent;I)Landroid/app/PendingIntent;",
+            &[
+                JValue::Object(activity),
+                JValue::Int(REQUEST_USB_PERMISSION),
+                JValue::Object(&intent),
+                JValue::Int(PENDING_INTENT_FLAG_MUTABLE),
+            ],
+        )
+        .map_err(|e| jni_err("PendingIntent.getBroadcast", e))?
+        .l()
+        .map_err(|e| jni_err("getBroadcast->l", e))?;
+
+    env.call_method(
+        manager,
+        "requestPermission",
+        "(Landroid/hardware/usb/UsbDevice;Landroid/app/PendingIntent;)V",
+        &[JValue::Object(device), JValue::Object(&pending)],
+    )
+    .map_err(|e| jni_err("requestPermission", e))?;
+
+    tracing::info!("Android: USB permission requested");
+    Ok(())
+}
+
+/// Send `data` over a bulk endpoint in [`USB_CHUNK_SIZE`] pieces.
+fn bulk_write(
+    env: &mut JNIEnv<'_>,
+    connection: &JObject<'_>,
+    endpoint: &JObject<'_>,
+    data: &[u8],
+) -> Result<()> {
+    for chunk in data.chunks(USB_CHUNK_SIZE) {
+        let buffer = env
+            .byte_array_from_slice(chunk)
+            .map_err(|e| jni_err("byte_array_from_slice", e))?;
+        let mut offset = 0;
+        while offset < chunk.len() {
+            let remaining = &chunk[offset..];
+            // bulkTransfer(endpoint, buffer, offset, length, timeout) — API 18+
+            let written = env
+                .call_method(
+                    connection,
+                    "bulkTransfer",
+                    "(Landroid/hardware/usb/UsbEndpoint;[BIII)I",
+                    &[
+                        JValue::Object(endpoint),
+                        JValue::Object(&buffer),
+                        JValue::Int(offset as i32),
+                        JValue::Int(remaining.len() as i32),
+                        JValue::Int(USB_TRANSFER_TIMEOUT_MS),
+                    ],
+                )
+                .map_err(|e| jni_err("bulkTransfer", e))?
+                .i()
+                .map_err(|e| jni_err("bulkTransfer->i", e))?;
+            if written <= 0 {
+                return Err(PresswerkError::Bridge(format!(
+                    "USB bulk transfer failed ({written})"
+                )));
+            }
+            offset += written as usize;
+        }
+        env.delete_local_ref(buffer)
+            .map_err(|e| jni_err("delete_local_ref(buffer)", e))?;
+    }
+    Ok(())
+}
+
+/// Like [`jni_err`], but first clears the Java exception a failed call
+/// leaves pending, so later JNI calls on this thread still work.
+fn java_err(env: &mut JNIEnv<'_>, context: &str, e: jni::errors::Error) -> PresswerkError {
+    if env.exception_check().unwrap_or(false) {
+        let _ = env.exception_clear();
+    }
+    jni_err(context, e)
+}
+
+/// Obtain the default `BluetoothAdapter`, or `PlatformUnavailable` if the
+/// device has no Bluetooth or it is switched off.
+fn bluetooth_adapter<'a>(env: &mut JNIEnv<'a>, activity: &JObject<'_>) -> Result<JObject<'a>> {
+    let service_name = env
+        .new_string("bluetooth")
+        .map_err(|e| jni_err("new_string(bluetooth)", e))?;
+    let manager = env
+        .call_method(
+            activity,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[JValue::Object(&service_name)],
+        )
+        .map_err(|e| jni_err("getSystemService(bluetooth)", e))?
+        .l()
+        .map_err(|e| jni_err("getSystemService->l", e))?;
+    if manager.is_null() {
+        return Err(PresswerkError::PlatformUnavailable);
+    }
+
+    let adapter = env
+        .call_method(
+            &manager,
+            "getAdapter",
+            "()Landroid/bluetooth/BluetoothAdapter;",
+            &[],
+        )
+        .map_err(|e| jni_err("getAdapter", e))?
+        .l()
+        .map_err(|e| jni_err("getAdapter->l", e))?;
+    if adapter.is_null() {
+        return Err(PresswerkError::PlatformUnavailable);
+    }
+
+    let enabled = env
+        .call_method(&adapter, "isEnabled", "()Z", &[])
+        .map_err(|e| java_err(env, "isEnabled", e))?
+        .z()
+        .map_err(|e| jni_err("isEnabled->z", e))?;
+    if !enabled {
+        tracing::debug!("Android: Bluetooth is off");
+        return Err(PresswerkError::PlatformUnavailable);
     }
+    Ok(adapter)
 }
 
+/// Read the fields of a bonded `BluetoothDevice`.
+fn read_bonded_device(env: &mut JNIEnv<'_>, device: &JObject<'_>) -> Result<BondedDevice> {
+    let class = env
+        .call_method(
+            device,
+            "getBluetoothClass",
+            "()Landroid/bluetooth/BluetoothClass;",
+            &[],
+        )
+        .map_err(|e| java_err(env, "getBluetoothClass", e))?
+        .l()
+        .map_err(|e| jni_err("getBluetoothClass->l", e))?;
+    let device_class = if class.is_null() {
+        0
+    } else {
+        int_getter(env, &class, "getDeviceClass")?
+    };
+
+    Ok(BondedDevice {
+        address: string_getter(env, device, "getAddress")?.unwrap_or_default(),
+        name: string_getter(env, device, "getName")?,
+        device_class,
+        device_type: int_getter(env, device, "getType")?,
+    })
+}
+
+/// Write `data` to a connected socket's output stream in
+/// [`BLUETOOTH_CHUNK_SIZE`] pieces.
+fn rfcomm_write(env: &mut JNIEnv<'_>, socket: &JObject<'_>, data: &[u8]) -> Result<()> {
+    let stream = env
+        .call_method(socket, "getOutputStream", "()Ljava/io/OutputStream;", &[])
+        .map_err(|e| java_err(env, "getOutputStream", e))?
+        .l()
+        .map_err(|e| jni_err("getOutputStream->l", e))?;
+
+    for chunk in data.chunks(BLUETOOTH_CHUNK_SIZE) {
+        let buffer = env
+            .byte_array_from_slice(chunk)
+            .map_err(|e| jni_err("byte_array_from_slice", e))?;
+        env.call_method(&stream, "write", "([B)V", &[JValue::Object(&buffer)])
+            .map_err(|e| java_err(env, "OutputStream.write", e))?;
+        env.delete_local_ref(buffer)
+            .map_err(|e| jni_err("delete_local_ref(buffer)", e))?;
+    }
+    env.call_method(&stream, "flush", "()V", &[])
+        .map_err(|e| java_err(env, "OutputStream.flush", e))?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Stub implementations for connection types not yet wired to Android APIs
+// ---------------------------------------------------------------------------
+
 impl NativeNfcPrint for AndroidBridge {
     fn read_nfc_printer_tag(&self) -> Result<Option<NfcPrinterInfo>> {
         Err(PresswerkError::PlatformUnavailable)
@@ -1057,8 +2041,65 @@ fn read_nfc_printer_tag(&self) -> Result<Option<NfcPrinterInfo>> {
 }
 
 impl NativeConnectivity for AndroidBridge {
+    /// Read the SSID from `WifiManager.getConnectionInfo()`.
+    ///
+    /// Android only reveals the SSID to apps holding a location permission;
+    /// without one (or when not on Wi-Fi) this returns `Ok(None)`.
     fn wifi_ssid(&self) -> Result<Option<String>> {
-        Err(PresswerkError::PlatformUnavailable)
+        let mut env = jni_env()?;
+        let activity = activity()?;
+
+        let j_permission: JString = env
+            .new_string("android.permission.ACCESS_FINE_LOCATION")
+            .map_err(|e| jni_err("new_string(permission)", e))?;
+        let permission_state = env
+            .call_method(
+                &activity,
+                "checkSelfPermission",
+                "(Ljava/lang/String;)I",
+                &[JValue::Object(&j_permission)],
+            )
+            .map_err(|e| jni_err("checkSelfPermission", e))?
+            .i()
+            .map_err(|e| jni_err("checkSelfPermission->i", e))?;
+        if permission_state != 0 {
+            // PERMISSION_DENIED
+            tracing::debug!("Android: no location permission, SSID unavailable");
+            return Ok(None);
+        }
+
+        let service_name = env
+            .new_string("wifi")
+            .map_err(|e| jni_err("new_string(wifi)", e))?;
+        let manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[JValue::Object(&service_name)],
+            )
+            .map_err(|e| jni_err("getSystemService(wifi)", e))?
+            .l()
+            .map_err(|e| jni_err("getSystemService->l", e))?;
+        if manager.is_null() {
+            return Err(PresswerkError::PlatformUnavailable);
+        }
+
+        let info = env
+            .call_method(
+                &manager,
+                "getConnectionInfo",
+                "()Landroid/net/wifi/WifiInfo;",
+                &[],
+            )
+            .map_err(|e| jni_err("getConnectionInfo", e))?
+            .l()
+            .map_err(|e| jni_err("getConnectionInfo->l", e))?;
+        if info.is_null() {
+            return Ok(None);
+        }
+
+        Ok(string_getter(&mut env, &info, "getSSID")?.and_then(|raw| wifi::normalize_ssid(&raw)))
     }
 
     fn supports_wifi_direct(&self) -> bool {
@@ -1068,6 +2109,32 @@ fn supports_wifi_direct(&self) -> bool {
     fn discover_wifi_direct_printers(&self) -> Result<Vec<WifiDirectPrinterInfo>> {
         Err(PresswerkError::PlatformUnavailable)
     }
+
+    /// Asks `ConnectivityManager.isActiveNetworkMetered()`, which needs the
+    /// `ACCESS_NETWORK_STATE` permission.
+    fn is_metered(&self) -> Result<bool> {
+        let mut env = jni_env()?;
+        let activity = activity()?;
+
+        let service_name = env
+            .new_string("connectivity")
+            .map_err(|e| jni_err("new_string(connectivity)", e))?;
+        let manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[JValue::Object(&service_name)],
+            )
+            .map_err(|e| jni_err("getSystemService(connectivity)", e))?
+            .l()
+            .map_err(|e| jni_err("getSystemService->l", e))?;
+
+        env.call_method(&manager, "isActiveNetworkMetered", "()Z", &[])
+            .map_err(|e| jni_err("isActiveNetworkMetered", e))?
+            .z()
+            .map_err(|e| jni_err("isActiveNetworkMetered->z", e))
+    }
 }
 
 impl NativeFireWirePrint for AndroidBridge {
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// JNI entry points for `org.presswerk.PresswerkResultReceiver`.
+//
+// The host Activity forwards every `onActivityResult` and
+// `onRequestPermissionsResult` to Presswerk; results for request codes
+// Presswerk did not issue are ignored.  The Java side is one class and one
+// line per callback in the Activity:
+//
+//     package org.presswerk;
+//
+//     public final class PresswerkResultReceiver {
+//         public static native void onActivityResult(
+//                 int requestCode, int resultCode, android.content.Intent data);
+//         public static native void onRequestPermissionsResult(
+//                 int requestCode, String[] permissions, int[] grantResults);
+//         public static native void onShareTargetChosen(String packageName);
+//     }
+//
+//     public final class PresswerkShareReceiver extends BroadcastReceiver {
+//         @Override
+//         public void onReceive(Context context, Intent intent) {
+//             ComponentName chosen =
+//                     intent.getParcelableExtra(Intent.EXTRA_CHOSEN_COMPONENT);
+//             PresswerkResultReceiver.onShareTargetChosen(
+//                     chosen == null ? null : chosen.getPackageName());
+//         }
+//     }
+//
+//     // in the Activity:
+//     @Override
+//     protected void onActivityResult(int requestCode, int resultCode, Intent data) {
+//         super.onActivityResult(requestCode, resultCode, data);
+//         PresswerkResultReceiver.onActivityResult(requestCode, resultCode, data);
+//     }
+//
+//     @Override
+//     public void onRequestPermissionsResult(
+//             int requestCode, String[] permissions, int[] grantResults) {
+//         super.onRequestPermissionsResult(requestCode, permissions, grantResults);
+//         PresswerkResultReceiver.onRequestPermissionsResult(
+//                 requestCode, permissions, grantResults);
+//     }
+//
+// The permissions must also be declared in `AndroidManifest.xml`
+// (`CAMERA`, `BLUETOOTH_CONNECT`, `INTERNET`) or the prompt is never shown
+// and the request reads as denied.
+//
+// `PresswerkShareReceiver` must be declared as a non-exported `<receiver>` in
+// the manifest too, or the share chooser's choice is never reported and
+// `share_file_with_result` can only answer `ShareOutcome::Unknown`.
+//
+// The result is read here (the captured photo from the cache directory, or
+// the picked document's URI from `data`) and handed to the waiting bridge
+// call through `crate::result_receiver`.
+
+use jni::JNIEnv;
+use jni::objects::{JClass, JIntArray, JObject, JObjectArray, JString};
+use jni::sys::jint;
+
+use presswerk_core::error::Result;
+
+use super::{
+    CAPTURE_FILENAME, PERMISSION_GRANTED, REQUEST_IMAGE_CAPTURE, REQUEST_PERMISSION,
+    REQUEST_PICK_FILE, REQUEST_SHARE, REQUEST_SHARE_TARGET, activity, jni_err,
+};
+use crate::result_receiver::{self, ActivityResult};
+
+/// `Activity.RESULT_OK`.
+const RESULT_OK: jint = -1;
+
+/// `PresswerkResultReceiver.onActivityResult(int, int, Intent)`.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_org_presswerk_PresswerkResultReceiver_onActivityResult<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    request_code: jint,
+    result_code: jint,
+    data: JObject<'local>,
+) {
+    if ![REQUEST_IMAGE_CAPTURE, REQUEST_PICK_FILE, REQUEST_SHARE].contains(&request_code) {
+        return;
+    }
+
+    // The chooser's own result only says it closed: the chosen target comes
+    // separately through `onShareTargetChosen`, before or after this.
+    let result = if result_code != RESULT_OK {
+        ActivityResult::Cancelled
+    } else if request_code == REQUEST_SHARE {
+        ActivityResult::Shared { target: None }
+    } else if request_code == REQUEST_IMAGE_CAPTURE {
+        read_capture(&mut env)
+            .map(ActivityResult::Image)
+            .unwrap_or_else(|e| ActivityResult::Failed(e.to_string()))
+    } else {
+        match picked_uri(&mut env, &data) {
+            Ok(Some(uri)) => ActivityResult::Document(uri),
+            Ok(None) => ActivityResult::Cancelled,
+            Err(e) => ActivityResult::Failed(e.to_string()),
+        }
+    };
+
+    tracing::info!(
+        request_code,
+        result_code,
+        "Android: activity result received"
+    );
+    result_receiver::deliver(request_code, result);
+}
+
+/// `PresswerkResultReceiver.onRequestPermissionsResult(int, String[], int[])`.
+///
+/// Presswerk asks for one permission at a time, so the first grant result is
+/// the answer.  An empty array means the prompt was interrupted.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_org_presswerk_PresswerkResultReceiver_onRequestPermissionsResult<
+    'local,
+>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    request_code: jint,
+    _permissions: JObjectArray<'local>,
+    grant_results: JIntArray<'local>,
+) {
+    if request_code != REQUEST_PERMISSION {
+        return;
+    }
+
+    let result = match first_grant_result(&mut env, &grant_results) {
+        Ok(Some(grant)) => ActivityResult::Permission {
+            granted: grant == PERMISSION_GRANTED,
+        },
+        Ok(None) => ActivityResult::Cancelled,
+        Err(e) => ActivityResult::Failed(e.to_string()),
+    };
+
+    tracing::info!(request_code, ?result, "Android: permission result received");
+    result_receiver::deliver(request_code, result);
+}
+
+/// `PresswerkResultReceiver.onShareTargetChosen(String)`.
+///
+/// Called by `PresswerkShareReceiver` with the package of the app the user
+/// picked in the share chooser (`null` if Android did not say).
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_org_presswerk_PresswerkResultReceiver_onShareTargetChosen<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    package_name: JString<'local>,
+) {
+    let result = if package_name.is_null() {
+        ActivityResult::Shared { target: None }
+    } else {
+        match env.get_string(&package_name) {
+            Ok(package) => ActivityResult::Shared {
+                target: Some(package.into()),
+            },
+            Err(e) => ActivityResult::Failed(jni_err("get_string(packageName)", e).to_string()),
+        }
+    };
+
+    tracing::info!(?result, "Android: share target chosen");
+    result_receiver::deliver(REQUEST_SHARE_TARGET, result);
+}
+
+/// `grantResults[0]`, or `None` if the array is empty.
+fn first_grant_result(env: &mut JNIEnv<'_>, grant_results: &JIntArray<'_>) -> Result<Option<jint>> {
+    if grant_results.is_null() {
+        return Ok(None);
+    }
+    let len = env
+        .get_array_length(grant_results)
+        .map_err(|e| jni_err("grantResults.length", e))?;
+    if len == 0 {
+        return Ok(None);
+    }
+    let mut first = [0 as jint];
+    env.get_int_array_region(grant_results, 0, &mut first)
+        .map_err(|e| jni_err("grantResults[0]", e))?;
+    Ok(Some(first[0]))
+}
+
+/// Read the photo the camera wrote to `{cacheDir}/presswerk_capture.jpg`.
+fn read_capture(env: &mut JNIEnv<'_>) -> Result<Vec<u8>> {
+    let activity = activity()?;
+    let cache_dir = env
+        .call_method(&activity, "getCacheDir", "()Ljava/io/File;", &[])
+        .map_err(|e| jni_err("getCacheDir", e))?
+        .l()
+        .map_err(|e| jni_err("getCacheDir->l", e))?;
+    let path = env
+        .call_method(&cache_dir, "getAbsolutePath", "()Ljava/lang/String;", &[])
+        .map_err(|e| jni_err("getAbsolutePath", e))?
+        .l()
+        .map_err(|e| jni_err("getAbsolutePath->l", e))?;
+    let path: String = env
+        .get_string(&JString::from(path))
+        .map_err(|e| jni_err("get_string(cacheDir)", e))?
+        .into();
+
+    let capture = std::path::Path::new(&path).join(CAPTURE_FILENAME);
+    let bytes = std::fs::read(&capture)?;
+    let _ = std::fs::remove_file(&capture);
+    Ok(bytes)
+}
+
+/// `data.getData().toString()`, or `None` if the Intent carries no URI.
+fn picked_uri(env: &mut JNIEnv<'_>, data: &JObject<'_>) -> Result<Option<String>> {
+    if data.is_null() {
+        return Ok(None);
+    }
+    let uri = env
+        .call_method(data, "getData", "()Landroid/net/Uri;", &[])
+        .map_err(|e| jni_err("Intent.getData", e))?
+        .l()
+        .map_err(|e| jni_err("Intent.getData->l", e))?;
+    if uri.is_null() {
+        return Ok(None);
+    }
+    let uri_string = env
+        .call_method(&uri, "toString", "()Ljava/lang/String;", &[])
+        .map_err(|e| jni_err("Uri.toString", e))?
+        .l()
+        .map_err(|e| jni_err("Uri.toString->l", e))?;
+    let uri_string: String = env
+        .get_string(&JString::from(uri_string))
+        .map_err(|e| jni_err("get_string(uri)", e))?
+        .into();
+    Ok(Some(uri_string))
+}
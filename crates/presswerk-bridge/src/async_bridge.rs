@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Awaitable versions of the bridge calls that wait on the user.
+//
+// `capture_image`, `pick_file` and friends block until the user is done with
+// the camera, picker or print dialog — on Android literally parked on a
+// channel.  Called from the Dioxus async UI they would stall the executor,
+// so these wrappers move the blocking call onto tokio's blocking pool and
+// await it there.  UIKit only works on the main thread, so on iOS the calls
+// that present UI are queued onto the main thread instead.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use presswerk_core::error::{PresswerkError, Result};
+
+use crate::traits::{Permission, PermissionState, PlatformBridge, ShareOutcome};
+
+/// Async counterparts of the blocking [`PlatformBridge`] calls.
+///
+/// Implemented for every shared bridge (`Arc<B>`), by running the sync
+/// method on `spawn_blocking`, or on the main thread for the calls that
+/// present UI on iOS.
+pub trait PlatformBridgeAsync {
+    /// See [`NativeCamera::capture_image`](crate::traits::NativeCamera::capture_image).
+    fn capture_image_async(&self) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send;
+
+    /// See [`NativeFilePicker::pick_file`](crate::traits::NativeFilePicker::pick_file).
+    fn pick_file_async(
+        &self,
+        mime_types: &[&str],
+    ) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// See [`NativeFilePicker::read_picked_file`](crate::traits::NativeFilePicker::read_picked_file).
+    fn read_picked_file_async(&self, path: &str) -> impl Future<Output = Result<Vec<u8>>> + Send;
+
+    /// See [`NativePrint::show_print_dialog`](crate::traits::NativePrint::show_print_dialog).
+    fn show_print_dialog_async(
+        &self,
+        document: Vec<u8>,
+        mime_type: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// See [`NativeShare::share_file_with_result`](crate::traits::NativeShare::share_file_with_result).
+    fn share_file_with_result_async(
+        &self,
+        path: &str,
+        mime_type: &str,
+    ) -> impl Future<Output = Result<ShareOutcome>> + Send;
+
+    /// See [`PlatformBridge::request_permission`]; resolves to the user's
+    /// answer.
+    fn request_permission_async(
+        &self,
+        permission: Permission,
+    ) -> impl Future<Output = PermissionState> + Send;
+}
+
+impl<B> PlatformBridgeAsync for Arc<B>
+where
+    B: PlatformBridge + Send + Sync + ?Sized + 'static,
+{
+    fn capture_image_async(&self) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send {
+        let bridge = Arc::clone(self);
+        run_ui(move || bridge.capture_image())
+    }
+
+    fn pick_file_async(
+        &self,
+        mime_types: &[&str],
+    ) -> impl Future<Output = Result<Option<String>>> + Send {
+        let bridge = Arc::clone(self);
+        let mime_types: Vec<String> = mime_types.iter().map(|m| m.to_string()).collect();
+        run_ui(move || {
+            let mime_types: Vec<&str> = mime_types.iter().map(String::as_str).collect();
+            bridge.pick_file(&mime_types)
+        })
+    }
+
+    fn read_picked_file_async(&self, path: &str) -> impl Future<Output = Result<Vec<u8>>> + Send {
+        let bridge = Arc::clone(self);
+        let path = path.to_string();
+        run_blocking(move || bridge.read_picked_file(&path))
+    }
+
+    fn show_print_dialog_async(
+        &self,
+        document: Vec<u8>,
+        mime_type: &str,
+    ) -> impl Future<Output = Result<()>> + Send {
+        let bridge = Arc::clone(self);
+        let mime_type = mime_type.to_string();
+        run_ui(move || bridge.show_print_dialog(&document, &mime_type))
+    }
+
+    fn share_file_with_result_async(
+        &self,
+        path: &str,
+        mime_type: &str,
+    ) -> impl Future<Output = Result<ShareOutcome>> + Send {
+        let bridge = Arc::clone(self);
+        let path = path.to_string();
+        let mime_type = mime_type.to_string();
+        run_ui(move || bridge.share_file_with_result(&path, &mime_type))
+    }
+
+    fn request_permission_async(
+        &self,
+        permission: Permission,
+    ) -> impl Future<Output = PermissionState> + Send {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.request_permission(
+            permission,
+            Box::new(move |state| {
+                let _ = sender.send(state);
+            }),
+        );
+        // A bridge that dropped the callback unanswered broke its contract;
+        // treat it like a prompt that could not be shown.
+        async move { receiver.await.unwrap_or(PermissionState::Unknown) }
+    }
+}
+
+/// Run `call`, which presents UI, where the platform allows it: on the
+/// main thread on iOS, and on the blocking pool elsewhere.
+async fn run_ui<T, F>(call: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    #[cfg(target_os = "ios")]
+    {
+        crate::ios::run_on_main_thread(call).await
+    }
+    #[cfg(not(target_os = "ios"))]
+    {
+        run_blocking(call).await
+    }
+}
+
+/// Run `call` on the blocking pool and await its result.
+async fn run_blocking<T, F>(call: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| PresswerkError::Bridge(format!("bridge call did not complete: {e}")))?
+}
+
+// The stub only exists off-device.
+#[cfg(all(test, not(any(target_os = "ios", target_os = "android"))))]
+mod tests {
+    use super::*;
+    use crate::stub::{BridgeCall, StubBridge};
+
+    #[tokio::test]
+    async fn stub_answers_through_the_async_path() {
+        let stub = StubBridge::new()
+            .with_captured_image(Some(vec![0xFF, 0xD8, 0xFF]))
+            .with_picked_file(Some("/tmp/scan.pdf".into()));
+        let bridge: Arc<dyn PlatformBridge + Send + Sync> = Arc::new(stub.clone());
+
+        assert_eq!(
+            bridge.capture_image_async().await.unwrap(),
+            Some(vec![0xFF, 0xD8, 0xFF])
+        );
+        assert_eq!(
+            bridge
+                .pick_file_async(&["application/pdf"])
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("/tmp/scan.pdf")
+        );
+        assert!(
+            bridge
+                .read_picked_file_async("/tmp/scan.pdf")
+                .await
+                .is_err()
+        );
+
+        assert_eq!(
+            bridge
+                .share_file_with_result_async("/tmp/scan.pdf", "application/pdf")
+                .await
+                .unwrap(),
+            ShareOutcome::Cancelled
+        );
+
+        assert_eq!(
+            bridge.request_permission_async(Permission::Camera).await,
+            PermissionState::Granted
+        );
+
+        assert_eq!(stub.call_count(&BridgeCall::CaptureImage), 1);
+        assert_eq!(
+            stub.call_count(&BridgeCall::PickFile {
+                mime_types: vec!["application/pdf".into()],
+            }),
+            1
+        );
+    }
+}
@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Platform-neutral Bluetooth helpers shared by the native bridges.
+//
+// As with USB, the platform code only reads the raw fields of each bonded
+// device (over JNI on Android); which of them are printers and what to call
+// them is decided here so it can be tested on any host.
+
+use crate::traits::BluetoothPrinterInfo;
+
+/// Serial Port Profile UUID, spoken by most portable receipt and label
+/// printers.
+pub const SPP_UUID: &str = "00001101-0000-1000-8000-00805F9B34FB";
+
+/// Mask for the major device class in a Class of Device value.
+const MAJOR_CLASS_MASK: i32 = 0x1F00;
+
+/// Major device class "Imaging" (`BluetoothClass.Device.Major.IMAGING`).
+pub const MAJOR_CLASS_IMAGING: i32 = 0x0600;
+
+/// Imaging minor-class bit marking a printer (Bluetooth Assigned Numbers,
+/// Class of Device).
+pub const IMAGING_PRINTER_BIT: i32 = 0x0080;
+
+/// `BluetoothDevice.DEVICE_TYPE_LE`.
+pub const DEVICE_TYPE_LE: i32 = 2;
+
+/// Raw fields read from a bonded Bluetooth device.
+#[derive(Debug, Clone, Default)]
+pub struct BondedDevice {
+    /// Hardware address, e.g. `00:11:22:AA:BB:CC`.
+    pub address: String,
+    /// Friendly name; `None` if the device never reported one.
+    pub name: Option<String>,
+    /// Class of Device (`BluetoothClass.getDeviceClass()`): major and minor
+    /// class, without the service bits.
+    pub device_class: i32,
+    /// `BluetoothDevice.getType()`: classic, LE, dual or unknown.
+    pub device_type: i32,
+}
+
+impl BondedDevice {
+    /// Whether the device announces itself as an imaging-class printer.
+    pub fn is_printer(&self) -> bool {
+        self.device_class & MAJOR_CLASS_MASK == MAJOR_CLASS_IMAGING
+            && self.device_class & IMAGING_PRINTER_BIT != 0
+    }
+}
+
+/// Turn a bonded device into [`BluetoothPrinterInfo`], or `None` if it is
+/// not a printer.
+///
+/// The `device_id` is the hardware address; the name falls back to it when
+/// the device has none.
+pub fn printer_info(device: &BondedDevice) -> Option<BluetoothPrinterInfo> {
+    if !device.is_printer() {
+        return None;
+    }
+
+    let name = device
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map_or_else(
+            || format!("Bluetooth printer {}", device.address),
+            String::from,
+        );
+
+    Some(BluetoothPrinterInfo {
+        device_id: device.address.clone(),
+        name,
+        is_ble: device.device_type == DEVICE_TYPE_LE,
+    })
+}
+
+/// The printers among a list of bonded devices, in listing order.
+pub fn bonded_printers(devices: &[BondedDevice]) -> Vec<BluetoothPrinterInfo> {
+    devices.iter().filter_map(printer_info).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bonded_listing_keeps_only_printers() {
+        let devices = [
+            BondedDevice {
+                address: "00:11:22:AA:BB:CC".into(),
+                name: Some(" PT-210 ".into()),
+                device_class: 0x0680, // imaging, printer
+                device_type: 1,
+            },
+            BondedDevice {
+                address: "00:11:22:AA:BB:CD".into(),
+                name: Some("Headphones".into()),
+                device_class: 0x0418, // audio/video, headphones
+                device_type: 3,
+            },
+            BondedDevice {
+                address: "00:11:22:AA:BB:CE".into(),
+                name: Some("Scanner".into()),
+                device_class: 0x0640, // imaging, scanner only
+                device_type: 1,
+            },
+            BondedDevice {
+                address: "00:11:22:AA:BB:CF".into(),
+                name: None,
+                device_class: 0x06A0, // imaging, scanner and printer
+                device_type: DEVICE_TYPE_LE,
+            },
+        ];
+
+        let printers = bonded_printers(&devices);
+        assert_eq!(printers.len(), 2);
+        assert_eq!(printers[0].device_id, "00:11:22:AA:BB:CC");
+        assert_eq!(printers[0].name, "PT-210");
+        assert!(!printers[0].is_ble);
+        assert_eq!(printers[1].name, "Bluetooth printer 00:11:22:AA:BB:CF");
+        assert!(printers[1].is_ble);
+    }
+}
@@ -9,7 +9,10 @@
 // This module is cfg-gated to `target_os = "ios"` and will not compile on other
 // platforms.  All UIKit interactions require the main thread; methods that
 // present view controllers will return `PresswerkError::Bridge` if called
-// off-main.
+// off-main.  The awaitable calls in `async_bridge` get there through
+// `run_on_main_thread`, and the presenting methods wait for the user with
+// `wait_on_main`, which keeps the main run loop turning so UIKit can deliver
+// the answer to the thread that is waiting for it.
 //
 // ## ABI Safety (src/abi/Bridge.idr)
 //
@@ -32,10 +35,13 @@
 
 use std::cell::RefCell;
 use std::ffi::c_void;
+use std::future::Future;
 use std::sync::mpsc;
+use std::time::Duration;
 
+use block2::RcBlock;
 use objc2::rc::Retained;
-use objc2::runtime::{AnyObject, Bool, NSObject, ProtocolObject};
+use objc2::runtime::{AnyClass, AnyObject, Bool, NSObject, ProtocolObject};
 use objc2::{AllocAnyThread, MainThreadMarker, define_class, msg_send};
 use objc2_foundation::{NSArray, NSData, NSDictionary, NSString, NSURL};
 use objc2_ui_kit::{
@@ -110,6 +116,97 @@ fn UIImageJPEGRepresentation(
     ) -> *mut AnyObject;
 }
 
+// ---------------------------------------------------------------------------
+// Main-thread dispatch -- libdispatch / CoreFoundation
+// ---------------------------------------------------------------------------
+
+/// How long each turn of the run loop in [`wait_on_main`] lasts.
+const RUN_LOOP_SLICE: Duration = Duration::from_millis(50);
+
+unsafe extern "C" {
+    /// The main dispatch queue; `dispatch_get_main_queue()` is a macro
+    /// returning its address.
+    static _dispatch_main_q: c_void;
+
+    fn dispatch_async_f(
+        queue: *const c_void,
+        context: *mut c_void,
+        work: extern "C" fn(*mut c_void),
+    );
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+unsafe extern "C" {
+    static kCFRunLoopDefaultMode: *const c_void;
+
+    fn CFRunLoopRunInMode(mode: *const c_void, seconds: f64, return_after_source: u8) -> i32;
+}
+
+/// Run `call` on the main thread and await its result from any thread.
+///
+/// The call is queued on the main dispatch queue, so it runs on the next
+/// turn of the main run loop; awaiting from the main thread itself is fine
+/// as the await gives the run loop back.
+pub(crate) fn run_on_main_thread<T, F>(call: F) -> impl Future<Output = Result<T>> + Send
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    type Work = Box<dyn FnOnce() + Send>;
+
+    extern "C" fn trampoline(context: *mut c_void) {
+        // SAFETY: `context` is the `Box<Work>` leaked below, handed to us
+        // exactly once by libdispatch.
+        let work = unsafe { Box::from_raw(context as *mut Work) };
+        work();
+    }
+
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let work: Box<Work> = Box::new(Box::new(move || {
+        let _ = sender.send(call());
+    }));
+    // SAFETY: `_dispatch_main_q` is the main queue object, which lives for
+    // the whole process; ownership of `work` passes to `trampoline`.
+    unsafe {
+        dispatch_async_f(
+            &_dispatch_main_q as *const c_void,
+            Box::into_raw(work) as *mut c_void,
+            trampoline,
+        );
+    }
+
+    async move {
+        receiver.await.map_err(|e| {
+            PresswerkError::Bridge(format!("main-thread call did not complete: {e}"))
+        })?
+    }
+}
+
+/// Wait on the main thread for the answer a delegate or completion handler
+/// sends on `rx`, running the main run loop meanwhile.
+///
+/// UIKit calls delegates on the main thread, so a plain `recv` there would
+/// never see the answer; each slice of the run loop lets UIKit deliver it.
+fn wait_on_main<T>(_mtm: MainThreadMarker, rx: &mpsc::Receiver<T>, what: &str) -> Result<T> {
+    loop {
+        match rx.try_recv() {
+            Ok(answer) => return Ok(answer),
+            Err(mpsc::TryRecvError::Empty) => {
+                // SAFETY: on the main thread (the marker proves it), running
+                // its own run loop in the default mode.
+                unsafe {
+                    CFRunLoopRunInMode(kCFRunLoopDefaultMode, RUN_LOOP_SLICE.as_secs_f64(), 1);
+                }
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                return Err(PresswerkError::Bridge(format!(
+                    "{what} closed without an answer"
+                )));
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -357,6 +454,150 @@ impl PlatformBridge for IosBridge {
     fn platform_name(&self) -> &str {
         "iOS"
     }
+
+    /// Camera and Bluetooth come from `AVCaptureDevice` and `CBManager`.
+    ///
+    /// iOS has no API to read the Local Network permission — the first
+    /// connection triggers the prompt and a refusal only shows as failing
+    /// connections — so it is always `Unknown`.
+    fn permission_status(&self, permission: Permission) -> PermissionState {
+        let status = match permission {
+            Permission::LocalNetwork => None,
+            Permission::Camera => camera_authorization(),
+            Permission::Bluetooth => bluetooth_authorization(),
+        };
+        authorization_state(status)
+    }
+
+    /// Prompt through the framework that owns the permission.
+    ///
+    /// The camera uses `+[AVCaptureDevice requestAccessForMediaType:
+    /// completionHandler:]`.  Bluetooth has no request call: creating a
+    /// `CBCentralManager` shows the prompt, and `CBManager.authorization` is
+    /// polled until the user answers.  Local Network cannot be requested and
+    /// answers `Unknown` at once.
+    fn request_permission(&self, permission: Permission, on_result: PermissionCallback) {
+        let current = self.permission_status(permission);
+        if current != PermissionState::NotDetermined {
+            on_result(current);
+            return;
+        }
+        match permission {
+            Permission::LocalNetwork => on_result(PermissionState::Unknown),
+            Permission::Camera => request_camera_access(on_result),
+            Permission::Bluetooth => request_bluetooth_access(on_result),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Runtime permissions -- AVFoundation / CoreBluetooth
+// ---------------------------------------------------------------------------
+// Both frameworks are looked up at runtime, so an app that links neither
+// still runs and reports `Unknown`.
+
+/// `AVMediaTypeVideo`.
+const AV_MEDIA_TYPE_VIDEO: &str = "vide";
+
+/// How long to wait for the user to answer the Bluetooth prompt.
+const BLUETOOTH_PROMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// How often `CBManager.authorization` is re-read while the prompt is up.
+const BLUETOOTH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+thread_local! {
+    /// The central manager whose creation showed the Bluetooth prompt; kept
+    /// alive so the prompt is not dismissed.
+    static BLUETOOTH_MANAGER: RefCell<Option<Retained<AnyObject>>> = const { RefCell::new(None) };
+}
+
+/// Map an `AVAuthorizationStatus` / `CBManagerAuthorization` value.
+///
+/// Both enums use the same values: notDetermined 0, restricted 1, denied 2,
+/// authorized 3.
+fn authorization_state(status: Option<isize>) -> PermissionState {
+    match status {
+        Some(0) => PermissionState::NotDetermined,
+        Some(1 | 2) => PermissionState::Denied,
+        Some(3) => PermissionState::Granted,
+        _ => PermissionState::Unknown,
+    }
+}
+
+fn camera_authorization() -> Option<isize> {
+    // SAFETY: msg_send to the AVCaptureDevice class method
+    // +authorizationStatusForMediaType:, which takes an NSString media type
+    // and returns an NSInteger.
+    AnyClass::get(c"AVCaptureDevice").map(|class| unsafe {
+        let media_type = NSString::from_str(AV_MEDIA_TYPE_VIDEO);
+        msg_send![class, authorizationStatusForMediaType: &*media_type]
+    })
+}
+
+fn bluetooth_authorization() -> Option<isize> {
+    // SAFETY: msg_send to the CBManager class property +authorization
+    // (iOS 13.1+), an NSInteger.
+    AnyClass::get(c"CBManager").map(|class| unsafe { msg_send![class, authorization] })
+}
+
+fn request_camera_access(on_result: PermissionCallback) {
+    let Some(class) = AnyClass::get(c"AVCaptureDevice") else {
+        on_result(PermissionState::Unknown);
+        return;
+    };
+
+    // The block type is `Fn`; the callback is taken out on the one call
+    // AVFoundation makes.
+    let on_result = std::sync::Mutex::new(Some(on_result));
+    let handler = RcBlock::new(move |granted: Bool| {
+        let callback = on_result
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        if let Some(callback) = callback {
+            callback(if granted.as_bool() {
+                PermissionState::Granted
+            } else {
+                PermissionState::Denied
+            });
+        }
+    });
+
+    // SAFETY: msg_send to +requestAccessForMediaType:completionHandler:,
+    // which copies the block and calls it once on an arbitrary queue.
+    unsafe {
+        let media_type = NSString::from_str(AV_MEDIA_TYPE_VIDEO);
+        let _: () = msg_send![
+            class,
+            requestAccessForMediaType: &*media_type,
+            completionHandler: &*handler
+        ];
+    }
+}
+
+fn request_bluetooth_access(on_result: PermissionCallback) {
+    let Some(class) = AnyClass::get(c"CBCentralManager") else {
+        on_result(PermissionState::Unknown);
+        return;
+    };
+
+    // SAFETY: +new on CBCentralManager; a manager without a delegate is
+    // valid and only serves to trigger the prompt.
+    let manager: Option<Retained<AnyObject>> = unsafe { msg_send![class, new] };
+    BLUETOOTH_MANAGER.with(|slot| *slot.borrow_mut() = manager);
+
+    std::thread::spawn(move || {
+        let deadline = std::time::Instant::now() + BLUETOOTH_PROMPT_TIMEOUT;
+        let state = loop {
+            let state = authorization_state(bluetooth_authorization());
+            if state != PermissionState::NotDetermined || std::time::Instant::now() >= deadline {
+                break state;
+            }
+            std::thread::sleep(BLUETOOTH_POLL_INTERVAL);
+        };
+        tracing::info!(?state, "iOS: Bluetooth permission answered");
+        on_result(state);
+    });
 }
 
 // ---------------------------------------------------------------------------
@@ -471,14 +712,9 @@ fn capture_image(&self) -> Result<Option<Vec<u8>>> {
             root_vc.presentViewController_animated_completion(&picker, true, None);
         }
 
-        // Block until the delegate fires.  The main run loop continues to
-        // pump while the picker is presented, so the delegate callbacks
-        // will execute on the main thread as expected.
-        let result = rx
-            .recv()
-            .map_err(|e| PresswerkError::Bridge(format!("camera delegate channel error: {e}")))?;
-
-        Ok(result)
+        // Wait for the delegate, which runs on this (main) thread as the
+        // run loop turns.
+        wait_on_main(mtm, &rx, "camera delegate")
     }
 }
 
@@ -570,11 +806,7 @@ fn pick_file(&self, mime_types: &[&str]) -> Result<Option<String>> {
             root_vc.presentViewController_animated_completion(&picker, true, None);
         }
 
-        let result = rx
-            .recv()
-            .map_err(|e| PresswerkError::Bridge(format!("document picker channel error: {e}")))?;
-
-        Ok(result)
+        wait_on_main(mtm, &rx, "document picker delegate")
     }
 
     /// Read the bytes of a previously picked file.
@@ -812,26 +1044,7 @@ fn share_file(&self, path: &str, _mime_type: &str) -> Result<()> {
 
         tracing::info!(path, "iOS: presenting UIActivityViewController");
 
-        let ns_path = NSString::from_str(path);
-        let url = NSURL::fileURLWithPath(&ns_path);
-
-        // UIActivityViewController expects an NSArray of activity items.
-        // We upcast NSURL -> AnyObject via Retained::into_super.
-        let url_as_obj: Retained<AnyObject> = Retained::into_super(Retained::into_super(url));
-        let items = NSArray::from_retained_slice(&[url_as_obj]);
-
-        // SAFETY: ObjC alloc+init pattern for UIActivityViewController.
-        // initWithActivityItems:applicationActivities: takes NSArray of activity
-        // items and optional NSArray of UIActivity objects (nil = system default).
-        let activity_vc: Retained<UIActivityViewController> = unsafe {
-            let alloc: Retained<UIActivityViewController> =
-                msg_send![objc2::class!(UIActivityViewController), alloc];
-            msg_send![
-                alloc,
-                initWithActivityItems: &*items,
-                applicationActivities: std::ptr::null::<AnyObject>()
-            ]
-        };
+        let activity_vc = file_activity_controller(path);
 
         let root_vc = root_view_controller()?;
         // SAFETY: presentViewController is a UIViewController method.
@@ -844,6 +1057,61 @@ fn share_file(&self, path: &str, _mime_type: &str) -> Result<()> {
         Ok(())
     }
 
+    /// Present the share sheet for `path` and wait until it is dismissed.
+    ///
+    /// The answer comes from the controller's `completionWithItemsHandler`,
+    /// which UIKit calls on the main thread with the chosen activity type
+    /// (e.g. `com.apple.UIKit.activity.Mail`, or an app extension's bundle
+    /// identifier) and whether the share completed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PresswerkError::Bridge` if not called from the main thread,
+    /// if no root view controller is available, or if the share failed.
+    fn share_file_with_result(&self, path: &str, _mime_type: &str) -> Result<ShareOutcome> {
+        let mtm = require_main_thread()?;
+
+        tracing::info!(path, "iOS: presenting UIActivityViewController for result");
+
+        let activity_vc = file_activity_controller(path);
+
+        let (tx, rx) = mpsc::channel::<Result<ShareOutcome>>();
+        let handler = RcBlock::new(
+            move |activity_type: *mut NSString,
+                  completed: Bool,
+                  _returned_items: *mut AnyObject,
+                  error: *mut AnyObject| {
+                // SAFETY: UIKit passes either nil or a valid NSString /
+                // NSError that outlives the handler call.
+                let outcome = if let Some(error) = unsafe { error.as_ref() } {
+                    Err(PresswerkError::Bridge(format!("share failed: {error:?}")))
+                } else if completed.as_bool() {
+                    Ok(ShareOutcome::Completed {
+                        target: unsafe { activity_type.as_ref() }.map(|t| t.to_string()),
+                    })
+                } else {
+                    Ok(ShareOutcome::Cancelled)
+                };
+                let _ = tx.send(outcome);
+            },
+        );
+
+        // SAFETY: setCompletionWithItemsHandler: copies the block; UIKit
+        // calls it once when the sheet is dismissed.
+        unsafe {
+            let _: () = msg_send![&*activity_vc, setCompletionWithItemsHandler: &*handler];
+        }
+
+        let root_vc = root_view_controller()?;
+        // SAFETY: presentViewController — main thread confirmed above.
+        unsafe {
+            root_vc.presentViewController_animated_completion(&activity_vc, true, None);
+        }
+
+        // Wait for the handler, which UIKit calls on this (main) thread.
+        wait_on_main(mtm, &rx, "share completion handler")?
+    }
+
     /// Share text content via the iOS share sheet.
     fn share_text(&self, text: &str) -> Result<()> {
         let _mtm = require_main_thread()?;
@@ -875,6 +1143,30 @@ fn share_text(&self, text: &str) -> Result<()> {
     }
 }
 
+/// A share sheet offering the file at `path`.
+fn file_activity_controller(path: &str) -> Retained<UIActivityViewController> {
+    let ns_path = NSString::from_str(path);
+    let url = NSURL::fileURLWithPath(&ns_path);
+
+    // UIActivityViewController expects an NSArray of activity items.
+    // We upcast NSURL -> AnyObject via Retained::into_super.
+    let url_as_obj: Retained<AnyObject> = Retained::into_super(Retained::into_super(url));
+    let items = NSArray::from_retained_slice(&[url_as_obj]);
+
+    // SAFETY: ObjC alloc+init pattern for UIActivityViewController.
+    // initWithActivityItems:applicationActivities: takes NSArray of activity
+    // items and optional NSArray of UIActivity objects (nil = system default).
+    unsafe {
+        let alloc: Retained<UIActivityViewController> =
+            msg_send![objc2::class!(UIActivityViewController), alloc];
+        msg_send![
+            alloc,
+            initWithActivityItems: &*items,
+            applicationActivities: std::ptr::null::<AnyObject>()
+        ]
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -922,6 +1214,10 @@ fn supports_wifi_direct(&self) -> bool {
     fn discover_wifi_direct_printers(&self) -> Result<Vec<WifiDirectPrinterInfo>> {
         Err(PresswerkError::PlatformUnavailable)
     }
+
+    fn is_metered(&self) -> Result<bool> {
+        Err(PresswerkError::PlatformUnavailable)
+    }
 }
 
 impl NativeFireWirePrint for IosBridge {
@@ -9,7 +9,12 @@
 |||
 ||| SECURITY: Implementations must adhere to the proofs in `src/abi/Bridge.idr`.
 
+pub mod async_bridge;
+pub mod bluetooth;
+pub mod result_receiver;
 pub mod traits;
+pub mod usb;
+pub mod wifi;
 
 #[cfg(target_os = "ios")]
 pub mod ios;
@@ -17,27 +22,48 @@
 #[cfg(target_os = "android")]
 pub mod android;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 #[cfg(not(any(target_os = "ios", target_os = "android")))]
 pub mod stub;
 
+use std::sync::Arc;
+
 /// Retrieves the singleton bridge implementation for the target operating system.
 /// 
 /// RETURNS: A boxed trait object (`dyn PlatformBridge`) that abstracts away
 /// the underlying native SDK details.
 pub fn platform_bridge() -> Box<dyn traits::PlatformBridge> {
+    Box::new(native_bridge())
+}
+
+/// Like [`platform_bridge`], but shareable across threads, so the blocking
+/// calls can be awaited through [`async_bridge::PlatformBridgeAsync`].
+pub fn shared_platform_bridge() -> Arc<dyn traits::PlatformBridge + Send + Sync> {
+    Arc::new(native_bridge())
+}
+
+fn native_bridge() -> impl traits::PlatformBridge + Send + Sync + 'static {
     #[cfg(target_os = "ios")]
     {
         // iOS: Uses `objc2` for type-safe message passing to Objective-C.
-        Box::new(ios::IosBridge::new())
+        ios::IosBridge::new()
     }
     #[cfg(target_os = "android")]
     {
         // Android: Uses `jni-rs` to invoke methods on the JVM/ART.
-        Box::new(android::AndroidBridge::new())
+        android::AndroidBridge::new()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // LINUX DESKTOP: CUPS, zenity and the Secret Service, with the stub
+        // answering anything a desktop has no equivalent for.
+        linux::LinuxBridge::new()
     }
-    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    #[cfg(not(any(target_os = "ios", target_os = "android", target_os = "linux")))]
     {
-        // DESKTOP/CI: Uses a mock implementation to allow non-native builds.
-        Box::new(stub::StubBridge)
+        // OTHER DESKTOPS/CI: Uses a mock implementation to allow non-native builds.
+        stub::StubBridge::new()
     }
 }
@@ -0,0 +1,434 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Desktop keychain: the freedesktop Secret Service (GNOME Keyring, KWallet)
+// when a session bus offers one, otherwise age-encrypted files.
+//
+// The Secret Service is spoken to directly over D-Bus, so no helper binary
+// has to be installed.  Headless CI machines and minimal desktops often have
+// no Secret Service running; there secrets go to
+// `$XDG_DATA_HOME/presswerk/secrets` encrypted with `EncryptedStorage`.  The
+// file fallback is keyed only from `PRESSWERK_KEYCHAIN_PASSPHRASE`: anything
+// derived from the machine itself (such as its machine id) is readable by
+// every local user and would protect nothing.  Without the variable the
+// fallback is unavailable and storing a secret fails.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_security::EncryptedStorage;
+use secret_service::EncryptionType;
+use secret_service::blocking::SecretService;
+
+/// `service` attribute all Presswerk items are stored under.
+const SECRET_SERVICE_NAME: &str = "presswerk";
+
+/// Environment variable holding the file fallback's passphrase.
+const PASSPHRASE_ENV: &str = "PRESSWERK_KEYCHAIN_PASSPHRASE";
+
+/// A place secrets can be kept.
+///
+/// Returning `PresswerkError::PlatformUnavailable` means "this store cannot
+/// be used right now" and makes [`LinuxKeychain`] try its fallback.
+pub trait SecretStore: Send + Sync {
+    fn store(&self, key: &str, value: &[u8]) -> Result<()>;
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    fn delete(&self, key: &str) -> Result<()>;
+}
+
+// ---------------------------------------------------------------------------
+// Keychain
+// ---------------------------------------------------------------------------
+
+/// Secret storage with a preferred store and a fallback.
+pub struct LinuxKeychain {
+    primary: Box<dyn SecretStore>,
+    fallback: Box<dyn SecretStore>,
+}
+
+impl LinuxKeychain {
+    /// The Secret Service, falling back to encrypted files.
+    pub fn new() -> Self {
+        Self::with_stores(
+            Box::new(SecretServiceStore),
+            Box::new(EncryptedFileStore::new(
+                secrets_dir(),
+                fallback_passphrase(),
+            )),
+        )
+    }
+
+    /// Use `primary` when available and `fallback` otherwise.
+    pub fn with_stores(primary: Box<dyn SecretStore>, fallback: Box<dyn SecretStore>) -> Self {
+        Self { primary, fallback }
+    }
+
+    pub fn store(&self, key: &str, value: &[u8]) -> Result<()> {
+        match self.primary.store(key, value) {
+            Ok(()) => {
+                // Drop any copy written while the Secret Service was away,
+                // so it cannot shadow this one later.
+                let _ = self.fallback.delete(key);
+                Ok(())
+            }
+            Err(PresswerkError::PlatformUnavailable) => {
+                tracing::warn!("Secret Service unavailable; using encrypted file store");
+                self.fallback.store(key, value)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self.primary.load(key) {
+            Ok(Some(value)) => Ok(Some(value)),
+            Ok(None) | Err(PresswerkError::PlatformUnavailable) => self.fallback.load(key),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn delete(&self, key: &str) -> Result<()> {
+        match self.primary.delete(key) {
+            Ok(()) | Err(PresswerkError::PlatformUnavailable) => {}
+            Err(e) => return Err(e),
+        }
+        self.fallback.delete(key)
+    }
+}
+
+impl Default for LinuxKeychain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Secret Service (D-Bus)
+// ---------------------------------------------------------------------------
+
+/// Items in the user's default Secret Service collection.
+///
+/// Values are stored hex-encoded, the format earlier releases wrote through
+/// `secret-tool`, so their items still read back.
+pub struct SecretServiceStore;
+
+impl SecretServiceStore {
+    /// Open a session with the Secret Service on the session bus.
+    fn connect() -> Result<SecretService<'static>> {
+        SecretService::connect(EncryptionType::Dh).map_err(secret_service_error)
+    }
+
+    /// Attributes identifying the item for `key`.
+    fn attributes(key: &str) -> HashMap<&str, &str> {
+        HashMap::from([("service", SECRET_SERVICE_NAME), ("key", key)])
+    }
+}
+
+impl SecretStore for SecretServiceStore {
+    fn store(&self, key: &str, value: &[u8]) -> Result<()> {
+        let service = Self::connect()?;
+        let collection = service
+            .get_default_collection()
+            .map_err(secret_service_error)?;
+        collection.ensure_unlocked().map_err(secret_service_error)?;
+        collection
+            .create_item(
+                &format!("Presswerk: {key}"),
+                Self::attributes(key),
+                to_hex(value).as_bytes(),
+                true,
+                "text/plain",
+            )
+            .map_err(secret_service_error)?;
+        Ok(())
+    }
+
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let service = Self::connect()?;
+        let items = service
+            .search_items(Self::attributes(key))
+            .map_err(secret_service_error)?;
+        let Some(item) = items.unlocked.first().or(items.locked.first()) else {
+            return Ok(None);
+        };
+        item.ensure_unlocked().map_err(secret_service_error)?;
+        let secret = item.get_secret().map_err(secret_service_error)?;
+        std::str::from_utf8(&secret)
+            .ok()
+            .and_then(|hex| from_hex(hex.trim()))
+            .map(Some)
+            .ok_or_else(|| {
+                PresswerkError::Bridge(format!("Secret Service item {key} is not Presswerk data"))
+            })
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        let service = Self::connect()?;
+        let items = service
+            .search_items(Self::attributes(key))
+            .map_err(secret_service_error)?;
+        for item in items.unlocked.iter().chain(&items.locked) {
+            item.delete().map_err(secret_service_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Map a Secret Service failure onto the keychain's errors.
+///
+/// A dismissed unlock prompt is the user's decision and is reported as
+/// such; anything else means the service cannot be used right now, which
+/// lets [`LinuxKeychain`] fall back to files.
+fn secret_service_error(e: secret_service::Error) -> PresswerkError {
+    match e {
+        secret_service::Error::Prompt => {
+            PresswerkError::PermissionDenied("Secret Service unlock was dismissed".into())
+        }
+        other => {
+            tracing::debug!(error = %other, "Secret Service unavailable");
+            PresswerkError::PlatformUnavailable
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Encrypted file fallback
+// ---------------------------------------------------------------------------
+
+/// One age-encrypted file per secret in `dir`.
+pub struct EncryptedFileStore {
+    dir: PathBuf,
+    storage: Option<EncryptedStorage>,
+}
+
+impl EncryptedFileStore {
+    /// Store secrets in `dir`, encrypted with `passphrase`.
+    ///
+    /// With no passphrase the store is unavailable.
+    pub fn new(dir: impl Into<PathBuf>, passphrase: Option<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            storage: passphrase.map(EncryptedStorage::new),
+        }
+    }
+
+    fn storage(&self) -> Result<&EncryptedStorage> {
+        self.storage
+            .as_ref()
+            .ok_or(PresswerkError::PlatformUnavailable)
+    }
+
+    /// File for `key`; the key is hex-encoded so it cannot name a path.
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.age", to_hex(key.as_bytes())))
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn store(&self, key: &str, value: &[u8]) -> Result<()> {
+        let ciphertext = self.storage()?.encrypt(value)?;
+        fs::create_dir_all(&self.dir)?;
+        write_private(&self.path(key), &ciphertext)
+    }
+
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let storage = self.storage()?;
+        match fs::read(self.path(key)) {
+            Ok(ciphertext) => storage.decrypt(&ciphertext).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(PresswerkError::Io(e)),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path(key)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(PresswerkError::Io(e)),
+        }
+    }
+}
+
+/// Write `data` to `path`, readable by the owner only.
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(data)?;
+    Ok(())
+}
+
+/// `$XDG_DATA_HOME/presswerk/secrets`, or `~/.local/share/presswerk/secrets`.
+fn secrets_dir() -> PathBuf {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .unwrap_or_else(std::env::temp_dir);
+    data_home.join("presswerk").join("secrets")
+}
+
+/// Passphrase for the file fallback, if the user has set one.
+fn fallback_passphrase() -> Option<String> {
+    std::env::var(PASSPHRASE_ENV)
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// In-memory Secret Service that can be switched off.
+    #[derive(Default)]
+    struct MockSecretService {
+        offline: AtomicBool,
+        items: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    impl SecretStore for Arc<MockSecretService> {
+        fn store(&self, key: &str, value: &[u8]) -> Result<()> {
+            self.check()?;
+            self.items
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_vec());
+            Ok(())
+        }
+
+        fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            self.check()?;
+            Ok(self.items.lock().unwrap().get(key).cloned())
+        }
+
+        fn delete(&self, key: &str) -> Result<()> {
+            self.check()?;
+            self.items.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+
+    impl MockSecretService {
+        fn check(&self) -> Result<()> {
+            if self.offline.load(Ordering::SeqCst) {
+                Err(PresswerkError::PlatformUnavailable)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn keychain(service: &Arc<MockSecretService>, dir: &Path) -> LinuxKeychain {
+        LinuxKeychain::with_stores(
+            Box::new(Arc::clone(service)),
+            Box::new(EncryptedFileStore::new(dir, Some("test passphrase".into()))),
+        )
+    }
+
+    #[test]
+    fn secret_service_is_used_when_available() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let service = Arc::new(MockSecretService::default());
+        let keychain = keychain(&service, tmp.path());
+
+        keychain.store("tls-key", b"\x00secret\xff").unwrap();
+        assert_eq!(
+            service
+                .items
+                .lock()
+                .unwrap()
+                .get("tls-key")
+                .map(Vec::as_slice),
+            Some(&b"\x00secret\xff"[..])
+        );
+        assert!(fs::read_dir(tmp.path()).unwrap().next().is_none());
+        assert_eq!(
+            keychain.load("tls-key").unwrap().as_deref(),
+            Some(&b"\x00secret\xff"[..])
+        );
+
+        keychain.delete("tls-key").unwrap();
+        assert_eq!(keychain.load("tls-key").unwrap(), None);
+    }
+
+    #[test]
+    fn falls_back_to_encrypted_files_without_secret_service() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let service = Arc::new(MockSecretService::default());
+        service.offline.store(true, Ordering::SeqCst);
+        let keychain = keychain(&service, tmp.path());
+
+        keychain.store("../../passwd", b"hunter2").unwrap();
+        let files: Vec<_> = fs::read_dir(tmp.path()).unwrap().flatten().collect();
+        assert_eq!(files.len(), 1);
+        assert!(
+            !fs::read(files[0].path())
+                .unwrap()
+                .windows(7)
+                .any(|w| w == b"hunter2")
+        );
+
+        // Still found once the Secret Service comes back.
+        service.offline.store(false, Ordering::SeqCst);
+        assert_eq!(
+            keychain.load("../../passwd").unwrap().as_deref(),
+            Some(&b"hunter2"[..])
+        );
+
+        // A fresh store goes to the service and retires the file copy.
+        keychain.store("../../passwd", b"correct horse").unwrap();
+        assert!(fs::read_dir(tmp.path()).unwrap().next().is_none());
+        assert_eq!(
+            keychain.load("../../passwd").unwrap().as_deref(),
+            Some(&b"correct horse"[..])
+        );
+    }
+
+    #[test]
+    fn no_passphrase_means_no_file_fallback() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let service = Arc::new(MockSecretService::default());
+        service.offline.store(true, Ordering::SeqCst);
+        let keychain = LinuxKeychain::with_stores(
+            Box::new(Arc::clone(&service)),
+            Box::new(EncryptedFileStore::new(tmp.path(), None)),
+        );
+
+        assert!(matches!(
+            keychain.store("tls-key", b"secret"),
+            Err(PresswerkError::PlatformUnavailable)
+        ));
+        assert!(fs::read_dir(tmp.path()).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(from_hex(&to_hex(b"\x00\x7f\xff")).unwrap(), b"\x00\x7f\xff");
+        assert!(from_hex("abc").is_none());
+        assert!(from_hex("zz").is_none());
+    }
+}
@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Linux desktop bridge.
+//
+// Lets developers exercise the print and document flows without a phone by
+// using the tools a desktop already has:
+//
+// - printing goes to the default CUPS destination via `lp`;
+// - files are chosen with `zenity --file-selection`;
+// - secrets live in the Secret Service, or encrypted files without one (see
+//   the `keychain` submodule).
+//
+// A missing tool surfaces as `PlatformUnavailable`, as do the capabilities
+// a desktop has no equivalent for; those are answered by `StubBridge`.
+
+#![cfg(target_os = "linux")]
+
+pub mod keychain;
+
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Output, Stdio};
+
+use presswerk_core::error::{PresswerkError, Result};
+
+use crate::stub::StubBridge;
+use crate::traits::*;
+
+use self::keychain::LinuxKeychain;
+
+/// Title given to jobs submitted through `lp`.
+const LP_JOB_TITLE: &str = "Presswerk";
+
+// ---------------------------------------------------------------------------
+// Bridge struct
+// ---------------------------------------------------------------------------
+
+/// Desktop Linux implementation of the Presswerk platform bridge.
+pub struct LinuxBridge {
+    keychain: LinuxKeychain,
+    stub: StubBridge,
+}
+
+impl LinuxBridge {
+    /// Create a new Linux bridge. No tools are run until a method is used.
+    pub fn new() -> Self {
+        Self {
+            keychain: LinuxKeychain::new(),
+            stub: StubBridge::new(),
+        }
+    }
+}
+
+impl Default for LinuxBridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlatformBridge for LinuxBridge {
+    fn platform_name(&self) -> &str {
+        "Linux"
+    }
+
+    /// Desktop Linux has no runtime permissions.
+    fn permission_status(&self, _permission: Permission) -> PermissionState {
+        PermissionState::Granted
+    }
+
+    fn request_permission(&self, _permission: Permission, on_result: PermissionCallback) {
+        on_result(PermissionState::Granted);
+    }
+}
+
+/// Run `command`, feeding it `stdin`, and collect its output.
+///
+/// A program that is not installed maps to `PlatformUnavailable`.
+pub(crate) fn run_tool(command: &mut Command, stdin: Option<&[u8]>) -> Result<Output> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    command
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command.spawn().map_err(|e| {
+        if e.kind() == ErrorKind::NotFound {
+            tracing::warn!(program, "not installed");
+            PresswerkError::PlatformUnavailable
+        } else {
+            PresswerkError::Bridge(format!("failed to run {program}: {e}"))
+        }
+    })?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input)
+            .map_err(|e| PresswerkError::Bridge(format!("failed to write to {program}: {e}")))?;
+    }
+    child
+        .wait_with_output()
+        .map_err(|e| PresswerkError::Bridge(format!("{program} did not finish: {e}")))
+}
+
+// ---------------------------------------------------------------------------
+// NativePrint — CUPS via lp
+// ---------------------------------------------------------------------------
+
+impl NativePrint for LinuxBridge {
+    /// There is no dialog: the document goes straight to the default CUPS
+    /// destination (`lpoptions -d` or `$PRINTER`).
+    fn show_print_dialog(&self, document: &[u8], mime_type: &str) -> Result<()> {
+        let output = run_tool(
+            Command::new("lp")
+                .args(["-t", LP_JOB_TITLE])
+                .arg("-o")
+                .arg(format!("document-format={mime_type}")),
+            Some(document),
+        )?;
+        if !output.status.success() {
+            return Err(PresswerkError::Bridge(format!(
+                "lp failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        tracing::info!(
+            bytes = document.len(),
+            mime_type,
+            response = %String::from_utf8_lossy(&output.stdout).trim(),
+            "submitted to CUPS"
+        );
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// NativeFilePicker — zenity
+// ---------------------------------------------------------------------------
+
+impl NativeFilePicker for LinuxBridge {
+    fn pick_file(&self, mime_types: &[&str]) -> Result<Option<String>> {
+        let mut command = Command::new("zenity");
+        command.args(["--file-selection", "--title=Choose a document"]);
+        if let Some(filter) = zenity_file_filter(mime_types) {
+            command.arg(format!("--file-filter={filter}"));
+        }
+
+        let output = run_tool(&mut command, None)?;
+        // zenity exits 1 when the dialog is cancelled.
+        if output.status.code() == Some(1) {
+            return Ok(None);
+        }
+        if !output.status.success() {
+            return Err(PresswerkError::Bridge(format!(
+                "zenity failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!path.is_empty()).then_some(path))
+    }
+
+    fn read_picked_file(&self, path: &str) -> Result<Vec<u8>> {
+        Ok(std::fs::read(path)?)
+    }
+}
+
+/// Build a zenity `--file-filter` ("Documents | *.pdf *.png") for
+/// `mime_types`, or `None` if any type is allowed.
+fn zenity_file_filter(mime_types: &[&str]) -> Option<String> {
+    let mut patterns: Vec<&str> = Vec::new();
+    for mime_type in mime_types {
+        let extensions: &[&str] = match *mime_type {
+            "application/pdf" => &["*.pdf"],
+            "image/jpeg" => &["*.jpg", "*.jpeg"],
+            "image/png" => &["*.png"],
+            "image/tiff" => &["*.tif", "*.tiff"],
+            "image/heic" => &["*.heic", "*.heif"],
+            "image/webp" => &["*.webp"],
+            "image/*" => &[
+                "*.jpg", "*.jpeg", "*.png", "*.tif", "*.tiff", "*.heic", "*.webp",
+            ],
+            "text/plain" => &["*.txt"],
+            "application/postscript" => &["*.ps"],
+            // Unknown or wildcard types: do not hide anything.
+            _ => return None,
+        };
+        for extension in extensions {
+            if !patterns.contains(extension) {
+                patterns.push(extension);
+            }
+        }
+    }
+    (!patterns.is_empty()).then(|| format!("Documents | {}", patterns.join(" ")))
+}
+
+// ---------------------------------------------------------------------------
+// NativeKeychain — Secret Service with encrypted-file fallback
+// ---------------------------------------------------------------------------
+
+impl NativeKeychain for LinuxBridge {
+    fn store_secret(&self, key: &str, value: &[u8]) -> Result<()> {
+        self.keychain.store(key, value)
+    }
+
+    fn load_secret(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.keychain.load(key)
+    }
+
+    fn delete_secret(&self, key: &str) -> Result<()> {
+        self.keychain.delete(key)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Everything else — answered by the stub bridge
+// ---------------------------------------------------------------------------
+
+impl NativeCamera for LinuxBridge {
+    fn capture_image(&self) -> Result<Option<Vec<u8>>> {
+        self.stub.capture_image()
+    }
+}
+
+impl NativeShare for LinuxBridge {
+    fn share_file(&self, path: &str, mime_type: &str) -> Result<()> {
+        self.stub.share_file(path, mime_type)
+    }
+
+    fn share_file_with_result(&self, path: &str, mime_type: &str) -> Result<ShareOutcome> {
+        self.stub.share_file_with_result(path, mime_type)
+    }
+
+    fn share_text(&self, text: &str) -> Result<()> {
+        self.stub.share_text(text)
+    }
+}
+
+impl NativeUsbPrint for LinuxBridge {
+    fn detect_usb_printers(&self) -> Result<Vec<UsbPrinterInfo>> {
+        self.stub.detect_usb_printers()
+    }
+
+    fn print_usb(&self, device_id: &str, document: &[u8], mime_type: &str) -> Result<()> {
+        self.stub.print_usb(device_id, document, mime_type)
+    }
+}
+
+impl NativeBluetoothPrint for LinuxBridge {
+    fn scan_bluetooth_printers(&self) -> Result<Vec<BluetoothPrinterInfo>> {
+        self.stub.scan_bluetooth_printers()
+    }
+
+    fn print_bluetooth(&self, device_id: &str, document: &[u8]) -> Result<()> {
+        self.stub.print_bluetooth(device_id, document)
+    }
+}
+
+impl NativeNfcPrint for LinuxBridge {
+    fn read_nfc_printer_tag(&self) -> Result<Option<NfcPrinterInfo>> {
+        self.stub.read_nfc_printer_tag()
+    }
+}
+
+impl NativeConnectivity for LinuxBridge {
+    fn wifi_ssid(&self) -> Result<Option<String>> {
+        self.stub.wifi_ssid()
+    }
+
+    fn supports_wifi_direct(&self) -> bool {
+        self.stub.supports_wifi_direct()
+    }
+
+    fn discover_wifi_direct_printers(&self) -> Result<Vec<WifiDirectPrinterInfo>> {
+        self.stub.discover_wifi_direct_printers()
+    }
+
+    fn is_metered(&self) -> Result<bool> {
+        self.stub.is_metered()
+    }
+}
+
+impl NativeFireWirePrint for LinuxBridge {
+    fn detect_firewire_printers(&self) -> Result<Vec<FireWirePrinterInfo>> {
+        self.stub.detect_firewire_printers()
+    }
+
+    fn print_firewire(&self, device_id: &str, document: &[u8], mime_type: &str) -> Result<()> {
+        self.stub.print_firewire(device_id, document, mime_type)
+    }
+}
+
+impl NativeLightningPrint for LinuxBridge {
+    fn detect_lightning_printers(&self) -> Result<Vec<LightningPrinterInfo>> {
+        self.stub.detect_lightning_printers()
+    }
+
+    fn print_lightning(&self, device_id: &str, document: &[u8], mime_type: &str) -> Result<()> {
+        self.stub.print_lightning(device_id, document, mime_type)
+    }
+}
+
+impl NativeThunderboltPrint for LinuxBridge {
+    fn detect_thunderbolt_printers(&self) -> Result<Vec<ThunderboltPrinterInfo>> {
+        self.stub.detect_thunderbolt_printers()
+    }
+
+    fn print_thunderbolt(&self, device_id: &str, document: &[u8], mime_type: &str) -> Result<()> {
+        self.stub.print_thunderbolt(device_id, document, mime_type)
+    }
+}
+
+impl NativeSerialPrint for LinuxBridge {
+    fn detect_serial_printers(&self) -> Result<Vec<SerialPrinterInfo>> {
+        self.stub.detect_serial_printers()
+    }
+
+    fn print_serial(&self, port: &str, baud_rate: u32, document: &[u8]) -> Result<()> {
+        self.stub.print_serial(port, baud_rate, document)
+    }
+}
+
+impl NativeParallelPrint for LinuxBridge {
+    fn detect_parallel_printers(&self) -> Result<Vec<ParallelPrinterInfo>> {
+        self.stub.detect_parallel_printers()
+    }
+
+    fn print_parallel(&self, port: &str, document: &[u8]) -> Result<()> {
+        self.stub.print_parallel(port, document)
+    }
+}
+
+impl NativeInfraredPrint for LinuxBridge {
+    fn scan_infrared_printers(&self) -> Result<Vec<InfraredPrinterInfo>> {
+        self.stub.scan_infrared_printers()
+    }
+
+    fn print_infrared(&self, device_id: &str, document: &[u8]) -> Result<()> {
+        self.stub.print_infrared(device_id, document)
+    }
+}
+
+impl NativeIBeaconDiscover for LinuxBridge {
+    fn scan_ibeacon_printers(&self) -> Result<Vec<IBeaconPrinterInfo>> {
+        self.stub.scan_ibeacon_printers()
+    }
+}
+
+impl NativeLiFiPrint for LinuxBridge {
+    fn detect_lifi_endpoints(&self) -> Result<Vec<LiFiEndpointInfo>> {
+        self.stub.detect_lifi_endpoints()
+    }
+
+    fn print_lifi(&self, endpoint_id: &str, document: &[u8]) -> Result<()> {
+        self.stub.print_lifi(endpoint_id, document)
+    }
+}
+
+impl NativeUsbDrivePrint for LinuxBridge {
+    fn detect_usb_drives(&self) -> Result<Vec<UsbDriveInfo>> {
+        self.stub.detect_usb_drives()
+    }
+
+    fn copy_to_usb_drive(&self, drive_id: &str, document: &[u8], filename: &str) -> Result<String> {
+        self.stub.copy_to_usb_drive(drive_id, document, filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_filter_lists_each_extension_once() {
+        assert_eq!(
+            zenity_file_filter(&["application/pdf", "image/jpeg", "image/*"]).as_deref(),
+            Some("Documents | *.pdf *.jpg *.jpeg *.png *.tif *.tiff *.heic *.webp")
+        );
+        assert_eq!(zenity_file_filter(&["application/pdf", "*/*"]), None);
+        assert_eq!(zenity_file_filter(&[]), None);
+    }
+}
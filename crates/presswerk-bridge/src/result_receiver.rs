@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Hand-off point for results of activities started on Presswerk's behalf
+// (camera capture, document picker, share chooser) and of runtime
+// permission prompts.
+//
+// The native side launches an activity and then blocks on a `PendingResult`
+// registered under the request code.  When the host Activity's
+// `onActivityResult` or `onRequestPermissionsResult` fires, the platform glue
+// (see `android::result_receiver` for the JNI entry points) calls `deliver`
+// with the same request code and the waiting call resumes.  Nothing here
+// touches JNI, so the channel itself is exercised by ordinary unit tests.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use presswerk_core::error::{PresswerkError, Result};
+
+/// What an activity returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityResult {
+    /// Image bytes from the camera.
+    Image(Vec<u8>),
+    /// A `content://` URI chosen in the document picker.
+    Document(String),
+    /// The user's answer to a permission prompt.
+    Permission { granted: bool },
+    /// The user picked a share target: its package, when known.
+    Shared { target: Option<String> },
+    /// The user backed out.
+    Cancelled,
+    /// The activity succeeded but its result could not be read.
+    Failed(String),
+}
+
+/// A registered waiter: its registration number and sender.
+type Waiter = (u64, Sender<ActivityResult>);
+
+/// Waiters for a result, keyed by request code.
+static PENDING: LazyLock<Mutex<HashMap<i32, Waiter>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Source of registration numbers, so a timed-out wait only unregisters
+/// itself and not a newer wait on the same request code.
+static NEXT_REGISTRATION: AtomicU64 = AtomicU64::new(0);
+
+fn pending() -> std::sync::MutexGuard<'static, HashMap<i32, Waiter>> {
+    PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A result that has been asked for but not yet delivered.
+#[derive(Debug)]
+pub struct PendingResult {
+    request_code: i32,
+    registration: u64,
+    receiver: Receiver<ActivityResult>,
+}
+
+/// Start waiting for the result of `request_code`.
+///
+/// Call this *before* launching the activity so a fast result cannot be
+/// missed.  Registering the same code again abandons the earlier wait.
+pub fn register(request_code: i32) -> PendingResult {
+    let (sender, receiver) = mpsc::channel();
+    let registration = NEXT_REGISTRATION.fetch_add(1, Ordering::Relaxed);
+    pending().insert(request_code, (registration, sender));
+    PendingResult {
+        request_code,
+        registration,
+        receiver,
+    }
+}
+
+/// Hand `result` to whoever is waiting on `request_code`.
+///
+/// Returns `false` if nobody is waiting (e.g. the wait timed out, or the
+/// request code is not one of ours).
+pub fn deliver(request_code: i32, result: ActivityResult) -> bool {
+    match pending().remove(&request_code) {
+        Some((_, sender)) => sender.send(result).is_ok(),
+        None => {
+            tracing::debug!(request_code, "activity result with no waiter");
+            false
+        }
+    }
+}
+
+impl PendingResult {
+    /// The request code this result was registered under.
+    pub fn request_code(&self) -> i32 {
+        self.request_code
+    }
+
+    /// Block until the result arrives or `timeout` passes.
+    ///
+    /// Must not be called on the thread that delivers the result (on
+    /// Android, the main/UI thread), or it will wait out the timeout.
+    pub fn wait(self, timeout: Duration) -> Result<ActivityResult> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Ok(result),
+            Err(RecvTimeoutError::Timeout) => {
+                let mut waiters = pending();
+                if waiters
+                    .get(&self.request_code)
+                    .is_some_and(|(registration, _)| *registration == self.registration)
+                {
+                    waiters.remove(&self.request_code);
+                }
+                Err(PresswerkError::Bridge(format!(
+                    "no activity result for request {:#x} after {}s",
+                    self.request_code,
+                    timeout.as_secs()
+                )))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(PresswerkError::Bridge(format!(
+                "wait for request {:#x} was superseded",
+                self.request_code
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivered_result_reaches_the_waiter() {
+        let pending = register(0x7001);
+        let delivery = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(20));
+            deliver(0x7001, ActivityResult::Image(vec![0xFF, 0xD8, 0xFF]))
+        });
+
+        let result = pending.wait(Duration::from_secs(5)).expect("result");
+        assert!(delivery.join().unwrap());
+        assert_eq!(result, ActivityResult::Image(vec![0xFF, 0xD8, 0xFF]));
+
+        // The registration is consumed by the delivery.
+        assert!(!deliver(0x7001, ActivityResult::Cancelled));
+    }
+
+    #[test]
+    fn timeout_and_superseded_waits_fail() {
+        let stale = register(0x7002);
+        assert!(stale.wait(Duration::from_millis(10)).is_err());
+        assert!(!deliver(0x7002, ActivityResult::Cancelled));
+
+        let first = register(0x7003);
+        let second = register(0x7003);
+        assert!(first.wait(Duration::from_secs(5)).is_err());
+        assert!(deliver(
+            0x7003,
+            ActivityResult::Document("content://x/1".into())
+        ));
+        assert_eq!(
+            second.wait(Duration::from_secs(5)).unwrap(),
+            ActivityResult::Document("content://x/1".into())
+        );
+    }
+}
@@ -4,23 +4,174 @@
 // Stub bridge for desktop/CI builds where native mobile APIs are unavailable.
 //
 // Every trait method returns `PlatformUnavailable` — real implementations live
-// in the `ios` and `android` modules.
+// in the `ios`, `android` and `linux` modules.  The Linux bridge also defers
+// to this one for capabilities a desktop lacks.
+//
+// The stub doubles as a test double for UI code: calls are recorded (see
+// `StubBridge::calls`), and the camera, file picker and Wi-Fi methods can be
+// given canned answers with the `with_*` builders.  Permissions read as
+// granted, and requests for them are granted at once, unless overridden with
+// `with_permission`.  Because the same stub also serves real desktop builds
+// for the lifetime of the app, only the most recent `MAX_RECORDED_CALLS`
+// calls are kept.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 
 use presswerk_core::error::{PresswerkError, Result};
 
 use crate::traits::*;
 
-/// No-op bridge returned on non-mobile platforms.
-pub struct StubBridge;
+/// Calls kept in a stub's log; older ones are dropped.
+pub const MAX_RECORDED_CALLS: usize = 256;
+
+/// A bridge method invoked on a [`StubBridge`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeCall {
+    ShowPrintDialog {
+        mime_type: String,
+        bytes: usize,
+    },
+    CaptureImage,
+    PickFile {
+        mime_types: Vec<String>,
+    },
+    ReadPickedFile {
+        path: String,
+    },
+    StoreSecret {
+        key: String,
+    },
+    LoadSecret {
+        key: String,
+    },
+    DeleteSecret {
+        key: String,
+    },
+    ShareFile {
+        path: String,
+        mime_type: String,
+    },
+    ShareText {
+        text: String,
+    },
+    /// Any transport or discovery method, by trait method name.
+    Other(&'static str),
+}
+
+/// Bridge returned on non-mobile platforms, and a recording test double.
+///
+/// Clones share the call log, so a test can keep one clone and hand another
+/// to the code under test.  The log holds the last [`MAX_RECORDED_CALLS`]
+/// calls.
+#[derive(Debug, Clone, Default)]
+pub struct StubBridge {
+    calls: Arc<Mutex<VecDeque<BridgeCall>>>,
+    captured_image: Option<Option<Vec<u8>>>,
+    picked_file: Option<Option<String>>,
+    picked_file_contents: Option<Vec<u8>>,
+    wifi_ssid: Option<Option<String>>,
+    permissions: HashMap<Permission, PermissionState>,
+}
+
+impl StubBridge {
+    /// A stub that records calls and answers everything with
+    /// `PlatformUnavailable`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `capture_image` return `image` (`None` = the user cancelled).
+    pub fn with_captured_image(mut self, image: Option<Vec<u8>>) -> Self {
+        self.captured_image = Some(image);
+        self
+    }
+
+    /// Make `pick_file` return `path` (`None` = the user cancelled).
+    pub fn with_picked_file(mut self, path: Option<String>) -> Self {
+        self.picked_file = Some(path);
+        self
+    }
+
+    /// Make `read_picked_file` return `contents` for any path.
+    pub fn with_picked_file_contents(mut self, contents: Vec<u8>) -> Self {
+        self.picked_file_contents = Some(contents);
+        self
+    }
+
+    /// Make `wifi_ssid` return `ssid`.
+    pub fn with_wifi_ssid(mut self, ssid: Option<String>) -> Self {
+        self.wifi_ssid = Some(ssid);
+        self
+    }
+
+    /// Make `permission_status(permission)` return `state`, and requests for
+    /// it answer with `state`.
+    pub fn with_permission(mut self, permission: Permission, state: PermissionState) -> Self {
+        self.permissions.insert(permission, state);
+        self
+    }
+
+    /// The recorded calls, oldest first.
+    pub fn calls(&self) -> Vec<BridgeCall> {
+        self.log().iter().cloned().collect()
+    }
+
+    /// How many recorded calls equal `call`.
+    pub fn call_count(&self, call: &BridgeCall) -> usize {
+        self.log().iter().filter(|made| *made == call).count()
+    }
+
+    fn record(&self, call: BridgeCall) {
+        let mut log = self.log();
+        if log.len() == MAX_RECORDED_CALLS {
+            log.pop_front();
+        }
+        log.push_back(call);
+    }
+
+    fn unavailable<T>(&self, method: &'static str) -> Result<T> {
+        self.record(BridgeCall::Other(method));
+        Err(PresswerkError::PlatformUnavailable)
+    }
+
+    fn log(&self) -> std::sync::MutexGuard<'_, VecDeque<BridgeCall>> {
+        self.calls
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
 
 impl PlatformBridge for StubBridge {
     fn platform_name(&self) -> &str {
         "Desktop (stub)"
     }
+
+    fn permission_status(&self, permission: Permission) -> PermissionState {
+        self.record(BridgeCall::Other("permission_status"));
+        self.permissions
+            .get(&permission)
+            .copied()
+            .unwrap_or(PermissionState::Granted)
+    }
+
+    fn request_permission(&self, permission: Permission, on_result: PermissionCallback) {
+        self.record(BridgeCall::Other("request_permission"));
+        let state = self
+            .permissions
+            .get(&permission)
+            .copied()
+            .unwrap_or(PermissionState::Granted);
+        on_result(state);
+    }
 }
 
 impl NativePrint for StubBridge {
-    fn show_print_dialog(&self, _document: &[u8], _mime_type: &str) -> Result<()> {
+    fn show_print_dialog(&self, document: &[u8], mime_type: &str) -> Result<()> {
+        self.record(BridgeCall::ShowPrintDialog {
+            mime_type: mime_type.to_string(),
+            bytes: document.len(),
+        });
         tracing::warn!("NativePrint::show_print_dialog called on stub bridge");
         Err(PresswerkError::PlatformUnavailable)
     }
@@ -28,45 +179,90 @@ fn show_print_dialog(&self, _document: &[u8], _mime_type: &str) -> Result<()> {
 
 impl NativeCamera for StubBridge {
     fn capture_image(&self) -> Result<Option<Vec<u8>>> {
-        tracing::warn!("NativeCamera::capture_image called on stub bridge");
-        Err(PresswerkError::PlatformUnavailable)
+        self.record(BridgeCall::CaptureImage);
+        match &self.captured_image {
+            Some(image) => Ok(image.clone()),
+            None => {
+                tracing::warn!("NativeCamera::capture_image called on stub bridge");
+                Err(PresswerkError::PlatformUnavailable)
+            }
+        }
     }
 }
 
 impl NativeFilePicker for StubBridge {
-    fn pick_file(&self, _mime_types: &[&str]) -> Result<Option<String>> {
-        tracing::warn!("NativeFilePicker::pick_file called on stub bridge");
-        Err(PresswerkError::PlatformUnavailable)
-    }
-
-    fn read_picked_file(&self, _path: &str) -> Result<Vec<u8>> {
-        Err(PresswerkError::PlatformUnavailable)
+    fn pick_file(&self, mime_types: &[&str]) -> Result<Option<String>> {
+        self.record(BridgeCall::PickFile {
+            mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+        });
+        match &self.picked_file {
+            Some(path) => Ok(path.clone()),
+            None => {
+                tracing::warn!("NativeFilePicker::pick_file called on stub bridge");
+                Err(PresswerkError::PlatformUnavailable)
+            }
+        }
+    }
+
+    fn read_picked_file(&self, path: &str) -> Result<Vec<u8>> {
+        self.record(BridgeCall::ReadPickedFile {
+            path: path.to_string(),
+        });
+        self.picked_file_contents
+            .clone()
+            .ok_or(PresswerkError::PlatformUnavailable)
     }
 }
 
 impl NativeKeychain for StubBridge {
-    fn store_secret(&self, _key: &str, _value: &[u8]) -> Result<()> {
+    fn store_secret(&self, key: &str, _value: &[u8]) -> Result<()> {
+        self.record(BridgeCall::StoreSecret {
+            key: key.to_string(),
+        });
         tracing::warn!("NativeKeychain::store_secret called on stub bridge");
         Err(PresswerkError::PlatformUnavailable)
     }
 
-    fn load_secret(&self, _key: &str) -> Result<Option<Vec<u8>>> {
+    fn load_secret(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.record(BridgeCall::LoadSecret {
+            key: key.to_string(),
+        });
         tracing::warn!("NativeKeychain::load_secret called on stub bridge");
         Err(PresswerkError::PlatformUnavailable)
     }
 
-    fn delete_secret(&self, _key: &str) -> Result<()> {
+    fn delete_secret(&self, key: &str) -> Result<()> {
+        self.record(BridgeCall::DeleteSecret {
+            key: key.to_string(),
+        });
         Err(PresswerkError::PlatformUnavailable)
     }
 }
 
 impl NativeShare for StubBridge {
-    fn share_file(&self, _path: &str, _mime_type: &str) -> Result<()> {
+    fn share_file(&self, path: &str, mime_type: &str) -> Result<()> {
+        self.record(BridgeCall::ShareFile {
+            path: path.to_string(),
+            mime_type: mime_type.to_string(),
+        });
         tracing::warn!("NativeShare::share_file called on stub bridge");
         Err(PresswerkError::PlatformUnavailable)
     }
 
-    fn share_text(&self, _text: &str) -> Result<()> {
+    fn share_file_with_result(&self, path: &str, mime_type: &str) -> Result<ShareOutcome> {
+        self.record(BridgeCall::ShareFile {
+            path: path.to_string(),
+            mime_type: mime_type.to_string(),
+        });
+        tracing::warn!("NativeShare::share_file_with_result called on stub bridge");
+        // No share sheet to show, so nobody can choose a target.
+        Ok(ShareOutcome::Cancelled)
+    }
+
+    fn share_text(&self, text: &str) -> Result<()> {
+        self.record(BridgeCall::ShareText {
+            text: text.to_string(),
+        });
         tracing::warn!("NativeShare::share_text called on stub bridge");
         Err(PresswerkError::PlatformUnavailable)
     }
@@ -74,67 +270,78 @@ fn share_text(&self, _text: &str) -> Result<()> {
 
 impl NativeUsbPrint for StubBridge {
     fn detect_usb_printers(&self) -> Result<Vec<UsbPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_usb_printers")
     }
 
     fn print_usb(&self, _device_id: &str, _document: &[u8], _mime_type: &str) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_usb")
     }
 }
 
 impl NativeBluetoothPrint for StubBridge {
     fn scan_bluetooth_printers(&self) -> Result<Vec<BluetoothPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("scan_bluetooth_printers")
     }
 
     fn print_bluetooth(&self, _device_id: &str, _document: &[u8]) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_bluetooth")
     }
 }
 
 impl NativeNfcPrint for StubBridge {
     fn read_nfc_printer_tag(&self) -> Result<Option<NfcPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("read_nfc_printer_tag")
     }
 }
 
 impl NativeConnectivity for StubBridge {
     fn wifi_ssid(&self) -> Result<Option<String>> {
-        Err(PresswerkError::PlatformUnavailable)
+        match &self.wifi_ssid {
+            Some(ssid) => {
+                self.record(BridgeCall::Other("wifi_ssid"));
+                Ok(ssid.clone())
+            }
+            None => self.unavailable("wifi_ssid"),
+        }
     }
 
     fn supports_wifi_direct(&self) -> bool {
+        self.record(BridgeCall::Other("supports_wifi_direct"));
         false
     }
 
     fn discover_wifi_direct_printers(&self) -> Result<Vec<WifiDirectPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("discover_wifi_direct_printers")
+    }
+
+    fn is_metered(&self) -> Result<bool> {
+        self.unavailable("is_metered")
     }
 }
 
 impl NativeFireWirePrint for StubBridge {
     fn detect_firewire_printers(&self) -> Result<Vec<FireWirePrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_firewire_printers")
     }
 
     fn print_firewire(&self, _device_id: &str, _document: &[u8], _mime_type: &str) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_firewire")
     }
 }
 
 impl NativeLightningPrint for StubBridge {
     fn detect_lightning_printers(&self) -> Result<Vec<LightningPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_lightning_printers")
     }
 
     fn print_lightning(&self, _device_id: &str, _document: &[u8], _mime_type: &str) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_lightning")
     }
 }
 
 impl NativeThunderboltPrint for StubBridge {
     fn detect_thunderbolt_printers(&self) -> Result<Vec<ThunderboltPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_thunderbolt_printers")
     }
 
     fn print_thunderbolt(
@@ -143,59 +350,59 @@ fn print_thunderbolt(
         _document: &[u8],
         _mime_type: &str,
     ) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_thunderbolt")
     }
 }
 
 impl NativeSerialPrint for StubBridge {
     fn detect_serial_printers(&self) -> Result<Vec<SerialPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_serial_printers")
     }
 
     fn print_serial(&self, _port: &str, _baud_rate: u32, _document: &[u8]) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_serial")
     }
 }
 
 impl NativeParallelPrint for StubBridge {
     fn detect_parallel_printers(&self) -> Result<Vec<ParallelPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_parallel_printers")
     }
 
     fn print_parallel(&self, _port: &str, _document: &[u8]) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_parallel")
     }
 }
 
 impl NativeInfraredPrint for StubBridge {
     fn scan_infrared_printers(&self) -> Result<Vec<InfraredPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("scan_infrared_printers")
     }
 
     fn print_infrared(&self, _device_id: &str, _document: &[u8]) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_infrared")
     }
 }
 
 impl NativeIBeaconDiscover for StubBridge {
     fn scan_ibeacon_printers(&self) -> Result<Vec<IBeaconPrinterInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("scan_ibeacon_printers")
     }
 }
 
 impl NativeLiFiPrint for StubBridge {
     fn detect_lifi_endpoints(&self) -> Result<Vec<LiFiEndpointInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_lifi_endpoints")
     }
 
     fn print_lifi(&self, _endpoint_id: &str, _document: &[u8]) -> Result<()> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("print_lifi")
     }
 }
 
 impl NativeUsbDrivePrint for StubBridge {
     fn detect_usb_drives(&self) -> Result<Vec<UsbDriveInfo>> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("detect_usb_drives")
     }
 
     fn copy_to_usb_drive(
@@ -204,6 +411,129 @@ fn copy_to_usb_drive(
         _document: &[u8],
         _filename: &str,
     ) -> Result<String> {
-        Err(PresswerkError::PlatformUnavailable)
+        self.unavailable("copy_to_usb_drive")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_are_recorded_across_clones() {
+        let stub = StubBridge::new().with_captured_image(Some(vec![0xFF, 0xD8]));
+        let bridge: Box<dyn PlatformBridge> = Box::new(stub.clone());
+
+        assert_eq!(bridge.capture_image().unwrap(), Some(vec![0xFF, 0xD8]));
+        assert!(
+            bridge
+                .print_usb("usb-1", b"doc", "application/pdf")
+                .is_err()
+        );
+
+        assert_eq!(stub.call_count(&BridgeCall::CaptureImage), 1);
+        assert_eq!(
+            stub.calls(),
+            vec![BridgeCall::CaptureImage, BridgeCall::Other("print_usb")]
+        );
+    }
+
+    #[test]
+    fn call_log_keeps_only_recent_calls() {
+        let stub = StubBridge::new();
+        for _ in 0..MAX_RECORDED_CALLS {
+            stub.record(BridgeCall::Other("first"));
+        }
+        stub.record(BridgeCall::CaptureImage);
+
+        let calls = stub.calls();
+        assert_eq!(calls.len(), MAX_RECORDED_CALLS);
+        assert_eq!(calls.last(), Some(&BridgeCall::CaptureImage));
+        assert_eq!(
+            stub.call_count(&BridgeCall::Other("first")),
+            MAX_RECORDED_CALLS - 1
+        );
+    }
+
+    #[test]
+    fn share_with_result_reports_cancelled() {
+        let stub = StubBridge::new();
+        let bridge: Box<dyn PlatformBridge> = Box::new(stub.clone());
+
+        let outcome = bridge
+            .share_file_with_result("/tmp/report.pdf", "application/pdf")
+            .unwrap();
+
+        assert_eq!(outcome, ShareOutcome::Cancelled);
+        assert_eq!(
+            stub.call_count(&BridgeCall::ShareFile {
+                path: "/tmp/report.pdf".into(),
+                mime_type: "application/pdf".into(),
+            }),
+            1
+        );
+    }
+
+    #[test]
+    fn canned_picker_answers_and_defaults() {
+        let stub = StubBridge::new()
+            .with_picked_file(Some("/tmp/report.pdf".into()))
+            .with_picked_file_contents(b"%PDF-1.7".to_vec());
+        assert_eq!(
+            stub.pick_file(&["application/pdf"]).unwrap().as_deref(),
+            Some("/tmp/report.pdf")
+        );
+        assert_eq!(
+            stub.read_picked_file("/tmp/report.pdf").unwrap(),
+            b"%PDF-1.7"
+        );
+
+        let cancelled = StubBridge::new().with_picked_file(None);
+        assert_eq!(cancelled.pick_file(&[]).unwrap(), None);
+
+        let bare = StubBridge::new();
+        assert!(matches!(
+            bare.capture_image(),
+            Err(PresswerkError::PlatformUnavailable)
+        ));
+        assert!(bare.wifi_ssid().is_err());
+    }
+
+    #[test]
+    fn permissions_default_to_granted() {
+        let stub =
+            StubBridge::new().with_permission(Permission::LocalNetwork, PermissionState::Denied);
+        assert_eq!(
+            stub.permission_status(Permission::LocalNetwork),
+            PermissionState::Denied
+        );
+        assert_eq!(
+            stub.permission_status(Permission::Camera),
+            PermissionState::Granted
+        );
+    }
+
+    #[test]
+    fn permission_requests_answer_through_the_callback() {
+        let stub =
+            StubBridge::new().with_permission(Permission::Bluetooth, PermissionState::Denied);
+        let answers = Arc::new(Mutex::new(Vec::new()));
+
+        for permission in [Permission::Camera, Permission::Bluetooth] {
+            let answers = Arc::clone(&answers);
+            stub.request_permission(
+                permission,
+                Box::new(move |state| answers.lock().unwrap().push((permission, state))),
+            );
+        }
+
+        assert_eq!(
+            *answers.lock().unwrap(),
+            [
+                (Permission::Camera, PermissionState::Granted),
+                (Permission::Bluetooth, PermissionState::Denied),
+            ]
+        );
+        assert_eq!(stub.call_count(&BridgeCall::Other("request_permission")), 2);
     }
 }
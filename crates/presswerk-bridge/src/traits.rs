@@ -7,6 +7,10 @@
 // provide abstractions for platform-specific implementations.
 
 use presswerk_core::error::Result;
+pub use presswerk_core::types::{Permission, PermissionState};
+
+/// Receives the answer to [`PlatformBridge::request_permission`].
+pub type PermissionCallback = Box<dyn FnOnce(PermissionState) + Send + 'static>;
 
 /// Unified bridge that groups all native capabilities.
 ///
@@ -35,6 +39,21 @@ pub trait PlatformBridge:
 {
     /// Human-readable platform name (e.g. "iOS 17", "Android 14").
     fn platform_name(&self) -> &str;
+
+    /// Whether the OS currently grants `permission` to the app.
+    ///
+    /// Platforms without runtime permissions report
+    /// [`PermissionState::Granted`].
+    fn permission_status(&self, permission: Permission) -> PermissionState;
+
+    /// Ask the user for `permission`, showing the OS prompt if needed.
+    ///
+    /// The prompt is answered on the platform's UI thread, so the result
+    /// arrives later through `on_result` rather than as a return value.
+    /// `on_result` is called exactly once — immediately when no prompt is
+    /// needed — and gets [`PermissionState::Unknown`] if the prompt could not
+    /// be shown.
+    fn request_permission(&self, permission: Permission, on_result: PermissionCallback);
 }
 
 /// Send documents to the OS-level print dialog.
@@ -78,10 +97,38 @@ pub trait NativeShare {
     /// Share a file with other apps via the native share sheet.
     fn share_file(&self, path: &str, mime_type: &str) -> Result<()>;
 
+    /// Like [`share_file`](Self::share_file), but block until the user has
+    /// picked a target or backed out, and report which.
+    ///
+    /// * iOS reads the answer from `UIActivityViewController`'s
+    ///   `completionWithItemsHandler`; the target is the chosen activity
+    ///   type (a bundle identifier for app extensions).
+    /// * Android passes `Intent.createChooser` a `PendingIntent` for
+    ///   `org.presswerk.PresswerkShareReceiver`, which reports the chosen
+    ///   component's package.  The chooser's own result does not say
+    ///   whether anything was chosen, so without that report the outcome is
+    ///   [`ShareOutcome::Unknown`].
+    fn share_file_with_result(&self, path: &str, mime_type: &str) -> Result<ShareOutcome>;
+
     /// Share text content (e.g. diagnostic report summary).
     fn share_text(&self, text: &str) -> Result<()>;
 }
 
+/// How a share sheet was closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareOutcome {
+    /// The user sent the file to another app.
+    Completed {
+        /// Bundle or package id of the chosen app, when the OS reports it.
+        target: Option<String>,
+    },
+    /// The user dismissed the share sheet without choosing anything.
+    Cancelled,
+    /// The share sheet closed, but the OS did not say whether a target was
+    /// chosen.
+    Unknown,
+}
+
 /// Print via USB connection (OTG on mobile, direct on desktop).
 pub trait NativeUsbPrint {
     /// Detect USB-connected printers.
@@ -116,6 +163,10 @@ pub trait NativeConnectivity {
 
     /// Discover printers via Wi-Fi Direct.
     fn discover_wifi_direct_printers(&self) -> Result<Vec<WifiDirectPrinterInfo>>;
+
+    /// Whether the active network is metered (typically mobile data), so
+    /// large uploads should be throttled.
+    fn is_metered(&self) -> Result<bool>;
 }
 
 /// Print via FireWire (IEEE 1394) — legacy high-speed connection.
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Platform-neutral USB helpers shared by the native bridges.
+//
+// The platform code only gathers raw descriptor fields (over JNI on
+// Android); deciding whether a device is a printer and what to call it
+// happens here so it can be tested on any host.
+
+use crate::traits::UsbPrinterInfo;
+
+/// USB class code for printers (`USB_CLASS_PRINTER`).
+pub const USB_CLASS_PRINTER: i32 = 7;
+
+/// Raw fields read from a USB device descriptor.
+#[derive(Debug, Clone, Default)]
+pub struct UsbDeviceDescriptor {
+    /// Platform device path, e.g. `/dev/bus/usb/001/002` on Android.
+    pub device_name: String,
+    pub vendor_id: i32,
+    pub product_id: i32,
+    /// Device-level class; usually 0, meaning "defined per interface".
+    pub device_class: i32,
+    /// Class of each interface the device exposes.
+    pub interface_classes: Vec<i32>,
+    pub manufacturer_name: Option<String>,
+    pub product_name: Option<String>,
+}
+
+impl UsbDeviceDescriptor {
+    /// Whether the device, or any of its interfaces, is a printer.
+    pub fn is_printer(&self) -> bool {
+        self.device_class == USB_CLASS_PRINTER
+            || self.interface_classes.contains(&USB_CLASS_PRINTER)
+    }
+}
+
+/// Turn a descriptor into [`UsbPrinterInfo`], or `None` if it is not a
+/// printer.
+///
+/// The display name is "<manufacturer> <product>" when the device reports
+/// them, falling back to the vendor and product ids.
+pub fn printer_info(descriptor: &UsbDeviceDescriptor) -> Option<UsbPrinterInfo> {
+    if !descriptor.is_printer() {
+        return None;
+    }
+
+    let vendor_id = descriptor.vendor_id as u16;
+    let product_id = descriptor.product_id as u16;
+    let parts: Vec<&str> = [&descriptor.manufacturer_name, &descriptor.product_name]
+        .into_iter()
+        .flatten()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let name = if parts.is_empty() {
+        format!("USB printer {vendor_id:04x}:{product_id:04x}")
+    } else {
+        parts.join(" ")
+    };
+
+    Some(UsbPrinterInfo {
+        device_id: descriptor.device_name.clone(),
+        name,
+        vendor_id,
+        product_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn printer_interface_is_detected_and_named() {
+        let descriptor = UsbDeviceDescriptor {
+            device_name: "/dev/bus/usb/001/004".into(),
+            vendor_id: 0x04b8,
+            product_id: 0x0e28,
+            interface_classes: vec![0xff, USB_CLASS_PRINTER],
+            manufacturer_name: Some("EPSON".into()),
+            product_name: Some(" ET-2810 Series ".into()),
+            ..Default::default()
+        };
+
+        let info = printer_info(&descriptor).expect("is a printer");
+        assert_eq!(info.device_id, "/dev/bus/usb/001/004");
+        assert_eq!(info.name, "EPSON ET-2810 Series");
+        assert_eq!((info.vendor_id, info.product_id), (0x04b8, 0x0e28));
+    }
+
+    #[test]
+    fn non_printers_are_skipped_and_unnamed_printers_use_ids() {
+        let keyboard = UsbDeviceDescriptor {
+            interface_classes: vec![3],
+            ..Default::default()
+        };
+        assert!(printer_info(&keyboard).is_none());
+
+        let bare = UsbDeviceDescriptor {
+            vendor_id: 0x03f0,
+            product_id: 0x0c17,
+            device_class: USB_CLASS_PRINTER,
+            product_name: Some("  ".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            printer_info(&bare).expect("is a printer").name,
+            "USB printer 03f0:0c17"
+        );
+    }
+}
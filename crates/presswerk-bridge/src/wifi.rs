@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Platform-neutral Wi-Fi helpers shared by the native bridges.
+
+/// Placeholder Android reports when the SSID is hidden from the app (no
+/// location permission, location services off, or not connected).
+pub const UNKNOWN_SSID: &str = "<unknown ssid>";
+
+/// Clean up an SSID as reported by the platform.
+///
+/// Android wraps UTF-8 SSIDs in double quotes and reports unreadable ones as
+/// [`UNKNOWN_SSID`] (or an empty string); those come back as `None`.
+pub fn normalize_ssid(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let ssid = raw
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(raw);
+
+    if ssid.is_empty() || raw == UNKNOWN_SSID {
+        None
+    } else {
+        Some(ssid.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssid_quotes_are_stripped_and_placeholders_dropped() {
+        assert_eq!(
+            normalize_ssid("\"Home Wi-Fi\"").as_deref(),
+            Some("Home Wi-Fi")
+        );
+        assert_eq!(normalize_ssid("0x4a6f").as_deref(), Some("0x4a6f"));
+        assert_eq!(
+            normalize_ssid("\"say \"hi\"\"").as_deref(),
+            Some("say \"hi\"")
+        );
+        assert_eq!(normalize_ssid(UNKNOWN_SSID), None);
+        assert_eq!(normalize_ssid("\"\""), None);
+        assert_eq!(normalize_ssid(""), None);
+    }
+}
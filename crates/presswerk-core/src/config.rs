@@ -2,18 +2,40 @@
 // Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
 //
 // Application configuration.
+//
+// Config files carry a `version`.  Loading a file written by an older build
+// first migrates it step by step to `CONFIG_VERSION` (files without a
+// version are version 1), then fills any key it still lacks from the
+// defaults and checks the values are in range.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::error::{PresswerkError, Result};
+use crate::logging::LogLevel;
+use crate::types::{DiscoveredPrinter, PrintSettings};
+
+/// Version of the config format this build writes.
+pub const CONFIG_VERSION: u32 = 2;
 
 /// Persistent application settings.
+///
+/// Keys missing from a stored config take their [`Default`] values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Format version, so files from older builds can be migrated.
+    pub version: u32,
     /// Default paper size for new print jobs.
     pub default_paper_size: crate::PaperSize,
     /// Whether the IPP print server starts automatically on launch.
     pub auto_start_server: bool,
     /// Port for the IPP print server (default 631).
-    pub server_port: u16,
+    pub ipp_server_port: u16,
     /// Require TLS for print server connections.
     pub server_require_tls: bool,
     /// Auto-accept incoming network print jobs (if false, jobs are held for review).
@@ -28,14 +50,30 @@ pub struct AppConfig {
     pub query_timeout_secs: u64,
     /// Whether Easy Mode is the default interface.
     pub easy_mode: bool,
+    /// Browse for printers via mDNS as soon as the app starts.
+    pub auto_discovery: bool,
+    /// Directory holding the OCR models; `None` uses the OCR engine's
+    /// default cache directory.
+    pub ocr_model_dir: Option<PathBuf>,
+    /// Wait for the printer to report a job finished before marking it
+    /// Completed, instead of trusting that an accepted job prints.
+    pub confirm_completion: bool,
+    /// How much the app logs; applied at once when settings are saved.
+    /// `RUST_LOG`, if set, wins at startup.
+    pub log_level: LogLevel,
+    /// User-chosen printer nicknames, keyed by printer UUID or URI.
+    pub printer_aliases: HashMap<String, String>,
+    /// Saved print settings per printer, keyed by printer URI.
+    pub printer_defaults: HashMap<String, PrintSettings>,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             default_paper_size: crate::PaperSize::A4,
             auto_start_server: false,
-            server_port: 631,
+            ipp_server_port: 631,
             server_require_tls: true,
             auto_accept_network_jobs: false,
             audit_enabled: true,
@@ -43,6 +81,367 @@ fn default() -> Self {
             print_timeout_secs: 60,
             query_timeout_secs: 15,
             easy_mode: true,
+            auto_discovery: true,
+            ocr_model_dir: None,
+            confirm_completion: false,
+            log_level: LogLevel::default(),
+            printer_aliases: HashMap::new(),
+            printer_defaults: HashMap::new(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Read the config stored at `path`, or the defaults if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read, is not a config, or holds values
+    /// out of range, so a damaged file is reported rather than silently
+    /// replaced.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match std::fs::read(path) {
+            Ok(data) => Self::from_json(&data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the config to `path` as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.validate()?;
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Parse a stored config, migrating it from older versions and filling
+    /// missing keys with their defaults.
+    pub fn from_json(data: &[u8]) -> Result<Self> {
+        let mut value: Value = serde_json::from_slice(data)?;
+        let Some(fields) = value.as_object_mut() else {
+            return Err(PresswerkError::InvalidConfig(
+                "config is not a JSON object".into(),
+            ));
+        };
+        migrate(fields)?;
+        check_port(fields.get("ipp_server_port"))?;
+
+        let config: Self = serde_json::from_value(value)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The config as pretty-printed JSON, for storing.
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Check every value is in range.
+    pub fn validate(&self) -> Result<()> {
+        if self.version != CONFIG_VERSION {
+            return Err(PresswerkError::InvalidConfig(format!(
+                "config version {} is not {CONFIG_VERSION}",
+                self.version
+            )));
+        }
+        if self.ipp_server_port == 0 {
+            return Err(PresswerkError::InvalidConfig(
+                "IPP server port must be between 1 and 65535".into(),
+            ));
+        }
+        for (name, secs) in [
+            ("print timeout", self.print_timeout_secs),
+            ("query timeout", self.query_timeout_secs),
+        ] {
+            if secs == 0 {
+                return Err(PresswerkError::InvalidConfig(format!(
+                    "{name} must be at least one second"
+                )));
+            }
         }
+        Ok(())
+    }
+
+    /// Give the printer identified by `key` (its UUID or URI) a nickname.
+    ///
+    /// A blank alias removes the nickname.
+    pub fn set_alias(&mut self, key: impl Into<String>, alias: &str) {
+        let key = key.into();
+        let alias = alias.trim();
+        if alias.is_empty() {
+            self.printer_aliases.remove(&key);
+        } else {
+            self.printer_aliases.insert(key, alias.to_string());
+        }
+    }
+
+    /// The nickname stored under `key`, if any.
+    pub fn alias_for(&self, key: &str) -> Option<&str> {
+        self.printer_aliases.get(key).map(String::as_str)
+    }
+
+    /// The name to show for `printer`: its alias if one is set (by UUID,
+    /// then by URI), otherwise the discovered name.
+    pub fn display_name<'a>(&'a self, printer: &'a DiscoveredPrinter) -> &'a str {
+        printer
+            .uuid
+            .as_deref()
+            .and_then(|uuid| self.alias_for(uuid))
+            .or_else(|| self.alias_for(&printer.uri))
+            .unwrap_or(&printer.name)
+    }
+
+    /// Remember `settings` as the defaults for the printer at `uri`.
+    ///
+    /// The page range belongs to one document, so it is not saved.
+    pub fn set_printer_defaults(&mut self, uri: impl Into<String>, settings: &PrintSettings) {
+        let settings = PrintSettings {
+            page_range: None,
+            ..settings.clone()
+        };
+        self.printer_defaults.insert(uri.into(), settings);
+    }
+
+    /// The settings to start from when printing to the printer at `uri`: its
+    /// saved defaults, otherwise the global defaults.
+    pub fn defaults_for(&self, uri: &str) -> PrintSettings {
+        self.printer_defaults
+            .get(uri)
+            .cloned()
+            .unwrap_or_else(|| PrintSettings {
+                paper_size: self.default_paper_size,
+                ..PrintSettings::default()
+            })
+    }
+}
+
+// -- Migration ----------------------------------------------------------------
+
+/// Bring the keys of a stored config up to [`CONFIG_VERSION`].
+fn migrate(fields: &mut serde_json::Map<String, Value>) -> Result<()> {
+    let from = match fields.get("version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                PresswerkError::InvalidConfig(format!("bad config version {version}"))
+            })?,
+    };
+    if from > CONFIG_VERSION {
+        return Err(PresswerkError::InvalidConfig(format!(
+            "config version {from} is newer than this app ({CONFIG_VERSION})"
+        )));
+    }
+
+    if from < 2 {
+        // Version 2 renamed `server_port`.
+        if let Some(port) = fields.remove("server_port") {
+            fields.insert("ipp_server_port".into(), port);
+        }
+    }
+
+    if from != CONFIG_VERSION {
+        info!(from, to = CONFIG_VERSION, "config migrated");
+    }
+    fields.insert("version".into(), CONFIG_VERSION.into());
+    Ok(())
+}
+
+/// Check the stored port before it is narrowed to a `u16`, so 70000 is
+/// reported as out of range rather than as a type error.
+fn check_port(port: Option<&Value>) -> Result<()> {
+    match port {
+        None => Ok(()),
+        Some(value) => match value.as_u64() {
+            Some(1..=65535) => Ok(()),
+            _ => Err(PresswerkError::InvalidConfig(format!(
+                "IPP server port {value} is not between 1 and 65535"
+            ))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printer() -> DiscoveredPrinter {
+        DiscoveredPrinter {
+            name: "HP1A2B3C".into(),
+            uri: "ipp://192.168.1.20:631/ipp/print".into(),
+            ip: "192.168.1.20".parse().unwrap(),
+            port: 631,
+            make_and_model: None,
+            location: None,
+            supports_color: false,
+            supports_duplex: false,
+            supports_tls: false,
+            paper_sizes: Vec::new(),
+            last_seen: chrono::Utc::now(),
+            stale: false,
+            manually_added: false,
+            addresses: Vec::new(),
+            uuid: None,
+            document_formats: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn alias_round_trips_and_wins_over_discovered_name() {
+        let mut printer = printer();
+        let mut config = AppConfig::default();
+        assert_eq!(config.display_name(&printer), "HP1A2B3C");
+
+        config.set_alias(printer.uri.clone(), " Downstairs Printer ");
+        assert_eq!(config.alias_for(&printer.uri), Some("Downstairs Printer"));
+        assert_eq!(config.display_name(&printer), "Downstairs Printer");
+
+        // An alias under the printer's UUID follows it to a new address.
+        printer.uuid = Some("4509a320-00a0-008f-00b6-002507510eca".into());
+        printer.uri = "ipp://192.168.1.21:631/ipp/print".into();
+        config.set_alias("4509a320-00a0-008f-00b6-002507510eca", "Office");
+        assert_eq!(config.display_name(&printer), "Office");
+
+        let json = serde_json::to_string(&config).unwrap();
+        let restored: AppConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.printer_aliases, config.printer_aliases);
+
+        config.set_alias("4509a320-00a0-008f-00b6-002507510eca", "");
+        assert_eq!(
+            config.alias_for("4509a320-00a0-008f-00b6-002507510eca"),
+            None
+        );
+    }
+
+    #[test]
+    fn printer_defaults_round_trip_per_printer() {
+        use crate::types::{DuplexMode, PageRange};
+
+        let office = "ipps://office.local:631/ipp/print";
+        let home = "ipp://192.168.1.20:631/ipp/print";
+        let mut config = AppConfig {
+            default_paper_size: crate::PaperSize::Letter,
+            ..AppConfig::default()
+        };
+
+        config.set_printer_defaults(
+            office,
+            &PrintSettings {
+                color: true,
+                duplex: DuplexMode::LongEdge,
+                page_range: PageRange::parse("1-2").ok(),
+                ..PrintSettings::default()
+            },
+        );
+        config.set_printer_defaults(
+            home,
+            &PrintSettings {
+                color: false,
+                duplex: DuplexMode::Simplex,
+                copies: 2,
+                ..PrintSettings::default()
+            },
+        );
+
+        let json = serde_json::to_string(&config).unwrap();
+        let restored: AppConfig = serde_json::from_str(&json).unwrap();
+
+        let office_settings = restored.defaults_for(office);
+        assert!(office_settings.color);
+        assert_eq!(office_settings.duplex, DuplexMode::LongEdge);
+        assert!(office_settings.page_range.is_none());
+
+        let home_settings = restored.defaults_for(home);
+        assert!(!home_settings.color);
+        assert_eq!(home_settings.duplex, DuplexMode::Simplex);
+        assert_eq!(home_settings.copies, 2);
+
+        // A printer without saved settings gets the global defaults.
+        let other = restored.defaults_for("ipp://10.0.0.9:631/ipp/print");
+        assert_eq!(other.paper_size, crate::PaperSize::Letter);
+        assert_eq!(other.copies, 1);
+    }
+
+    #[test]
+    fn config_without_aliases_still_loads() {
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        value.as_object_mut().unwrap().remove("printer_aliases");
+        value.as_object_mut().unwrap().remove("printer_defaults");
+
+        let config: AppConfig = serde_json::from_value(value).unwrap();
+        assert!(config.printer_aliases.is_empty());
+        assert!(config.printer_defaults.is_empty());
+    }
+
+    #[test]
+    fn missing_file_gives_valid_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        let config = AppConfig::load_or_default(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.ipp_server_port, 631);
+        assert!(config.auto_discovery);
+        assert_eq!(config.ocr_model_dir, None);
+        config.validate().unwrap();
+
+        config.save(&path).unwrap();
+        let reloaded = AppConfig::load_or_default(&path).unwrap();
+        assert_eq!(reloaded.to_json().unwrap(), config.to_json().unwrap());
+    }
+
+    #[test]
+    fn out_of_range_port_is_rejected() {
+        for port in ["0", "65536", "-1", "\"631\""] {
+            let json = format!(r#"{{"version": 2, "ipp_server_port": {port}}}"#);
+            let err = AppConfig::from_json(json.as_bytes()).unwrap_err();
+            assert!(
+                matches!(err, PresswerkError::InvalidConfig(_)),
+                "{port}: {err}"
+            );
+        }
+
+        let config = AppConfig {
+            ipp_server_port: 0,
+            ..AppConfig::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        assert!(config.save(dir.path().join("config.json")).is_err());
+    }
+
+    #[test]
+    fn version_1_file_is_migrated() {
+        // As written before the config had a version.
+        let v1 = r#"{
+            "default_paper_size": "Letter",
+            "auto_start_server": true,
+            "server_port": 8631,
+            "server_require_tls": true,
+            "auto_accept_network_jobs": false,
+            "audit_enabled": true,
+            "encryption_enabled": true,
+            "print_timeout_secs": 90,
+            "query_timeout_secs": 15,
+            "easy_mode": false
+        }"#;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, v1).unwrap();
+
+        let config = AppConfig::load_or_default(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.ipp_server_port, 8631);
+        assert_eq!(config.default_paper_size, crate::PaperSize::Letter);
+        assert_eq!(config.print_timeout_secs, 90);
+        assert!(!config.easy_mode);
+        // Keys added since take their defaults.
+        assert!(config.auto_discovery);
+        assert_eq!(config.ocr_model_dir, None);
+        assert_eq!(config.log_level, LogLevel::Info);
+
+        let newer = br#"{"version": 3}"#;
+        assert!(AppConfig::from_json(newer).is_err());
     }
 }
@@ -15,12 +15,31 @@ pub enum PresswerkError {
     #[error("IPP request failed: {0}")]
     IppRequest(String),
 
+    /// An IPP request never reached an IPP server: the printer could not be
+    /// connected to, or answered something other than IPP.
+    #[error("IPP request failed: {0}")]
+    IppUnreachable(String),
+
+    /// The printer answered an IPP request with a failing status code.
+    #[error("IPP request failed: {detail}")]
+    IppStatus {
+        /// Status code as sent, e.g. 0x040A.
+        code: u16,
+        /// Status-code name, e.g. "ClientErrorDocumentFormatNotSupported".
+        status: String,
+        /// What was asked and what came back, for messages.
+        detail: String,
+    },
+
     #[error("print server error: {0}")]
     PrintServer(String),
 
     #[error("no printer selected")]
     NoPrinterSelected,
 
+    #[error("invalid job id: {0}")]
+    InvalidJobId(String),
+
     // -- Document errors --
     #[error("unsupported document type: {0}")]
     UnsupportedDocument(String),
@@ -34,6 +53,9 @@ pub enum PresswerkError {
     #[error("OCR failed: {0}")]
     OcrError(String),
 
+    #[error("invalid page range: {0}")]
+    InvalidPageRange(String),
+
     // -- Security errors --
     #[error("encryption failed: {0}")]
     Encryption(String),
@@ -57,12 +79,18 @@ pub enum PresswerkError {
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+
     // -- Platform bridge --
     #[error("platform bridge error: {0}")]
     Bridge(String),
 
     #[error("feature not available on this platform")]
     PlatformUnavailable,
+
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
 }
 
 /// Alias used throughout the codebase.
@@ -3,10 +3,16 @@
 //
 // Human-readable error messages for vulnerable users (elderly, children).
 //
-// Every technical error is mapped to plain English with a clear suggestion.
-// The taxonomy uses four severity levels that drive UI presentation.
+// Every technical error is mapped to a stable `ErrorCode`, and every code to
+// a plain-language message with a clear suggestion, looked up per language in
+// `CATALOGUE`.  The taxonomy uses four severity levels that drive UI
+// presentation.
 
 use crate::error::PresswerkError;
+use crate::types::{ErrorClass, squash_ipp_detail};
+
+/// Language used when a message has no translation.
+pub const DEFAULT_LANGUAGE: &str = "en";
 
 /// Severity of an error from the user's perspective.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,9 +27,295 @@ pub enum Severity {
     BuyRequired,
 }
 
+/// Stable identifier for each error the user can be shown.
+///
+/// Codes never change meaning, so they can key translations, support
+/// articles and analytics.  [`as_str`](Self::as_str) gives the code's
+/// string form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    // -- Printers and network --
+    DiscoveryUnavailable,
+    NoPrintersFound,
+    PrinterTimedOut,
+    PrinterRefused,
+    ConnectionInterrupted,
+    PrinterInternalError,
+    SettingsNotSupported,
+    FileTypeNotSupported,
+    InvalidPrinterAddress,
+    OutOfPaper,
+    OutOfInk,
+    CoverOpen,
+    PaperJam,
+    PrinterProblem,
+    PrintServerFailed,
+    NoPrinterSelected,
+    JobNotFound,
+    // -- Documents --
+    UnsupportedDocument,
+    DamagedPdf,
+    DamagedImage,
+    TextRecognitionFailed,
+    InvalidPageRange,
+    // -- Security --
+    SecurityProblem,
+    FileChanged,
+    SecureConnectionFailed,
+    // -- Storage --
+    StorageProblem,
+    FileNotFound,
+    FileAccessDenied,
+    FileProblem,
+    InternalDataProblem,
+    // -- Platform --
+    DeviceFeatureFailed,
+    FeatureUnavailable,
+    PermissionDenied,
+}
+
+impl ErrorCode {
+    /// Every code, in declaration order.
+    pub const ALL: [Self; 33] = [
+        Self::DiscoveryUnavailable,
+        Self::NoPrintersFound,
+        Self::PrinterTimedOut,
+        Self::PrinterRefused,
+        Self::ConnectionInterrupted,
+        Self::PrinterInternalError,
+        Self::SettingsNotSupported,
+        Self::FileTypeNotSupported,
+        Self::InvalidPrinterAddress,
+        Self::OutOfPaper,
+        Self::OutOfInk,
+        Self::CoverOpen,
+        Self::PaperJam,
+        Self::PrinterProblem,
+        Self::PrintServerFailed,
+        Self::NoPrinterSelected,
+        Self::JobNotFound,
+        Self::UnsupportedDocument,
+        Self::DamagedPdf,
+        Self::DamagedImage,
+        Self::TextRecognitionFailed,
+        Self::InvalidPageRange,
+        Self::SecurityProblem,
+        Self::FileChanged,
+        Self::SecureConnectionFailed,
+        Self::StorageProblem,
+        Self::FileNotFound,
+        Self::FileAccessDenied,
+        Self::FileProblem,
+        Self::InternalDataProblem,
+        Self::DeviceFeatureFailed,
+        Self::FeatureUnavailable,
+        Self::PermissionDenied,
+    ];
+
+    /// The code's stable string form, e.g. `"out-of-paper"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DiscoveryUnavailable => "discovery-unavailable",
+            Self::NoPrintersFound => "no-printers-found",
+            Self::PrinterTimedOut => "printer-timed-out",
+            Self::PrinterRefused => "printer-refused",
+            Self::ConnectionInterrupted => "connection-interrupted",
+            Self::PrinterInternalError => "printer-internal-error",
+            Self::SettingsNotSupported => "settings-not-supported",
+            Self::FileTypeNotSupported => "file-type-not-supported",
+            Self::InvalidPrinterAddress => "invalid-printer-address",
+            Self::OutOfPaper => "out-of-paper",
+            Self::OutOfInk => "out-of-ink",
+            Self::CoverOpen => "cover-open",
+            Self::PaperJam => "paper-jam",
+            Self::PrinterProblem => "printer-problem",
+            Self::PrintServerFailed => "print-server-failed",
+            Self::NoPrinterSelected => "no-printer-selected",
+            Self::JobNotFound => "job-not-found",
+            Self::UnsupportedDocument => "unsupported-document",
+            Self::DamagedPdf => "damaged-pdf",
+            Self::DamagedImage => "damaged-image",
+            Self::TextRecognitionFailed => "text-recognition-failed",
+            Self::InvalidPageRange => "invalid-page-range",
+            Self::SecurityProblem => "security-problem",
+            Self::FileChanged => "file-changed",
+            Self::SecureConnectionFailed => "secure-connection-failed",
+            Self::StorageProblem => "storage-problem",
+            Self::FileNotFound => "file-not-found",
+            Self::FileAccessDenied => "file-access-denied",
+            Self::FileProblem => "file-problem",
+            Self::InternalDataProblem => "internal-data-problem",
+            Self::DeviceFeatureFailed => "device-feature-failed",
+            Self::FeatureUnavailable => "feature-unavailable",
+            Self::PermissionDenied => "permission-denied",
+        }
+    }
+
+    /// The code for `err`, with the detail (if any) its suggestion quotes.
+    pub fn for_error(err: &PresswerkError) -> (Self, Option<&str>) {
+        match err {
+            // -- Print errors --
+            PresswerkError::Discovery(detail) => {
+                if detail.contains("daemon") || detail.contains("multicast") {
+                    (Self::DiscoveryUnavailable, None)
+                } else {
+                    (Self::NoPrintersFound, None)
+                }
+            }
+            PresswerkError::IppRequest(detail)
+            | PresswerkError::IppUnreachable(detail)
+            | PresswerkError::IppStatus { detail, .. } => match Self::for_ipp_detail(detail) {
+                Self::PrinterProblem => (Self::PrinterProblem, Some(detail)),
+                code => (code, None),
+            },
+            PresswerkError::PrintServer(detail) => (Self::PrintServerFailed, Some(detail)),
+            PresswerkError::NoPrinterSelected => (Self::NoPrinterSelected, None),
+            PresswerkError::InvalidJobId(detail) => (Self::JobNotFound, Some(detail)),
+
+            // -- Document errors --
+            PresswerkError::UnsupportedDocument(detail) => {
+                (Self::UnsupportedDocument, Some(detail))
+            }
+            PresswerkError::PdfError(_) => (Self::DamagedPdf, None),
+            PresswerkError::ImageError(_) => (Self::DamagedImage, None),
+            PresswerkError::OcrError(_) => (Self::TextRecognitionFailed, None),
+            PresswerkError::InvalidPageRange(detail) => (Self::InvalidPageRange, Some(detail)),
+
+            // -- Security errors --
+            PresswerkError::Encryption(_) | PresswerkError::Decryption(_) => {
+                (Self::SecurityProblem, None)
+            }
+            PresswerkError::IntegrityMismatch { .. } => (Self::FileChanged, None),
+            PresswerkError::Certificate(_) => (Self::SecureConnectionFailed, None),
+
+            // -- Storage --
+            PresswerkError::Database(_) => (Self::StorageProblem, None),
+            PresswerkError::Io(io_err) => match io_err.kind() {
+                std::io::ErrorKind::NotFound => (Self::FileNotFound, None),
+                std::io::ErrorKind::PermissionDenied => (Self::FileAccessDenied, None),
+                _ => (Self::FileProblem, None),
+            },
+            PresswerkError::Serialization(_) | PresswerkError::InvalidConfig(_) => {
+                (Self::InternalDataProblem, None)
+            }
+
+            // -- Platform --
+            PresswerkError::Bridge(_) => (Self::DeviceFeatureFailed, None),
+            PresswerkError::PlatformUnavailable => (Self::FeatureUnavailable, None),
+            PresswerkError::PermissionDenied(_) => (Self::PermissionDenied, None),
+        }
+    }
+
+    /// Parse IPP-specific error details into a code.
+    ///
+    /// The code is picked within the class [`ErrorClass::classify`] gives the
+    /// same detail, so the message never contradicts the retry decision,
+    /// and keywords are matched the same way (whole status and reason
+    /// names, not fragments such as `ink` in `link`).
+    fn for_ipp_detail(detail: &str) -> Self {
+        let squashed = squash_ipp_detail(detail);
+        let mentions = |needles: &[&str]| needles.iter().any(|n| squashed.contains(n));
+
+        match ErrorClass::classify_ipp_detail(detail) {
+            ErrorClass::UserAction => {
+                if mentions(&["mediajam", "paperjam"]) {
+                    Self::PaperJam
+                } else if mentions(&["tonerempty", "inkempty", "markersupply"]) {
+                    Self::OutOfInk
+                } else if mentions(&["dooropen", "coveropen", "interlockopen"]) {
+                    Self::CoverOpen
+                } else {
+                    Self::OutOfPaper
+                }
+            }
+            ErrorClass::Permanent => {
+                if mentions(&["clienterrordocumentformat"]) {
+                    Self::FileTypeNotSupported
+                } else if mentions(&["invaliduri", "invalidurl"]) {
+                    Self::InvalidPrinterAddress
+                } else {
+                    Self::SettingsNotSupported
+                }
+            }
+            ErrorClass::Transient => {
+                if mentions(&["timedout"]) {
+                    Self::PrinterTimedOut
+                } else if mentions(&["connectionrefused"]) {
+                    Self::PrinterRefused
+                } else if mentions(&["connectionreset", "brokenpipe"]) {
+                    Self::ConnectionInterrupted
+                } else if mentions(&["servererror"]) {
+                    Self::PrinterInternalError
+                } else {
+                    Self::PrinterProblem
+                }
+            }
+        }
+    }
+
+    /// How the UI should present the error.
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::DiscoveryUnavailable
+            | Self::NoPrintersFound
+            | Self::PrinterTimedOut
+            | Self::PrinterRefused
+            | Self::ConnectionInterrupted
+            | Self::PrinterInternalError
+            | Self::PrinterProblem
+            | Self::PrintServerFailed
+            | Self::TextRecognitionFailed
+            | Self::SecureConnectionFailed
+            | Self::StorageProblem
+            | Self::FileProblem
+            | Self::InternalDataProblem
+            | Self::DeviceFeatureFailed => Severity::Transient,
+
+            Self::OutOfPaper
+            | Self::CoverOpen
+            | Self::PaperJam
+            | Self::NoPrinterSelected
+            | Self::JobNotFound
+            | Self::InvalidPageRange
+            | Self::FileNotFound
+            | Self::FileAccessDenied
+            | Self::PermissionDenied => Severity::ActionRequired,
+
+            Self::SettingsNotSupported
+            | Self::FileTypeNotSupported
+            | Self::InvalidPrinterAddress
+            | Self::UnsupportedDocument
+            | Self::DamagedPdf
+            | Self::DamagedImage
+            | Self::SecurityProblem
+            | Self::FileChanged
+            | Self::FeatureUnavailable => Severity::Permanent,
+
+            Self::OutOfInk => Severity::BuyRequired,
+        }
+    }
+
+    /// Whether trying again may help on its own.  Everything transient is,
+    /// and so is asking for a permission again.
+    pub fn retriable(self) -> bool {
+        self.severity() == Severity::Transient || self == Self::PermissionDenied
+    }
+
+    /// The retry class matching the code's severity.
+    pub fn class(self) -> ErrorClass {
+        match self.severity() {
+            Severity::Transient => ErrorClass::Transient,
+            Severity::ActionRequired | Severity::BuyRequired => ErrorClass::UserAction,
+            Severity::Permanent => ErrorClass::Permanent,
+        }
+    }
+}
+
 /// A human-readable error with plain English message and actionable suggestion.
 #[derive(Debug, Clone)]
 pub struct HumanError {
+    /// Stable code identifying the error.
+    pub code: ErrorCode,
     /// Plain English summary (shown as a heading).
     pub message: String,
     /// What the user should try (shown as body text).
@@ -36,242 +328,470 @@ pub struct HumanError {
 
 /// Convert a `PresswerkError` into a `HumanError` that a grandparent can understand.
 pub fn humanize_error(err: &PresswerkError) -> HumanError {
-    match err {
-        // -- Print errors --
-        PresswerkError::Discovery(detail) => {
-            if detail.contains("daemon") || detail.contains("multicast") {
-                HumanError {
-                    message: "We can't search for printers right now.".into(),
-                    suggestion: "Make sure you're connected to Wi-Fi, then try again.".into(),
-                    retriable: true,
-                    severity: Severity::Transient,
-                }
-            } else {
-                HumanError {
-                    message: "We couldn't find any printers.".into(),
-                    suggestion: "Make sure your printer is turned on and connected to the same Wi-Fi network as this device.".into(),
-                    retriable: true,
-                    severity: Severity::Transient,
-                }
-            }
-        }
-
-        PresswerkError::IppRequest(detail) => humanize_ipp_error(detail),
-
-        PresswerkError::PrintServer(detail) => HumanError {
-            message: "The print server had a problem.".into(),
-            suggestion: format!("Try restarting the print server. ({detail})"),
-            retriable: true,
-            severity: Severity::Transient,
-        },
-
-        PresswerkError::NoPrinterSelected => HumanError {
-            message: "No printer selected.".into(),
-            suggestion: "Please choose a printer from the list, then try again.".into(),
-            retriable: false,
-            severity: Severity::ActionRequired,
-        },
-
-        // -- Document errors --
-        PresswerkError::UnsupportedDocument(detail) => HumanError {
-            message: "This type of document isn't supported.".into(),
-            suggestion: format!("Try saving the file as a PDF first, then print the PDF. (File type: {detail})"),
-            retriable: false,
-            severity: Severity::Permanent,
-        },
-
-        PresswerkError::PdfError(_) => HumanError {
-            message: "There's a problem with this PDF file.".into(),
-            suggestion: "The file may be damaged. Try opening it on a computer first to check it works, or try a different file.".into(),
-            retriable: false,
-            severity: Severity::Permanent,
-        },
-
-        PresswerkError::ImageError(_) => HumanError {
-            message: "There's a problem with this image.".into(),
-            suggestion: "The image may be damaged or in an unusual format. Try saving it as a JPEG or PNG first.".into(),
-            retriable: false,
-            severity: Severity::Permanent,
-        },
-
-        PresswerkError::OcrError(_) => HumanError {
-            message: "Text recognition didn't work on this scan.".into(),
-            suggestion: "Try scanning the document again with better lighting, making sure the text is clear and in focus.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        },
-
-        // -- Security errors --
-        PresswerkError::Encryption(_) | PresswerkError::Decryption(_) => HumanError {
-            message: "There was a security problem.".into(),
-            suggestion: "The app's secure storage may need to be reset. Go to Settings and try clearing the security data.".into(),
-            retriable: false,
-            severity: Severity::Permanent,
-        },
-
-        PresswerkError::IntegrityMismatch { .. } => HumanError {
-            message: "This file has been changed since it was stored.".into(),
-            suggestion: "The stored copy doesn't match the original. Try loading the file again from the original source.".into(),
-            retriable: false,
-            severity: Severity::Permanent,
-        },
-
-        PresswerkError::Certificate(_) => HumanError {
-            message: "Secure connection setup failed.".into(),
-            suggestion: "Try restarting the app. If this keeps happening, the security certificates may need to be regenerated in Settings.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        },
-
-        // -- Storage --
-        PresswerkError::Database(_) => HumanError {
-            message: "The app's data storage had a problem.".into(),
-            suggestion: "Try closing and reopening the app. Your print jobs should still be there.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        },
-
-        PresswerkError::Io(io_err) => {
-            if io_err.kind() == std::io::ErrorKind::NotFound {
-                HumanError {
-                    message: "The file couldn't be found.".into(),
-                    suggestion: "It may have been moved or deleted. Try choosing the file again.".into(),
-                    retriable: false,
-                    severity: Severity::ActionRequired,
-                }
-            } else if io_err.kind() == std::io::ErrorKind::PermissionDenied {
-                HumanError {
-                    message: "The app doesn't have permission to read that file.".into(),
-                    suggestion: "Check the file permissions, or try copying the file to a different location first.".into(),
-                    retriable: false,
-                    severity: Severity::ActionRequired,
-                }
-            } else {
-                HumanError {
-                    message: "There was a problem reading or writing a file.".into(),
-                    suggestion: "Try again. If this keeps happening, your device's storage may be full.".into(),
-                    retriable: true,
-                    severity: Severity::Transient,
-                }
-            }
-        }
+    humanize_error_localized(err, DEFAULT_LANGUAGE)
+}
 
-        PresswerkError::Serialization(_) => HumanError {
-            message: "The app had an internal data problem.".into(),
-            suggestion: "Try again. If this keeps happening, please report it.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        },
-
-        // -- Platform --
-        PresswerkError::Bridge(_) => HumanError {
-            message: "A device-specific feature didn't work.".into(),
-            suggestion: "Try restarting the app. Some features may not be available on all devices.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        },
-
-        PresswerkError::PlatformUnavailable => HumanError {
-            message: "This feature isn't available on your device.".into(),
-            suggestion: "Some features require a specific type of phone or tablet.".into(),
-            retriable: false,
-            severity: Severity::Permanent,
-        },
+/// Like [`humanize_error`], in the language `lang` (a BCP 47 tag such as
+/// `"de"` or `"de-AT"`), falling back to English for untranslated messages.
+pub fn humanize_error_localized(err: &PresswerkError, lang: &str) -> HumanError {
+    let (code, detail) = ErrorCode::for_error(err);
+    let (message, suggestion) = localized_strings(code, lang);
+    let suggestion = match detail {
+        Some(detail) => suggestion.replace("{detail}", detail),
+        None => suggestion.to_string(),
+    };
+    HumanError {
+        code,
+        message: message.to_string(),
+        suggestion,
+        retriable: code.retriable(),
+        severity: code.severity(),
     }
 }
 
-/// Parse IPP-specific error details into human-readable messages.
-fn humanize_ipp_error(detail: &str) -> HumanError {
-    let lower = detail.to_ascii_lowercase();
+/// Message and suggestion for `code` in `lang`, or in English.
+fn localized_strings(code: ErrorCode, lang: &str) -> (&'static str, &'static str) {
+    let primary = lang.split(['-', '_']).next().unwrap_or(lang);
+    let lookup = |lang: &str| {
+        CATALOGUE
+            .iter()
+            .find(|entry| entry.code == code && entry.lang.eq_ignore_ascii_case(lang))
+    };
+    let entry = lookup(primary)
+        .or_else(|| lookup(DEFAULT_LANGUAGE))
+        .expect("every error code has an English message");
+    (entry.message, entry.suggestion)
+}
 
-    if lower.contains("timed out") {
-        HumanError {
-            message: "The printer didn't respond in time.".into(),
-            suggestion: "The printer might be busy or turned off. Check it's on and connected, then try again.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        }
-    } else if lower.contains("connection refused") {
-        HumanError {
-            message: "The printer refused our connection.".into(),
-            suggestion: "The printer may be turned off, busy, or not accepting network connections. Try turning it off and on again.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        }
-    } else if lower.contains("connection reset") || lower.contains("broken pipe") {
-        HumanError {
-            message: "The connection to the printer was interrupted.".into(),
-            suggestion: "This sometimes happens with Wi-Fi. We'll try again automatically.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        }
-    } else if lower.contains("server-error") {
-        HumanError {
-            message: "The printer reported an internal error.".into(),
-            suggestion: "Try turning the printer off, waiting 10 seconds, and turning it back on.".into(),
-            retriable: true,
-            severity: Severity::Transient,
-        }
-    } else if lower.contains("client-error-not-possible") || lower.contains("client-error-attributes") {
-        HumanError {
-            message: "The printer can't handle those settings.".into(),
-            suggestion: "Try changing the print settings (paper size, duplex, colour) and print again.".into(),
-            retriable: false,
-            severity: Severity::ActionRequired,
-        }
-    } else if lower.contains("client-error-document-format") {
-        HumanError {
-            message: "The printer doesn't understand this file type.".into(),
-            suggestion: "Try saving the file as a PDF first, then print the PDF.".into(),
-            retriable: false,
-            severity: Severity::Permanent,
-        }
-    } else if lower.contains("invalid uri") || lower.contains("invalid url") {
-        HumanError {
-            message: "The printer address doesn't look right.".into(),
-            suggestion: "Check the printer address and try again. It should look like 192.168.1.100.".into(),
-            retriable: false,
-            severity: Severity::ActionRequired,
-        }
-    } else if lower.contains("media-empty") || lower.contains("out of paper") {
-        HumanError {
-            message: "The printer is out of paper.".into(),
-            suggestion: "Please add paper to the printer's tray, then tap Retry.".into(),
-            retriable: false,
-            severity: Severity::ActionRequired,
-        }
-    } else if lower.contains("toner-empty") || lower.contains("ink") || lower.contains("marker-supply") {
-        HumanError {
-            message: "The printer needs new ink or toner.".into(),
-            suggestion: "You'll need to buy a replacement cartridge. Check your printer's model number and search online for the right one.".into(),
-            retriable: false,
-            severity: Severity::BuyRequired,
-        }
-    } else if lower.contains("door-open") || lower.contains("cover-open") {
-        HumanError {
-            message: "A door or cover is open on the printer.".into(),
-            suggestion: "Please close all doors and covers on the printer, then tap Retry.".into(),
-            retriable: false,
-            severity: Severity::ActionRequired,
-        }
-    } else if lower.contains("paper-jam") || lower.contains("media-jam") {
-        HumanError {
-            message: "Paper is stuck in the printer.".into(),
-            suggestion: "Gently pull the stuck paper out. Check there are no torn pieces left inside, then close all doors.".into(),
-            retriable: false,
-            severity: Severity::ActionRequired,
-        }
-    } else {
-        // Generic IPP error fallback
-        HumanError {
-            message: "The printer had a problem.".into(),
-            suggestion: format!("Try again. If this keeps happening, try turning the printer off and on again. (Detail: {detail})"),
-            retriable: true,
-            severity: Severity::Transient,
-        }
+// ---------------------------------------------------------------------------
+// Catalogue
+// ---------------------------------------------------------------------------
+
+/// One translated message.  `{detail}` in a suggestion is replaced by the
+/// error's detail.
+struct CatalogueEntry {
+    code: ErrorCode,
+    lang: &'static str,
+    message: &'static str,
+    suggestion: &'static str,
+}
+
+const fn entry(
+    code: ErrorCode,
+    lang: &'static str,
+    message: &'static str,
+    suggestion: &'static str,
+) -> CatalogueEntry {
+    CatalogueEntry {
+        code,
+        lang,
+        message,
+        suggestion,
     }
 }
 
+/// Every message, by code and language.  English must cover every code.
+const CATALOGUE: &[CatalogueEntry] = &[
+    // -- English --
+    entry(
+        ErrorCode::DiscoveryUnavailable,
+        "en",
+        "We can't search for printers right now.",
+        "Make sure you're connected to Wi-Fi, then try again.",
+    ),
+    entry(
+        ErrorCode::NoPrintersFound,
+        "en",
+        "We couldn't find any printers.",
+        "Make sure your printer is turned on and connected to the same Wi-Fi network as this device.",
+    ),
+    entry(
+        ErrorCode::PrinterTimedOut,
+        "en",
+        "The printer didn't respond in time.",
+        "The printer might be busy or turned off. Check it's on and connected, then try again.",
+    ),
+    entry(
+        ErrorCode::PrinterRefused,
+        "en",
+        "The printer refused our connection.",
+        "The printer may be turned off, busy, or not accepting network connections. Try turning it off and on again.",
+    ),
+    entry(
+        ErrorCode::ConnectionInterrupted,
+        "en",
+        "The connection to the printer was interrupted.",
+        "This sometimes happens with Wi-Fi. We'll try again automatically.",
+    ),
+    entry(
+        ErrorCode::PrinterInternalError,
+        "en",
+        "The printer reported an internal error.",
+        "Try turning the printer off, waiting 10 seconds, and turning it back on.",
+    ),
+    entry(
+        ErrorCode::SettingsNotSupported,
+        "en",
+        "The printer can't handle those settings.",
+        "Try changing the print settings (paper size, duplex, colour) and print again.",
+    ),
+    entry(
+        ErrorCode::FileTypeNotSupported,
+        "en",
+        "The printer doesn't understand this file type.",
+        "Try saving the file as a PDF first, then print the PDF.",
+    ),
+    entry(
+        ErrorCode::InvalidPrinterAddress,
+        "en",
+        "The printer address doesn't look right.",
+        "Check the printer address and try again. It should look like 192.168.1.100.",
+    ),
+    entry(
+        ErrorCode::OutOfPaper,
+        "en",
+        "The printer is out of paper.",
+        "Please add paper to the printer's tray, then tap Retry.",
+    ),
+    entry(
+        ErrorCode::OutOfInk,
+        "en",
+        "The printer needs new ink or toner.",
+        "You'll need to buy a replacement cartridge. Check your printer's model number and search online for the right one.",
+    ),
+    entry(
+        ErrorCode::CoverOpen,
+        "en",
+        "A door or cover is open on the printer.",
+        "Please close all doors and covers on the printer, then tap Retry.",
+    ),
+    entry(
+        ErrorCode::PaperJam,
+        "en",
+        "Paper is stuck in the printer.",
+        "Gently pull the stuck paper out. Check there are no torn pieces left inside, then close all doors.",
+    ),
+    entry(
+        ErrorCode::PrinterProblem,
+        "en",
+        "The printer had a problem.",
+        "Try again. If this keeps happening, try turning the printer off and on again. (Detail: {detail})",
+    ),
+    entry(
+        ErrorCode::PrintServerFailed,
+        "en",
+        "The print server had a problem.",
+        "Try restarting the print server. ({detail})",
+    ),
+    entry(
+        ErrorCode::NoPrinterSelected,
+        "en",
+        "No printer selected.",
+        "Please choose a printer from the list, then try again.",
+    ),
+    entry(
+        ErrorCode::JobNotFound,
+        "en",
+        "We couldn't find that print job.",
+        "Check the job number and try again. ({detail})",
+    ),
+    entry(
+        ErrorCode::UnsupportedDocument,
+        "en",
+        "This type of document isn't supported.",
+        "Try saving the file as a PDF first, then print the PDF. (File type: {detail})",
+    ),
+    entry(
+        ErrorCode::DamagedPdf,
+        "en",
+        "There's a problem with this PDF file.",
+        "The file may be damaged. Try opening it on a computer first to check it works, or try a different file.",
+    ),
+    entry(
+        ErrorCode::DamagedImage,
+        "en",
+        "There's a problem with this image.",
+        "The image may be damaged or in an unusual format. Try saving it as a JPEG or PNG first.",
+    ),
+    entry(
+        ErrorCode::TextRecognitionFailed,
+        "en",
+        "Text recognition didn't work on this scan.",
+        "Try scanning the document again with better lighting, making sure the text is clear and in focus.",
+    ),
+    entry(
+        ErrorCode::InvalidPageRange,
+        "en",
+        "Those page numbers don't look right.",
+        "Check the pages you asked for, for example \"1-3, 5\". ({detail})",
+    ),
+    entry(
+        ErrorCode::SecurityProblem,
+        "en",
+        "There was a security problem.",
+        "The app's secure storage may need to be reset. Go to Settings and try clearing the security data.",
+    ),
+    entry(
+        ErrorCode::FileChanged,
+        "en",
+        "This file has been changed since it was stored.",
+        "The stored copy doesn't match the original. Try loading the file again from the original source.",
+    ),
+    entry(
+        ErrorCode::SecureConnectionFailed,
+        "en",
+        "Secure connection setup failed.",
+        "Try restarting the app. If this keeps happening, the security certificates may need to be regenerated in Settings.",
+    ),
+    entry(
+        ErrorCode::StorageProblem,
+        "en",
+        "The app's data storage had a problem.",
+        "Try closing and reopening the app. Your print jobs should still be there.",
+    ),
+    entry(
+        ErrorCode::FileNotFound,
+        "en",
+        "The file couldn't be found.",
+        "It may have been moved or deleted. Try choosing the file again.",
+    ),
+    entry(
+        ErrorCode::FileAccessDenied,
+        "en",
+        "The app doesn't have permission to read that file.",
+        "Check the file permissions, or try copying the file to a different location first.",
+    ),
+    entry(
+        ErrorCode::FileProblem,
+        "en",
+        "There was a problem reading or writing a file.",
+        "Try again. If this keeps happening, your device's storage may be full.",
+    ),
+    entry(
+        ErrorCode::InternalDataProblem,
+        "en",
+        "The app had an internal data problem.",
+        "Try again. If this keeps happening, please report it.",
+    ),
+    entry(
+        ErrorCode::DeviceFeatureFailed,
+        "en",
+        "A device-specific feature didn't work.",
+        "Try restarting the app. Some features may not be available on all devices.",
+    ),
+    entry(
+        ErrorCode::FeatureUnavailable,
+        "en",
+        "This feature isn't available on your device.",
+        "Some features require a specific type of phone or tablet.",
+    ),
+    entry(
+        ErrorCode::PermissionDenied,
+        "en",
+        "Presswerk wasn't given permission to use that.",
+        "Try again and tap Allow when asked, or grant the permission in your device settings.",
+    ),
+    // -- German --
+    entry(
+        ErrorCode::DiscoveryUnavailable,
+        "de",
+        "Wir können gerade nicht nach Druckern suchen.",
+        "Prüfen Sie, ob Sie mit dem WLAN verbunden sind, und versuchen Sie es dann noch einmal.",
+    ),
+    entry(
+        ErrorCode::NoPrintersFound,
+        "de",
+        "Wir haben keinen Drucker gefunden.",
+        "Prüfen Sie, ob der Drucker eingeschaltet und mit demselben WLAN verbunden ist wie dieses Gerät.",
+    ),
+    entry(
+        ErrorCode::PrinterTimedOut,
+        "de",
+        "Der Drucker hat nicht rechtzeitig geantwortet.",
+        "Der Drucker ist vielleicht beschäftigt oder ausgeschaltet. Prüfen Sie, ob er an und verbunden ist, und versuchen Sie es dann noch einmal.",
+    ),
+    entry(
+        ErrorCode::PrinterRefused,
+        "de",
+        "Der Drucker hat die Verbindung abgelehnt.",
+        "Der Drucker ist vielleicht aus, beschäftigt oder nimmt keine Netzwerkverbindungen an. Schalten Sie ihn aus und wieder ein.",
+    ),
+    entry(
+        ErrorCode::ConnectionInterrupted,
+        "de",
+        "Die Verbindung zum Drucker wurde unterbrochen.",
+        "Das kommt im WLAN manchmal vor. Wir versuchen es automatisch noch einmal.",
+    ),
+    entry(
+        ErrorCode::PrinterInternalError,
+        "de",
+        "Der Drucker meldet einen internen Fehler.",
+        "Schalten Sie den Drucker aus, warten Sie 10 Sekunden und schalten Sie ihn wieder ein.",
+    ),
+    entry(
+        ErrorCode::SettingsNotSupported,
+        "de",
+        "Der Drucker kann diese Einstellungen nicht verwenden.",
+        "Ändern Sie die Druckeinstellungen (Papierformat, beidseitig, Farbe) und drucken Sie noch einmal.",
+    ),
+    entry(
+        ErrorCode::FileTypeNotSupported,
+        "de",
+        "Der Drucker versteht diesen Dateityp nicht.",
+        "Speichern Sie die Datei zuerst als PDF und drucken Sie dann das PDF.",
+    ),
+    entry(
+        ErrorCode::InvalidPrinterAddress,
+        "de",
+        "Die Druckeradresse scheint nicht zu stimmen.",
+        "Prüfen Sie die Druckeradresse und versuchen Sie es noch einmal. Sie sieht etwa so aus: 192.168.1.100.",
+    ),
+    entry(
+        ErrorCode::OutOfPaper,
+        "de",
+        "Im Drucker ist kein Papier mehr.",
+        "Legen Sie Papier in das Fach des Druckers und tippen Sie dann auf Wiederholen.",
+    ),
+    entry(
+        ErrorCode::OutOfInk,
+        "de",
+        "Der Drucker braucht neue Tinte oder neuen Toner.",
+        "Sie müssen eine neue Patrone kaufen. Suchen Sie mit der Modellnummer Ihres Druckers nach der passenden.",
+    ),
+    entry(
+        ErrorCode::CoverOpen,
+        "de",
+        "Eine Klappe oder Abdeckung am Drucker ist offen.",
+        "Schließen Sie alle Klappen und Abdeckungen am Drucker und tippen Sie dann auf Wiederholen.",
+    ),
+    entry(
+        ErrorCode::PaperJam,
+        "de",
+        "Im Drucker steckt Papier fest.",
+        "Ziehen Sie das Papier vorsichtig heraus. Achten Sie darauf, dass keine Fetzen zurückbleiben, und schließen Sie dann alle Klappen.",
+    ),
+    entry(
+        ErrorCode::PrinterProblem,
+        "de",
+        "Der Drucker hatte ein Problem.",
+        "Versuchen Sie es noch einmal. Wenn das öfter passiert, schalten Sie den Drucker aus und wieder ein. (Details: {detail})",
+    ),
+    entry(
+        ErrorCode::PrintServerFailed,
+        "de",
+        "Der Druckserver hatte ein Problem.",
+        "Starten Sie den Druckserver neu. ({detail})",
+    ),
+    entry(
+        ErrorCode::NoPrinterSelected,
+        "de",
+        "Kein Drucker ausgewählt.",
+        "Wählen Sie einen Drucker aus der Liste und versuchen Sie es dann noch einmal.",
+    ),
+    entry(
+        ErrorCode::JobNotFound,
+        "de",
+        "Wir konnten diesen Druckauftrag nicht finden.",
+        "Prüfen Sie die Auftragsnummer und versuchen Sie es noch einmal. ({detail})",
+    ),
+    entry(
+        ErrorCode::UnsupportedDocument,
+        "de",
+        "Diese Art von Dokument wird nicht unterstützt.",
+        "Speichern Sie die Datei zuerst als PDF und drucken Sie dann das PDF. (Dateityp: {detail})",
+    ),
+    entry(
+        ErrorCode::DamagedPdf,
+        "de",
+        "Mit dieser PDF-Datei stimmt etwas nicht.",
+        "Die Datei ist vielleicht beschädigt. Öffnen Sie sie zuerst an einem Computer, oder nehmen Sie eine andere Datei.",
+    ),
+    entry(
+        ErrorCode::DamagedImage,
+        "de",
+        "Mit diesem Bild stimmt etwas nicht.",
+        "Das Bild ist vielleicht beschädigt oder in einem ungewöhnlichen Format. Speichern Sie es zuerst als JPEG oder PNG.",
+    ),
+    entry(
+        ErrorCode::TextRecognitionFailed,
+        "de",
+        "Die Texterkennung hat bei diesem Scan nicht funktioniert.",
+        "Scannen Sie das Dokument noch einmal bei besserem Licht und achten Sie darauf, dass der Text scharf ist.",
+    ),
+    entry(
+        ErrorCode::InvalidPageRange,
+        "de",
+        "Diese Seitenzahlen scheinen nicht zu stimmen.",
+        "Prüfen Sie die gewünschten Seiten, zum Beispiel \"1-3, 5\". ({detail})",
+    ),
+    entry(
+        ErrorCode::SecurityProblem,
+        "de",
+        "Es gab ein Sicherheitsproblem.",
+        "Der sichere Speicher der App muss vielleicht zurückgesetzt werden. Löschen Sie in den Einstellungen die Sicherheitsdaten.",
+    ),
+    entry(
+        ErrorCode::FileChanged,
+        "de",
+        "Diese Datei wurde seit dem Speichern verändert.",
+        "Die gespeicherte Kopie stimmt nicht mit dem Original überein. Laden Sie die Datei noch einmal aus der ursprünglichen Quelle.",
+    ),
+    entry(
+        ErrorCode::SecureConnectionFailed,
+        "de",
+        "Die sichere Verbindung konnte nicht eingerichtet werden.",
+        "Starten Sie die App neu. Wenn das öfter passiert, müssen die Sicherheitszertifikate in den Einstellungen neu erstellt werden.",
+    ),
+    entry(
+        ErrorCode::StorageProblem,
+        "de",
+        "Der Datenspeicher der App hatte ein Problem.",
+        "Schließen Sie die App und öffnen Sie sie wieder. Ihre Druckaufträge sollten noch da sein.",
+    ),
+    entry(
+        ErrorCode::FileNotFound,
+        "de",
+        "Die Datei wurde nicht gefunden.",
+        "Sie wurde vielleicht verschoben oder gelöscht. Wählen Sie die Datei noch einmal aus.",
+    ),
+    entry(
+        ErrorCode::FileAccessDenied,
+        "de",
+        "Die App darf diese Datei nicht lesen.",
+        "Prüfen Sie die Dateiberechtigungen, oder kopieren Sie die Datei zuerst an einen anderen Ort.",
+    ),
+    entry(
+        ErrorCode::FileProblem,
+        "de",
+        "Beim Lesen oder Schreiben einer Datei ist ein Problem aufgetreten.",
+        "Versuchen Sie es noch einmal. Wenn das öfter passiert, ist der Speicher Ihres Geräts vielleicht voll.",
+    ),
+    entry(
+        ErrorCode::InternalDataProblem,
+        "de",
+        "Die App hatte ein internes Datenproblem.",
+        "Versuchen Sie es noch einmal. Wenn das öfter passiert, melden Sie es uns bitte.",
+    ),
+    entry(
+        ErrorCode::DeviceFeatureFailed,
+        "de",
+        "Eine Gerätefunktion hat nicht funktioniert.",
+        "Starten Sie die App neu. Manche Funktionen gibt es nicht auf allen Geräten.",
+    ),
+    entry(
+        ErrorCode::FeatureUnavailable,
+        "de",
+        "Diese Funktion gibt es auf Ihrem Gerät nicht.",
+        "Manche Funktionen brauchen ein bestimmtes Telefon oder Tablet.",
+    ),
+    entry(
+        ErrorCode::PermissionDenied,
+        "de",
+        "Presswerk hat dafür keine Erlaubnis bekommen.",
+        "Versuchen Sie es noch einmal und tippen Sie auf Erlauben, oder erteilen Sie die Erlaubnis in den Geräteeinstellungen.",
+    ),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,10 +825,154 @@ fn paper_jam_is_action_required() {
         assert_eq!(human.severity, Severity::ActionRequired);
     }
 
+    #[test]
+    fn ipp_codes_agree_with_the_retry_class() {
+        for detail in [
+            "printer stopped: media-empty",
+            "printer stopped: media-jam",
+            "printer stopped: marker-supply-low",
+            "printer stopped: door-open",
+            "Print-Job failed: ClientErrorDocumentFormatNotSupported",
+            "Print-Job failed: client-error-attributes-or-values-not-supported",
+            "Print-Job failed: client-error-bad-request",
+            "invalid URL: relative URL without a base",
+            "Get-Printer-Attributes timed out after 15s",
+            "connection refused",
+            "Print-Job failed: server-error-busy",
+            "uplink lost",
+        ] {
+            let err = PresswerkError::IppRequest(detail.into());
+            let (code, _) = ErrorCode::for_error(&err);
+            assert_eq!(code.class(), ErrorClass::classify(&err), "{detail}");
+        }
+    }
+
+    #[test]
+    fn ink_inside_other_words_is_not_out_of_ink() {
+        let err = PresswerkError::IppRequest("network link down".into());
+        assert_eq!(ErrorCode::for_error(&err).0, ErrorCode::PrinterProblem);
+    }
+
     #[test]
     fn unsupported_format_is_permanent() {
         let err = PresswerkError::UnsupportedDocument("application/msword".into());
         let human = humanize_error(&err);
         assert_eq!(human.severity, Severity::Permanent);
     }
+
+    /// One error of every `PresswerkError` variant, with the code it maps to.
+    fn every_variant() -> Vec<(PresswerkError, &'static str)> {
+        vec![
+            (
+                PresswerkError::Discovery("no multicast".into()),
+                "discovery-unavailable",
+            ),
+            (
+                PresswerkError::IppRequest("printer stopped: media-empty".into()),
+                "out-of-paper",
+            ),
+            (
+                PresswerkError::PrintServer("bind failed".into()),
+                "print-server-failed",
+            ),
+            (PresswerkError::NoPrinterSelected, "no-printer-selected"),
+            (PresswerkError::InvalidJobId("42".into()), "job-not-found"),
+            (
+                PresswerkError::UnsupportedDocument("docx".into()),
+                "unsupported-document",
+            ),
+            (PresswerkError::PdfError("xref".into()), "damaged-pdf"),
+            (PresswerkError::ImageError("decode".into()), "damaged-image"),
+            (
+                PresswerkError::OcrError("no text".into()),
+                "text-recognition-failed",
+            ),
+            (
+                PresswerkError::InvalidPageRange("0-2".into()),
+                "invalid-page-range",
+            ),
+            (PresswerkError::Encryption("key".into()), "security-problem"),
+            (PresswerkError::Decryption("tag".into()), "security-problem"),
+            (
+                PresswerkError::IntegrityMismatch {
+                    expected: "a".into(),
+                    actual: "b".into(),
+                },
+                "file-changed",
+            ),
+            (
+                PresswerkError::Certificate("expired".into()),
+                "secure-connection-failed",
+            ),
+            (PresswerkError::Database("locked".into()), "storage-problem"),
+            (
+                PresswerkError::Io(std::io::ErrorKind::NotFound.into()),
+                "file-not-found",
+            ),
+            (
+                PresswerkError::Serialization(serde_json::from_str::<u8>("x").unwrap_err()),
+                "internal-data-problem",
+            ),
+            (
+                PresswerkError::InvalidConfig("port 0".into()),
+                "internal-data-problem",
+            ),
+            (
+                PresswerkError::Bridge("jni".into()),
+                "device-feature-failed",
+            ),
+            (PresswerkError::PlatformUnavailable, "feature-unavailable"),
+            (
+                PresswerkError::PermissionDenied("camera".into()),
+                "permission-denied",
+            ),
+        ]
+    }
+
+    #[test]
+    fn every_error_has_a_stable_code_and_message() {
+        for (err, code) in every_variant() {
+            for lang in ["en", "de", "fr"] {
+                let human = humanize_error_localized(&err, lang);
+                assert_eq!(human.code.as_str(), code, "{err:?}");
+                assert!(!human.message.is_empty(), "{err:?} in {lang}");
+                assert!(!human.suggestion.is_empty(), "{err:?} in {lang}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_code_has_an_english_message() {
+        for code in ErrorCode::ALL {
+            assert!(
+                CATALOGUE
+                    .iter()
+                    .any(|entry| entry.code == code && entry.lang == DEFAULT_LANGUAGE),
+                "{code:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn localized_messages_fall_back_to_english() {
+        let err = PresswerkError::IppRequest("printer stopped: media-empty".into());
+        assert_eq!(
+            humanize_error_localized(&err, "de-AT").message,
+            "Im Drucker ist kein Papier mehr."
+        );
+        assert_eq!(
+            humanize_error_localized(&err, "fr").message,
+            humanize_error(&err).message
+        );
+    }
+
+    #[test]
+    fn suggestion_quotes_the_detail() {
+        let err = PresswerkError::InvalidPageRange("\"0\" is not a page number".into());
+        assert!(
+            humanize_error_localized(&err, "de")
+                .suggestion
+                .ends_with("(\"0\" is not a page number)")
+        );
+    }
 }
@@ -6,6 +6,8 @@
 pub mod config;
 pub mod error;
 pub mod human_errors;
+pub mod logging;
+pub mod redact;
 pub mod types;
 
 pub use config::AppConfig;
@@ -0,0 +1,306 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Runtime log control — a level filter the settings page can change without
+// a restart, and an in-memory ring buffer of recent entries for the in-app
+// log viewer.
+//
+// The app builds its subscriber from these pieces:
+//
+//   let (filter, handle) = logging::reloadable_filter(initial);
+//   let buffer = LogBuffer::new(logging::DEFAULT_BUFFER_CAPACITY);
+//   tracing_subscriber::registry().with(filter).with(buffer.clone())...
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::{EnvFilter, Layer, Registry, reload};
+
+use crate::redact::redact_line;
+
+/// How many entries the log viewer keeps unless told otherwise.
+pub const DEFAULT_BUFFER_CAPACITY: usize = 500;
+
+/// How much the app logs, most severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// Every level, most severe first.
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    /// The level's name as `RUST_LOG` and the config file spell it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+
+    /// Parse a level name, ignoring case.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    fn from_tracing(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => Self::Error,
+            Level::WARN => Self::Warn,
+            Level::INFO => Self::Info,
+            Level::DEBUG => Self::Debug,
+            Level::TRACE => Self::Trace,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Level filter
+// ---------------------------------------------------------------------------
+
+/// Changes the level of a running subscriber's filter.
+#[derive(Clone)]
+pub struct LogHandle {
+    inner: reload::Handle<EnvFilter, Registry>,
+}
+
+/// Wrap `initial` so its level can be changed later through the returned
+/// handle.  The layer must sit directly on the [`Registry`].
+pub fn reloadable_filter(initial: EnvFilter) -> (reload::Layer<EnvFilter, Registry>, LogHandle) {
+    let (layer, inner) = reload::Layer::new(initial);
+    (layer, LogHandle { inner })
+}
+
+impl LogHandle {
+    /// Log at `level` and above from now on, for every target.
+    ///
+    /// # Errors
+    ///
+    /// Fails only if the subscriber the filter belonged to is gone.
+    pub fn set_level(&self, level: LogLevel) -> Result<(), reload::Error> {
+        self.inner.reload(EnvFilter::new(level.as_str()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Ring buffer
+// ---------------------------------------------------------------------------
+
+/// One event as the log viewer shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    /// Module path the event came from, e.g. `presswerk_print::ipp_client`.
+    pub target: String,
+    /// The message followed by the event's other fields as `key=value`,
+    /// with secrets redacted.
+    pub message: String,
+}
+
+/// `tracing` layer keeping the most recent events in memory.
+///
+/// Clones share the same buffer, so one can be installed in the subscriber
+/// while another is read from the UI.
+#[derive(Clone)]
+pub struct LogBuffer {
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    /// A buffer holding at most `capacity` entries, dropping the oldest.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// The retained entries, oldest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Forget every retained entry.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn push(&self, entry: LogEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// A panic elsewhere leaves the entries intact, so poison is ignored.
+    fn lock(&self) -> MutexGuard<'_, VecDeque<LogEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.push(LogEntry {
+            timestamp: Utc::now(),
+            level: LogLevel::from_tracing(metadata.level()),
+            target: metadata.target().to_string(),
+            message: redact_line(&visitor.finish()),
+        });
+    }
+}
+
+/// Collects an event's `message` and its other fields.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        match (self.message.is_empty(), self.fields.is_empty()) {
+            (_, true) => self.message,
+            (true, false) => self.fields,
+            (false, false) => format!("{} {}", self.message, self.fields),
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tracing_subscriber::prelude::*;
+
+    fn messages(buffer: &LogBuffer) -> Vec<String> {
+        buffer.entries().into_iter().map(|e| e.message).collect()
+    }
+
+    #[test]
+    fn level_change_applies_to_later_events() {
+        let (filter, handle) = reloadable_filter(EnvFilter::new("info"));
+        let buffer = LogBuffer::new(DEFAULT_BUFFER_CAPACITY);
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(buffer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("hidden");
+            tracing::info!("shown");
+
+            handle.set_level(LogLevel::Debug).unwrap();
+            tracing::debug!("now shown");
+
+            handle.set_level(LogLevel::Warn).unwrap();
+            tracing::info!("hidden again");
+            tracing::warn!(port = 631, "printer slow");
+        });
+
+        assert_eq!(
+            messages(&buffer),
+            ["shown", "now shown", "printer slow port=631"]
+        );
+        let levels: Vec<LogLevel> = buffer.entries().iter().map(|e| e.level).collect();
+        assert_eq!(levels, [LogLevel::Info, LogLevel::Debug, LogLevel::Warn]);
+    }
+
+    #[test]
+    fn buffer_keeps_only_the_newest_entries() {
+        let buffer = LogBuffer::new(3);
+        let subscriber = tracing_subscriber::registry().with(buffer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            for n in 1..=5 {
+                tracing::info!("entry {n}");
+            }
+        });
+
+        assert_eq!(messages(&buffer), ["entry 3", "entry 4", "entry 5"]);
+        buffer.clear();
+        assert!(buffer.entries().is_empty());
+    }
+
+    #[test]
+    fn buffered_messages_are_redacted() {
+        let buffer = LogBuffer::new(1);
+        let subscriber = tracing_subscriber::registry().with(buffer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(password = "hunter2", "login");
+        });
+
+        assert_eq!(messages(&buffer), [r#"login password="[redacted]""#]);
+    }
+
+    #[test]
+    fn level_names_round_trip() {
+        for level in LogLevel::ALL {
+            assert_eq!(LogLevel::parse(level.as_str()), Some(level));
+        }
+        assert_eq!(LogLevel::parse(" DEBUG "), Some(LogLevel::Debug));
+        assert_eq!(LogLevel::parse("verbose"), None);
+        assert_eq!(serde_json::to_string(&LogLevel::Warn).unwrap(), "\"warn\"");
+    }
+}
@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Redaction of secrets from log output.
+//
+// Applied to every line written to the on-disk log (via `RedactingWriter`)
+// and again to the log tail packed into diagnostic bundles, so credentials
+// never leave the device even in logs written by older versions.
+
+use std::io::{self, Write};
+
+/// Replacement text for anything that looks like a secret.
+pub const REDACTED: &str = "[redacted]";
+
+/// Field names whose values are always redacted (matched as substrings,
+/// case-insensitively).
+const SENSITIVE_KEYS: &[&str] = &[
+    "secret",
+    "password",
+    "passphrase",
+    "token",
+    "credential",
+    "private_key",
+    "api_key",
+    "apikey",
+    "authorization",
+];
+
+/// Prefix of keychain / keystore entry names; fields named with it hold
+/// key material.
+const KEYCHAIN_PREFIX: &str = "presswerk_";
+
+/// Punctuation that may follow a closing quote, as in `"value",`.
+const QUOTE_TRAILERS: &[char] = &[',', ';', ')', ']', '}'];
+
+/// HTTP authentication schemes that precede the credentials in an
+/// `Authorization` value.
+const AUTH_SCHEMES: &[&str] = &["basic", "bearer", "digest", "negotiate"];
+
+/// Redact every line of `text`.
+pub fn redact(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\n', '\r']);
+        out.push_str(&redact_line(body));
+        out.push_str(&line[body.len()..]);
+    }
+    out
+}
+
+/// Replace secret-looking values in a single log line with `[redacted]`.
+///
+/// Catches `key=value`, `key: value` and `key = value` pairs whose key names
+/// a secret or a `presswerk_` keychain entry (quoted values spanning several
+/// words included), `Authorization` header values with their scheme, email
+/// addresses, and bare runs of hex or base64 long enough to be key material.
+/// Everything else is left as it was.
+pub fn redact_line(line: &str) -> String {
+    let words: Vec<&str> = line.split_inclusive(char::is_whitespace).collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < words.len() {
+        let token = words[i];
+        let word = token.trim_end_matches(char::is_whitespace);
+        let space = &token[word.len()..];
+        i += 1;
+
+        // `key=value` or `key:value` in one word (tracing's own format).
+        // A value starting with `:` is a module path such as
+        // `presswerk_print::queue:`, not a field.
+        if let Some((key, value)) = word.split_once(['=', ':'])
+            && !value.starts_with(':')
+            && is_sensitive_key(key)
+        {
+            out.push_str(&word[..=key.len()]);
+            if value.is_empty() {
+                // `key: value` — the value is the next word.
+                out.push_str(space);
+                i = redact_value(&words, i, &mut out);
+            } else {
+                i = push_redacted(&words, i - 1, value, &mut out, space);
+            }
+            continue;
+        }
+
+        // `key = value` with a free-standing separator.
+        if is_sensitive_key(word)
+            && let Some(sep) = words.get(i)
+            && matches!(sep.trim_end(), "=" | ":")
+        {
+            out.push_str(token);
+            out.push_str(sep);
+            i = redact_value(&words, i + 1, &mut out);
+            continue;
+        }
+
+        if looks_like_key_material(word) {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(&redact_emails(word));
+        }
+        out.push_str(space);
+    }
+
+    out
+}
+
+/// Redact the value starting at `words[i]`; returns the index after it.
+fn redact_value(words: &[&str], i: usize, out: &mut String) -> usize {
+    let Some(token) = words.get(i) else {
+        return i;
+    };
+    let word = token.trim_end_matches(char::is_whitespace);
+
+    // `Authorization: Basic dXNlcjpwYXNz` — the credentials follow the scheme.
+    // A quoted `"Basic abc"` is covered by the quote handling below.
+    let scheme = word.to_ascii_lowercase();
+    if AUTH_SCHEMES.contains(&scheme.as_str()) && i + 1 < words.len() {
+        let next = words[i + 1].trim_end_matches(char::is_whitespace);
+        out.push_str(REDACTED);
+        out.push_str(&words[i + 1][next.len()..]);
+        return i + 2;
+    }
+
+    push_redacted(words, i, word, out, &token[word.len()..])
+}
+
+/// Write `[redacted]` in place of `value` (the tail of `words[i]`) and the
+/// whitespace that follows it; returns the index of the next unprocessed
+/// word.  A quoted value runs to its closing quote and keeps its quotes and
+/// any punctuation after them, so `"a b",` becomes `"[redacted]",`.
+fn push_redacted(words: &[&str], i: usize, value: &str, out: &mut String, space: &str) -> usize {
+    if !value.starts_with('"') {
+        out.push_str(REDACTED);
+        out.push_str(space);
+        return i + 1;
+    }
+
+    out.push('"');
+    out.push_str(REDACTED);
+    out.push('"');
+    if value.len() > 1 && closes_quote(value) {
+        out.push_str(after_quote(value));
+        out.push_str(space);
+        return i + 1;
+    }
+    for (j, token) in words.iter().enumerate().skip(i + 1) {
+        let word = token.trim_end_matches(char::is_whitespace);
+        if closes_quote(word) {
+            out.push_str(after_quote(word));
+            out.push_str(&token[word.len()..]);
+            return j + 1;
+        }
+    }
+    words.len()
+}
+
+/// Whether `word` ends a quoted string, allowing for trailing punctuation
+/// as in `"value",` or `"value"}`.
+fn closes_quote(word: &str) -> bool {
+    word.trim_end_matches(QUOTE_TRAILERS).ends_with('"')
+}
+
+/// The punctuation after the closing quote of `word`.
+fn after_quote(word: &str) -> &str {
+    &word[word.trim_end_matches(QUOTE_TRAILERS).len()..]
+}
+
+/// Whether a field name refers to secret material.
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key
+        .trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .to_ascii_lowercase();
+    !key.is_empty()
+        && (key.starts_with(KEYCHAIN_PREFIX) || SENSITIVE_KEYS.iter().any(|s| key.contains(s)))
+}
+
+/// Long unbroken runs of hex or base64 are almost always keys, tokens or
+/// hashes of secrets; document hashes are fine to lose from a shared log.
+fn looks_like_key_material(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| matches!(c, '"' | '\'' | ',' | ';' | '(' | ')'));
+    word.len() >= 32
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '-' | '_'))
+        && word.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Replace any `local@domain.tld` inside `word`, keeping surrounding
+/// punctuation such as quotes or `user=` prefixes.
+fn redact_emails(word: &str) -> String {
+    let is_local = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-');
+    let is_domain = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '-');
+
+    let mut out = String::with_capacity(word.len());
+    let mut rest = word;
+
+    while let Some(at) = rest.find('@') {
+        let local_start = rest[..at]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_local(c))
+            .last()
+            .map_or(at, |(idx, _)| idx);
+        let domain_len = rest[at + 1..]
+            .find(|c: char| !is_domain(c))
+            .unwrap_or(rest.len() - at - 1);
+        let domain = rest[at + 1..at + 1 + domain_len].trim_end_matches('.');
+
+        let is_email = local_start < at
+            && domain
+                .rsplit_once('.')
+                .is_some_and(|(host, tld)| !host.is_empty() && tld.len() >= 2);
+
+        if is_email {
+            out.push_str(&rest[..local_start]);
+            out.push_str(REDACTED);
+            rest = &rest[at + 1 + domain.len()..];
+        } else {
+            out.push_str(&rest[..=at]);
+            rest = &rest[at + 1..];
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// `io::Write` adapter that redacts each chunk before passing it on.
+///
+/// Intended for line-oriented writers such as a `tracing_subscriber` fmt
+/// layer, which writes each formatted event in a single call.
+pub struct RedactingWriter<W> {
+    inner: W,
+}
+
+impl<W: Write> RedactingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.inner.write_all(redact(&text).as_bytes())?;
+        // The whole chunk was consumed even though the written length
+        // differs; report that so callers don't re-send the tail.
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_keychain_fields_and_keeps_context() {
+        assert_eq!(
+            redact_line("DEBUG presswerk_bridge::ios: stored presswerk_db_key=s3cr3t bytes=32"),
+            "DEBUG presswerk_bridge::ios: stored presswerk_db_key=[redacted] bytes=32"
+        );
+        assert_eq!(
+            redact_line(r#"INFO keychain: loaded secret="two words" ok=true"#),
+            r#"INFO keychain: loaded secret="[redacted]" ok=true"#
+        );
+    }
+
+    #[test]
+    fn redacts_authorization_header_with_scheme() {
+        assert_eq!(
+            redact_line("WARN ipp: Authorization: Basic dXNlcjpwYXNz rejected"),
+            "WARN ipp: Authorization: [redacted] rejected"
+        );
+        assert_eq!(
+            redact_line(r#"DEBUG headers {"authorization": "Bearer abc.def", "host": "printer"}"#),
+            r#"DEBUG headers {"authorization": "[redacted]", "host": "printer"}"#
+        );
+    }
+
+    #[test]
+    fn redacts_email_addresses() {
+        assert_eq!(
+            redact_line("INFO job submitted by alice.smith+print@example.co.uk to printer@office"),
+            "INFO job submitted by [redacted] to printer@office"
+        );
+        assert_eq!(
+            redact_line(r#"user="bob@example.com""#),
+            r#"user="[redacted]""#
+        );
+    }
+
+    #[test]
+    fn leaves_ordinary_lines_alone() {
+        let line = "INFO presswerk_print::queue: token refresh failed port=631 job=42";
+        assert_eq!(redact_line(line), line);
+        assert_eq!(
+            redact_line("api_key = abc123 ok"),
+            "api_key = [redacted] ok"
+        );
+    }
+
+    #[test]
+    fn redact_preserves_line_endings() {
+        assert_eq!(redact("password=x\r\nok\n"), "password=[redacted]\r\nok\n");
+    }
+
+    #[test]
+    fn redacting_writer_filters_output() {
+        let mut writer = RedactingWriter::new(Vec::new());
+        writer.write_all(b"INFO login password=hunter2\n").unwrap();
+        assert_eq!(writer.inner, b"INFO login password=[redacted]\n");
+    }
+}
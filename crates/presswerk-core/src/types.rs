@@ -6,8 +6,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 use uuid::Uuid;
 
+use crate::error::PresswerkError;
+
 /// Unique identifier for a print job.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct JobId(pub Uuid);
@@ -16,6 +20,12 @@ impl JobId {
     pub fn new() -> Self {
         Self(Uuid::new_v4())
     }
+
+    /// The first 8 hex digits, for showing to the user.  Unique enough to
+    /// tell a queue's jobs apart, but not a key: look jobs up by the full id.
+    pub fn short(&self) -> String {
+        self.0.simple().to_string()[..8].to_string()
+    }
 }
 
 impl Default for JobId {
@@ -30,6 +40,17 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+impl std::str::FromStr for JobId {
+    type Err = PresswerkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Uuid::parse_str(s)
+            .map(Self)
+            .map_err(|e| PresswerkError::InvalidJobId(format!("{s:?} is not a job id ({e})")))
+    }
+}
+
 /// Where a print job originated from.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JobSource {
@@ -114,8 +135,89 @@ pub fn from_extension(ext: &str) -> Option<Self> {
     }
 }
 
-/// Standard paper sizes.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// How much of the start of a document [`sniff_document_type`] looks at.
+pub const SNIFF_LEN: usize = 1024;
+
+/// Identify a document from its leading bytes rather than the name or
+/// format it was declared with.
+///
+/// PDF (`%PDF-` at the start, after an optional byte-order mark and
+/// whitespace), JPEG, PNG, TIFF, PostScript and PWG Raster are recognised
+/// by their signatures.  Anything else that is UTF-8 without control
+/// characters other than tab, line breaks and form feed is plain text;
+/// the rest is `NativeDelegate`.
+pub fn sniff_document_type(bytes: &[u8]) -> DocumentType {
+    let head = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let text_start = head
+        .strip_prefix(b"\xEF\xBB\xBF")
+        .unwrap_or(head)
+        .trim_ascii_start();
+    if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        DocumentType::Jpeg
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        DocumentType::Png
+    } else if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*") {
+        DocumentType::Tiff
+    } else if head.starts_with(b"RaS2") {
+        DocumentType::PwgRaster
+    } else if text_start.starts_with(b"%PDF-") {
+        DocumentType::Pdf
+    } else if head.starts_with(b"%!") {
+        DocumentType::PostScript
+    } else if looks_like_text(head) {
+        DocumentType::PlainText
+    } else {
+        DocumentType::NativeDelegate
+    }
+}
+
+/// The type to handle `bytes` as, given the type they were `declared` as.
+///
+/// A recognised signature overrides the declaration, so a JPEG picked with
+/// a `.pdf` name still prints as a JPEG — except that a declared text
+/// document stays text while it reads as text, since text can begin with
+/// `%PDF-` or `%!` as well.  Looking like text only replaces
+/// `NativeDelegate` (e.g. `application/octet-stream`): PostScript without
+/// its header and other printer languages can pass for text too.
+pub fn resolve_document_type(declared: DocumentType, bytes: &[u8]) -> DocumentType {
+    match sniff_document_type(bytes) {
+        DocumentType::NativeDelegate => declared,
+        DocumentType::PlainText if declared != DocumentType::NativeDelegate => declared,
+        DocumentType::Pdf | DocumentType::PostScript
+            if declared == DocumentType::PlainText
+                && looks_like_text(&bytes[..bytes.len().min(SNIFF_LEN)]) =>
+        {
+            declared
+        }
+        sniffed => sniffed,
+    }
+}
+
+/// Whether `sample` reads as UTF-8 text, allowing a byte-order mark and a
+/// character cut off at the end of the sample.
+fn looks_like_text(sample: &[u8]) -> bool {
+    let sample = sample.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(sample);
+    let text = match std::str::from_utf8(sample) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&sample[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    !text.is_empty()
+        && !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0C'))
+}
+
+/// How far apart two paper dimensions may be, in millimetres, and still
+/// name the same sheet: inch sizes are rounded to whole millimetres here
+/// (Letter is 215.9 mm wide) and printers round their own names.
+pub const MEDIA_TOLERANCE_MM: f32 = 1.5;
+
+/// Standard paper sizes, or any other size given in millimetres (labels,
+/// envelopes, A6 cards).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PaperSize {
     A4,
     A3,
@@ -123,19 +225,29 @@ pub enum PaperSize {
     Letter,
     Legal,
     Tabloid,
-    Custom { width_mm: u32, height_mm: u32 },
+    Custom { width_mm: f32, height_mm: f32 },
 }
 
 impl PaperSize {
+    /// The named sizes, with their IPP `media` keywords.
+    const NAMED: [(PaperSize, &'static str); 6] = [
+        (Self::A4, "iso_a4_210x297mm"),
+        (Self::A3, "iso_a3_297x420mm"),
+        (Self::A5, "iso_a5_148x210mm"),
+        (Self::Letter, "na_letter_8.5x11in"),
+        (Self::Legal, "na_legal_8.5x14in"),
+        (Self::Tabloid, "na_ledger_11x17in"),
+    ];
+
     /// Dimensions in millimetres (width, height).
-    pub fn dimensions_mm(&self) -> (u32, u32) {
+    pub fn dimensions_mm(&self) -> (f32, f32) {
         match self {
-            Self::A4 => (210, 297),
-            Self::A3 => (297, 420),
-            Self::A5 => (148, 210),
-            Self::Letter => (216, 279),
-            Self::Legal => (216, 356),
-            Self::Tabloid => (279, 432),
+            Self::A4 => (210.0, 297.0),
+            Self::A3 => (297.0, 420.0),
+            Self::A5 => (148.0, 210.0),
+            Self::Letter => (216.0, 279.0),
+            Self::Legal => (216.0, 356.0),
+            Self::Tabloid => (279.0, 432.0),
             Self::Custom {
                 width_mm,
                 height_mm,
@@ -143,17 +255,65 @@ pub fn dimensions_mm(&self) -> (u32, u32) {
         }
     }
 
-    /// IPP `media` keyword (RFC 8011 §5.2.13) for this paper size.
-    pub fn ipp_media_keyword(&self) -> &'static str {
+    /// Whether `other` is the same sheet, whatever it is called: the
+    /// dimensions agree within [`MEDIA_TOLERANCE_MM`] in either orientation.
+    pub fn same_size_as(&self, other: &PaperSize) -> bool {
+        let portrait = |size: &PaperSize| {
+            let (w, h) = size.dimensions_mm();
+            (w.min(h), w.max(h))
+        };
+        let (a_w, a_h) = portrait(self);
+        let (b_w, b_h) = portrait(other);
+        (a_w - b_w).abs() <= MEDIA_TOLERANCE_MM && (a_h - b_h).abs() <= MEDIA_TOLERANCE_MM
+    }
+
+    /// IPP `media` keyword (RFC 8011 §5.2.13, PWG 5101.1) for this paper
+    /// size.  Custom sizes get a self-describing name such as
+    /// `custom_105x148mm_105x148mm`.
+    pub fn to_ipp_media_keyword(&self) -> String {
         match self {
-            Self::A4 => "iso_a4_210x297mm",
-            Self::A3 => "iso_a3_297x420mm",
-            Self::A5 => "iso_a5_148x210mm",
-            Self::Letter => "na_letter_8.5x11in",
-            Self::Legal => "na_legal_8.5x14in",
-            Self::Tabloid => "na_ledger_11x17in",
-            Self::Custom { .. } => "custom", // custom sizes need special handling
+            Self::Custom {
+                width_mm,
+                height_mm,
+            } => {
+                let size = format!("{width_mm}x{height_mm}mm");
+                format!("custom_{size}_{size}")
+            }
+            named => Self::NAMED
+                .iter()
+                .find(|(size, _)| size == named)
+                .map(|(_, keyword)| keyword.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Parse an IPP `media` keyword: a named size's own keyword, or any
+    /// PWG 5101.1 self-describing name (`iso_a6_105x148mm`,
+    /// `na_number-10_4.125x9.5in`) as a custom size.
+    ///
+    /// The `custom_min_` and `custom_max_` entries of `media-supported`
+    /// bound the custom sizes a printer takes and are not sizes themselves.
+    pub fn from_ipp_media_keyword(keyword: &str) -> Option<Self> {
+        if let Some((size, _)) = Self::NAMED.iter().find(|(_, k)| *k == keyword) {
+            return Some(*size);
+        }
+        if keyword.starts_with("custom_min_") || keyword.starts_with("custom_max_") {
+            return None;
         }
+        let dimensions = keyword.rsplit('_').next()?;
+        let (dimensions, mm_per_unit) = if let Some(mm) = dimensions.strip_suffix("mm") {
+            (mm, 1.0)
+        } else {
+            (dimensions.strip_suffix("in")?, 25.4)
+        };
+        let (width, height) = dimensions.split_once('x')?;
+        let width_mm = width.parse::<f32>().ok()? * mm_per_unit;
+        let height_mm = height.parse::<f32>().ok()? * mm_per_unit;
+        (width_mm.is_finite() && width_mm > 0.0 && height_mm.is_finite() && height_mm > 0.0)
+            .then_some(Self::Custom {
+                width_mm,
+                height_mm,
+            })
     }
 }
 
@@ -197,6 +357,54 @@ pub fn ipp_enum_value(&self) -> i32 {
     }
 }
 
+/// Print protocols, ordered from most secure to least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrintProtocol {
+    /// IPP over TLS (port 631, ipps://).
+    Ipps,
+    /// IPP/1.1 plain (port 631, ipp://).
+    Ipp11,
+    /// IPP/1.0 plain (legacy printers).
+    Ipp10,
+    /// LPR/LPD (RFC 1179, port 515).
+    Lpr,
+    /// Raw TCP socket (port 9100, JetDirect).
+    RawTcp,
+}
+
+impl PrintProtocol {
+    /// All protocols in security-preferred order.
+    pub fn chain() -> &'static [PrintProtocol] {
+        &[
+            PrintProtocol::Ipps,
+            PrintProtocol::Ipp11,
+            PrintProtocol::Ipp10,
+            PrintProtocol::Lpr,
+            PrintProtocol::RawTcp,
+        ]
+    }
+
+    /// Human-readable name for UI display.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Ipps => "Secure IPP (TLS)",
+            Self::Ipp11 => "IPP 1.1",
+            Self::Ipp10 => "IPP 1.0",
+            Self::Lpr => "LPR/LPD",
+            Self::RawTcp => "Direct TCP",
+        }
+    }
+
+    /// Default port for this protocol.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Self::Ipps | Self::Ipp11 | Self::Ipp10 => 631,
+            Self::Lpr => 515,
+            Self::RawTcp => 9100,
+        }
+    }
+}
+
 /// Print settings for a job.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrintSettings {
@@ -205,8 +413,12 @@ pub struct PrintSettings {
     pub duplex: DuplexMode,
     pub orientation: Orientation,
     pub color: bool,
+    /// Pages to print; `None` prints the whole document.
     pub page_range: Option<PageRange>,
     pub scale_to_fit: bool,
+    /// Send with exactly this protocol instead of falling back through
+    /// [`PrintProtocol::chain`]. For debugging printer interoperability.
+    pub forced_protocol: Option<PrintProtocol>,
 }
 
 impl Default for PrintSettings {
@@ -219,19 +431,237 @@ fn default() -> Self {
             color: true,
             page_range: None,
             scale_to_fit: true,
+            forced_protocol: None,
         }
     }
 }
 
-/// Page range specification.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PageRange {
+/// One contiguous run of pages inside a [`PageRange`] (1-indexed, inclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageSpan {
     pub start: u32,
-    pub end: u32,
+    /// Last page of the span, or `None` for "to the end of the document".
+    pub end: Option<u32>,
+}
+
+impl PageSpan {
+    /// Whether `page` falls inside this span.
+    pub fn contains(&self, page: u32) -> bool {
+        page >= self.start && self.end.is_none_or(|end| page <= end)
+    }
+}
+
+/// Page range specification, e.g. `"1-3,5,7-"`.
+///
+/// Spans are kept sorted and non-overlapping (IPP `page-ranges` requires
+/// ascending order), so iteration always yields each page once, in order.
+/// Serialised as the canonical string form; the legacy `{start, end}` object
+/// is still accepted when deserialising older job records.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "PageRangeRepr")]
+pub struct PageRange {
+    spans: Vec<PageSpan>,
+}
+
+impl PageRange {
+    /// Parse a comma-separated list of pages and spans: `"1-3,5,7-"`.
+    ///
+    /// Whitespace is ignored. Pages are 1-indexed; `"7-"` means page 7 to
+    /// the last page.
+    pub fn parse(spec: &str) -> Result<Self, PresswerkError> {
+        let mut spans = Vec::new();
+
+        for part in spec.split(',') {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+
+            let span = match part.split_once('-') {
+                Some((start, end)) => {
+                    let start = parse_page_number(start, spec)?;
+                    let end = match end.trim() {
+                        "" => None,
+                        end => Some(parse_page_number(end, spec)?),
+                    };
+                    PageSpan { start, end }
+                }
+                None => {
+                    let page = parse_page_number(part, spec)?;
+                    PageSpan {
+                        start: page,
+                        end: Some(page),
+                    }
+                }
+            };
+
+            if let Some(end) = span.end
+                && end < span.start
+            {
+                return Err(PresswerkError::InvalidPageRange(format!(
+                    "{part} runs backwards in \"{spec}\""
+                )));
+            }
+            spans.push(span);
+        }
+
+        if spans.is_empty() {
+            return Err(PresswerkError::InvalidPageRange(format!(
+                "no pages in \"{spec}\""
+            )));
+        }
+
+        Ok(Self::from_spans(spans))
+    }
+
+    /// A range covering a single page.
+    pub fn single(page: u32) -> Self {
+        Self::span(page, page)
+    }
+
+    /// A closed range `start..=end`. The bounds are swapped if reversed.
+    pub fn span(start: u32, end: u32) -> Self {
+        Self::from_spans(vec![PageSpan {
+            start: start.min(end).max(1),
+            end: Some(start.max(end).max(1)),
+        }])
+    }
+
+    /// An open-ended range from `start` to the last page.
+    pub fn from_page(start: u32) -> Self {
+        Self::from_spans(vec![PageSpan {
+            start: start.max(1),
+            end: None,
+        }])
+    }
+
+    /// The normalised spans making up this range.
+    pub fn spans(&self) -> &[PageSpan] {
+        &self.spans
+    }
+
+    /// Whether `page` (1-indexed) is selected by this range.
+    pub fn contains(&self, page: u32) -> bool {
+        self.spans.iter().any(|span| span.contains(page))
+    }
+
+    /// Iterate over the selected pages of a `total_pages` document in
+    /// ascending order. Pages beyond `total_pages` are skipped.
+    pub fn iter(&self, total_pages: u32) -> impl Iterator<Item = u32> + '_ {
+        self.spans.iter().flat_map(move |span| {
+            let end = span.end.unwrap_or(total_pages).min(total_pages);
+            span.start..=end
+        })
+    }
+
+    /// Number of pages selected from a `total_pages` document.
+    pub fn page_count(&self, total_pages: u32) -> u32 {
+        self.iter(total_pages).count() as u32
+    }
+
+    /// Check that every page this range names exists in a document of
+    /// `total_pages` pages.
+    pub fn validate(&self, total_pages: u32) -> Result<(), PresswerkError> {
+        for span in &self.spans {
+            let last = span.end.unwrap_or(span.start);
+            if last > total_pages {
+                return Err(PresswerkError::InvalidPageRange(format!(
+                    "page {last} requested but the document has {total_pages} pages"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sort and merge overlapping or adjacent spans.
+    fn from_spans(mut spans: Vec<PageSpan>) -> Self {
+        spans.sort_by_key(|span| span.start);
+
+        let mut merged: Vec<PageSpan> = Vec::with_capacity(spans.len());
+        for span in spans {
+            if let Some(last) = merged.last_mut() {
+                let touches = last
+                    .end
+                    .is_none_or(|end| span.start <= end.saturating_add(1));
+                if touches {
+                    last.end = match (last.end, span.end) {
+                        (Some(a), Some(b)) => Some(a.max(b)),
+                        _ => None,
+                    };
+                    continue;
+                }
+            }
+            merged.push(span);
+        }
+
+        Self { spans: merged }
+    }
+}
+
+impl std::fmt::Display for PageRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, span) in self.spans.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            match span.end {
+                Some(end) if end == span.start => write!(f, "{}", span.start)?,
+                Some(end) => write!(f, "{}-{}", span.start, end)?,
+                None => write!(f, "{}-", span.start)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for PageRange {
+    type Err = PresswerkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<PageRange> for String {
+    fn from(range: PageRange) -> Self {
+        range.to_string()
+    }
+}
+
+/// Accepted serialised forms of [`PageRange`].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PageRangeRepr {
+    Spec(String),
+    Legacy { start: u32, end: u32 },
+}
+
+impl TryFrom<PageRangeRepr> for PageRange {
+    type Error = PresswerkError;
+
+    fn try_from(repr: PageRangeRepr) -> Result<Self, Self::Error> {
+        match repr {
+            PageRangeRepr::Spec(spec) => Self::parse(&spec),
+            PageRangeRepr::Legacy { start, end } => Ok(Self::span(start, end)),
+        }
+    }
+}
+
+fn parse_page_number(text: &str, spec: &str) -> Result<u32, PresswerkError> {
+    match text.trim().parse::<u32>() {
+        Ok(0) => Err(PresswerkError::InvalidPageRange(format!(
+            "pages start at 1 in \"{spec}\""
+        ))),
+        Ok(page) => Ok(page),
+        Err(_) => Err(PresswerkError::InvalidPageRange(format!(
+            "\"{}\" is not a page number in \"{spec}\"",
+            text.trim()
+        ))),
+    }
 }
 
 /// Classification of errors for retry logic.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ErrorClass {
     /// Network blip, timeout, busy printer — safe to retry automatically.
     Transient,
@@ -241,6 +671,142 @@ pub enum ErrorClass {
     Permanent,
 }
 
+impl ErrorClass {
+    /// Classify an error for retry decisions and for how the UI words it.
+    pub fn classify(err: &PresswerkError) -> Self {
+        match err {
+            // Transient — network, timeout, temporary server issues
+            PresswerkError::IppStatus { code, detail, .. } => Self::classify_ipp_status(*code)
+                .unwrap_or_else(|| Self::classify_ipp_detail(detail)),
+            PresswerkError::IppRequest(detail) | PresswerkError::IppUnreachable(detail) => {
+                Self::classify_ipp_detail(detail)
+            }
+            PresswerkError::Discovery(_) => Self::Transient,
+            PresswerkError::PrintServer(_) => Self::Transient,
+            PresswerkError::Database(_) => Self::Transient,
+            PresswerkError::Certificate(_) => Self::Transient,
+            PresswerkError::OcrError(_) => Self::Transient,
+
+            // User action needed
+            PresswerkError::NoPrinterSelected => Self::UserAction,
+            PresswerkError::InvalidPageRange(_) => Self::UserAction,
+            PresswerkError::InvalidJobId(_) => Self::UserAction,
+            PresswerkError::PermissionDenied(_) => Self::UserAction,
+
+            // Permanent — wrong format, bad data, platform missing
+            PresswerkError::UnsupportedDocument(_) => Self::Permanent,
+            PresswerkError::PdfError(_) => Self::Permanent,
+            PresswerkError::ImageError(_) => Self::Permanent,
+            PresswerkError::Encryption(_) => Self::Permanent,
+            PresswerkError::Decryption(_) => Self::Permanent,
+            PresswerkError::IntegrityMismatch { .. } => Self::Permanent,
+            PresswerkError::PlatformUnavailable => Self::Permanent,
+            PresswerkError::Bridge(_) => Self::Permanent,
+            PresswerkError::Serialization(_) => Self::Permanent,
+            PresswerkError::InvalidConfig(_) => Self::Permanent,
+
+            // IO errors depend on the kind
+            PresswerkError::Io(io_err) => match io_err.kind() {
+                std::io::ErrorKind::TimedOut
+                | std::io::ErrorKind::ConnectionRefused
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::Interrupted => Self::Transient,
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied => {
+                    Self::UserAction
+                }
+                _ => Self::Transient,
+            },
+        }
+    }
+
+    /// Classify an IPP status code the printer answered with, or `None` for
+    /// a code outside the client-error (0x04xx) and server-error (0x05xx)
+    /// ranges.
+    ///
+    /// Client errors fail the same way when resent, so they are permanent,
+    /// except that credentials can be supplied and a timed-out request can
+    /// be sent again.  Server errors are the printer's own trouble and
+    /// worth retrying, unless it does not support the operation or version.
+    pub(crate) fn classify_ipp_status(code: u16) -> Option<Self> {
+        match code {
+            // client-error-not-authenticated, client-error-not-authorized
+            0x0402 | 0x0403 => Some(Self::UserAction),
+            // client-error-timeout
+            0x0405 => Some(Self::Transient),
+            0x0400..=0x04FF => Some(Self::Permanent),
+            // server-error-operation-not-supported,
+            // server-error-version-not-supported
+            0x0501 | 0x0503 => Some(Self::Permanent),
+            0x0500..=0x05FF => Some(Self::Transient),
+            _ => None,
+        }
+    }
+
+    /// Classify the detail of an IPP (or raw/LPR transport) error.
+    ///
+    /// Matches printer-state-reasons keywords (`media-empty`), IPP status
+    /// names in either spelling (`client-error-bad-request`,
+    /// `ClientErrorBadRequest`) and I/O error text.
+    pub(crate) fn classify_ipp_detail(detail: &str) -> Self {
+        let detail = squash_ipp_detail(detail);
+        let mentions = |needles: &[&str]| needles.iter().any(|n| detail.contains(n));
+
+        // User action required (printer physical state)
+        if mentions(&[
+            "mediaempty",
+            "mediajam",
+            "medianeeded",
+            "paperjam",
+            "tonerempty",
+            "inkempty",
+            "markersupply",
+            "dooropen",
+            "coveropen",
+            "interlockopen",
+        ]) {
+            return Self::UserAction;
+        }
+
+        // Permanent client errors: the same request will fail again
+        if mentions(&[
+            "clienterrordocumentformat",
+            "clienterrornotpossible",
+            "clienterrorbadrequest",
+            "notsupported",
+            "malformed",
+            "invaliduri",
+            "invalidurl",
+        ]) {
+            return Self::Permanent;
+        }
+
+        // Transient network/server errors
+        if mentions(&[
+            "timedout",
+            "connectionrefused",
+            "connectionreset",
+            "brokenpipe",
+            "servererror",
+        ]) {
+            return Self::Transient;
+        }
+
+        // Default to transient (optimistic — retry first, give up later)
+        Self::Transient
+    }
+}
+
+/// `detail` lowercased with everything but letters and digits dropped, so
+/// `media-empty`, `MediaEmpty` and `media empty` compare equal.
+pub(crate) fn squash_ipp_detail(detail: &str) -> String {
+    detail
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
 /// A complete print job.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrintJob {
@@ -268,6 +834,12 @@ pub struct PrintJob {
     pub bytes_sent: u64,
     /// Total document size in bytes.
     pub total_bytes: u64,
+    /// Integer job-id the submitting client knows this job by, for jobs
+    /// received through the IPP print server.
+    pub ipp_job_id: Option<i32>,
+    /// Every delivery attempt, oldest first.
+    #[serde(default)]
+    pub attempts: Vec<AttemptRecord>,
 }
 
 impl PrintJob {
@@ -296,6 +868,81 @@ pub fn new(
             error_history: Vec::new(),
             bytes_sent: 0,
             total_bytes: 0,
+            ipp_job_id: None,
+            attempts: Vec::new(),
+        }
+    }
+
+    /// Why the job failed: the protocols tried and what each one got back.
+    ///
+    /// `None` unless the job has failed and at least one attempt was made.
+    pub fn failure_detail(&self) -> Option<FailureDetail> {
+        if self.status != JobStatus::Failed || self.attempts.is_empty() {
+            return None;
+        }
+        Some(FailureDetail {
+            attempts: self.attempts.clone(),
+        })
+    }
+}
+
+/// The attempts behind a failed job, for users and support.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureDetail {
+    pub attempts: Vec<AttemptRecord>,
+}
+
+/// One try at delivering a job over a single protocol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttemptRecord {
+    pub protocol: PrintProtocol,
+    pub at: DateTime<Utc>,
+    pub outcome: AttemptOutcome,
+}
+
+/// How a delivery attempt ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttemptOutcome {
+    Delivered,
+    Failed {
+        /// The error as reported.
+        error: String,
+        /// IPP status-code name, when the printer answered with one
+        /// (e.g. "ClientErrorDocumentFormatNotSupported").
+        ipp_status: Option<String>,
+        /// How the error was classified; `None` for attempts recorded
+        /// before classification existed.
+        #[serde(default)]
+        error_class: Option<ErrorClass>,
+    },
+}
+
+impl AttemptRecord {
+    /// A successful attempt over `protocol`, made now.
+    pub fn delivered(protocol: PrintProtocol) -> Self {
+        Self {
+            protocol,
+            at: Utc::now(),
+            outcome: AttemptOutcome::Delivered,
+        }
+    }
+
+    /// A failed attempt over `protocol`, made now.
+    pub fn failed(protocol: PrintProtocol, error: &PresswerkError) -> Self {
+        let error_class = Some(ErrorClass::classify(error));
+        let ipp_status = match error {
+            PresswerkError::IppStatus { status, .. } => Some(status.clone()),
+            _ => None,
+        };
+        let error = error.to_string();
+        Self {
+            protocol,
+            at: Utc::now(),
+            outcome: AttemptOutcome::Failed {
+                error,
+                ipp_status,
+                error_class,
+            },
         }
     }
 }
@@ -319,6 +966,62 @@ pub struct DiscoveredPrinter {
     pub stale: bool,
     /// Whether this printer was added manually (IP entry) rather than via mDNS.
     pub manually_added: bool,
+    /// Every address the printer was seen on, `ip` first.  A printer on
+    /// several interfaces (Wi-Fi and Ethernet) has more than one.
+    #[serde(default)]
+    pub addresses: Vec<IpAddr>,
+    /// The printer's `UUID` TXT record, which stays the same across
+    /// interfaces.
+    #[serde(default)]
+    pub uuid: Option<String>,
+    /// Document formats (MIME types) the printer advertised, sorted; empty
+    /// when unknown.
+    #[serde(default)]
+    pub document_formats: Vec<String>,
+}
+
+/// A runtime permission the OS can withhold from the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Permission {
+    /// Talking to devices on the local network (iOS Local Network, Android
+    /// Nearby Wi-Fi devices).  Without it printers cannot be found or
+    /// reached.
+    LocalNetwork,
+    /// Connecting to Bluetooth printers.
+    Bluetooth,
+    /// The camera, for scanning documents.
+    Camera,
+}
+
+impl Permission {
+    /// Every permission the app may ask for.
+    pub const ALL: [Permission; 3] = [Self::LocalNetwork, Self::Bluetooth, Self::Camera];
+
+    /// Whether network printing fails without this permission.
+    pub fn blocks_printing(&self) -> bool {
+        matches!(self, Self::LocalNetwork)
+    }
+
+    /// Human-readable name for UI display.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::LocalNetwork => "Local network access",
+            Self::Bluetooth => "Bluetooth access",
+            Self::Camera => "Camera access",
+        }
+    }
+}
+
+/// Whether the user has granted a [`Permission`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PermissionState {
+    Granted,
+    /// Refused by the user, or blocked by a device policy.
+    Denied,
+    /// Not asked yet; the OS prompts on first use.
+    NotDetermined,
+    /// The platform offers no way to ask (e.g. iOS Local Network).
+    Unknown,
 }
 
 /// Status of the embedded IPP print server.
@@ -329,3 +1032,490 @@ pub enum ServerStatus {
     Running,
     Error,
 }
+
+/// Settings for the embedded IPP print server.
+///
+/// Start from [`ServerConfig::default`] and change what differs:
+///
+/// ```
+/// # use presswerk_core::ServerConfig;
+/// let config = ServerConfig::default()
+///     .with_port(8631)
+///     .with_name("Office Phone");
+/// assert_eq!(config.port, 8631);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    /// TCP port to listen on (IANA-assigned IPP port by default).
+    pub port: u16,
+    /// Name advertised over mDNS and reported as `printer-name`.
+    pub name: String,
+    /// Root directory for received documents and the server certificate.
+    /// `None` uses a `presswerk` directory under the system temp dir.
+    pub data_dir: Option<PathBuf>,
+    /// Connections handled at once; more are closed straight away.
+    pub max_connections: u32,
+    /// How long a client may take to send its whole request.
+    pub read_timeout: Duration,
+    /// Largest request accepted; bigger ones are refused, not truncated.
+    pub max_request_bytes: usize,
+    /// Requests up to this size are held in memory; the document data of
+    /// larger ones is spooled to disk.
+    pub spool_threshold: usize,
+}
+
+impl ServerConfig {
+    /// Port used unless told otherwise.
+    pub const DEFAULT_PORT: u16 = 631;
+    /// Name used unless told otherwise.
+    pub const DEFAULT_NAME: &str = "Presswerk Virtual Printer";
+
+    /// Listen on `port`.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Advertise the server as `name`.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Keep documents and the certificate under `data_dir`.
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    /// Handle at most `max_connections` clients at once.
+    pub fn with_max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Give clients `read_timeout` to send a request.
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Refuse requests over `max_request_bytes`, and spool document data
+    /// to disk once a request passes `spool_threshold`.
+    pub fn with_request_limits(mut self, max_request_bytes: usize, spool_threshold: usize) -> Self {
+        self.max_request_bytes = max_request_bytes;
+        self.spool_threshold = spool_threshold;
+        self
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: Self::DEFAULT_PORT,
+            name: Self::DEFAULT_NAME.into(),
+            data_dir: None,
+            max_connections: 16,
+            read_timeout: Duration::from_secs(120),
+            max_request_bytes: 64 * 1024 * 1024, // 64 MiB
+            spool_threshold: 8 * 1024 * 1024,    // 8 MiB
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffing_recognises_each_signature() {
+        let cases: [(&[u8], DocumentType); 8] = [
+            (b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n1 0 obj", DocumentType::Pdf),
+            (b"\xFF\xD8\xFF\xE0\x00\x10JFIF\x00", DocumentType::Jpeg),
+            (b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR", DocumentType::Png),
+            (b"II*\x00\x08\x00\x00\x00", DocumentType::Tiff),
+            (b"MM\x00*\x00\x00\x00\x08", DocumentType::Tiff),
+            (b"%!PS-Adobe-3.0\n", DocumentType::PostScript),
+            (b"RaS2PwgRaster\x00", DocumentType::PwgRaster),
+            ("Grüße\tvom Drucker\r\n".as_bytes(), DocumentType::PlainText),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(sniff_document_type(bytes), expected, "{bytes:?}");
+        }
+
+        // The header may follow a byte-order mark and whitespace, but not
+        // anything else.
+        assert_eq!(
+            sniff_document_type(b"\xEF\xBB\xBF \r\n%PDF-1.4\n"),
+            DocumentType::Pdf
+        );
+        assert_eq!(
+            sniff_document_type(b"\x00\x00garbage\n%PDF-1.4\n"),
+            DocumentType::NativeDelegate
+        );
+        assert_eq!(sniff_document_type(b""), DocumentType::NativeDelegate);
+        assert_eq!(
+            sniff_document_type(b"PK\x03\x04\x14\x00\x06\x00"),
+            DocumentType::NativeDelegate
+        );
+    }
+
+    #[test]
+    fn ambiguous_text_blob() {
+        // Text that mentions a PDF header inside the sniffed prefix, with a
+        // multi-byte character cut at the sample boundary.
+        let mut blob = b"Save it as %PDF-1.7 and print again.\n".to_vec();
+        blob.extend_from_slice("é".repeat(SNIFF_LEN / 2).as_bytes());
+        assert_eq!(sniff_document_type(&blob), DocumentType::PlainText);
+
+        // PCL escapes and Latin-1 are not text.
+        assert_eq!(
+            sniff_document_type(b"\x1bE\x1b&l0O"),
+            DocumentType::NativeDelegate
+        );
+        assert_eq!(
+            sniff_document_type(b"Gr\xFC\xDFe"),
+            DocumentType::NativeDelegate
+        );
+
+        // Text only settles an unknown declaration.
+        let source = b"/Helvetica findfont 12 scalefont setfont";
+        assert_eq!(
+            resolve_document_type(DocumentType::PostScript, source),
+            DocumentType::PostScript
+        );
+        assert_eq!(
+            resolve_document_type(DocumentType::NativeDelegate, source),
+            DocumentType::PlainText
+        );
+        // A signature wins over other declarations, but declared text that
+        // reads as text stays text.
+        assert_eq!(
+            resolve_document_type(DocumentType::Jpeg, b"%PDF-1.7\n"),
+            DocumentType::Pdf
+        );
+        assert_eq!(
+            resolve_document_type(DocumentType::PlainText, b"%PDF-1.7 notes\n"),
+            DocumentType::PlainText
+        );
+        assert_eq!(
+            resolve_document_type(DocumentType::PlainText, b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n"),
+            DocumentType::Pdf
+        );
+    }
+
+    #[test]
+    fn job_id_short_form_and_parsing() {
+        let id = JobId::new();
+        let short = id.short();
+        assert_eq!(short.len(), 8);
+        assert!(id.to_string().starts_with(&short));
+        assert_eq!(id.to_string().parse::<JobId>().unwrap(), id);
+
+        let err = "not-a-job".parse::<JobId>().unwrap_err();
+        assert!(matches!(err, PresswerkError::InvalidJobId(_)));
+        assert!(err.to_string().contains("\"not-a-job\""), "{err}");
+    }
+
+    #[test]
+    fn page_range_open_ended() {
+        let range = PageRange::parse("7-").unwrap();
+        assert!(!range.contains(6));
+        assert!(range.contains(7));
+        assert!(range.contains(1000));
+        assert_eq!(range.iter(9).collect::<Vec<_>>(), vec![7, 8, 9]);
+        assert_eq!(range.to_string(), "7-");
+    }
+
+    #[test]
+    fn page_range_single_pages_and_spans() {
+        let range: PageRange = " 5, 1-3 ,2".parse().unwrap();
+        assert_eq!(range.iter(10).collect::<Vec<_>>(), vec![1, 2, 3, 5]);
+        assert!(range.contains(5));
+        assert!(!range.contains(4));
+        assert_eq!(range.to_string(), "1-3,5");
+        assert_eq!(PageRange::single(4).to_string(), "4");
+    }
+
+    #[test]
+    fn failed_attempt_keeps_ipp_status() {
+        let attempt = AttemptRecord::failed(
+            PrintProtocol::Ipp11,
+            &PresswerkError::IppStatus {
+                code: 0x040A,
+                status: "ClientErrorDocumentFormatNotSupported".into(),
+                detail: "Print-Job returned status ClientErrorDocumentFormatNotSupported".into(),
+            },
+        );
+        assert_eq!(
+            attempt.outcome,
+            AttemptOutcome::Failed {
+                error: "IPP request failed: Print-Job returned status \
+                        ClientErrorDocumentFormatNotSupported"
+                    .into(),
+                ipp_status: Some("ClientErrorDocumentFormatNotSupported".into()),
+                error_class: Some(ErrorClass::Permanent),
+            }
+        );
+
+        // Only a status the printer sent counts, not one quoted in a message.
+        let quoted = AttemptRecord::failed(
+            PrintProtocol::Lpr,
+            &PresswerkError::IppRequest("queue said: returned status Nonsense".into()),
+        );
+        assert!(matches!(
+            quoted.outcome,
+            AttemptOutcome::Failed {
+                ipp_status: None,
+                ..
+            }
+        ));
+
+        let mut job = PrintJob::new(
+            JobSource::Local,
+            DocumentType::Pdf,
+            "a.pdf".into(),
+            "hash".into(),
+        );
+        job.attempts.push(attempt);
+        assert!(job.failure_detail().is_none());
+        job.status = JobStatus::Failed;
+        assert_eq!(job.failure_detail().unwrap().attempts.len(), 1);
+    }
+
+    #[test]
+    fn transport_failures_are_transient() {
+        for detail in [
+            "Print-Job timed out after 60s",
+            "Raw TCP connect to 10.0.0.5:9100: Connection refused (os error 111)",
+            "LPR send: Connection reset by peer",
+            "Print-Job returned status ServerErrorBusy",
+            "printer aborted job 12",
+        ] {
+            let err = PresswerkError::IppRequest(detail.into());
+            assert_eq!(
+                ErrorClass::classify(&err),
+                ErrorClass::Transient,
+                "{detail}"
+            );
+        }
+        let io = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        assert_eq!(
+            ErrorClass::classify(&PresswerkError::Io(io)),
+            ErrorClass::Transient
+        );
+    }
+
+    #[test]
+    fn printer_state_reasons_need_the_user() {
+        for detail in [
+            "printer is stopped: media-empty-error",
+            "printer is stopped: door-open",
+            "printer stopped: paper-jam",
+            "printer is stopped: toner-empty-warning, cover-open-report",
+        ] {
+            let err = PresswerkError::IppRequest(detail.into());
+            assert_eq!(
+                ErrorClass::classify(&err),
+                ErrorClass::UserAction,
+                "{detail}"
+            );
+        }
+        assert_eq!(
+            ErrorClass::classify(&PresswerkError::NoPrinterSelected),
+            ErrorClass::UserAction
+        );
+    }
+
+    #[test]
+    fn unsupported_or_malformed_requests_are_permanent() {
+        for detail in [
+            "Print-Job returned status ClientErrorDocumentFormatNotSupported",
+            "client-error-document-format-not-supported",
+            "Print-Job returned status ClientErrorBadRequest",
+            "Validate-Job returned status ServerErrorOperationNotSupported",
+            "malformed IPP response",
+        ] {
+            let err = PresswerkError::IppRequest(detail.into());
+            assert_eq!(
+                ErrorClass::classify(&err),
+                ErrorClass::Permanent,
+                "{detail}"
+            );
+        }
+        assert_eq!(
+            ErrorClass::classify(&PresswerkError::UnsupportedDocument("docx".into())),
+            ErrorClass::Permanent
+        );
+    }
+
+    /// A client-error status is classed by its code, whatever the message.
+    #[test]
+    fn client_error_statuses_are_permanent_or_need_the_user() {
+        for (code, status, expected) in [
+            (0x0400, "ClientErrorBadRequest", ErrorClass::Permanent),
+            (0x0401, "ClientErrorForbidden", ErrorClass::Permanent),
+            (
+                0x0402,
+                "ClientErrorNotAuthenticated",
+                ErrorClass::UserAction,
+            ),
+            (0x0403, "ClientErrorNotAuthorized", ErrorClass::UserAction),
+            (0x0405, "ClientErrorTimeout", ErrorClass::Transient),
+            (0x0406, "ClientErrorNotFound", ErrorClass::Permanent),
+            (
+                0x0408,
+                "ClientErrorRequestEntityTooLarge",
+                ErrorClass::Permanent,
+            ),
+            (
+                0x0409,
+                "ClientErrorRequestValueTooLong",
+                ErrorClass::Permanent,
+            ),
+            (
+                0x040A,
+                "ClientErrorDocumentFormatNotSupported",
+                ErrorClass::Permanent,
+            ),
+        ] {
+            let err = PresswerkError::IppStatus {
+                code,
+                status: status.into(),
+                detail: format!("Print-Job returned status {status}"),
+            };
+            assert_eq!(ErrorClass::classify(&err), expected, "{status}");
+        }
+    }
+
+    /// A server-error status is worth retrying unless the printer lacks the
+    /// operation or IPP version altogether.
+    #[test]
+    fn server_error_statuses_are_transient() {
+        for (code, status, expected) in [
+            (0x0500, "ServerErrorInternalError", ErrorClass::Transient),
+            (
+                0x0501,
+                "ServerErrorOperationNotSupported",
+                ErrorClass::Permanent,
+            ),
+            (
+                0x0502,
+                "ServerErrorServiceUnavailable",
+                ErrorClass::Transient,
+            ),
+            (
+                0x0503,
+                "ServerErrorVersionNotSupported",
+                ErrorClass::Permanent,
+            ),
+            (0x0507, "ServerErrorBusy", ErrorClass::Transient),
+        ] {
+            let err = PresswerkError::IppStatus {
+                code,
+                status: status.into(),
+                detail: format!("Print-Job returned status {status}"),
+            };
+            assert_eq!(ErrorClass::classify(&err), expected, "{status}");
+        }
+    }
+
+    #[test]
+    fn page_range_rejects_malformed() {
+        assert!(PageRange::parse("").is_err());
+        assert!(PageRange::parse("0-2").is_err());
+        assert!(PageRange::parse("5-3").is_err());
+        assert!(PageRange::parse("a").is_err());
+    }
+
+    #[test]
+    fn page_range_validates_against_total() {
+        let total = 4;
+        assert!(PageRange::parse("1-4").unwrap().validate(total).is_ok());
+        assert!(PageRange::parse("3-").unwrap().validate(total).is_ok());
+        assert!(PageRange::parse("2,5").unwrap().validate(total).is_err());
+        assert!(PageRange::parse("5-").unwrap().validate(total).is_err());
+    }
+
+    #[test]
+    fn page_range_serde_round_trip_and_legacy() {
+        let range = PageRange::parse("1-3,7-").unwrap();
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, "\"1-3,7-\"");
+        let back: PageRange = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, range);
+
+        let legacy: PageRange = serde_json::from_str(r#"{"start":2,"end":4}"#).unwrap();
+        assert_eq!(legacy.to_string(), "2-4");
+    }
+
+    #[test]
+    fn named_paper_sizes_round_trip_through_media_keywords() {
+        for (size, keyword) in PaperSize::NAMED {
+            assert_eq!(size.to_ipp_media_keyword(), keyword);
+            assert_eq!(PaperSize::from_ipp_media_keyword(keyword), Some(size));
+        }
+    }
+
+    #[test]
+    fn custom_paper_sizes_round_trip_through_media_keywords() {
+        let a6 = PaperSize::Custom {
+            width_mm: 105.0,
+            height_mm: 148.0,
+        };
+        assert_eq!(a6.dimensions_mm(), (105.0, 148.0));
+        assert_eq!(a6.to_ipp_media_keyword(), "custom_105x148mm_105x148mm");
+
+        let label = PaperSize::Custom {
+            width_mm: 62.5,
+            height_mm: 29.0,
+        };
+        for size in [a6, label] {
+            let keyword = size.to_ipp_media_keyword();
+            assert_eq!(PaperSize::from_ipp_media_keyword(&keyword), Some(size));
+        }
+
+        // Other printers' self-describing names parse as custom sizes.
+        assert_eq!(
+            PaperSize::from_ipp_media_keyword("iso_a6_105x148mm"),
+            Some(a6)
+        );
+        let PaperSize::Custom {
+            width_mm,
+            height_mm,
+        } = PaperSize::from_ipp_media_keyword("na_number-10_4.125x9.5in").unwrap()
+        else {
+            panic!("expected a custom size");
+        };
+        assert!((width_mm - 104.775).abs() < 0.01);
+        assert!((height_mm - 241.3).abs() < 0.01);
+
+        for bad in [
+            "custom",
+            "iso_a6",
+            "custom_0x148mm_0x148mm",
+            "custom_axbmm_axbmm",
+            "custom_min_25.4x25.4mm",
+            "custom_max_8.5x14in",
+        ] {
+            assert_eq!(PaperSize::from_ipp_media_keyword(bad), None, "{bad}");
+        }
+
+        // Sizes compare by their dimensions, whatever their name.
+        assert!(a6.same_size_as(&PaperSize::from_ipp_media_keyword("iso_a6_105x148mm").unwrap()));
+        assert!(PaperSize::Letter.same_size_as(
+            &PaperSize::from_ipp_media_keyword("custom_215.9x279.4mm_215.9x279.4mm").unwrap()
+        ));
+        let landscape = PaperSize::Custom {
+            width_mm: 148.0,
+            height_mm: 105.0,
+        };
+        assert!(landscape.same_size_as(&a6));
+        assert!(!a6.same_size_as(&PaperSize::A5));
+
+        // Sizes saved before the dimensions were fractional still load.
+        let legacy: PaperSize =
+            serde_json::from_str(r#"{"Custom":{"width_mm":105,"height_mm":148}}"#).unwrap();
+        assert_eq!(legacy, a6);
+    }
+}
@@ -11,10 +11,15 @@
 
 use std::collections::HashSet;
 
+use image::DynamicImage;
 use tracing::{debug, info, warn};
 
+use presswerk_core::PaperSize;
 use presswerk_core::error::{PresswerkError, Result};
-use presswerk_core::types::DocumentType;
+use presswerk_core::types::{DocumentType, Orientation};
+
+use crate::pdf::writer::{Fit, PdfWriter};
+use crate::scan::enhance::ScanEnhancer;
 
 /// Document converter with format chain.
 pub struct DocumentConverter;
@@ -119,14 +124,19 @@ fn convert(
         (DocumentType::PlainText, DocumentType::Pdf) => {
             let text = String::from_utf8_lossy(document_bytes);
             let writer = crate::pdf::writer::PdfWriter::a4();
-            let pdf_bytes = writer.create_from_text(&text)?;
+            let pdf_bytes = writer.create_from_text(
+                &text,
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )?;
             Ok(pdf_bytes)
         }
 
         // Image → PDF: use PdfWriter
         (DocumentType::Jpeg | DocumentType::Png | DocumentType::Tiff, DocumentType::Pdf) => {
             let writer = crate::pdf::writer::PdfWriter::a4();
-            let pdf_bytes = writer.create_from_image(document_bytes)?;
+            let pdf_bytes =
+                writer.create_from_image(document_bytes, Orientation::Portrait, Fit::Contain)?;
             Ok(pdf_bytes)
         }
 
@@ -163,6 +173,275 @@ fn convert(
     }
 }
 
+// -- Mixed-document assembly ------------------------------------------------
+
+/// One part of a mixed document passed to [`assemble`].
+#[derive(Debug, Clone, Copy)]
+pub enum DocumentInput<'a> {
+    /// An encoded image (JPEG, PNG, ...), placed on a page of its own.
+    Image(&'a [u8]),
+    /// A PDF whose pages are appended as they are.
+    Pdf(&'a [u8]),
+}
+
+/// Combine photos and PDFs into one PDF, in the order given.
+///
+/// Each image becomes one A4 page; each PDF contributes all of its pages.
+/// JPEGs are embedded without re-compression where possible.
+pub fn assemble(inputs: &[DocumentInput<'_>]) -> Result<Vec<u8>> {
+    if inputs.is_empty() {
+        return Err(PresswerkError::PdfError("nothing to assemble".into()));
+    }
+
+    let writer = crate::pdf::writer::PdfWriter::a4();
+    let parts = inputs
+        .iter()
+        .map(|input| match *input {
+            DocumentInput::Image(bytes) => image_page(&writer, bytes),
+            DocumentInput::Pdf(bytes) => Ok(bytes.to_vec()),
+        })
+        .collect::<Result<Vec<Vec<u8>>>>()?;
+
+    info!(parts = parts.len(), "assembling mixed document");
+    let rest: Vec<&[u8]> = parts[1..].iter().map(Vec::as_slice).collect();
+    crate::pdf::reader::PdfReader::from_bytes(&parts[0])?.merge(&rest)
+}
+
+/// Combine scanned or photographed pages into one PDF, one image per
+/// `paper_size` page, in the order given.
+///
+/// An entry that cannot be decoded is skipped with a warning rather than
+/// failing the batch; only a batch with no usable image at all is an
+/// error.  JPEGs are embedded without re-compression where possible.
+pub fn images_to_pdf(images: &[Vec<u8>], paper_size: PaperSize) -> Result<Vec<u8>> {
+    let writer = PdfWriter::new(paper_size);
+    let pages: Vec<Vec<u8>> = images
+        .iter()
+        .enumerate()
+        .filter_map(|(index, bytes)| match image_page(&writer, bytes) {
+            Ok(page) => Some(page),
+            Err(e) => {
+                warn!(index, error = %e, "skipping image that cannot be decoded");
+                None
+            }
+        })
+        .collect();
+
+    let Some((first, rest)) = pages.split_first() else {
+        return Err(PresswerkError::ImageError(format!(
+            "none of the {} images could be decoded",
+            images.len()
+        )));
+    };
+    info!(
+        images = images.len(),
+        pages = pages.len(),
+        "combining images into one PDF"
+    );
+    let rest: Vec<&[u8]> = rest.iter().map(Vec::as_slice).collect();
+    crate::pdf::reader::PdfReader::from_bytes(first)?.merge(&rest)
+}
+
+/// Render one image as a single-page PDF.
+fn image_page(writer: &crate::pdf::writer::PdfWriter, bytes: &[u8]) -> Result<Vec<u8>> {
+    if crate::image::format::detect_format(bytes) == Some(crate::image::format::ImageKind::Jpeg) {
+        // CMYK JPEGs cannot be embedded directly; decode those instead.
+        if let Ok(pdf) = writer.create_from_jpeg(bytes, Orientation::Portrait, Fit::Contain) {
+            return Ok(pdf);
+        }
+    }
+    writer.create_from_image(bytes, Orientation::Portrait, Fit::Contain)
+}
+
+// -- Batch splitting ----------------------------------------------------------
+
+/// What marks the boundary between documents in a scanned batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeparatorKind {
+    /// An empty page (see [`ScanEnhancer::is_blank`]).
+    Blank,
+    /// A page carrying a barcode (QR, Code 128 or Code 39) with this value
+    /// (see [`detect_barcodes`](crate::scan::barcode::detect_barcodes)).
+    Barcode(String),
+}
+
+/// Split a scanned stack into one PDF per document, cutting at separator
+/// pages.
+///
+/// Separator pages are dropped; so are the empty documents left by a
+/// separator at either end or by two in a row.  Pages are laid out on A4.
+pub fn split_batch(pages: &[DynamicImage], separator: SeparatorKind) -> Result<Vec<Vec<u8>>> {
+    let mut documents: Vec<Vec<&DynamicImage>> = vec![Vec::new()];
+    for page in pages {
+        if is_separator(page, &separator)? {
+            documents.push(Vec::new());
+        } else if let Some(current) = documents.last_mut() {
+            current.push(page);
+        }
+    }
+    documents.retain(|document| !document.is_empty());
+
+    info!(
+        pages = pages.len(),
+        documents = documents.len(),
+        "splitting scanned batch"
+    );
+    documents
+        .iter()
+        .map(|document| {
+            let parts = document
+                .iter()
+                .map(|&page| ScanEnhancer::from_dynamic(page.clone(), PaperSize::A4).scan_to_pdf())
+                .collect::<Result<Vec<Vec<u8>>>>()?;
+            let rest: Vec<&[u8]> = parts[1..].iter().map(Vec::as_slice).collect();
+            crate::pdf::reader::PdfReader::from_bytes(&parts[0])?.merge(&rest)
+        })
+        .collect()
+}
+
+/// Whether `page` separates two documents.
+fn is_separator(page: &DynamicImage, separator: &SeparatorKind) -> Result<bool> {
+    match separator {
+        SeparatorKind::Blank => {
+            Ok(ScanEnhancer::from_dynamic(page.clone(), PaperSize::A4).is_blank())
+        }
+        SeparatorKind::Barcode(value) => Ok(crate::scan::barcode::detect_barcodes(page)
+            .iter()
+            .any(|barcode| barcode.value == *value)),
+    }
+}
+
+// -- Page rendering -----------------------------------------------------------
+
+/// Highest resolution pages are rendered at; beyond it a page could exceed
+/// the renderer's 65535-pixel limit.
+#[cfg(feature = "render")]
+const MAX_RENDER_DPI: u32 = 1200;
+
+/// Highest resolution [`pdf_to_images`] renders at: it holds every page in
+/// memory at once, and an A4 page at 300 DPI is already about 26 MB.
+#[cfg(feature = "render")]
+pub const MAX_COLLECTED_DPI: u32 = 300;
+
+/// Render every page of `pdf` at `dpi`, one image per page, on a white
+/// background.
+///
+/// Every page is kept in memory, so a `dpi` above [`MAX_COLLECTED_DPI`] is
+/// refused; use [`for_each_pdf_page`] to render at higher resolutions or to
+/// handle pages one at a time.
+#[cfg(feature = "render")]
+pub fn pdf_to_images(pdf: &[u8], dpi: u32) -> Result<Vec<DynamicImage>> {
+    if dpi > MAX_COLLECTED_DPI {
+        return Err(PresswerkError::PdfError(format!(
+            "{dpi} DPI is above the {MAX_COLLECTED_DPI} DPI all pages can be rendered at \
+             at once; render them one at a time instead"
+        )));
+    }
+    let mut pages = Vec::new();
+    for_each_pdf_page(pdf, dpi, |_, page| {
+        pages.push(page);
+        Ok(())
+    })?;
+    Ok(pages)
+}
+
+/// Render the pages of `pdf` at `dpi` (clamped to 1-1200) on a white
+/// background, handing each to `on_page` with its 0-based index before the
+/// next is rendered.
+///
+/// Password-protected PDFs are refused with a clear error; ones that only
+/// restrict printing or copying open without a password and render.  An
+/// error from `on_page` stops the rendering and is returned.
+#[cfg(feature = "render")]
+pub fn for_each_pdf_page(
+    pdf: &[u8],
+    dpi: u32,
+    mut on_page: impl FnMut(usize, DynamicImage) -> Result<()>,
+) -> Result<()> {
+    let document = load_for_rendering(pdf)?;
+    let scale = dpi.clamp(1, MAX_RENDER_DPI) as f32 / 72.0;
+    let cache = hayro::RenderCache::new();
+    let settings = hayro::hayro_interpret::InterpreterSettings::default();
+
+    let pages = document.pages();
+    for (index, page) in pages.iter().enumerate() {
+        on_page(index, render_page(page, &cache, &settings, scale)?)?;
+    }
+    debug!(pages = pages.len(), dpi, "PDF rendered");
+    Ok(())
+}
+
+/// Render page `page` (1-based) of `pdf` so its longer side is `max_px`
+/// pixels, for previews.
+#[cfg(feature = "render")]
+pub fn thumbnail(pdf: &[u8], page: u32, max_px: u32) -> Result<DynamicImage> {
+    let document = load_for_rendering(pdf)?;
+    let pages = document.pages();
+    let count = pages.len();
+    let page = page
+        .checked_sub(1)
+        .and_then(|index| pages.get(index as usize))
+        .ok_or_else(|| {
+            PresswerkError::InvalidPageRange(format!("page {} of a {}-page document", page, count))
+        })?;
+
+    let (width, height) = page.render_dimensions();
+    let longest = width.max(height).max(1.0);
+    let scale = max_px.clamp(1, u32::from(u16::MAX)) as f32 / longest;
+    let cache = hayro::RenderCache::new();
+    let settings = hayro::hayro_interpret::InterpreterSettings::default();
+    render_page(page, &cache, &settings, scale)
+}
+
+/// Parse `pdf` for the renderer, explaining a password-protected file.
+#[cfg(feature = "render")]
+fn load_for_rendering(pdf: &[u8]) -> Result<hayro::hayro_syntax::Pdf> {
+    use hayro::hayro_syntax::LoadPdfError;
+
+    hayro::hayro_syntax::Pdf::new(pdf.to_vec()).map_err(|err| match err {
+        LoadPdfError::Decryption(_) => {
+            PresswerkError::PdfError("the PDF is password-protected and cannot be previewed".into())
+        }
+        LoadPdfError::Invalid => {
+            PresswerkError::PdfError("the PDF cannot be read for rendering".into())
+        }
+    })
+}
+
+/// Render one page at `scale` pixels per point.
+#[cfg(feature = "render")]
+fn render_page<'a>(
+    page: &'a hayro::hayro_syntax::page::Page<'a>,
+    cache: &hayro::RenderCache<'a>,
+    settings: &hayro::hayro_interpret::InterpreterSettings,
+    scale: f32,
+) -> Result<DynamicImage> {
+    let (width, height) = page.render_dimensions();
+    if width * scale > f32::from(u16::MAX) || height * scale > f32::from(u16::MAX) {
+        return Err(PresswerkError::PdfError(format!(
+            "page of {}×{} pt is too large to render at this resolution",
+            width, height
+        )));
+    }
+
+    let pixmap = hayro::render(
+        page,
+        cache,
+        settings,
+        &hayro::RenderSettings::default(),
+        &hayro::PixmapSettings {
+            x_scale: scale,
+            y_scale: scale,
+            bg_color: hayro::vello_cpu::color::palette::css::WHITE,
+        },
+    );
+    let (width, height) = (u32::from(pixmap.width()), u32::from(pixmap.height()));
+    // The background is opaque, so premultiplied RGBA is plain RGBA.
+    let rgba = image::RgbaImage::from_raw(width, height, pixmap.data_as_u8_slice().to_vec())
+        .ok_or_else(|| PresswerkError::PdfError("renderer returned a short pixmap".into()))?;
+    Ok(DynamicImage::ImageRgba8(rgba).to_rgb8().into())
+}
+
 /// Rasterise a document to PNG as the ultimate fallback.
 fn rasterise_to_png(
     document_bytes: &[u8],
@@ -223,4 +502,193 @@ fn text_to_pdf_conversion() {
         let chain = conversion_chain(DocumentType::PlainText);
         assert_eq!(chain[0], DocumentType::Pdf);
     }
+
+    #[test]
+    fn assemble_puts_image_and_pdf_pages_in_order() {
+        use crate::pdf::reader::PdfReader;
+        use crate::pdf::writer::PdfWriter;
+
+        let photo =
+            image::RgbImage::from_fn(32, 24, |x, y| image::Rgb([x as u8 * 8, y as u8 * 10, 90]));
+        let mut jpeg = Vec::new();
+        image::DynamicImage::ImageRgb8(photo)
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+
+        let writer = PdfWriter::a4();
+        let first = writer
+            .create_from_text(
+                "Page one",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let second = writer
+            .create_from_text(
+                "Page two",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let two_pages = PdfReader::from_bytes(&first)
+            .unwrap()
+            .merge(&[&second])
+            .unwrap();
+
+        let pdf = assemble(&[DocumentInput::Image(&jpeg), DocumentInput::Pdf(&two_pages)]).unwrap();
+        assert_eq!(PdfReader::from_bytes(&pdf).unwrap().page_count(), 3);
+
+        assert!(assemble(&[]).is_err());
+    }
+
+    #[test]
+    fn images_to_pdf_keeps_order_and_skips_undecodable_entries() {
+        use crate::pdf::reader::PdfReader;
+
+        let encode = |width: u32, format: image::ImageFormat| {
+            let mut bytes = Vec::new();
+            DynamicImage::ImageRgb8(image::RgbImage::new(width, 20))
+                .write_to(&mut std::io::Cursor::new(&mut bytes), format)
+                .unwrap();
+            bytes
+        };
+        let images = vec![
+            encode(10, image::ImageFormat::Png),
+            b"not an image".to_vec(),
+            encode(20, image::ImageFormat::Jpeg),
+            encode(30, image::ImageFormat::Png),
+        ];
+
+        let pdf = images_to_pdf(&images, PaperSize::A5).unwrap();
+
+        let reader = PdfReader::from_bytes(&pdf).unwrap();
+        assert_eq!(reader.page_count(), 3);
+        let widths: Vec<u32> = (1..=3)
+            .map(|page| reader.extract_images(page).unwrap()[0].width)
+            .collect();
+        assert_eq!(widths, [10, 20, 30]);
+
+        assert!(images_to_pdf(&[b"junk".to_vec()], PaperSize::A4).is_err());
+        assert!(images_to_pdf(&[], PaperSize::A4).is_err());
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn single_page_pdf_renders_to_one_page_shaped_image() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Rendered",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+
+        let images = pdf_to_images(&pdf, 72).unwrap();
+        assert_eq!(images.len(), 1);
+        let (width, height) = (images[0].width() as f32, images[0].height() as f32);
+        assert!(
+            (width / height - 210.0 / 297.0).abs() < 0.01,
+            "{width}×{height}"
+        );
+        assert!((width - 595.0).abs() <= 1.0, "{width}");
+        // Ink somewhere on a white page.
+        let pixels = images[0].to_luma8();
+        assert!(pixels.pixels().any(|p| p.0[0] < 128));
+        assert!(pixels.get_pixel(0, 0).0[0] > 250);
+
+        let thumb = thumbnail(&pdf, 1, 200).unwrap();
+        assert_eq!(thumb.height(), 200);
+        assert!((thumb.width() as f32 - 200.0 * 210.0 / 297.0).abs() <= 1.0);
+        assert!(thumbnail(&pdf, 2, 200).is_err());
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn pages_stream_one_at_a_time_and_collected_pages_are_capped() {
+        let pdf = PdfWriter::a4()
+            .create_from_text("One", PdfWriter::TEXT_FONT_SIZE, PdfWriter::TEXT_MARGIN_MM)
+            .unwrap();
+        let pdf = crate::pdf::reader::PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .merge(&[pdf.as_slice()])
+            .unwrap();
+
+        let mut seen = Vec::new();
+        for_each_pdf_page(&pdf, 36, |index, page| {
+            seen.push((index, page.width()));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[1].0, 1);
+        assert!((seen[0].1 as f32 - 297.5).abs() <= 1.0, "{:?}", seen);
+
+        // An error from the callback stops the rendering.
+        let mut calls = 0;
+        let err = for_each_pdf_page(&pdf, 36, |_, _| {
+            calls += 1;
+            Err(PresswerkError::PdfError("stop".into()))
+        });
+        assert!(err.is_err());
+        assert_eq!(calls, 1);
+
+        let images = pdf_to_images(&pdf, MAX_COLLECTED_DPI).unwrap();
+        assert!((images[0].width() as f32 - 595.0 * 300.0 / 72.0).abs() <= 2.0);
+        let err = pdf_to_images(&pdf, MAX_COLLECTED_DPI + 1).unwrap_err();
+        assert!(err.to_string().contains("one at a time"), "{err}");
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn password_protected_pdf_is_refused_clearly() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Secret",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let mut doc = lopdf::Document::load_mem(&pdf).unwrap();
+        // The encryption key is derived from the file identifier.
+        let id = lopdf::Object::string_literal(b"presswerk-test-id".to_vec());
+        doc.trailer.set("ID", vec![id.clone(), id]);
+        let state = lopdf::EncryptionState::try_from(lopdf::EncryptionVersion::V2 {
+            document: &doc,
+            owner_password: "owner",
+            user_password: "user",
+            key_length: 128,
+            permissions: lopdf::Permissions::all(),
+        })
+        .unwrap();
+        doc.encrypt(&state).unwrap();
+        let mut encrypted = Vec::new();
+        doc.save_to(&mut encrypted).unwrap();
+
+        let err = pdf_to_images(&encrypted, 72).unwrap_err();
+        assert!(err.to_string().contains("password-protected"), "{err}");
+    }
+
+    #[test]
+    fn split_batch_cuts_at_blank_pages() {
+        use crate::pdf::reader::PdfReader;
+
+        let page = |ink: bool| {
+            DynamicImage::ImageLuma8(image::GrayImage::from_fn(200, 280, |x, y| {
+                let text = ink && (40..160).contains(&x) && y % 20 < 4 && (40..240).contains(&y);
+                image::Luma([if text { 20 } else { 245 }])
+            }))
+        };
+        let pages = [page(true), page(true), page(false), page(true), page(true)];
+
+        let documents = split_batch(&pages, SeparatorKind::Blank).unwrap();
+
+        let page_counts: Vec<usize> = documents
+            .iter()
+            .map(|pdf| PdfReader::from_bytes(pdf).unwrap().page_count())
+            .collect();
+        assert_eq!(page_counts, [2, 2]);
+    }
 }
@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Image format detection from magic bytes, and decoders for formats the
+// `image` crate does not handle itself.
+//
+// WebP is decoded by the `image` crate (its `webp` feature, enabled through
+// our own `webp` feature).  HEIC — the default photo format on iPhones — is
+// decoded with libheif behind the `heic` feature, since it needs the native
+// library at build time.  AVIF shares HEIC's container but is recognised
+// only to be refused clearly: there is no AV1 decoder in the build.
+
+use presswerk_core::error::PresswerkError;
+
+/// Image container formats recognised by [`detect_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    Jpeg,
+    Png,
+    Gif,
+    Bmp,
+    Tiff,
+    WebP,
+    Heic,
+    Avif,
+}
+
+impl ImageKind {
+    /// Human-readable format name for messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "JPEG",
+            Self::Png => "PNG",
+            Self::Gif => "GIF",
+            Self::Bmp => "BMP",
+            Self::Tiff => "TIFF",
+            Self::WebP => "WebP",
+            Self::Heic => "HEIC",
+            Self::Avif => "AVIF",
+        }
+    }
+
+    /// Whether this build can decode the format.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::WebP => cfg!(feature = "webp"),
+            Self::Heic => cfg!(feature = "heic"),
+            Self::Avif => false,
+            _ => true,
+        }
+    }
+}
+
+/// `ftyp` brands used by HEIF files holding HEVC-coded images.
+const HEIC_BRANDS: &[&[u8; 4]] = &[
+    b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis", b"mif1", b"msf1",
+];
+
+/// `ftyp` brands of AVIF, which shares the HEIF container (and its generic
+/// `mif1`/`msf1` brands) but holds AV1-coded images.
+const AVIF_BRANDS: &[&[u8; 4]] = &[b"avif", b"avis"];
+
+/// Identify an image format from its leading bytes.
+///
+/// Returns `None` for anything that is not one of the [`ImageKind`]
+/// formats, including truncated data.
+pub fn detect_format(bytes: &[u8]) -> Option<ImageKind> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageKind::Jpeg)
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(ImageKind::Png)
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(ImageKind::Gif)
+    } else if bytes.starts_with(b"BM") && bytes.len() >= 14 {
+        Some(ImageKind::Bmp)
+    } else if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+        Some(ImageKind::Tiff)
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some(ImageKind::WebP)
+    } else if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        let brands = ftyp_brands(bytes);
+        let has_any = |wanted: &[&[u8; 4]]| brands.iter().any(|brand| wanted.contains(brand));
+        if has_any(AVIF_BRANDS) {
+            Some(ImageKind::Avif)
+        } else if has_any(HEIC_BRANDS) {
+            Some(ImageKind::Heic)
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+/// The major and compatible brands of the `ftyp` box `bytes` starts with,
+/// as far as `bytes` holds them.
+fn ftyp_brands(bytes: &[u8]) -> Vec<&[u8; 4]> {
+    let size = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    let end = size.clamp(12, bytes.len());
+    // Major brand, minor version, then the compatible brands.
+    let compatible = bytes.get(16..end).unwrap_or_default();
+    std::iter::once(&bytes[8..12])
+        .chain(compatible.chunks_exact(4))
+        .filter_map(|brand| brand.try_into().ok())
+        .collect()
+}
+
+/// Decode the primary image of a HEIC file.
+///
+/// libheif applies the container's rotation and mirroring, so the result is
+/// already upright.
+#[cfg(feature = "heic")]
+pub(crate) fn decode_heic(data: &[u8]) -> Result<image::DynamicImage, PresswerkError> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let heif_error = |err: libheif_rs::HeifError| {
+        PresswerkError::ImageError(format!("HEIC decoding failed: {}", err))
+    };
+
+    let context = HeifContext::read_from_bytes(data).map_err(heif_error)?;
+    let handle = context.primary_image_handle().map_err(heif_error)?;
+    let decoded = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+        .map_err(heif_error)?;
+
+    let planes = decoded.planes();
+    let plane = planes
+        .interleaved
+        .ok_or_else(|| PresswerkError::ImageError("HEIC image has no RGB plane".into()))?;
+    let row_len = plane.width as usize * 3;
+    let mut pixels = Vec::with_capacity(row_len * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+
+    image::RgbImage::from_raw(plane.width, plane.height, pixels)
+        .map(image::DynamicImage::ImageRgb8)
+        .ok_or_else(|| PresswerkError::ImageError("HEIC plane size mismatch".into()))
+}
+
+/// Stand-in when HEIC support is not compiled in.
+#[cfg(not(feature = "heic"))]
+pub(crate) fn decode_heic(_data: &[u8]) -> Result<image::DynamicImage, PresswerkError> {
+    Err(unsupported(ImageKind::Heic))
+}
+
+/// The error returned for a recognised format this build cannot decode.
+pub(crate) fn unsupported(kind: ImageKind) -> PresswerkError {
+    PresswerkError::UnsupportedDocument(format!(
+        "{} images are not supported in this build",
+        kind.name()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_common_signatures() {
+        assert_eq!(
+            detect_format(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some(ImageKind::Jpeg)
+        );
+        assert_eq!(
+            detect_format(b"\x89PNG\r\n\x1a\n...."),
+            Some(ImageKind::Png)
+        );
+        assert_eq!(
+            detect_format(b"RIFF\x24\0\0\0WEBPVP8 "),
+            Some(ImageKind::WebP)
+        );
+        assert_eq!(
+            detect_format(b"\0\0\0\x18ftypheic\0\0\0\0"),
+            Some(ImageKind::Heic)
+        );
+        assert_eq!(detect_format(b"\0\0\0\x1cftypisom\0\0\0\0"), None);
+        assert_eq!(detect_format(b"%PDF-1.7"), None);
+        assert_eq!(detect_format(b""), None);
+    }
+
+    #[test]
+    fn avif_is_not_taken_for_heic() {
+        assert_eq!(
+            detect_format(b"\0\0\0\x1cftypavif\0\0\0\0mif1miafMA1B"),
+            Some(ImageKind::Avif)
+        );
+        // Generic HEIF major brand, AVIF only among the compatible ones.
+        assert_eq!(
+            detect_format(b"\0\0\0\x18ftypmif1\0\0\0\0avis\0\0\0\x08meta"),
+            Some(ImageKind::Avif)
+        );
+        // Brands past the end of the ftyp box do not count.
+        assert_eq!(
+            detect_format(b"\0\0\0\x14ftypmif1\0\0\0\0heicavif"),
+            Some(ImageKind::Heic)
+        );
+        assert!(!ImageKind::Avif.is_supported());
+    }
+}
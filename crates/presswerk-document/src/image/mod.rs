@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: PMPL-1.0-or-later
 // Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
 //
-// Image module — resize, rotate, crop, grayscale, and brightness/contrast adjustment.
+// Image module — format detection, resize, rotate, crop, grayscale,
+// brightness/contrast adjustment, and dithering.
 
+pub mod format;
 pub mod processor;
 
-pub use processor::ImageProcessor;
+pub use format::{ImageKind, detect_format};
+pub use processor::{Dither, ImageProcessor};
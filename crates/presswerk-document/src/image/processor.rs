@@ -4,12 +4,44 @@
 // Image processor — resize, rotate, crop, grayscale, brightness/contrast
 // adjustment. Operates on in-memory images using the `image` and `imageproc`
 // crates.
+//
+// Images are decoded upright: the EXIF orientation tag written by phone
+// cameras is applied on load, so portrait photos don't arrive sideways.
+
+use std::io::Cursor;
 
-use image::{DynamicImage, ImageFormat, RgbaImage};
+use image::codecs::jpeg::{JpegEncoder, PixelDensity};
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Rgba, RgbaImage};
 use imageproc::geometric_transformations::{self, Interpolation};
 use presswerk_core::error::PresswerkError;
 use tracing::{debug, info, instrument};
 
+use super::format::{self, ImageKind, detect_format};
+
+/// JPEG quality used when [`ImageProcessor::auto_orient`] has to re-encode a
+/// photo; high enough that the extra generation is not visible.
+const REORIENT_JPEG_QUALITY: u8 = 95;
+
+/// 4×4 Bayer threshold matrix for ordered dithering.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Largest spread between a pixel's red, green and blue values still taken
+/// as grey.  Scans and JPEGs of black-and-white pages carry some colour
+/// noise.
+const GRAY_TOLERANCE: u8 = 24;
+
+/// Algorithm used by [`ImageProcessor::dither`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Floyd–Steinberg error diffusion. Best tonal fidelity for photos.
+    FloydSteinberg,
+    /// Ordered dithering with a 4×4 Bayer matrix. A regular cross-hatch
+    /// pattern that survives low-resolution print heads better than
+    /// diffusion noise.
+    Bayer4x4,
+}
+
 /// Image processing pipeline operating on a single in-memory image.
 ///
 /// All operations are non-destructive: each method consumes `self` and returns a
@@ -17,8 +49,9 @@
 ///
 /// ```ignore
 /// let result = ImageProcessor::open("photo.jpg")?
+///     .crop(0, 0, 1200, 900)?
 ///     .resize(800, 600)
-///     .rotate(90.0)
+///     .rotate(90.0, Rgba([255, 255, 255, 255]))
 ///     .grayscale()
 ///     .adjust_brightness(10)
 ///     .to_png_bytes()?;
@@ -26,42 +59,106 @@
 pub struct ImageProcessor {
     /// The current working image.
     image: DynamicImage,
+    /// Resolution recorded in encoded output, if known.
+    dpi: Option<u32>,
 }
 
 impl ImageProcessor {
     // -- Construction ---------------------------------------------------------
 
-    /// Load an image from a file path.
+    /// Load an image from a file path, applying its EXIF orientation.
     #[instrument(skip_all, fields(path = %path.as_ref().display()))]
     pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, PresswerkError> {
-        let img = image::open(path.as_ref()).map_err(|err| {
+        let data = std::fs::read(path.as_ref()).map_err(|err| {
             PresswerkError::ImageError(format!(
                 "failed to open {}: {}",
                 path.as_ref().display(),
                 err
             ))
         })?;
+        let img = decode_upright(&data)?;
         info!(width = img.width(), height = img.height(), "Image loaded");
-        Ok(Self { image: img })
+        Ok(Self {
+            image: img,
+            dpi: None,
+        })
     }
 
-    /// Create a processor from raw encoded bytes (JPEG, PNG, etc.).
+    /// Create a processor from raw encoded bytes (JPEG, PNG, WebP, HEIC with
+    /// the `heic` feature, etc.), applying their EXIF orientation.
     #[instrument(skip(data), fields(data_len = data.len()))]
     pub fn from_bytes(data: &[u8]) -> Result<Self, PresswerkError> {
-        let img = image::load_from_memory(data).map_err(|err| {
-            PresswerkError::ImageError(format!("failed to decode image: {}", err))
-        })?;
+        let img = decode_upright(data)?;
         debug!(
             width = img.width(),
             height = img.height(),
             "Image decoded from bytes"
         );
-        Ok(Self { image: img })
+        Ok(Self {
+            image: img,
+            dpi: None,
+        })
+    }
+
+    /// Bake the EXIF orientation of an encoded image into its pixels.
+    ///
+    /// Returns the input unchanged when it has no orientation tag (or the
+    /// identity one). Otherwise the image is rotated/flipped upright and
+    /// re-encoded in its original format without EXIF data, so downstream
+    /// tools that honour the tag don't rotate it a second time. JPEGs are
+    /// re-encoded at quality 95.
+    #[instrument(skip(data), fields(data_len = data.len()))]
+    pub fn auto_orient(data: &[u8]) -> Result<Vec<u8>, PresswerkError> {
+        // HEIC keeps its rotation in the container rather than EXIF, and
+        // libheif applies it on decode.
+        if detect_format(data) == Some(ImageKind::Heic) {
+            return Ok(data.to_vec());
+        }
+
+        let decode_error = |err: image::ImageError| {
+            PresswerkError::ImageError(format!("failed to decode image: {}", err))
+        };
+
+        let reader = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(|err| decode_error(err.into()))?;
+        let format = reader.format().ok_or_else(|| {
+            PresswerkError::UnsupportedDocument("unrecognised image format".into())
+        })?;
+        let mut decoder = reader.into_decoder().map_err(decode_error)?;
+        let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+        if orientation == Orientation::NoTransforms {
+            return Ok(data.to_vec());
+        }
+
+        let mut img = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+        img.apply_orientation(orientation);
+        info!(
+            ?orientation,
+            width = img.width(),
+            height = img.height(),
+            "EXIF orientation applied"
+        );
+
+        match format {
+            ImageFormat::Jpeg => encode_jpeg(&img, REORIENT_JPEG_QUALITY),
+            other => encode_to_format(&img, other),
+        }
     }
 
     /// Wrap an already-decoded `DynamicImage`.
     pub fn from_dynamic(image: DynamicImage) -> Self {
-        Self { image }
+        Self { image, dpi: None }
+    }
+
+    /// Record the image resolution, e.g. the scanner's native DPI, so that
+    /// encoded output tells printers its physical size instead of leaving
+    /// them to assume 72 DPI.
+    pub fn with_dpi(self, dpi: u32) -> Self {
+        Self {
+            dpi: Some(dpi),
+            ..self
+        }
     }
 
     // -- Accessors ------------------------------------------------------------
@@ -76,6 +173,11 @@ pub fn height(&self) -> u32 {
         self.image.height()
     }
 
+    /// Resolution set with [`with_dpi`](Self::with_dpi), if any.
+    pub fn dpi(&self) -> Option<u32> {
+        self.dpi
+    }
+
     /// Borrow the underlying `DynamicImage`.
     pub fn as_dynamic(&self) -> &DynamicImage {
         &self.image
@@ -107,7 +209,10 @@ pub fn resize(self, max_width: u32, max_height: u32) -> Self {
             new_h = resized.height(),
             "Resize complete"
         );
-        Self { image: resized }
+        Self {
+            image: resized,
+            ..self
+        }
     }
 
     /// Resize the image to exactly `width` x `height`, ignoring aspect ratio.
@@ -115,16 +220,20 @@ pub fn resize_exact(self, width: u32, height: u32) -> Self {
         let resized = self
             .image
             .resize_exact(width, height, image::imageops::FilterType::Lanczos3);
-        Self { image: resized }
+        Self {
+            image: resized,
+            ..self
+        }
     }
 
     /// Rotate the image by an arbitrary angle in degrees (clockwise).
     ///
     /// For 90/180/270 degree rotations, lossless rotation is used. For other
     /// angles, affine transformation with bilinear interpolation is applied and
-    /// the canvas expands to contain the rotated image.
+    /// the canvas expands to contain the rotated image, with the uncovered
+    /// corners painted in `fill`.
     #[instrument(skip(self), fields(degrees))]
-    pub fn rotate(self, degrees: f32) -> Self {
+    pub fn rotate(self, degrees: f32, fill: Rgba<u8>) -> Self {
         info!(degrees, "Rotating image");
 
         // Fast-path for exact multiples of 90.
@@ -132,58 +241,81 @@ pub fn rotate(self, degrees: f32) -> Self {
         if (normalised - 90.0).abs() < 0.01 {
             return Self {
                 image: self.image.rotate90(),
+                ..self
             };
         }
         if (normalised - 180.0).abs() < 0.01 {
             return Self {
                 image: self.image.rotate180(),
+                ..self
             };
         }
         if (normalised - 270.0).abs() < 0.01 {
             return Self {
                 image: self.image.rotate270(),
+                ..self
             };
         }
         if normalised.abs() < 0.01 || (normalised - 360.0).abs() < 0.01 {
             return self;
         }
 
-        // General rotation via imageproc's rotate_about_center helper.
+        // Centre the image on a canvas large enough for its rotated bounding
+        // box, then rotate about the centre with imageproc.
         let rgba = self.image.to_rgba8();
         let radians = degrees.to_radians();
-        let default_pixel = image::Rgba([255u8, 255, 255, 0]);
+        let (w, h) = (rgba.width() as f32, rgba.height() as f32);
+        let (sin, cos) = (radians.sin().abs(), radians.cos().abs());
+        let canvas_w = (w * cos + h * sin).ceil() as u32;
+        let canvas_h = (w * sin + h * cos).ceil() as u32;
+
+        let mut canvas = RgbaImage::from_pixel(canvas_w, canvas_h, fill);
+        image::imageops::replace(
+            &mut canvas,
+            &rgba,
+            ((canvas_w - rgba.width()) / 2).into(),
+            ((canvas_h - rgba.height()) / 2).into(),
+        );
 
         let rotated: RgbaImage = geometric_transformations::rotate_about_center(
-            &rgba,
+            &canvas,
             radians,
             Interpolation::Bilinear,
-            default_pixel,
+            fill,
         );
 
-        debug!("General rotation applied");
+        debug!(canvas_w, canvas_h, "General rotation applied");
         Self {
             image: DynamicImage::ImageRgba8(rotated),
+            ..self
         }
     }
 
     /// Crop a rectangular region from the image.
     ///
     /// `x` and `y` are the top-left corner; `width` and `height` define the
-    /// size of the crop rectangle. Values are clamped to image bounds.
+    /// size of the crop rectangle, which must be non-empty and lie entirely
+    /// within the image.
     #[instrument(skip(self), fields(x, y, width, height))]
-    pub fn crop(self, x: u32, y: u32, width: u32, height: u32) -> Self {
-        let img_w = self.image.width();
-        let img_h = self.image.height();
-
-        let safe_x = x.min(img_w.saturating_sub(1));
-        let safe_y = y.min(img_h.saturating_sub(1));
-        let safe_w = width.min(img_w - safe_x);
-        let safe_h = height.min(img_h - safe_y);
+    pub fn crop(self, x: u32, y: u32, width: u32, height: u32) -> Result<Self, PresswerkError> {
+        let (img_w, img_h) = (self.image.width(), self.image.height());
+        let fits =
+            |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+
+        if width == 0 || height == 0 || !fits(x, width, img_w) || !fits(y, height, img_h) {
+            return Err(PresswerkError::ImageError(format!(
+                "crop rectangle {}x{} at ({}, {}) is outside the {}x{} image",
+                width, height, x, y, img_w, img_h
+            )));
+        }
 
-        info!(safe_x, safe_y, safe_w, safe_h, "Cropping image");
+        info!(x, y, width, height, "Cropping image");
 
-        let cropped = self.image.crop_imm(safe_x, safe_y, safe_w, safe_h);
-        Self { image: cropped }
+        let cropped = self.image.crop_imm(x, y, width, height);
+        Ok(Self {
+            image: cropped,
+            ..self
+        })
     }
 
     /// Convert the image to grayscale (luma).
@@ -192,9 +324,16 @@ pub fn grayscale(self) -> Self {
         info!("Converting to grayscale");
         Self {
             image: self.image.grayscale(),
+            ..self
         }
     }
 
+    /// Whether the image has colour a black-and-white printer would lose,
+    /// i.e. any pixel clearly off the grey axis.
+    pub fn has_color(&self) -> bool {
+        has_color(&self.image)
+    }
+
     /// Adjust brightness by `value` (-255..=255).
     ///
     /// Positive values brighten, negative values darken. The value is clamped to
@@ -218,6 +357,7 @@ pub fn adjust_brightness(self, value: i32) -> Self {
         });
         Self {
             image: DynamicImage::ImageRgba8(brightened),
+            ..self
         }
     }
 
@@ -241,21 +381,68 @@ pub fn adjust_contrast(self, factor: f32) -> Self {
 
         Self {
             image: DynamicImage::ImageRgba8(contrasted),
+            ..self
+        }
+    }
+
+    /// Reduce the image to pure black and white while keeping its perceived
+    /// tone, for thermal, dot-matrix and other 1-bit printers.
+    ///
+    /// Unlike a hard threshold, mid-greys become a proportional mix of black
+    /// and white dots. The result is a greyscale image holding only 0 and
+    /// 255.
+    #[instrument(skip(self), fields(?algorithm))]
+    pub fn dither(self, algorithm: Dither) -> Self {
+        info!(?algorithm, "Dithering image");
+
+        let mut gray = self.image.to_luma8();
+        match algorithm {
+            Dither::FloydSteinberg => floyd_steinberg(&mut gray),
+            Dither::Bayer4x4 => {
+                for (x, y, pixel) in gray.enumerate_pixels_mut() {
+                    // Thresholds sit mid-way between the 17 tone levels.
+                    let level = BAYER_4X4[(y % 4) as usize][(x % 4) as usize] as u16;
+                    let threshold = level * 16 + 8;
+                    pixel.0[0] = if pixel.0[0] as u16 > threshold {
+                        255
+                    } else {
+                        0
+                    };
+                }
+            }
+        }
+
+        Self {
+            image: DynamicImage::ImageLuma8(gray),
+            ..self
         }
     }
 
     // -- Output ---------------------------------------------------------------
 
     /// Encode the current image as PNG bytes.
+    ///
+    /// With a resolution set via [`with_dpi`](Self::with_dpi) the PNG carries
+    /// a `pHYs` chunk recording it.
     pub fn to_png_bytes(&self) -> Result<Vec<u8>, PresswerkError> {
-        encode_to_format(&self.image, ImageFormat::Png)
+        let png = encode_to_format(&self.image, ImageFormat::Png)?;
+        Ok(match self.dpi {
+            Some(dpi) => insert_png_phys(png, dpi),
+            None => png,
+        })
     }
 
     /// Encode the current image as JPEG bytes with the given quality (1-100).
+    ///
+    /// A resolution set via [`with_dpi`](Self::with_dpi) is written as the
+    /// JFIF pixel density.
     pub fn to_jpeg_bytes(&self, quality: u8) -> Result<Vec<u8>, PresswerkError> {
         let mut buffer = Vec::new();
         let rgb = self.image.to_rgb8();
-        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, quality);
+        let mut encoder = JpegEncoder::new_with_quality(&mut buffer, quality);
+        if let Some(dpi) = self.dpi {
+            encoder.set_pixel_density(PixelDensity::dpi(dpi.min(u16::MAX as u32) as u16));
+        }
         rgb.write_with_encoder(encoder)
             .map_err(|err| PresswerkError::ImageError(format!("JPEG encoding failed: {}", err)))?;
         Ok(buffer)
@@ -273,6 +460,124 @@ pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), PresswerkErr
     }
 }
 
+/// Decode an image of any supported format and apply its EXIF orientation.
+///
+/// Recognised formats this build cannot decode (HEIC without the `heic`
+/// feature, say) and unrecognised data fail with `UnsupportedDocument`
+/// rather than a generic decode error.
+pub(crate) fn decode_upright(data: &[u8]) -> Result<DynamicImage, PresswerkError> {
+    let kind = detect_format(data);
+    match kind {
+        Some(ImageKind::Heic) => return format::decode_heic(data),
+        Some(kind) if !kind.is_supported() => return Err(format::unsupported(kind)),
+        _ => {}
+    }
+
+    let decode_error = |err: image::ImageError| {
+        let name = kind.map_or("image", |kind| kind.name());
+        PresswerkError::ImageError(format!("failed to decode {}: {}", name, err))
+    };
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| decode_error(err.into()))?;
+    if reader.format().is_none() {
+        return Err(PresswerkError::UnsupportedDocument(
+            "unrecognised image format".into(),
+        ));
+    }
+
+    let mut decoder = reader.into_decoder().map_err(decode_error)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut img = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    if orientation != Orientation::NoTransforms {
+        debug!(?orientation, "Applying EXIF orientation");
+        img.apply_orientation(orientation);
+    }
+    Ok(img)
+}
+
+/// Whether any pixel of `image` is further than [`GRAY_TOLERANCE`] from
+/// grey.  Single-channel images are grey by construction.
+pub(crate) fn has_color(image: &DynamicImage) -> bool {
+    if !image.color().has_color() {
+        return false;
+    }
+    image.to_rgb8().pixels().any(|pixel| {
+        let [r, g, b] = pixel.0;
+        r.max(g).max(b) - r.min(g).min(b) > GRAY_TOLERANCE
+    })
+}
+
+/// Floyd–Steinberg error diffusion in place: each pixel is snapped to black
+/// or white and the rounding error is pushed onto its unvisited neighbours
+/// (7/16 right, 3/16 below-left, 5/16 below, 1/16 below-right).
+fn floyd_steinberg(gray: &mut image::GrayImage) {
+    let (width, height) = (gray.width() as usize, gray.height() as usize);
+    let mut values: Vec<f32> = gray.as_raw().iter().map(|&v| v as f32).collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let old = values[index];
+            let new = if old < 128.0 { 0.0 } else { 255.0 };
+            values[index] = new;
+            let error = old - new;
+
+            if x + 1 < width {
+                values[index + 1] += error * 7.0 / 16.0;
+            }
+            if y + 1 < height {
+                let below = index + width;
+                if x > 0 {
+                    values[below - 1] += error * 3.0 / 16.0;
+                }
+                values[below] += error * 5.0 / 16.0;
+                if x + 1 < width {
+                    values[below + 1] += error / 16.0;
+                }
+            }
+        }
+    }
+
+    for (pixel, value) in gray.pixels_mut().zip(values) {
+        pixel.0[0] = value as u8;
+    }
+}
+
+/// Encode as baseline JPEG, keeping greyscale images single-channel.
+pub(crate) fn encode_jpeg(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, PresswerkError> {
+    let mut buffer = Vec::new();
+    let encoder = JpegEncoder::new_with_quality(&mut buffer, quality);
+    let result = match image {
+        DynamicImage::ImageLuma8(gray) => gray.write_with_encoder(encoder),
+        other => other.to_rgb8().write_with_encoder(encoder),
+    };
+    result.map_err(|err| PresswerkError::ImageError(format!("JPEG encoding failed: {}", err)))?;
+    Ok(buffer)
+}
+
+/// Insert a `pHYs` chunk recording `dpi` right after the IHDR chunk of an
+/// encoded PNG (and so before any image data, as the spec requires).
+fn insert_png_phys(mut png: Vec<u8>, dpi: u32) -> Vec<u8> {
+    // Signature (8) + IHDR length, type, 13 data bytes and CRC.
+    const IHDR_END: usize = 8 + 4 + 4 + 13 + 4;
+
+    let pixels_per_metre = (dpi as f64 / 0.0254).round() as u32;
+    let mut body = b"pHYs".to_vec();
+    body.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    body.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    body.push(1); // unit: metre
+
+    let mut crc = flate2::Crc::new();
+    crc.update(&body);
+
+    let mut chunk = 9u32.to_be_bytes().to_vec();
+    chunk.extend_from_slice(&body);
+    chunk.extend_from_slice(&crc.sum().to_be_bytes());
+    png.splice(IHDR_END..IHDR_END, chunk);
+    png
+}
+
 /// Encode a `DynamicImage` into the specified format, returning the raw bytes.
 fn encode_to_format(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, PresswerkError> {
     let mut buffer = Vec::new();
@@ -282,3 +587,212 @@ fn encode_to_format(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>
         .map_err(|err| PresswerkError::ImageError(format!("image encoding failed: {}", err)))?;
     Ok(buffer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageEncoder, Rgb, RgbImage};
+
+    /// Little-endian TIFF block with a single IFD entry: Orientation (0x0112).
+    fn exif_orientation(value: u8) -> Vec<u8> {
+        let mut exif = b"II*\0\x08\0\0\0".to_vec();
+        exif.extend_from_slice(&[1, 0]); // one entry
+        exif.extend_from_slice(&[0x12, 0x01, 3, 0, 1, 0, 0, 0, value, 0, 0, 0]);
+        exif.extend_from_slice(&[0, 0, 0, 0]); // no next IFD
+        exif
+    }
+
+    /// A 40×20 JPEG, red on the left half and blue on the right, tagged with
+    /// the given EXIF orientation.
+    fn tagged_jpeg(orientation: u8) -> Vec<u8> {
+        let img = RgbImage::from_fn(40, 20, |x, _| {
+            if x < 20 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        let mut buffer = Vec::new();
+        let mut encoder = JpegEncoder::new_with_quality(&mut buffer, 90);
+        encoder
+            .set_exif_metadata(exif_orientation(orientation))
+            .unwrap();
+        encoder
+            .write_image(img.as_raw(), 40, 20, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        buffer
+    }
+
+    fn orientation_of(data: &[u8]) -> Orientation {
+        ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .unwrap()
+            .into_decoder()
+            .unwrap()
+            .orientation()
+            .unwrap()
+    }
+
+    #[test]
+    fn from_bytes_applies_exif_rotation() {
+        let jpeg = tagged_jpeg(6);
+        assert_eq!(orientation_of(&jpeg), Orientation::Rotate90);
+
+        let processor = ImageProcessor::from_bytes(&jpeg).unwrap();
+        assert_eq!((processor.width(), processor.height()), (20, 40));
+        // Rotated 90° clockwise: the red left half ends up on top.
+        let rgb = processor.into_dynamic().to_rgb8();
+        assert!(rgb.get_pixel(10, 5)[0] > 200);
+        assert!(rgb.get_pixel(10, 35)[2] > 200);
+    }
+
+    #[test]
+    fn auto_orient_rotates_and_strips_tag() {
+        let upright = ImageProcessor::auto_orient(&tagged_jpeg(6)).unwrap();
+
+        assert_eq!(orientation_of(&upright), Orientation::NoTransforms);
+        let img = image::load_from_memory(&upright).unwrap();
+        assert_eq!((img.width(), img.height()), (20, 40));
+    }
+
+    #[test]
+    fn auto_orient_handles_every_orientation() {
+        for value in 1..=8u8 {
+            let upright = ImageProcessor::auto_orient(&tagged_jpeg(value)).unwrap();
+            let img = image::load_from_memory(&upright).unwrap();
+            let expected = if value >= 5 { (20, 40) } else { (40, 20) };
+            assert_eq!((img.width(), img.height()), expected, "orientation {value}");
+            assert_eq!(orientation_of(&upright), Orientation::NoTransforms);
+        }
+    }
+
+    #[test]
+    fn quarter_turn_swaps_dimensions() {
+        let processor = ImageProcessor::from_dynamic(DynamicImage::new_rgb8(20, 30))
+            .rotate(90.0, Rgba([255, 255, 255, 255]));
+        assert_eq!((processor.width(), processor.height()), (30, 20));
+    }
+
+    #[test]
+    fn arbitrary_rotation_expands_canvas_with_fill() {
+        let fill = Rgba([10, 20, 30, 255]);
+        let rotated = ImageProcessor::from_dynamic(DynamicImage::new_rgb8(100, 100))
+            .rotate(45.0, fill)
+            .into_dynamic()
+            .to_rgba8();
+
+        // A 100 px square turned 45° needs about 142 px each way.
+        assert!(
+            (141..=142).contains(&rotated.width()),
+            "{}",
+            rotated.width()
+        );
+        assert_eq!(rotated.width(), rotated.height());
+        assert_eq!(*rotated.get_pixel(0, 0), fill);
+        assert_eq!(*rotated.get_pixel(71, 71), Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn crop_rejects_rectangles_outside_the_image() {
+        let processor = || ImageProcessor::from_dynamic(DynamicImage::new_rgb8(40, 30));
+
+        let cropped = processor().crop(10, 5, 30, 25).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (30, 25));
+
+        assert!(processor().crop(10, 5, 31, 25).is_err());
+        assert!(processor().crop(0, 30, 1, 1).is_err());
+        assert!(processor().crop(0, 0, 0, 10).is_err());
+        assert!(processor().crop(u32::MAX, 0, 2, 1).is_err());
+    }
+
+    #[test]
+    fn from_bytes_decodes_webp() {
+        let mut webp = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(7, 5, Rgb([0, 128, 255])))
+            .write_to(&mut Cursor::new(&mut webp), ImageFormat::WebP)
+            .unwrap();
+        assert_eq!(detect_format(&webp), Some(ImageKind::WebP));
+
+        let processor = ImageProcessor::from_bytes(&webp).unwrap();
+        assert_eq!((processor.width(), processor.height()), (7, 5));
+        assert_eq!(
+            processor.into_dynamic().to_rgb8().get_pixel(3, 2),
+            &Rgb([0, 128, 255])
+        );
+    }
+
+    #[test]
+    fn unknown_and_disabled_formats_are_unsupported() {
+        let err = ImageProcessor::from_bytes(b"definitely not an image").err();
+        assert!(matches!(err, Some(PresswerkError::UnsupportedDocument(_))));
+
+        if !cfg!(feature = "heic") {
+            let heic = b"\0\0\0\x18ftypheic\0\0\0\0mif1heic";
+            let err = ImageProcessor::from_bytes(heic).err();
+            assert!(matches!(err, Some(PresswerkError::UnsupportedDocument(_))));
+        }
+    }
+
+    #[test]
+    fn auto_orient_passes_untagged_images_through() {
+        let png = ImageProcessor::from_dynamic(DynamicImage::new_rgb8(4, 3))
+            .to_png_bytes()
+            .unwrap();
+        assert_eq!(ImageProcessor::auto_orient(&png).unwrap(), png);
+    }
+
+    #[test]
+    fn dpi_is_recorded_in_png_and_jpeg_output() {
+        let processor = ImageProcessor::from_dynamic(DynamicImage::new_luma8(8, 8))
+            .with_dpi(300)
+            .grayscale();
+        assert_eq!(processor.dpi(), Some(300));
+
+        let png = processor.to_png_bytes().unwrap();
+        let at = png
+            .windows(4)
+            .position(|w| w == b"pHYs")
+            .expect("pHYs chunk");
+        assert!(at < png.windows(4).position(|w| w == b"IDAT").unwrap());
+        // 300 DPI is 11811 pixels per metre, unit 1 (metre).
+        let mut phys = 11811u32.to_be_bytes().repeat(2);
+        phys.push(1);
+        assert_eq!(&png[at + 4..at + 13], &phys[..]);
+        // The decoder checks chunk CRCs, so this also validates the chunk.
+        assert!(ImageProcessor::from_bytes(&png).is_ok());
+
+        // JFIF APP0: units 1 (dots per inch), then X and Y density.
+        let jpeg = processor.to_jpeg_bytes(90).unwrap();
+        assert_eq!(&jpeg[6..11], b"JFIF\0");
+        assert_eq!(&jpeg[13..18], &[1, 0x01, 0x2C, 0x01, 0x2C]);
+    }
+
+    #[test]
+    fn dithered_gradient_keeps_tone_across_bands() {
+        // Horizontal ramp from black to white, split into four bands.
+        let ramp = image::GrayImage::from_fn(256, 64, |x, _| image::Luma([x as u8]));
+
+        for algorithm in [Dither::FloydSteinberg, Dither::Bayer4x4] {
+            let dithered = ImageProcessor::from_dynamic(DynamicImage::ImageLuma8(ramp.clone()))
+                .dither(algorithm)
+                .into_dynamic()
+                .to_luma8();
+            assert!(dithered.pixels().all(|p| p.0[0] == 0 || p.0[0] == 255));
+
+            for band in 0..4 {
+                let columns = band * 64..(band + 1) * 64;
+                let black = dithered
+                    .enumerate_pixels()
+                    .filter(|(x, _, p)| columns.contains(x) && p.0[0] == 0)
+                    .count();
+                let density = black as f32 / (64.0 * 64.0);
+                // Band means are 31.5, 95.5, 159.5 and 223.5.
+                let expected = 1.0 - (band as f32 * 64.0 + 31.5) / 255.0;
+                assert!(
+                    (density - expected).abs() < 0.05,
+                    "{algorithm:?} band {band}: density {density}, expected {expected}"
+                );
+            }
+        }
+    }
+}
@@ -10,19 +10,24 @@
 ||| CORE CAPABILITIES:
 ||| 1. PDF Engineering: Direct manipulation of PDF structures using `lopdf`.
 ||| 2. Visual Enhancement: Binarization and denoising for scanned documents.
-||| 3. Format Conversion: Stable conversion between Image and PDF formats.
+||| 3. Format Conversion: Stable conversion between Image and PDF formats,
+|||    plus PWG Raster output for driverless printers.
 ||| 4. Verified Metadata: Embedding proof-of-authenticity into document headers.
 
 pub mod convert;
 pub mod image;
 pub mod pdf;
+pub mod raster;
 pub mod scan;
 
 // CONVENIENCE: Primary interfaces for document transformation.
-pub use image::processor::ImageProcessor;
+pub use image::format::{ImageKind, detect_format};
+pub use image::processor::{Dither, ImageProcessor};
 pub use pdf::reader::PdfReader;
-pub use pdf::writer::PdfWriter;
-pub use scan::enhance::ScanEnhancer;
+pub use pdf::writer::{Fit, PdfWriter};
+pub use raster::to_pwg_raster;
+pub use scan::barcode::{Barcode, BarcodeKind, BoundingBox, detect_barcodes};
+pub use scan::enhance::{Edge, ScanContent, ScanEnhancer, ScanImageFormat};
 
 // OPTIONAL: OCR integration using `ocrs` (enabled via the "ocr" feature gate).
 #[cfg(feature = "ocr")]
@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Image recompression — downsample embedded images to a target resolution
+// and re-encode them as JPEG (`/DCTDecode`) to shrink print jobs.
+//
+// The effective resolution of an image is estimated from the size of the
+// pages it is used on, assuming it fills the page.  That under-estimates the
+// DPI of images drawn smaller than the page, so they are never downsampled
+// further than the target.
+
+use std::collections::HashMap;
+
+use image::imageops::FilterType;
+use lopdf::{Document, Object, ObjectId};
+use presswerk_core::error::PresswerkError;
+use tracing::{debug, info, instrument};
+
+use super::{images, overlay};
+use crate::image::processor::encode_jpeg;
+
+/// Downsample images above `target_dpi` and re-encode them as JPEG.
+///
+/// Only 8-bit DeviceRGB / DeviceGray images stored raw, Flate-compressed or
+/// already as JPEG are touched; images with soft masks, other colour spaces
+/// or exotic filters are left alone. A re-encoded image replaces the
+/// original only when it is smaller.
+#[instrument(skip(pdf), fields(bytes_len = pdf.len()))]
+pub(crate) fn compress(
+    pdf: &[u8],
+    target_dpi: u32,
+    jpeg_quality: u8,
+) -> Result<Vec<u8>, PresswerkError> {
+    if target_dpi == 0 {
+        return Err(PresswerkError::PdfError(
+            "target DPI must be greater than zero".into(),
+        ));
+    }
+    let quality = jpeg_quality.clamp(1, 100);
+
+    let mut doc = Document::load_mem(pdf)
+        .map_err(|err| PresswerkError::PdfError(format!("failed to load PDF: {}", err)))?;
+
+    let mut recompressed = 0;
+    for (image_id, page_inches) in image_placements(&doc) {
+        let Ok(Object::Stream(stream)) = doc.get_object(image_id) else {
+            continue;
+        };
+        // Resampling the image would leave its mask at the old size.
+        let masked = stream.dict.has(b"SMask") || stream.dict.has(b"Mask");
+        let Some(decoded) = images::decode_image(stream).filter(|_| !masked) else {
+            debug!(?image_id, "Skipping unsupported image");
+            continue;
+        };
+
+        let (width, height) = (decoded.width(), decoded.height());
+        let dpi = (width as f32 / page_inches.0).max(height as f32 / page_inches.1);
+        let scale = (target_dpi as f32 / dpi).min(1.0);
+        let resized = if scale < 1.0 {
+            let new_w = ((width as f32 * scale).round() as u32).max(1);
+            let new_h = ((height as f32 * scale).round() as u32).max(1);
+            decoded.resize_exact(new_w, new_h, FilterType::Triangle)
+        } else {
+            decoded
+        };
+
+        let jpeg = encode_jpeg(&resized, quality)?;
+        let original_len = stream.content.len();
+        if jpeg.len() >= original_len {
+            debug!(?image_id, dpi, "Re-encoding would not shrink image");
+            continue;
+        }
+
+        debug!(
+            ?image_id,
+            dpi,
+            from = ?(width, height),
+            to = ?(resized.width(), resized.height()),
+            original_len,
+            new_len = jpeg.len(),
+            "Image recompressed"
+        );
+
+        let Ok(Object::Stream(stream)) = doc.get_object_mut(image_id) else {
+            continue;
+        };
+        stream.dict.set("Width", resized.width() as i64);
+        stream.dict.set("Height", resized.height() as i64);
+        stream.dict.set("BitsPerComponent", 8);
+        stream.dict.set("Filter", "DCTDecode");
+        stream.dict.remove(b"DecodeParms");
+        stream.set_content(jpeg);
+        recompressed += 1;
+    }
+
+    let output = overlay::save(&mut doc)?;
+    info!(
+        images = recompressed,
+        before_bytes = pdf.len(),
+        after_bytes = output.len(),
+        "PDF images compressed"
+    );
+    Ok(output)
+}
+
+/// Map each image XObject drawn on a page to the largest page size
+/// (width, height in inches) it appears on.
+fn image_placements(doc: &Document) -> HashMap<ObjectId, (f32, f32)> {
+    let mut placements: HashMap<ObjectId, (f32, f32)> = HashMap::new();
+
+    for page_id in doc.get_pages().into_values() {
+        let [x0, y0, x1, y1] = overlay::page_media_box(doc, page_id);
+        let inches = ((x1 - x0) / 72.0, (y1 - y0) / 72.0);
+        if inches.0 <= 0.0 || inches.1 <= 0.0 {
+            continue;
+        }
+
+        for id in images::page_image_ids(doc, page_id) {
+            let entry = placements.entry(id).or_insert(inches);
+            entry.0 = entry.0.max(inches.0);
+            entry.1 = entry.1.max(inches.1);
+        }
+    }
+
+    placements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::reader::PdfReader;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{Dictionary, Stream, dictionary};
+
+    /// One A4 page showing a `size`×`size` RGB gradient, Flate-compressed —
+    /// 2000 px across 8.3 in is roughly 240 DPI.
+    fn pdf_with_image(size: u32) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+
+        let pixels: Vec<u8> = (0..size * size)
+            .flat_map(|i| {
+                let (x, y) = (i % size, i / size);
+                [(x * 255 / size) as u8, (y * 255 / size) as u8, 128]
+            })
+            .collect();
+        let mut image = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => size as i64,
+                "Height" => size as i64,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            pixels,
+        );
+        image.compress().unwrap();
+        let image_id = doc.add_object(image);
+
+        let content = Content {
+            operations: vec![
+                Operation::new("q", vec![]),
+                Operation::new(
+                    "cm",
+                    vec![
+                        595.into(),
+                        0.into(),
+                        0.into(),
+                        595.into(),
+                        0.into(),
+                        123.into(),
+                    ],
+                ),
+                Operation::new("Do", vec![Object::Name(b"Im1".to_vec())]),
+                Operation::new("Q", vec![]),
+            ],
+        };
+        let content_id = doc.add_object(Stream::new(Dictionary::new(), content.encode().unwrap()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => dictionary! { "XObject" => dictionary! { "Im1" => image_id } },
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        overlay::save(&mut doc).unwrap()
+    }
+
+    fn image_dict(pdf: &[u8]) -> Dictionary {
+        let doc = Document::load_mem(pdf).unwrap();
+        doc.objects
+            .values()
+            .filter_map(|obj| obj.as_stream().ok())
+            .find(|s| s.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image"))
+            .map(|s| s.dict.clone())
+            .expect("image XObject")
+    }
+
+    #[test]
+    fn oversized_image_is_downsampled_to_jpeg() {
+        let pdf = pdf_with_image(2000);
+        let compressed = compress(&pdf, 150, 80).unwrap();
+
+        assert!(
+            compressed.len() < pdf.len(),
+            "expected shrink: {} -> {}",
+            pdf.len(),
+            compressed.len()
+        );
+
+        let reader = PdfReader::from_bytes(&compressed).unwrap();
+        assert_eq!(reader.page_count(), 1);
+        let doc = Document::load_mem(&compressed).unwrap();
+        let page_id = doc.page_iter().next().unwrap();
+        assert_eq!(
+            overlay::page_media_box(&doc, page_id),
+            [0.0, 0.0, 595.0, 842.0]
+        );
+
+        let dict = image_dict(&compressed);
+        assert_eq!(
+            dict.get(b"Filter").unwrap().as_name().unwrap(),
+            b"DCTDecode"
+        );
+        // A square image is limited by the 595 pt (8.26 in) page width, so
+        // 150 DPI is about 1240 px.
+        let width = dict.get(b"Width").unwrap().as_i64().unwrap();
+        assert!((1200..=1280).contains(&width), "width {width}");
+    }
+
+    #[test]
+    fn image_below_target_keeps_its_size() {
+        let pdf = pdf_with_image(200);
+        let compressed = compress(&pdf, 300, 80).unwrap();
+
+        let dict = image_dict(&compressed);
+        assert_eq!(dict.get(b"Width").unwrap().as_i64().unwrap(), 200);
+    }
+
+    #[test]
+    fn zero_dpi_is_rejected() {
+        assert!(compress(&pdf_with_image(10), 0, 80).is_err());
+    }
+}
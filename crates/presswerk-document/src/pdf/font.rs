@@ -0,0 +1,662 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Bundled font — the text Presswerk generates (text documents, watermarks,
+// page numbers) is set in a Latin, Greek, Cyrillic and symbols subset of
+// DejaVu Sans Mono shipped inside the crate (see `fonts/README.md`), so the
+// output does not depend on which fonts the viewer or printer has.
+//
+// The font is embedded as a composite font: a `/Type0` font with
+// `Identity-H` encoding over a `/CIDFontType2` descendant carrying the
+// TrueType program in `/FontFile2`.  Text is encoded as two-byte glyph ids,
+// CIDs equal glyph ids (`/CIDToGIDMap /Identity`), and a `/ToUnicode` CMap
+// maps them back to characters for copy-paste and search.
+//
+// Characters the font has no glyph for (CJK, emoji, ...) are drawn as a
+// white square inside a `/Span` marked-content sequence whose `/ActualText`
+// holds the original characters, so the gap is visible on paper but the text
+// still copies, searches and extracts as written.
+//
+// Only the glyphs a document actually uses are kept in the embedded program.
+// Glyph ids are left unchanged — unused glyphs simply become empty — which
+// keeps the CID mapping trivial at the cost of a few bytes of `loca`/`hmtx`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::LazyLock;
+
+use lopdf::content::Operation;
+use lopdf::{Document, Object, ObjectId, Stream, StringFormat, dictionary};
+use presswerk_core::error::PresswerkError;
+use sha2::{Digest, Sha256};
+
+/// The bundled TrueType program.
+const FONT_DATA: &[u8] = include_bytes!("../../fonts/DejaVuSansMono-Presswerk.ttf");
+
+/// PostScript name of the bundled font, without the subset tag.
+const BASE_FONT: &str = "DejaVuSansMono";
+
+/// Glyph id of `.notdef`.
+const NOTDEF: u16 = 0;
+
+/// Character drawn in place of each one the font lacks (WHITE SQUARE).
+const FALLBACK: char = '\u{25A1}';
+
+/// Maximum entries per `beginbfchar` block allowed by the CMap format.
+const BFCHAR_BLOCK: usize = 100;
+
+static BUNDLED: LazyLock<Result<Font, String>> = LazyLock::new(|| Font::parse(FONT_DATA));
+
+/// A parsed TrueType font: the metrics and tables needed to lay out text and
+/// embed a subset.
+pub(crate) struct Font {
+    data: &'static [u8],
+    /// Table tag -> (offset, length) in `data`.
+    tables: BTreeMap<[u8; 4], (usize, usize)>,
+    units_per_em: u16,
+    /// `[x_min, y_min, x_max, y_max]` in font units.
+    bbox: [i16; 4],
+    ascent: i16,
+    descent: i16,
+    cap_height: i16,
+    /// Start of each glyph in `glyf`, plus the end of the last one.
+    glyph_offsets: Vec<usize>,
+    /// Advance width of each glyph in font units.
+    advances: Vec<u16>,
+    cmap: HashMap<char, u16>,
+}
+
+impl Font {
+    /// The font bundled with the crate.
+    pub(crate) fn bundled() -> Result<&'static Font, PresswerkError> {
+        BUNDLED
+            .as_ref()
+            .map_err(|err| PresswerkError::PdfError(format!("bundled font is unusable: {err}")))
+    }
+
+    fn parse(data: &'static [u8]) -> Result<Self, String> {
+        let num_tables = read_u16(data, 4)?;
+        let mut tables = BTreeMap::new();
+        for i in 0..usize::from(num_tables) {
+            let record = 12 + 16 * i;
+            let tag: [u8; 4] = data
+                .get(record..record + 4)
+                .and_then(|t| t.try_into().ok())
+                .ok_or("truncated table directory")?;
+            let offset = read_u32(data, record + 8)? as usize;
+            let len = read_u32(data, record + 12)? as usize;
+            if offset.checked_add(len).is_none_or(|end| end > data.len()) {
+                return Err(format!("table {} out of bounds", tag_name(&tag)));
+            }
+            tables.insert(tag, (offset, len));
+        }
+
+        let table = |tag: &[u8; 4]| {
+            tables
+                .get(tag)
+                .map(|&(offset, len)| &data[offset..offset + len])
+                .ok_or_else(|| format!("missing {} table", tag_name(tag)))
+        };
+
+        let head = table(b"head")?;
+        let units_per_em = read_u16(head, 18)?;
+        let bbox = [
+            read_i16(head, 36)?,
+            read_i16(head, 38)?,
+            read_i16(head, 40)?,
+            read_i16(head, 42)?,
+        ];
+        let long_offsets = read_i16(head, 50)? != 0;
+
+        let num_glyphs = usize::from(read_u16(table(b"maxp")?, 4)?);
+
+        let hhea = table(b"hhea")?;
+        let ascent = read_i16(hhea, 4)?;
+        let descent = read_i16(hhea, 6)?;
+        let num_metrics = usize::from(read_u16(hhea, 34)?).clamp(1, num_glyphs.max(1));
+
+        // sCapHeight only exists from OS/2 version 2; fall back to the ascent.
+        let cap_height = table(b"OS/2")
+            .ok()
+            .and_then(|os2| read_i16(os2, 88).ok())
+            .unwrap_or(ascent);
+
+        let loca = table(b"loca")?;
+        let glyph_offsets = (0..=num_glyphs)
+            .map(|i| {
+                if long_offsets {
+                    read_u32(loca, 4 * i).map(|o| o as usize)
+                } else {
+                    read_u16(loca, 2 * i).map(|o| usize::from(o) * 2)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let glyf_len = table(b"glyf")?.len();
+        if glyph_offsets.windows(2).any(|w| w[0] > w[1]) || glyph_offsets[num_glyphs] > glyf_len {
+            return Err("inconsistent loca table".into());
+        }
+
+        let hmtx = table(b"hmtx")?;
+        let advances = (0..num_glyphs)
+            .map(|g| read_u16(hmtx, 4 * g.min(num_metrics - 1)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let cmap = parse_cmap(table(b"cmap")?)?;
+
+        Ok(Self {
+            data,
+            tables,
+            units_per_em,
+            bbox,
+            ascent,
+            descent,
+            cap_height,
+            glyph_offsets,
+            advances,
+            cmap,
+        })
+    }
+
+    /// Glyph id for `c`, or `None` if the font has no glyph for it.
+    pub(crate) fn glyph(&self, c: char) -> Option<u16> {
+        self.cmap.get(&c).copied()
+    }
+
+    /// Glyph id for `c`, or the fallback box if the font lacks it.
+    fn glyph_or_fallback(&self, c: char) -> u16 {
+        self.glyph(c)
+            .or_else(|| self.glyph(FALLBACK))
+            .unwrap_or(NOTDEF)
+    }
+
+    /// Width of `text` set at `size` points.
+    pub(crate) fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = text
+            .chars()
+            .map(|c| u32::from(self.advance(self.glyph_or_fallback(c))))
+            .sum();
+        units as f32 * size / f32::from(self.units_per_em)
+    }
+
+    /// Capital-letter height as a fraction of the font size.
+    pub(crate) fn cap_height(&self) -> f32 {
+        f32::from(self.cap_height) / f32::from(self.units_per_em)
+    }
+
+    fn advance(&self, glyph: u16) -> u16 {
+        self.advances.get(usize::from(glyph)).copied().unwrap_or(0)
+    }
+
+    /// Font units scaled to the 1000-unit glyph space PDF uses.
+    fn to_pdf_units(&self, value: i32) -> i64 {
+        (i64::from(value) * 1000) / i64::from(self.units_per_em)
+    }
+
+    fn table(&self, tag: &[u8; 4]) -> &'static [u8] {
+        let data = self.data;
+        self.tables
+            .get(tag)
+            .map_or(&[][..], |&(offset, len)| &data[offset..offset + len])
+    }
+
+    fn glyph_data(&self, glyph: u16) -> &'static [u8] {
+        let glyf = self.table(b"glyf");
+        let g = usize::from(glyph);
+        match (self.glyph_offsets.get(g), self.glyph_offsets.get(g + 1)) {
+            (Some(&start), Some(&end)) => &glyf[start..end],
+            _ => &[],
+        }
+    }
+
+    /// Components referenced by a composite glyph (empty for simple ones).
+    fn components(&self, glyph: u16) -> Vec<u16> {
+        let data = self.glyph_data(glyph);
+        let mut components = Vec::new();
+        if read_i16(data, 0).is_ok_and(|contours| contours < 0) {
+            let mut pos = 10;
+            while let (Ok(flags), Ok(component)) = (read_u16(data, pos), read_u16(data, pos + 2)) {
+                components.push(component);
+                pos += 4 + composite_args_len(flags);
+                if flags & MORE_COMPONENTS == 0 {
+                    break;
+                }
+            }
+        }
+        components
+    }
+
+    /// A copy of the font program in which only `glyphs` (and the glyphs
+    /// they are built from) keep their outlines.
+    fn subset_program(&self, glyphs: &BTreeSet<u16>) -> Vec<u8> {
+        let mut keep = BTreeSet::new();
+        let mut pending: Vec<u16> = glyphs.iter().copied().chain([NOTDEF]).collect();
+        while let Some(glyph) = pending.pop() {
+            if keep.insert(glyph) {
+                pending.extend(self.components(glyph));
+            }
+        }
+
+        let num_glyphs = self.advances.len();
+        let mut glyf = Vec::new();
+        let mut loca = Vec::with_capacity((num_glyphs + 1) * 4);
+        for glyph in 0..num_glyphs {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            if keep.contains(&(glyph as u16)) {
+                glyf.extend_from_slice(self.glyph_data(glyph as u16));
+                glyf.resize(glyf.len().next_multiple_of(4), 0);
+            }
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+        // `loca` is rewritten with 32-bit offsets, which `head` has to say.
+        let mut head = self.table(b"head").to_vec();
+        head[50..52].copy_from_slice(&1i16.to_be_bytes());
+
+        let mut tables: BTreeMap<[u8; 4], Vec<u8>> = self
+            .tables
+            .keys()
+            .map(|tag| (*tag, self.table(tag).to_vec()))
+            .collect();
+        tables.insert(*b"glyf", glyf);
+        tables.insert(*b"loca", loca);
+        tables.insert(*b"head", head);
+        write_font(tables)
+    }
+}
+
+/// Text set in the bundled font, remembering which glyphs it used so that
+/// [`embed`](Self::embed) can write just those.
+pub(crate) struct FontSubset {
+    font: &'static Font,
+    /// Glyph id -> the character it was used for, for `/ToUnicode`.
+    used: BTreeMap<u16, char>,
+}
+
+impl FontSubset {
+    pub(crate) fn new() -> Result<Self, PresswerkError> {
+        Ok(Self {
+            font: Font::bundled()?,
+            used: BTreeMap::new(),
+        })
+    }
+
+    pub(crate) fn font(&self) -> &'static Font {
+        self.font
+    }
+
+    /// Operations showing `text` at the current text position (inside
+    /// `BT`/`ET`, with this font selected).
+    ///
+    /// Runs of characters the font lacks are drawn as boxes and wrapped in a
+    /// `/Span` carrying the original text as `/ActualText`.
+    pub(crate) fn show(&mut self, text: &str) -> Vec<Operation> {
+        let mut operations = Vec::new();
+        let mut run = String::new();
+        let mut run_covered = true;
+
+        for c in text.chars() {
+            let covered = self.font.glyph(c).is_some();
+            if covered != run_covered && !run.is_empty() {
+                operations.extend(self.show_run(&run, run_covered));
+                run.clear();
+            }
+            run_covered = covered;
+            run.push(c);
+        }
+        if !run.is_empty() {
+            operations.extend(self.show_run(&run, run_covered));
+        }
+        operations
+    }
+
+    fn show_run(&mut self, run: &str, covered: bool) -> Vec<Operation> {
+        let mut bytes = Vec::with_capacity(run.len() * 2);
+        for c in run.chars() {
+            let glyph = self.font.glyph_or_fallback(c);
+            if glyph != NOTDEF {
+                // A box stands for the fallback character itself in
+                // `/ToUnicode`; `/ActualText` covers what it replaced.
+                let mapped = if covered { c } else { FALLBACK };
+                self.used.entry(glyph).or_insert(mapped);
+            }
+            bytes.extend_from_slice(&glyph.to_be_bytes());
+        }
+        let show = Operation::new("Tj", vec![Object::String(bytes, StringFormat::Hexadecimal)]);
+
+        if covered {
+            vec![show]
+        } else {
+            vec![
+                Operation::new(
+                    "BDC",
+                    vec![
+                        Object::Name(b"Span".to_vec()),
+                        Object::Dictionary(dictionary! { "ActualText" => lopdf::text_string(run) }),
+                    ],
+                ),
+                show,
+                Operation::new("EMC", vec![]),
+            ]
+        }
+    }
+
+    /// Add the font, subset to the glyphs encoded so far, to `doc` and
+    /// return the id of its `/Type0` font dictionary.
+    pub(crate) fn embed(&self, doc: &mut Document) -> ObjectId {
+        let font = self.font;
+        let glyphs: BTreeSet<u16> = self.used.keys().copied().collect();
+        let base_font = format!("{}+{BASE_FONT}", subset_tag(&glyphs));
+
+        let program = font.subset_program(&glyphs);
+        let mut program_stream =
+            Stream::new(dictionary! { "Length1" => program.len() as i64 }, program);
+        let _ = program_stream.compress();
+        let program_id = doc.add_object(program_stream);
+
+        let [x_min, y_min, x_max, y_max] = font.bbox.map(|v| font.to_pdf_units(v.into()));
+        let descriptor_id = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => Object::Name(base_font.clone().into_bytes()),
+            // FixedPitch | Nonsymbolic
+            "Flags" => 1 | 32,
+            "FontBBox" => vec![x_min.into(), y_min.into(), x_max.into(), y_max.into()],
+            "ItalicAngle" => 0,
+            "Ascent" => font.to_pdf_units(font.ascent.into()),
+            "Descent" => font.to_pdf_units(font.descent.into()),
+            "CapHeight" => font.to_pdf_units(font.cap_height.into()),
+            "StemV" => 80,
+            "FontFile2" => program_id,
+        });
+
+        let default_width = font.to_pdf_units(font.advance(NOTDEF).into());
+        let widths: Vec<Object> = glyphs
+            .iter()
+            .map(|&g| (g, font.to_pdf_units(font.advance(g).into())))
+            .filter(|&(_, width)| width != default_width)
+            .flat_map(|(g, width)| [i64::from(g).into(), vec![width.into()].into()])
+            .collect();
+
+        let cid_font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "CIDFontType2",
+            "BaseFont" => Object::Name(base_font.clone().into_bytes()),
+            "CIDSystemInfo" => dictionary! {
+                "Registry" => Object::string_literal("Adobe"),
+                "Ordering" => Object::string_literal("Identity"),
+                "Supplement" => 0,
+            },
+            "FontDescriptor" => descriptor_id,
+            "DW" => default_width,
+            "W" => widths,
+            "CIDToGIDMap" => "Identity",
+        });
+
+        let mut to_unicode = Stream::new(dictionary! {}, to_unicode_cmap(&self.used).into_bytes());
+        let _ = to_unicode.compress();
+        let to_unicode_id = doc.add_object(to_unicode);
+
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => Object::Name(base_font.into_bytes()),
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![cid_font_id.into()],
+            "ToUnicode" => to_unicode_id,
+        })
+    }
+}
+
+// -- TrueType helpers ---------------------------------------------------------
+
+/// Composite glyph flags (OpenType `glyf` table).
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// Bytes following a component's flags and glyph id.
+fn composite_args_len(flags: u16) -> usize {
+    let args = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+        4
+    } else {
+        2
+    };
+    let transform = if flags & WE_HAVE_A_SCALE != 0 {
+        2
+    } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+        4
+    } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+        8
+    } else {
+        0
+    };
+    args + transform
+}
+
+/// Read the Windows Unicode BMP (3, 1) format 4 subtable into a map.
+fn parse_cmap(cmap: &[u8]) -> Result<HashMap<char, u16>, String> {
+    let num_subtables = usize::from(read_u16(cmap, 2)?);
+    let subtable = (0..num_subtables)
+        .map(|i| 4 + 8 * i)
+        .find(|&record| {
+            read_u16(cmap, record).ok() == Some(3) && read_u16(cmap, record + 2).ok() == Some(1)
+        })
+        .ok_or("no Unicode BMP cmap subtable")?;
+    let base = read_u32(cmap, subtable + 4)? as usize;
+    if read_u16(cmap, base)? != 4 {
+        return Err("Unicode cmap subtable is not format 4".into());
+    }
+
+    let seg_count = usize::from(read_u16(cmap, base + 6)? / 2);
+    let ends = base + 14;
+    let starts = ends + 2 * seg_count + 2;
+    let deltas = starts + 2 * seg_count;
+    let range_offsets = deltas + 2 * seg_count;
+
+    let mut map = HashMap::new();
+    for seg in 0..seg_count {
+        let end = read_u16(cmap, ends + 2 * seg)?;
+        let start = read_u16(cmap, starts + 2 * seg)?;
+        let delta = read_u16(cmap, deltas + 2 * seg)?;
+        let range_offset = usize::from(read_u16(cmap, range_offsets + 2 * seg)?);
+
+        for code in start..=end {
+            if code == 0xFFFF {
+                break;
+            }
+            let glyph = if range_offset == 0 {
+                code.wrapping_add(delta)
+            } else {
+                let at = range_offsets + 2 * seg + range_offset + 2 * usize::from(code - start);
+                match read_u16(cmap, at)? {
+                    0 => 0,
+                    glyph => glyph.wrapping_add(delta),
+                }
+            };
+            if let (Some(c), true) = (char::from_u32(code.into()), glyph != NOTDEF) {
+                map.insert(c, glyph);
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// Assemble a TrueType file from its tables.
+fn write_font(mut tables: BTreeMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+    // The whole-font checksum adjustment is computed with this zeroed.
+    if let Some(head) = tables.get_mut(b"head") {
+        head[8..12].fill(0);
+    }
+
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.max(1).leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    out.extend_from_slice(&num_tables.to_be_bytes());
+    out.extend_from_slice(&search_range.to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&(num_tables * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, data) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&checksum(data).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for data in tables.values() {
+        out.extend_from_slice(data);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(at) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out));
+        out[at + 8..at + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    out
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn read_u16(data: &[u8], at: usize) -> Result<u16, String> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| format!("read past end of table at {at}"))
+}
+
+fn read_i16(data: &[u8], at: usize) -> Result<i16, String> {
+    read_u16(data, at).map(|v| v as i16)
+}
+
+fn read_u32(data: &[u8], at: usize) -> Result<u32, String> {
+    data.get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format!("read past end of table at {at}"))
+}
+
+fn tag_name(tag: &[u8; 4]) -> String {
+    String::from_utf8_lossy(tag).trim_end().to_string()
+}
+
+// -- PDF helpers --------------------------------------------------------------
+
+/// Six capital letters identifying a subset, as the PDF spec asks for in
+/// front of a subset font's name.  Derived from the glyph set so the same
+/// subset always gets the same tag.
+fn subset_tag(glyphs: &BTreeSet<u16>) -> String {
+    let mut hasher = Sha256::new();
+    for glyph in glyphs {
+        hasher.update(glyph.to_be_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .take(6)
+        .map(|b| char::from(b'A' + b % 26))
+        .collect()
+}
+
+/// A `/ToUnicode` CMap mapping each used glyph id back to its character.
+fn to_unicode_cmap(used: &BTreeMap<u16, char>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n\
+         12 dict begin\n\
+         begincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n\
+         /CMapType 2 def\n\
+         1 begincodespacerange\n\
+         <0000> <FFFF>\n\
+         endcodespacerange\n",
+    );
+
+    let entries: Vec<(&u16, &char)> = used.iter().collect();
+    for block in entries.chunks(BFCHAR_BLOCK) {
+        cmap.push_str(&format!("{} beginbfchar\n", block.len()));
+        for (glyph, c) in block {
+            let mut units = [0u16; 2];
+            let utf16: String = c
+                .encode_utf16(&mut units)
+                .iter()
+                .map(|unit| format!("{unit:04X}"))
+                .collect();
+            cmap.push_str(&format!("<{glyph:04X}> <{utf16}>\n"));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+
+    cmap.push_str(
+        "endcmap\n\
+         CMapName currentdict /CMap defineresource pop\n\
+         end\n\
+         end\n",
+    );
+    cmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_font_covers_latin_greek_and_cyrillic() {
+        let font = Font::bundled().unwrap();
+        for c in ['A', 'z', 'é', 'ß', 'Ł', 'Ω', 'Ж', '€', '□'] {
+            assert!(font.glyph(c).is_some(), "no glyph for {c:?}");
+        }
+        assert!(font.glyph('漢').is_none());
+        assert_eq!(font.glyph_or_fallback('漢'), font.glyph('□').unwrap());
+
+        // Monospaced: every character is as wide as any other.
+        assert_eq!(font.text_width("iii", 10.0), font.text_width("WWW", 10.0));
+    }
+
+    #[test]
+    fn subset_keeps_only_used_outlines() {
+        let font = Font::bundled().unwrap();
+        let glyphs = BTreeSet::from([font.glyph('A').unwrap()]);
+        let program = font.subset_program(&glyphs);
+        assert!(program.len() < FONT_DATA.len() / 4);
+
+        // The subset is itself a well-formed font with the same glyph ids.
+        let leaked: &'static [u8] = Box::leak(program.into_boxed_slice());
+        let subset = Font::parse(leaked).unwrap();
+        assert_eq!(subset.advances.len(), font.advances.len());
+        assert!(!subset.glyph_data(font.glyph('A').unwrap()).is_empty());
+        assert!(subset.glyph_data(font.glyph('B').unwrap()).is_empty());
+        assert_eq!(checksum(leaked), 0xB1B0_AFBA);
+    }
+
+    #[test]
+    fn missing_characters_become_boxes_with_actual_text() {
+        let mut subset = FontSubset::new().unwrap();
+        let ops = subset.show("Tōkyō 東京!");
+
+        let operators: Vec<&str> = ops.iter().map(|op| op.operator.as_str()).collect();
+        assert_eq!(operators, ["Tj", "BDC", "Tj", "EMC", "Tj"]);
+
+        let actual = ops[1].operands[1]
+            .as_dict()
+            .unwrap()
+            .get(b"ActualText")
+            .unwrap();
+        assert_eq!(lopdf::decode_text_string(actual).unwrap(), "東京");
+
+        let boxes = ops[2].operands[0].as_str().unwrap();
+        let square = subset.font().glyph('□').unwrap().to_be_bytes();
+        assert_eq!(boxes, [square, square].concat());
+    }
+}
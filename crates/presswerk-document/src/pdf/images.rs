@@ -0,0 +1,582 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Raster images embedded in PDFs — locating the image XObjects a page draws,
+// extracting their encoded data, and decoding them into pixels.
+//
+// Extraction undoes the general-purpose filters (Flate, LZW, ASCII85,
+// RunLength) and keeps image codecs intact: JPEG data is returned as a JPEG
+// file and CCITT fax data is wrapped in a TIFF header, so callers can save or
+// decode either with ordinary image tooling.
+
+use image::{DynamicImage, GrayImage, ImageFormat, RgbImage};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use presswerk_core::error::PresswerkError;
+
+/// How deeply nested Form XObjects are searched for images.
+const MAX_FORM_DEPTH: usize = 4;
+
+/// Default `/Columns` of CCITT fax data (the width of a standard fax line).
+const CCITT_DEFAULT_COLUMNS: i64 = 1728;
+
+/// Colour space of an extracted image's samples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageColorSpace {
+    /// One component; also used for stencil masks and `CalGray`.
+    Gray,
+    /// Three components; also used for `CalRGB`.
+    Rgb,
+    Cmyk,
+    /// One component per sample, an index into `palette`, which holds
+    /// `hival + 1` colours in the `base` colour space.
+    Indexed {
+        base: Box<ImageColorSpace>,
+        palette: Vec<u8>,
+    },
+    /// Any other colour space (`Lab`, `Separation`, `DeviceN`, ...), by name.
+    Other(String),
+}
+
+impl ImageColorSpace {
+    /// Number of colour components per sample, if known.
+    pub fn components(&self) -> Option<usize> {
+        match self {
+            Self::Gray | Self::Indexed { .. } => Some(1),
+            Self::Rgb => Some(3),
+            Self::Cmyk => Some(4),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+/// Image data pulled out of a PDF, in the most useful form for its filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageData {
+    /// A complete JPEG file (`/DCTDecode`).
+    Jpeg(Vec<u8>),
+    /// A complete TIFF file holding CCITT fax data (`/CCITTFaxDecode`).
+    Tiff(Vec<u8>),
+    /// Uncompressed samples, row by row with each row padded to a whole
+    /// byte, as described by the image's colour space and bit depth.
+    Raw(Vec<u8>),
+}
+
+/// An image XObject extracted from a PDF page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedImage {
+    pub width: u32,
+    pub height: u32,
+    pub bits_per_component: u8,
+    pub color_space: ImageColorSpace,
+    pub data: ImageData,
+}
+
+impl ExtractedImage {
+    /// Decode into pixels.
+    ///
+    /// Raw samples are supported for Gray, RGB, CMYK (converted to RGB) and
+    /// Indexed images at 1, 2, 4, 8 or 16 bits per component. CCITT images
+    /// decode when they use Group 4 encoding. `/Decode` arrays and soft
+    /// masks are not applied.
+    pub fn to_dynamic(&self) -> Result<DynamicImage, PresswerkError> {
+        let (bytes, format) = match &self.data {
+            ImageData::Jpeg(bytes) => (bytes, ImageFormat::Jpeg),
+            ImageData::Tiff(bytes) => (bytes, ImageFormat::Tiff),
+            ImageData::Raw(samples) => return self.decode_raw(samples),
+        };
+        image::load_from_memory_with_format(bytes, format).map_err(|err| {
+            PresswerkError::ImageError(format!("cannot decode {format:?} image: {err}"))
+        })
+    }
+
+    fn decode_raw(&self, samples: &[u8]) -> Result<DynamicImage, PresswerkError> {
+        let unsupported = || {
+            PresswerkError::ImageError(format!(
+                "cannot decode {}-bit {:?} image samples",
+                self.bits_per_component, self.color_space
+            ))
+        };
+        let components = self.color_space.components().ok_or_else(unsupported)?;
+        let values = unpack_samples(
+            samples,
+            self.width,
+            self.height,
+            components,
+            self.bits_per_component,
+        )
+        .ok_or_else(|| {
+            PresswerkError::ImageError(format!(
+                "image data too short for {}x{} pixels",
+                self.width, self.height
+            ))
+        })?;
+
+        // Scale samples to 8 bits, except palette indices. Sixteen-bit
+        // samples are already down to their high byte.
+        let max = (1u32 << self.bits_per_component.min(8)) - 1;
+        let scale = |v: u16| (u32::from(v) * 255 / max) as u8;
+
+        let (pixels, base) = match &self.color_space {
+            ImageColorSpace::Indexed { base, palette } => {
+                let n = base.components().ok_or_else(unsupported)?;
+                let pixels = values
+                    .iter()
+                    .flat_map(|&index| {
+                        let start = usize::from(index) * n;
+                        match palette.get(start..start + n) {
+                            Some(colour) => colour.to_vec(),
+                            None => vec![0; n],
+                        }
+                    })
+                    .collect();
+                (pixels, base.as_ref())
+            }
+            other => (values.into_iter().map(scale).collect(), other),
+        };
+
+        let image = match base {
+            ImageColorSpace::Gray => {
+                GrayImage::from_raw(self.width, self.height, pixels).map(DynamicImage::ImageLuma8)
+            }
+            ImageColorSpace::Rgb => {
+                RgbImage::from_raw(self.width, self.height, pixels).map(DynamicImage::ImageRgb8)
+            }
+            ImageColorSpace::Cmyk => {
+                let rgb = pixels
+                    .chunks_exact(4)
+                    .flat_map(|p| {
+                        let k = u16::from(p[3]);
+                        [0, 1, 2].map(|i| 255 - (u16::from(p[i]) + k).min(255) as u8)
+                    })
+                    .collect();
+                RgbImage::from_raw(self.width, self.height, rgb).map(DynamicImage::ImageRgb8)
+            }
+            _ => None,
+        };
+        image.ok_or_else(unsupported)
+    }
+}
+
+/// Object IDs of the image XObjects in a page's resources, including those
+/// inside Form XObjects the page uses. Each image is listed once.
+pub(crate) fn page_image_ids(doc: &Document, page_id: ObjectId) -> Vec<ObjectId> {
+    let mut ids = Vec::new();
+    let Ok((resources, resource_ids)) = doc.get_page_resources(page_id) else {
+        return ids;
+    };
+
+    let dictionaries = resources.into_iter().chain(
+        resource_ids
+            .iter()
+            .filter_map(|&id| doc.get_dictionary(id).ok()),
+    );
+    for resources in dictionaries {
+        collect_images(doc, resources, 0, &mut ids);
+    }
+    ids
+}
+
+fn collect_images(doc: &Document, resources: &Dictionary, depth: usize, ids: &mut Vec<ObjectId>) {
+    let Ok(xobjects) = resources
+        .get(b"XObject")
+        .and_then(|obj| doc.dereference(obj))
+        .and_then(|(_, obj)| obj.as_dict())
+    else {
+        return;
+    };
+
+    for (_, xobject) in xobjects.iter() {
+        let Ok(id) = xobject.as_reference() else {
+            continue;
+        };
+        let Ok(stream) = doc.get_object(id).and_then(Object::as_stream) else {
+            continue;
+        };
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") if !ids.contains(&id) => ids.push(id),
+            Ok(b"Form") if depth < MAX_FORM_DEPTH => {
+                if let Ok((_, form_resources)) = stream
+                    .dict
+                    .get(b"Resources")
+                    .and_then(|obj| doc.dereference(obj))
+                    && let Ok(form_resources) = form_resources.as_dict()
+                {
+                    collect_images(doc, form_resources, depth + 1, ids);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Decode an image XObject into pixels.
+///
+/// Supports 8-bit DeviceRGB and DeviceGray images stored raw, with
+/// `/FlateDecode`, or as JPEG (`/DCTDecode`). Soft masks are ignored.
+/// Returns `None` for anything else.
+pub(crate) fn decode_image(stream: &Stream) -> Option<DynamicImage> {
+    let dict = &stream.dict;
+    if dict.has(b"Decode")
+        || dict
+            .get(b"ImageMask")
+            .and_then(Object::as_bool)
+            .unwrap_or(false)
+    {
+        return None;
+    }
+
+    let width = u32::try_from(dict.get(b"Width").and_then(Object::as_i64).ok()?).ok()?;
+    let height = u32::try_from(dict.get(b"Height").and_then(Object::as_i64).ok()?).ok()?;
+    let gray = match dict.get(b"ColorSpace").and_then(Object::as_name).ok()? {
+        b"DeviceRGB" => false,
+        b"DeviceGray" => true,
+        _ => return None,
+    };
+    let filters = stream.filters().unwrap_or_default();
+
+    if filters == [b"DCTDecode".as_slice()] {
+        let image =
+            image::load_from_memory_with_format(&stream.content, image::ImageFormat::Jpeg).ok()?;
+        let channels = if gray { 1 } else { 3 };
+        let matches = image.width() == width
+            && image.height() == height
+            && image.color().channel_count() == channels;
+        return matches.then_some(image);
+    }
+
+    if dict
+        .get(b"BitsPerComponent")
+        .and_then(Object::as_i64)
+        .ok()?
+        != 8
+    {
+        return None;
+    }
+    let pixels = match filters.as_slice() {
+        [] => stream.content.clone(),
+        [b"FlateDecode"] => stream.decompressed_content().ok()?,
+        _ => return None,
+    };
+
+    if gray {
+        GrayImage::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8)
+    } else {
+        RgbImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8)
+    }
+}
+
+/// Extract the image XObject `stream` (object `id`) with its codec intact.
+///
+/// Fails for JPEG 2000 (`/JPXDecode`), JBIG2 and other filters Presswerk
+/// cannot unpack.
+pub(crate) fn extract_image(
+    doc: &Document,
+    id: ObjectId,
+    stream: &Stream,
+) -> Result<ExtractedImage, PresswerkError> {
+    let dict = &stream.dict;
+    let dimension = |key: &[u8]| {
+        dict.get(key)
+            .and_then(Object::as_i64)
+            .ok()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|&v| v > 0)
+            .ok_or_else(|| {
+                PresswerkError::PdfError(format!(
+                    "image {} {} has no valid /{}",
+                    id.0,
+                    id.1,
+                    String::from_utf8_lossy(key)
+                ))
+            })
+    };
+    let width = dimension(b"Width")?;
+    let height = dimension(b"Height")?;
+
+    let is_mask = dict
+        .get(b"ImageMask")
+        .and_then(Object::as_bool)
+        .unwrap_or(false);
+    let bits_per_component = if is_mask {
+        1
+    } else {
+        dict.get(b"BitsPerComponent")
+            .and_then(Object::as_i64)
+            .ok()
+            .and_then(|v| u8::try_from(v).ok())
+            .unwrap_or(8)
+    };
+    let color_space = match dict.get(b"ColorSpace") {
+        Ok(obj) if !is_mask => parse_color_space(doc, obj, 0),
+        _ => ImageColorSpace::Gray,
+    };
+
+    let filters = stream.filters().unwrap_or_default();
+    let (codec, general) = match filters.split_last() {
+        Some((&last, rest)) if is_image_codec(last) => (Some((last, filters.len() - 1)), rest),
+        _ => (None, filters.as_slice()),
+    };
+
+    let content = decode_filters(dict, &stream.content, general)
+        .map_err(|err| unsupported_filter(id, &filters, err))?;
+
+    let data = match codec {
+        None => ImageData::Raw(content),
+        Some((b"DCTDecode", _)) => ImageData::Jpeg(content),
+        Some((b"CCITTFaxDecode", index)) => {
+            let params = decode_parms(dict, index);
+            ImageData::Tiff(ccitt_to_tiff(&content, width, height, params))
+        }
+        Some((b"JPXDecode", _)) => {
+            return Err(PresswerkError::PdfError(format!(
+                "image {} {} is JPEG 2000 (JPXDecode), which is not supported",
+                id.0, id.1
+            )));
+        }
+        Some((b"JBIG2Decode", _)) => {
+            return Err(PresswerkError::PdfError(format!(
+                "image {} {} is JBIG2-compressed, which is not supported",
+                id.0, id.1
+            )));
+        }
+        Some((other, _)) => {
+            return Err(PresswerkError::PdfError(format!(
+                "image {} {} uses unsupported filter /{}",
+                id.0,
+                id.1,
+                String::from_utf8_lossy(other)
+            )));
+        }
+    };
+
+    Ok(ExtractedImage {
+        width,
+        height,
+        bits_per_component,
+        color_space,
+        data,
+    })
+}
+
+fn is_image_codec(filter: &[u8]) -> bool {
+    matches!(
+        filter,
+        b"DCTDecode" | b"CCITTFaxDecode" | b"JPXDecode" | b"JBIG2Decode"
+    )
+}
+
+/// Undo the general-purpose `filters` at the front of a stream's `/Filter`
+/// list, in order.  lopdf has no `/RunLengthDecode`, so that one is done
+/// here.
+fn decode_filters(dict: &Dictionary, content: &[u8], filters: &[&[u8]]) -> lopdf::Result<Vec<u8>> {
+    let mut data = content.to_vec();
+    for (index, &filter) in filters.iter().enumerate() {
+        data = if filter == b"RunLengthDecode" {
+            run_length_decode(&data)
+        } else {
+            let mut single = Dictionary::new();
+            single.set("Filter", Object::Name(filter.to_vec()));
+            if let Some(params) = decode_parms(dict, index) {
+                single.set("DecodeParms", params.clone());
+            }
+            Stream::new(single, data).decompressed_content()?
+        };
+    }
+    Ok(data)
+}
+
+/// Decode `/RunLengthDecode` data (PDF 32000-1 §7.4.5): a length byte below
+/// 128 is followed by that many plus one literal bytes, one above 128 by a
+/// byte to repeat 257 minus it times, and 128 ends the data.  Truncated
+/// input yields what could be decoded.
+fn run_length_decode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut rest = data;
+    while let Some((&length, tail)) = rest.split_first() {
+        match length {
+            128 => break,
+            0..=127 => {
+                let count = (usize::from(length) + 1).min(tail.len());
+                out.extend_from_slice(&tail[..count]);
+                rest = &tail[count..];
+            }
+            _ => {
+                let Some((&byte, tail)) = tail.split_first() else {
+                    break;
+                };
+                out.resize(out.len() + 257 - usize::from(length), byte);
+                rest = tail;
+            }
+        }
+    }
+    out
+}
+
+fn unsupported_filter(id: ObjectId, filters: &[&[u8]], err: lopdf::Error) -> PresswerkError {
+    let names: Vec<_> = filters.iter().map(|f| String::from_utf8_lossy(f)).collect();
+    PresswerkError::PdfError(format!(
+        "cannot unpack image {} {} (filters {}): {}",
+        id.0,
+        id.1,
+        names.join(", "),
+        err
+    ))
+}
+
+/// The `/DecodeParms` entry for the filter at `index`: a dictionary for a
+/// single filter, or an array parallel to `/Filter`.
+fn decode_parms(dict: &Dictionary, index: usize) -> Option<&Dictionary> {
+    match dict.get(b"DecodeParms").ok()? {
+        Object::Dictionary(params) => Some(params),
+        Object::Array(params) => params.get(index)?.as_dict().ok(),
+        _ => None,
+    }
+}
+
+fn parse_color_space(doc: &Document, obj: &Object, depth: usize) -> ImageColorSpace {
+    let Ok((_, obj)) = doc.dereference(obj) else {
+        return ImageColorSpace::Other(String::new());
+    };
+    let (name, args): (&[u8], &[Object]) = match obj {
+        Object::Name(name) => (name, &[]),
+        Object::Array(items) => match items.split_first() {
+            Some((Object::Name(name), args)) => (name, args),
+            _ => return ImageColorSpace::Other(String::new()),
+        },
+        _ => return ImageColorSpace::Other(String::new()),
+    };
+
+    match name {
+        b"DeviceGray" | b"CalGray" | b"G" => ImageColorSpace::Gray,
+        b"DeviceRGB" | b"CalRGB" | b"RGB" => ImageColorSpace::Rgb,
+        b"DeviceCMYK" | b"CMYK" => ImageColorSpace::Cmyk,
+        b"ICCBased" => {
+            let components = args
+                .first()
+                .and_then(|obj| doc.dereference(obj).ok())
+                .and_then(|(_, obj)| obj.as_stream().ok())
+                .and_then(|profile| profile.dict.get(b"N").and_then(Object::as_i64).ok());
+            match components {
+                Some(1) => ImageColorSpace::Gray,
+                Some(3) => ImageColorSpace::Rgb,
+                Some(4) => ImageColorSpace::Cmyk,
+                _ => ImageColorSpace::Other("ICCBased".into()),
+            }
+        }
+        b"Indexed" | b"I" if depth == 0 && args.len() >= 3 => {
+            let base = parse_color_space(doc, &args[0], depth + 1);
+            let palette = match doc.dereference(&args[2]).map(|(_, obj)| obj) {
+                Ok(Object::String(bytes, _)) => bytes.clone(),
+                Ok(Object::Stream(lookup)) => lookup
+                    .decompressed_content()
+                    .unwrap_or_else(|_| lookup.content.clone()),
+                _ => Vec::new(),
+            };
+            ImageColorSpace::Indexed {
+                base: Box::new(base),
+                palette,
+            }
+        }
+        other => ImageColorSpace::Other(String::from_utf8_lossy(other).into_owned()),
+    }
+}
+
+/// Split byte-padded rows of packed samples into one value per component.
+/// Sixteen-bit samples keep their high byte. Returns `None` if `data` is
+/// too short.
+fn unpack_samples(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    components: usize,
+    bits: u8,
+) -> Option<Vec<u16>> {
+    if !matches!(bits, 1 | 2 | 4 | 8 | 16) {
+        return None;
+    }
+    let per_row = width as usize * components;
+    let row_bytes = (per_row * usize::from(bits)).div_ceil(8);
+    let rows = data.get(..row_bytes.checked_mul(height as usize)?)?;
+
+    let mut values = Vec::with_capacity(per_row * height as usize);
+    for row in rows.chunks_exact(row_bytes) {
+        match bits {
+            8 => values.extend(row.iter().map(|&b| u16::from(b))),
+            16 => values.extend(row.chunks_exact(2).map(|pair| u16::from(pair[0]))),
+            _ => {
+                let mask = (1u16 << bits) - 1;
+                values.extend((0..per_row).map(|i| {
+                    let bit = i * usize::from(bits);
+                    let shift = 8 - usize::from(bits) - bit % 8;
+                    (u16::from(row[bit / 8]) >> shift) & mask
+                }));
+            }
+        }
+    }
+    Some(values)
+}
+
+/// Wrap raw CCITT fax data in a single-strip little-endian TIFF.
+fn ccitt_to_tiff(data: &[u8], width: u32, height: u32, params: Option<&Dictionary>) -> Vec<u8> {
+    let param = |key: &[u8]| params.and_then(|p| p.get(key).and_then(Object::as_i64).ok());
+    let k = param(b"K").unwrap_or(0);
+    let columns = param(b"Columns").unwrap_or(CCITT_DEFAULT_COLUMNS);
+    let rows = param(b"Rows")
+        .filter(|&r| r > 0)
+        .unwrap_or(i64::from(height));
+    let black_is_1 = params
+        .and_then(|p| p.get(b"BlackIs1").and_then(Object::as_bool).ok())
+        .unwrap_or(false);
+    let columns = u32::try_from(columns).unwrap_or(width);
+    let rows = u32::try_from(rows).unwrap_or(height);
+
+    // Fax decoders emit 1 for black, which TIFF calls WhiteIsZero. With
+    // /BlackIs1 the PDF shows the inverse.
+    let photometric = if black_is_1 { 1 } else { 0 };
+    // K < 0 is pure two-dimensional Group 4; otherwise Group 3, two-
+    // dimensional when K > 0.
+    let (compression, t4_options) = if k < 0 {
+        (4, None)
+    } else {
+        (3, Some(u32::from(k > 0)))
+    };
+
+    let mut entries: Vec<(u16, u16, u32)> = vec![
+        (256, 4, columns),           // ImageWidth
+        (257, 4, rows),              // ImageLength
+        (258, 3, 1),                 // BitsPerSample
+        (259, 3, compression),       // Compression
+        (262, 3, photometric),       // PhotometricInterpretation
+        (273, 4, 0),                 // StripOffsets (patched below)
+        (277, 3, 1),                 // SamplesPerPixel
+        (278, 4, rows),              // RowsPerStrip
+        (279, 4, data.len() as u32), // StripByteCounts
+    ];
+    if let Some(options) = t4_options {
+        entries.push((292, 4, options)); // T4Options
+    }
+
+    let ifd_len = 2 + entries.len() * 12 + 4;
+    let data_offset = (8 + ifd_len) as u32;
+    for entry in &mut entries {
+        if entry.0 == 273 {
+            entry.2 = data_offset;
+        }
+    }
+
+    let mut out = Vec::with_capacity(data_offset as usize + data.len());
+    out.extend_from_slice(b"II");
+    out.extend_from_slice(&42u16.to_le_bytes());
+    out.extend_from_slice(&8u32.to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for (tag, kind, value) in entries {
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&kind.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        // SHORT values sit in the low half of the 4-byte value field.
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out.extend_from_slice(&0u32.to_le_bytes()); // no further IFDs
+    out.extend_from_slice(data);
+    out
+}
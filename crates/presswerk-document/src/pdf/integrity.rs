@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Verified metadata — embedding an `IntegrityProof` into the PDF Info
+// dictionary and checking it again later.
+//
+// The proof covers every object of the document except the Info dictionary:
+// page dictionaries, resources, fonts and annotations as well as the raw data
+// of every stream.  The Info dictionary is left out, so writing the proof
+// into it does not invalidate it.
+
+use lopdf::{Dictionary, Document, Object};
+use presswerk_core::error::PresswerkError;
+use presswerk_security::{IntegrityProof, hash_bytes};
+use tracing::{debug, info, instrument};
+
+use super::overlay;
+
+/// Info dictionary key holding [`IntegrityProof::hash`].
+const HASH_KEY: &[u8] = b"PresswerkHash";
+
+/// Info dictionary key holding [`IntegrityProof::signature`].
+const SIGNATURE_KEY: &[u8] = b"PresswerkSignature";
+
+/// Serialise the parts of `doc` covered by the proof.
+///
+/// Each object is written together with its object ID, so that moving
+/// content between objects changes the result. The Info dictionary holds the
+/// proof itself and is skipped; cross-reference and object streams and the
+/// linearisation dictionary are containers, not content, and are skipped
+/// because lopdf unpacks or rewrites them on load and save.
+pub(crate) fn content_bytes(doc: &Document) -> Vec<u8> {
+    let info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).ok();
+    let mut out = Vec::new();
+
+    for (number, (id, generation)) in doc.get_pages() {
+        out.extend_from_slice(format!("page {number} {id} {generation}\n").as_bytes());
+    }
+
+    for (&(id, generation), object) in &doc.objects {
+        if Some((id, generation)) == info_id {
+            continue;
+        }
+        if let Ok(kind) = object.type_name()
+            && matches!(kind, b"XRef" | b"ObjStm" | b"Linearized")
+        {
+            continue;
+        }
+        out.extend_from_slice(format!("obj {id} {generation}\n").as_bytes());
+        write_object(&mut out, object);
+        out.push(b'\n');
+    }
+
+    out
+}
+
+/// Append an unambiguous encoding of `object` to `out`.
+///
+/// Names, strings and stream data are length-prefixed so that no value can
+/// be mistaken for the start of the next one, and dictionary keys are sorted
+/// so that merely reordering entries does not change the result.
+fn write_object(out: &mut Vec<u8>, object: &Object) {
+    match object {
+        Object::Null => out.extend_from_slice(b"null"),
+        Object::Boolean(value) => out.extend_from_slice(if *value { b"true" } else { b"false" }),
+        Object::Integer(value) => out.extend_from_slice(format!("i{value}").as_bytes()),
+        Object::Real(value) => out.extend_from_slice(format!("f{value}").as_bytes()),
+        Object::Name(name) => write_bytes(out, b'/', name),
+        Object::String(text, _) => write_bytes(out, b'(', text),
+        Object::Array(items) => {
+            out.push(b'[');
+            for item in items {
+                write_object(out, item);
+                out.push(b' ');
+            }
+            out.push(b']');
+        }
+        Object::Dictionary(dict) => write_dictionary(out, dict),
+        Object::Stream(stream) => {
+            write_dictionary(out, &stream.dict);
+            write_bytes(out, b's', &stream.content);
+        }
+        Object::Reference((id, generation)) => {
+            out.extend_from_slice(format!("R{id} {generation}").as_bytes());
+        }
+    }
+}
+
+/// Append `dict` with its keys in sorted order.
+fn write_dictionary(out: &mut Vec<u8>, dict: &Dictionary) {
+    let mut entries: Vec<_> = dict.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    out.extend_from_slice(b"<<");
+    for (key, value) in entries {
+        write_bytes(out, b'/', key);
+        write_object(out, value);
+        out.push(b' ');
+    }
+    out.extend_from_slice(b">>");
+}
+
+/// Append `bytes` behind `tag` and their length.
+fn write_bytes(out: &mut Vec<u8>, tag: u8, bytes: &[u8]) {
+    out.push(tag);
+    out.extend_from_slice(format!("{}:", bytes.len()).as_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Write `proof` into the Info dictionary of `pdf`.
+///
+/// The proof must match the document's current content; a stale proof is
+/// rejected with [`PresswerkError::IntegrityMismatch`] rather than embedded.
+#[instrument(skip_all, fields(bytes_len = pdf.len()))]
+pub(crate) fn embed(pdf: &[u8], proof: &IntegrityProof) -> Result<Vec<u8>, PresswerkError> {
+    let mut doc = Document::load_mem(pdf)
+        .map_err(|err| PresswerkError::PdfError(format!("failed to load PDF: {}", err)))?;
+
+    let current = hash_bytes(&content_bytes(&doc));
+    if current != proof.hash {
+        return Err(PresswerkError::IntegrityMismatch {
+            expected: proof.hash.clone(),
+            actual: current,
+        });
+    }
+
+    let info = info_dictionary_mut(&mut doc)?;
+    info.set(HASH_KEY, Object::string_literal(proof.hash.as_str()));
+    info.set(
+        SIGNATURE_KEY,
+        Object::string_literal(proof.signature.as_str()),
+    );
+
+    info!(hash = %proof.hash, "Integrity proof embedded");
+    overlay::save(&mut doc)
+}
+
+/// Read the proof stored in the Info dictionary, if any.
+pub(crate) fn read(doc: &Document) -> Option<IntegrityProof> {
+    let info = doc
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|obj| doc.dereference(obj).ok())
+        .and_then(|(_, obj)| obj.as_dict().ok())?;
+
+    let text = |key: &[u8]| {
+        info.get(key)
+            .and_then(Object::as_str)
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    };
+
+    let proof = IntegrityProof {
+        hash: text(HASH_KEY)?,
+        signature: text(SIGNATURE_KEY)?,
+    };
+    debug!(hash = %proof.hash, "Integrity proof found");
+    Some(proof)
+}
+
+/// Fetch the document's Info dictionary for editing, creating it if absent.
+fn info_dictionary_mut(doc: &mut Document) -> Result<&mut Dictionary, PresswerkError> {
+    let info_id = match doc.trailer.get(b"Info").and_then(Object::as_reference) {
+        Ok(id) => id,
+        Err(_) => {
+            // Missing, or a direct dictionary in the trailer: move it into an
+            // indirect object so it can be edited in place.
+            let existing = doc
+                .trailer
+                .get(b"Info")
+                .and_then(Object::as_dict)
+                .cloned()
+                .unwrap_or_default();
+            let id = doc.add_object(existing);
+            doc.trailer.set("Info", id);
+            id
+        }
+    };
+
+    doc.get_dictionary_mut(info_id)
+        .map_err(|err| PresswerkError::PdfError(format!("invalid Info dictionary: {}", err)))
+}
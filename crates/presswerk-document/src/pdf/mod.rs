@@ -1,10 +1,22 @@
 // SPDX-License-Identifier: PMPL-1.0-or-later
 // Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
 //
-// PDF module — reading, merging, splitting, rotating, and creating PDFs.
+// PDF module — reading, merging, splitting, rotating, stamping, compressing,
+// bookmarking, extracting images from, and creating PDFs.
 
+mod compress;
+mod font;
+mod images;
+mod integrity;
+mod outline;
+mod overlay;
 pub mod reader;
+mod text;
+mod win_ansi;
 pub mod writer;
 
+pub use images::{ExtractedImage, ImageColorSpace, ImageData};
+pub use outline::{OutlineEntry, outline_from_headings};
+pub use overlay::FooterPosition;
 pub use reader::PdfReader;
-pub use writer::PdfWriter;
+pub use writer::{Fit, PdfWriter};
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Document outline (bookmarks) — the `/Outlines` tree viewers show in their
+// sidebar, so long scanned documents can be navigated by section.
+//
+// The outline is flat: one item per entry, in the order given, each with an
+// explicit `/Dest [page /Fit]` destination.  The catalog's `/PageMode` is set
+// to `/UseOutlines` so viewers open with the bookmarks visible.
+
+use lopdf::{Document, Object, ObjectId, dictionary};
+use presswerk_core::error::PresswerkError;
+use tracing::{info, instrument};
+
+use super::overlay;
+
+/// Longest OCR line taken as a heading, in characters.
+const MAX_HEADING_CHARS: usize = 60;
+
+/// A bookmark pointing at a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// Text shown in the viewer's bookmark list.
+    pub title: String,
+    /// 1-based page number the bookmark opens.
+    pub page: u32,
+}
+
+impl OutlineEntry {
+    pub fn new(title: impl Into<String>, page: u32) -> Self {
+        Self {
+            title: title.into(),
+            page,
+        }
+    }
+}
+
+/// Check entries before any PDF exists: titles must not be blank and pages
+/// count from 1.
+pub(crate) fn validate(entries: &[OutlineEntry]) -> Result<(), PresswerkError> {
+    for entry in entries {
+        if entry.title.trim().is_empty() {
+            return Err(PresswerkError::PdfError(format!(
+                "outline entry for page {} has no title",
+                entry.page
+            )));
+        }
+        if entry.page == 0 {
+            return Err(PresswerkError::PdfError(format!(
+                "outline entry '{}' points to page 0; pages count from 1",
+                entry.title
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Replace the outline of `pdf` with `entries`.
+///
+/// Every entry must point to a page the document has.  No entries leaves
+/// the document as it is.
+#[instrument(skip_all, fields(bytes_len = pdf.len(), entries = entries.len()))]
+pub(crate) fn add_outline(pdf: &[u8], entries: &[OutlineEntry]) -> Result<Vec<u8>, PresswerkError> {
+    validate(entries)?;
+    if entries.is_empty() {
+        return Ok(pdf.to_vec());
+    }
+
+    let mut doc = Document::load_mem(pdf)
+        .map_err(|err| PresswerkError::PdfError(format!("failed to load PDF: {}", err)))?;
+
+    let pages = doc.get_pages();
+    let targets = entries
+        .iter()
+        .map(|entry| {
+            pages.get(&entry.page).copied().ok_or_else(|| {
+                PresswerkError::PdfError(format!(
+                    "outline entry '{}' points to page {} of {}",
+                    entry.title,
+                    entry.page,
+                    pages.len()
+                ))
+            })
+        })
+        .collect::<Result<Vec<ObjectId>, _>>()?;
+
+    let outlines_id = doc.new_object_id();
+    let item_ids: Vec<ObjectId> = entries.iter().map(|_| doc.new_object_id()).collect();
+
+    for (index, (entry, page_id)) in entries.iter().zip(targets).enumerate() {
+        let mut item = dictionary! {
+            "Title" => Object::string_literal(text_string(&entry.title)),
+            "Parent" => outlines_id,
+            "Dest" => vec![page_id.into(), "Fit".into()],
+        };
+        if index > 0 {
+            item.set("Prev", item_ids[index - 1]);
+        }
+        if let Some(&next) = item_ids.get(index + 1) {
+            item.set("Next", next);
+        }
+        doc.objects.insert(item_ids[index], item.into());
+    }
+
+    doc.objects.insert(
+        outlines_id,
+        dictionary! {
+            "Type" => "Outlines",
+            "First" => item_ids[0],
+            "Last" => item_ids[item_ids.len() - 1],
+            "Count" => item_ids.len() as i64,
+        }
+        .into(),
+    );
+
+    let catalog = doc
+        .catalog_mut()
+        .map_err(|err| PresswerkError::PdfError(format!("PDF has no catalog: {}", err)))?;
+    catalog.set("Outlines", outlines_id);
+    catalog.set("PageMode", "UseOutlines");
+
+    info!(entries = entries.len(), "Outline added");
+    overlay::save(&mut doc)
+}
+
+/// Encode `text` as a PDF text string: as-is if ASCII, otherwise UTF-16BE
+/// with a byte order mark.
+fn text_string(text: &str) -> Vec<u8> {
+    if text.is_ascii() {
+        return text.as_bytes().to_vec();
+    }
+    let mut bytes = vec![0xFE, 0xFF];
+    bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+    bytes
+}
+
+// -- Headings -----------------------------------------------------------------
+
+/// Build an outline from the recognised text of each page (as returned by
+/// OCR, one string per page), with an entry for every line that looks like
+/// a heading.
+///
+/// A heading is a short line without closing punctuation that is either in
+/// capitals ("INVOICE") or numbered ("2.1 Payment terms", "Chapter 3").
+/// OCR gives no font sizes, so this is a guess from the text alone.
+pub fn outline_from_headings<S: AsRef<str>>(pages: &[S]) -> Vec<OutlineEntry> {
+    pages
+        .iter()
+        .zip(1u32..)
+        .flat_map(|(text, page)| {
+            text.as_ref()
+                .lines()
+                .map(str::trim)
+                .filter(|line| is_heading(line))
+                .map(move |line| OutlineEntry::new(line, page))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn is_heading(line: &str) -> bool {
+    let chars = line.chars().count();
+    if !(3..=MAX_HEADING_CHARS).contains(&chars) || line.ends_with(['.', ',', ';', ':']) {
+        return false;
+    }
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() < 2 {
+        return false;
+    }
+    let capitals = letters.iter().all(|c| c.is_uppercase());
+    let first_word = line.split_whitespace().next().unwrap_or("");
+    // "2." or "2.1" but not "12", which starts addresses as often as
+    // headings.
+    let numbered = first_word.contains('.')
+        && first_word
+            .trim_end_matches('.')
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        || ["chapter", "section", "part"]
+            .iter()
+            .any(|word| first_word.eq_ignore_ascii_case(word));
+    capitals || numbered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pdf::text;
+    use presswerk_core::PaperSize;
+
+    /// A text PDF long enough to run to `pages` pages.
+    fn pdf_with_pages(pages: usize) -> Vec<u8> {
+        let body = "line\n".repeat(80 * pages);
+        let pdf = text::create(
+            PaperSize::A4,
+            "Outline test",
+            &body,
+            text::FONT_SIZE,
+            text::MARGIN_MM,
+        )
+        .unwrap();
+        let doc = Document::load_mem(&pdf).unwrap();
+        assert!(doc.get_pages().len() >= pages);
+        pdf
+    }
+
+    #[test]
+    fn outline_items_point_at_their_pages() {
+        let pdf = pdf_with_pages(3);
+        let entries = [
+            OutlineEntry::new("Cover", 1),
+            OutlineEntry::new("Rechnung für März", 3),
+        ];
+
+        let out = add_outline(&pdf, &entries).unwrap();
+        let doc = Document::load_mem(&out).unwrap();
+        let pages = doc.get_pages();
+
+        let catalog = doc.catalog().unwrap();
+        assert_eq!(
+            catalog.get(b"PageMode").and_then(Object::as_name).unwrap(),
+            b"UseOutlines"
+        );
+        let outlines_id = catalog
+            .get(b"Outlines")
+            .and_then(Object::as_reference)
+            .unwrap();
+        let outlines = doc.get_dictionary(outlines_id).unwrap();
+        assert_eq!(outlines.get(b"Count").and_then(Object::as_i64).unwrap(), 2);
+
+        let mut item_id = outlines
+            .get(b"First")
+            .and_then(Object::as_reference)
+            .unwrap();
+        for (index, entry) in entries.iter().enumerate() {
+            let item = doc.get_dictionary(item_id).unwrap();
+            let title = item.get(b"Title").and_then(Object::as_str).unwrap();
+            assert_eq!(title, text_string(&entry.title).as_slice());
+            let dest = item.get(b"Dest").and_then(Object::as_array).unwrap();
+            assert_eq!(dest[0].as_reference().unwrap(), pages[&entry.page]);
+            assert_eq!(
+                item.get(b"Parent").and_then(Object::as_reference).unwrap(),
+                outlines_id
+            );
+            if index + 1 < entries.len() {
+                item_id = item.get(b"Next").and_then(Object::as_reference).unwrap();
+            } else {
+                assert!(item.get(b"Next").is_err());
+                assert_eq!(
+                    outlines
+                        .get(b"Last")
+                        .and_then(Object::as_reference)
+                        .unwrap(),
+                    item_id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn entry_past_the_last_page_is_rejected() {
+        let pdf = pdf_with_pages(1);
+        let pages = Document::load_mem(&pdf).unwrap().get_pages().len() as u32;
+
+        let err = add_outline(&pdf, &[OutlineEntry::new("Appendix", pages + 1)]).unwrap_err();
+        assert!(err.to_string().contains("Appendix"), "{err}");
+        assert!(add_outline(&pdf, &[OutlineEntry::new("Intro", 0)]).is_err());
+        assert!(add_outline(&pdf, &[OutlineEntry::new("  ", 1)]).is_err());
+        assert_eq!(add_outline(&pdf, &[]).unwrap(), pdf);
+    }
+
+    #[test]
+    fn writer_outline_applied_to_created_documents() {
+        let mut writer = crate::pdf::PdfWriter::a4();
+        assert!(
+            writer
+                .set_outline(&[OutlineEntry::new("Start", 0)])
+                .is_err()
+        );
+        writer
+            .set_outline(&[OutlineEntry::new("Start", 1)])
+            .unwrap();
+
+        let pdf = writer
+            .create_from_text(
+                "hello",
+                crate::pdf::PdfWriter::TEXT_FONT_SIZE,
+                crate::pdf::PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let doc = Document::load_mem(&pdf).unwrap();
+        let outlines_id = doc
+            .catalog()
+            .and_then(|catalog| catalog.get(b"Outlines"))
+            .and_then(Object::as_reference)
+            .unwrap();
+        let outlines = doc.get_dictionary(outlines_id).unwrap();
+        assert_eq!(outlines.get(b"Count").and_then(Object::as_i64).unwrap(), 1);
+    }
+
+    #[test]
+    fn headings_found_in_ocr_text() {
+        let pages = [
+            "ACME LTD\n12 High Street\nDear customer,\n",
+            "1. Summary\nThe amount due is shown below.\n2.1 Payment terms\n",
+            "Chapter 3\nNo headings on this line, though it is long enough to be a paragraph.\n",
+        ];
+
+        assert_eq!(
+            outline_from_headings(&pages),
+            [
+                OutlineEntry::new("ACME LTD", 1),
+                OutlineEntry::new("1. Summary", 2),
+                OutlineEntry::new("2.1 Payment terms", 2),
+                OutlineEntry::new("Chapter 3", 3),
+            ]
+        );
+    }
+}
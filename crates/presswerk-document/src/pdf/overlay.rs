@@ -0,0 +1,553 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Overlays — stamp extra content (watermarks, labels) onto every page of an
+// existing PDF without touching the original content streams.
+//
+// The original page content is bracketed by `q`/`Q` so that any graphics
+// state it leaves behind (transforms, colours) cannot leak into the overlay,
+// and the overlay is appended as a separate content stream.  Overlay text is
+// set in the bundled font (see `font`), subset and embedded once per overlay.
+// Each overlay registers its resources under names the page does not use
+// yet, so stamping a PDF twice (a watermark, then page numbers) never
+// replaces the font an earlier overlay's text refers to.
+
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, dictionary};
+use presswerk_core::error::PresswerkError;
+use tracing::{debug, info, instrument};
+
+use super::font::FontSubset;
+
+/// Base resource name of the overlay font (chosen to avoid clashing with the
+/// names producers typically use, such as `/F1`); a number is appended when
+/// the page already uses it.
+const OVERLAY_FONT: &str = "PwOverlayFont";
+
+/// Base resource name of the watermark transparency graphics state.
+const WATERMARK_GS: &str = "PwWatermarkGS";
+
+/// MediaBox assumed when a page (and its ancestors) does not declare one:
+/// A4 in points.
+const DEFAULT_MEDIA_BOX: [f32; 4] = [0.0, 0.0, 595.0, 842.0];
+
+/// Font size of page numbers, in points.
+const PAGE_NUMBER_SIZE: f32 = 9.0;
+
+/// Distance of page numbers from the page edges, in points (half an inch,
+/// inside the unprintable margin of most printers).
+const PAGE_NUMBER_MARGIN: f32 = 36.0;
+
+/// Where page numbers are placed along the bottom edge of each page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FooterPosition {
+    BottomLeft,
+    #[default]
+    BottomCenter,
+    BottomRight,
+}
+
+/// Overlay `text` diagonally across the centre of every page.
+///
+/// `opacity` is clamped to `0.0..=1.0` and applied through an `ExtGState`
+/// (`/ca` fill alpha); `angle_deg` rotates the text anti-clockwise from the
+/// horizontal. The font size is chosen so the text spans roughly two thirds
+/// of the page diagonal.
+#[instrument(skip(pdf), fields(bytes_len = pdf.len()))]
+pub(crate) fn add_text_watermark(
+    pdf: &[u8],
+    text: &str,
+    opacity: f32,
+    angle_deg: f32,
+) -> Result<Vec<u8>, PresswerkError> {
+    if text.trim().is_empty() {
+        return Err(PresswerkError::PdfError(
+            "watermark text must not be empty".into(),
+        ));
+    }
+
+    let mut doc = Document::load_mem(pdf)
+        .map_err(|err| PresswerkError::PdfError(format!("failed to load PDF: {}", err)))?;
+
+    let opacity = opacity.clamp(0.0, 1.0);
+    let mut font = FontSubset::new()?;
+    let shown = font.show(text);
+    let width_per_point = font.font().text_width(text, 1.0);
+    let cap_height = font.font().cap_height();
+    let font_id = font.embed(&mut doc);
+    let gs_id = doc.add_object(dictionary! {
+        "Type" => "ExtGState",
+        "ca" => opacity,
+        "CA" => opacity,
+    });
+
+    let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+    for &page_id in &pages {
+        let [x0, y0, x1, y1] = page_media_box(&doc, page_id);
+        let (width, height) = (x1 - x0, y1 - y0);
+        let diagonal = (width * width + height * height).sqrt();
+
+        let font_size = (diagonal * 0.66 / width_per_point).clamp(8.0, 200.0);
+        let text_w = width_per_point * font_size;
+        let text_h = cap_height * font_size;
+
+        // Rotate about the text's own centre, then move that centre to the
+        // middle of the page.
+        let (sin, cos) = angle_deg.to_radians().sin_cos();
+        let (cx, cy) = (x0 + width / 2.0, y0 + height / 2.0);
+        let tx = cx - cos * text_w / 2.0 + sin * text_h / 2.0;
+        let ty = cy - sin * text_w / 2.0 - cos * text_h / 2.0;
+
+        let font_name = set_page_resource(&mut doc, page_id, b"Font", OVERLAY_FONT, font_id)?;
+        let gs_name = set_page_resource(&mut doc, page_id, b"ExtGState", WATERMARK_GS, gs_id)?;
+        let mut operations = vec![
+            Operation::new("q", vec![]),
+            Operation::new("gs", vec![Object::Name(gs_name.into_bytes())]),
+            // Mid grey so the mark reads on both white paper and dark photos.
+            Operation::new("rg", vec![0.5.into(), 0.5.into(), 0.5.into()]),
+            Operation::new("BT", vec![]),
+            Operation::new(
+                "Tf",
+                vec![Object::Name(font_name.into_bytes()), font_size.into()],
+            ),
+            Operation::new(
+                "Tm",
+                vec![
+                    cos.into(),
+                    sin.into(),
+                    (-sin).into(),
+                    cos.into(),
+                    tx.into(),
+                    ty.into(),
+                ],
+            ),
+        ];
+        operations.extend(shown.iter().cloned());
+        operations.extend([Operation::new("ET", vec![]), Operation::new("Q", vec![])]);
+
+        append_overlay(&mut doc, page_id, Content { operations })?;
+
+        debug!(?page_id, font_size, "Watermark stamped");
+    }
+
+    info!(pages = pages.len(), opacity, angle_deg, "Watermark applied");
+
+    save(&mut doc)
+}
+
+/// Stamp "Page X of Y" at the bottom of every page.
+///
+/// The first page is numbered `start_at` and Y is the number of the last
+/// page, so a document continuing from an earlier printout reads e.g.
+/// "Page 5 of 8".
+#[instrument(skip(pdf), fields(bytes_len = pdf.len()))]
+pub(crate) fn add_page_numbers(
+    pdf: &[u8],
+    position: FooterPosition,
+    start_at: u32,
+) -> Result<Vec<u8>, PresswerkError> {
+    let mut doc = Document::load_mem(pdf)
+        .map_err(|err| PresswerkError::PdfError(format!("failed to load PDF: {}", err)))?;
+
+    let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+    let last = start_at.saturating_add((pages.len() as u32).saturating_sub(1));
+
+    // Every label is encoded before the font is embedded, so the subset
+    // holds all the digits used.
+    let mut font = FontSubset::new()?;
+    let labels: Vec<(Vec<Operation>, f32)> = (0..pages.len())
+        .map(|index| {
+            let number = start_at.saturating_add(index as u32);
+            let label = format!("Page {number} of {last}");
+            let width = font.font().text_width(&label, PAGE_NUMBER_SIZE);
+            (font.show(&label), width)
+        })
+        .collect();
+    let font_id = font.embed(&mut doc);
+
+    for (&page_id, (label, text_w)) in pages.iter().zip(labels) {
+        let [x0, y0, x1, _] = page_media_box(&doc, page_id);
+        let tx = match position {
+            FooterPosition::BottomLeft => x0 + PAGE_NUMBER_MARGIN,
+            FooterPosition::BottomCenter => (x0 + x1 - text_w) / 2.0,
+            FooterPosition::BottomRight => x1 - PAGE_NUMBER_MARGIN - text_w,
+        };
+        let ty = y0 + PAGE_NUMBER_MARGIN;
+
+        let font_name = set_page_resource(&mut doc, page_id, b"Font", OVERLAY_FONT, font_id)?;
+        let mut operations = vec![
+            Operation::new("q", vec![]),
+            Operation::new("rg", vec![0.into(), 0.into(), 0.into()]),
+            Operation::new("BT", vec![]),
+            Operation::new(
+                "Tf",
+                vec![
+                    Object::Name(font_name.into_bytes()),
+                    PAGE_NUMBER_SIZE.into(),
+                ],
+            ),
+            Operation::new("Td", vec![tx.into(), ty.into()]),
+        ];
+        operations.extend(label);
+        operations.extend([Operation::new("ET", vec![]), Operation::new("Q", vec![])]);
+        append_overlay(&mut doc, page_id, Content { operations })?;
+
+        debug!(?page_id, "Page number stamped");
+    }
+
+    info!(
+        pages = pages.len(),
+        ?position,
+        start_at,
+        "Page numbers applied"
+    );
+
+    save(&mut doc)
+}
+
+/// The page's effective MediaBox as `[x0, y0, x1, y1]`, following the
+/// `/Parent` chain because the attribute is inheritable.
+pub(crate) fn page_media_box(doc: &Document, page_id: ObjectId) -> [f32; 4] {
+    let mut current = Some(page_id);
+    let mut depth = 0;
+
+    while let Some(id) = current {
+        let Ok(dict) = doc.get_dictionary(id) else {
+            break;
+        };
+        if let Ok(media_box) = dict.get(b"MediaBox") {
+            let media_box = doc
+                .dereference(media_box)
+                .map(|(_, obj)| obj)
+                .unwrap_or(media_box);
+            if let Ok(values) = media_box.as_array() {
+                let numbers: Vec<f32> = values.iter().filter_map(|v| v.as_float().ok()).collect();
+                if let [a, b, c, d] = numbers[..] {
+                    return [a.min(c), b.min(d), a.max(c), b.max(d)];
+                }
+            }
+        }
+        current = dict.get(b"Parent").and_then(|p| p.as_reference()).ok();
+        depth += 1;
+        if depth > 32 {
+            break;
+        }
+    }
+
+    DEFAULT_MEDIA_BOX
+}
+
+/// Register `id` in the page's `/Resources/<category>` dictionary and
+/// return the name it is registered under.
+///
+/// The name is `base`, or `base` followed by the lowest number from 2 that
+/// the page does not use yet, so resources already on the page (from the
+/// producer or an earlier overlay) are never replaced.  Inherited or shared
+/// (indirect) resource dictionaries are copied onto the page first so the
+/// addition never leaks onto pages that were not stamped.
+pub(crate) fn set_page_resource(
+    doc: &mut Document,
+    page_id: ObjectId,
+    category: &[u8],
+    base: &str,
+    id: ObjectId,
+) -> Result<String, PresswerkError> {
+    let mut resources = effective_resources(doc, page_id);
+
+    let mut entries = match resources.get(category) {
+        Ok(Object::Dictionary(dict)) => dict.clone(),
+        Ok(Object::Reference(ref_id)) => doc.get_dictionary(*ref_id).cloned().unwrap_or_default(),
+        _ => Dictionary::new(),
+    };
+    let name = std::iter::once(base.to_string())
+        .chain((2..).map(|n| format!("{base}{n}")))
+        .find(|name| match entries.get(name.as_bytes()) {
+            Ok(existing) => existing.as_reference().ok() == Some(id),
+            Err(_) => true,
+        })
+        .expect("unbounded candidate names");
+    entries.set(name.as_str(), Object::Reference(id));
+    resources.set(category.to_vec(), Object::Dictionary(entries));
+
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|err| PresswerkError::PdfError(format!("cannot read page: {}", err)))?;
+    page.set("Resources", Object::Dictionary(resources));
+    Ok(name)
+}
+
+/// Append `overlay` as a new content stream, isolating the page's existing
+/// content inside a `q`/`Q` pair.
+pub(crate) fn append_overlay(
+    doc: &mut Document,
+    page_id: ObjectId,
+    overlay: Content<Vec<Operation>>,
+) -> Result<(), PresswerkError> {
+    let existing: Vec<Object> = doc
+        .get_page_contents(page_id)
+        .into_iter()
+        .map(Object::Reference)
+        .collect();
+
+    let overlay_bytes = overlay
+        .encode()
+        .map_err(|err| PresswerkError::PdfError(format!("failed to encode overlay: {}", err)))?;
+
+    let mut contents = Vec::with_capacity(existing.len() + 2);
+    if !existing.is_empty() {
+        let open = doc.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+        contents.push(Object::Reference(open));
+        contents.extend(existing);
+        let mut close = b"Q\n".to_vec();
+        close.extend(overlay_bytes);
+        contents.push(Object::Reference(
+            doc.add_object(Stream::new(Dictionary::new(), close)),
+        ));
+    } else {
+        contents.push(Object::Reference(
+            doc.add_object(Stream::new(Dictionary::new(), overlay_bytes)),
+        ));
+    }
+
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|err| PresswerkError::PdfError(format!("cannot read page: {}", err)))?;
+    page.set("Contents", Object::Array(contents));
+    Ok(())
+}
+
+/// Serialise the document to bytes.
+pub(crate) fn save(doc: &mut Document) -> Result<Vec<u8>, PresswerkError> {
+    let mut output = Vec::new();
+    doc.save_to(&mut output)
+        .map_err(|err| PresswerkError::PdfError(format!("failed to serialise PDF: {}", err)))?;
+    Ok(output)
+}
+
+/// Resolve the page's resource dictionary (direct, indirect, or inherited)
+/// into an owned copy.
+fn effective_resources(doc: &Document, page_id: ObjectId) -> Dictionary {
+    let mut current = Some(page_id);
+    let mut depth = 0;
+
+    while let Some(id) = current {
+        let Ok(dict) = doc.get_dictionary(id) else {
+            break;
+        };
+        match dict.get(b"Resources") {
+            Ok(Object::Dictionary(resources)) => return resources.clone(),
+            Ok(Object::Reference(ref_id)) => {
+                return doc.get_dictionary(*ref_id).cloned().unwrap_or_default();
+            }
+            _ => {}
+        }
+        current = dict.get(b"Parent").and_then(|p| p.as_reference()).ok();
+        depth += 1;
+        if depth > 32 {
+            break;
+        }
+    }
+
+    Dictionary::new()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::pdf::reader::PdfReader;
+
+    /// Add a standard-14 Helvetica font object with WinAnsi encoding.
+    fn add_helvetica(doc: &mut Document) -> ObjectId {
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        })
+    }
+
+    /// Build a minimal PDF with `pages` pages, each showing "Page N" in
+    /// Helvetica.
+    pub(crate) fn sample_pdf(pages: usize) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = add_helvetica(&mut doc);
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+
+        let kids: Vec<Object> = (1..=pages)
+            .map(|n| {
+                let content = Content {
+                    operations: vec![
+                        Operation::new("BT", vec![]),
+                        Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                        Operation::new("Td", vec![72.into(), 770.into()]),
+                        Operation::new("Tj", vec![Object::string_literal(format!("Page {n}"))]),
+                        Operation::new("ET", vec![]),
+                    ],
+                };
+                let content_id =
+                    doc.add_object(Stream::new(Dictionary::new(), content.encode().unwrap()));
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "Resources" => resources_id,
+                })
+                .into()
+            })
+            .collect();
+
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => pages as i64,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        save(&mut doc).unwrap()
+    }
+
+    #[test]
+    fn watermark_keeps_page_count_and_parses() {
+        let pdf = sample_pdf(3);
+        let stamped = add_text_watermark(&pdf, "DRAFT", 0.3, 45.0).unwrap();
+
+        let reader = PdfReader::from_bytes(&stamped).unwrap();
+        assert_eq!(reader.page_count(), 3);
+    }
+
+    #[test]
+    fn watermark_preserves_original_text() {
+        let pdf = sample_pdf(2);
+        let stamped = add_text_watermark(&pdf, "CONFIDENTIAL", 0.2, 30.0).unwrap();
+
+        let text = PdfReader::from_bytes(&stamped)
+            .unwrap()
+            .extract_text()
+            .unwrap();
+        assert!(text.contains("Page 1"), "got: {text:?}");
+        assert!(text.contains("Page 2"), "got: {text:?}");
+        assert!(text.contains("CONFIDENTIAL"), "got: {text:?}");
+    }
+
+    #[test]
+    fn watermark_registers_transparency_state() {
+        let stamped = add_text_watermark(&sample_pdf(1), "DRAFT", 1.7, 45.0).unwrap();
+        let doc = Document::load_mem(&stamped).unwrap();
+        let page_id = doc.get_pages()[&1];
+
+        let resources = effective_resources(&doc, page_id);
+        let states = resources.get(b"ExtGState").unwrap().as_dict().unwrap();
+        let gs_id = states
+            .get(WATERMARK_GS.as_bytes())
+            .unwrap()
+            .as_reference()
+            .unwrap();
+        let gs = doc.get_dictionary(gs_id).unwrap();
+        assert_eq!(gs.get(b"ca").unwrap().as_float().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn watermark_rejects_empty_text() {
+        assert!(add_text_watermark(&sample_pdf(1), "  ", 0.3, 45.0).is_err());
+    }
+
+    #[test]
+    fn page_numbers_extend_every_page() {
+        let pdf = sample_pdf(3);
+        let numbered = add_page_numbers(&pdf, FooterPosition::BottomRight, 1).unwrap();
+
+        let content_len = |bytes: &[u8]| -> Vec<usize> {
+            let doc = Document::load_mem(bytes).unwrap();
+            doc.page_iter()
+                .map(|id| doc.get_page_content(id).unwrap().len())
+                .collect()
+        };
+        let (before, after) = (content_len(&pdf), content_len(&numbered));
+        assert_eq!(after.len(), 3);
+        for (old, new) in before.iter().zip(&after) {
+            assert!(new > old, "content did not grow: {old} -> {new}");
+        }
+
+        let reader = PdfReader::from_bytes(&numbered).unwrap();
+        assert_eq!(reader.page_count(), 3);
+        let text = reader.extract_text().unwrap();
+        assert!(text.contains("Page 1 of 3"), "got: {text:?}");
+        assert!(text.contains("Page 3 of 3"), "got: {text:?}");
+    }
+
+    #[test]
+    fn page_numbers_start_at_offset_and_share_one_font() {
+        let numbered = add_page_numbers(&sample_pdf(2), FooterPosition::BottomCenter, 5).unwrap();
+
+        let text = PdfReader::from_bytes(&numbered)
+            .unwrap()
+            .extract_text()
+            .unwrap();
+        assert!(text.contains("Page 5 of 6"), "got: {text:?}");
+        assert!(text.contains("Page 6 of 6"), "got: {text:?}");
+
+        // Both pages point at the same embedded font.
+        let doc = Document::load_mem(&numbered).unwrap();
+        let font_ids: Vec<ObjectId> = doc
+            .page_iter()
+            .map(|page_id| {
+                let resources = effective_resources(&doc, page_id);
+                let fonts = doc.dereference(resources.get(b"Font").unwrap()).unwrap().1;
+                let font = fonts.as_dict().unwrap().get(OVERLAY_FONT.as_bytes());
+                font.unwrap().as_reference().unwrap()
+            })
+            .collect();
+        assert_eq!(font_ids[0], font_ids[1]);
+        let font = doc.get_dictionary(font_ids[0]).unwrap();
+        assert_eq!(font.get(b"Subtype").unwrap().as_name().unwrap(), b"Type0");
+    }
+
+    #[test]
+    fn watermark_handles_text_beyond_win_ansi() {
+        let stamped = add_text_watermark(&sample_pdf(1), "Черновик", 0.3, 45.0).unwrap();
+        let text = PdfReader::from_bytes(&stamped)
+            .unwrap()
+            .extract_text()
+            .unwrap();
+        assert!(text.contains("Черновик"), "got: {text:?}");
+    }
+
+    #[test]
+    fn watermark_and_page_numbers_keep_their_own_fonts() {
+        let watermarked = add_text_watermark(&sample_pdf(2), "DRAFT", 0.3, 45.0).unwrap();
+        let stamped = add_page_numbers(&watermarked, FooterPosition::BottomCenter, 1).unwrap();
+
+        let doc = Document::load_mem(&stamped).unwrap();
+        for page_id in doc.page_iter() {
+            let resources = effective_resources(&doc, page_id);
+            let fonts = doc.dereference(resources.get(b"Font").unwrap()).unwrap().1;
+            let fonts = fonts.as_dict().unwrap();
+            let watermark_font = fonts.get(OVERLAY_FONT.as_bytes()).unwrap();
+            let number_font = fonts.get(format!("{OVERLAY_FONT}2").as_bytes()).unwrap();
+            assert_ne!(
+                watermark_font.as_reference().unwrap(),
+                number_font.as_reference().unwrap()
+            );
+            assert!(fonts.get(b"F1").is_ok());
+        }
+
+        let text = PdfReader::from_bytes(&stamped)
+            .unwrap()
+            .extract_text()
+            .unwrap();
+        assert!(text.contains("DRAFT"), "got: {text:?}");
+        assert!(text.contains("Page 2 of 2"), "got: {text:?}");
+        assert!(text.contains("Page 1"), "got: {text:?}");
+    }
+}
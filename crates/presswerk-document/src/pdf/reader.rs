@@ -1,15 +1,28 @@
 // SPDX-License-Identifier: PMPL-1.0-or-later
 // Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
 //
-// PDF reader — open, inspect, merge, split, and rotate existing PDF documents
-// using the `lopdf` crate.
+// PDF reader — open, inspect, merge, split, rotate, extract text from, and
+// verify existing PDF documents using the `lopdf` crate.
 
+use std::collections::HashMap;
 use std::path::Path;
 
-use lopdf::{Document, Object, ObjectId};
+use image::DynamicImage;
+use lopdf::content::Content;
+use lopdf::{Document, Encoding, Object, ObjectId, dictionary};
+use presswerk_core::PageRange;
 use presswerk_core::error::PresswerkError;
+use presswerk_security::IntegrityProof;
 use tracing::{debug, info, instrument, warn};
 
+use super::images::{self, ExtractedImage, ImageColorSpace};
+use super::{integrity, win_ansi};
+use crate::image::processor;
+
+/// Largest spread between the red, green and blue (or cyan, magenta and
+/// yellow) components of a fill or stroke colour still taken as grey.
+const GRAY_TOLERANCE: f32 = 0.1;
+
 /// Reads and manipulates existing PDF files.
 ///
 /// Wraps `lopdf::Document` and provides higher-level operations such as merging
@@ -69,8 +82,155 @@ pub fn source_path(&self) -> Option<&str> {
         self.source_path.as_deref()
     }
 
+    /// Whether any page has colour a black-and-white printer would lose.
+    ///
+    /// Looks at the fill and stroke colours set in each page's content
+    /// stream (`rg`, `k`, `sc` and their stroking forms) and at the pixels
+    /// of the images it draws.  Near-greys count as grey, so a scanned
+    /// letter is not mistaken for colour.  Images that cannot be decoded
+    /// are judged by their colour space.
+    #[instrument(skip(self))]
+    pub fn has_color(&self) -> bool {
+        for (&page_number, &page_id) in &self.document.get_pages() {
+            if page_content_has_color(&self.document, page_id) {
+                debug!(page = page_number, "Colour set in page content");
+                return true;
+            }
+            match self.extract_images(page_number) {
+                Ok(images) if images.iter().any(image_has_color) => {
+                    debug!(page = page_number, "Colour image on page");
+                    return true;
+                }
+                Ok(_) => {}
+                Err(err) => debug!(page = page_number, %err, "Page images not checked for colour"),
+            }
+        }
+        false
+    }
+
+    /// Object ID of a page (1-indexed).
+    fn page_id(&self, page_number: u32) -> Result<ObjectId, PresswerkError> {
+        let pages = self.document.get_pages();
+        pages.get(&page_number).copied().ok_or_else(|| {
+            PresswerkError::PdfError(format!(
+                "page {} not found (document has {} pages)",
+                page_number,
+                pages.len()
+            ))
+        })
+    }
+
     // -- Extraction -----------------------------------------------------------
 
+    /// Extract the text layer of every page as plain text.
+    ///
+    /// Walks each page's content streams and decodes the `Tj`, `TJ`, `'`, and
+    /// `"` show-text operators using the font's declared encoding (WinAnsi and
+    /// the other single-byte encodings, or `Identity-H`/`Identity-V` via the
+    /// font's `/ToUnicode` CMap). Marked-content spans with an `/ActualText`
+    /// contribute that text in place of the glyphs they enclose. Text objects
+    /// and line moves become line breaks; pages are separated by a blank line.
+    ///
+    /// Image-only pages (scans without OCR) contribute an empty string rather
+    /// than an error, so the result may be empty.
+    #[instrument(skip(self))]
+    pub fn extract_text(&self) -> Result<String, PresswerkError> {
+        let pages = self.document.get_pages();
+        let mut page_texts: Vec<String> = Vec::with_capacity(pages.len());
+
+        for (&page_number, &page_id) in &pages {
+            let text = extract_page_text(&self.document, page_id).map_err(|err| {
+                PresswerkError::PdfError(format!(
+                    "failed to read text on page {}: {}",
+                    page_number, err
+                ))
+            })?;
+            page_texts.push(text);
+        }
+
+        let text = page_texts.join("\n\n");
+        debug!(pages = pages.len(), chars = text.len(), "Text extracted");
+        Ok(text)
+    }
+
+    /// Extract the raster images drawn on a page (1-indexed), including
+    /// those inside Form XObjects, each listed once.
+    ///
+    /// JPEG images are returned as JPEG files and CCITT fax images as TIFF
+    /// files, untouched; everything else is unpacked to raw samples. See
+    /// [`ExtractedImage::to_dynamic`] for decoding to pixels. Fails if any
+    /// image uses JPEG 2000, JBIG2 or another filter that cannot be
+    /// unpacked. Vector-only pages give an empty list.
+    #[instrument(skip(self))]
+    pub fn extract_images(&self, page_number: u32) -> Result<Vec<ExtractedImage>, PresswerkError> {
+        let page_id = self.page_id(page_number)?;
+
+        let extracted = images::page_image_ids(&self.document, page_id)
+            .into_iter()
+            .map(|id| {
+                let stream = self
+                    .document
+                    .get_object(id)
+                    .and_then(Object::as_stream)
+                    .map_err(|err| {
+                        PresswerkError::PdfError(format!("cannot read image {:?}: {}", id, err))
+                    })?;
+                images::extract_image(&self.document, id, stream)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        debug!(page_number, images = extracted.len(), "Images extracted");
+        Ok(extracted)
+    }
+
+    /// Decode the largest raster image drawn on a page (1-indexed).
+    ///
+    /// Returns `Ok(None)` for vector-only pages. Images that cannot be
+    /// decoded are skipped; fails only if none of them can.
+    pub(crate) fn page_image(
+        &self,
+        page_number: u32,
+    ) -> Result<Option<DynamicImage>, PresswerkError> {
+        let page_id = self.page_id(page_number)?;
+
+        let ids = images::page_image_ids(&self.document, page_id);
+        if ids.is_empty() {
+            return Ok(None);
+        }
+
+        let largest = ids
+            .iter()
+            .filter_map(|&id| {
+                let stream = self
+                    .document
+                    .get_object(id)
+                    .and_then(Object::as_stream)
+                    .ok()?;
+                images::extract_image(&self.document, id, stream)
+                    .and_then(|image| image.to_dynamic())
+                    .inspect_err(|err| debug!(?id, %err, "Skipping undecodable image"))
+                    .ok()
+            })
+            .max_by_key(|image| u64::from(image.width()) * u64::from(image.height()));
+
+        match largest {
+            Some(image) => {
+                debug!(
+                    page_number,
+                    width = image.width(),
+                    height = image.height(),
+                    "Page image decoded"
+                );
+                Ok(Some(image))
+            }
+            None => Err(PresswerkError::PdfError(format!(
+                "page {} contains {} image(s), none in a supported encoding",
+                page_number,
+                ids.len()
+            ))),
+        }
+    }
+
     /// Extract a single page (1-indexed) into a new standalone PDF document.
     ///
     /// Returns the serialised bytes of the single-page PDF.
@@ -90,7 +250,7 @@ pub fn extract_page(&self, page_number: u32) -> Result<Vec<u8>, PresswerkError>
             PresswerkError::PdfError(format!("page {} not found in page tree", page_number))
         })?;
 
-        let mut new_doc = Document::with_version("1.5");
+        let mut new_doc = empty_document();
         clone_page_into(&self.document, &mut new_doc, page_object_id)?;
 
         let mut output = Vec::new();
@@ -102,6 +262,37 @@ pub fn extract_page(&self, page_number: u32) -> Result<Vec<u8>, PresswerkError>
         Ok(output)
     }
 
+    /// Extract the pages selected by `range` into a new PDF document.
+    ///
+    /// The range is validated against the page count first, so asking for
+    /// page 9 of an 8-page document is an error rather than a silent skip.
+    #[instrument(skip(self), fields(range = %range))]
+    pub fn extract_pages(&self, range: &PageRange) -> Result<Vec<u8>, PresswerkError> {
+        let total = self.page_count() as u32;
+        range.validate(total)?;
+
+        let pages = self.document.get_pages();
+        let mut new_doc = empty_document();
+
+        for page_num in range.iter(total) {
+            let page_id = *pages.get(&page_num).ok_or_else(|| {
+                PresswerkError::PdfError(format!(
+                    "page {} not found during range extraction",
+                    page_num
+                ))
+            })?;
+            clone_page_into(&self.document, &mut new_doc, page_id)?;
+        }
+
+        let mut output = Vec::new();
+        new_doc.save_to(&mut output).map_err(|err| {
+            PresswerkError::PdfError(format!("failed to serialise page range: {}", err))
+        })?;
+
+        debug!(range = %range, output_bytes = output.len(), "Pages extracted");
+        Ok(output)
+    }
+
     /// Split the document at `after_page` (1-indexed, inclusive) producing two
     /// byte-vectors: pages [1..=after_page] and pages [after_page+1..=end].
     #[instrument(skip(self), fields(after_page))]
@@ -116,8 +307,8 @@ pub fn split(&self, after_page: u32) -> Result<(Vec<u8>, Vec<u8>), PresswerkErro
 
         info!(after_page, total, "Splitting PDF");
 
-        let first = self.extract_page_range(1, after_page)?;
-        let second = self.extract_page_range(after_page + 1, total)?;
+        let first = self.extract_pages(&PageRange::span(1, after_page))?;
+        let second = self.extract_pages(&PageRange::from_page(after_page + 1))?;
 
         Ok((first, second))
     }
@@ -168,16 +359,70 @@ pub fn merge(&self, others: &[&[u8]]) -> Result<Vec<u8>, PresswerkError> {
     /// Returns the full document as bytes with the rotation applied.
     #[instrument(skip(self), fields(page_number, degrees))]
     pub fn rotate_page(&self, page_number: u32, degrees: i32) -> Result<Vec<u8>, PresswerkError> {
-        if degrees % 90 != 0 {
-            return Err(PresswerkError::PdfError(format!(
-                "rotation must be a multiple of 90, got {}",
-                degrees
-            )));
-        }
+        self.rotate_pages(&[(page_number as usize, degrees)])
+    }
 
+    /// Rotate several pages at once.
+    ///
+    /// Each entry is a `(page_number, degrees)` pair where `page_number` is
+    /// 1-indexed and `degrees` is a multiple of 90 (negative values rotate
+    /// anti-clockwise). The rotation accumulates onto any `/Rotate` value the
+    /// page already carries (including one inherited from the page tree) and
+    /// is normalised to 0, 90, 180, or 270.
+    ///
+    /// Returns the full document as bytes with all rotations applied.
+    #[instrument(skip_all, fields(count = rotations.len()))]
+    pub fn rotate_pages(&self, rotations: &[(usize, i32)]) -> Result<Vec<u8>, PresswerkError> {
         let mut doc = self.document.clone();
         let pages = doc.get_pages();
 
+        for &(page_number, degrees) in rotations {
+            if degrees % 90 != 0 {
+                return Err(PresswerkError::PdfError(format!(
+                    "rotation must be a multiple of 90, got {}",
+                    degrees
+                )));
+            }
+
+            let page_id = u32::try_from(page_number)
+                .ok()
+                .and_then(|n| pages.get(&n))
+                .copied()
+                .ok_or_else(|| {
+                    PresswerkError::PdfError(format!(
+                        "page {} not found (document has {} pages)",
+                        page_number,
+                        pages.len()
+                    ))
+                })?;
+
+            let existing_rotation = page_rotation(&doc, page_id);
+            // Normalise both before adding, so huge multiples of 90 cannot
+            // overflow.
+            let new_rotation =
+                (existing_rotation.rem_euclid(360) + degrees.rem_euclid(360)).rem_euclid(360);
+
+            // Set /Rotate on the page dictionary itself so it overrides any
+            // inherited value.
+            if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page_id) {
+                dict.set("Rotate", Object::Integer(new_rotation as i64));
+            }
+
+            info!(page_number, existing_rotation, new_rotation, "Page rotated");
+        }
+
+        let mut output = Vec::new();
+        doc.save_to(&mut output).map_err(|err| {
+            PresswerkError::PdfError(format!("failed to serialise rotated PDF: {}", err))
+        })?;
+
+        Ok(output)
+    }
+
+    /// Current `/Rotate` value (0, 90, 180, or 270) of a page (1-indexed),
+    /// taking inherited values from the page tree into account.
+    pub fn page_rotation(&self, page_number: u32) -> Result<i32, PresswerkError> {
+        let pages = self.document.get_pages();
         let page_id = *pages.get(&page_number).ok_or_else(|| {
             PresswerkError::PdfError(format!(
                 "page {} not found (document has {} pages)",
@@ -185,63 +430,245 @@ pub fn rotate_page(&self, page_number: u32, degrees: i32) -> Result<Vec<u8>, Pre
                 pages.len()
             ))
         })?;
+        Ok(page_rotation(&self.document, page_id))
+    }
 
-        // Read existing /Rotate value, default 0.
-        let existing_rotation = doc
-            .get_object(page_id)
-            .ok()
-            .and_then(|obj| match obj {
-                Object::Dictionary(dict) => dict
-                    .get(b"Rotate")
-                    .ok()
-                    .and_then(|r| r.as_i64().ok())
-                    .map(|v| v as i32),
-                _ => None,
-            })
-            .unwrap_or(0);
+    // -- Integrity ------------------------------------------------------------
 
-        let new_rotation = (existing_rotation + degrees).rem_euclid(360);
+    /// Compute the integrity proof for the document's current content,
+    /// sealed with `key`, ready for
+    /// [`PdfWriter::embed_integrity_proof`](super::PdfWriter::embed_integrity_proof).
+    ///
+    /// Only holders of `key` can produce or check the seal, so keep it
+    /// secret (e.g. in the encrypted storage) rather than in the document.
+    pub fn integrity_proof(&self, key: &[u8]) -> IntegrityProof {
+        IntegrityProof::for_content(key, &integrity::content_bytes(&self.document))
+    }
+
+    /// Check the `/PresswerkHash` and `/PresswerkSignature` entries in the
+    /// Info dictionary against the document's content and `key`.
+    ///
+    /// Returns `false` if the content was modified after the proof was
+    /// embedded, if the proof was sealed with another key, or if the
+    /// document carries no proof at all.
+    pub fn verify_integrity_proof(&self, key: &[u8]) -> Result<bool, PresswerkError> {
+        let Some(proof) = integrity::read(&self.document) else {
+            debug!("No integrity proof embedded");
+            return Ok(false);
+        };
 
-        // Set /Rotate on the page dictionary.
-        if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page_id) {
-            dict.set("Rotate", Object::Integer(new_rotation as i64));
+        let valid = proof.verify(key, &integrity::content_bytes(&self.document));
+        if !valid {
+            warn!(expected = %proof.hash, "Integrity proof does not match content");
         }
+        Ok(valid)
+    }
+}
 
-        info!(page_number, existing_rotation, new_rotation, "Page rotated");
+/// Decode the text shown on a single page.
+fn extract_page_text(doc: &Document, page_id: ObjectId) -> lopdf::Result<String> {
+    // Fonts whose encoding lopdf cannot resolve (e.g. Identity-H without a
+    // /ToUnicode CMap) map to `None` and their text is skipped.
+    let encodings: HashMap<Vec<u8>, Option<Encoding<'_>>> = doc
+        .get_page_fonts(page_id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, font)| {
+            let encoding = match font.get_font_encoding(doc) {
+                Ok(encoding) => Some(encoding),
+                Err(err) => {
+                    warn!(font = %String::from_utf8_lossy(&name), %err, "Unsupported font encoding");
+                    None
+                }
+            };
+            (name, encoding)
+        })
+        .collect();
 
-        let mut output = Vec::new();
-        doc.save_to(&mut output).map_err(|err| {
-            PresswerkError::PdfError(format!("failed to serialise rotated PDF: {}", err))
-        })?;
+    let content_data = match doc.get_page_content(page_id) {
+        Ok(data) => data,
+        // A page without content streams simply has no text.
+        Err(_) => return Ok(String::new()),
+    };
+    let content = Content::decode(&content_data)?;
 
-        Ok(output)
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut font: Option<&Option<Encoding<'_>>> = None;
+    // One entry per open marked-content sequence: whether it replaced its
+    // glyphs with `/ActualText`.
+    let mut marked: Vec<bool> = Vec::new();
+
+    for operation in &content.operations {
+        let replaced = marked.contains(&true);
+        match operation.operator.as_str() {
+            "Tf" => {
+                font = operation
+                    .operands
+                    .first()
+                    .and_then(|op| op.as_name().ok())
+                    .and_then(|name| encodings.get(name));
+            }
+            "BMC" => marked.push(false),
+            "BDC" => {
+                let actual_text = operation
+                    .operands
+                    .get(1)
+                    .and_then(|op| op.as_dict().ok())
+                    .and_then(|props| props.get(b"ActualText").ok())
+                    .and_then(|text| lopdf::decode_text_string(text).ok());
+                match actual_text {
+                    Some(text) if !replaced => {
+                        current.push_str(&text);
+                        marked.push(true);
+                    }
+                    _ => marked.push(false),
+                }
+            }
+            "EMC" => {
+                marked.pop();
+            }
+            "Tj" | "TJ" if !replaced => show_text(&mut current, font, &operation.operands),
+            "'" | "\"" => {
+                end_line(&mut lines, &mut current);
+                // `"` carries word and character spacing before the string.
+                if let (Some(string), false) = (operation.operands.last(), replaced) {
+                    show_text(&mut current, font, std::slice::from_ref(string));
+                }
+            }
+            "T*" | "Td" | "TD" | "Tm" | "ET" => end_line(&mut lines, &mut current),
+            _ => {}
+        }
     }
+    end_line(&mut lines, &mut current);
 
-    // -- Helpers --------------------------------------------------------------
+    Ok(lines.join("\n"))
+}
 
-    /// Extract a contiguous range of pages [start..=end] (1-indexed) into a new
-    /// PDF returned as bytes.
-    fn extract_page_range(&self, start: u32, end: u32) -> Result<Vec<u8>, PresswerkError> {
-        let pages = self.document.get_pages();
-        let mut new_doc = Document::with_version("1.5");
+/// Whether a page's content stream sets a fill or stroke colour that is not
+/// grey.
+fn page_content_has_color(doc: &Document, page_id: ObjectId) -> bool {
+    let Ok(content_data) = doc.get_page_content(page_id) else {
+        return false;
+    };
+    let Ok(content) = Content::decode(&content_data) else {
+        return false;
+    };
+    content
+        .operations
+        .iter()
+        .any(|operation| is_color_operation(&operation.operator, &operation.operands))
+}
 
-        for page_num in start..=end {
-            let page_id = *pages.get(&page_num).ok_or_else(|| {
-                PresswerkError::PdfError(format!(
-                    "page {} not found during range extraction",
-                    page_num
-                ))
-            })?;
-            clone_page_into(&self.document, &mut new_doc, page_id)?;
+/// Whether `operator` sets a colour off the grey axis.  `sc`/`scn` with
+/// three components are taken as RGB and with four as CMYK; pattern names
+/// and single components are ignored.
+fn is_color_operation(operator: &str, operands: &[Object]) -> bool {
+    let components: Vec<f32> = operands
+        .iter()
+        .filter_map(|operand| operand.as_float().ok())
+        .collect();
+    let spread = |values: &[f32]| {
+        let max = values.iter().copied().fold(f32::MIN, f32::max);
+        let min = values.iter().copied().fold(f32::MAX, f32::min);
+        max - min > GRAY_TOLERANCE
+    };
+    match (operator, components.as_slice()) {
+        ("rg" | "RG" | "sc" | "SC" | "scn" | "SCN", rgb @ [_, _, _]) => spread(rgb),
+        ("k" | "K" | "sc" | "SC" | "scn" | "SCN", [c, m, y, _]) => spread(&[*c, *m, *y]),
+        _ => false,
+    }
+}
+
+/// Whether an extracted image has colour, by its pixels when they can be
+/// decoded and by its colour space otherwise.
+fn image_has_color(image: &ExtractedImage) -> bool {
+    match image.to_dynamic() {
+        Ok(pixels) => processor::has_color(&pixels),
+        Err(_) => image.color_space != ImageColorSpace::Gray,
+    }
+}
+
+/// Append decoded show-text operands to `out`.
+///
+/// Large negative kerning adjustments inside a `TJ` array are treated as word
+/// gaps, which is how most producers encode inter-word spacing.
+fn show_text(out: &mut String, font: Option<&Option<Encoding<'_>>>, operands: &[Object]) {
+    for operand in operands {
+        match operand {
+            Object::String(bytes, _) => match font {
+                Some(Some(encoding)) => match Document::decode_text(encoding, bytes) {
+                    Ok(text) => out.push_str(&text),
+                    Err(_) => out.push_str(&win_ansi::decode(bytes)),
+                },
+                // Known font with an undecodable encoding: skip rather than
+                // emit glyph-ID garbage.
+                Some(None) => {}
+                // No font selected or not in the resources: best-effort WinAnsi.
+                None => out.push_str(&win_ansi::decode(bytes)),
+            },
+            Object::Array(items) => show_text(out, font, items),
+            Object::Integer(adjust) if *adjust < -200 => push_space(out),
+            Object::Real(adjust) if *adjust < -200.0 => push_space(out),
+            _ => {}
         }
+    }
+}
 
-        let mut output = Vec::new();
-        new_doc.save_to(&mut output).map_err(|err| {
-            PresswerkError::PdfError(format!("failed to serialise page range: {}", err))
-        })?;
+fn push_space(out: &mut String) {
+    if !out.is_empty() && !out.ends_with(' ') {
+        out.push(' ');
+    }
+}
 
-        Ok(output)
+/// Flush the current line, ignoring runs of blank lines.
+fn end_line(lines: &mut Vec<String>, current: &mut String) {
+    let line = current.trim_end().to_string();
+    current.clear();
+    if !line.is_empty() {
+        lines.push(line);
+    }
+}
+
+/// Read the effective `/Rotate` value of a page, walking up the `/Parent`
+/// chain because the attribute is inheritable. Defaults to 0.
+fn page_rotation(doc: &Document, page_id: ObjectId) -> i32 {
+    let mut current = Some(page_id);
+    // Guard against malformed, cyclic page trees.
+    let mut depth = 0;
+
+    while let Some(id) = current {
+        let Ok(dict) = doc.get_dictionary(id) else {
+            break;
+        };
+        if let Ok(rotate) = dict.get(b"Rotate").and_then(|r| r.as_i64()) {
+            return (rotate as i32).rem_euclid(360);
+        }
+        current = dict.get(b"Parent").and_then(|p| p.as_reference()).ok();
+        depth += 1;
+        if depth > 32 {
+            break;
+        }
     }
+
+    0
+}
+
+/// Create an empty document with a catalog and an empty page tree, ready for
+/// [`clone_page_into`] to append pages to.
+fn empty_document() -> Document {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.add_object(dictionary! {
+        "Type" => "Pages",
+        "Kids" => Vec::<Object>::new(),
+        "Count" => 0,
+    });
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc
 }
 
 /// Clone a single page object (and its referenced resources) from `source` into
@@ -375,3 +802,472 @@ fn deep_clone_object(
         other => Ok(other.clone()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::images::{ImageColorSpace, ImageData};
+    use crate::pdf::writer::PdfWriter;
+
+    /// Rotating page 1 by 90 degrees is reflected in its `/Rotate` entry.
+    #[test]
+    fn rotate_pages_sets_rotate_entry() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Sideways scan",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let reader = PdfReader::from_bytes(&pdf).unwrap();
+
+        let rotated = reader.rotate_pages(&[(1, 90)]).unwrap();
+        let reread = PdfReader::from_bytes(&rotated).unwrap();
+
+        assert_eq!(reread.page_rotation(1).unwrap(), 90);
+    }
+
+    /// Rotations accumulate onto the existing value and normalise.
+    #[test]
+    fn rotate_pages_accumulates_and_normalises() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Upside down",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let reader = PdfReader::from_bytes(&pdf).unwrap();
+
+        let once = reader.rotate_pages(&[(1, 270)]).unwrap();
+        let twice = PdfReader::from_bytes(&once)
+            .unwrap()
+            .rotate_pages(&[(1, 180)])
+            .unwrap();
+        let reread = PdfReader::from_bytes(&twice).unwrap();
+
+        assert_eq!(reread.page_rotation(1).unwrap(), 90);
+
+        let negative = reader.rotate_pages(&[(1, -90)]).unwrap();
+        let reread = PdfReader::from_bytes(&negative).unwrap();
+        assert_eq!(reread.page_rotation(1).unwrap(), 270);
+    }
+
+    /// Multiples of 90 near the ends of `i32` do not overflow.
+    #[test]
+    fn rotate_pages_handles_extreme_angles() {
+        // The largest multiple of 90 in an i32, a quarter turn mod 360.
+        const HUGE: i32 = i32::MAX / 90 * 90;
+
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Spun round",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let turned = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .rotate_pages(&[(1, 270)])
+            .unwrap();
+        let reader = PdfReader::from_bytes(&turned).unwrap();
+
+        let forward = reader.rotate_pages(&[(1, HUGE)]).unwrap();
+        let reread = PdfReader::from_bytes(&forward).unwrap();
+        assert_eq!(reread.page_rotation(1).unwrap(), 0);
+
+        let backward = reader.rotate_pages(&[(1, -HUGE)]).unwrap();
+        let reread = PdfReader::from_bytes(&backward).unwrap();
+        assert_eq!(reread.page_rotation(1).unwrap(), 180);
+    }
+
+    /// Text written by `PdfWriter` round-trips through `extract_text`.
+    #[test]
+    fn extract_text_round_trips_writer_output() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Hello Presswerk\nSecond line of text",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let reader = PdfReader::from_bytes(&pdf).unwrap();
+
+        let text = reader.extract_text().unwrap();
+        assert!(text.contains("Hello Presswerk"), "got: {text:?}");
+        assert!(text.contains("Second line of text"), "got: {text:?}");
+    }
+
+    /// WinAnsi fallback maps the 0x80 block correctly.
+    #[test]
+    fn decode_win_ansi_high_block() {
+        assert_eq!(win_ansi::decode(b"caf\xe9 \x80 \x93q\x94"), "café € “q”");
+        assert_eq!(
+            win_ansi::encode("café € “q” ✓"),
+            b"caf\xe9 \x80 \x93q\x94 ?"
+        );
+    }
+
+    /// Angles that are not a multiple of 90 are rejected.
+    #[test]
+    fn rotate_pages_rejects_non_right_angles() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Tilted",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let reader = PdfReader::from_bytes(&pdf).unwrap();
+
+        assert!(reader.rotate_pages(&[(1, 45)]).is_err());
+        assert!(reader.rotate_pages(&[(2, 90)]).is_err());
+    }
+
+    /// Key the integrity proof tests seal with.
+    const SEAL_KEY: &[u8] = b"presswerk test sealing key";
+
+    /// An embedded proof verifies, and any change to the page content
+    /// afterwards breaks it.
+    #[test]
+    fn integrity_proof_roundtrip_and_tamper() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Signed and sealed",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let proof = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .integrity_proof(SEAL_KEY);
+
+        let sealed = PdfWriter::embed_integrity_proof(&pdf, &proof).unwrap();
+        let reader = PdfReader::from_bytes(&sealed).unwrap();
+        assert!(reader.verify_integrity_proof(SEAL_KEY).unwrap());
+        assert!(!reader.verify_integrity_proof(b"another key").unwrap());
+        assert!(
+            !PdfReader::from_bytes(&pdf)
+                .unwrap()
+                .verify_integrity_proof(SEAL_KEY)
+                .unwrap()
+        );
+
+        // Flip one byte of the first page's content stream.
+        let mut doc = Document::load_mem(&sealed).unwrap();
+        let page_id = doc.page_iter().next().unwrap();
+        let content_id = doc.get_page_contents(page_id)[0];
+        let stream = doc
+            .get_object_mut(content_id)
+            .unwrap()
+            .as_stream_mut()
+            .unwrap();
+        stream.content[0] ^= 0x01;
+        let mut tampered = Vec::new();
+        doc.save_to(&mut tampered).unwrap();
+
+        let reader = PdfReader::from_bytes(&tampered).unwrap();
+        assert!(!reader.verify_integrity_proof(SEAL_KEY).unwrap());
+
+        // Resealing the edited content without the key does not help.
+        let forged = PdfReader::from_bytes(&tampered)
+            .unwrap()
+            .integrity_proof(b"forger's key");
+        let resealed = PdfWriter::embed_integrity_proof(&tampered, &forged).unwrap();
+        let reader = PdfReader::from_bytes(&resealed).unwrap();
+        assert!(!reader.verify_integrity_proof(SEAL_KEY).unwrap());
+    }
+
+    /// Editing a sealed page's dictionary — its size, rotation or
+    /// annotations — breaks the proof even though no stream changed.
+    #[test]
+    fn integrity_proof_covers_page_dictionaries() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Signed and sealed",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let proof = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .integrity_proof(SEAL_KEY);
+        let sealed = PdfWriter::embed_integrity_proof(&pdf, &proof).unwrap();
+
+        let edits: [(&str, Object); 3] = [
+            (
+                "MediaBox",
+                vec![0.into(), 0.into(), 612.into(), 792.into()].into(),
+            ),
+            ("Rotate", 90.into()),
+            ("Annots", Vec::<Object>::new().into()),
+        ];
+        for (key, value) in edits {
+            let mut doc = Document::load_mem(&sealed).unwrap();
+            let page_id = doc.page_iter().next().unwrap();
+            doc.get_dictionary_mut(page_id).unwrap().set(key, value);
+            let mut tampered = Vec::new();
+            doc.save_to(&mut tampered).unwrap();
+
+            let reader = PdfReader::from_bytes(&tampered).unwrap();
+            assert!(!reader.verify_integrity_proof(SEAL_KEY).unwrap(), "{key}");
+        }
+    }
+
+    /// A proof computed for other content is refused rather than embedded.
+    #[test]
+    fn embed_rejects_stale_proof() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "Current",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let other = PdfWriter::a4()
+            .create_from_text(
+                "Something else",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let stale = PdfReader::from_bytes(&other)
+            .unwrap()
+            .integrity_proof(SEAL_KEY);
+
+        let err = PdfWriter::embed_integrity_proof(&pdf, &stale).unwrap_err();
+        assert!(matches!(err, PresswerkError::IntegrityMismatch { .. }));
+    }
+
+    /// One-page PDF drawing `image` full-page as `/Im1`.
+    fn pdf_with_image(image: lopdf::Stream) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let image_id = doc.add_object(image);
+        let content = lopdf::Stream::new(
+            lopdf::Dictionary::new(),
+            b"q 595 0 0 842 0 0 cm /Im1 Do Q".to_vec(),
+        );
+        let content_id = doc.add_object(content);
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => dictionary! { "XObject" => dictionary! { "Im1" => image_id } },
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut out = Vec::new();
+        doc.save_to(&mut out).unwrap();
+        out
+    }
+
+    fn image_stream(width: i64, height: i64, filter: &str, data: Vec<u8>) -> lopdf::Stream {
+        lopdf::Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => width,
+                "Height" => height,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+                "Filter" => filter,
+            },
+            data,
+        )
+    }
+
+    #[test]
+    fn extract_images_returns_embedded_jpeg() {
+        let source =
+            image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([x as u8 * 4, y as u8 * 5, 90]));
+        let mut jpeg = Vec::new();
+        image::DynamicImage::ImageRgb8(source)
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+        let pdf = pdf_with_image(image_stream(64, 48, "DCTDecode", jpeg.clone()));
+
+        let images = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .extract_images(1)
+            .unwrap();
+        assert_eq!(images.len(), 1);
+        let extracted = &images[0];
+        assert_eq!((extracted.width, extracted.height), (64, 48));
+        assert_eq!(extracted.color_space, ImageColorSpace::Rgb);
+        assert_eq!(extracted.data, ImageData::Jpeg(jpeg));
+
+        let decoded = extracted.to_dynamic().unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 48));
+    }
+
+    #[test]
+    fn extract_images_wraps_ccitt_in_tiff() {
+        // Group 4 encoding of an all-white 8x8 image: one V0 code per row,
+        // then the end-of-block marker.
+        let g4 = vec![0xFF, 0x00, 0x10, 0x01];
+        let mut stream = image_stream(8, 8, "CCITTFaxDecode", g4);
+        stream.dict.set("ColorSpace", "DeviceGray");
+        stream.dict.set("BitsPerComponent", 1);
+        stream.dict.set(
+            "DecodeParms",
+            dictionary! { "K" => -1, "Columns" => 8, "Rows" => 8 },
+        );
+        let pdf = pdf_with_image(stream);
+
+        let images = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .extract_images(1)
+            .unwrap();
+        let ImageData::Tiff(tiff) = &images[0].data else {
+            panic!("expected TIFF, got {:?}", images[0].data);
+        };
+        assert!(tiff.starts_with(b"II*\0"));
+
+        let decoded = images[0].to_dynamic().unwrap().to_luma8();
+        assert_eq!(decoded.dimensions(), (8, 8));
+        assert!(decoded.pixels().all(|p| p.0[0] == 255));
+    }
+
+    #[test]
+    fn extract_images_unpacks_indexed_samples() {
+        // 4x1 two-bit indices 0,1,2,3 into a red/green/blue/white palette.
+        let mut stream = image_stream(4, 1, "FlateDecode", Vec::new());
+        stream.dict.remove(b"Filter");
+        stream.dict.set("BitsPerComponent", 2);
+        stream.dict.set(
+            "ColorSpace",
+            vec![
+                Object::Name(b"Indexed".to_vec()),
+                Object::Name(b"DeviceRGB".to_vec()),
+                3.into(),
+                Object::string_literal(vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]),
+            ],
+        );
+        stream.set_plain_content(vec![0b0001_1011]);
+        stream.compress().unwrap();
+        let pdf = pdf_with_image(stream);
+
+        let images = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .extract_images(1)
+            .unwrap();
+        assert!(matches!(
+            images[0].color_space,
+            ImageColorSpace::Indexed { .. }
+        ));
+        assert_eq!(images[0].data, ImageData::Raw(vec![0b0001_1011]));
+
+        let rgb = images[0].to_dynamic().unwrap().to_rgb8();
+        assert_eq!(rgb.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(rgb.get_pixel(2, 0).0, [0, 0, 255]);
+        assert_eq!(rgb.get_pixel(3, 0).0, [255, 255, 255]);
+    }
+
+    #[test]
+    fn has_color_ignores_grey_text_and_grey_pixels() {
+        let text = PdfWriter::a4()
+            .create_from_text(
+                "Black on white",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        assert!(!PdfReader::from_bytes(&text).unwrap().has_color());
+
+        // Grey stored as RGB, with a little noise, is still grey.
+        let grey: Vec<u8> = (0..16u8).flat_map(|v| [v * 8, v * 8 + 3, v * 8]).collect();
+        let mut stream = image_stream(4, 4, "FlateDecode", Vec::new());
+        stream.dict.remove(b"Filter");
+        stream.set_plain_content(grey);
+        let pdf = pdf_with_image(stream);
+        assert!(!PdfReader::from_bytes(&pdf).unwrap().has_color());
+    }
+
+    #[test]
+    fn has_color_finds_colour_images_and_fills() {
+        let mut red = [200, 200, 200].repeat(16);
+        red[..3].copy_from_slice(&[220, 20, 20]);
+        let mut stream = image_stream(4, 4, "FlateDecode", Vec::new());
+        stream.dict.remove(b"Filter");
+        stream.set_plain_content(red);
+        let pdf = pdf_with_image(stream);
+        assert!(PdfReader::from_bytes(&pdf).unwrap().has_color());
+
+        let colour = |ops: &[u8]| {
+            Content::decode(ops)
+                .unwrap()
+                .operations
+                .iter()
+                .any(|op| is_color_operation(&op.operator, &op.operands))
+        };
+        assert!(colour(b"1 0 0 rg"));
+        assert!(colour(b"0 0.8 0.2 0 K"));
+        assert!(!colour(b"0.5 g 0.3 0.3 0.3 RG 0 0 0 1 k"));
+        assert!(!colour(b"/P1 scn"));
+    }
+
+    #[test]
+    fn extract_images_undoes_run_length_in_front_of_a_codec() {
+        // Two grey pixels as a literal run, then a run of two white ones.
+        let mut stream = image_stream(4, 1, "RunLengthDecode", vec![1, 10, 20, 255, 255, 128]);
+        stream.dict.set("ColorSpace", "DeviceGray");
+        let pdf = pdf_with_image(stream);
+        let images = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .extract_images(1)
+            .unwrap();
+        assert_eq!(images[0].data, ImageData::Raw(vec![10, 20, 255, 255]));
+
+        // RunLength wrapped around a JPEG is unwrapped and the JPEG kept.
+        let mut jpeg = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(8, 8))
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+        let encoded: Vec<u8> = jpeg
+            .chunks(128)
+            .flat_map(|chunk| std::iter::once(chunk.len() as u8 - 1).chain(chunk.iter().copied()))
+            .chain([128])
+            .collect();
+        let mut stream = image_stream(8, 8, "DCTDecode", encoded);
+        stream.dict.set(
+            "Filter",
+            vec![
+                Object::Name(b"RunLengthDecode".to_vec()),
+                Object::Name(b"DCTDecode".to_vec()),
+            ],
+        );
+        let pdf = pdf_with_image(stream);
+        let images = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .extract_images(1)
+            .unwrap();
+        assert_eq!(images[0].data, ImageData::Jpeg(jpeg));
+    }
+
+    #[test]
+    fn extract_images_rejects_jpeg_2000() {
+        let pdf = pdf_with_image(image_stream(4, 4, "JPXDecode", vec![0; 16]));
+        let err = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .extract_images(1)
+            .unwrap_err();
+        assert!(err.to_string().contains("JPEG 2000"), "got: {err}");
+    }
+}
@@ -0,0 +1,309 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Text documents — plain text laid out top to bottom on as many pages as it
+// needs, set in the bundled font.
+//
+// Built directly with lopdf rather than printpdf so the text goes through the
+// same font embedding as the overlays (see `font`): one font program, subset
+// to the glyphs used, with a `/ToUnicode` map for search and copy-paste.
+
+use lopdf::content::{Content, Operation};
+use lopdf::{Document, Object, ObjectId, dictionary};
+use presswerk_core::PaperSize;
+use presswerk_core::error::PresswerkError;
+use tracing::debug;
+
+use super::font::FontSubset;
+use super::overlay;
+
+/// Resource name of the text font.
+const TEXT_FONT: &str = "PwText";
+
+/// Usual font size of body text, in points.
+pub(crate) const FONT_SIZE: f32 = 11.0;
+
+/// Font size range accepted, in points.
+const FONT_SIZE_RANGE: std::ops::RangeInclusive<f32> = 4.0..=72.0;
+
+/// Distance between baselines as a multiple of the font size (14 pt lines
+/// for 11 pt text).
+const LINE_SPACING: f32 = 14.0 / 11.0;
+
+/// Usual page margin on every side, in millimetres.
+pub(crate) const MARGIN_MM: f32 = 20.0;
+
+/// Columns between tab stops.
+const TAB_WIDTH: usize = 4;
+
+/// Lay `text` out on `paper_size` pages in `font_size` point type within
+/// `margin_mm` of the edges, and serialise the document.
+///
+/// The font size is clamped to 4-72 pt and the margin to a third of the
+/// paper's shorter side, so there is always room for some text.
+pub(crate) fn create(
+    paper_size: PaperSize,
+    title: &str,
+    text: &str,
+    font_size: f32,
+    margin_mm: f32,
+) -> Result<Vec<u8>, PresswerkError> {
+    let (w_mm, h_mm) = paper_size.dimensions_mm();
+    let (page_w, page_h) = (mm_to_pt(w_mm), mm_to_pt(h_mm));
+    let font_size = if font_size.is_finite() {
+        font_size.clamp(*FONT_SIZE_RANGE.start(), *FONT_SIZE_RANGE.end())
+    } else {
+        FONT_SIZE
+    };
+    let margin_mm = if margin_mm.is_finite() {
+        margin_mm.clamp(0.0, w_mm.min(h_mm) / 3.0)
+    } else {
+        MARGIN_MM
+    };
+    let margin = mm_to_pt(margin_mm);
+    let line_height = font_size * LINE_SPACING;
+
+    let mut font = FontSubset::new()?;
+
+    // The font is monospaced, so a line holds a fixed number of characters.
+    let char_width = font.font().text_width(" ", font_size);
+    let max_chars = (((page_w - 2.0 * margin) / char_width) as usize).max(1);
+    let lines_per_page = (((page_h - 2.0 * margin) / line_height) as usize).max(1);
+
+    let lines = wrap_text(&expand_tabs(text), max_chars);
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let resources_id = doc.new_object_id();
+
+    let mut kids: Vec<Object> = Vec::new();
+    for page_lines in lines.chunks(lines_per_page) {
+        let mut operations = vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec![Object::Name(TEXT_FONT.into()), font_size.into()]),
+            Operation::new("TL", vec![line_height.into()]),
+            // The first baseline sits one font size below the top margin.
+            Operation::new(
+                "Td",
+                vec![margin.into(), (page_h - margin - font_size).into()],
+            ),
+        ];
+        for line in page_lines {
+            if !line.is_empty() {
+                operations.extend(font.show(line));
+            }
+            operations.push(Operation::new("T*", vec![]));
+        }
+        operations.push(Operation::new("ET", vec![]));
+
+        kids.push(add_page(&mut doc, pages_id, resources_id, Content { operations })?.into());
+    }
+
+    // No text at all still makes a (blank) page.
+    if kids.is_empty() {
+        let blank = Content { operations: vec![] };
+        kids.push(add_page(&mut doc, pages_id, resources_id, blank)?.into());
+    }
+
+    // Embedded last, once every line has been encoded.
+    let font_id = font.embed(&mut doc);
+    doc.objects.insert(
+        resources_id,
+        Object::Dictionary(dictionary! {
+            "Font" => dictionary! { TEXT_FONT => font_id },
+        }),
+    );
+
+    let page_count = kids.len();
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => page_count as i64,
+            "MediaBox" => vec![0.into(), 0.into(), page_w.into(), page_h.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    let info_id = doc.add_object(dictionary! {
+        "Title" => lopdf::text_string(title),
+        "Producer" => Object::string_literal("Presswerk"),
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.trailer.set("Info", info_id);
+
+    debug!(
+        lines = lines.len(),
+        pages = page_count,
+        font_size,
+        margin_mm,
+        "Text layout complete"
+    );
+
+    doc.compress();
+    overlay::save(&mut doc)
+}
+
+/// Add a page showing `content` under `pages_id`.
+fn add_page(
+    doc: &mut Document,
+    pages_id: ObjectId,
+    resources_id: ObjectId,
+    content: Content<Vec<Operation>>,
+) -> Result<ObjectId, PresswerkError> {
+    let bytes = content
+        .encode()
+        .map_err(|err| PresswerkError::PdfError(format!("failed to encode page: {}", err)))?;
+    let content_id = doc.add_object(lopdf::Stream::new(dictionary! {}, bytes));
+    Ok(doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "Resources" => resources_id,
+    }))
+}
+
+fn mm_to_pt(mm: f32) -> f32 {
+    mm * 72.0 / 25.4
+}
+
+/// Replace tabs with spaces up to the next tab stop and drop carriage
+/// returns, which would otherwise show as boxes.
+fn expand_tabs(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut column = 0;
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                out.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            '\r' => {}
+            '\n' => {
+                out.push(c);
+                column = 0;
+            }
+            _ => {
+                out.push(c);
+                column += 1;
+            }
+        }
+    }
+    out
+}
+
+// -- Text wrapping helper -----------------------------------------------------
+
+/// Wrap a multi-line string so that no line exceeds `max_width` characters.
+///
+/// Splits on existing newlines first, then performs simple word-wrap within each
+/// paragraph. Words longer than `max_width` are force-broken.
+fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
+    let mut result = Vec::new();
+
+    for paragraph in text.split('\n') {
+        if paragraph.is_empty() {
+            result.push(String::new());
+            continue;
+        }
+
+        let words: Vec<&str> = paragraph.split_whitespace().collect();
+        if words.is_empty() {
+            result.push(String::new());
+            continue;
+        }
+
+        let mut current_line = String::with_capacity(max_width);
+        let mut current_len = 0;
+
+        for word in words {
+            let word_len = word.chars().count();
+            if word_len > max_width {
+                // Flush any accumulated line.
+                if !current_line.is_empty() {
+                    result.push(std::mem::take(&mut current_line));
+                }
+                // Force-break the oversized word.
+                let chars: Vec<char> = word.chars().collect();
+                let mut chunks = chars.chunks(max_width).peekable();
+                while let Some(chunk) = chunks.next() {
+                    if chunks.peek().is_some() {
+                        result.push(chunk.iter().collect());
+                    } else {
+                        current_line = chunk.iter().collect();
+                        current_len = chunk.len();
+                    }
+                }
+            } else if current_line.is_empty() {
+                current_line.push_str(word);
+                current_len = word_len;
+            } else if current_len + 1 + word_len <= max_width {
+                current_line.push(' ');
+                current_line.push_str(word);
+                current_len += 1 + word_len;
+            } else {
+                result.push(std::mem::take(&mut current_line));
+                current_line.push_str(word);
+                current_len = word_len;
+            }
+        }
+
+        if !current_line.is_empty() {
+            result.push(current_line);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::reader::PdfReader;
+
+    #[test]
+    fn text_pdf_embeds_the_font_and_round_trips() {
+        let text = "Grüße aus Köln — 10 €\nΚαλημέρα, Привет";
+        let pdf = create(PaperSize::A4, "Prüfung", text, FONT_SIZE, MARGIN_MM).unwrap();
+
+        let doc = Document::load_mem(&pdf).unwrap();
+        let font_files = doc
+            .objects
+            .values()
+            .filter_map(|o| o.as_dict().ok())
+            .filter(|d| d.has(b"FontFile2"))
+            .count();
+        assert_eq!(font_files, 1);
+        assert!(
+            !doc.objects
+                .values()
+                .filter_map(|o| o.as_dict().ok())
+                .any(|d| d.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type1"))
+        );
+
+        let extracted = PdfReader::from_bytes(&pdf).unwrap().extract_text().unwrap();
+        assert_eq!(extracted, text);
+    }
+
+    /// Characters outside the bundled font print as boxes but still come
+    /// back out of the text layer as written.
+    #[test]
+    fn text_beyond_the_font_round_trips_through_actual_text() {
+        let text = "Crème brûlée für 5 €\n東京タワー ✓ done\nΑθήνα 🙂";
+        let pdf = create(PaperSize::A4, "Unicode", text, FONT_SIZE, MARGIN_MM).unwrap();
+
+        let extracted = PdfReader::from_bytes(&pdf).unwrap().extract_text().unwrap();
+        assert_eq!(extracted, text);
+    }
+
+    #[test]
+    fn wrapping_counts_characters_not_bytes() {
+        assert_eq!(wrap_text("äöü äöü", 3), ["äöü", "äöü"]);
+        assert_eq!(wrap_text("ααααα", 2), ["αα", "αα", "α"]);
+        assert_eq!(expand_tabs("a\tb\r\n\tc"), "a   b\n    c");
+    }
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// WinAnsiEncoding (Windows-1252) codec for the standard 14 PDF fonts.
+
+/// Code points for bytes 0x80..=0x9F, where Windows-1252 differs from
+/// Latin-1. Undefined slots map to U+FFFD.
+const HIGH_BLOCK: [char; 32] = [
+    '€', '\u{FFFD}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{FFFD}', 'Ž',
+    '\u{FFFD}', '\u{FFFD}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{FFFD}',
+    'ž', 'Ÿ',
+];
+
+/// Decode WinAnsi bytes into a `String`.
+pub(crate) fn decode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => HIGH_BLOCK[(b - 0x80) as usize],
+            _ => b as char,
+        })
+        .collect()
+}
+
+/// Encode text as WinAnsi bytes. Characters outside the code page become `?`.
+///
+/// Generated text uses the bundled font now; only the tests still need this.
+#[cfg(test)]
+pub(crate) fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x00..=0x7F | 0xA0..=0xFF => c as u8,
+            _ => HIGH_BLOCK
+                .iter()
+                .position(|&h| h == c && h != '\u{FFFD}')
+                .map_or(b'?', |i| 0x80 + i as u8),
+        })
+        .collect()
+}
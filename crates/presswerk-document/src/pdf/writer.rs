@@ -5,18 +5,50 @@
 //
 // printpdf 0.8 uses a data-oriented API: documents are built by constructing
 // `PdfPage` structs containing `Vec<Op>` operation lists, then serialised via
-// `PdfDocument::save()`.
+// `PdfDocument::save()`.  Text documents are the exception: they are laid out
+// by `text` with lopdf, in the bundled font shared with the overlays.
 
+use std::collections::BTreeMap;
 use std::path::Path;
 
-use presswerk_core::PaperSize;
 use presswerk_core::error::PresswerkError;
+use presswerk_core::{Orientation, PaperSize};
+use presswerk_security::IntegrityProof;
 use printpdf::{
-    BuiltinFont, Mm, Op, PdfDocument, PdfPage, PdfSaveOptions, PdfWarnMsg, Point, Pt, RawImage,
-    RawImageData, RawImageFormat, TextItem, XObjectTransform,
+    CurTransMat, DictItem, ExternalStream, ExternalXObject, Mm, Op, PaintMode, PdfDocument,
+    PdfPage, PdfSaveOptions, PdfWarnMsg, Pt, Px, RawImage, RawImageData, RawImageFormat, Rect,
+    XObjectId, XObjectTransform,
 };
 use tracing::{debug, info, instrument};
 
+use crate::image::processor::{ImageProcessor, decode_upright};
+
+use super::outline::OutlineEntry;
+use super::overlay::FooterPosition;
+use super::{compress, integrity, outline, overlay, text};
+
+/// Resolution assumed for images of unknown DPI (reasonable for print).
+const DEFAULT_IMAGE_DPI: u32 = 150;
+
+/// Blank border kept around images, clear of most printers' unprintable
+/// edge.
+const IMAGE_MARGIN_MM: f32 = 15.0;
+
+/// How an image is sized within the margins of its page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fit {
+    /// The whole image, aspect-correct: at its physical size if that fits
+    /// within the margins, scaled down until it does otherwise.
+    #[default]
+    Contain,
+    /// The area within the margins filled, aspect-correct; whatever
+    /// overflows it is cropped.
+    Cover,
+    /// The area within the margins filled exactly, ignoring the aspect
+    /// ratio.
+    Stretch,
+}
+
 /// Creates new PDF documents from text content or raster images.
 ///
 /// Uses `printpdf` 0.8 for generation, producing standards-compliant PDF output
@@ -26,14 +58,29 @@ pub struct PdfWriter {
     paper_size: PaperSize,
     /// Title metadata embedded in the PDF /Info dictionary.
     title: Option<String>,
+    /// Resolution images are placed at; `None` assumes
+    /// [`DEFAULT_IMAGE_DPI`].
+    image_dpi: Option<u32>,
+    /// Bookmarks added to every document created.
+    outline: Vec<OutlineEntry>,
 }
 
 impl PdfWriter {
+    /// Usual font size for [`create_from_text`](Self::create_from_text), in
+    /// points.
+    pub const TEXT_FONT_SIZE: f32 = text::FONT_SIZE;
+
+    /// Usual page margin for [`create_from_text`](Self::create_from_text),
+    /// in millimetres.
+    pub const TEXT_MARGIN_MM: f32 = text::MARGIN_MM;
+
     /// Create a new writer targeting the given paper size.
     pub fn new(paper_size: PaperSize) -> Self {
         Self {
             paper_size,
             title: None,
+            image_dpi: None,
+            outline: Vec::new(),
         }
     }
 
@@ -52,125 +99,99 @@ pub fn set_title(&mut self, title: impl Into<String>) {
         self.title = Some(title.into());
     }
 
+    /// Set the resolution images are placed at, so a 300 DPI scan of an A4
+    /// page comes out A4-sized rather than at the default 150 DPI.
+    pub fn set_image_dpi(&mut self, dpi: u32) {
+        self.image_dpi = Some(dpi.max(1));
+    }
+
+    /// Give created documents an outline (bookmarks) with `entries`, in
+    /// order.  Entries must have a title and a page from 1; a page the
+    /// document turns out not to have fails the create call.  An empty
+    /// slice removes the outline.
+    pub fn set_outline(&mut self, entries: &[OutlineEntry]) -> Result<(), PresswerkError> {
+        outline::validate(entries)?;
+        self.outline = entries.to_vec();
+        Ok(())
+    }
+
+    /// Add the configured outline, if any, to a freshly created document.
+    fn finish(&self, pdf: Vec<u8>) -> Result<Vec<u8>, PresswerkError> {
+        if self.outline.is_empty() {
+            return Ok(pdf);
+        }
+        outline::add_outline(&pdf, &self.outline)
+    }
+
     /// Paper dimensions in printpdf's Mm units.
     fn page_dimensions(&self) -> (Mm, Mm) {
         let (w_mm, h_mm) = self.paper_size.dimensions_mm();
-        (Mm(w_mm as f32), Mm(h_mm as f32))
+        (Mm(w_mm), Mm(h_mm))
+    }
+
+    /// Image resolution in force, for its physical size.
+    fn image_dpi(&self) -> f32 {
+        self.image_dpi.unwrap_or(DEFAULT_IMAGE_DPI) as f32
     }
 
     // -- Text to PDF ----------------------------------------------------------
 
     /// Create a PDF from plain text content.
     ///
-    /// The text is laid out in a simple top-to-bottom flow using the built-in
-    /// Helvetica font. Long lines are wrapped at an estimated character width
-    /// and pages break automatically.
+    /// The text is laid out top to bottom in the bundled DejaVu Sans Mono
+    /// subset, which is embedded with just the glyphs used, so accented
+    /// Latin, Greek and Cyrillic print and copy correctly without relying on
+    /// the printer's fonts. Long lines are word-wrapped, tabs expand to
+    /// four-column stops and pages break automatically.
+    ///
+    /// `font_size` is in points (4-72) and `margins_mm` applies to every
+    /// side; [`TEXT_FONT_SIZE`](Self::TEXT_FONT_SIZE) and
+    /// [`TEXT_MARGIN_MM`](Self::TEXT_MARGIN_MM) suit most documents.
     #[instrument(skip(self, text), fields(text_len = text.len()))]
-    pub fn create_from_text(&self, text: &str) -> Result<Vec<u8>, PresswerkError> {
-        let (page_w, page_h) = self.page_dimensions();
+    pub fn create_from_text(
+        &self,
+        text: &str,
+        font_size: f32,
+        margins_mm: f32,
+    ) -> Result<Vec<u8>, PresswerkError> {
         let title = self.title.as_deref().unwrap_or("Presswerk Document");
 
         info!(
             paper = ?self.paper_size,
             title,
+            font_size,
+            margins_mm,
             "Creating text PDF"
         );
 
-        let font_size_pt: f32 = 11.0;
-        let line_height_pt: f32 = 14.0;
-        let margin_mm: f32 = 20.0;
-        let margin_pt: f32 = Mm(margin_mm).into_pt().0;
-        let usable_width_mm = page_w.0 - 2.0 * margin_mm;
-
-        // Approximate characters per line based on Helvetica at 11pt.
-        // Average Helvetica glyph width is roughly 0.50 * font_size in pt,
-        // converted to mm (1pt = 0.3528mm).
-        let avg_char_width_mm: f32 = 0.50 * font_size_pt * 0.3528;
-        let max_chars_per_line = (usable_width_mm / avg_char_width_mm) as usize;
-
-        let wrapped_lines = wrap_text(text, max_chars_per_line);
-        let page_h_pt = page_h.into_pt().0;
-        let usable_height_pt = page_h_pt - 2.0 * margin_pt;
-        let lines_per_page = (usable_height_pt / line_height_pt) as usize;
-
-        let mut doc = PdfDocument::new(title);
-        let mut pages: Vec<PdfPage> = Vec::new();
-
-        // Process lines in chunks of `lines_per_page`.
-        let mut line_iter = wrapped_lines.iter().peekable();
-        while line_iter.peek().is_some() {
-            let mut ops: Vec<Op> = Vec::new();
-
-            // Collect up to `lines_per_page` lines for this page.
-            let mut line_idx: usize = 0;
-            while line_idx < lines_per_page {
-                let line = match line_iter.next() {
-                    Some(l) => l,
-                    None => break,
-                };
-
-                // Position: top-left of the page, moving downward.
-                let y_pt = page_h_pt - margin_pt - (line_idx as f32 * line_height_pt);
-
-                ops.push(Op::StartTextSection);
-                ops.push(Op::SetTextCursor {
-                    pos: Point {
-                        x: Pt(margin_pt),
-                        y: Pt(y_pt),
-                    },
-                });
-                ops.push(Op::SetFontSizeBuiltinFont {
-                    size: Pt(font_size_pt),
-                    font: BuiltinFont::Helvetica,
-                });
-                ops.push(Op::WriteTextBuiltinFont {
-                    items: vec![TextItem::Text(line.clone())],
-                    font: BuiltinFont::Helvetica,
-                });
-                ops.push(Op::EndTextSection);
-
-                line_idx += 1;
-            }
-
-            pages.push(PdfPage::new(page_w, page_h, ops));
-        }
-
-        // If there were no lines at all, add a single blank page.
-        if pages.is_empty() {
-            pages.push(PdfPage::new(page_w, page_h, Vec::new()));
-        }
-
-        doc.with_pages(pages);
-
-        debug!(
-            total_lines = wrapped_lines.len(),
-            pages = doc.pages.len(),
-            "Text layout complete"
-        );
-
-        let mut warnings: Vec<PdfWarnMsg> = Vec::new();
-        let output = doc.save(&PdfSaveOptions::default(), &mut warnings);
-
-        Ok(output)
+        let pdf = text::create(self.paper_size, title, text, font_size, margins_mm)?;
+        self.finish(pdf)
     }
 
     // -- Image to PDF ---------------------------------------------------------
 
     /// Create a single-page PDF containing the given image.
     ///
-    /// The image is scaled to fit within the page margins while preserving its
-    /// aspect ratio.
+    /// The page is the writer's paper turned to `orientation`; a reversed
+    /// orientation turns the image upside down on it.  `fit` sizes the
+    /// image within the page margins, centred — with [`Fit::Contain`] at
+    /// its physical size for the writer's image DPI (see
+    /// [`set_image_dpi`](Self::set_image_dpi)) unless that is too big.  A
+    /// landscape photo on portrait A4 thus fills the width between the
+    /// margins, with space above and below.
     #[instrument(skip(self, image_bytes), fields(bytes_len = image_bytes.len()))]
-    pub fn create_from_image(&self, image_bytes: &[u8]) -> Result<Vec<u8>, PresswerkError> {
-        let (page_w, page_h) = self.page_dimensions();
+    pub fn create_from_image(
+        &self,
+        image_bytes: &[u8],
+        orientation: Orientation,
+        fit: Fit,
+    ) -> Result<Vec<u8>, PresswerkError> {
         let title = self.title.as_deref().unwrap_or("Presswerk Image");
 
         info!(paper = ?self.paper_size, title, "Creating image PDF");
 
         // Decode the image to get its dimensions and pixel data.
-        let dynamic_image = ::image::load_from_memory(image_bytes).map_err(|err| {
-            PresswerkError::ImageError(format!("failed to decode image for PDF: {}", err))
-        })?;
+        let dynamic_image = decode_upright(image_bytes)?;
 
         let img_width = dynamic_image.width() as usize;
         let img_height = dynamic_image.height() as usize;
@@ -188,61 +209,213 @@ pub fn create_from_image(&self, image_bytes: &[u8]) -> Result<Vec<u8>, Presswerk
         let mut doc = PdfDocument::new(title);
         let xobject_id = doc.add_image(&raw);
 
-        // Compute transform to place the image on the page with margins.
-        let margin_mm: f32 = 15.0;
-        let usable_w_pt = Mm(page_w.0 - 2.0 * margin_mm).into_pt().0;
-        let usable_h_pt = Mm(page_h.0 - 2.0 * margin_mm).into_pt().0;
+        self.save_single_image_page(doc, xobject_id, img_width, img_height, orientation, fit)
+    }
+
+    /// Create a single-page PDF embedding JPEG data as-is (`/DCTDecode`).
+    ///
+    /// Unlike [`create_from_image`](Self::create_from_image) the JPEG is not
+    /// decoded and re-compressed, so photos keep their original size and
+    /// quality — unless they carry an EXIF rotation, which is applied first
+    /// (see [`ImageProcessor::auto_orient`]). Only greyscale and RGB
+    /// baseline/progressive JPEGs are accepted; CMYK JPEGs are rejected.
+    /// `orientation` and `fit` place it as for `create_from_image`.
+    #[instrument(skip(self, jpeg_bytes), fields(bytes_len = jpeg_bytes.len()))]
+    pub fn create_from_jpeg(
+        &self,
+        jpeg_bytes: &[u8],
+        orientation: Orientation,
+        fit: Fit,
+    ) -> Result<Vec<u8>, PresswerkError> {
+        let title = self.title.as_deref().unwrap_or("Presswerk Image");
+        info!(paper = ?self.paper_size, title, "Creating JPEG PDF");
 
-        // Image native size at a default DPI of 150 (reasonable for print).
-        let dpi: f32 = 150.0;
-        let img_w_pt = img_width as f32 / dpi * 72.0;
-        let img_h_pt = img_height as f32 / dpi * 72.0;
+        // The PDF viewer knows nothing of EXIF, so a rotated photo has to be
+        // turned upright before embedding.
+        let jpeg_bytes = &ImageProcessor::auto_orient(jpeg_bytes)?;
+        let header = jpeg_header(jpeg_bytes).ok_or_else(|| {
+            PresswerkError::ImageError("not a valid JPEG (no frame header found)".into())
+        })?;
+        let color_space = match header.components {
+            1 => "DeviceGray",
+            3 => "DeviceRGB",
+            n => {
+                return Err(PresswerkError::ImageError(format!(
+                    "JPEG with {} colour components cannot be embedded directly",
+                    n
+                )));
+            }
+        };
 
-        // Scale to fit while preserving aspect ratio; do not upscale.
-        let scale_x = usable_w_pt / img_w_pt;
-        let scale_y = usable_h_pt / img_h_pt;
-        let scale = scale_x.min(scale_y).min(1.0);
+        let name = |value: &str| DictItem::Name(value.as_bytes().to_vec());
+        let dict = BTreeMap::from([
+            ("Type".to_string(), name("XObject")),
+            ("Subtype".to_string(), name("Image")),
+            ("Width".to_string(), DictItem::Int(header.width.into())),
+            ("Height".to_string(), DictItem::Int(header.height.into())),
+            ("ColorSpace".to_string(), name(color_space)),
+            ("BitsPerComponent".to_string(), DictItem::Int(8)),
+            ("Filter".to_string(), name("DCTDecode")),
+        ]);
+        let xobject = ExternalXObject {
+            stream: ExternalStream {
+                dict,
+                content: jpeg_bytes.to_vec(),
+                // Already compressed; Flate on top would only cost time.
+                compress: false,
+            },
+            width: Some(Px(header.width as usize)),
+            height: Some(Px(header.height as usize)),
+            dpi: None,
+        };
 
-        let rendered_w_pt = img_w_pt * scale;
-        let rendered_h_pt = img_h_pt * scale;
+        let mut doc = PdfDocument::new(title);
+        let xobject_id = doc.add_xobject(&xobject);
+
+        self.save_single_image_page(
+            doc,
+            xobject_id,
+            header.width as usize,
+            header.height as usize,
+            orientation,
+            fit,
+        )
+    }
 
-        // Centre the image on the page.
-        let margin_pt = Mm(margin_mm).into_pt().0;
-        let x_offset = margin_pt + (usable_w_pt - rendered_w_pt) / 2.0;
-        let y_offset = margin_pt + (usable_h_pt - rendered_h_pt) / 2.0;
+    /// Place an `img_width`×`img_height` image XObject on a single page as
+    /// [`place_image`] says, and serialise `doc`.
+    fn save_single_image_page(
+        &self,
+        mut doc: PdfDocument,
+        xobject_id: XObjectId,
+        img_width: usize,
+        img_height: usize,
+        orientation: Orientation,
+        fit: Fit,
+    ) -> Result<Vec<u8>, PresswerkError> {
+        let (paper_w, paper_h) = self.page_dimensions();
+        let dpi = self.image_dpi();
+        let placement = place_image(
+            (paper_w.into_pt().0, paper_h.into_pt().0),
+            (img_width as f32, img_height as f32),
+            dpi,
+            orientation,
+            fit,
+        );
 
-        let ops = vec![Op::UseXobject {
+        let mut ops = vec![Op::SaveGraphicsState];
+        if let Some(clip) = placement.clip {
+            let mut polygon = clip.to_polygon();
+            polygon.mode = PaintMode::Clip;
+            ops.push(Op::DrawPolygon { polygon });
+        }
+        // printpdf draws the image `img_width`×`img_height` points at
+        // 72 DPI, so the pixel scale is taken back out of the placement.
+        let [a, b, c, d, e, f] = placement.matrix;
+        let (w_px, h_px) = (img_width as f32, img_height as f32);
+        ops.push(Op::SetTransformationMatrix {
+            matrix: CurTransMat::Raw([a / w_px, b / w_px, c / h_px, d / h_px, e, f]),
+        });
+        ops.push(Op::UseXobject {
             id: xobject_id,
             transform: XObjectTransform {
-                translate_x: Some(Pt(x_offset)),
-                translate_y: Some(Pt(y_offset)),
-                scale_x: Some(scale),
-                scale_y: Some(scale),
-                dpi: Some(dpi),
-                rotate: None,
+                dpi: Some(72.0),
+                ..Default::default()
             },
-        }];
+        });
+        ops.push(Op::RestoreGraphicsState);
 
-        let page = PdfPage::new(page_w, page_h, ops);
+        let (page_w, page_h) = placement.page;
+        let page = PdfPage::new(Pt(page_w).into(), Pt(page_h).into(), ops);
         doc.with_pages(vec![page]);
 
-        debug!(rendered_w_pt, rendered_h_pt, scale, "Image placed on page");
+        debug!(?orientation, ?fit, matrix = ?placement.matrix, "Image placed on page");
 
         let mut warnings: Vec<PdfWarnMsg> = Vec::new();
         let output = doc.save(&PdfSaveOptions::default(), &mut warnings);
 
-        Ok(output)
+        self.finish(output)
+    }
+
+    // -- Post-processing ------------------------------------------------------
+
+    /// Overlay semi-transparent diagonal text (e.g. "DRAFT", "CONFIDENTIAL")
+    /// centred on every page of an existing PDF.
+    ///
+    /// `opacity` ranges from 0.0 (invisible) to 1.0 (solid); `angle_deg`
+    /// rotates the text anti-clockwise, 45 being the classic diagonal. The
+    /// original page content is left intact — the stamp is drawn on top in a
+    /// separate content stream.
+    pub fn add_text_watermark(
+        pdf: &[u8],
+        text: &str,
+        opacity: f32,
+        angle_deg: f32,
+    ) -> Result<Vec<u8>, PresswerkError> {
+        overlay::add_text_watermark(pdf, text, opacity, angle_deg)
+    }
+
+    /// Add "Page X of Y" footers to every page of an existing PDF.
+    ///
+    /// `position` selects the bottom-left corner, centre or bottom-right
+    /// corner; `start_at` is the number printed on the first page (normally
+    /// 1). The numbers are drawn in small black Helvetica on top of the
+    /// original content.
+    pub fn add_page_numbers(
+        pdf: &[u8],
+        position: FooterPosition,
+        start_at: u32,
+    ) -> Result<Vec<u8>, PresswerkError> {
+        overlay::add_page_numbers(pdf, position, start_at)
+    }
+
+    /// Replace the outline (bookmarks) of an existing PDF with `entries`,
+    /// e.g. after merging scanned pages into one document.
+    ///
+    /// See [`outline_from_headings`](super::outline_from_headings) for
+    /// entries guessed from OCR text.
+    pub fn add_outline(pdf: &[u8], entries: &[OutlineEntry]) -> Result<Vec<u8>, PresswerkError> {
+        outline::add_outline(pdf, entries)
+    }
+
+    /// Shrink a PDF by downsampling embedded images above `target_dpi` and
+    /// re-encoding them as JPEG at `jpeg_quality` (1-100).
+    ///
+    /// Page count, page sizes and layout are unchanged; only image data is
+    /// rewritten. Before/after sizes are logged at `info` level. Typical
+    /// values for scans are 150-200 DPI at quality 75-85.
+    pub fn compress(
+        pdf: &[u8],
+        target_dpi: u32,
+        jpeg_quality: u8,
+    ) -> Result<Vec<u8>, PresswerkError> {
+        compress::compress(pdf, target_dpi, jpeg_quality)
+    }
+
+    /// Record `proof` in the document Info dictionary as `/PresswerkHash`
+    /// and `/PresswerkSignature`.
+    ///
+    /// Obtain the proof from [`PdfReader::integrity_proof`](super::PdfReader::integrity_proof)
+    /// on the same bytes; a proof for different content is rejected with
+    /// `IntegrityMismatch`. Check it later with
+    /// [`PdfReader::verify_integrity_proof`](super::PdfReader::verify_integrity_proof).
+    pub fn embed_integrity_proof(
+        pdf: &[u8],
+        proof: &IntegrityProof,
+    ) -> Result<Vec<u8>, PresswerkError> {
+        integrity::embed(pdf, proof)
     }
 
     // -- File output convenience ----------------------------------------------
 
-    /// Create a text PDF and write it directly to a file.
+    /// Create a text PDF with the usual font size and margins and write it
+    /// directly to a file.
     pub fn write_text_to_file(
         &self,
         text: &str,
         path: impl AsRef<Path>,
     ) -> Result<(), PresswerkError> {
-        let bytes = self.create_from_text(text)?;
+        let bytes = self.create_from_text(text, Self::TEXT_FONT_SIZE, Self::TEXT_MARGIN_MM)?;
         std::fs::write(path.as_ref(), &bytes)?;
         info!("Wrote text PDF to {}", path.as_ref().display());
         Ok(())
@@ -252,71 +425,364 @@ pub fn write_text_to_file(
     pub fn write_image_to_file(
         &self,
         image_bytes: &[u8],
+        orientation: Orientation,
+        fit: Fit,
         path: impl AsRef<Path>,
     ) -> Result<(), PresswerkError> {
-        let bytes = self.create_from_image(image_bytes)?;
+        let bytes = self.create_from_image(image_bytes, orientation, fit)?;
         std::fs::write(path.as_ref(), &bytes)?;
         info!("Wrote image PDF to {}", path.as_ref().display());
         Ok(())
     }
 }
 
-// -- Text wrapping helper -----------------------------------------------------
+// -- Image placement ----------------------------------------------------------
+
+/// Where an image goes on its page, in points.
+#[derive(Debug, Clone, PartialEq)]
+struct ImagePlacement {
+    /// Page width and height, as the page's media box.
+    page: (f32, f32),
+    /// Maps the image's unit square onto the page, as in a PDF `cm`
+    /// operator: `[a b c d e f]`.
+    matrix: [f32; 6],
+    /// Area the image is cropped to, for [`Fit::Cover`].
+    clip: Option<Rect>,
+}
 
-/// Wrap a multi-line string so that no line exceeds `max_width` characters.
-///
-/// Splits on existing newlines first, then performs simple word-wrap within each
-/// paragraph. Words longer than `max_width` are force-broken.
-fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
-    let mut result = Vec::new();
-
-    for paragraph in text.split('\n') {
-        if paragraph.is_empty() {
-            result.push(String::new());
-            continue;
+/// Lay a `pixels` (width, height) image at `dpi` out on `paper` (width,
+/// height, in points) turned to `orientation`, centred within the margins
+/// and sized as `fit` says.
+fn place_image(
+    paper: (f32, f32),
+    pixels: (f32, f32),
+    dpi: f32,
+    orientation: Orientation,
+    fit: Fit,
+) -> ImagePlacement {
+    let (short, long) = (paper.0.min(paper.1), paper.0.max(paper.1));
+    let (page_w, page_h) = match orientation {
+        Orientation::Portrait | Orientation::ReversePortrait => (short, long),
+        Orientation::Landscape | Orientation::ReverseLandscape => (long, short),
+    };
+
+    let margin = Mm(IMAGE_MARGIN_MM).into_pt().0;
+    let area_w = (page_w - 2.0 * margin).max(1.0);
+    let area_h = (page_h - 2.0 * margin).max(1.0);
+    let img_w = pixels.0 / dpi * 72.0;
+    let img_h = pixels.1 / dpi * 72.0;
+
+    let (drawn_w, drawn_h) = match fit {
+        Fit::Contain => {
+            let scale = (area_w / img_w).min(area_h / img_h).min(1.0);
+            (img_w * scale, img_h * scale)
+        }
+        Fit::Cover => {
+            let scale = (area_w / img_w).max(area_h / img_h);
+            (img_w * scale, img_h * scale)
         }
+        Fit::Stretch => (area_w, area_h),
+    };
+    let x = (page_w - drawn_w) / 2.0;
+    let y = (page_h - drawn_h) / 2.0;
+
+    let matrix = match orientation {
+        Orientation::Portrait | Orientation::Landscape => [drawn_w, 0.0, 0.0, drawn_h, x, y],
+        // Turned half a turn about its centre.
+        Orientation::ReversePortrait | Orientation::ReverseLandscape => {
+            [-drawn_w, 0.0, 0.0, -drawn_h, x + drawn_w, y + drawn_h]
+        }
+    };
+    let clip = (fit == Fit::Cover).then_some(Rect {
+        x: Pt(margin),
+        y: Pt(margin),
+        width: Pt(area_w),
+        height: Pt(area_h),
+    });
+
+    ImagePlacement {
+        page: (page_w, page_h),
+        matrix,
+        clip,
+    }
+}
+
+// -- JPEG header helper -------------------------------------------------------
 
-        let words: Vec<&str> = paragraph.split_whitespace().collect();
-        if words.is_empty() {
-            result.push(String::new());
+/// Frame dimensions and component count read from a JPEG's SOF marker.
+struct JpegHeader {
+    width: u16,
+    height: u16,
+    components: u8,
+}
+
+/// Scan the JPEG marker segments for the first start-of-frame header.
+fn jpeg_header(data: &[u8]) -> Option<JpegHeader> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // Fill bytes before a marker.
+        if marker == 0xFF {
+            pos += 1;
             continue;
         }
-
-        let mut current_line = String::with_capacity(max_width);
-
-        for word in words {
-            if word.len() > max_width {
-                // Flush any accumulated line.
-                if !current_line.is_empty() {
-                    result.push(current_line.clone());
-                    current_line.clear();
-                }
-                // Force-break the oversized word.
-                let mut remaining = word;
-                while remaining.len() > max_width {
-                    let (chunk, rest) = remaining.split_at(max_width);
-                    result.push(chunk.to_string());
-                    remaining = rest;
-                }
-                if !remaining.is_empty() {
-                    current_line.push_str(remaining);
-                }
-            } else if current_line.is_empty() {
-                current_line.push_str(word);
-            } else if current_line.len() + 1 + word.len() <= max_width {
-                current_line.push(' ');
-                current_line.push_str(word);
-            } else {
-                result.push(current_line.clone());
-                current_line.clear();
-                current_line.push_str(word);
+        let len = usize::from(u16::from_be_bytes([data[pos + 2], data[pos + 3]]));
+        // SOF0..SOF15, excluding DHT (C4), JPG (C8) and DAC (CC).
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let frame = data.get(pos + 4..pos + 2 + len)?;
+            if frame.len() < 6 {
+                return None;
             }
+            return Some(JpegHeader {
+                height: u16::from_be_bytes([frame[1], frame[2]]),
+                width: u16::from_be_bytes([frame[3], frame[4]]),
+                components: frame[5],
+            })
+            .filter(|h| h.width > 0 && h.height > 0);
+        }
+        // Start of scan without a frame header, or end of image.
+        if matches!(marker, 0xDA | 0xD9) {
+            return None;
         }
+        pos += 2 + len;
+    }
+    None
+}
 
-        if !current_line.is_empty() {
-            result.push(current_line);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+
+    /// A4 and the image margin, in points.
+    const A4: (f32, f32) = (595.276, 841.89);
+    const MARGIN: f32 = 15.0 * 72.0 / 25.4;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 0.01, "{actual:?} != {expected:?}");
         }
     }
 
-    result
+    #[test]
+    fn long_text_runs_onto_several_pages_and_stays_extractable() {
+        use crate::pdf::reader::PdfReader;
+
+        // 150 short lines and one paragraph far wider than the page.
+        let mut text: String = (1..=150).map(|n| format!("Line {n}\n")).collect();
+        text.push_str(&"wrapped words ".repeat(40));
+        let pdf = PdfWriter::a4()
+            .create_from_text(&text, PdfWriter::TEXT_FONT_SIZE, PdfWriter::TEXT_MARGIN_MM)
+            .unwrap();
+
+        let reader = PdfReader::from_bytes(&pdf).unwrap();
+        assert!(reader.page_count() > 1, "{} pages", reader.page_count());
+        let extracted = reader.extract_text().unwrap();
+        assert!(extracted.contains("Line 1\n"));
+        assert!(extracted.contains("Line 150\n"));
+        assert!(extracted.contains("wrapped words wrapped"));
+    }
+
+    #[test]
+    fn larger_type_and_margins_take_more_pages() {
+        use crate::pdf::reader::PdfReader;
+
+        let text: String = (1..=120).map(|n| format!("Line {n}\n")).collect();
+        let pages = |font_size, margins_mm| {
+            let pdf = PdfWriter::a4()
+                .create_from_text(&text, font_size, margins_mm)
+                .unwrap();
+            PdfReader::from_bytes(&pdf).unwrap().page_count()
+        };
+
+        let small = pages(9.0, 10.0);
+        let usual = pages(PdfWriter::TEXT_FONT_SIZE, PdfWriter::TEXT_MARGIN_MM);
+        let large = pages(14.0, 30.0);
+        assert!(small < usual && usual < large, "{small}, {usual}, {large}");
+    }
+
+    #[test]
+    fn landscape_photo_on_portrait_a4_fits_the_width() {
+        // 3000×2000 at 150 DPI is 1440×960 pt, wider than the page.
+        let placement = place_image(
+            A4,
+            (3000.0, 2000.0),
+            150.0,
+            Orientation::Portrait,
+            Fit::Contain,
+        );
+
+        let width = A4.0 - 2.0 * MARGIN;
+        let height = width * 2.0 / 3.0;
+        assert_close(&[placement.page.0, placement.page.1], &[A4.0, A4.1]);
+        assert_close(
+            &placement.matrix,
+            &[width, 0.0, 0.0, height, MARGIN, (A4.1 - height) / 2.0],
+        );
+        assert!(placement.clip.is_none());
+    }
+
+    #[test]
+    fn landscape_orientation_turns_the_page() {
+        let placement = place_image(
+            A4,
+            (3000.0, 2000.0),
+            150.0,
+            Orientation::Landscape,
+            Fit::Contain,
+        );
+
+        // Now the photo is held by the width of the turned page too.
+        let width = A4.1 - 2.0 * MARGIN;
+        let height = width * 2.0 / 3.0;
+        assert_close(&[placement.page.0, placement.page.1], &[A4.1, A4.0]);
+        assert_close(
+            &placement.matrix,
+            &[width, 0.0, 0.0, height, MARGIN, (A4.0 - height) / 2.0],
+        );
+    }
+
+    #[test]
+    fn small_image_is_contained_at_its_physical_size() {
+        // One inch square at 150 DPI.
+        let placement = place_image(
+            A4,
+            (150.0, 150.0),
+            150.0,
+            Orientation::Portrait,
+            Fit::Contain,
+        );
+
+        assert_close(
+            &placement.matrix,
+            &[
+                72.0,
+                0.0,
+                0.0,
+                72.0,
+                (A4.0 - 72.0) / 2.0,
+                (A4.1 - 72.0) / 2.0,
+            ],
+        );
+    }
+
+    #[test]
+    fn cover_fills_the_printable_area_and_crops() {
+        let placement = place_image(
+            A4,
+            (3000.0, 2000.0),
+            150.0,
+            Orientation::Portrait,
+            Fit::Cover,
+        );
+
+        let height = A4.1 - 2.0 * MARGIN;
+        let width = height * 1.5;
+        assert_close(
+            &placement.matrix,
+            &[width, 0.0, 0.0, height, (A4.0 - width) / 2.0, MARGIN],
+        );
+        let clip = placement.clip.expect("cover crops");
+        assert_close(
+            &[clip.x.0, clip.y.0, clip.width.0, clip.height.0],
+            &[MARGIN, MARGIN, A4.0 - 2.0 * MARGIN, A4.1 - 2.0 * MARGIN],
+        );
+    }
+
+    #[test]
+    fn stretch_fills_the_printable_area_exactly() {
+        let placement = place_image(
+            A4,
+            (150.0, 150.0),
+            150.0,
+            Orientation::Portrait,
+            Fit::Stretch,
+        );
+
+        assert_close(
+            &placement.matrix,
+            &[
+                A4.0 - 2.0 * MARGIN,
+                0.0,
+                0.0,
+                A4.1 - 2.0 * MARGIN,
+                MARGIN,
+                MARGIN,
+            ],
+        );
+    }
+
+    #[test]
+    fn reverse_orientation_turns_the_image_upside_down() {
+        let placement = place_image(
+            A4,
+            (150.0, 150.0),
+            150.0,
+            Orientation::ReversePortrait,
+            Fit::Contain,
+        );
+
+        let (x, y) = ((A4.0 - 72.0) / 2.0, (A4.1 - 72.0) / 2.0);
+        assert_close(
+            &placement.matrix,
+            &[-72.0, 0.0, 0.0, -72.0, x + 72.0, y + 72.0],
+        );
+    }
+
+    /// The written page has the turned media box, and its `cm` operators
+    /// compose to the computed placement.
+    #[test]
+    fn created_page_uses_the_placement() {
+        use lopdf::content::Content;
+        use lopdf::{Document, Object};
+
+        let photo = DynamicImage::ImageRgb8(RgbImage::new(300, 200));
+        let png = ImageProcessor::from_dynamic(photo).to_png_bytes().unwrap();
+        let pdf = PdfWriter::a4()
+            .create_from_image(&png, Orientation::Landscape, Fit::Contain)
+            .unwrap();
+
+        let doc = Document::load_mem(&pdf).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let media_box: Vec<f32> = doc
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(b"MediaBox")
+            .and_then(Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|v| v.as_float().unwrap())
+            .collect();
+        // printpdf writes the media box in whole points.
+        assert_close(&media_box, &[0.0, 0.0, A4.1.round(), A4.0.round()]);
+
+        let content = Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+        let mut ctm = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        for op in content.operations.iter().filter(|op| op.operator == "cm") {
+            let m: Vec<f32> = op.operands.iter().map(|v| v.as_float().unwrap()).collect();
+            ctm = [
+                m[0] * ctm[0] + m[1] * ctm[2],
+                m[0] * ctm[1] + m[1] * ctm[3],
+                m[2] * ctm[0] + m[3] * ctm[2],
+                m[2] * ctm[1] + m[3] * ctm[3],
+                m[4] * ctm[0] + m[5] * ctm[2] + ctm[4],
+                m[4] * ctm[1] + m[5] * ctm[3] + ctm[5],
+            ];
+        }
+        let expected = place_image(
+            A4,
+            (300.0, 200.0),
+            DEFAULT_IMAGE_DPI as f32,
+            Orientation::Landscape,
+            Fit::Contain,
+        );
+        assert_close(&ctm, &expected.matrix);
+    }
 }
@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// PWG Raster encoding (PWG 5102.4) for driverless printers that accept only
+// `image/pwg-raster`.
+//
+// A stream is the sync word `RaS2` followed, for every page, by a 1796-byte
+// big-endian page header and the compressed raster rows.  Rows are
+// compressed with the PWG variant of PackBits: a line-repeat byte, then runs
+// of repeated or literal pixels.
+
+use image::DynamicImage;
+use tracing::{debug, info, instrument};
+
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::{DuplexMode, Orientation, PrintSettings};
+
+/// Sync word opening every PWG Raster stream.
+const SYNC_WORD: &[u8; 4] = b"RaS2";
+
+/// Size of the page header in bytes.
+const HEADER_LEN: usize = 1796;
+
+/// `cupsColorSpace` values for the two colour spaces we emit.
+const COLOR_SPACE_SGRAY: u32 = 18;
+const COLOR_SPACE_SRGB: u32 = 19;
+
+/// Longest pixel run (repeated or literal) one run byte can describe.
+const MAX_RUN: usize = 128;
+
+/// Most rows one line-repeat byte can cover.
+const MAX_LINE_REPEAT: usize = 256;
+
+/// Encode rendered pages as a PWG Raster stream.
+///
+/// Each image becomes one page, 8 bits per colour: sRGB when
+/// `settings.color` is set, sGray otherwise. The resolution is derived from
+/// the image width and `settings.paper_size`, so pages should be rendered to
+/// fill the paper at the printer's resolution (e.g. 2480×3508 for A4 at
+/// 300 DPI). Landscape images are described as landscape paper.
+#[instrument(skip_all, fields(pages = pages.len(), color = settings.color))]
+pub fn to_pwg_raster(pages: &[DynamicImage], settings: &PrintSettings) -> Result<Vec<u8>> {
+    if pages.is_empty() {
+        return Err(PresswerkError::ImageError(
+            "no pages to encode as PWG Raster".into(),
+        ));
+    }
+
+    let mut out = SYNC_WORD.to_vec();
+    for (index, page) in pages.iter().enumerate() {
+        if page.width() == 0 || page.height() == 0 {
+            return Err(PresswerkError::ImageError(format!(
+                "page {} is empty",
+                index + 1
+            )));
+        }
+
+        let (samples, channels) = if settings.color {
+            (page.to_rgb8().into_raw(), 3)
+        } else {
+            (page.to_luma8().into_raw(), 1)
+        };
+        let page_size = page_size_points(page, settings);
+        let resolution = (page.width() as f32 * 72.0 / page_size.0 as f32)
+            .round()
+            .max(1.0) as u32;
+
+        write_header(
+            &mut out,
+            page,
+            settings,
+            pages.len(),
+            page_size,
+            resolution,
+            channels,
+        );
+        let bytes_per_line = page.width() as usize * channels;
+        compress_rows(&mut out, &samples, bytes_per_line, channels);
+
+        debug!(
+            page = index + 1,
+            width = page.width(),
+            height = page.height(),
+            resolution,
+            "PWG Raster page encoded"
+        );
+    }
+
+    info!(
+        pages = pages.len(),
+        bytes = out.len(),
+        "PWG Raster stream encoded"
+    );
+    Ok(out)
+}
+
+/// Paper size in points, turned to match the orientation of `page`.
+fn page_size_points(page: &DynamicImage, settings: &PrintSettings) -> (u32, u32) {
+    let (w_mm, h_mm) = settings.paper_size.dimensions_mm();
+    let to_points = |mm: f32| (mm * 72.0 / 25.4).round() as u32;
+    let (short, long) = (to_points(w_mm.min(h_mm)), to_points(w_mm.max(h_mm)));
+    if page.width() > page.height() {
+        (long, short)
+    } else {
+        (short, long)
+    }
+}
+
+/// Append one page header.  Unset fields stay zero, which PWG defines as
+/// "printer default".
+fn write_header(
+    out: &mut Vec<u8>,
+    page: &DynamicImage,
+    settings: &PrintSettings,
+    total_pages: usize,
+    page_size: (u32, u32),
+    resolution: u32,
+    channels: usize,
+) {
+    let mut header = vec![0u8; HEADER_LEN];
+
+    let color_space = if channels == 3 {
+        COLOR_SPACE_SRGB
+    } else {
+        COLOR_SPACE_SGRAY
+    };
+    let duplex = settings.duplex != DuplexMode::Simplex;
+    // Tumble flips the back side; PWG defines it relative to the feed, so
+    // short-edge binding tumbles in portrait and long-edge in landscape.
+    let landscape = matches!(
+        settings.orientation,
+        Orientation::Landscape | Orientation::ReverseLandscape
+    );
+    let tumble = match settings.duplex {
+        DuplexMode::Simplex => false,
+        DuplexMode::LongEdge => landscape,
+        DuplexMode::ShortEdge => !landscape,
+    };
+
+    put_u32(&mut header, 272, duplex as u32);
+    put_u32(&mut header, 276, resolution);
+    put_u32(&mut header, 280, resolution);
+    put_u32(&mut header, 340, settings.copies.max(1));
+    put_u32(&mut header, 352, page_size.0);
+    put_u32(&mut header, 356, page_size.1);
+    put_u32(&mut header, 368, tumble as u32);
+    put_u32(&mut header, 372, page.width());
+    put_u32(&mut header, 376, page.height());
+    put_u32(&mut header, 384, 8); // BitsPerColor
+    put_u32(&mut header, 388, 8 * channels as u32); // BitsPerPixel
+    put_u32(&mut header, 392, page.width() * channels as u32); // BytesPerLine
+    put_u32(&mut header, 396, 0); // ColorOrder: chunky
+    put_u32(&mut header, 400, color_space);
+    put_u32(&mut header, 420, channels as u32); // NumColors
+    put_u32(&mut header, 452, total_pages as u32);
+    put_u32(&mut header, 456, 1); // CrossFeedTransform
+    put_u32(&mut header, 460, 1); // FeedTransform
+
+    put_cstr(&mut header, 0, "PwgRaster");
+    put_cstr(
+        &mut header,
+        1732,
+        &settings.paper_size.to_ipp_media_keyword(),
+    );
+
+    out.extend_from_slice(&header);
+}
+
+/// Write a big-endian integer field.
+fn put_u32(header: &mut [u8], offset: usize, value: u32) {
+    header[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Write a NUL-terminated string into a 64-byte header field.
+fn put_cstr(header: &mut [u8], offset: usize, value: &str) {
+    let len = value.len().min(63);
+    header[offset..offset + len].copy_from_slice(&value.as_bytes()[..len]);
+}
+
+/// Compress raster rows: identical consecutive rows share one line-repeat
+/// byte, and each row is split into runs of repeated or literal pixels.
+fn compress_rows(out: &mut Vec<u8>, samples: &[u8], bytes_per_line: usize, bpp: usize) {
+    let rows: Vec<&[u8]> = samples.chunks_exact(bytes_per_line).collect();
+    let mut y = 0;
+    while y < rows.len() {
+        let repeat = rows[y..]
+            .iter()
+            .take(MAX_LINE_REPEAT)
+            .take_while(|row| **row == rows[y])
+            .count();
+        out.push((repeat - 1) as u8);
+        compress_row(out, rows[y], bpp);
+        y += repeat;
+    }
+}
+
+/// Compress one row of `bpp`-byte pixels.
+fn compress_row(out: &mut Vec<u8>, row: &[u8], bpp: usize) {
+    let pixels: Vec<&[u8]> = row.chunks_exact(bpp).collect();
+    let mut x = 0;
+    while x < pixels.len() {
+        let run = pixels[x..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|p| **p == pixels[x])
+            .count();
+        if run > 1 || x + 1 == pixels.len() {
+            // `n` means the pixel that follows is repeated n + 1 times.
+            out.push((run - 1) as u8);
+            out.extend_from_slice(pixels[x]);
+            x += run;
+            continue;
+        }
+
+        // Literal run: stop where two identical pixels start a repeat.
+        let mut end = x + 1;
+        while end < pixels.len() && end - x < MAX_RUN && pixels[end] != pixels[end - 1] {
+            end += 1;
+        }
+        if end < pixels.len() && pixels[end] == pixels[end - 1] {
+            end -= 1;
+        }
+        let count = end - x;
+        if count == 1 {
+            out.push(0);
+        } else {
+            // `257 - n`: n literal pixels follow.
+            out.push((257 - count) as u8);
+        }
+        for pixel in &pixels[x..end] {
+            out.extend_from_slice(pixel);
+        }
+        x = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma, Rgb, RgbImage};
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Decode one page's rows starting at `pos`; returns the samples and the
+    /// position after the page.
+    fn decode_page(data: &[u8], mut pos: usize, header: &[u8]) -> (Vec<u8>, usize) {
+        let (width, height) = (u32_at(header, 372) as usize, u32_at(header, 376) as usize);
+        let bpp = u32_at(header, 388) as usize / 8;
+        let mut samples = Vec::new();
+        let mut y = 0;
+        while y < height {
+            let repeat = data[pos] as usize + 1;
+            pos += 1;
+            let mut row = Vec::new();
+            while row.len() < width * bpp {
+                let code = data[pos];
+                pos += 1;
+                if code < 128 {
+                    for _ in 0..=code {
+                        row.extend_from_slice(&data[pos..pos + bpp]);
+                    }
+                    pos += bpp;
+                } else {
+                    let n = (257 - code as usize) * bpp;
+                    row.extend_from_slice(&data[pos..pos + n]);
+                    pos += n;
+                }
+            }
+            for _ in 0..repeat {
+                samples.extend_from_slice(&row);
+            }
+            y += repeat;
+        }
+        (samples, pos)
+    }
+
+    #[test]
+    fn stream_has_sync_word_and_a_header_per_page() {
+        let pages = vec![
+            DynamicImage::ImageRgb8(RgbImage::from_pixel(210, 297, Rgb([255, 255, 255]))),
+            DynamicImage::ImageRgb8(RgbImage::from_fn(300, 200, |x, y| {
+                Rgb([(x % 256) as u8, (y % 256) as u8, 7])
+            })),
+        ];
+        let settings = PrintSettings::default();
+        let data = to_pwg_raster(&pages, &settings).unwrap();
+
+        assert_eq!(&data[..4], b"RaS2");
+        let mut pos = 4;
+        for page in &pages {
+            let header = &data[pos..pos + HEADER_LEN];
+            assert_eq!(&header[..10], b"PwgRaster\0");
+            assert_eq!(u32_at(header, 372), page.width());
+            assert_eq!(u32_at(header, 376), page.height());
+            assert_eq!(u32_at(header, 400), COLOR_SPACE_SRGB);
+            assert_eq!(u32_at(header, 452), 2);
+
+            let (samples, next) = decode_page(&data, pos + HEADER_LEN, header);
+            assert_eq!(samples, page.to_rgb8().into_raw());
+            pos = next;
+        }
+        assert_eq!(pos, data.len());
+
+        // 210 px across 210 mm is 25.4 DPI; A4 in points is 595×842.
+        assert_eq!(u32_at(&data, 4 + 276), 25);
+        assert_eq!(u32_at(&data, 4 + 352), 595);
+        assert_eq!(u32_at(&data, 4 + 356), 842);
+    }
+
+    #[test]
+    fn monochrome_settings_emit_gray_and_compress_blank_rows() {
+        let mut page = GrayImage::from_pixel(1000, 1000, Luma([255]));
+        page.put_pixel(500, 10, Luma([0]));
+        let settings = PrintSettings {
+            color: false,
+            duplex: DuplexMode::LongEdge,
+            ..PrintSettings::default()
+        };
+        let data = to_pwg_raster(&[DynamicImage::ImageLuma8(page.clone())], &settings).unwrap();
+
+        let header = &data[4..4 + HEADER_LEN];
+        assert_eq!(u32_at(header, 400), COLOR_SPACE_SGRAY);
+        assert_eq!(u32_at(header, 388), 8);
+        assert_eq!(u32_at(header, 272), 1);
+        assert_eq!(u32_at(header, 368), 0);
+        assert!(data.len() < HEADER_LEN + 200, "{} bytes", data.len());
+
+        let (samples, _) = decode_page(&data, 4 + HEADER_LEN, header);
+        assert_eq!(samples, page.into_raw());
+    }
+
+    #[test]
+    fn empty_page_list_is_rejected() {
+        assert!(to_pwg_raster(&[], &PrintSettings::default()).is_err());
+    }
+}
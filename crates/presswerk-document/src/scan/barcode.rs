@@ -0,0 +1,678 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Barcode reading for scanned pages — separator sheets in a batch and the
+// QR codes printers print on their configuration pages.
+//
+// QR codes are located by their finder patterns and decoded with `rqrr`,
+// which fits each code's perspective itself, so codes are found at any
+// rotation and under moderate skew.
+//
+// Linear codes (Code 128 and Code 39, the symbologies separator sheets and
+// asset labels use) are read from scanlines: every few rows and columns the
+// page is thresholded into runs of bars and spaces, which are matched
+// against the symbol tables in both directions.  That finds codes upright,
+// sideways or upside down, and tilted as long as a scanline still crosses
+// every bar; codes at steeper angles are missed.  Code 128 is accepted only
+// with a valid check character; Code 39, which has none, only once it has
+// been read on two neighbouring scanlines.
+
+use image::{DynamicImage, GrayImage};
+use tracing::debug;
+
+/// Which symbology a [`Barcode`] was read as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarcodeKind {
+    /// A QR code (ISO/IEC 18004).
+    Qr,
+    /// A Code 128 linear barcode (ISO/IEC 15417).
+    Code128,
+    /// A Code 39 linear barcode (ISO/IEC 16388).
+    Code39,
+}
+
+/// Axis-aligned box around a barcode, in image pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A barcode found on an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Barcode {
+    pub kind: BarcodeKind,
+    /// The decoded payload.
+    pub value: String,
+    /// Where the code sits on the image, corners included however it is
+    /// rotated.
+    pub bbox: BoundingBox,
+}
+
+/// Find and decode every barcode on `image`.
+///
+/// Codes that are located but fail to decode (damaged, cut off, or not
+/// UTF-8) are skipped.
+pub fn detect_barcodes(image: &DynamicImage) -> Vec<Barcode> {
+    let gray = image.to_luma8();
+    let mut barcodes = detect_qr_codes(&gray);
+    barcodes.extend(detect_linear_codes(&gray));
+    debug!(count = barcodes.len(), "Barcodes detected");
+    barcodes
+}
+
+// -- QR codes ---------------------------------------------------------------
+
+fn detect_qr_codes(gray: &GrayImage) -> Vec<Barcode> {
+    let (width, height) = gray.dimensions();
+    let mut prepared =
+        rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+            gray.get_pixel(x as u32, y as u32).0[0]
+        });
+
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| match grid.decode() {
+            Ok((_, value)) => Some(Barcode {
+                kind: BarcodeKind::Qr,
+                value,
+                bbox: bounding_box(&grid.bounds, width, height),
+            }),
+            Err(e) => {
+                debug!(error = %e, "QR code found but not decoded");
+                None
+            }
+        })
+        .collect()
+}
+
+/// The box around a code's four corners, clamped to the image.
+fn bounding_box(corners: &[rqrr::Point; 4], width: u32, height: u32) -> BoundingBox {
+    let clamp = |value: i32, max: u32| value.clamp(0, max as i32) as u32;
+    let xs = corners.iter().map(|p| clamp(p.x, width));
+    let ys = corners.iter().map(|p| clamp(p.y, height));
+    let (x_min, x_max) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0));
+    let (y_min, y_max) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(0));
+    BoundingBox {
+        x: x_min,
+        y: y_min,
+        width: x_max - x_min,
+        height: y_max - y_min,
+    }
+}
+
+// -- Linear codes -----------------------------------------------------------
+
+/// Pixels between the rows (and columns) scanned for linear codes.
+const LINEAR_SCAN_STEP: u32 = 4;
+
+/// Smallest light-to-dark spread on a scanline worth thresholding.
+const MIN_CONTRAST: u8 = 64;
+
+/// Narrow elements of light space required before a code's start
+/// character.  The symbologies ask for ten; scans cropped tight get less.
+const QUIET_ZONE_MODULES: f32 = 5.0;
+
+/// Code 128 symbols 0–105 as bar/space widths in modules, and the stop
+/// pattern, which has a seventh element.
+const CODE128_PATTERNS: [&[u8; 6]; 106] = [
+    b"212222", b"222122", b"222221", b"121223", b"121322", b"131222", b"122213", b"122312",
+    b"132212", b"221213", b"221312", b"231212", b"112232", b"122132", b"122231", b"113222",
+    b"123122", b"123221", b"223211", b"221132", b"221231", b"213212", b"223112", b"312131",
+    b"311222", b"321122", b"321221", b"312212", b"322112", b"322211", b"212123", b"212321",
+    b"232121", b"111323", b"131123", b"131321", b"112313", b"132113", b"132311", b"211313",
+    b"231113", b"231311", b"112133", b"112331", b"132131", b"113123", b"113321", b"133121",
+    b"313121", b"211331", b"231131", b"213113", b"213311", b"213131", b"311123", b"311321",
+    b"331121", b"312113", b"312311", b"332111", b"314111", b"221411", b"431111", b"111224",
+    b"111422", b"121124", b"121421", b"141122", b"141221", b"112214", b"112412", b"122114",
+    b"122411", b"142112", b"142211", b"241211", b"221114", b"413111", b"241112", b"134111",
+    b"111242", b"121142", b"121241", b"114212", b"124112", b"124211", b"411212", b"421112",
+    b"421211", b"212141", b"214121", b"412121", b"111143", b"111341", b"131141", b"114113",
+    b"114311", b"411113", b"411311", b"113141", b"114131", b"311141", b"411131", b"211412",
+    b"211214", b"211232",
+];
+const CODE128_STOP: &[u8; 7] = b"2331112";
+const CODE128_START_A: u8 = 103;
+const CODE128_START_C: u8 = 105;
+
+/// Code 39 characters, and their nine elements (bar first) in the same
+/// order as a mask of the wide ones, the first element in bit 8.  `*` is
+/// the start/stop character.
+const CODE39_CHARS: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. *$/+%";
+const CODE39_PATTERNS: [u16; 44] = [
+    0x034, 0x121, 0x061, 0x160, 0x031, 0x130, 0x070, 0x025, 0x124, 0x064, 0x109, 0x049, 0x148,
+    0x019, 0x118, 0x058, 0x00d, 0x10c, 0x04c, 0x01c, 0x103, 0x043, 0x142, 0x013, 0x112, 0x052,
+    0x007, 0x106, 0x046, 0x016, 0x181, 0x0c1, 0x1c0, 0x091, 0x190, 0x0d0, 0x085, 0x184, 0x0c4,
+    0x094, 0x0a8, 0x0a2, 0x08a, 0x02a,
+];
+
+/// A run of same-coloured pixels along a scanline.
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    dark: bool,
+    start: u32,
+    len: u32,
+}
+
+/// A linear code read on one or more neighbouring scanlines.
+struct LinearHit {
+    kind: BarcodeKind,
+    value: String,
+    /// Read along a column rather than a row.
+    vertical: bool,
+    first_line: u32,
+    last_line: u32,
+    lines: u32,
+    start: u32,
+    end: u32,
+}
+
+fn detect_linear_codes(gray: &GrayImage) -> Vec<Barcode> {
+    let (width, height) = gray.dimensions();
+    let mut hits: Vec<LinearHit> = Vec::new();
+
+    for vertical in [false, true] {
+        let (lines, length) = if vertical {
+            (width, height)
+        } else {
+            (height, width)
+        };
+        for line in (0..lines).step_by(LINEAR_SCAN_STEP as usize) {
+            let pixels: Vec<u8> = (0..length)
+                .map(|i| {
+                    let (x, y) = if vertical { (line, i) } else { (i, line) };
+                    gray.get_pixel(x, y).0[0]
+                })
+                .collect();
+            let runs = scanline_runs(&pixels);
+            let reversed: Vec<Run> = runs.iter().rev().copied().collect();
+            for runs in [&runs, &reversed] {
+                for (kind, value, span) in read_scanline(runs) {
+                    record_hit(&mut hits, kind, value, vertical, line, span);
+                }
+            }
+        }
+    }
+
+    hits.into_iter()
+        .filter(|hit| hit.kind == BarcodeKind::Code128 || hit.lines >= 2)
+        .map(|hit| {
+            let (along, across) = (
+                (hit.start, hit.end - hit.start),
+                (hit.first_line, hit.last_line - hit.first_line + 1),
+            );
+            let ((x, width), (y, height)) = if hit.vertical {
+                (across, along)
+            } else {
+                (along, across)
+            };
+            Barcode {
+                kind: hit.kind,
+                value: hit.value,
+                bbox: BoundingBox {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Threshold `pixels` halfway between their darkest and lightest value and
+/// split them into runs.  A scanline without enough contrast has none.
+fn scanline_runs(pixels: &[u8]) -> Vec<Run> {
+    let (Some(&min), Some(&max)) = (pixels.iter().min(), pixels.iter().max()) else {
+        return Vec::new();
+    };
+    if max - min < MIN_CONTRAST {
+        return Vec::new();
+    }
+    let threshold = min + (max - min) / 2;
+
+    let mut runs: Vec<Run> = Vec::new();
+    for (i, &pixel) in pixels.iter().enumerate() {
+        let dark = pixel < threshold;
+        match runs.last_mut() {
+            Some(run) if run.dark == dark => run.len += 1,
+            _ => runs.push(Run {
+                dark,
+                start: i as u32,
+                len: 1,
+            }),
+        }
+    }
+    runs
+}
+
+/// Every code readable on one direction of a scanline, with the pixel span
+/// it covers.
+fn read_scanline(runs: &[Run]) -> Vec<(BarcodeKind, String, (u32, u32))> {
+    let mut found = Vec::new();
+    let mut i = 1;
+    while i < runs.len() {
+        let read = if runs[i].dark {
+            read_code128(runs, i)
+                .map(|(value, end)| (BarcodeKind::Code128, value, end))
+                .or_else(|| {
+                    read_code39(runs, i).map(|(value, end)| (BarcodeKind::Code39, value, end))
+                })
+        } else {
+            None
+        };
+        match read {
+            Some((kind, value, end)) => {
+                let span = runs[i..end].iter().fold((u32::MAX, 0), |(lo, hi), run| {
+                    (lo.min(run.start), hi.max(run.start + run.len))
+                });
+                found.push((kind, value, span));
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    found
+}
+
+/// Add a scanline reading to the code it continues, or start a new one.
+fn record_hit(
+    hits: &mut Vec<LinearHit>,
+    kind: BarcodeKind,
+    value: String,
+    vertical: bool,
+    line: u32,
+    (start, end): (u32, u32),
+) {
+    let continued = hits.iter_mut().find(|hit| {
+        hit.kind == kind
+            && hit.value == value
+            && hit.vertical == vertical
+            && hit.start < end
+            && start < hit.end
+            && line <= hit.last_line + 2 * LINEAR_SCAN_STEP
+    });
+    match continued {
+        Some(hit) => {
+            hit.last_line = line;
+            hit.lines += 1;
+            hit.start = hit.start.min(start);
+            hit.end = hit.end.max(end);
+        }
+        None => hits.push(LinearHit {
+            kind,
+            value,
+            vertical,
+            first_line: line,
+            last_line: line,
+            lines: 1,
+            start,
+            end,
+        }),
+    }
+}
+
+/// Whether the light run before `runs[i]` is a quiet zone for a code
+/// whose narrow element is `module` pixels.
+fn quiet_before(runs: &[Run], i: usize, module: f32) -> bool {
+    i > 0 && !runs[i - 1].dark && runs[i - 1].len as f32 >= QUIET_ZONE_MODULES * module
+}
+
+/// Element widths of `runs` in modules, `total` modules in all.
+fn module_widths(runs: &[Run], total: f32) -> Option<Vec<u8>> {
+    let pixels: u32 = runs.iter().map(|run| run.len).sum();
+    let module = pixels as f32 / total;
+    runs.iter()
+        .map(|run| {
+            let width = (run.len as f32 / module).round();
+            (1.0..=4.0).contains(&width).then_some(b'0' + width as u8)
+        })
+        .collect()
+}
+
+/// Read a Code 128 symbol whose start character begins at `runs[i]`,
+/// returning its text and the index just past its stop pattern.
+fn read_code128(runs: &[Run], i: usize) -> Option<(String, usize)> {
+    let start = code128_symbol(runs.get(i..i + 6)?)?;
+    if !(CODE128_START_A..=CODE128_START_C).contains(&start) {
+        return None;
+    }
+    let module = runs[i..i + 6].iter().map(|run| run.len).sum::<u32>() as f32 / 11.0;
+    if !quiet_before(runs, i, module) {
+        return None;
+    }
+
+    let mut symbols = vec![start];
+    let mut j = i + 6;
+    loop {
+        if let Some(stop) = runs.get(j..j + 7)
+            && module_widths(stop, 13.0).is_some_and(|widths| widths == CODE128_STOP)
+        {
+            j += 7;
+            break;
+        }
+        let symbol = code128_symbol(runs.get(j..j + 6)?)?;
+        if symbol >= CODE128_START_A {
+            return None;
+        }
+        symbols.push(symbol);
+        j += 6;
+    }
+
+    // Start, at least one data symbol, then the check character.
+    let (&check, body) = symbols.split_last()?;
+    if body.len() < 2 {
+        return None;
+    }
+    let sum = body
+        .iter()
+        .enumerate()
+        .map(|(weight, &symbol)| weight.max(1) * symbol as usize)
+        .sum::<usize>();
+    if sum % 103 != check as usize {
+        return None;
+    }
+    Some((code128_text(body), j))
+}
+
+/// The Code 128 symbol value of six runs, if they form one.
+fn code128_symbol(runs: &[Run]) -> Option<u8> {
+    if !runs[0].dark {
+        return None;
+    }
+    let widths = module_widths(runs, 11.0)?;
+    CODE128_PATTERNS
+        .iter()
+        .position(|pattern| pattern.as_slice() == widths.as_slice())
+        .map(|value| value as u8)
+}
+
+/// Decode Code 128 symbols (start character first, check character
+/// removed) into text.  Function characters are dropped.
+fn code128_text(symbols: &[u8]) -> String {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Set {
+        A,
+        B,
+        C,
+    }
+
+    let mut set = match symbols[0] {
+        CODE128_START_A => Set::A,
+        CODE128_START_C => Set::C,
+        _ => Set::B,
+    };
+    let mut shifted = false;
+    let mut text = String::new();
+    for &value in &symbols[1..] {
+        let current = match (shifted, set) {
+            (true, Set::A) => Set::B,
+            (true, Set::B) => Set::A,
+            _ => set,
+        };
+        shifted = false;
+        match (current, value) {
+            (Set::C, 0..=99) => text.push_str(&format!("{value:02}")),
+            (Set::C, 100) => set = Set::B,
+            (Set::C, 101) => set = Set::A,
+            (_, 0..=63) => text.push((value + 32) as char),
+            (Set::A, 64..=95) => text.push((value - 64) as char),
+            (Set::B, 64..=95) => text.push((value + 32) as char),
+            (_, 98) => shifted = true,
+            (_, 99) => set = Set::C,
+            (Set::A, 100) => set = Set::B,
+            (Set::B, 101) => set = Set::A,
+            // FNC1–FNC4.
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Read a Code 39 symbol whose start character begins at `runs[i]`,
+/// returning its text and the index just past its stop character.
+fn read_code39(runs: &[Run], i: usize) -> Option<(String, usize)> {
+    let (start, narrow) = code39_char(runs.get(i..i + 9)?)?;
+    if start != '*' || !quiet_before(runs, i, narrow) {
+        return None;
+    }
+
+    let mut text = String::new();
+    let mut j = i + 9;
+    loop {
+        // The gap between characters is a light element about as wide as
+        // a narrow one.
+        let gap = runs.get(j)?;
+        if gap.dark || gap.len as f32 > 3.0 * narrow {
+            return None;
+        }
+        let (c, _) = code39_char(runs.get(j + 1..j + 10)?)?;
+        j += 10;
+        if c == '*' {
+            break;
+        }
+        text.push(c);
+    }
+    (!text.is_empty()).then_some((text, j))
+}
+
+/// The Code 39 character of nine runs, if they form one, with the width of
+/// their narrow elements.
+fn code39_char(runs: &[Run]) -> Option<(char, f32)> {
+    if !runs[0].dark {
+        return None;
+    }
+    // Exactly three elements are wide, clearly wider than the rest.
+    let mut lens: Vec<u32> = runs.iter().map(|run| run.len).collect();
+    lens.sort_unstable();
+    let (widest_narrow, narrowest_wide) = (lens[5], lens[6]);
+    if narrowest_wide as f32 <= 1.5 * widest_narrow as f32 {
+        return None;
+    }
+    let wide = runs.iter().fold(0u16, |mask, run| {
+        mask << 1 | u16::from(run.len >= narrowest_wide)
+    });
+    let narrow = lens[..6].iter().sum::<u32>() as f32 / 6.0;
+    CODE39_PATTERNS
+        .iter()
+        .position(|&pattern| pattern == wide)
+        .and_then(|index| CODE39_CHARS.chars().nth(index))
+        .map(|c| (c, narrow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::{GrayImage, Luma};
+
+    /// Pixels per QR module in the test images.
+    const MODULE: u32 = 6;
+
+    /// Render `payload` as a QR code placed `offset` pixels into a white
+    /// page, whose margin serves as the quiet zone.
+    fn qr_page(payload: &str, offset: (u32, u32)) -> (GrayImage, u32) {
+        let code = qrcode::QrCode::new(payload.as_bytes()).unwrap();
+        let modules = code.width() as u32;
+        let colors = code.to_colors();
+        let side = modules * MODULE;
+        let page = GrayImage::from_fn(side + offset.0 * 2, side + offset.1 * 2, |x, y| {
+            let inside = (offset.0..offset.0 + side).contains(&x)
+                && (offset.1..offset.1 + side).contains(&y);
+            let dark = inside && {
+                let (mx, my) = ((x - offset.0) / MODULE, (y - offset.1) / MODULE);
+                colors[(my * modules + mx) as usize] == qrcode::Color::Dark
+            };
+            Luma([if dark { 0 } else { 255 }])
+        });
+        (page, side)
+    }
+
+    #[test]
+    fn qr_code_decodes_to_its_payload_and_position() {
+        let payload = "ipp://192.168.1.40/ipp/print";
+        let (page, side) = qr_page(payload, (120, 80));
+
+        let barcodes = detect_barcodes(&DynamicImage::ImageLuma8(page));
+
+        assert_eq!(barcodes.len(), 1);
+        let barcode = &barcodes[0];
+        assert_eq!(barcode.kind, BarcodeKind::Qr);
+        assert_eq!(barcode.value, payload);
+        // The corners rqrr fits lie within a module of the drawn code.
+        let near = |actual: u32, expected: u32| actual.abs_diff(expected) <= MODULE;
+        assert!(near(barcode.bbox.x, 120), "{:?}", barcode.bbox);
+        assert!(near(barcode.bbox.y, 80), "{:?}", barcode.bbox);
+        assert!(near(barcode.bbox.width, side), "{:?}", barcode.bbox);
+        assert!(near(barcode.bbox.height, side), "{:?}", barcode.bbox);
+    }
+
+    #[test]
+    fn rotated_qr_code_is_still_read() {
+        let (page, _) = qr_page("SEPARATOR", (60, 60));
+        let rotated = imageproc::geometric_transformations::rotate_about_center(
+            &page,
+            0.5,
+            imageproc::geometric_transformations::Interpolation::Bilinear,
+            Luma([255]),
+        );
+        let upside_down = image::imageops::rotate180(&page);
+
+        for image in [rotated, upside_down] {
+            let barcodes = detect_barcodes(&DynamicImage::ImageLuma8(image));
+            let values: Vec<&str> = barcodes.iter().map(|b| b.value.as_str()).collect();
+            assert_eq!(values, ["SEPARATOR"]);
+        }
+    }
+
+    /// Pixels per narrow element in the linear test codes.
+    const BAR: u32 = 3;
+
+    /// Draw elements of the given widths (in `BAR`s, bar first) as a code
+    /// 80 pixels tall with a quiet zone of 12 narrow elements all round.
+    fn linear_page(widths: &[u32]) -> GrayImage {
+        let margin = 12 * BAR;
+        let length: u32 = widths.iter().sum::<u32>() * BAR;
+        let mut page = GrayImage::from_pixel(length + 2 * margin, 80 + 2 * margin, Luma([255]));
+        let mut x = margin;
+        for (i, &width) in widths.iter().enumerate() {
+            if i % 2 == 0 {
+                for px in x..x + width * BAR {
+                    for y in margin..margin + 80 {
+                        page.put_pixel(px, y, Luma([0]));
+                    }
+                }
+            }
+            x += width * BAR;
+        }
+        page
+    }
+
+    /// Code 128 set B elements for `text`, check character included.
+    fn code128_widths(text: &str) -> Vec<u32> {
+        let mut symbols = vec![104];
+        symbols.extend(text.bytes().map(|b| b - 32));
+        let check = symbols
+            .iter()
+            .enumerate()
+            .map(|(weight, &symbol)| weight.max(1) * symbol as usize)
+            .sum::<usize>()
+            % 103;
+        symbols.push(check as u8);
+        symbols
+            .iter()
+            .flat_map(|&symbol| CODE128_PATTERNS[symbol as usize].iter())
+            .chain(CODE128_STOP.iter())
+            .map(|&digit| u32::from(digit - b'0'))
+            .collect()
+    }
+
+    /// Code 39 elements for `*text*`, wide elements three narrow ones wide.
+    fn code39_widths(text: &str) -> Vec<u32> {
+        let mut widths = Vec::new();
+        for c in format!("*{text}*").chars() {
+            if !widths.is_empty() {
+                widths.push(1);
+            }
+            let index = CODE39_CHARS.find(c).unwrap();
+            let wide = CODE39_PATTERNS[index];
+            widths.extend(
+                (0..9)
+                    .rev()
+                    .map(|bit| if wide >> bit & 1 == 1 { 3 } else { 1 }),
+            );
+        }
+        widths
+    }
+
+    fn read(image: GrayImage) -> Vec<(BarcodeKind, String)> {
+        detect_barcodes(&DynamicImage::ImageLuma8(image))
+            .into_iter()
+            .map(|barcode| (barcode.kind, barcode.value))
+            .collect()
+    }
+
+    #[test]
+    fn code128_is_read_in_any_orientation() {
+        let page = linear_page(&code128_widths("SEP-0001"));
+        let expected = vec![(BarcodeKind::Code128, "SEP-0001".to_string())];
+
+        let barcodes = detect_barcodes(&DynamicImage::ImageLuma8(page.clone()));
+        assert_eq!(barcodes.len(), 1);
+        let bbox = barcodes[0].bbox;
+        assert_eq!(bbox.x, 12 * BAR);
+        assert_eq!(bbox.width, page.width() - 24 * BAR);
+        assert!(bbox.y.abs_diff(12 * BAR) < LINEAR_SCAN_STEP, "{bbox:?}");
+        assert!(bbox.height.abs_diff(80) < 2 * LINEAR_SCAN_STEP, "{bbox:?}");
+
+        assert_eq!(read(image::imageops::rotate90(&page)), expected);
+        assert_eq!(read(image::imageops::rotate180(&page)), expected);
+        let tilted = imageproc::geometric_transformations::rotate_about_center(
+            &page,
+            0.1,
+            imageproc::geometric_transformations::Interpolation::Bilinear,
+            Luma([255]),
+        );
+        assert_eq!(read(tilted), expected);
+    }
+
+    #[test]
+    fn code128_with_a_wrong_check_character_is_rejected() {
+        let mut widths = code128_widths("SEP-0001");
+        // Swap the last data symbol for another, leaving the check as is.
+        let last_data = widths.len() - 7 - 12;
+        widths.splice(
+            last_data..last_data + 6,
+            CODE128_PATTERNS[b'2' as usize - 32]
+                .iter()
+                .map(|&digit| u32::from(digit - b'0')),
+        );
+        assert!(read(linear_page(&widths)).is_empty());
+    }
+
+    #[test]
+    fn code128_text_follows_code_sets() {
+        // Start C, "12" "34", switch to set B, "A".
+        assert_eq!(code128_text(&[105, 12, 34, 100, 33]), "1234A");
+        // Start A, "A", shift: one set-B "a", then set A again, "B".
+        assert_eq!(code128_text(&[103, 33, 98, 65, 34]), "AaB");
+    }
+
+    #[test]
+    fn code39_is_read_upright_and_upside_down() {
+        let page = linear_page(&code39_widths("PRESSWERK-01"));
+        let expected = vec![(BarcodeKind::Code39, "PRESSWERK-01".to_string())];
+        assert_eq!(read(page.clone()), expected);
+        assert_eq!(read(image::imageops::rotate180(&page)), expected);
+        assert_eq!(read(image::imageops::rotate270(&page)), expected);
+    }
+
+    #[test]
+    fn page_without_codes_yields_nothing() {
+        let blank = GrayImage::from_pixel(200, 200, Luma([250]));
+        assert!(detect_barcodes(&DynamicImage::ImageLuma8(blank)).is_empty());
+    }
+}
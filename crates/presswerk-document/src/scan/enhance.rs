@@ -3,18 +3,120 @@
 //
 // Scan enhancement pipeline — binarization, contrast boosting, edge-aware
 // cleanup, and scan-to-PDF conversion for scanned document images.
+//
+// `ScanEnhancer::auto` picks the pipeline itself: it looks at how colourful
+// the scan is, how much of it is plain ink-or-paper and how many sharp edges
+// it has, and treats it as a text document, a mixed page or a photo.
 
 use image::{DynamicImage, GrayImage, Luma, Rgba, RgbaImage};
 use imageproc::edges::canny;
 use imageproc::filter::gaussian_blur_f32;
 use imageproc::geometric_transformations::{Interpolation, Projection, warp_into};
 use imageproc::hough::{LineDetectionOptions, PolarLine, detect_lines};
-use presswerk_core::PaperSize;
 use presswerk_core::error::PresswerkError;
+use presswerk_core::{Orientation, PaperSize};
 use tracing::{debug, info, instrument, warn};
 
-use crate::image::processor::ImageProcessor;
-use crate::pdf::writer::PdfWriter;
+use crate::image::processor::{ImageProcessor, decode_upright};
+use crate::pdf::reader::PdfReader;
+use crate::pdf::writer::{Fit, PdfWriter};
+
+/// JPEG quality used for colour and greyscale scans unless overridden.
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// Neighbourhood radius [`ScanEnhancer::enhance_scan`] binarizes with.
+pub const DEFAULT_BLOCK_RADIUS: u32 = 15;
+
+/// Offset below the local mean [`ScanEnhancer::enhance_scan`] binarizes with.
+pub const DEFAULT_THRESHOLD_OFFSET: i32 = 10;
+
+/// Longest side of the thumbnail [`ScanEnhancer::detect_content`] analyses.
+const ANALYSIS_SIZE: u32 = 256;
+
+/// Mean chroma (0-255) below which a scan counts as black and white.
+const MONOCHROME_CHROMA: f32 = 24.0;
+
+/// Share of ink-or-paper pixels from which a monochrome scan is a document.
+const DOCUMENT_BILEVEL_SHARE: f32 = 0.8;
+
+/// Share of ink-or-paper pixels below which a scan may be a photo.
+const PHOTO_BILEVEL_SHARE: f32 = 0.5;
+
+/// Share of sharp-edge pixels below which a scan may be a photo.
+const PHOTO_EDGE_SHARE: f32 = 0.05;
+
+/// Share of pixels clipped at each end when levelling a photo.
+const LEVEL_CLIP: f32 = 0.005;
+
+/// Smallest and largest punch hole diameters removed (mm): ISO 838's 6 mm
+/// holes and US 1/4" ones, with room for blur and shadow.
+const PUNCH_HOLE_DIAMETER_MM: (f32, f32) = (4.0, 9.5);
+
+/// How far in from the edge punch holes are looked for (mm).  ISO 838 puts
+/// their centres 12 mm in.
+const PUNCH_HOLE_MARGIN_MM: f32 = 25.0;
+
+/// Share of its bounding box a blob must cover to count as round; a disc
+/// covers π/4 (0.785), a solid square all of it.
+const PUNCH_HOLE_FILL: (f32, f32) = (0.65, 0.92);
+
+/// Longest to shortest side of a punch hole's bounding box.
+const PUNCH_HOLE_MAX_ASPECT: f32 = 1.3;
+
+/// Share of the page, measured in from the edge, that
+/// [`ScanEnhancer::remove_edge_shadow`] corrects.
+const EDGE_SHADOW_DEPTH: f32 = 0.35;
+
+/// Brightness percentile taken as the paper's along each line of a scan, so
+/// the text on the line does not count.
+const PAPER_PERCENTILE: f32 = 0.9;
+
+/// Strongest brightening applied to a shadowed line.
+const MAX_SHADOW_GAIN: f32 = 4.0;
+
+/// Share of the page at each edge [`ScanEnhancer::is_blank`] ignores, where
+/// scanner shadows and the paper's own edge show up.
+const BLANK_BORDER: f32 = 0.05;
+
+/// How much darker than the paper a pixel must be to count as ink.
+const BLANK_INK_CONTRAST: u8 = 64;
+
+/// Share of ink pixels up to which a page still counts as blank, allowing
+/// for dust and show-through.
+const BLANK_MAX_INK_SHARE: f32 = 0.002;
+
+/// What a scan shows, as far as [`ScanEnhancer::detect_content`] can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanContent {
+    /// Text or line art on plain paper; binarized.
+    Document,
+    /// Text together with pictures or tinted backgrounds; kept in greyscale
+    /// with boosted contrast.
+    Mixed,
+    /// A photograph; colours kept, levels stretched and sharpened.
+    Photo,
+}
+
+/// How the scan image is compressed when embedded in a PDF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanImageFormat {
+    /// Pixels handed to the PDF writer via PNG. Best for black-and-white
+    /// (binarized) scans, which are stored losslessly and compress to almost
+    /// nothing.
+    Png,
+    /// Lossy JPEG at `quality` (1-100). Far smaller for colour and
+    /// greyscale photos; the JPEG is embedded without re-encoding.
+    Jpeg { quality: u8 },
+}
+
+/// A page edge, e.g. the one a binder's punch holes run along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
 
 /// Enhances scanned document images for print-quality output.
 ///
@@ -26,23 +128,28 @@ pub struct ScanEnhancer {
     image: DynamicImage,
     /// Target paper size for PDF output.
     paper_size: PaperSize,
+    /// Resolution of the scan, if known; see [`with_dpi`](Self::with_dpi).
+    dpi: Option<u32>,
 }
 
 impl ScanEnhancer {
     // -- Construction ---------------------------------------------------------
 
-    /// Create an enhancer from raw image bytes (JPEG, PNG, TIFF, etc.).
+    /// Create an enhancer from raw image bytes (JPEG, PNG, TIFF, WebP, HEIC
+    /// with the `heic` feature, etc.).
     #[instrument(skip(data), fields(data_len = data.len()))]
     pub fn from_bytes(data: &[u8], paper_size: PaperSize) -> Result<Self, PresswerkError> {
-        let image = image::load_from_memory(data).map_err(|err| {
-            PresswerkError::ImageError(format!("failed to decode scan image: {}", err))
-        })?;
+        let image = decode_upright(data)?;
         info!(
             width = image.width(),
             height = image.height(),
             "Scan image loaded"
         );
-        Ok(Self { image, paper_size })
+        Ok(Self {
+            image,
+            paper_size,
+            dpi: None,
+        })
     }
 
     /// Create an enhancer from a file path.
@@ -51,19 +158,69 @@ pub fn open(
         path: impl AsRef<std::path::Path>,
         paper_size: PaperSize,
     ) -> Result<Self, PresswerkError> {
-        let image = image::open(path.as_ref()).map_err(|err| {
+        let data = std::fs::read(path.as_ref()).map_err(|err| {
             PresswerkError::ImageError(format!(
                 "failed to open scan image {}: {}",
                 path.as_ref().display(),
                 err
             ))
         })?;
-        Ok(Self { image, paper_size })
+        let image = decode_upright(&data)?;
+        Ok(Self {
+            image,
+            paper_size,
+            dpi: None,
+        })
+    }
+
+    /// Create an enhancer from the scanned image on one page (1-indexed) of
+    /// an existing PDF, so a poor scan can be cleaned up again.
+    ///
+    /// When the page holds several images the largest is used. Fails with
+    /// `ImageError` if the page is vector-only and has no raster to enhance.
+    #[instrument(skip(pdf_bytes), fields(pdf_len = pdf_bytes.len()))]
+    pub fn from_pdf_page(
+        pdf_bytes: &[u8],
+        page: u32,
+        paper_size: PaperSize,
+    ) -> Result<Self, PresswerkError> {
+        let reader = PdfReader::from_bytes(pdf_bytes)?;
+        let image = reader.page_image(page)?.ok_or_else(|| {
+            PresswerkError::ImageError(format!(
+                "page {} has no scanned image to enhance (it contains only text or vector graphics)",
+                page
+            ))
+        })?;
+        info!(
+            page,
+            width = image.width(),
+            height = image.height(),
+            "Scan image extracted from PDF"
+        );
+        Ok(Self {
+            image,
+            paper_size,
+            dpi: None,
+        })
     }
 
     /// Create an enhancer wrapping an existing `DynamicImage`.
     pub fn from_dynamic(image: DynamicImage, paper_size: PaperSize) -> Self {
-        Self { image, paper_size }
+        Self {
+            image,
+            paper_size,
+            dpi: None,
+        }
+    }
+
+    /// Record the scanner's native resolution, so PDF and PNG output keep
+    /// the page at its physical size instead of being stretched or assumed
+    /// to be 72 DPI.
+    pub fn with_dpi(self, dpi: u32) -> Self {
+        Self {
+            dpi: Some(dpi.max(1)),
+            ..self
+        }
     }
 
     // -- Accessors ------------------------------------------------------------
@@ -113,6 +270,7 @@ pub fn binarize(self, block_radius: u32, c: i32) -> Self {
         Self {
             image: DynamicImage::ImageLuma8(output),
             paper_size: self.paper_size,
+            dpi: self.dpi,
         }
     }
 
@@ -128,23 +286,37 @@ pub fn binarize_otsu(self) -> Self {
         let threshold = otsu_threshold(&gray);
         debug!(threshold, "Otsu threshold computed");
 
-        let (width, height) = gray.dimensions();
-        let mut output = GrayImage::new(width, height);
-
-        for y in 0..height {
-            for x in 0..width {
-                let val = gray.get_pixel(x, y).0[0];
-                let binary = if val < threshold { 0u8 } else { 255u8 };
-                output.put_pixel(x, y, Luma([binary]));
-            }
+        Self {
+            image: DynamicImage::ImageLuma8(global_threshold(&gray, threshold)),
+            paper_size: self.paper_size,
+            dpi: self.dpi,
         }
+    }
 
+    /// Global binarization at a threshold chosen by the caller, e.g. from a
+    /// slider over [`luminance_histogram`](Self::luminance_histogram).
+    ///
+    /// Pixels darker than `threshold` become black; the rest become white.
+    #[instrument(skip(self))]
+    pub fn binarize_at(self, threshold: u8) -> Self {
+        info!(threshold, "Applying manual binarization");
+
+        let gray = self.image.to_luma8();
         Self {
-            image: DynamicImage::ImageLuma8(output),
+            image: DynamicImage::ImageLuma8(global_threshold(&gray, threshold)),
             paper_size: self.paper_size,
+            dpi: self.dpi,
         }
     }
 
+    /// Number of pixels at each luminance level (0 = black, 255 = white).
+    ///
+    /// Lets a UI show where a binarization threshold falls between the ink
+    /// and paper peaks.
+    pub fn luminance_histogram(&self) -> [u64; 256] {
+        luminance_histogram(&self.image.to_luma8())
+    }
+
     // -- Enhancement pipeline -------------------------------------------------
 
     /// Run the full scan enhancement pipeline:
@@ -158,20 +330,110 @@ pub fn binarize_otsu(self) -> Self {
     pub fn enhance_scan(self) -> Self {
         info!("Running full scan enhancement pipeline");
 
-        let paper_size = self.paper_size;
+        self.prepare_for_binarization()
+            .binarize(DEFAULT_BLOCK_RADIUS, DEFAULT_THRESHOLD_OFFSET)
+    }
+
+    /// Steps 1 and 2 of [`enhance_scan`](Self::enhance_scan): grayscale and
+    /// contrast boost, leaving the choice of binarization to the caller.
+    pub fn prepare_for_binarization(self) -> Self {
+        let (paper_size, dpi) = (self.paper_size, self.dpi);
 
-        // Step 1: Grayscale conversion.
         let processor = ImageProcessor::from_dynamic(self.image)
             .grayscale()
             .adjust_contrast(1.4);
 
-        // Step 2+3: Re-wrap and binarize.
-        let enhanced = Self {
+        Self {
             image: processor.into_dynamic(),
             paper_size,
+            dpi,
+        }
+    }
+
+    // -- Automatic enhancement ------------------------------------------------
+
+    /// Classify the scan as a document, a mixed page or a photo.
+    ///
+    /// Looks at a thumbnail: its mean chroma (how colourful it is), the share
+    /// of near-black and near-white pixels (ink and paper), and the share of
+    /// pixels on a sharp edge (text strokes).
+    pub fn detect_content(&self) -> ScanContent {
+        let stats = ContentStats::of(&self.image);
+        let content = if stats.chroma < MONOCHROME_CHROMA
+            && stats.bilevel_share >= DOCUMENT_BILEVEL_SHARE
+        {
+            ScanContent::Document
+        } else if stats.bilevel_share < PHOTO_BILEVEL_SHARE && stats.edge_share < PHOTO_EDGE_SHARE {
+            ScanContent::Photo
+        } else {
+            ScanContent::Mixed
+        };
+        debug!(
+            chroma = stats.chroma,
+            bilevel_share = stats.bilevel_share,
+            edge_share = stats.edge_share,
+            ?content,
+            "Scan content detected"
+        );
+        content
+    }
+
+    /// Whether the page is blank: next to no pixel in it, away from the
+    /// edges, is clearly darker than the paper.
+    pub fn is_blank(&self) -> bool {
+        let gray = self.image.to_luma8();
+        let (width, height) = gray.dimensions();
+        let (border_x, border_y) = (
+            (width as f32 * BLANK_BORDER) as u32,
+            (height as f32 * BLANK_BORDER) as u32,
+        );
+        let (x_range, y_range) = (
+            border_x..width.saturating_sub(border_x),
+            border_y..height.saturating_sub(border_y),
+        );
+        let total = x_range.len() * y_range.len();
+        if total == 0 {
+            return true;
+        }
+        let gray = &gray;
+        let interior = || {
+            y_range
+                .clone()
+                .flat_map(|y| x_range.clone().map(move |x| gray.get_pixel(x, y).0[0]))
         };
 
-        enhanced.binarize(15, 10)
+        let paper = paper_level(interior()) as u8;
+        let ink_below = paper.saturating_sub(BLANK_INK_CONTRAST);
+        let ink = interior().filter(|&p| p < ink_below).count();
+        let ink_share = ink as f32 / total as f32;
+        debug!(paper, ink_share, "Blank page check");
+        ink_share <= BLANK_MAX_INK_SHARE
+    }
+
+    /// Enhance the scan with the pipeline that suits its content (see
+    /// [`detect_content`](Self::detect_content)):
+    ///
+    /// - documents go through [`enhance_scan`](Self::enhance_scan) and come
+    ///   out black and white
+    /// - mixed pages get [`prepare_for_binarization`](Self::prepare_for_binarization)
+    ///   (greyscale, contrast boost) and stay greyscale
+    /// - photos keep their colours, with levels stretched and a light sharpen
+    #[instrument(skip(self))]
+    pub fn auto(self) -> Self {
+        let content = self.detect_content();
+        info!(?content, "Running automatic scan enhancement");
+
+        match content {
+            ScanContent::Document => self.enhance_scan(),
+            ScanContent::Mixed => self.prepare_for_binarization(),
+            ScanContent::Photo => {
+                let leveled = auto_level(&self.image);
+                Self {
+                    image: DynamicImage::ImageRgb8(leveled).unsharpen(1.0, 4),
+                    ..self
+                }
+            }
+        }
     }
 
     // -- Perspective correction -----------------------------------------------
@@ -302,8 +564,8 @@ pub fn correct_perspective(self) -> Self {
         // upscaling). Fall back to the original image size if paper-based
         // pixels would be larger.
         let (paper_w_mm, paper_h_mm) = self.paper_size.dimensions_mm();
-        let paper_w_px = (paper_w_mm as f32 * 300.0 / 25.4).round() as u32;
-        let paper_h_px = (paper_h_mm as f32 * 300.0 / 25.4).round() as u32;
+        let paper_w_px = (paper_w_mm * 300.0 / 25.4).round() as u32;
+        let paper_h_px = (paper_h_mm * 300.0 / 25.4).round() as u32;
         let out_w = paper_w_px.min(orig_w);
         let out_h = paper_h_px.min(orig_h);
 
@@ -340,9 +602,141 @@ pub fn correct_perspective(self) -> Self {
 
         info!(out_w, out_h, "Perspective correction applied");
 
+        // The document now spans the paper width, which fixes its resolution.
+        let dpi = (out_w as f32 * 25.4 / paper_w_mm).round() as u32;
         Self {
             image: DynamicImage::ImageRgba8(output),
             paper_size: self.paper_size,
+            dpi: Some(dpi.max(1)),
+        }
+    }
+
+    // -- Cleanup --------------------------------------------------------------
+
+    /// Paint over binder punch holes along `edge` with the paper colour.
+    ///
+    /// A hole is a dark, round blob of a punch hole's size lying wholly
+    /// within 25 mm of the edge, so text, rules and anything reaching further
+    /// into the page are left alone.  Sizes are worked out from the scan's
+    /// DPI or, failing that, from fitting the page to the paper size.
+    #[instrument(skip(self))]
+    pub fn remove_punch_holes(self, edge: Edge) -> Self {
+        let gray = self.image.to_luma8();
+        let threshold = otsu_threshold(&gray);
+        let px_per_mm = self.pixels_per_mm();
+        let band = MarginBand::new(
+            gray.width(),
+            gray.height(),
+            edge,
+            (PUNCH_HOLE_MARGIN_MM * px_per_mm).round() as u32,
+        );
+
+        let holes = find_punch_holes(&gray, threshold, &band, px_per_mm);
+        if holes.is_empty() {
+            debug!(?edge, "No punch holes found");
+            return self;
+        }
+
+        let mut rgba = self.image.to_rgba8();
+        let paper = paper_colour(&rgba, &gray, threshold, &band);
+        // Grow each hole a little to cover its soft, grey rim.
+        let rim = (0.5 * px_per_mm).max(1.0);
+        for hole in &holes {
+            fill_disc(&mut rgba, hole.centre(), hole.radius() + rim, paper);
+        }
+        info!(count = holes.len(), ?edge, "Removed punch holes");
+        self.with_pixels(rgba)
+    }
+
+    /// Brighten the shadow a book's spine casts along `edge`.
+    ///
+    /// Models the falloff as the paper brightness of each line parallel to
+    /// the edge, across the outer 35% of the page, and scales every line up
+    /// to the brightness of the page interior.  Text keeps its contrast
+    /// against the paper, so it binarizes cleanly right up to the spine.
+    #[instrument(skip(self))]
+    pub fn remove_edge_shadow(self, edge: Edge) -> Self {
+        let gray = self.image.to_luma8();
+        let (width, height) = gray.dimensions();
+        let lines = match edge {
+            Edge::Left | Edge::Right => width,
+            Edge::Top | Edge::Bottom => height,
+        };
+        let depth = (lines as f32 * EDGE_SHADOW_DEPTH).round() as usize;
+        if depth == 0 || depth >= lines as usize {
+            return self;
+        }
+
+        // Paper brightness of each line, from the edge inwards.
+        let profile: Vec<f32> = (0..lines)
+            .map(|d| paper_level(line_pixels(&gray, edge, d)))
+            .collect();
+        let mut interior = profile[depth..].to_vec();
+        interior.sort_by(f32::total_cmp);
+        let reference = interior[interior.len() / 2];
+
+        let smoothed = moving_average(&profile[..depth], (lines as usize / 100).max(1));
+        let gains: Vec<f32> = smoothed
+            .iter()
+            .map(|&level| (reference / level.max(1.0)).clamp(1.0, MAX_SHADOW_GAIN))
+            .collect();
+        if gains.iter().all(|&gain| gain < 1.01) {
+            debug!(?edge, "No edge shadow found");
+            return self;
+        }
+
+        let mut rgba = self.image.to_rgba8();
+        for (x, y, pixel) in rgba.enumerate_pixels_mut() {
+            let d = match edge {
+                Edge::Left => x,
+                Edge::Right => width - 1 - x,
+                Edge::Top => y,
+                Edge::Bottom => height - 1 - y,
+            } as usize;
+            if let Some(&gain) = gains.get(d) {
+                for channel in &mut pixel.0[..3] {
+                    *channel = (*channel as f32 * gain).round().min(255.0) as u8;
+                }
+            }
+        }
+        info!(
+            ?edge,
+            reference,
+            max_gain = gains.iter().copied().fold(1.0, f32::max),
+            "Removed edge shadow"
+        );
+        self.with_pixels(rgba)
+    }
+
+    /// Replace the working image with `rgba`, keeping the pixel layout of
+    /// greyscale and RGB images.
+    fn with_pixels(self, rgba: RgbaImage) -> Self {
+        let image = match &self.image {
+            DynamicImage::ImageLuma8(_) => {
+                DynamicImage::ImageLuma8(DynamicImage::ImageRgba8(rgba).to_luma8())
+            }
+            DynamicImage::ImageRgb8(_) => {
+                DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(rgba).to_rgb8())
+            }
+            _ => DynamicImage::ImageRgba8(rgba),
+        };
+        Self {
+            image,
+            paper_size: self.paper_size,
+            dpi: self.dpi,
+        }
+    }
+
+    /// Scan resolution in pixels per millimetre: from the DPI if known,
+    /// otherwise assuming the image's short side spans the paper's.
+    fn pixels_per_mm(&self) -> f32 {
+        match self.dpi {
+            Some(dpi) => dpi as f32 / 25.4,
+            None => {
+                let (paper_w, paper_h) = self.paper_size.dimensions_mm();
+                let short_px = self.image.width().min(self.image.height());
+                short_px as f32 / paper_w.min(paper_h).max(1.0)
+            }
         }
     }
 
@@ -350,17 +744,49 @@ pub fn correct_perspective(self) -> Self {
 
     /// Convert the (possibly enhanced) scan image to a print-ready PDF.
     ///
-    /// The image is encoded as PNG, then embedded in a single-page PDF sized to
-    /// the configured paper size.
-    #[instrument(skip(self))]
+    /// Black-and-white images (such as the output of [`binarize`](Self::binarize))
+    /// are embedded as PNG; anything else as JPEG at quality 85. Use
+    /// [`scan_to_pdf_with`](Self::scan_to_pdf_with) to choose explicitly.
     pub fn scan_to_pdf(&self) -> Result<Vec<u8>, PresswerkError> {
-        info!(paper = ?self.paper_size, "Converting scan to PDF");
+        let format = if is_bilevel(&self.image) {
+            ScanImageFormat::Png
+        } else {
+            ScanImageFormat::Jpeg {
+                quality: DEFAULT_JPEG_QUALITY,
+            }
+        };
+        self.scan_to_pdf_with(format)
+    }
 
-        let png_bytes = ImageProcessor::from_dynamic(self.image.clone()).to_png_bytes()?;
+    /// Convert the scan image to a single-page PDF sized to the configured
+    /// paper size, compressing the image with `format`.
+    ///
+    /// With a resolution set via [`with_dpi`](Self::with_dpi) the image is
+    /// drawn at its physical size (shrunk only if it exceeds the margins).
+    #[instrument(skip(self))]
+    pub fn scan_to_pdf_with(&self, format: ScanImageFormat) -> Result<Vec<u8>, PresswerkError> {
+        info!(paper = ?self.paper_size, ?format, "Converting scan to PDF");
 
+        let mut processor = ImageProcessor::from_dynamic(self.image.clone());
         let mut writer = PdfWriter::new(self.paper_size);
         writer.set_title("Presswerk Scan");
-        let pdf_bytes = writer.create_from_image(&png_bytes)?;
+        if let Some(dpi) = self.dpi {
+            processor = processor.with_dpi(dpi);
+            writer.set_image_dpi(dpi);
+        }
+
+        let pdf_bytes = match format {
+            ScanImageFormat::Png => writer.create_from_image(
+                &processor.to_png_bytes()?,
+                Orientation::Portrait,
+                Fit::Contain,
+            )?,
+            ScanImageFormat::Jpeg { quality } => writer.create_from_jpeg(
+                &processor.to_jpeg_bytes(quality.clamp(1, 100))?,
+                Orientation::Portrait,
+                Fit::Contain,
+            )?,
+        };
 
         debug!(pdf_bytes = pdf_bytes.len(), "Scan-to-PDF complete");
         Ok(pdf_bytes)
@@ -375,6 +801,104 @@ pub fn enhance_and_convert(self) -> Result<Vec<u8>, PresswerkError> {
     }
 }
 
+/// Whether the image is greyscale with only pure black and white pixels.
+fn is_bilevel(image: &DynamicImage) -> bool {
+    image
+        .as_luma8()
+        .is_some_and(|gray| gray.pixels().all(|p| matches!(p.0[0], 0 | 255)))
+}
+
+// -- Content detection helpers ------------------------------------------------
+
+/// What [`ScanEnhancer::detect_content`] measures on a scan.
+struct ContentStats {
+    /// Mean of `max(r, g, b) - min(r, g, b)` over all pixels.
+    chroma: f32,
+    /// Share of pixels darker than 80 or lighter than 175.
+    bilevel_share: f32,
+    /// Share of pixels whose luminance differs from the next pixel across or
+    /// down by more than 64.
+    edge_share: f32,
+}
+
+impl ContentStats {
+    fn of(image: &DynamicImage) -> Self {
+        let thumbnail = image.thumbnail(ANALYSIS_SIZE, ANALYSIS_SIZE).to_rgb8();
+        let gray = DynamicImage::ImageRgb8(thumbnail.clone()).to_luma8();
+        let (width, height) = gray.dimensions();
+        let total = (width as f32 * height as f32).max(1.0);
+
+        let chroma_sum: u64 = thumbnail
+            .pixels()
+            .map(|p| {
+                let [r, g, b] = p.0;
+                u64::from(r.max(g).max(b) - r.min(g).min(b))
+            })
+            .sum();
+        let bilevel = gray
+            .pixels()
+            .filter(|p| p.0[0] < 80 || p.0[0] > 175)
+            .count();
+        let edges = gray
+            .enumerate_pixels()
+            .filter(|&(x, y, p)| {
+                let here = i32::from(p.0[0]);
+                let right = (x + 1 < width).then(|| i32::from(gray.get_pixel(x + 1, y).0[0]));
+                let below = (y + 1 < height).then(|| i32::from(gray.get_pixel(x, y + 1).0[0]));
+                [right, below]
+                    .into_iter()
+                    .flatten()
+                    .any(|next| (next - here).abs() > 64)
+            })
+            .count();
+
+        Self {
+            chroma: chroma_sum as f32 / total,
+            bilevel_share: bilevel as f32 / total,
+            edge_share: edges as f32 / total,
+        }
+    }
+}
+
+/// Stretch the luminance range of `image` to the full 0-255, clipping
+/// [`LEVEL_CLIP`] of the pixels at each end.  Colours keep their hue.
+fn auto_level(image: &DynamicImage) -> image::RgbImage {
+    let mut rgb = image.to_rgb8();
+    let histogram = luminance_histogram(&image.to_luma8());
+    let total: u64 = histogram.iter().sum();
+    let clip = (total as f32 * LEVEL_CLIP) as u64;
+
+    let mut seen = 0;
+    let low = histogram
+        .iter()
+        .position(|&count| {
+            seen += count;
+            seen > clip
+        })
+        .unwrap_or(0);
+    seen = 0;
+    let high = 255
+        - histogram
+            .iter()
+            .rev()
+            .position(|&count| {
+                seen += count;
+                seen > clip
+            })
+            .unwrap_or(0);
+
+    if high <= low {
+        return rgb;
+    }
+    let scale = 255.0 / (high - low) as f32;
+    for pixel in rgb.pixels_mut() {
+        for channel in pixel.0.iter_mut() {
+            *channel = ((f32::from(*channel) - low as f32) * scale).clamp(0.0, 255.0) as u8;
+        }
+    }
+    rgb
+}
+
 // -- Integral image helpers ---------------------------------------------------
 
 /// Compute the integral (summed-area table) of a grayscale image.
@@ -437,11 +961,7 @@ fn region_mean(
 /// Finds the threshold value that minimises the intra-class variance of the
 /// black and white pixel groups.
 fn otsu_threshold(gray: &GrayImage) -> u8 {
-    // Build histogram.
-    let mut histogram = [0u64; 256];
-    for pixel in gray.pixels() {
-        histogram[pixel.0[0] as usize] += 1;
-    }
+    let histogram = luminance_histogram(gray);
 
     let total_pixels = gray.width() as u64 * gray.height() as u64;
     if total_pixels == 0 {
@@ -485,6 +1005,250 @@ fn otsu_threshold(gray: &GrayImage) -> u8 {
     best_threshold
 }
 
+/// Count the pixels at each grey level.
+fn luminance_histogram(gray: &GrayImage) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    histogram
+}
+
+/// Black where `gray` is darker than `threshold`, white elsewhere.
+fn global_threshold(gray: &GrayImage, threshold: u8) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    GrayImage::from_fn(width, height, |x, y| {
+        let val = gray.get_pixel(x, y).0[0];
+        Luma([if val < threshold { 0u8 } else { 255u8 }])
+    })
+}
+
+// -- Punch hole helpers -------------------------------------------------------
+
+/// The strip along one edge of the page searched for punch holes, as
+/// half-open pixel ranges.
+#[derive(Debug)]
+struct MarginBand {
+    edge: Edge,
+    x: std::ops::Range<u32>,
+    y: std::ops::Range<u32>,
+}
+
+impl MarginBand {
+    fn new(width: u32, height: u32, edge: Edge, depth: u32) -> Self {
+        let (x, y) = match edge {
+            Edge::Left => (0..depth.min(width), 0..height),
+            Edge::Right => (width.saturating_sub(depth)..width, 0..height),
+            Edge::Top => (0..width, 0..depth.min(height)),
+            Edge::Bottom => (0..width, height.saturating_sub(depth)..height),
+        };
+        Self { edge, x, y }
+    }
+
+    fn contains(&self, x: u32, y: u32) -> bool {
+        self.x.contains(&x) && self.y.contains(&y)
+    }
+
+    /// Whether (`x`, `y`) lies on the band's side facing into the page.
+    fn on_inner_side(&self, x: u32, y: u32) -> bool {
+        match self.edge {
+            Edge::Left => x + 1 == self.x.end,
+            Edge::Right => x == self.x.start,
+            Edge::Top => y + 1 == self.y.end,
+            Edge::Bottom => y == self.y.start,
+        }
+    }
+}
+
+/// A connected patch of dark pixels.
+#[derive(Debug)]
+struct Blob {
+    pixels: u32,
+    min: (u32, u32),
+    max: (u32, u32),
+    /// Whether the blob reaches the band's inner side, i.e. carries on into
+    /// the page.
+    spills_inward: bool,
+}
+
+impl Blob {
+    fn size(&self) -> (f32, f32) {
+        (
+            (self.max.0 - self.min.0 + 1) as f32,
+            (self.max.1 - self.min.1 + 1) as f32,
+        )
+    }
+
+    fn centre(&self) -> (f32, f32) {
+        (
+            (self.min.0 + self.max.0) as f32 / 2.0,
+            (self.min.1 + self.max.1) as f32 / 2.0,
+        )
+    }
+
+    fn radius(&self) -> f32 {
+        let (w, h) = self.size();
+        w.max(h) / 2.0
+    }
+
+    /// Whether the blob has a punch hole's size and round shape.
+    fn is_punch_hole(&self, px_per_mm: f32) -> bool {
+        let (w, h) = self.size();
+        let (min_d, max_d) = (
+            PUNCH_HOLE_DIAMETER_MM.0 * px_per_mm,
+            PUNCH_HOLE_DIAMETER_MM.1 * px_per_mm,
+        );
+        let fill = self.pixels as f32 / (w * h);
+        !self.spills_inward
+            && (min_d..=max_d).contains(&w)
+            && (min_d..=max_d).contains(&h)
+            && w.max(h) / w.min(h) <= PUNCH_HOLE_MAX_ASPECT
+            && (PUNCH_HOLE_FILL.0..=PUNCH_HOLE_FILL.1).contains(&fill)
+    }
+}
+
+/// Label the dark (Otsu `threshold` or below) pixels in `band` into 8-connected
+/// blobs and keep those shaped like punch holes.
+fn find_punch_holes(
+    gray: &GrayImage,
+    threshold: u8,
+    band: &MarginBand,
+    px_per_mm: f32,
+) -> Vec<Blob> {
+    let is_dark = |x: u32, y: u32| gray.get_pixel(x, y).0[0] <= threshold;
+    let band_width = band.x.len();
+    let mut seen = vec![false; band_width * band.y.len()];
+    let index =
+        |x: u32, y: u32| (y - band.y.start) as usize * band_width + (x - band.x.start) as usize;
+
+    let mut holes = Vec::new();
+    let mut stack = Vec::new();
+    for y in band.y.clone() {
+        for x in band.x.clone() {
+            if seen[index(x, y)] || !is_dark(x, y) {
+                continue;
+            }
+            seen[index(x, y)] = true;
+            stack.push((x, y));
+            let mut blob = Blob {
+                pixels: 0,
+                min: (x, y),
+                max: (x, y),
+                spills_inward: false,
+            };
+            while let Some((px, py)) = stack.pop() {
+                blob.pixels += 1;
+                blob.min = (blob.min.0.min(px), blob.min.1.min(py));
+                blob.max = (blob.max.0.max(px), blob.max.1.max(py));
+                blob.spills_inward |= band.on_inner_side(px, py);
+                for (dx, dy) in [
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (-1, 0),
+                    (1, 0),
+                    (-1, 1),
+                    (0, 1),
+                    (1, 1),
+                ] {
+                    let (nx, ny) = (px.wrapping_add_signed(dx), py.wrapping_add_signed(dy));
+                    if band.contains(nx, ny) && !seen[index(nx, ny)] && is_dark(nx, ny) {
+                        seen[index(nx, ny)] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            if blob.is_punch_hole(px_per_mm) {
+                debug!(centre = ?blob.centre(), radius = blob.radius(), "Punch hole found");
+                holes.push(blob);
+            }
+        }
+    }
+    holes
+}
+
+/// Average colour of the light (paper) pixels in `band`; white if there are
+/// none.
+fn paper_colour(rgba: &RgbaImage, gray: &GrayImage, threshold: u8, band: &MarginBand) -> Rgba<u8> {
+    let mut sum = [0u64; 4];
+    let mut count = 0u64;
+    for y in band.y.clone() {
+        for x in band.x.clone() {
+            if gray.get_pixel(x, y).0[0] > threshold {
+                for (total, channel) in sum.iter_mut().zip(rgba.get_pixel(x, y).0) {
+                    *total += u64::from(channel);
+                }
+                count += 1;
+            }
+        }
+    }
+    if count == 0 {
+        return Rgba([255, 255, 255, 255]);
+    }
+    Rgba(sum.map(|total| (total / count) as u8))
+}
+
+/// Paint every pixel within `radius` of `centre` with `colour`.
+fn fill_disc(rgba: &mut RgbaImage, centre: (f32, f32), radius: f32, colour: Rgba<u8>) {
+    let (cx, cy) = centre;
+    let x_range =
+        (cx - radius).floor().max(0.0) as u32..((cx + radius).ceil() as u32 + 1).min(rgba.width());
+    let y_range =
+        (cy - radius).floor().max(0.0) as u32..((cy + radius).ceil() as u32 + 1).min(rgba.height());
+    for y in y_range {
+        for x in x_range.clone() {
+            let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                rgba.put_pixel(x, y, colour);
+            }
+        }
+    }
+}
+
+// -- Edge shadow helpers ------------------------------------------------------
+
+/// The pixels of the line parallel to `edge`, `distance` lines in from it.
+fn line_pixels(gray: &GrayImage, edge: Edge, distance: u32) -> Box<dyn Iterator<Item = u8> + '_> {
+    let (width, height) = gray.dimensions();
+    let pixel = move |x, y| gray.get_pixel(x, y).0[0];
+    match edge {
+        Edge::Left => Box::new((0..height).map(move |y| pixel(distance, y))),
+        Edge::Right => Box::new((0..height).map(move |y| pixel(width - 1 - distance, y))),
+        Edge::Top => Box::new((0..width).map(move |x| pixel(x, distance))),
+        Edge::Bottom => Box::new((0..width).map(move |x| pixel(x, height - 1 - distance))),
+    }
+}
+
+/// Brightness of the paper among `pixels`: their [`PAPER_PERCENTILE`]th
+/// percentile.
+fn paper_level(pixels: impl Iterator<Item = u8>) -> f32 {
+    let mut histogram = [0u32; 256];
+    let mut count = 0u32;
+    for value in pixels {
+        histogram[value as usize] += 1;
+        count += 1;
+    }
+    let target = (count as f32 * PAPER_PERCENTILE).ceil() as u32;
+    let mut seen = 0;
+    for (level, &n) in histogram.iter().enumerate() {
+        seen += n;
+        if seen >= target.max(1) {
+            return level as f32;
+        }
+    }
+    255.0
+}
+
+/// Average of each value with its `radius` neighbours on either side.
+fn moving_average(values: &[f32], radius: usize) -> Vec<f32> {
+    (0..values.len())
+        .map(|i| {
+            let window = &values[i.saturating_sub(radius)..(i + radius + 1).min(values.len())];
+            window.iter().sum::<f32>() / window.len() as f32
+        })
+        .collect()
+}
+
 // -- Perspective correction helpers -------------------------------------------
 
 /// Which document edge a line corresponds to.
@@ -614,6 +1378,7 @@ fn intersect_polar_lines(a: &PolarLine, b: &PolarLine) -> Option<(f32, f32)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pdf::ImageData;
     use image::{GrayImage, Luma, RgbaImage};
 
     /// Verify that `correct_perspective` on a blank image does not panic and
@@ -632,6 +1397,30 @@ fn correct_perspective_blank_image_returns_unchanged() {
         assert_eq!(out.height(), 300);
     }
 
+    #[test]
+    fn luminance_histogram_counts_every_pixel() {
+        let img = DynamicImage::ImageLuma8(GrayImage::from_fn(37, 23, |x, y| {
+            Luma([((x * 7 + y * 3) % 256) as u8])
+        }));
+        let enhancer = ScanEnhancer::from_dynamic(img, PaperSize::A4);
+
+        let histogram = enhancer.luminance_histogram();
+        assert_eq!(histogram.iter().sum::<u64>(), 37 * 23);
+    }
+
+    #[test]
+    fn binarize_at_cuts_exactly_at_the_threshold() {
+        let gray = GrayImage::from_fn(256, 4, |x, _| Luma([x as u8]));
+        let enhancer =
+            ScanEnhancer::from_dynamic(DynamicImage::ImageLuma8(gray.clone()), PaperSize::A4);
+
+        let out = enhancer.binarize_at(100).into_dynamic().to_luma8();
+        for (original, binary) in gray.pixels().zip(out.pixels()) {
+            let expected = if original.0[0] < 100 { 0 } else { 255 };
+            assert_eq!(binary.0[0], expected, "luminance {}", original.0[0]);
+        }
+    }
+
     /// Verify that `correct_perspective` on a small RGBA image does not panic.
     #[test]
     fn correct_perspective_small_rgba_no_panic() {
@@ -642,6 +1431,132 @@ fn correct_perspective_small_rgba_no_panic() {
         let _result = enhancer.correct_perspective();
     }
 
+    /// A scan exported to PDF can be loaded back at its original size.
+    #[test]
+    fn from_pdf_page_extracts_embedded_image() {
+        let img = DynamicImage::ImageLuma8(GrayImage::from_fn(300, 400, |x, y| {
+            Luma([((x + y) % 256) as u8])
+        }));
+        let pdf = ScanEnhancer::from_dynamic(img, PaperSize::A4)
+            .scan_to_pdf()
+            .unwrap();
+
+        let enhancer = ScanEnhancer::from_pdf_page(&pdf, 1, PaperSize::A4).unwrap();
+        assert_eq!(enhancer.as_dynamic().width(), 300);
+        assert_eq!(enhancer.as_dynamic().height(), 400);
+    }
+
+    /// Image data embedded by the default `scan_to_pdf`.
+    fn embedded_image(enhancer: &ScanEnhancer) -> ImageData {
+        let pdf = enhancer.scan_to_pdf().unwrap();
+        let mut images = PdfReader::from_bytes(&pdf)
+            .unwrap()
+            .extract_images(1)
+            .unwrap();
+        images.remove(0).data
+    }
+
+    /// Embedding a colour photo as JPEG beats the lossless PNG path by a
+    /// wide margin.
+    #[test]
+    fn scan_to_pdf_jpeg_is_smaller_than_png_for_colour() {
+        // Smooth gradients with a little sensor-like noise, as in a photo.
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(800, 600, |x, y| {
+            let noise = ((x.wrapping_mul(7919) ^ y.wrapping_mul(104_729)) % 13) as u8;
+            image::Rgb([
+                (x * 255 / 800) as u8 / 2 + noise,
+                (y * 255 / 600) as u8 / 2 + noise,
+                ((x + y) % 256) as u8 / 2 + noise,
+            ])
+        }));
+        let enhancer = ScanEnhancer::from_dynamic(img, PaperSize::A4);
+
+        let png = enhancer.scan_to_pdf_with(ScanImageFormat::Png).unwrap();
+        let jpeg = enhancer
+            .scan_to_pdf_with(ScanImageFormat::Jpeg { quality: 85 })
+            .unwrap();
+        assert!(
+            jpeg.len() * 3 < png.len(),
+            "JPEG {} bytes vs PNG {} bytes",
+            jpeg.len(),
+            png.len()
+        );
+        // The default picks JPEG for colour.
+        assert!(matches!(embedded_image(&enhancer), ImageData::Jpeg(_)));
+
+        let reread = ScanEnhancer::from_pdf_page(&jpeg, 1, PaperSize::A4).unwrap();
+        assert_eq!(reread.as_dynamic().width(), 800);
+    }
+
+    /// Binarized scans default to lossless PNG.
+    #[test]
+    fn scan_to_pdf_defaults_to_png_for_black_and_white() {
+        let img = DynamicImage::ImageLuma8(GrayImage::from_fn(64, 64, |x, _| {
+            Luma([if x % 8 < 4 { 0 } else { 255 }])
+        }));
+        assert!(is_bilevel(&img));
+        let enhancer = ScanEnhancer::from_dynamic(img, PaperSize::A4);
+        assert!(matches!(embedded_image(&enhancer), ImageData::Raw(_)));
+    }
+
+    /// A text-only page has nothing to enhance.
+    #[test]
+    fn from_pdf_page_rejects_vector_only_page() {
+        let pdf = PdfWriter::a4()
+            .create_from_text(
+                "No pictures here",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+        let err = ScanEnhancer::from_pdf_page(&pdf, 1, PaperSize::A4)
+            .err()
+            .expect("vector-only page must fail");
+        assert!(matches!(err, PresswerkError::ImageError(_)), "{err}");
+    }
+
+    /// A6 labels and other custom sizes give pages of that size, for scans
+    /// and for text alike.
+    #[test]
+    fn custom_paper_size_sets_the_page_size() {
+        use lopdf::{Document, Object};
+
+        let a6 = PaperSize::Custom {
+            width_mm: 105.0,
+            height_mm: 148.0,
+        };
+        let img = DynamicImage::ImageLuma8(GrayImage::from_pixel(105, 148, Luma([90u8])));
+        let scan = ScanEnhancer::from_dynamic(img, a6).scan_to_pdf().unwrap();
+        let text = PdfWriter::new(a6)
+            .create_from_text(
+                "Label",
+                PdfWriter::TEXT_FONT_SIZE,
+                PdfWriter::TEXT_MARGIN_MM,
+            )
+            .unwrap();
+
+        for pdf in [scan, text] {
+            let doc = Document::load_mem(&pdf).unwrap();
+            let page = doc.get_dictionary(doc.get_pages()[&1]).unwrap();
+            // The MediaBox may be inherited from the page tree.
+            let media_box = page.get(b"MediaBox").or_else(|_| {
+                let parent = page.get(b"Parent").and_then(Object::as_reference)?;
+                doc.get_dictionary(parent)?.get(b"MediaBox")
+            });
+            let media_box: Vec<f32> = media_box
+                .and_then(Object::as_array)
+                .unwrap()
+                .iter()
+                .map(|v| v.as_float().unwrap())
+                .collect();
+            let [x0, y0, x1, y1] = media_box[..] else {
+                panic!("MediaBox {media_box:?}");
+            };
+            assert!(((x1 - x0) - 105.0 * 72.0 / 25.4).abs() < 1.0);
+            assert!(((y1 - y0) - 148.0 * 72.0 / 25.4).abs() < 1.0);
+        }
+    }
+
     /// Verify the shoelace area computation for a known rectangle.
     #[test]
     fn shoelace_area_rectangle() {
@@ -746,4 +1661,180 @@ fn correct_perspective_synthetic_rectangle() {
         assert!(result.as_dynamic().width() > 0);
         assert!(result.as_dynamic().height() > 0);
     }
+
+    /// Black text lines on white paper are binarized.
+    #[test]
+    fn auto_binarizes_high_contrast_text() {
+        // Rows of "glyphs": short dark strokes with gaps, on slightly grey paper.
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(600, 800, |x, y| {
+            let in_line = y % 24 < 12;
+            let in_glyph = x % 9 < 5 && (x / 45) % 6 != 5;
+            if in_line && in_glyph && (40..560).contains(&x) {
+                image::Rgb([20, 20, 25])
+            } else {
+                image::Rgb([235, 233, 228])
+            }
+        }));
+        let enhancer = ScanEnhancer::from_dynamic(img, PaperSize::A4);
+        assert_eq!(enhancer.detect_content(), ScanContent::Document);
+
+        let out = enhancer.auto().into_dynamic();
+        assert!(
+            is_bilevel(&out),
+            "text scan should come out black and white"
+        );
+    }
+
+    /// A smooth colour gradient keeps its colours and gets its levels
+    /// stretched.
+    #[test]
+    fn auto_treats_a_smooth_gradient_as_a_photo() {
+        // Washed-out gradient between luminance ~70 and ~170.
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(400, 300, |x, y| {
+            image::Rgb([
+                (60 + x * 120 / 400) as u8,
+                (80 + y * 100 / 300) as u8,
+                (170 - x * 90 / 400) as u8,
+            ])
+        }));
+        let before = luminance_histogram(&img.to_luma8());
+        let enhancer = ScanEnhancer::from_dynamic(img, PaperSize::A4);
+        assert_eq!(enhancer.detect_content(), ScanContent::Photo);
+
+        let out = enhancer.auto().into_dynamic();
+        assert!(!is_bilevel(&out));
+        assert!(out.as_rgb8().is_some(), "photo should stay in colour");
+        let chroma = ContentStats::of(&out).chroma;
+        assert!(chroma > MONOCHROME_CHROMA, "colours lost: chroma {chroma}");
+
+        // Levels now span (nearly) the full range.
+        let span = |histogram: &[u64; 256]| {
+            let low = histogram.iter().position(|&c| c > 0).unwrap();
+            let high = 255 - histogram.iter().rev().position(|&c| c > 0).unwrap();
+            high - low
+        };
+        let after = luminance_histogram(&out.to_luma8());
+        assert!(span(&after) > span(&before) + 60, "levels not stretched");
+    }
+
+    // -- Punch holes ------------------------------------------------------------
+
+    /// Draw a filled disc of `value` into `gray`.
+    fn draw_disc(gray: &mut GrayImage, centre: (u32, u32), radius: u32, value: u8) {
+        let r = radius as i64;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy <= r * r {
+                    let (x, y) = (centre.0 as i64 + dx, centre.1 as i64 + dy);
+                    gray.put_pixel(x as u32, y as u32, Luma([value]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn remove_punch_holes_clears_the_margin_but_not_the_content() {
+        // 600 px across A4's 210 mm: about 2.9 px/mm, so 6 mm holes are
+        // 17 px wide with their centres 12 mm (34 px) in.
+        let mut gray = GrayImage::from_pixel(600, 848, Luma([240u8]));
+        for y in [200, 424, 648] {
+            draw_disc(&mut gray, (34, y), 8, 20);
+        }
+        // A hole-sized dot in the middle of the page and a rule in the
+        // margin are content.
+        draw_disc(&mut gray, (300, 424), 8, 20);
+        for x in 20..60 {
+            for y in 100..104 {
+                gray.put_pixel(x, y, Luma([20]));
+            }
+        }
+
+        let out = ScanEnhancer::from_dynamic(DynamicImage::ImageLuma8(gray), PaperSize::A4)
+            .remove_punch_holes(Edge::Left)
+            .into_dynamic()
+            .to_luma8();
+
+        for y in [200, 424, 648] {
+            for (dx, dy) in [(0, 0), (8, 0), (0, 8), (-8, 0), (0, -8)] {
+                let (x, y) = ((34 + dx) as u32, (y + dy) as u32);
+                assert_eq!(out.get_pixel(x, y).0[0], 240, "hole pixel at ({x}, {y})");
+            }
+        }
+        assert_eq!(out.get_pixel(300, 424).0[0], 20, "central dot removed");
+        assert_eq!(out.get_pixel(40, 102).0[0], 20, "margin rule removed");
+    }
+
+    #[test]
+    fn remove_punch_holes_only_searches_the_given_edge() {
+        let mut gray = GrayImage::from_pixel(600, 848, Luma([240u8]));
+        draw_disc(&mut gray, (34, 424), 8, 20);
+
+        let out = ScanEnhancer::from_dynamic(DynamicImage::ImageLuma8(gray), PaperSize::A4)
+            .remove_punch_holes(Edge::Right)
+            .into_dynamic()
+            .to_luma8();
+        assert_eq!(out.get_pixel(34, 424).0[0], 20);
+    }
+
+    // -- Edge shadow ------------------------------------------------------------
+
+    /// A 400x560 page at brightness 230 whose left 100 columns darken to 90
+    /// at the spine, with lines of dark text across it.
+    fn spine_shadowed_page() -> GrayImage {
+        GrayImage::from_fn(400, 560, |x, y| {
+            if y % 40 < 4 && (10..390).contains(&x) {
+                return Luma([20]);
+            }
+            let paper = if x < 100 {
+                90.0 + 140.0 * x as f32 / 100.0
+            } else {
+                230.0
+            };
+            Luma([paper.round() as u8])
+        })
+    }
+
+    /// Mean brightness of the paper (non-text) pixels of column `x`.
+    fn paper_brightness(gray: &GrayImage, x: u32) -> f32 {
+        let paper: Vec<f32> = (0..gray.height())
+            .filter(|y| y % 40 >= 4)
+            .map(|y| gray.get_pixel(x, y).0[0] as f32)
+            .collect();
+        paper.iter().sum::<f32>() / paper.len() as f32
+    }
+
+    #[test]
+    fn remove_edge_shadow_flattens_the_spine_gradient() {
+        let page = spine_shadowed_page();
+        let interior = paper_brightness(&page, 300);
+        assert!(interior - paper_brightness(&page, 5) > 100.0);
+
+        let out = ScanEnhancer::from_dynamic(DynamicImage::ImageLuma8(page), PaperSize::A4)
+            .remove_edge_shadow(Edge::Left)
+            .into_dynamic()
+            .to_luma8();
+
+        for x in [2, 5, 25, 50, 75] {
+            let brightness = paper_brightness(&out, x);
+            assert!(
+                (brightness - interior).abs() < 15.0,
+                "column {x}: {brightness} vs interior {interior}"
+            );
+        }
+        assert_eq!(paper_brightness(&out, 300), interior);
+        // Text near the spine stays dark enough to binarize.
+        assert!(out.get_pixel(20, 2).0[0] < 100);
+    }
+
+    #[test]
+    fn remove_edge_shadow_leaves_an_evenly_lit_page_alone() {
+        let page = GrayImage::from_fn(200, 280, |x, y| {
+            Luma([if (x + y) % 17 == 0 { 20 } else { 230 }])
+        });
+        let out = ScanEnhancer::from_dynamic(DynamicImage::ImageLuma8(page.clone()), PaperSize::A4)
+            .remove_edge_shadow(Edge::Right)
+            .into_dynamic()
+            .to_luma8();
+        assert_eq!(out, page);
+    }
 }
@@ -2,14 +2,19 @@
 // Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
 //
 // Scanning pipeline — binarization, contrast enhancement, scan-to-PDF conversion,
-// and optical character recognition (OCR).
+// barcode reading and optical character recognition (OCR).  Resumable
+// multi-page sessions are kept by `presswerk-print`, next to the document
+// store holding their pages, and re-exported here.
 
+pub mod barcode;
 pub mod enhance;
 
 #[cfg(feature = "ocr")]
 pub mod ocr;
 
-pub use enhance::ScanEnhancer;
+pub use barcode::{Barcode, BarcodeKind, BoundingBox, detect_barcodes};
+pub use enhance::{Edge, ScanContent, ScanEnhancer, ScanImageFormat};
+pub use presswerk_print::scan_session::{PageAssembler, ScanSession, ScanSessionInfo};
 
 #[cfg(feature = "ocr")]
 pub use ocr::OcrEngine;
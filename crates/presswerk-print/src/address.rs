@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Printer addresses: which to prefer, how to write them into URIs, and how to
+// connect when a printer has several.
+//
+// Printers announce A and AAAA records over mDNS, so one printer often has an
+// IPv4 address, a routable IPv6 address and an IPv6 link-local (`fe80::/10`)
+// address.  A link-local address is only meaningful together with the
+// interface it was seen on (its zone, `fe80::1%wlan0`).  mDNS does not tell us
+// which interface that was, so a link-local address is tried on every local
+// interface that has one of its own.  Zones cannot be written into URIs that
+// HTTP clients accept, so printer URIs use a routable address or the
+// printer's mDNS hostname instead.
+//
+// Connecting follows "happy eyeballs" (RFC 8305): attempts start in
+// preference order, each `CONNECTION_ATTEMPT_DELAY` after the previous one
+// unless an earlier attempt has already failed, and whichever connects first
+// wins.
+
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6, TcpStream};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use ipnet::{IpNet, Ipv4Net};
+use tracing::debug;
+
+/// How long to wait for an attempt before starting the next one in
+/// parallel (RFC 8305 recommends 250 ms).
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Rank of an address; lower is preferred.
+///
+/// IPv4 first, as printers' IPv4 stacks are the best tested; then routable
+/// IPv6 (global and unique-local); then link-local addresses of either
+/// family, which need a zone (IPv6) or are a DHCP fallback (IPv4
+/// `169.254/16`).
+pub fn preference(ip: &IpAddr) -> u8 {
+    match ip {
+        IpAddr::V4(v4) if v4.is_link_local() => 3,
+        IpAddr::V4(_) => 0,
+        IpAddr::V6(v6) if is_ipv6_link_local(v6) => 2,
+        IpAddr::V6(v6) if v6.to_ipv4_mapped().is_some() => 0,
+        IpAddr::V6(_) => 1,
+    }
+}
+
+/// Sort `addresses` by [`preference`], keeping the order of equally ranked
+/// ones, and drop repeats.
+pub fn sort_by_preference(addresses: &mut Vec<IpAddr>) {
+    addresses.sort_by_key(preference);
+    let mut seen = Vec::with_capacity(addresses.len());
+    addresses.retain(|ip| {
+        let first = !seen.contains(ip);
+        seen.push(*ip);
+        first
+    });
+}
+
+/// Whether `ip` is an IPv6 link-local address (`fe80::/10`).
+pub fn is_ipv6_link_local(ip: &Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xffc0 == 0xfe80
+}
+
+/// Whether `ip` is in a range used on local networks: IPv4 private
+/// (RFC 1918) or link-local, IPv6 unique-local (`fc00::/7`) or link-local.
+pub fn is_local_network(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_link_local(),
+        IpAddr::V6(v6) => v6.segments()[0] & 0xfe00 == 0xfc00 || is_ipv6_link_local(v6),
+    }
+}
+
+/// The addresses of this device's non-loopback network interfaces.
+pub fn interface_addresses() -> Vec<IpAddr> {
+    match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces
+            .iter()
+            .filter(|iface| !iface.is_loopback())
+            .map(|iface| iface.ip())
+            .collect(),
+        Err(e) => {
+            debug!(error = %e, "cannot list network interfaces");
+            Vec::new()
+        }
+    }
+}
+
+/// The IPv4 subnets of this device's non-loopback interfaces, for probing
+/// when mDNS finds nothing.  A subnet wider than a /24 is narrowed to the
+/// /24 around the device's own address: probing more takes minutes and
+/// looks like a port scan to network monitoring.
+pub fn local_subnets() -> Vec<IpNet> {
+    let interfaces = match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            debug!(error = %e, "cannot list network interfaces");
+            return Vec::new();
+        }
+    };
+    let mut subnets: Vec<IpNet> = interfaces
+        .iter()
+        .filter(|iface| !iface.is_loopback())
+        .filter_map(|iface| match &iface.addr {
+            if_addrs::IfAddr::V4(v4) => Ipv4Net::with_netmask(v4.ip, v4.netmask).ok(),
+            if_addrs::IfAddr::V6(_) => None,
+        })
+        .map(|net| match net.prefix_len() {
+            0..24 => Ipv4Net::new(net.addr(), 24).expect("24 is a valid prefix"),
+            _ => net,
+        })
+        .map(|net| IpNet::V4(net.trunc()))
+        .collect();
+    subnets.sort_unstable();
+    subnets.dedup();
+    subnets
+}
+
+/// Interface indices that have an IPv6 link-local address of their own —
+/// the zones a link-local peer can be reached through.
+pub fn link_local_zones() -> Vec<u32> {
+    let interfaces = match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            debug!(error = %e, "cannot list network interfaces");
+            return Vec::new();
+        }
+    };
+    let mut zones: Vec<u32> = interfaces
+        .iter()
+        .filter(|iface| !iface.is_loopback())
+        .filter(|iface| matches!(iface.ip(), IpAddr::V6(v6) if is_ipv6_link_local(&v6)))
+        .filter_map(|iface| iface.index)
+        .collect();
+    zones.sort_unstable();
+    zones.dedup();
+    zones
+}
+
+/// The socket addresses to try for `ip`:`port`, given the local link-local
+/// `zones` (see [`link_local_zones`]).
+///
+/// A link-local IPv6 address gives one candidate per zone, or none if the
+/// device has no IPv6 link; any other address gives itself.
+pub fn socket_addrs(ip: IpAddr, port: u16, zones: &[u32]) -> Vec<SocketAddr> {
+    match ip {
+        IpAddr::V6(v6) if is_ipv6_link_local(&v6) => zones
+            .iter()
+            .map(|&zone| SocketAddr::V6(SocketAddrV6::new(v6, port, 0, zone)))
+            .collect(),
+        _ => vec![SocketAddr::new(ip, port)],
+    }
+}
+
+/// The host part of a URI for `ip`, IPv6 in brackets.
+///
+/// `None` for an IPv6 link-local address: its zone cannot go into a URI
+/// that HTTP clients accept (RFC 6874 zone IDs are rejected by `url`), so
+/// such a printer is addressed by name or another address instead.
+pub fn uri_host(ip: IpAddr) -> Option<String> {
+    match ip {
+        IpAddr::V4(v4) => Some(v4.to_string()),
+        IpAddr::V6(v6) if is_ipv6_link_local(&v6) => None,
+        IpAddr::V6(v6) => Some(format!("[{v6}]")),
+    }
+}
+
+/// The host to put into a printer URI: the first of `addresses` (best
+/// first, see [`sort_by_preference`]) that can be written into a URI, else
+/// the printer's mDNS `hostname` (e.g. `printer.local.`).
+///
+/// `None` if the printer only has link-local IPv6 addresses and no name.
+pub fn uri_host_for(addresses: &[IpAddr], hostname: Option<&str>) -> Option<String> {
+    addresses.iter().find_map(|&ip| uri_host(ip)).or_else(|| {
+        hostname
+            .map(|name| name.trim_end_matches('.'))
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+    })
+}
+
+/// Connect to whichever of `candidates` answers first, starting attempts in
+/// order with [`CONNECTION_ATTEMPT_DELAY`] between them.
+///
+/// Gives up after `timeout`, or once every attempt has failed, returning the
+/// last error.
+pub fn connect_first(
+    candidates: &[SocketAddr],
+    timeout: Duration,
+) -> io::Result<(SocketAddr, TcpStream)> {
+    if candidates.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "no address to connect to",
+        ));
+    }
+
+    let deadline = Instant::now() + timeout;
+    let (sender, receiver) = mpsc::channel();
+    let mut started = 0;
+    let mut failed = 0;
+    let mut last_error = None;
+
+    loop {
+        // Start the next attempt: at first, after each delay, and as soon
+        // as an earlier one fails.
+        if started < candidates.len() {
+            let addr = candidates[started];
+            let sender = sender.clone();
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            std::thread::spawn(move || {
+                let result =
+                    TcpStream::connect_timeout(&addr, remaining.max(Duration::from_millis(1)));
+                // The receiver is gone once another attempt has won.
+                let _ = sender.send((addr, result));
+            });
+            started += 1;
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        let wait = if started < candidates.len() {
+            CONNECTION_ATTEMPT_DELAY.min(deadline - now)
+        } else {
+            deadline - now
+        };
+
+        match receiver.recv_timeout(wait) {
+            Ok((addr, Ok(stream))) => {
+                debug!(%addr, "connected");
+                return Ok((addr, stream));
+            }
+            Ok((addr, Err(e))) => {
+                debug!(%addr, error = %e, "connection attempt failed");
+                last_error = Some(e);
+                failed += 1;
+                if failed == candidates.len() {
+                    break;
+                }
+            }
+            // Next attempt due; `sender` is still held here, so the channel
+            // cannot disconnect.
+            Err(_) => {}
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| io::Error::new(ErrorKind::TimedOut, "connection timed out")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn addresses_sort_ipv4_then_routable_ipv6_then_link_local() {
+        let mut addresses: Vec<IpAddr> = [
+            "fe80::1c2:3ff:fe45:6789",
+            "2001:db8::20",
+            "169.254.10.20",
+            "192.168.1.20",
+            "fd12:3456::20",
+            "192.168.1.20",
+        ]
+        .iter()
+        .map(|a| a.parse().unwrap())
+        .collect();
+
+        sort_by_preference(&mut addresses);
+        let sorted: Vec<String> = addresses.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            sorted,
+            [
+                "192.168.1.20",
+                "2001:db8::20",
+                "fd12:3456::20",
+                "fe80::1c2:3ff:fe45:6789",
+                "169.254.10.20",
+            ]
+        );
+    }
+
+    #[test]
+    fn link_local_addresses_carry_their_zone() {
+        let link_local: IpAddr = "fe80::1".parse().unwrap();
+        let global: IpAddr = "2001:db8::1".parse().unwrap();
+
+        let candidates = socket_addrs(link_local, 631, &[2, 3]);
+        assert_eq!(
+            candidates
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["[fe80::1%2]:631", "[fe80::1%3]:631"]
+        );
+        assert!(socket_addrs(link_local, 631, &[]).is_empty());
+        assert_eq!(socket_addrs(global, 631, &[2]).len(), 1);
+    }
+
+    #[test]
+    fn uri_hosts_never_carry_a_zone() {
+        let link_local: IpAddr = "fe80::1".parse().unwrap();
+        let global: IpAddr = "2001:db8::1".parse().unwrap();
+        let v4: IpAddr = "10.0.0.5".parse().unwrap();
+
+        assert_eq!(uri_host(link_local), None);
+        assert_eq!(uri_host(global).as_deref(), Some("[2001:db8::1]"));
+        assert_eq!(uri_host(v4).as_deref(), Some("10.0.0.5"));
+
+        assert_eq!(
+            uri_host_for(&[link_local, global], Some("printer.local.")).as_deref(),
+            Some("[2001:db8::1]")
+        );
+        assert_eq!(
+            uri_host_for(&[link_local], Some("printer.local.")).as_deref(),
+            Some("printer.local")
+        );
+        assert_eq!(uri_host_for(&[link_local], None), None);
+    }
+
+    #[test]
+    fn first_address_that_answers_wins() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap();
+        // A port nothing listens on: refused straight away.
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let (addr, _stream) = connect_first(&[closed, open], Duration::from_secs(5)).unwrap();
+        assert_eq!(addr, open);
+
+        assert!(connect_first(&[closed], Duration::from_secs(5)).is_err());
+        assert!(connect_first(&[], Duration::from_secs(5)).is_err());
+    }
+}
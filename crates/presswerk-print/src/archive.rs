@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Minimal in-memory zip writer for diagnostic bundles.
+//
+// Supports exactly what the bundle exporter needs: a handful of small files,
+// deflate-compressed, no directories, no zip64, no encryption.  Timestamps
+// are written as the DOS epoch (1980-01-01) so identical inputs produce
+// byte-identical archives.
+
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::Crc;
+use flate2::write::DeflateEncoder;
+
+use presswerk_core::error::{PresswerkError, Result};
+
+const LOCAL_HEADER_SIG: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIG: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIR_SIG: u32 = 0x0605_4b50;
+
+/// "Version needed to extract": 2.0, the baseline for deflate.
+const VERSION: u16 = 20;
+/// General-purpose flag bit 11: file names are UTF-8.
+const FLAG_UTF8: u16 = 1 << 11;
+const METHOD_DEFLATE: u16 = 8;
+/// MS-DOS date for 1980-01-01 (time is 00:00:00).
+const DOS_EPOCH_DATE: u16 = (1 << 5) | 1;
+
+/// Central-directory record kept until the archive is finished.
+struct Entry {
+    name: String,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Builds a zip archive in memory.
+#[derive(Default)]
+pub(crate) struct ZipBuilder {
+    buf: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+impl ZipBuilder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a file named `name` with the given contents.
+    pub(crate) fn add_file(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let mut crc = Crc::new();
+        crc.update(data);
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+
+        let entry = Entry {
+            name: name.to_string(),
+            crc: crc.sum(),
+            compressed_size: to_u32(compressed.len())?,
+            size: to_u32(data.len())?,
+            offset: to_u32(self.buf.len())?,
+        };
+
+        put_u32(&mut self.buf, LOCAL_HEADER_SIG);
+        put_u16(&mut self.buf, VERSION);
+        put_u16(&mut self.buf, FLAG_UTF8);
+        put_u16(&mut self.buf, METHOD_DEFLATE);
+        put_u16(&mut self.buf, 0); // mod time
+        put_u16(&mut self.buf, DOS_EPOCH_DATE);
+        put_u32(&mut self.buf, entry.crc);
+        put_u32(&mut self.buf, entry.compressed_size);
+        put_u32(&mut self.buf, entry.size);
+        put_u16(&mut self.buf, to_u16(name.len())?);
+        put_u16(&mut self.buf, 0); // extra field length
+        self.buf.extend_from_slice(name.as_bytes());
+        self.buf.extend_from_slice(&compressed);
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Write the central directory and return the finished archive.
+    pub(crate) fn finish(mut self) -> Result<Vec<u8>> {
+        let central_start = to_u32(self.buf.len())?;
+
+        for entry in &self.entries {
+            put_u32(&mut self.buf, CENTRAL_HEADER_SIG);
+            put_u16(&mut self.buf, VERSION); // version made by
+            put_u16(&mut self.buf, VERSION); // version needed
+            put_u16(&mut self.buf, FLAG_UTF8);
+            put_u16(&mut self.buf, METHOD_DEFLATE);
+            put_u16(&mut self.buf, 0);
+            put_u16(&mut self.buf, DOS_EPOCH_DATE);
+            put_u32(&mut self.buf, entry.crc);
+            put_u32(&mut self.buf, entry.compressed_size);
+            put_u32(&mut self.buf, entry.size);
+            put_u16(&mut self.buf, to_u16(entry.name.len())?);
+            put_u16(&mut self.buf, 0); // extra field length
+            put_u16(&mut self.buf, 0); // comment length
+            put_u16(&mut self.buf, 0); // disk number start
+            put_u16(&mut self.buf, 0); // internal attributes
+            put_u32(&mut self.buf, 0); // external attributes
+            put_u32(&mut self.buf, entry.offset);
+            self.buf.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let central_size = to_u32(self.buf.len())? - central_start;
+        let count = to_u16(self.entries.len())?;
+
+        put_u32(&mut self.buf, END_OF_CENTRAL_DIR_SIG);
+        put_u16(&mut self.buf, 0); // this disk
+        put_u16(&mut self.buf, 0); // disk with central directory
+        put_u16(&mut self.buf, count);
+        put_u16(&mut self.buf, count);
+        put_u32(&mut self.buf, central_size);
+        put_u32(&mut self.buf, central_start);
+        put_u16(&mut self.buf, 0); // comment length
+
+        Ok(self.buf)
+    }
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn to_u16(n: usize) -> Result<u16> {
+    u16::try_from(n).map_err(|_| PresswerkError::Io(std::io::Error::other("zip field too large")))
+}
+
+fn to_u32(n: usize) -> Result<u32> {
+    u32::try_from(n).map_err(|_| PresswerkError::Io(std::io::Error::other("zip archive too large")))
+}
+
+/// Read back an archive produced by [`ZipBuilder`] as `(name, contents)`
+/// pairs, verifying each CRC.
+#[cfg(test)]
+pub(crate) fn read_entries(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+    use std::io::Read;
+
+    let u16_at = |pos: usize| u16::from_le_bytes([archive[pos], archive[pos + 1]]);
+    let u32_at = |pos: usize| {
+        u32::from_le_bytes([
+            archive[pos],
+            archive[pos + 1],
+            archive[pos + 2],
+            archive[pos + 3],
+        ])
+    };
+
+    let mut entries = Vec::new();
+    let mut pos = 0;
+    while u32_at(pos) == LOCAL_HEADER_SIG {
+        let crc = u32_at(pos + 14);
+        let compressed_size = u32_at(pos + 18) as usize;
+        let name_len = u16_at(pos + 26) as usize;
+        let extra_len = u16_at(pos + 28) as usize;
+        let name_start = pos + 30;
+        let data_start = name_start + name_len + extra_len;
+
+        let name = String::from_utf8(archive[name_start..name_start + name_len].to_vec())
+            .expect("utf-8 name");
+        let mut data = Vec::new();
+        flate2::read::DeflateDecoder::new(&archive[data_start..data_start + compressed_size])
+            .read_to_end(&mut data)
+            .expect("inflate entry");
+
+        let mut check = Crc::new();
+        check.update(&data);
+        assert_eq!(check.sum(), crc, "CRC mismatch for {name}");
+
+        entries.push((name, data));
+        pos = data_start + compressed_size;
+    }
+    assert_eq!(
+        u32_at(pos),
+        CENTRAL_HEADER_SIG,
+        "central directory follows entries"
+    );
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_entries() {
+        let mut zip = ZipBuilder::new();
+        zip.add_file("a.txt", b"hello").unwrap();
+        zip.add_file("b.json", &[b'x'; 4096]).unwrap();
+        let bytes = zip.finish().unwrap();
+
+        let entries = read_entries(&bytes);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], ("a.txt".to_string(), b"hello".to_vec()));
+        assert_eq!(entries[1].1.len(), 4096);
+        // End-of-central-directory record closes the archive.
+        assert_eq!(
+            &bytes[bytes.len() - 22..bytes.len() - 18],
+            &[0x50, 0x4b, 0x05, 0x06]
+        );
+    }
+}
@@ -5,12 +5,24 @@
 //
 // Queries Get-Printer-Attributes to determine what the printer actually
 // supports, then validates and auto-corrects user settings to match.
+//
+// Before any query, a first estimate comes from the printer's mDNS TXT record
+// (see `from_txt`): the IPP Everywhere and AirPrint keys already say whether
+// it prints colour and duplex and which document formats it takes.
+//
+// `from_ipp_response` reads the typed values of a Get-Printer-Attributes
+// response, so each member of a `1setOf` attribute stays a value of its own;
+// `from_attributes` has to split values flattened to strings.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
+use ipp::prelude::{DelimiterTag, IppAttributes, IppRequestResponse, IppValue};
 use tracing::{debug, info};
 
-use presswerk_core::types::{DuplexMode, PaperSize, PrintSettings};
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::{
+    DocumentType, DuplexMode, MEDIA_TOLERANCE_MM, PaperSize, PrintSettings,
+};
 
 use crate::ipp_client::{IppClient, PrinterAttributes};
 
@@ -27,44 +39,236 @@ pub struct PrinterCapabilities {
     pub document_formats_supported: HashSet<String>,
     /// Maximum copies the printer supports (0 = unknown).
     pub max_copies: u32,
+    /// Whether the printer tries to handle documents it does not list in
+    /// `document-format-supported` (`pdl-override-supported`).
+    pub pdl_override: PdlOverride,
+    /// Options the fields above do not cover (trays, finishing, ...); see
+    /// [`vendor_options`](Self::vendor_options).
+    pub vendor_options: Vec<VendorOption>,
+}
+
+/// A printer option outside the standard set, taken from a `*-supported`
+/// attribute this module does not otherwise interpret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VendorOption {
+    /// Job attribute name, i.e. the `*-supported` name without the suffix
+    /// (e.g. "media-source").
+    pub name: String,
+    /// Values the printer accepts, in the order it listed them.
+    pub values: Vec<String>,
 }
 
+/// The printer's `pdl-override-supported` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PdlOverride {
+    /// `attempted` — the printer makes a best effort with whatever it is sent.
+    Attempted,
+    /// `not-attempted` — documents in other formats are rejected or misprinted.
+    NotAttempted,
+    /// Attribute absent or unrecognised.
+    #[default]
+    Unknown,
+}
+
+/// How a document has to be prepared before it is sent to a printer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatPlan {
+    /// The printer accepts the document as it is.
+    SendAsIs,
+    /// The document must be rendered to this raster format first.
+    Rasterize(DocumentType),
+}
+
+/// Raster formats a document can be rendered to, in order of preference.
+const RASTER_FALLBACKS: [DocumentType; 2] = [DocumentType::PwgRaster, DocumentType::Jpeg];
+
+/// `*-supported` attributes that are not vendor options: those parsed into
+/// fields of [`PrinterCapabilities`], and those describing the printer or
+/// the protocol rather than something a job can choose.
+const NON_OPTION_ATTRIBUTES: &[&str] = &[
+    "media-supported",
+    "sides-supported",
+    "color-supported",
+    "document-format-supported",
+    "copies-supported",
+    "pdl-override-supported",
+    "operations-supported",
+    "ipp-versions-supported",
+    "ipp-features-supported",
+    "charset-supported",
+    "generated-natural-language-supported",
+    "uri-authentication-supported",
+    "uri-security-supported",
+    "compression-supported",
+    "job-creation-attributes-supported",
+    "which-jobs-supported",
+    "printer-settable-attributes-supported",
+    "multiple-document-jobs-supported",
+    "multiple-operation-time-out-action-supported",
+];
+
 impl PrinterCapabilities {
     /// Parse capabilities from raw IPP printer attributes.
     pub fn from_attributes(attrs: &PrinterAttributes) -> Self {
-        let media_supported = parse_set(attrs.get("media-supported"));
-        let sides_supported = parse_set(attrs.get("sides-supported"));
-        let document_formats_supported =
-            parse_set(attrs.get("document-format-supported"));
+        Self::from_values(&split_attributes(attrs))
+    }
+
+    /// Parse capabilities from a Get-Printer-Attributes response.
+    ///
+    /// Reads the printer attributes group: every member of a `1setOf`
+    /// attribute (`media-supported`, `sides-supported`, ...) becomes a value
+    /// of its own, `color-supported` is read as a boolean and
+    /// `copies-supported` as a range.
+    pub fn from_ipp_response(response: &IppRequestResponse) -> Self {
+        Self::from_values(&response_values(response.attributes()))
+    }
+
+    /// Build capabilities from attribute values, each attribute's values in
+    /// the order the printer listed them.
+    fn from_values(values: &HashMap<String, Vec<String>>) -> Self {
+        let set = |name: &str| -> HashSet<String> {
+            values.get(name).into_iter().flatten().cloned().collect()
+        };
+        let first = |name: &str| values.get(name).and_then(|v| v.first());
 
         // Default to true (assume colour) when attribute is absent — same
         // "unknown = assume yes" pattern used for media and sides.
-        let color_supported = attrs
-            .get("color-supported")
+        let color_supported = first("color-supported")
             .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
             .unwrap_or(true);
 
-        let max_copies = attrs
-            .get("copies-supported")
+        let max_copies = first("copies-supported")
             .and_then(|v| {
                 // Usually "1-999" range format
                 v.split('-').next_back().and_then(|n| n.trim().parse().ok())
             })
             .unwrap_or(0);
 
+        let pdl_override = match first("pdl-override-supported").map(|v| v.trim()) {
+            Some("attempted") => PdlOverride::Attempted,
+            Some("not-attempted") => PdlOverride::NotAttempted,
+            _ => PdlOverride::Unknown,
+        };
+
         Self {
-            media_supported,
+            media_supported: set("media-supported"),
+            sides_supported: set("sides-supported"),
+            color_supported,
+            document_formats_supported: set("document-format-supported"),
+            max_copies,
+            pdl_override,
+            vendor_options: vendor_options_from(values),
+        }
+    }
+
+    /// Estimate capabilities from an IPP Everywhere / AirPrint mDNS TXT
+    /// record, before the printer has been queried.
+    ///
+    /// Keys are matched case-insensitively:
+    ///   - `pdl`    — supported document formats, comma-separated
+    ///   - `Color`  — "T" or "F"
+    ///   - `Duplex` — "T" or "F"
+    ///   - `URF`    — Apple Raster support, e.g. "V1.4,CP99,DM1,SRGB24"
+    ///
+    /// A `URF` entry adds `image/urf` to the formats, gives the maximum
+    /// copies, and decides colour and duplex when their own keys are missing.
+    ///
+    /// A missing key leaves its field unknown (empty set, `max_copies` 0),
+    /// except colour, which a TXT record without `Color` or an sRGB URF
+    /// entry does not claim.
+    pub fn from_txt(txt: &HashMap<String, String>) -> Self {
+        let get = |key: &str| {
+            txt.iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.trim())
+        };
+
+        let mut document_formats_supported: HashSet<String> = get("pdl")
+            .map(parse_list)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+
+        let urf: Vec<&str> = get("URF")
+            .filter(|v| !v.eq_ignore_ascii_case("none"))
+            .map(|v| v.split(',').map(str::trim).collect())
+            .unwrap_or_default();
+        if !urf.is_empty() {
+            document_formats_supported.insert("image/urf".into());
+        }
+        let urf_value = |prefix: &str| {
+            urf.iter()
+                .find_map(|entry| entry.strip_prefix(prefix))
+                .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        };
+
+        let color_supported = match get("Color") {
+            Some(v) => txt_flag(v),
+            None => urf
+                .iter()
+                .any(|entry| entry.starts_with("SRGB") || entry.starts_with("ADOBERGB")),
+        };
+
+        let duplex = match get("Duplex") {
+            Some(v) => Some(txt_flag(v)),
+            None => urf_value("DM").map(|modes| modes != "0"),
+        };
+        let sides_supported: HashSet<String> = match duplex {
+            Some(true) => [
+                DuplexMode::Simplex,
+                DuplexMode::LongEdge,
+                DuplexMode::ShortEdge,
+            ]
+            .iter()
+            .map(|mode| mode.ipp_sides_keyword().to_string())
+            .collect(),
+            Some(false) => HashSet::from([DuplexMode::Simplex.ipp_sides_keyword().to_string()]),
+            None => HashSet::new(),
+        };
+
+        let max_copies = urf_value("CP")
+            .and_then(|copies| copies.parse().ok())
+            .unwrap_or(0);
+
+        debug!(
+            formats = document_formats_supported.len(),
+            color_supported,
+            duplex = ?duplex,
+            "capabilities estimated from TXT record"
+        );
+
+        Self {
+            media_supported: HashSet::new(),
             sides_supported,
             color_supported,
             document_formats_supported,
             max_copies,
+            pdl_override: PdlOverride::Unknown,
+            vendor_options: Vec::new(),
         }
     }
 
+    /// Whether the printer is known to print on both sides.
+    pub fn supports_two_sided(&self) -> bool {
+        self.sides_supported
+            .iter()
+            .any(|sides| sides.starts_with("two-sided"))
+    }
+
+    /// Collect the `*-supported` attributes that do not map to a known field
+    /// as generic options, sorted by name.
+    ///
+    /// Boolean and range-valued attributes (e.g. `page-ranges-supported`,
+    /// `copies-supported`) describe limits rather than choices and are left
+    /// out, as are attributes with no values.
+    pub fn vendor_options(attrs: &PrinterAttributes) -> Vec<VendorOption> {
+        vendor_options_from(&split_attributes(attrs))
+    }
+
     /// Query a printer's capabilities via IPP.
-    pub async fn query(client: &IppClient) -> Result<Self, presswerk_core::error::PresswerkError> {
-        let attrs = client.get_printer_attributes().await?;
-        Ok(Self::from_attributes(&attrs))
+    pub async fn query(client: &IppClient) -> Result<Self> {
+        let response = client.get_printer_attributes_response().await?;
+        Ok(Self::from_ipp_response(&response))
     }
 
     /// Whether the printer supports a given paper size.
@@ -72,7 +276,56 @@ pub fn supports_media(&self, paper: &PaperSize) -> bool {
         if self.media_supported.is_empty() {
             return true; // unknown capabilities = assume yes
         }
-        self.media_supported.contains(paper.ipp_media_keyword())
+        self.media_keyword(paper).is_some()
+    }
+
+    /// The printer's own `media` keyword for `paper`: the one it lists for
+    /// a sheet of the same dimensions, so that e.g. a custom 105×148 mm
+    /// size is sent as the printer's `iso_a6_105x148mm`.  A sheet it does
+    /// not list but that falls within its `custom_min_`/`custom_max_` range
+    /// is sent as a custom size such as `custom_100x150mm_100x150mm`.
+    pub fn media_keyword(&self, paper: &PaperSize) -> Option<String> {
+        let own = paper.to_ipp_media_keyword();
+        if self.media_supported.contains(&own) {
+            return Some(own);
+        }
+        sorted(&self.media_supported)
+            .into_iter()
+            .find(|keyword| {
+                PaperSize::from_ipp_media_keyword(keyword)
+                    .is_some_and(|size| size.same_size_as(paper))
+            })
+            .or_else(|| {
+                let (width_mm, height_mm) = paper.dimensions_mm();
+                self.custom_range_contains(width_mm, height_mm).then(|| {
+                    PaperSize::Custom {
+                        width_mm,
+                        height_mm,
+                    }
+                    .to_ipp_media_keyword()
+                })
+            })
+    }
+
+    /// Whether a `width_mm`×`height_mm` sheet lies within the custom size
+    /// range the printer advertises with `custom_min_` and `custom_max_`
+    /// entries in `media-supported`.
+    fn custom_range_contains(&self, width_mm: f32, height_mm: f32) -> bool {
+        let bound = |prefix: &str| {
+            sorted(&self.media_supported)
+                .into_iter()
+                .find_map(|keyword| {
+                    PaperSize::from_ipp_media_keyword(keyword.strip_prefix(prefix)?)
+                        .map(|size| size.dimensions_mm())
+                })
+        };
+        let (Some((min_w, min_h)), Some((max_w, max_h))) =
+            (bound("custom_min_"), bound("custom_max_"))
+        else {
+            return false;
+        };
+        (min_w - MEDIA_TOLERANCE_MM..=max_w + MEDIA_TOLERANCE_MM).contains(&width_mm)
+            && (min_h - MEDIA_TOLERANCE_MM..=max_h + MEDIA_TOLERANCE_MM).contains(&height_mm)
     }
 
     /// Whether the printer supports a given duplex mode.
@@ -90,6 +343,156 @@ pub fn supports_format(&self, mime_type: &str) -> bool {
         }
         self.document_formats_supported.contains(mime_type)
     }
+
+    /// Check `settings` against what the printer supports, without changing
+    /// anything.
+    ///
+    /// Reports every colour, sides and media request the printer does not
+    /// list, so they can be shown before the job is sent.  Unknown
+    /// capabilities count as supported, as do one-sided jobs.
+    pub fn validate(
+        &self,
+        settings: &PrintSettings,
+    ) -> std::result::Result<(), Vec<CapabilityMismatch>> {
+        let mut mismatches = Vec::new();
+
+        if settings.color && !self.color_supported {
+            mismatches.push(CapabilityMismatch::Color);
+        }
+        if settings.duplex != DuplexMode::Simplex && !self.supports_sides(&settings.duplex) {
+            mismatches.push(CapabilityMismatch::Sides {
+                requested: settings.duplex.ipp_sides_keyword().into(),
+                supported: sorted(&self.sides_supported),
+            });
+        }
+        if !self.supports_media(&settings.paper_size) {
+            mismatches.push(CapabilityMismatch::Media {
+                requested: settings.paper_size.to_ipp_media_keyword(),
+                supported: sorted(&self.media_supported),
+            });
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            debug!(?mismatches, "print settings exceed printer capabilities");
+            Err(mismatches)
+        }
+    }
+
+    /// Things worth telling the user before sending a document, none of
+    /// which stop it printing.
+    ///
+    /// `document_has_color` describes the document itself (see
+    /// `PdfReader::has_color`), not the colour setting: a colour page sent
+    /// to a black-and-white printer loses its colour either way.
+    pub fn print_warnings(&self, document_has_color: bool) -> Vec<PrintWarning> {
+        let mut warnings = Vec::new();
+        if document_has_color && !self.color_supported {
+            warnings.push(PrintWarning::ColorLost);
+        }
+        warnings
+    }
+
+    /// Decide whether a document can be sent as-is or must be rasterized.
+    ///
+    /// Documents in a listed format (or any format, when the printer
+    /// auto-senses `application/octet-stream`) are sent unchanged, as are
+    /// documents for printers whose `pdl-override-supported` is `attempted`.
+    /// Otherwise the first supported raster format is chosen, preferring PWG
+    /// Raster over JPEG. Fails if the printer accepts neither.
+    pub fn format_plan(&self, document_type: DocumentType) -> Result<FormatPlan> {
+        let mime_type = document_type.mime_type();
+        if self.supports_format(mime_type) || self.supports_format("application/octet-stream") {
+            return Ok(FormatPlan::SendAsIs);
+        }
+        if self.pdl_override == PdlOverride::Attempted {
+            debug!(
+                mime_type,
+                "format not listed, printer will attempt it anyway"
+            );
+            return Ok(FormatPlan::SendAsIs);
+        }
+
+        RASTER_FALLBACKS
+            .into_iter()
+            .find(|target| *target != document_type && self.supports_format(target.mime_type()))
+            .map(FormatPlan::Rasterize)
+            .ok_or_else(|| {
+                let mut formats: Vec<&str> = self
+                    .document_formats_supported
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                formats.sort_unstable();
+                PresswerkError::UnsupportedDocument(format!(
+                    "printer cannot print {} and accepts no raster format (supports: {})",
+                    mime_type,
+                    formats.join(", ")
+                ))
+            })
+    }
+}
+
+/// A requested setting the printer does not support, as reported by
+/// [`PrinterCapabilities::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapabilityMismatch {
+    /// Colour was asked of a black-and-white printer.
+    Color,
+    /// The printer does not list the requested `sides` keyword.
+    Sides {
+        requested: String,
+        /// The `sides-supported` values, sorted.
+        supported: Vec<String>,
+    },
+    /// The printer does not list the requested media keyword.
+    Media {
+        requested: String,
+        /// The `media-supported` values, sorted.
+        supported: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for CapabilityMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Color => write!(f, "This printer only prints in black and white."),
+            Self::Sides { supported, .. } => {
+                if supported.iter().any(|s| s.starts_with("two-sided")) {
+                    write!(f, "This printer can't flip two-sided pages on that edge.")
+                } else {
+                    write!(f, "This printer only prints one-sided.")
+                }
+            }
+            Self::Media { requested, .. } => {
+                write!(f, "This printer doesn't take {requested} paper.")
+            }
+        }
+    }
+}
+
+/// Something about a print the user may want to know before sending it,
+/// as reported by [`PrinterCapabilities::print_warnings`].  The job can
+/// still be sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrintWarning {
+    /// The document has colour and the printer prints in black and white.
+    ColorLost,
+    /// The printer said it would not accept the job's settings when asked
+    /// with Validate-Job.
+    SettingsRejected,
+}
+
+impl std::fmt::Display for PrintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ColorLost => write!(f, "This printer prints in black & white."),
+            Self::SettingsRejected => {
+                write!(f, "This printer may not accept these print settings.")
+            }
+        }
+    }
 }
 
 /// A notice about a setting that was auto-corrected.
@@ -236,25 +639,108 @@ fn find_closest_media(
 
     candidates
         .iter()
-        .filter(|c| supported.contains(c.ipp_media_keyword()))
-        .min_by_key(|c| {
-            let (w, h) = c.dimensions_mm();
-            let area = w * h;
-            (area as i64 - req_area as i64).unsigned_abs()
+        .filter(|c| {
+            supported.iter().any(|keyword| {
+                PaperSize::from_ipp_media_keyword(keyword).is_some_and(|size| size.same_size_as(c))
+            })
+        })
+        .min_by(|a, b| {
+            let distance = |c: &PaperSize| {
+                let (w, h) = c.dimensions_mm();
+                (w * h - req_area).abs()
+            };
+            distance(a).total_cmp(&distance(b))
         })
         .copied()
 }
 
-/// Parse a comma-separated or multi-valued IPP attribute into a HashSet.
-fn parse_set(value: Option<&String>) -> HashSet<String> {
-    match value {
-        Some(v) => v
-            .split([',', ';'])
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect(),
-        None => HashSet::new(),
+/// The values of `set` in sorted order.
+fn sorted(set: &HashSet<String>) -> Vec<String> {
+    let mut values: Vec<String> = set.iter().cloned().collect();
+    values.sort_unstable();
+    values
+}
+
+/// The vendor-specific options among `values`; see
+/// [`PrinterCapabilities::vendor_options`].
+fn vendor_options_from(values: &HashMap<String, Vec<String>>) -> Vec<VendorOption> {
+    let mut options: Vec<VendorOption> = values
+        .iter()
+        .filter(|(name, _)| !NON_OPTION_ATTRIBUTES.contains(&name.as_str()))
+        .filter_map(|(name, values)| {
+            let name = name.strip_suffix("-supported")?;
+            let is_choice = !values.is_empty()
+                && !values
+                    .iter()
+                    .all(|v| v == "true" || v == "false" || is_range(v));
+            is_choice.then(|| VendorOption {
+                name: name.to_string(),
+                values: values.clone(),
+            })
+        })
+        .collect();
+    options.sort_by(|a, b| a.name.cmp(&b.name));
+    debug!(count = options.len(), "vendor options found");
+    options
+}
+
+/// Flattened attributes with each value split into its members.
+fn split_attributes(attrs: &PrinterAttributes) -> HashMap<String, Vec<String>> {
+    attrs
+        .iter()
+        .map(|(name, value)| (name.clone(), parse_list(value)))
+        .collect()
+}
+
+/// The printer attributes of a response, each with its values as strings:
+/// one per member of a `1setOf`, ranges as "min-max", booleans as
+/// "true"/"false".  Repeated values are dropped.
+fn response_values(attrs: &IppAttributes) -> HashMap<String, Vec<String>> {
+    let mut values = HashMap::new();
+    for group in attrs.groups_of(DelimiterTag::PrinterAttributes) {
+        for (name, attr) in group.attributes() {
+            let members = match attr.value() {
+                IppValue::Array(members) => members.as_slice(),
+                single => std::slice::from_ref(single),
+            };
+            let mut strings: Vec<String> = Vec::with_capacity(members.len());
+            for member in members {
+                let string = match member {
+                    IppValue::RangeOfInteger { min, max } => format!("{min}-{max}"),
+                    other => other.to_string(),
+                };
+                if !strings.contains(&string) {
+                    strings.push(string);
+                }
+            }
+            values.insert(name.clone(), strings);
+        }
+    }
+    values
+}
+
+/// Split a multi-valued attribute into its values, keeping their order and
+/// dropping repeats.
+fn parse_list(value: &str) -> Vec<String> {
+    let mut values: Vec<String> = Vec::new();
+    for v in value.split([',', ';']).map(str::trim) {
+        if !v.is_empty() && !values.iter().any(|seen| seen == v) {
+            values.push(v.to_string());
+        }
     }
+    values
+}
+
+/// Read a boolean TXT record value.  IPP Everywhere uses "T"/"F".
+pub(crate) fn txt_flag(value: &str) -> bool {
+    value.eq_ignore_ascii_case("t") || value.eq_ignore_ascii_case("true")
+}
+
+/// Whether `value` is an integer range such as "1-999".
+fn is_range(value: &str) -> bool {
+    value.split_once('-').is_some_and(|(lo, hi)| {
+        lo.trim().parse::<i64>().is_ok() && hi.trim().parse::<i64>().is_ok()
+    })
 }
 
 #[cfg(test)]
@@ -306,6 +792,122 @@ fn duplex_corrected_on_simplex_printer() {
         assert_eq!(result.corrections[0].field, "Duplex");
     }
 
+    #[test]
+    fn validate_reports_duplex_on_simplex_printer() {
+        let mut attrs = HashMap::new();
+        attrs.insert("sides-supported".into(), "one-sided".into());
+        let caps = PrinterCapabilities::from_attributes(&attrs);
+
+        let settings = PrintSettings {
+            duplex: DuplexMode::LongEdge,
+            ..PrintSettings::default()
+        };
+
+        assert_eq!(
+            caps.validate(&settings),
+            Err(vec![CapabilityMismatch::Sides {
+                requested: "two-sided-long-edge".into(),
+                supported: vec!["one-sided".into()],
+            }])
+        );
+    }
+
+    #[test]
+    fn validate_passes_supported_settings_and_lists_every_mismatch() {
+        let caps = test_caps();
+        let supported = PrintSettings {
+            paper_size: PaperSize::Letter,
+            duplex: DuplexMode::LongEdge,
+            color: true,
+            ..PrintSettings::default()
+        };
+        assert_eq!(caps.validate(&supported), Ok(()));
+
+        let mut bw = caps.clone();
+        bw.color_supported = false;
+        let unsupported = PrintSettings {
+            paper_size: PaperSize::A3,
+            duplex: DuplexMode::ShortEdge,
+            ..supported
+        };
+        let mismatches = bw.validate(&unsupported).unwrap_err();
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(mismatches[0], CapabilityMismatch::Color);
+        assert_eq!(
+            mismatches[2].to_string(),
+            "This printer doesn't take iso_a3_297x420mm paper."
+        );
+    }
+
+    #[test]
+    fn media_matches_by_dimensions_not_by_name() {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "media-supported".into(),
+            "iso_a6_105x148mm, custom_min_25.4x25.4mm, custom_max_215.9x355.6mm".into(),
+        );
+        let caps = PrinterCapabilities::from_attributes(&attrs);
+
+        let a6 = PaperSize::Custom {
+            width_mm: 105.0,
+            height_mm: 148.0,
+        };
+        assert!(caps.supports_media(&a6));
+        assert_eq!(caps.media_keyword(&a6).as_deref(), Some("iso_a6_105x148mm"));
+        let settings = PrintSettings {
+            paper_size: a6,
+            ..PrintSettings::default()
+        };
+        assert_eq!(caps.validate(&settings), Ok(()));
+
+        // Sizes the printer does not list are taken within its custom range
+        // and sent as custom sizes; the bounds themselves are inclusive.
+        let tiny = PaperSize::Custom {
+            width_mm: 25.4,
+            height_mm: 25.4,
+        };
+        assert!(caps.supports_media(&tiny));
+        assert_eq!(
+            caps.media_keyword(&tiny).as_deref(),
+            Some("custom_25.4x25.4mm_25.4x25.4mm")
+        );
+        assert_eq!(
+            caps.media_keyword(&PaperSize::A4).as_deref(),
+            Some("custom_210x297mm_210x297mm")
+        );
+        let postcard = PaperSize::Custom {
+            width_mm: 100.0,
+            height_mm: 150.0,
+        };
+        let settings = PrintSettings {
+            paper_size: postcard,
+            ..PrintSettings::default()
+        };
+        assert_eq!(caps.validate(&settings), Ok(()));
+
+        // Outside the range in either dimension.
+        assert!(!caps.supports_media(&PaperSize::A3));
+        let banner = PaperSize::Custom {
+            width_mm: 100.0,
+            height_mm: 600.0,
+        };
+        assert!(!caps.supports_media(&banner));
+    }
+
+    #[test]
+    fn closest_media_accepts_a_differently_named_sheet() {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "media-supported".into(),
+            "custom_215.9x279.4mm_215.9x279.4mm".into(),
+        );
+        let caps = PrinterCapabilities::from_attributes(&attrs);
+
+        let (corrected, result) = auto_correct_settings(&PrintSettings::default(), &caps);
+        assert!(!result.valid);
+        assert_eq!(corrected.paper_size, PaperSize::Letter);
+    }
+
     #[test]
     fn color_corrected_on_bw_printer() {
         let mut attrs = HashMap::new();
@@ -319,6 +921,21 @@ fn color_corrected_on_bw_printer() {
         assert!(!corrected.color);
     }
 
+    #[test]
+    fn colour_document_on_mono_printer_warns() {
+        let mut attrs = HashMap::new();
+        attrs.insert("color-supported".into(), "false".into());
+        let mono = PrinterCapabilities::from_attributes(&attrs);
+
+        assert_eq!(mono.print_warnings(true), [PrintWarning::ColorLost]);
+        assert!(mono.print_warnings(false).is_empty());
+        assert!(test_caps().print_warnings(true).is_empty());
+        assert_eq!(
+            PrintWarning::ColorLost.to_string(),
+            "This printer prints in black & white."
+        );
+    }
+
     #[test]
     fn copies_capped_at_printer_max() {
         let mut attrs = HashMap::new();
@@ -333,6 +950,78 @@ fn copies_capped_at_printer_max() {
         assert!(!result.valid);
     }
 
+    #[test]
+    fn format_plan_rasterizes_for_raster_only_printer() {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "document-format-supported".into(),
+            "image/pwg-raster, image/jpeg".into(),
+        );
+        attrs.insert("pdl-override-supported".into(), "not-attempted".into());
+        let caps = PrinterCapabilities::from_attributes(&attrs);
+
+        assert_eq!(caps.pdl_override, PdlOverride::NotAttempted);
+        assert_eq!(
+            caps.format_plan(DocumentType::Pdf).unwrap(),
+            FormatPlan::Rasterize(DocumentType::PwgRaster)
+        );
+        assert_eq!(
+            caps.format_plan(DocumentType::Jpeg).unwrap(),
+            FormatPlan::SendAsIs
+        );
+        assert_eq!(
+            test_caps().format_plan(DocumentType::Pdf).unwrap(),
+            FormatPlan::SendAsIs
+        );
+    }
+
+    #[test]
+    fn format_plan_honours_pdl_override_and_reports_dead_ends() {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "document-format-supported".into(),
+            "application/postscript".into(),
+        );
+        let caps = PrinterCapabilities::from_attributes(&attrs);
+        let err = caps.format_plan(DocumentType::Pdf).unwrap_err();
+        assert!(err.to_string().contains("application/postscript"), "{err}");
+
+        attrs.insert("pdl-override-supported".into(), "attempted".into());
+        let caps = PrinterCapabilities::from_attributes(&attrs);
+        assert_eq!(
+            caps.format_plan(DocumentType::Pdf).unwrap(),
+            FormatPlan::SendAsIs
+        );
+    }
+
+    #[test]
+    fn unmapped_supported_attributes_become_vendor_options() {
+        let mut attrs = HashMap::new();
+        attrs.insert("media-supported".into(), "iso_a4_210x297mm".into());
+        attrs.insert(
+            "media-source-supported".into(),
+            "auto, main, manual, by-pass-tray".into(),
+        );
+        attrs.insert("page-ranges-supported".into(), "true".into());
+        attrs.insert("number-up-supported".into(), "1-16".into());
+        attrs.insert("operations-supported".into(), "2, 4, 8, 9, 10, 11".into());
+        attrs.insert("media-source".into(), "main".into());
+
+        let caps = PrinterCapabilities::from_attributes(&attrs);
+        assert_eq!(
+            caps.vendor_options,
+            vec![VendorOption {
+                name: "media-source".into(),
+                values: vec![
+                    "auto".into(),
+                    "main".into(),
+                    "manual".into(),
+                    "by-pass-tray".into(),
+                ],
+            }]
+        );
+    }
+
     #[test]
     fn unknown_caps_allows_everything() {
         let caps = PrinterCapabilities::from_attributes(&HashMap::new());
@@ -344,4 +1033,118 @@ fn unknown_caps_allows_everything() {
         // No corrections when capabilities are unknown
         assert!(result.corrections.is_empty());
     }
+
+    #[test]
+    fn airprint_txt_record_decodes_into_capabilities() {
+        // As advertised by a typical AirPrint colour laser.
+        let txt: HashMap<String, String> = [
+            ("txtvers", "1"),
+            ("rp", "ipp/print"),
+            ("ty", "HP Color LaserJet MFP M283fdw"),
+            ("pdl", "application/pdf,image/jpeg,image/urf"),
+            ("Color", "T"),
+            ("Duplex", "T"),
+            ("URF", "V1.4,CP99,W8,PQ3-4-5,DM1,IS1-19,RS600,SRGB24"),
+            ("UUID", "564e4333-3230-3156-3836-a0481c7a3c22"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let caps = PrinterCapabilities::from_txt(&txt);
+        assert!(caps.color_supported);
+        assert!(caps.supports_two_sided());
+        assert!(caps.supports_sides(&DuplexMode::LongEdge));
+        assert!(caps.supports_format("application/pdf"));
+        assert!(caps.supports_format("image/urf"));
+        assert_eq!(caps.max_copies, 99);
+        assert_eq!(
+            caps.format_plan(DocumentType::Pdf).unwrap(),
+            FormatPlan::SendAsIs
+        );
+
+        // A bare record: monochrome, duplex unknown, any format.
+        let bare =
+            PrinterCapabilities::from_txt(&HashMap::from([("rp".into(), "ipp/print".into())]));
+        assert!(!bare.color_supported);
+        assert!(!bare.supports_two_sided());
+        assert!(bare.sides_supported.is_empty());
+        assert!(bare.supports_format("application/pdf"));
+
+        // Colour and duplex fall back to the URF entry, keys in any case.
+        let urf_only = PrinterCapabilities::from_txt(&HashMap::from([(
+            "urf".into(),
+            "CP1,DM3,SRGB24".into(),
+        )]));
+        assert!(urf_only.color_supported);
+        assert!(urf_only.supports_two_sided());
+        assert_eq!(urf_only.max_copies, 1);
+    }
+
+    #[test]
+    fn from_ipp_response_reads_typed_multi_valued_attributes() {
+        use ipp::prelude::{IppAttribute, IppVersion, StatusCode};
+
+        let keywords = |values: &[&str]| {
+            IppValue::Array(
+                values
+                    .iter()
+                    .map(|v| IppValue::Keyword(v.to_string()))
+                    .collect(),
+            )
+        };
+        let mut response =
+            IppRequestResponse::new_response(IppVersion::v2_0(), StatusCode::SuccessfulOk, 1);
+        let attrs = response.attributes_mut();
+        for attribute in [
+            IppAttribute::new(
+                "media-supported",
+                keywords(&[
+                    "iso_a4_210x297mm",
+                    "na_letter_8.5x11in",
+                    "iso_a5_148x210mm",
+                    "iso_a4_210x297mm",
+                ]),
+            ),
+            IppAttribute::new(
+                "sides-supported",
+                keywords(&["one-sided", "two-sided-long-edge"]),
+            ),
+            IppAttribute::new("color-supported", IppValue::Boolean(false)),
+            IppAttribute::new(
+                "copies-supported",
+                IppValue::RangeOfInteger { min: 1, max: 99 },
+            ),
+            IppAttribute::new(
+                "document-format-supported",
+                IppValue::MimeMediaType("application/pdf".into()),
+            ),
+            IppAttribute::new("output-bin-supported", keywords(&["face-down", "face-up"])),
+        ] {
+            attrs.add(DelimiterTag::PrinterAttributes, attribute);
+        }
+
+        let caps = PrinterCapabilities::from_ipp_response(&response);
+        assert_eq!(
+            caps.media_supported,
+            HashSet::from([
+                "iso_a4_210x297mm".to_string(),
+                "na_letter_8.5x11in".to_string(),
+                "iso_a5_148x210mm".to_string(),
+            ])
+        );
+        assert!(caps.supports_media(&PaperSize::A5));
+        assert!(caps.supports_sides(&DuplexMode::LongEdge));
+        assert!(!caps.supports_sides(&DuplexMode::ShortEdge));
+        assert!(!caps.color_supported);
+        assert_eq!(caps.max_copies, 99);
+        assert!(caps.supports_format("application/pdf"));
+        assert_eq!(
+            caps.vendor_options,
+            [VendorOption {
+                name: "output-bin".into(),
+                values: vec!["face-down".into(), "face-up".into()],
+            }]
+        );
+    }
 }
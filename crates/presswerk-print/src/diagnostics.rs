@@ -3,15 +3,43 @@
 //
 // End-to-end print pipeline diagnostics.
 //
-// Runs a sequence of checks: network → discovery → reachability → IPP support
-// → printer readiness → test print. Stops at the first failure and provides
-// a human-readable diagnosis with actionable guidance.
+// Runs a sequence of checks: app permissions → network → discovery →
+// reachability → IPP support → printer readiness → test print. Stops at the
+// first failure and provides a human-readable diagnosis with actionable
+// guidance.
+//
+// Permission states come from the platform bridge, which this crate does not
+// depend on, so the app queries them and passes them in.
+//
+// The finished report can be exported as a zip bundle (report, summary and
+// redacted log tail) for sending to whoever is helping the user.
 
-use std::net::{IpAddr, TcpStream};
+use std::io::{Read, Seek, SeekFrom};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
 use std::time::Duration;
 
+use serde::Serialize;
+
+use presswerk_core::error::Result;
+use presswerk_core::redact::redact;
+use presswerk_core::types::{Permission, PermissionState};
+
+use crate::address;
+use crate::archive::ZipBuilder;
+use crate::discovery::PrinterDiscovery;
+use crate::readiness::PrinterState;
+
+/// How long the subnet probe waits for each address to accept a
+/// connection.
+const SUBNET_PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long the subnet probe may take in all; subnets not reached by then
+/// are skipped.
+const SUBNET_PROBE_BUDGET: Duration = Duration::from_secs(10);
+
 /// Result of a single diagnostic step.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StepResult {
     /// Step name shown to the user.
     pub name: String,
@@ -26,7 +54,7 @@ pub struct StepResult {
 }
 
 /// Full diagnostic report.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DiagnosticReport {
     /// The sequential step results.
     pub steps: Vec<StepResult>,
@@ -41,14 +69,14 @@ pub struct DiagnosticReport {
 }
 
 /// Device information for the diagnostic report.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DeviceInfo {
     pub platform: String,
     pub wifi_network: Option<String>,
 }
 
 /// Printer information discovered during diagnostics.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PrinterInfo {
     pub name: String,
     pub ip: IpAddr,
@@ -58,14 +86,65 @@ pub struct PrinterInfo {
     pub status_reasons: Vec<String>,
 }
 
+/// Timeouts and optional steps of the diagnostic pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticsConfig {
+    /// How long the mDNS browse in the discovery step runs.
+    pub discovery_timeout: Duration,
+    /// How long the reachability step waits for a TCP connection.
+    pub connect_timeout: Duration,
+    /// Whether to finish by sending a physical test page.  When `false` the
+    /// run ends after the printer readiness check.
+    pub run_test_print: bool,
+    /// Whether to probe the local subnets for printers when mDNS finds
+    /// none and no printer address was given.
+    pub probe_subnets: bool,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            discovery_timeout: Duration::from_secs(15),
+            connect_timeout: Duration::from_secs(10),
+            run_test_print: true,
+            probe_subnets: true,
+        }
+    }
+}
+
+/// [`run_diagnostics`] with the default [`DiagnosticsConfig`].
+pub async fn run_diagnostics_default(
+    permissions: &[(Permission, PermissionState)],
+    printer_addresses: &[IpAddr],
+    printer_port: Option<u16>,
+    printer_uri: Option<&str>,
+) -> DiagnosticReport {
+    run_diagnostics(
+        permissions,
+        printer_addresses,
+        printer_port,
+        printer_uri,
+        &DiagnosticsConfig::default(),
+    )
+    .await
+}
+
 /// Run the full diagnostic pipeline.
 ///
-/// Each step depends on the previous one succeeding.
+/// `permissions` is the state of each runtime permission the app relies on,
+/// as reported by the platform bridge.  Each step depends on the previous
+/// one succeeding; a refused permission that printing does not need (e.g.
+/// the camera) is reported but does not stop the run.
+/// `printer_addresses` are all the addresses known for the printer (IPv4
+/// and IPv6); the reachability check tries each of them.
+/// `config` sets the step timeouts and whether a test page is printed.
 /// Returns as soon as a step fails, with guidance for the user.
 pub async fn run_diagnostics(
-    printer_ip: Option<IpAddr>,
+    permissions: &[(Permission, PermissionState)],
+    printer_addresses: &[IpAddr],
     printer_port: Option<u16>,
     printer_uri: Option<&str>,
+    config: &DiagnosticsConfig,
 ) -> DiagnosticReport {
     let mut report = DiagnosticReport {
         steps: Vec::new(),
@@ -75,66 +154,124 @@ pub async fn run_diagnostics(
         printer_info: None,
     };
 
-    // Step 1: Network Check
+    // Step 1: App Permissions
+    let granted = check_permissions(permissions, &report.device_info.platform);
+    report.steps.push(granted.clone());
+    let blocking = permissions
+        .iter()
+        .any(|(permission, state)| permission.blocks_printing() && is_refused(*state));
+    if blocking {
+        report.failed_step = Some(report.steps.len() - 1);
+        report.summary = granted.detail.clone();
+        return report;
+    }
+
+    // Step 2: Network Check
     let network_ok = check_network();
     report.steps.push(network_ok.clone());
     if !network_ok.passed {
-        report.failed_step = Some(0);
-        report.summary = "No network connection found.".into();
+        report.failed_step = Some(report.steps.len() - 1);
+        report.summary = network_ok.detail.clone();
         return report;
     }
 
-    // Step 2: Printer Discovery
-    let discovery = check_discovery().await;
+    // Step 3: Printer Discovery, falling back to a subnet probe
+    let port = printer_port.unwrap_or(631);
+    let mut discovery = check_discovery(config.discovery_timeout).await;
+    let mut probed = Vec::new();
+    if !discovery.passed && printer_addresses.is_empty() && config.probe_subnets {
+        probed = probe_local_subnets(port).await;
+        if !probed.is_empty() {
+            discovery = StepResult {
+                name: "Printer Discovery".into(),
+                passed: true,
+                detail: format!(
+                    "Your network hides printers from searches, but {} device(s) accept print connections.",
+                    probed.len()
+                ),
+                fix: None,
+                escalation: None,
+            };
+        }
+    }
     report.steps.push(discovery.clone());
-    if !discovery.passed && printer_ip.is_none() {
-        report.failed_step = Some(1);
+    if !discovery.passed && printer_addresses.is_empty() {
+        report.failed_step = Some(report.steps.len() - 1);
         report.summary = "No printers found on your network.".into();
         return report;
     }
 
-    // Step 3: Printer Reachable
-    let ip = printer_ip.unwrap_or(IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
-    let port = printer_port.unwrap_or(631);
-    let reachable = check_reachable(ip, port);
+    // Step 4: Printer Reachable
+    let mut addresses = printer_addresses.to_vec();
+    addresses.extend(probed);
+    if addresses.is_empty() {
+        addresses.push(IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
+    }
+    address::sort_by_preference(&mut addresses);
+    let (reachable, answered) = check_reachable(&addresses, port, config.connect_timeout);
     report.steps.push(reachable.clone());
-    if !reachable.passed {
-        report.failed_step = Some(2);
+    let Some(answered) = answered else {
+        report.failed_step = Some(report.steps.len() - 1);
         report.summary = "Printer found but not responding.".into();
         return report;
-    }
+    };
 
-    // Step 4: IPP Support
-    let uri = printer_uri
-        .map(String::from)
-        .unwrap_or_else(|| format!("ipp://{}:{}/ipp/print", ip, port));
+    // Step 5: IPP Support
+    // Without a URI, address the printer by the address that answered or,
+    // if that was link-local, by any other address it has.
+    let mut hosts = vec![answered.ip()];
+    hosts.extend(&addresses);
+    let uri = printer_uri.map(String::from).or_else(|| {
+        address::uri_host_for(&hosts, None).map(|host| format!("ipp://{host}:{port}/ipp/print"))
+    });
+    let Some(uri) = uri else {
+        report.steps.push(StepResult {
+            name: "Printer Speaks IPP".into(),
+            passed: false,
+            detail: format!(
+                "The printer only answered at the link-local address {}, which can't be used for printing.",
+                answered.ip()
+            ),
+            fix: Some("Choose the printer from the printer list, or connect it to your Wi-Fi router rather than directly to your phone.".into()),
+            escalation: None,
+        });
+        report.failed_step = Some(report.steps.len() - 1);
+        report.summary = "Printer found but its address can't be used.".into();
+        return report;
+    };
     let ipp = check_ipp_support(&uri).await;
     report.steps.push(ipp.clone());
     if !ipp.passed {
-        report.failed_step = Some(3);
+        report.failed_step = Some(report.steps.len() - 1);
         report.summary = "Printer doesn't support modern printing protocol.".into();
         return report;
     }
 
-    // Step 5: Printer Ready
-    let ready = check_printer_ready(&uri, &mut report).await;
+    // Step 6: Printer Ready
+    let ready = check_printer_ready(&uri, answered, &mut report).await;
     report.steps.push(ready.clone());
     if !ready.passed {
-        report.failed_step = Some(4);
+        report.failed_step = Some(report.steps.len() - 1);
         report.summary = ready.detail.clone();
         return report;
     }
 
-    // Step 6: Test Print
-    let test = send_test_print(&uri).await;
-    report.steps.push(test.clone());
-    if !test.passed {
-        report.failed_step = Some(5);
-        report.summary = "Test page couldn't be sent.".into();
-        return report;
+    // Step 7: Test Print
+    if config.run_test_print {
+        let test = send_test_print(&uri).await;
+        report.steps.push(test.clone());
+        if !test.passed {
+            report.failed_step = Some(report.steps.len() - 1);
+            report.summary = "Test page couldn't be sent.".into();
+            return report;
+        }
     }
 
-    report.summary = "Everything looks good! Your printer is ready.".into();
+    report.summary = if granted.passed {
+        "Everything looks good! Your printer is ready.".into()
+    } else {
+        format!("Your printer is ready. {}", granted.detail)
+    };
     report
 }
 
@@ -183,45 +320,207 @@ pub fn generate_help_summary(report: &DiagnosticReport) -> String {
     text
 }
 
+// -- Help bundle ------------------------------------------------------------
+
+/// Zip entry holding the full report as JSON.
+pub const BUNDLE_REPORT_ENTRY: &str = "report.json";
+/// Zip entry holding the output of [`generate_help_summary`].
+pub const BUNDLE_SUMMARY_ENTRY: &str = "summary.txt";
+/// Zip entry holding the redacted tail of the application log.
+pub const BUNDLE_LOG_ENTRY: &str = "log-tail.txt";
+
+/// How much of the log file (from the end) is read into the bundle.
+const LOG_TAIL_BYTES: u64 = 256 * 1024;
+/// Maximum number of log lines included in the bundle.
+const LOG_TAIL_LINES: usize = 1000;
+
+/// Build a zip archive for sending to a helper: the report as JSON, the
+/// plain-text summary, and the last lines of the log at `log_path` passed
+/// through [`redact`] so secret-looking values read `[redacted]`.
+///
+/// A missing log file is not an error — the bundle says so instead.
+pub fn export_bundle(report: &DiagnosticReport, log_path: &Path) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec_pretty(report)?;
+    let summary = generate_help_summary(report);
+    let log = match read_log_tail(log_path) {
+        Ok(tail) => redact(&tail),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            format!("(no log file at {})\n", log_path.display())
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut zip = ZipBuilder::new();
+    zip.add_file(BUNDLE_REPORT_ENTRY, &json)?;
+    zip.add_file(BUNDLE_SUMMARY_ENTRY, summary.as_bytes())?;
+    zip.add_file(BUNDLE_LOG_ENTRY, log.as_bytes())?;
+    let bytes = zip.finish()?;
+
+    tracing::info!(bytes = bytes.len(), "diagnostic bundle exported");
+    Ok(bytes)
+}
+
+/// Read the last [`LOG_TAIL_LINES`] lines of the log file.
+fn read_log_tail(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(LOG_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    let text = String::from_utf8_lossy(&buf);
+
+    // Drop the partial first line when we started mid-file.
+    let text = match (start > 0, text.find('\n')) {
+        (true, Some(newline)) => &text[newline + 1..],
+        _ => &text[..],
+    };
+
+    let lines: Vec<&str> = text.lines().collect();
+    let keep = lines.len().saturating_sub(LOG_TAIL_LINES);
+    let mut tail = lines[keep..].join("\n");
+    tail.push('\n');
+    Ok(tail)
+}
+
 // -- Step implementations ---------------------------------------------------
 
+/// Whether `state` means the permission is off.  Permissions not asked for
+/// yet will be prompted for on first use, so they do not count.
+fn is_refused(state: PermissionState) -> bool {
+    state == PermissionState::Denied
+}
+
+fn check_permissions(permissions: &[(Permission, PermissionState)], platform: &str) -> StepResult {
+    let refused: Vec<Permission> = permissions
+        .iter()
+        .filter(|(_, state)| is_refused(*state))
+        .map(|(permission, _)| *permission)
+        .collect();
+
+    if refused.is_empty() {
+        return StepResult {
+            name: "App Permissions".into(),
+            passed: true,
+            detail: "Presswerk has the permissions it needs.".into(),
+            fix: None,
+            escalation: None,
+        };
+    }
+
+    let names: Vec<&str> = refused.iter().map(Permission::display_name).collect();
+    let fixes: Vec<String> = refused
+        .iter()
+        .map(|permission| permission_fix(*permission, platform))
+        .collect();
+    StepResult {
+        name: "App Permissions".into(),
+        passed: false,
+        detail: format!("{} is turned off for Presswerk.", names.join(" and ")),
+        fix: Some(fixes.join(" ")),
+        escalation: None,
+    }
+}
+
+/// Where in system settings to turn `permission` back on.
+fn permission_fix(permission: Permission, platform: &str) -> String {
+    let consequence = match permission {
+        Permission::LocalNetwork => "Without it Presswerk can't find or reach your printer.",
+        Permission::Bluetooth => "Without it Bluetooth printers can't be used.",
+        Permission::Camera => "Without it you can't scan documents.",
+    };
+    let settings = match (platform, permission) {
+        ("iOS", Permission::LocalNetwork) => {
+            "Go to Settings \u{2192} Privacy & Security \u{2192} Local Network and switch on Presswerk."
+        }
+        ("iOS", Permission::Bluetooth) => {
+            "Go to Settings \u{2192} Privacy & Security \u{2192} Bluetooth and switch on Presswerk."
+        }
+        ("iOS", Permission::Camera) => {
+            "Go to Settings \u{2192} Privacy & Security \u{2192} Camera and switch on Presswerk."
+        }
+        ("Android", Permission::LocalNetwork) => {
+            "Go to Settings \u{2192} Apps \u{2192} Presswerk \u{2192} Mobile data & Wi-Fi and allow network access."
+        }
+        ("Android", Permission::Bluetooth) => {
+            "Go to Settings \u{2192} Apps \u{2192} Presswerk \u{2192} Permissions and allow Nearby devices."
+        }
+        ("Android", Permission::Camera) => {
+            "Go to Settings \u{2192} Apps \u{2192} Presswerk \u{2192} Permissions and allow Camera."
+        }
+        _ => "Allow it for Presswerk in your system settings.",
+    };
+    format!("{consequence} {settings}")
+}
+
 fn check_network() -> StepResult {
-    // Check for any non-loopback network interface
-    let has_network = std::net::UdpSocket::bind("0.0.0.0:0")
+    network_step(&address::interface_addresses(), has_internet_route())
+}
+
+/// Whether the device has a route to the internet.  Connecting a UDP socket
+/// sends nothing; it only asks the system for a route.
+fn has_internet_route() -> bool {
+    std::net::UdpSocket::bind("0.0.0.0:0")
         .and_then(|s| {
             s.connect("8.8.8.8:53")?;
             s.local_addr()
         })
         .map(|addr| !addr.ip().is_loopback())
-        .unwrap_or(false);
+        .unwrap_or(false)
+}
 
-    if has_network {
-        StepResult {
-            name: "Network Check".into(),
-            passed: true,
-            detail: "Your device is connected to a network.".into(),
-            fix: None,
-            escalation: None,
-        }
-    } else {
-        StepResult {
+/// The network step for a device with interface `addresses`.
+///
+/// Any non-loopback interface (or a route to the internet, where the
+/// interfaces cannot be listed) passes: printers on public or IPv6-only
+/// networks are reachable too.  Without a private-range address the step
+/// only notes that this may be mobile data; IPv6 link-local addresses do
+/// not count, as cellular interfaces have them as well.  Printing only
+/// needs the local network, so `internet` never fails the step.
+fn network_step(addresses: &[IpAddr], internet: bool) -> StepResult {
+    let connected = internet || addresses.iter().any(|ip| !ip.is_loopback());
+    if !connected {
+        return StepResult {
             name: "Network Check".into(),
             passed: false,
             detail: "No network connection found.".into(),
             fix: Some("Connect to your home Wi-Fi network. Go to Settings \u{2192} Wi-Fi on your phone.".into()),
             escalation: None,
+        };
+    }
+
+    let on_private_network = addresses.iter().any(|ip| {
+        !ip.is_loopback()
+            && address::is_local_network(ip)
+            && !matches!(ip, IpAddr::V6(v6) if address::is_ipv6_link_local(v6))
+    });
+    let detail = match (on_private_network, internet) {
+        (true, true) => "Your device is connected to a network.",
+        (true, false) => {
+            "Your device is connected to a local network without internet access. That's fine for printing."
+        }
+        (false, _) => {
+            "Your device is connected to a network, but not one that looks like a home or office network. If you're on mobile data, connect to the same Wi-Fi network as your printer."
         }
+    };
+    StepResult {
+        name: "Network Check".into(),
+        passed: true,
+        detail: detail.into(),
+        fix: None,
+        escalation: None,
     }
 }
 
-async fn check_discovery() -> StepResult {
-    // Try mDNS browse for 15 seconds
+async fn check_discovery(timeout: Duration) -> StepResult {
+    // Try an mDNS browse for `timeout`
     match presswerk_core::error::Result::Ok(()) {
         Ok(()) => {
             let discovery = crate::discovery::PrinterDiscovery::new();
             match discovery {
                 Ok(mut disc) => {
-                    let printers = disc.discover(Some(Duration::from_secs(15)));
+                    let printers = disc.discover(Some(timeout));
                     match printers {
                         Ok(list) if !list.is_empty() => StepResult {
                             name: "Printer Discovery".into(),
@@ -252,23 +551,63 @@ async fn check_discovery() -> StepResult {
     }
 }
 
-fn check_reachable(ip: IpAddr, port: u16) -> StepResult {
-    let addr = std::net::SocketAddr::new(ip, port);
-    match TcpStream::connect_timeout(&addr, Duration::from_secs(10)) {
-        Ok(_) => StepResult {
-            name: "Printer Reachable".into(),
-            passed: true,
-            detail: format!("Printer is responding at {ip}:{port}."),
-            fix: None,
-            escalation: None,
-        },
-        Err(_) => StepResult {
-            name: "Printer Reachable".into(),
-            passed: false,
-            detail: format!("Printer at {ip}:{port} is not responding."),
-            fix: Some("The printer was seen on the network but isn't answering. Try turning it off, waiting 10 seconds, and turning it back on.".into()),
-            escalation: Some("If the printer has a small screen, check if it shows any error messages.".into()),
-        },
+/// Addresses on the device's own subnets that accept connections on `port`,
+/// as far as [`SUBNET_PROBE_BUDGET`] allows.
+async fn probe_local_subnets(port: u16) -> Vec<IpAddr> {
+    let deadline = tokio::time::Instant::now() + SUBNET_PROBE_BUDGET;
+    let mut found = Vec::new();
+    for subnet in address::local_subnets() {
+        let probe = PrinterDiscovery::probe_subnet(subnet, port, SUBNET_PROBE_TIMEOUT);
+        match tokio::time::timeout_at(deadline, probe).await {
+            Ok(hosts) => found.extend(hosts),
+            Err(_) => {
+                tracing::warn!(%subnet, "subnet probe out of time");
+                break;
+            }
+        }
+    }
+    found
+}
+
+/// Try every address of the printer, happy-eyeballs style, and report the
+/// one that answered first.
+fn check_reachable(
+    addresses: &[IpAddr],
+    port: u16,
+    timeout: Duration,
+) -> (StepResult, Option<SocketAddr>) {
+    let zones = address::link_local_zones();
+    let candidates: Vec<SocketAddr> = addresses
+        .iter()
+        .flat_map(|&ip| address::socket_addrs(ip, port, &zones))
+        .collect();
+    let tried = addresses
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match address::connect_first(&candidates, timeout) {
+        Ok((addr, _)) => (
+            StepResult {
+                name: "Printer Reachable".into(),
+                passed: true,
+                detail: format!("Printer is responding at {addr}."),
+                fix: None,
+                escalation: None,
+            },
+            Some(addr),
+        ),
+        Err(_) => (
+            StepResult {
+                name: "Printer Reachable".into(),
+                passed: false,
+                detail: format!("Printer at {tried} (port {port}) is not responding."),
+                fix: Some("The printer was seen on the network but isn't answering. Try turning it off, waiting 10 seconds, and turning it back on.".into()),
+                escalation: Some("If the printer has a small screen, check if it shows any error messages.".into()),
+            },
+            None,
+        ),
     }
 }
 
@@ -313,8 +652,11 @@ async fn check_ipp_support(uri: &str) -> StepResult {
     }
 }
 
+/// Query the printer's state; `answered` is the address that passed the
+/// reachability check and is recorded in the report's printer info.
 async fn check_printer_ready(
     uri: &str,
+    answered: SocketAddr,
     report: &mut DiagnosticReport,
 ) -> StepResult {
     let client = match crate::ipp_client::IppClient::new(uri) {
@@ -366,15 +708,16 @@ async fn check_printer_ready(
     // Populate printer info in the report
     report.printer_info = Some(PrinterInfo {
         name: name.clone(),
-        ip: IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), // will be overridden by caller
-        port: 631,
+        ip: answered.ip(),
+        port: answered.port(),
         model: attrs.get("printer-make-and-model").cloned(),
         status: Some(state.clone()),
         status_reasons: reasons.clone(),
     });
 
     // Interpret printer state
-    if state.contains('3') || state.to_ascii_lowercase().contains("idle") {
+    let printer_state = PrinterState::parse(&state);
+    if printer_state == Some(PrinterState::Idle) {
         StepResult {
             name: "Printer Ready".into(),
             passed: true,
@@ -382,7 +725,7 @@ async fn check_printer_ready(
             fix: None,
             escalation: None,
         }
-    } else if state.contains('4') || state.to_ascii_lowercase().contains("processing") {
+    } else if printer_state == Some(PrinterState::Processing) {
         StepResult {
             name: "Printer Ready".into(),
             passed: true,
@@ -511,6 +854,244 @@ fn detect_device_info() -> DeviceInfo {
 
     DeviceInfo {
         platform: platform.into(),
-        wifi_network: None, // would need platform bridge for real network name
+        // Filled in by the app, which has the platform bridge.
+        wifi_network: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::archive::read_entries;
+    use crate::ipp_server::IppServer;
+    use crate::queue::JobQueue;
+
+    fn sample_report() -> DiagnosticReport {
+        DiagnosticReport {
+            steps: vec![StepResult {
+                name: "Network Check".into(),
+                passed: false,
+                detail: "No network connection found.".into(),
+                fix: Some("Connect to your home Wi-Fi network.".into()),
+                escalation: None,
+            }],
+            failed_step: Some(0),
+            summary: "No network connection found.".into(),
+            device_info: DeviceInfo {
+                platform: "Linux".into(),
+                wifi_network: None,
+            },
+            printer_info: None,
+        }
+    }
+
+    #[test]
+    fn bundle_contains_report_summary_and_redacted_log() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let log_path = tmp.path().join("presswerk.log");
+        std::fs::write(
+            &log_path,
+            "INFO presswerk: server started port=631\n\
+             DEBUG keychain: stored secret key=\"presswerk_tls\" secret=\"hunter2 hunter3\"\n\
+             DEBUG keychain: loaded key material 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08\n\
+             WARN ipp: password: letmein retrying\n\
+             INFO app: signed in as jo@example.org presswerk_db_key=0xfeed\n",
+        )
+        .unwrap();
+
+        let bytes = export_bundle(&sample_report(), &log_path).unwrap();
+        let entries = read_entries(&bytes);
+        let names: Vec<&str> = entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            [BUNDLE_REPORT_ENTRY, BUNDLE_SUMMARY_ENTRY, BUNDLE_LOG_ENTRY]
+        );
+
+        let json: serde_json::Value = serde_json::from_slice(&entries[0].1).unwrap();
+        assert_eq!(json["failed_step"], 0);
+        assert_eq!(json["steps"][0]["name"], "Network Check");
+
+        let summary = String::from_utf8(entries[1].1.clone()).unwrap();
+        assert!(summary.contains("FAILED AT: Step 1"));
+
+        let log = String::from_utf8(entries[2].1.clone()).unwrap();
+        assert!(log.contains("server started port=631"));
+        assert!(log.contains("key=\"presswerk_tls\""));
+        assert!(log.contains("secret=\"[redacted]\"\n"));
+        assert!(log.contains("password: [redacted] retrying"));
+        for leaked in [
+            "hunter2",
+            "hunter3",
+            "letmein",
+            "9f86d081884c7d65",
+            "jo@example.org",
+            "0xfeed",
+        ] {
+            assert!(!log.contains(leaked), "{leaked} leaked into bundle:\n{log}");
+        }
+    }
+
+    #[tokio::test]
+    async fn denied_local_network_stops_with_a_settings_fix() {
+        let permissions = [
+            (Permission::LocalNetwork, PermissionState::Denied),
+            (Permission::Camera, PermissionState::Granted),
+        ];
+        let report = run_diagnostics_default(&permissions, &[], None, None).await;
+
+        assert_eq!(report.failed_step, Some(0));
+        assert_eq!(report.steps.len(), 1);
+        let step = &report.steps[0];
+        assert_eq!(step.name, "App Permissions");
+        assert!(
+            step.detail.contains("Local network access"),
+            "{}",
+            step.detail
+        );
+        let fix = step.fix.as_deref().unwrap();
+        assert!(fix.contains("find or reach your printer"), "{fix}");
+        assert!(
+            fix.contains("settings") || fix.contains("Settings"),
+            "{fix}"
+        );
+    }
+
+    /// Start the embedded IPP server on a free loopback port.
+    async fn loopback_printer() -> (IppServer, Arc<Mutex<JobQueue>>, u16, tempfile::TempDir) {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let tmp = tempfile::tempdir().unwrap();
+        let mut server = IppServer::new(Some(port), Some(tmp.path().to_path_buf()));
+        let queue = Arc::new(Mutex::new(JobQueue::open_in_memory().unwrap()));
+        server.start(Arc::clone(&queue)).await.unwrap();
+        (server, queue, port, tmp)
+    }
+
+    /// Run diagnostics against `port` on loopback without printing.
+    async fn diagnose_loopback(port: u16, uri: Option<&str>) -> DiagnosticReport {
+        let config = DiagnosticsConfig {
+            discovery_timeout: Duration::from_millis(200),
+            connect_timeout: Duration::from_secs(2),
+            run_test_print: false,
+            probe_subnets: false,
+        };
+        run_diagnostics(
+            &[],
+            &[IpAddr::V4(Ipv4Addr::LOCALHOST)],
+            Some(port),
+            uri,
+            &config,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn skipping_the_test_print_stops_after_the_readiness_check() {
+        let (mut server, queue, port, _tmp) = loopback_printer().await;
+        let uri = format!("ipp://127.0.0.1:{port}/ipp/print");
+        let report = diagnose_loopback(port, Some(&uri)).await;
+        server.stop().await.unwrap();
+
+        assert_eq!(report.failed_step, None, "{report:?}");
+        let names: Vec<&str> = report.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names.last(), Some(&"Printer Ready"));
+        assert!(!names.contains(&"Test Print"));
+        assert!(queue.lock().unwrap().get_all_jobs().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn printer_info_records_the_address_that_answered() {
+        let (mut server, _queue, port, _tmp) = loopback_printer().await;
+        let report = diagnose_loopback(port, None).await;
+        server.stop().await.unwrap();
+
+        let info = report.printer_info.as_ref().expect("printer info");
+        assert_eq!(info.ip, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(info.port, port);
+        assert_ne!(info.port, 631);
+        let summary = generate_help_summary(&report);
+        assert!(
+            summary.contains(&format!("IP: 127.0.0.1:{port}")),
+            "{summary}"
+        );
+    }
+
+    #[test]
+    fn network_check_passes_on_a_local_network_without_internet() {
+        let addresses =
+            |list: &[&str]| -> Vec<IpAddr> { list.iter().map(|ip| ip.parse().unwrap()).collect() };
+
+        for lan in [
+            &["192.168.1.23"][..],
+            &["10.0.0.5"],
+            &["172.20.1.1"],
+            &["169.254.7.7"],
+            &["fd12:3456::1"],
+            &["fd00::5", "fe80::1c2:3ff:fe45:6789"],
+        ] {
+            let step = network_step(&addresses(lan), false);
+            assert!(step.passed, "{lan:?}");
+            assert!(step.detail.contains("without internet"), "{}", step.detail);
+            assert!(network_step(&addresses(lan), true).passed);
+        }
+
+        // Loopback alone is no network at all.
+        let step = network_step(&addresses(&["127.0.0.1", "::1"]), false);
+        assert!(!step.passed);
+        assert_eq!(step.detail, "No network connection found.");
+        assert!(!network_step(&[], false).passed);
+    }
+
+    #[test]
+    fn network_check_passes_without_a_private_address_but_says_so() {
+        let addresses =
+            |list: &[&str]| -> Vec<IpAddr> { list.iter().map(|ip| ip.parse().unwrap()).collect() };
+
+        for public in [
+            &["203.0.113.9", "2001:db8::1"][..],
+            // Cellular interfaces carry link-local IPv6 addresses too.
+            &["fe80::1c2:3ff:fe45:6789"],
+            // Interfaces that cannot be listed, but a route out.
+            &[],
+        ] {
+            let step = network_step(&addresses(public), true);
+            assert!(step.passed, "{public:?}");
+            assert!(step.detail.contains("mobile data"), "{}", step.detail);
+        }
+        assert!(network_step(&addresses(&["fe80::1"]), false).passed);
+    }
+
+    #[test]
+    fn refused_camera_is_reported_with_platform_settings_path() {
+        let step = check_permissions(
+            &[
+                (Permission::LocalNetwork, PermissionState::Granted),
+                (Permission::Camera, PermissionState::Denied),
+                (Permission::Bluetooth, PermissionState::NotDetermined),
+            ],
+            "iOS",
+        );
+        assert!(!step.passed);
+        assert_eq!(step.detail, "Camera access is turned off for Presswerk.");
+        assert!(
+            step.fix
+                .unwrap()
+                .contains("Privacy & Security \u{2192} Camera")
+        );
+    }
+
+    #[test]
+    fn missing_log_file_still_produces_bundle() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let bytes = export_bundle(&sample_report(), &tmp.path().join("absent.log")).unwrap();
+        let entries = read_entries(&bytes);
+        assert_eq!(entries.len(), 3);
+        assert!(String::from_utf8_lossy(&entries[2].1).contains("no log file"));
     }
 }
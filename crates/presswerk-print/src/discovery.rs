@@ -7,19 +7,54 @@
 // `_ipps._tcp.local.` (TLS-secured IPP) using the `mdns-sd` crate.  Resolved
 // services are converted into `DiscoveredPrinter` values that the rest of the
 // application can consume.
+//
+// A multi-homed printer (say Wi-Fi plus an Ethernet bridge) is resolved once
+// per interface.  Snapshots and the live watch merge those into a single
+// entry, keyed by the printer's `UUID` TXT record, with every address it was
+// seen on.  The entry is addressed by its best address (IPv4 first, see
+// `address::preference`), ties going to the one seen first, so the choice
+// does not depend on the order the daemon reports things in.
+//
+// `PrinterDiscovery::watch` runs its own daemon and turns the raw event
+// stream into `DiscoveryEvent`s for a UI that updates live.  Printers re-send
+// their TXT records in bursts (state changes, supply levels), so changes to
+// an already-known service are held back until it has been quiet for
+// `UPDATE_COALESCE_WINDOW` and then reported once.
+//
+// `PrinterDiscovery::with_cache` also lists the printers seen on earlier runs
+// (see `printer_cache`) until live results replace them.
+//
+// `PrinterDiscovery::discover_ready` goes one step further for Easy Mode:
+// it asks every printer found for its attributes, all at once, and keeps
+// the ones that answer and are idle, with their capabilities.
+//
+// Where mDNS is blocked (segmented corporate Wi-Fi, guest networks),
+// `PrinterDiscovery::probe_subnet` finds printers the slow way: a TCP
+// connect to the IPP port of every address in a subnet, a bounded number at
+// a time.
 
 use std::collections::HashMap;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
+use ipnet::IpNet;
 use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
 use tracing::{debug, info, warn};
 
 use presswerk_core::error::{PresswerkError, Result};
 use presswerk_core::types::DiscoveredPrinter;
 
+use crate::address;
+use crate::capabilities::PrinterCapabilities;
+use crate::ipp_client::IppClient;
+use crate::printer_cache::PrinterCache;
+use crate::readiness::PrinterReadiness;
+
 /// mDNS service type for plain IPP.
 const IPP_SERVICE: &str = "_ipp._tcp.local.";
 
@@ -30,6 +65,71 @@
 /// Increased from 5s to 15s to catch slow printers.
 const DEFAULT_BROWSE_TIMEOUT: Duration = Duration::from_secs(15);
 
+/// How long a known service must stop changing before its update is
+/// reported by [`PrinterDiscovery::watch`].
+const UPDATE_COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// How often watch threads check whether their handle was dropped.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Most connection attempts [`PrinterDiscovery::probe_subnet`] has open at
+/// once, well below the usual file descriptor limits.
+pub const MAX_PROBE_CONCURRENCY: usize = 128;
+
+/// Most addresses [`PrinterDiscovery::probe_subnet`] tries: a /16.
+pub const MAX_PROBE_HOSTS: usize = 1 << 16;
+
+/// A change reported by [`PrinterDiscovery::watch`].
+///
+/// Services are identified by their mDNS full name, which is the printer's
+/// `name`.
+#[derive(Debug, Clone)]
+pub enum DiscoveryEvent {
+    /// A service resolved for the first time.
+    Added(DiscoveredPrinter),
+    /// A known service now advertises something different (address, TXT
+    /// records).
+    Updated(DiscoveredPrinter),
+    /// A known service went away; carries its last advertisement.
+    Removed(DiscoveredPrinter),
+}
+
+/// Callback receiving [`DiscoveryEvent`]s, shared by the watch threads.
+type EventSink = Arc<dyn Fn(DiscoveryEvent) + Send + Sync>;
+
+/// Handle returned by [`PrinterDiscovery::watch`].  Events are reported
+/// until it is dropped.
+pub struct DiscoveryWatch {
+    daemon: ServiceDaemon,
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl Drop for DiscoveryWatch {
+    /// Stops browsing and waits for the watch threads, so the callback is
+    /// not invoked after the handle is gone.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Err(e) = self.daemon.shutdown() {
+            warn!(error = %e, "mDNS watch daemon did not shut down cleanly");
+        }
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+        debug!("mDNS watch stopped");
+    }
+}
+
+/// A printer that answered and is idle, ready for a job.
+#[derive(Debug, Clone)]
+pub struct ReadyPrinter {
+    pub printer: DiscoveredPrinter,
+    /// What the printer reported it can do.
+    pub capabilities: PrinterCapabilities,
+    /// Its state when it was asked.
+    pub readiness: PrinterReadiness,
+}
+
 /// Printer discovery engine using mDNS-SD.
 ///
 /// Wraps an `mdns-sd` `ServiceDaemon` that continuously browses for IPP and
@@ -41,6 +141,8 @@ pub struct PrinterDiscovery {
     daemon: ServiceDaemon,
     /// Thread-safe map of discovered printers keyed by mDNS full-name.
     printers: Arc<Mutex<HashMap<String, DiscoveredPrinter>>>,
+    /// Printers seen on earlier runs, if persistence is enabled.
+    cache: Option<Arc<PrinterCache>>,
     /// Whether we are currently browsing.
     browsing: bool,
 }
@@ -56,10 +158,23 @@ pub fn new() -> Result<Self> {
         Ok(Self {
             daemon,
             printers: Arc::new(Mutex::new(HashMap::new())),
+            cache: None,
             browsing: false,
         })
     }
 
+    /// Create a discovery engine that remembers printers across restarts in
+    /// the cache file at `cache_path`.
+    ///
+    /// The cache is loaded here, so [`printers`](Self::printers) lists the
+    /// printers of earlier runs straight away, before browsing has found
+    /// anything.  Resolved printers are written back as they appear.
+    pub fn with_cache(cache_path: impl Into<PathBuf>) -> Result<Self> {
+        let mut discovery = Self::new()?;
+        discovery.cache = Some(Arc::new(PrinterCache::load(cache_path)));
+        Ok(discovery)
+    }
+
     /// Start browsing for IPP and IPPS printers.
     ///
     /// Returns immediately.  Discovered printers are accumulated internally and
@@ -83,12 +198,19 @@ pub fn start(&mut self) -> Result<()> {
 
         // Spawn a background thread per service type to drain the receiver
         // channel and update the shared printer map.
-        Self::spawn_listener(IPP_SERVICE, false, ipp_receiver, Arc::clone(&self.printers));
+        Self::spawn_listener(
+            IPP_SERVICE,
+            false,
+            ipp_receiver,
+            Arc::clone(&self.printers),
+            self.cache.clone(),
+        );
         Self::spawn_listener(
             IPPS_SERVICE,
             true,
             ipps_receiver,
             Arc::clone(&self.printers),
+            self.cache.clone(),
         );
 
         self.browsing = true;
@@ -110,6 +232,7 @@ pub fn stop(&mut self) -> Result<()> {
             .map_err(|e| PresswerkError::Discovery(format!("stop browse {IPPS_SERVICE}: {e}")))?;
 
         self.browsing = false;
+        self.save_cache();
         info!("mDNS printer discovery stopped");
         Ok(())
     }
@@ -118,6 +241,7 @@ pub fn stop(&mut self) -> Result<()> {
     ///
     /// After calling this the `PrinterDiscovery` instance cannot be reused.
     pub fn shutdown(self) -> Result<()> {
+        self.save_cache();
         let _status_rx = self
             .daemon
             .shutdown()
@@ -126,14 +250,26 @@ pub fn shutdown(self) -> Result<()> {
         Ok(())
     }
 
-    /// Return a snapshot of all currently discovered printers.
+    /// Return a snapshot of all currently discovered printers, with a
+    /// printer seen on several interfaces reported once (see
+    /// [`deduplicate`]).
+    ///
+    /// With a cache, printers from earlier runs that have not resolved yet
+    /// follow the live ones (see [`PrinterCache::merge`]).
     pub fn printers(&self) -> Vec<DiscoveredPrinter> {
-        self.printers
+        let mut resolved: Vec<DiscoveredPrinter> = self
+            .printers
             .lock()
             .unwrap_or_else(|p| p.into_inner())
             .values()
             .cloned()
-            .collect()
+            .collect();
+        // Map order is arbitrary; merging needs a fixed one.
+        resolved.sort_by(|a, b| a.name.cmp(&b.name));
+        match &self.cache {
+            Some(cache) => deduplicate(cache.merge(resolved, Utc::now())),
+            None => deduplicate(resolved),
+        }
     }
 
     /// Browse the network for printers, wait up to `timeout` for initial
@@ -148,13 +284,85 @@ pub fn discover(&mut self, timeout: Option<Duration>) -> Result<Vec<DiscoveredPr
         Ok(self.printers())
     }
 
+    /// Browse for printers, then ask each one found for its attributes, and
+    /// return those that answered and are idle, within `max` overall.
+    ///
+    /// Browsing takes the first half of `max`; the printers are queried
+    /// concurrently in the rest, and any that has not answered by then is
+    /// left out.  Discovery keeps running in the background afterwards.
+    pub async fn discover_ready(&mut self, max: Duration) -> Vec<ReadyPrinter> {
+        let deadline = Instant::now() + max;
+        if let Err(e) = self.start() {
+            warn!(error = %e, "cannot browse for printers");
+            return Vec::new();
+        }
+        tokio::time::sleep(max / 2).await;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        ready_printers(self.printers(), remaining).await
+    }
+
+    /// Try a TCP connection to `port` on every host address in `cidr` and
+    /// return, in address order, those that accepted one within `timeout`.
+    ///
+    /// For networks where mDNS is blocked.  At most
+    /// [`MAX_PROBE_CONCURRENCY`] attempts run at once, and only the first
+    /// [`MAX_PROBE_HOSTS`] addresses of a larger range are tried.
+    pub async fn probe_subnet(cidr: IpNet, port: u16, timeout: Duration) -> Vec<IpAddr> {
+        probe_hosts(cidr, port, timeout, MAX_PROBE_HOSTS).await
+    }
+
     /// Whether the discovery engine is currently browsing.
     pub fn is_browsing(&self) -> bool {
         self.browsing
     }
 
+    /// Browse continuously and call `on_event` as printers appear, change
+    /// and disappear, until the returned handle is dropped.
+    ///
+    /// The watch runs on its own mDNS daemon, independent of any
+    /// `PrinterDiscovery` instance.  `on_event` is called from background
+    /// threads.
+    pub fn watch(
+        on_event: impl Fn(DiscoveryEvent) + Send + Sync + 'static,
+    ) -> Result<DiscoveryWatch> {
+        let daemon = ServiceDaemon::new()
+            .map_err(|e| PresswerkError::Discovery(format!("failed to start mDNS daemon: {e}")))?;
+        let sink: EventSink = Arc::new(on_event);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let mut threads = Vec::with_capacity(2);
+        for (service_type, tls) in [(IPP_SERVICE, false), (IPPS_SERVICE, true)] {
+            let receiver = daemon
+                .browse(service_type)
+                .map_err(|e| PresswerkError::Discovery(format!("browse {service_type}: {e}")))?;
+            let mut dispatcher = Dispatcher::new(tls, Arc::clone(&sink));
+            let stop = Arc::clone(&stop);
+            let thread = std::thread::Builder::new()
+                .name(format!("mdns-watch-{service_type}"))
+                .spawn(move || dispatcher.run(&receiver, &stop))
+                .map_err(|e| PresswerkError::Discovery(format!("spawn watch thread: {e}")))?;
+            threads.push(thread);
+        }
+
+        info!("mDNS printer watch started");
+        Ok(DiscoveryWatch {
+            daemon,
+            stop,
+            threads,
+        })
+    }
+
     // -- internal helpers ---------------------------------------------------
 
+    /// Write the cache, if any, logging rather than failing on error.
+    fn save_cache(&self) {
+        if let Some(cache) = &self.cache
+            && let Err(e) = cache.save()
+        {
+            warn!(path = %cache.path().display(), error = %e, "failed to save printer cache");
+        }
+    }
+
     /// Spawn a thread that drains the `flume::Receiver<ServiceEvent>` produced
     /// by `ServiceDaemon::browse` and populates the shared printer map.
     fn spawn_listener(
@@ -162,6 +370,7 @@ fn spawn_listener(
         tls: bool,
         receiver: mdns_sd::Receiver<ServiceEvent>,
         printers: Arc<Mutex<HashMap<String, DiscoveredPrinter>>>,
+        cache: Option<Arc<PrinterCache>>,
     ) {
         std::thread::Builder::new()
             .name(format!("mdns-{service_type}"))
@@ -185,6 +394,12 @@ fn spawn_listener(
                                         uri = %printer.uri,
                                         "printer resolved"
                                     );
+                                    if let Some(cache) = &cache
+                                        && cache.record(&printer)
+                                        && let Err(e) = cache.save()
+                                    {
+                                        warn!(error = %e, "failed to save printer cache");
+                                    }
                                     printers
                                         .lock()
                                         .unwrap_or_else(|p| p.into_inner())
@@ -217,36 +432,212 @@ fn spawn_listener(
     }
 }
 
+/// Turns the raw `ServiceEvent`s of one service type into
+/// [`DiscoveryEvent`]s: one entry per printer (see [`identity_key`]), merged
+/// over the full names it is advertised under, with updates coalesced.
+struct Dispatcher {
+    tls: bool,
+    sink: EventSink,
+    /// Printer identity of each full name currently advertised.
+    identities: HashMap<String, String>,
+    /// Latest resolution of each full name, grouped by printer identity,
+    /// in the order the full names were first seen.
+    members: HashMap<String, Vec<DiscoveredPrinter>>,
+    /// Last merged advertisement reported for each printer identity.
+    known: HashMap<String, DiscoveredPrinter>,
+    /// Updates not reported yet, with the time of the latest change.
+    pending: HashMap<String, (DiscoveredPrinter, Instant)>,
+}
+
+impl Dispatcher {
+    fn new(tls: bool, sink: EventSink) -> Self {
+        Self {
+            tls,
+            sink,
+            identities: HashMap::new(),
+            members: HashMap::new(),
+            known: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Drain `receiver` until it closes or `stop` is set.
+    fn run(&mut self, receiver: &mdns_sd::Receiver<ServiceEvent>, stop: &AtomicBool) {
+        while !stop.load(Ordering::Relaxed) {
+            let wait = self
+                .next_due()
+                .map(|due| due.saturating_duration_since(Instant::now()))
+                .map_or(WATCH_POLL_INTERVAL, |d| d.min(WATCH_POLL_INTERVAL));
+            match receiver.recv_timeout(wait) {
+                Ok(event) => self.handle(event, Instant::now()),
+                // The daemon shut down.
+                Err(_) if receiver.is_disconnected() => break,
+                Err(_) => {}
+            }
+            self.flush(Instant::now());
+        }
+    }
+
+    /// Apply one raw event received at `now`.
+    fn handle(&mut self, event: ServiceEvent, now: Instant) {
+        match event {
+            ServiceEvent::ServiceResolved(info) => {
+                let printer = match service_info_to_printer(&info, self.tls) {
+                    Ok(printer) => printer,
+                    Err(e) => {
+                        warn!(fullname = %info.get_fullname(), error = %e, "ignoring unusable service");
+                        return;
+                    }
+                };
+                let fullname = printer.name.clone();
+                let identity = identity_key(&printer);
+                // A full name that now belongs to another printer (a new
+                // UUID, say) leaves its old one first.
+                if let Some(old) = self.identities.insert(fullname.clone(), identity.clone())
+                    && old != identity
+                {
+                    self.leave(&old, &fullname, now);
+                }
+                let group = self.members.entry(identity.clone()).or_default();
+                match group.iter_mut().find(|member| member.name == fullname) {
+                    Some(member) => *member = printer,
+                    None => group.push(printer),
+                }
+                self.report(&identity, now);
+            }
+            ServiceEvent::ServiceRemoved(_, fullname) => {
+                if let Some(identity) = self.identities.remove(&fullname) {
+                    self.leave(&identity, &fullname, now);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Drop `fullname` from the printer `identity` and report the result.
+    fn leave(&mut self, identity: &str, fullname: &str, now: Instant) {
+        if let Some(group) = self.members.get_mut(identity) {
+            group.retain(|member| member.name != fullname);
+            if group.is_empty() {
+                self.members.remove(identity);
+            }
+        }
+        self.report(identity, now);
+    }
+
+    /// Compare the printer `identity`, merged over its full names, with
+    /// what was last reported: a new printer is added at once, a gone one
+    /// removed at once, and a change held back until it settles.
+    fn report(&mut self, identity: &str, now: Instant) {
+        let merged = self
+            .members
+            .get(identity)
+            .and_then(|group| deduplicate(group.clone()).into_iter().next());
+        let Some(printer) = merged else {
+            self.pending.remove(identity);
+            if let Some(printer) = self.known.remove(identity) {
+                (self.sink)(DiscoveryEvent::Removed(printer));
+            }
+            return;
+        };
+        match self.known.get(identity) {
+            None => {
+                self.known.insert(identity.to_string(), printer.clone());
+                (self.sink)(DiscoveryEvent::Added(printer));
+            }
+            Some(previous) if same_advertisement(previous, &printer) => {
+                // A repeat of what was reported; a pending change (if any)
+                // was reverted.
+                self.pending.remove(identity);
+            }
+            Some(_) => {
+                self.pending.insert(identity.to_string(), (printer, now));
+            }
+        }
+    }
+
+    /// Report updates that have been quiet for the coalesce window.
+    fn flush(&mut self, now: Instant) {
+        let due: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, (_, changed))| now.duration_since(*changed) >= UPDATE_COALESCE_WINDOW)
+            .map(|(identity, _)| identity.clone())
+            .collect();
+        for identity in due {
+            if let Some((printer, _)) = self.pending.remove(&identity) {
+                self.known.insert(identity, printer.clone());
+                (self.sink)(DiscoveryEvent::Updated(printer));
+            }
+        }
+    }
+
+    /// When the earliest pending update becomes reportable.
+    fn next_due(&self) -> Option<Instant> {
+        self.pending
+            .values()
+            .map(|(_, changed)| *changed + UPDATE_COALESCE_WINDOW)
+            .min()
+    }
+}
+
+/// Whether two resolutions of a service advertise the same thing, ignoring
+/// when they were seen.
+fn same_advertisement(a: &DiscoveredPrinter, b: &DiscoveredPrinter) -> bool {
+    a.uri == b.uri
+        && a.ip == b.ip
+        && a.port == b.port
+        && a.addresses == b.addresses
+        && a.supports_color == b.supports_color
+        && a.supports_duplex == b.supports_duplex
+        && a.make_and_model == b.make_and_model
+        && a.location == b.location
+        && a.uuid == b.uuid
+        && a.document_formats == b.document_formats
+}
+
 /// Convert a resolved `ServiceInfo` into a `DiscoveredPrinter`.
 ///
 /// TXT record keys (case-insensitive) commonly found on IPP printers:
 ///   - `printer-make-and-model` — human-readable make/model string
 ///   - `printer-location`       — physical location
-///   - `Color`                  — "T" or "F"
-///   - `Duplex`                 — "T" or "F"
 ///   - `rp`                     — resource path (e.g. "ipp/print")
+///
+/// Colour, duplex and document formats come from the capability keys
+/// (`Color`, `Duplex`, `pdl`, `URF`); see [`PrinterCapabilities::from_txt`].
 fn service_info_to_printer(info: &ServiceInfo, tls: bool) -> Result<DiscoveredPrinter> {
     let name = info.get_fullname().to_owned();
     let port = info.get_port();
 
-    // Pick the first address — prefer IPv4 for wider printer compatibility.
-    let ip: IpAddr = info
-        .get_addresses()
-        .iter()
-        .find(|a| a.is_ipv4())
-        .or_else(|| info.get_addresses().iter().next())
+    // Both A and AAAA records, best first (see `address::preference`): IPv4
+    // for wider printer compatibility, link-local only as a last resort.
+    // They come as a set, so equally ranked ones are put in address order.
+    let mut addresses: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+    addresses.sort_unstable();
+    address::sort_by_preference(&mut addresses);
+    let ip: IpAddr = addresses
+        .first()
         .copied()
         .ok_or_else(|| PresswerkError::Discovery(format!("no address for service {name}")))?;
 
-    // Build the IPP URI from TXT `rp` key or fall back to "ipp/print".
+    // Build the IPP URI from TXT `rp` key or fall back to "ipp/print".  A
+    // printer with only link-local IPv6 addresses is addressed by its mDNS
+    // hostname, as the zone cannot go into the URI.
     let resource_path = info.get_property_val_str("rp").unwrap_or("ipp/print");
-
     let scheme = if tls { "ipps" } else { "ipp" };
-    let uri = format!("{scheme}://{ip}:{port}/{resource_path}");
+    let host = address::uri_host_for(&addresses, Some(info.get_hostname())).ok_or_else(|| {
+        PresswerkError::Discovery(format!("no usable address for service {name}"))
+    })?;
+    let uri = format!("{scheme}://{host}:{port}/{resource_path}");
 
-    // Parse capability flags from TXT records.
-    let supports_color = txt_bool(info, "Color");
-    let supports_duplex = txt_bool(info, "Duplex");
+    // Capabilities as far as the TXT record tells them.
+    let capabilities = PrinterCapabilities::from_txt(&txt_properties(info));
+    let mut document_formats: Vec<String> = capabilities
+        .document_formats_supported
+        .iter()
+        .cloned()
+        .collect();
+    document_formats.sort_unstable();
 
     let make_and_model = info
         .get_property_val_str("printer-make-and-model")
@@ -254,14 +645,15 @@ fn service_info_to_printer(info: &ServiceInfo, tls: bool) -> Result<DiscoveredPr
     let location = info
         .get_property_val_str("printer-location")
         .map(String::from);
+    let uuid = info.get_property_val_str("UUID").and_then(normalize_uuid);
 
     Ok(DiscoveredPrinter {
         name,
         uri,
         ip,
         port,
-        supports_color,
-        supports_duplex,
+        supports_color: capabilities.color_supported,
+        supports_duplex: capabilities.supports_two_sided(),
         supports_tls: tls,
         paper_sizes: Vec::new(), // determined later via Get-Printer-Attributes
         make_and_model,
@@ -269,23 +661,446 @@ fn service_info_to_printer(info: &ServiceInfo, tls: bool) -> Result<DiscoveredPr
         last_seen: Utc::now(),
         stale: false,
         manually_added: false,
+        addresses,
+        uuid,
+        document_formats,
     })
 }
 
-/// Read a boolean TXT record value.  IPP Everywhere uses "T"/"F".
-fn txt_bool(info: &ServiceInfo, key: &str) -> bool {
-    info.get_property_val_str(key)
-        .map(|v| v.eq_ignore_ascii_case("t") || v.eq_ignore_ascii_case("true"))
-        .unwrap_or(false)
+/// Query all of `printers` at once and keep, in their original order, the
+/// ones that answered within `max` and are idle.
+pub async fn ready_printers(printers: Vec<DiscoveredPrinter>, max: Duration) -> Vec<ReadyPrinter> {
+    let mut checks = tokio::task::JoinSet::new();
+    for (position, printer) in printers.into_iter().enumerate() {
+        checks.spawn(async move {
+            let check = tokio::time::timeout(max, check_ready(&printer)).await;
+            let ready = match check {
+                Ok(Ok(Some((capabilities, readiness)))) => Some(ReadyPrinter {
+                    printer,
+                    capabilities,
+                    readiness,
+                }),
+                Ok(Ok(None)) => None,
+                Ok(Err(e)) => {
+                    debug!(printer = %printer.name, error = %e, "printer did not answer");
+                    None
+                }
+                Err(_) => {
+                    debug!(printer = %printer.name, "printer too slow to answer");
+                    None
+                }
+            };
+            (position, ready)
+        });
+    }
+
+    let mut ready = Vec::new();
+    while let Some(result) = checks.join_next().await {
+        if let Ok((position, Some(printer))) = result {
+            ready.push((position, printer));
+        }
+    }
+    ready.sort_by_key(|(position, _)| *position);
+    info!(count = ready.len(), "ready printers found");
+    ready.into_iter().map(|(_, printer)| printer).collect()
+}
+
+/// [`PrinterDiscovery::probe_subnet`], trying at most `max_hosts` addresses.
+async fn probe_hosts(cidr: IpNet, port: u16, timeout: Duration, max_hosts: usize) -> Vec<IpAddr> {
+    let mut hosts = cidr.hosts();
+    let mut probes = tokio::task::JoinSet::new();
+    let mut found = Vec::new();
+
+    for ip in hosts.by_ref().take(max_hosts) {
+        if probes.len() >= MAX_PROBE_CONCURRENCY
+            && let Some(Ok(Some(ip))) = probes.join_next().await
+        {
+            found.push(ip);
+        }
+        probes.spawn(async move {
+            let connect = tokio::net::TcpStream::connect(SocketAddr::new(ip, port));
+            matches!(tokio::time::timeout(timeout, connect).await, Ok(Ok(_))).then_some(ip)
+        });
+    }
+    if hosts.next().is_some() {
+        warn!(%cidr, max = max_hosts, "subnet too large, probed only its start");
+    }
+
+    while let Some(result) = probes.join_next().await {
+        if let Ok(Some(ip)) = result {
+            found.push(ip);
+        }
+    }
+    found.sort();
+    info!(%cidr, port, count = found.len(), "subnet probe finished");
+    found
+}
+
+/// One Get-Printer-Attributes round-trip, giving the capabilities and
+/// state if the printer is idle.
+async fn check_ready(
+    printer: &DiscoveredPrinter,
+) -> Result<Option<(PrinterCapabilities, PrinterReadiness)>> {
+    let attrs = IppClient::new(&printer.uri)?
+        .get_printer_attributes()
+        .await?;
+    let readiness = PrinterReadiness::from_attributes(&attrs);
+    if !readiness.is_idle() {
+        debug!(printer = %printer.name, state = %readiness.state, "printer not idle");
+        return Ok(None);
+    }
+    Ok(Some((
+        PrinterCapabilities::from_attributes(&attrs),
+        readiness,
+    )))
+}
+
+/// Merge entries that are the same printer seen on different interfaces.
+///
+/// Entries are the same printer if they share a `UUID` TXT record or, when
+/// either lacks one, the same make/model and service name.  IPP and IPPS
+/// advertisements stay separate since they lead to different URIs.  The
+/// first entry of each group is kept, with the addresses of the others
+/// appended and `last_seen` the latest of the group; it is addressed like
+/// whichever entry has the best-ranked address (see
+/// [`address::preference`]), the earliest one on a tie.
+pub fn deduplicate(printers: Vec<DiscoveredPrinter>) -> Vec<DiscoveredPrinter> {
+    let mut merged: Vec<DiscoveredPrinter> = Vec::with_capacity(printers.len());
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for printer in printers {
+        let key = identity_key(&printer);
+        match index.get(&key) {
+            Some(&i) => {
+                let kept = &mut merged[i];
+                absorb(kept, printer);
+                debug!(printer = %kept.name, "merged duplicate discovery result");
+            }
+            None => {
+                let mut printer = printer;
+                if printer.addresses.is_empty() {
+                    printer.addresses.push(printer.ip);
+                }
+                index.insert(key, merged.len());
+                merged.push(printer);
+            }
+        }
+    }
+    merged
+}
+
+/// Fold `other`, another sighting of the same printer, into `kept`.
+fn absorb(kept: &mut DiscoveredPrinter, other: DiscoveredPrinter) {
+    if address::preference(&other.ip) < address::preference(&kept.ip) {
+        kept.ip = other.ip;
+        kept.port = other.port;
+        kept.uri = other.uri;
+    }
+    // The address in use leads; the rest keep the order they were seen in
+    // within their rank.
+    let mut addresses = vec![kept.ip];
+    addresses.append(&mut kept.addresses);
+    addresses.push(other.ip);
+    addresses.extend(other.addresses);
+    address::sort_by_preference(&mut addresses);
+    kept.addresses = addresses;
+
+    kept.last_seen = kept.last_seen.max(other.last_seen);
+    kept.stale &= other.stale;
+}
+
+/// What makes two discovery results the same printer.
+fn identity_key(printer: &DiscoveredPrinter) -> String {
+    let scheme = if printer.supports_tls { "ipps" } else { "ipp" };
+    match &printer.uuid {
+        Some(uuid) => format!("{scheme}|uuid|{uuid}"),
+        None => format!(
+            "{scheme}|name|{}|{}",
+            printer.make_and_model.as_deref().unwrap_or(""),
+            printer.name
+        ),
+    }
+}
+
+/// Lower-case a `UUID` TXT value, dropping any `urn:uuid:` prefix.
+fn normalize_uuid(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let uuid = raw.strip_prefix("urn:uuid:").unwrap_or(raw);
+    (!uuid.is_empty()).then(|| uuid.to_ascii_lowercase())
+}
+
+/// The TXT record of a resolved service as a key/value map.
+fn txt_properties(info: &ServiceInfo) -> HashMap<String, String> {
+    info.get_properties()
+        .iter()
+        .map(|property| (property.key().to_string(), property.val_str().to_string()))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn resolved(name: &str, ip: &str, uuid: Option<&str>) -> DiscoveredPrinter {
+        let ip: IpAddr = ip.parse().unwrap();
+        DiscoveredPrinter {
+            name: name.into(),
+            uri: format!("ipp://{ip}:631/ipp/print"),
+            ip,
+            port: 631,
+            supports_color: true,
+            supports_duplex: false,
+            supports_tls: false,
+            paper_sizes: Vec::new(),
+            make_and_model: Some("HP OfficeJet 8010".into()),
+            location: None,
+            last_seen: Utc::now(),
+            stale: false,
+            manually_added: false,
+            addresses: vec![ip],
+            uuid: uuid.and_then(normalize_uuid),
+            document_formats: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn same_uuid_on_two_interfaces_collapses_into_one_entry() {
+        let uuid = "urn:uuid:4509A320-00A0-008F-00B6-002507510EEB";
+        let printers = deduplicate(vec![
+            resolved("OfficeJet._ipp._tcp.local.", "192.168.1.40", Some(uuid)),
+            resolved("OfficeJet (2)._ipp._tcp.local.", "10.0.0.40", Some(uuid)),
+            resolved("Brother._ipp._tcp.local.", "192.168.1.41", None),
+        ]);
+
+        assert_eq!(printers.len(), 2);
+        let merged = &printers[0];
+        assert_eq!(merged.ip, "192.168.1.40".parse::<IpAddr>().unwrap());
+        assert_eq!(
+            merged.addresses,
+            vec![
+                "192.168.1.40".parse::<IpAddr>().unwrap(),
+                "10.0.0.40".parse::<IpAddr>().unwrap(),
+            ]
+        );
+        assert_eq!(
+            merged.uuid.as_deref(),
+            Some("4509a320-00a0-008f-00b6-002507510eeb")
+        );
+    }
+
+    #[test]
+    fn merged_entry_is_addressed_by_its_best_address() {
+        let uuid = Some("4509a320-00a0-008f-00b6-002507510eeb");
+        let v6 = || resolved("OfficeJet._ipp._tcp.local.", "2001:db8::40", uuid);
+        let v4 = || resolved("OfficeJet (2)._ipp._tcp.local.", "192.168.1.40", uuid);
+
+        // IPv4 wins whichever sighting comes first.
+        for printers in [vec![v6(), v4()], vec![v4(), v6()]] {
+            let merged = deduplicate(printers);
+            assert_eq!(merged.len(), 1);
+            assert_eq!(merged[0].ip, "192.168.1.40".parse::<IpAddr>().unwrap());
+            assert_eq!(merged[0].uri, "ipp://192.168.1.40:631/ipp/print");
+            assert_eq!(
+                merged[0].addresses,
+                vec![
+                    "192.168.1.40".parse::<IpAddr>().unwrap(),
+                    "2001:db8::40".parse::<IpAddr>().unwrap(),
+                ]
+            );
+        }
+    }
+
+    /// The embedded print server on loopback is found ready, with its
+    /// capabilities; a printer that does not answer is left out.
+    #[tokio::test]
+    async fn loopback_server_is_a_ready_printer() {
+        use crate::ipp_server::IppServer;
+        use crate::queue::JobQueue;
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let tmp = tempfile::tempdir().unwrap();
+        let mut server = IppServer::new(Some(port), Some(tmp.path().to_path_buf()));
+        let queue = JobQueue::open_in_memory().unwrap();
+        server.start(Arc::new(Mutex::new(queue))).await.unwrap();
+
+        let mut embedded = resolved("Presswerk._ipp._tcp.local.", "127.0.0.1", None);
+        embedded.uri = format!("ipp://127.0.0.1:{port}/ipp/print");
+        let mut gone = resolved("Gone._ipp._tcp.local.", "127.0.0.1", None);
+        gone.uri = "ipp://127.0.0.1:9/ipp/print".into();
+
+        let ready = ready_printers(vec![gone, embedded], Duration::from_secs(10)).await;
+        server.stop().await.unwrap();
+
+        assert_eq!(ready.len(), 1, "{ready:?}");
+        let printer = &ready[0];
+        assert_eq!(printer.printer.name, "Presswerk._ipp._tcp.local.");
+        assert!(printer.readiness.is_idle());
+        assert!(
+            printer
+                .capabilities
+                .media_supported
+                .contains("iso_a4_210x297mm")
+        );
+        assert!(
+            printer
+                .capabilities
+                .document_formats_supported
+                .contains("application/pdf")
+        );
+    }
+
+    // Only 127.0.0.1 is used: other loopback addresses are not configured
+    // on macOS.
+
+    #[tokio::test]
+    async fn subnet_probe_finds_listeners_on_a_loopback_range() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let cidr: IpNet = "127.0.0.0/29".parse().unwrap();
+        let found = PrinterDiscovery::probe_subnet(cidr, port, Duration::from_millis(500)).await;
+        assert_eq!(found, vec![IpAddr::from([127, 0, 0, 1])]);
+
+        drop(listener);
+        let found = PrinterDiscovery::probe_subnet(cidr, port, Duration::from_millis(500)).await;
+        assert!(found.is_empty());
+    }
+
+    #[tokio::test]
+    async fn subnet_probe_caps_a_wide_range() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // A /8 is cut to its first few addresses, which hold the listener.
+        let cidr: IpNet = "127.0.0.0/8".parse().unwrap();
+        let found = probe_hosts(cidr, port, Duration::from_secs(2), 4).await;
+        assert_eq!(found, vec![IpAddr::from([127, 0, 0, 1])]);
+    }
+
+    fn service(instance: &str, ip: &str, location: &str) -> ServiceEvent {
+        let info = ServiceInfo::new(
+            IPP_SERVICE,
+            instance,
+            "printer.local.",
+            ip,
+            631,
+            &[("rp", "ipp/print"), ("printer-location", location)][..],
+        )
+        .unwrap();
+        ServiceEvent::ServiceResolved(info)
+    }
+
+    fn label(event: &DiscoveryEvent) -> String {
+        match event {
+            DiscoveryEvent::Added(p) => format!("added {}", p.name),
+            DiscoveryEvent::Updated(p) => {
+                format!("updated {} {}", p.name, p.location.as_deref().unwrap_or(""))
+            }
+            DiscoveryEvent::Removed(p) => format!("removed {}", p.name),
+        }
+    }
+
+    #[test]
+    fn dispatcher_dedups_and_coalesces_txt_updates() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&events);
+        let mut dispatcher = Dispatcher::new(
+            false,
+            Arc::new(move |event: DiscoveryEvent| log.lock().unwrap().push(label(&event))),
+        );
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+        let office = "Office._ipp._tcp.local.";
+
+        dispatcher.handle(service("Office", "192.168.1.40", "Hall"), ms(0));
+        // Same advertisement again, e.g. from a second interface query.
+        dispatcher.handle(service("Office", "192.168.1.40", "Hall"), ms(10));
+        // A burst of TXT changes.
+        dispatcher.handle(service("Office", "192.168.1.40", "Room 1"), ms(100));
+        dispatcher.handle(service("Office", "192.168.1.40", "Room 2"), ms(300));
+        dispatcher.flush(ms(700));
+        assert_eq!(dispatcher.next_due(), Some(ms(800)));
+        dispatcher.flush(ms(800));
+        assert_eq!(dispatcher.next_due(), None);
+
+        // A change that is reverted within the window is never reported.
+        dispatcher.handle(service("Office", "192.168.1.40", "Room 3"), ms(900));
+        dispatcher.handle(service("Office", "192.168.1.40", "Room 2"), ms(950));
+        dispatcher.flush(ms(2000));
+
+        let removed = || ServiceEvent::ServiceRemoved(IPP_SERVICE.into(), office.into());
+        dispatcher.handle(removed(), ms(2100));
+        dispatcher.handle(removed(), ms(2200));
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                format!("added {office}"),
+                format!("updated {office} Room 2"),
+                format!("removed {office}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn dispatcher_merges_names_with_the_same_uuid() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&events);
+        let mut dispatcher = Dispatcher::new(
+            false,
+            Arc::new(move |event: DiscoveryEvent| log.lock().unwrap().push(event)),
+        );
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+        let sighting = |instance: &str, ip: &str| {
+            let info = ServiceInfo::new(
+                IPP_SERVICE,
+                instance,
+                "printer.local.",
+                ip,
+                631,
+                &[("rp", "ipp/print"), ("UUID", "urn:uuid:4509A320-00A0-008F")][..],
+            )
+            .unwrap();
+            ServiceEvent::ServiceResolved(info)
+        };
+        let removed = |instance: &str| {
+            ServiceEvent::ServiceRemoved(IPP_SERVICE.into(), format!("{instance}.{IPP_SERVICE}"))
+        };
+
+        dispatcher.handle(sighting("OfficeJet", "192.168.1.40"), ms(0));
+        dispatcher.handle(sighting("OfficeJet (2)", "10.0.0.40"), ms(10));
+        dispatcher.flush(ms(600));
+        dispatcher.handle(removed("OfficeJet"), ms(700));
+        dispatcher.flush(ms(1300));
+        dispatcher.handle(removed("OfficeJet (2)"), ms(1400));
+
+        let events = events.lock().unwrap();
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        match &events[..] {
+            [
+                DiscoveryEvent::Added(added),
+                DiscoveryEvent::Updated(both),
+                DiscoveryEvent::Updated(left),
+                DiscoveryEvent::Removed(_),
+            ] => {
+                assert_eq!(added.addresses, vec![ip("192.168.1.40")]);
+                assert_eq!(both.addresses, vec![ip("192.168.1.40"), ip("10.0.0.40")]);
+                assert_eq!(left.ip, ip("10.0.0.40"));
+                assert_eq!(left.addresses, vec![ip("10.0.0.40")]);
+            }
+            other => panic!("unexpected events: {other:?}"),
+        }
+    }
+
     #[test]
     fn txt_bool_logic_parses_true_variants() {
-        // Tests the boolean-parsing logic used by `txt_bool`.
         // Full integration with `ServiceInfo` requires a live mDNS network.
-        let parse = |v: &str| v.eq_ignore_ascii_case("t") || v.eq_ignore_ascii_case("true");
+        let parse = crate::capabilities::txt_flag;
         assert!(parse("T"));
         assert!(parse("t"));
         assert!(parse("true"));
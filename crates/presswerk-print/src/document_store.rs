@@ -0,0 +1,555 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Content-addressed document store shared by the IPP server and the app.
+//
+// Document payloads live at `{data_dir}/documents/{sha256}.dat`, next to the
+// job queue database that references them by hash.  Identical documents are
+// stored once.  Writes are crash-safe: bytes go to a uniquely-named temp file
+// first and are published with `hard_link`, which (unlike `rename`) fails if
+// the target already exists — so when two writers race on the same content
+// exactly one of them publishes and the other simply discards its temp file.
+//
+// Each job holding a blob leaves a marker file under
+// `{data_dir}/document_refs/{sha256}/{job_id}`.  Releasing a job removes its
+// marker and deletes the blob only once no markers remain, so deleting one of
+// several jobs that printed the same document keeps the blob for the others.
+// Holders other than jobs (such as in-progress scan sessions) use their own
+// marker names through `add_named_ref` / `release_named`.
+//
+// A crash between storing a job's document and committing its queue row
+// leaves a marker for a job that does not exist; `sweep_orphans`, run at
+// startup, drops such markers and any blob nothing references.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, instrument, warn};
+use uuid::Uuid;
+
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::JobId;
+
+/// Subdirectory (under the data dir) holding the document blobs.
+const DOCUMENTS_DIR: &str = "documents";
+
+/// Subdirectory (under the data dir) holding per-job reference markers.
+const REFS_DIR: &str = "document_refs";
+
+/// Extension of published blobs.
+const BLOB_EXT: &str = "dat";
+
+/// Extension of in-flight temp files (cleaned up on open).
+const TEMP_EXT: &str = "tmp";
+
+/// Content-addressed, reference-counted document storage on disk.
+///
+/// `DocumentStore` is `Send + Sync`; share it behind an `Arc`.
+pub struct DocumentStore {
+    /// `{data_dir}/documents`.
+    documents_dir: PathBuf,
+    /// `{data_dir}/document_refs`.
+    refs_dir: PathBuf,
+    /// Serialises reference bookkeeping so a concurrent `add_ref` cannot
+    /// resurrect a blob that `release` is in the middle of deleting.
+    refs_lock: Mutex<()>,
+}
+
+impl DocumentStore {
+    /// Open (or create) the store rooted at `data_dir`.
+    ///
+    /// Leftover temp files from an interrupted write are removed.
+    #[instrument(skip_all, fields(path = %data_dir.as_ref().display()))]
+    pub fn open(data_dir: impl AsRef<Path>) -> Result<Self> {
+        let data_dir = data_dir.as_ref();
+        let documents_dir = data_dir.join(DOCUMENTS_DIR);
+        let refs_dir = data_dir.join(REFS_DIR);
+
+        fs::create_dir_all(&documents_dir)?;
+        fs::create_dir_all(&refs_dir)?;
+
+        let store = Self {
+            documents_dir,
+            refs_dir,
+            refs_lock: Mutex::new(()),
+        };
+        store.remove_stale_temp_files();
+
+        info!(path = %store.documents_dir.display(), "document store opened");
+        Ok(store)
+    }
+
+    /// Path where the blob for `hash` is (or would be) stored.
+    pub fn path(&self, hash: &str) -> PathBuf {
+        self.documents_dir.join(format!("{hash}.{BLOB_EXT}"))
+    }
+
+    /// Whether a blob for `hash` is present.
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path(hash).exists()
+    }
+
+    /// Store `data` and return its SHA-256 hex digest.
+    ///
+    /// If a blob with the same hash already exists nothing is written.  The
+    /// blob is unreferenced, so releasing the last holder of the same content
+    /// deletes it; use [`put_named`](Self::put_named) to keep it.
+    #[instrument(skip_all, fields(bytes = data.len()))]
+    pub fn put(&self, data: &[u8]) -> Result<String> {
+        let hash = hex::encode(Sha256::digest(data));
+        let target = self.path(&hash);
+
+        if target.exists() {
+            debug!(hash = %hash, "document already stored; skipping write");
+            return Ok(hash);
+        }
+
+        let temp = self
+            .documents_dir
+            .join(format!(".{hash}.{}.{TEMP_EXT}", Uuid::new_v4()));
+        let written = write_synced(&temp, data).and_then(|()| publish(&temp, &target));
+        // The temp name is only ever a staging link; drop it either way.
+        let _ = fs::remove_file(&temp);
+
+        match written {
+            Ok(true) => info!(hash = %hash, bytes = data.len(), "document stored"),
+            Ok(false) => debug!(hash = %hash, "concurrent writer stored document first"),
+            Err(e) => return Err(PresswerkError::Io(e)),
+        }
+
+        Ok(hash)
+    }
+
+    /// Store `data` on behalf of `job_id` and record the reference.
+    pub fn put_for_job(&self, job_id: &JobId, data: &[u8]) -> Result<String> {
+        self.put_named(data, &job_id.to_string())
+    }
+
+    /// Store `data` on behalf of `holder` and record the reference.
+    ///
+    /// The blob is published and the reference recorded under the same
+    /// lock as [`release_named`](Self::release_named), so a concurrent
+    /// release of the last other holder cannot delete the blob in between.
+    #[instrument(skip(self, data), fields(bytes = data.len()))]
+    pub fn put_named(&self, data: &[u8], holder: &str) -> Result<String> {
+        let hash = hex::encode(Sha256::digest(data));
+        let target = self.path(&hash);
+
+        // Write new content outside the lock; only publishing needs it.
+        let mut staged = None;
+        if !target.exists() {
+            staged = Some(self.stage(&hash, data)?);
+        }
+
+        let stored = {
+            let _guard = self.lock_refs();
+            self.publish_locked(&hash, &mut staged, data)
+                .and_then(|()| self.record_ref(&hash, holder))
+        };
+        if let Some(temp) = staged {
+            let _ = fs::remove_file(temp);
+        }
+        stored?;
+        Ok(hash)
+    }
+
+    /// A fresh path in the store's directory for the caller to write a
+    /// document to before handing it over with
+    /// [`put_file_for_job`](Self::put_file_for_job).  Left behind by a
+    /// crash, it is removed the next time the store is opened.
+    pub fn temp_path(&self) -> PathBuf {
+        self.documents_dir
+            .join(format!(".spool.{}.{TEMP_EXT}", Uuid::new_v4()))
+    }
+
+    /// Store the file at `temp` on behalf of `job_id` without reading it
+    /// into memory, and record the reference.
+    ///
+    /// `temp` must come from [`temp_path`](Self::temp_path) and already be
+    /// synced to disk; it is consumed either way.  `hash` must be its
+    /// SHA-256 hex digest, as computed while writing it.
+    #[instrument(skip(self, temp), fields(job_id = %job_id))]
+    pub fn put_file_for_job(&self, job_id: &JobId, temp: &Path, hash: &str) -> Result<String> {
+        let target = self.path(hash);
+        let stored = {
+            let _guard = self.lock_refs();
+            let published = if target.exists() {
+                Ok(false)
+            } else {
+                publish(temp, &target)
+            };
+            published.map_err(PresswerkError::Io).and_then(|published| {
+                if published {
+                    info!(hash, "spooled document stored");
+                } else {
+                    debug!(hash, "document already stored; spool discarded");
+                }
+                self.record_ref(hash, &job_id.to_string())
+            })
+        };
+        let _ = fs::remove_file(temp);
+
+        stored?;
+        Ok(hash.to_string())
+    }
+
+    /// Read the blob for `hash`.
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        Ok(fs::read(self.path(hash))?)
+    }
+
+    /// Record that `job_id` uses the blob for `hash`.
+    pub fn add_ref(&self, hash: &str, job_id: &JobId) -> Result<()> {
+        self.add_named_ref(hash, &job_id.to_string())
+    }
+
+    /// Record that `holder` uses the blob for `hash`.
+    ///
+    /// `holder` becomes a file name and shares its namespace with job ids,
+    /// so non-job holders should use a distinctive prefix.
+    pub fn add_named_ref(&self, hash: &str, holder: &str) -> Result<()> {
+        let _guard = self.lock_refs();
+        self.record_ref(hash, holder)
+    }
+
+    /// Number of jobs currently referencing the blob for `hash`.
+    pub fn ref_count(&self, hash: &str) -> usize {
+        let _guard = self.lock_refs();
+        count_entries(&self.refs_dir.join(hash))
+    }
+
+    /// Drop `job_id`'s reference to `hash`, deleting the blob when it was
+    /// the last one.
+    ///
+    /// Returns `true` if the blob was removed.
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub fn release(&self, hash: &str, job_id: &JobId) -> Result<bool> {
+        self.release_named(hash, &job_id.to_string())
+    }
+
+    /// Drop `holder`'s reference to `hash`, deleting the blob when it was
+    /// the last one.  See [`release`](Self::release).
+    pub fn release_named(&self, hash: &str, holder: &str) -> Result<bool> {
+        let _guard = self.lock_refs();
+        let dir = self.refs_dir.join(hash);
+
+        match fs::remove_file(dir.join(holder)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(PresswerkError::Io(e)),
+        }
+
+        if count_entries(&dir) > 0 {
+            debug!(hash, "document still referenced; keeping blob");
+            return Ok(false);
+        }
+
+        let _ = fs::remove_dir(&dir);
+        match fs::remove_file(self.path(hash)) {
+            Ok(()) => {
+                info!(hash, "last reference released; document removed");
+                Ok(true)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(PresswerkError::Io(e)),
+        }
+    }
+
+    /// Drop the markers of jobs for which `is_live` is false, then delete
+    /// every blob left without a marker.  Markers of non-job holders are
+    /// kept.  Returns how many blobs were removed.
+    ///
+    /// Call at startup, before anything stores documents: an unreferenced
+    /// blob written by [`put`](Self::put) is removed too.
+    #[instrument(skip_all)]
+    pub fn sweep_orphans(&self, is_live: impl Fn(&JobId) -> bool) -> Result<usize> {
+        let _guard = self.lock_refs();
+
+        for entry in fs::read_dir(&self.refs_dir)?.flatten() {
+            let dir = entry.path();
+            for marker in fs::read_dir(&dir).into_iter().flatten().flatten() {
+                let orphaned = marker
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.parse::<JobId>().ok())
+                    .is_some_and(|job_id| !is_live(&job_id));
+                if orphaned {
+                    fs::remove_file(marker.path())?;
+                    debug!(marker = %marker.path().display(), "removed marker of a missing job");
+                }
+            }
+            // Only succeeds once the directory is empty.
+            let _ = fs::remove_dir(&dir);
+        }
+
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.documents_dir)?.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != BLOB_EXT) {
+                continue;
+            }
+            let Some(hash) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if count_entries(&self.refs_dir.join(hash)) == 0 {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            info!(removed, "removed documents no job references");
+        }
+        Ok(removed)
+    }
+
+    /// Write `data` to a fresh temp file for the blob `hash`.
+    fn stage(&self, hash: &str, data: &[u8]) -> Result<PathBuf> {
+        let temp = self
+            .documents_dir
+            .join(format!(".{hash}.{}.{TEMP_EXT}", Uuid::new_v4()));
+        if let Err(e) = write_synced(&temp, data) {
+            let _ = fs::remove_file(&temp);
+            return Err(PresswerkError::Io(e));
+        }
+        Ok(temp)
+    }
+
+    /// Make sure the blob for `hash` exists, publishing `staged` (written
+    /// now if the blob vanished since the caller checked).  Call with the
+    /// bookkeeping lock held.
+    fn publish_locked(&self, hash: &str, staged: &mut Option<PathBuf>, data: &[u8]) -> Result<()> {
+        let target = self.path(hash);
+        if target.exists() {
+            debug!(hash, "document already stored; skipping write");
+            return Ok(());
+        }
+        let temp = match staged {
+            Some(temp) => temp.clone(),
+            None => staged.insert(self.stage(hash, data)?).clone(),
+        };
+        match publish(&temp, &target) {
+            Ok(true) => info!(hash, bytes = data.len(), "document stored"),
+            Ok(false) => debug!(hash, "concurrent writer stored document first"),
+            Err(e) => return Err(PresswerkError::Io(e)),
+        }
+        Ok(())
+    }
+
+    /// Create `holder`'s marker for `hash`.  Call with the bookkeeping lock
+    /// held.
+    fn record_ref(&self, hash: &str, holder: &str) -> Result<()> {
+        let dir = self.refs_dir.join(hash);
+        fs::create_dir_all(&dir)?;
+        File::create(dir.join(holder))?;
+        Ok(())
+    }
+
+    /// Acquire the bookkeeping lock, recovering from poison (the guarded
+    /// state lives on disk, so a panic elsewhere cannot corrupt it).
+    fn lock_refs(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.refs_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn remove_stale_temp_files(&self) {
+        let Ok(entries) = fs::read_dir(&self.documents_dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == TEMP_EXT) {
+                match fs::remove_file(&path) {
+                    Ok(()) => debug!(path = %path.display(), "removed stale temp file"),
+                    Err(e) => warn!(path = %path.display(), error = %e, "cannot remove temp file"),
+                }
+            }
+        }
+    }
+}
+
+/// Write `data` to a new file at `path` and flush it to stable storage.
+fn write_synced(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Atomically publish `temp` as `target`.
+///
+/// Returns `Ok(false)` if another writer published the same content first.
+/// Falls back to `rename` on filesystems without hard-link support.
+fn publish(temp: &Path, target: &Path) -> std::io::Result<bool> {
+    match fs::hard_link(temp, target) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => {
+            debug!(error = %e, "hard_link unsupported; falling back to rename");
+            if target.exists() {
+                return Ok(false);
+            }
+            fs::rename(temp, target)?;
+            Ok(true)
+        }
+    }
+}
+
+fn count_entries(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn blob_files(tmp: &Path) -> Vec<PathBuf> {
+        fs::read_dir(tmp.join(DOCUMENTS_DIR))
+            .expect("read documents dir")
+            .flatten()
+            .map(|e| e.path())
+            .collect()
+    }
+
+    #[test]
+    fn put_and_get_roundtrip() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = DocumentStore::open(tmp.path()).unwrap();
+
+        let hash = store.put(b"hello document").unwrap();
+        assert!(store.contains(&hash));
+        assert_eq!(store.get(&hash).unwrap(), b"hello document");
+        assert_eq!(
+            store.path(&hash),
+            tmp.path().join("documents").join(format!("{hash}.dat"))
+        );
+    }
+
+    #[test]
+    fn concurrent_put_of_identical_content_writes_one_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(DocumentStore::open(tmp.path()).unwrap());
+        let data = vec![0x42u8; 256 * 1024];
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                let data = data.clone();
+                std::thread::spawn(move || store.put(&data).unwrap())
+            })
+            .collect();
+        let hashes: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert!(hashes.windows(2).all(|w| w[0] == w[1]));
+        let files = blob_files(tmp.path());
+        assert_eq!(files.len(), 1, "expected exactly one blob, got {files:?}");
+        assert_eq!(store.get(&hashes[0]).unwrap(), data);
+    }
+
+    #[test]
+    fn releasing_one_of_two_jobs_keeps_shared_blob() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = DocumentStore::open(tmp.path()).unwrap();
+        let first = JobId::new();
+        let second = JobId::new();
+
+        let hash = store.put_for_job(&first, b"shared").unwrap();
+        assert_eq!(store.put_for_job(&second, b"shared").unwrap(), hash);
+        assert_eq!(store.ref_count(&hash), 2);
+
+        assert!(!store.release(&hash, &first).unwrap());
+        assert!(store.contains(&hash), "blob must survive while referenced");
+
+        assert!(store.release(&hash, &second).unwrap());
+        assert!(!store.contains(&hash));
+    }
+
+    #[test]
+    fn put_for_job_is_not_undone_by_a_concurrent_release() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(DocumentStore::open(tmp.path()).unwrap());
+        let data = b"shared by two jobs".to_vec();
+
+        let churn = {
+            let store = Arc::clone(&store);
+            let data = data.clone();
+            std::thread::spawn(move || {
+                for _ in 0..200 {
+                    let job = JobId::new();
+                    let hash = store.put_for_job(&job, &data).unwrap();
+                    store.release(&hash, &job).unwrap();
+                }
+            })
+        };
+        for _ in 0..200 {
+            let job = JobId::new();
+            let hash = store.put_for_job(&job, &data).unwrap();
+            assert!(store.contains(&hash), "blob deleted while referenced");
+            store.release(&hash, &job).unwrap();
+        }
+        churn.join().unwrap();
+    }
+
+    #[test]
+    fn put_file_for_job_takes_over_the_temp_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = DocumentStore::open(tmp.path()).unwrap();
+        let hash = hex::encode(Sha256::digest(b"spooled"));
+
+        let temp = store.temp_path();
+        fs::write(&temp, b"spooled").unwrap();
+        assert_eq!(
+            store.put_file_for_job(&JobId::new(), &temp, &hash).unwrap(),
+            hash
+        );
+        assert!(!temp.exists());
+        assert_eq!(store.get(&hash).unwrap(), b"spooled");
+
+        // The same content again only adds a reference.
+        let again = store.temp_path();
+        fs::write(&again, b"spooled").unwrap();
+        store
+            .put_file_for_job(&JobId::new(), &again, &hash)
+            .unwrap();
+        assert!(!again.exists());
+        assert_eq!(blob_files(tmp.path()), [store.path(&hash)]);
+        assert_eq!(store.ref_count(&hash), 2);
+    }
+
+    #[test]
+    fn sweep_removes_documents_of_missing_jobs() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = DocumentStore::open(tmp.path()).unwrap();
+        let (live, lost) = (JobId::new(), JobId::new());
+
+        let kept = store.put_for_job(&live, b"still queued").unwrap();
+        let shared = store.put_for_job(&lost, b"still queued").unwrap();
+        let orphan = store.put_for_job(&lost, b"crashed before commit").unwrap();
+        let saved = store.put_named(b"saved copy", "saved").unwrap();
+        let unreferenced = store.put(b"never referenced").unwrap();
+
+        assert_eq!(store.sweep_orphans(|id| *id == live).unwrap(), 2);
+        assert!(store.contains(&kept));
+        assert_eq!(store.ref_count(&shared), 1);
+        assert!(!store.contains(&orphan));
+        assert!(store.contains(&saved));
+        assert!(!store.contains(&unreferenced));
+    }
+
+    #[test]
+    fn open_removes_stale_temp_files() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let docs = tmp.path().join(DOCUMENTS_DIR);
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(docs.join(".abc.partial.tmp"), b"half written").unwrap();
+
+        let _store = DocumentStore::open(tmp.path()).unwrap();
+        assert!(blob_files(tmp.path()).is_empty());
+    }
+}
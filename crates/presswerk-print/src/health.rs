@@ -7,12 +7,28 @@
 // will just time out. Instead, short-circuit immediately and tell the user
 // the printer is having trouble. Periodically allow a probe request through
 // to check if the printer has recovered.
+//
+// Health polling adapts to the printer: a printer that keeps answering the
+// same way is checked less and less often, and any change or failure brings
+// the interval straight back down (see `PollInterval`).
+//
+// Outcomes recorded with `record_result` are also kept in SQLite, so the
+// success rate and latency of each printer survive restarts (see
+// `recent_health`), and picking between equivalent printers can favour
+// the one with the better record (see `best_of`).
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
 use tracing::{debug, info, warn};
 
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::ErrorClass;
+
+
 /// Circuit breaker state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CircuitState {
@@ -37,6 +53,8 @@ pub struct PrinterHealth {
     pub last_success: Option<Instant>,
     /// Last failure message.
     pub last_error: Option<String>,
+    /// How long to wait before the next health poll.
+    pub poll_interval: PollInterval,
 }
 
 impl Default for PrinterHealth {
@@ -47,16 +65,177 @@ fn default() -> Self {
             opened_at: None,
             last_success: None,
             last_error: None,
+            poll_interval: PollInterval::default(),
+        }
+    }
+}
+
+/// Shortest time between health polls.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest time between health polls.
+pub const MAX_POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Interval used for a printer we know nothing about yet.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Consistent results needed before the interval is widened.
+const STABLE_CHECKS: u32 = 3;
+
+/// Adaptive health-poll interval.
+///
+/// Every `STABLE_CHECKS` results that agree with the one before double the
+/// interval (up to [`MAX_POLL_INTERVAL`]); a result that differs, or the
+/// first failure after a success, drops it to [`MIN_POLL_INTERVAL`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollInterval {
+    current: Duration,
+    last_healthy: Option<bool>,
+    consistent: u32,
+}
+
+impl Default for PollInterval {
+    fn default() -> Self {
+        Self {
+            current: DEFAULT_POLL_INTERVAL,
+            last_healthy: None,
+            consistent: 0,
+        }
+    }
+}
+
+impl PollInterval {
+    /// The interval to wait before the next poll.
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Feed in the result of a poll and return the new interval.
+    pub fn record(&mut self, healthy: bool) -> Duration {
+        match self.last_healthy {
+            Some(last) if last == healthy => {
+                self.consistent += 1;
+                if self.consistent >= STABLE_CHECKS {
+                    self.current = (self.current * 2).min(MAX_POLL_INTERVAL);
+                    self.consistent = 0;
+                }
+            }
+            _ => {
+                // A state change (or a first failure): watch closely.
+                if self.last_healthy.is_some() || !healthy {
+                    self.current = MIN_POLL_INTERVAL;
+                }
+                self.consistent = 0;
+            }
+        }
+        self.last_healthy = Some(healthy);
+        self.current
+    }
+}
+
+/// Outcomes older than this are dropped when the history is opened.
+pub const HISTORY_RETENTION: chrono::Duration = chrono::Duration::days(30);
+
+/// How far back [`HealthTracker::best_of`] looks when ranking printers.
+pub const SELECTION_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Success rate assumed for a printer with nothing recorded, so an unknown
+/// printer ranks above one that mostly fails and below one that mostly works.
+const UNKNOWN_SUCCESS_RATE: f64 = 0.5;
+
+/// SQL to create the outcome history table.
+const CREATE_OUTCOMES_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS printer_outcomes (
+    printer_uri  TEXT NOT NULL,
+    recorded_at  INTEGER NOT NULL,
+    success      INTEGER NOT NULL,
+    error_class  TEXT,
+    latency_ms   INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_printer_outcomes_uri_time
+    ON printer_outcomes(printer_uri, recorded_at);
+"#;
+
+/// A printer's track record over a window of time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthSummary {
+    /// Operations recorded in the window.
+    pub attempts: u32,
+    /// How many of them succeeded.
+    pub successes: u32,
+    /// Failures in the window by error class.
+    pub failures_by_class: HashMap<ErrorClass, u32>,
+    /// Median latency over all attempts, if any.
+    pub median_latency: Option<Duration>,
+    /// When the most recent success in the window was recorded.
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+/// How reliable a printer has been, for a badge in the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reliability {
+    /// Nothing recorded yet.
+    Unknown,
+    /// At least 90% of attempts succeeded.
+    Good,
+    /// At least 60% of attempts succeeded.
+    Fair,
+    /// Most attempts failed.
+    Poor,
+}
+
+impl Reliability {
+    /// Short label for the badge.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Unknown => "New",
+            Self::Good => "Reliable",
+            Self::Fair => "Unsteady",
+            Self::Poor => "Unreliable",
         }
     }
 }
 
+impl HealthSummary {
+    /// Share of attempts that succeeded (0.0 to 1.0), or `None` with no
+    /// attempts.
+    pub fn success_rate(&self) -> Option<f64> {
+        (self.attempts > 0).then(|| f64::from(self.successes) / f64::from(self.attempts))
+    }
+
+    /// Badge for the printer's success rate.
+    pub fn reliability(&self) -> Reliability {
+        match self.success_rate() {
+            None => Reliability::Unknown,
+            Some(rate) if rate >= 0.9 => Reliability::Good,
+            Some(rate) if rate >= 0.6 => Reliability::Fair,
+            Some(_) => Reliability::Poor,
+        }
+    }
+
+    /// Order two summaries from least to most healthy: by success rate,
+    /// then lower median latency, then the more recent success.
+    fn rank(&self, other: &Self) -> Ordering {
+        let rate = |s: &Self| s.success_rate().unwrap_or(UNKNOWN_SUCCESS_RATE);
+        rate(self)
+            .total_cmp(&rate(other))
+            .then_with(|| match (self.median_latency, other.median_latency) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            })
+            .then_with(|| self.last_success.cmp(&other.last_success))
+    }
+}
+
 /// Manages health tracking for all known printers.
 pub struct HealthTracker {
     /// Per-printer health keyed by printer URI.
     printers: HashMap<String, PrinterHealth>,
     /// Number of failures before opening the circuit.
     failure_threshold: u32,
+    /// Outcome history, when opened with [`open`](Self::open) or
+    /// [`open_in_memory`](Self::open_in_memory).
+    history: Option<Connection>,
 }
 
 impl Default for HealthTracker {
@@ -66,13 +245,49 @@ fn default() -> Self {
 }
 
 impl HealthTracker {
+    /// A tracker without outcome history.
     pub fn new() -> Self {
         Self {
             printers: HashMap::new(),
             failure_threshold: 3,
+            history: None,
         }
     }
 
+    /// A tracker keeping its outcome history in the SQLite database at
+    /// `path`, created if missing.  Outcomes older than
+    /// [`HISTORY_RETENTION`] are dropped.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let conn = Connection::open(path.as_ref())
+            .map_err(|e| PresswerkError::Database(format!("open health history: {e}")))?;
+        Self::with_history(conn)
+    }
+
+    /// A tracker keeping its outcome history in memory (useful for tests).
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| PresswerkError::Database(format!("open in-memory health history: {e}")))?;
+        Self::with_history(conn)
+    }
+
+    fn with_history(conn: Connection) -> Result<Self> {
+        conn.execute_batch(CREATE_OUTCOMES_SQL)
+            .map_err(|e| PresswerkError::Database(format!("create printer_outcomes: {e}")))?;
+        let cutoff = (Utc::now() - HISTORY_RETENTION).timestamp_millis();
+        let pruned = conn
+            .execute(
+                "DELETE FROM printer_outcomes WHERE recorded_at < ?1",
+                params![cutoff],
+            )
+            .map_err(|e| PresswerkError::Database(format!("prune printer_outcomes: {e}")))?;
+        debug!(pruned, "health history opened");
+
+        Ok(Self {
+            history: Some(conn),
+            ..Self::new()
+        })
+    }
+
     /// Check whether a request to this printer should be allowed through.
     ///
     /// Returns `true` if the circuit is closed or half-open (probe allowed).
@@ -138,6 +353,7 @@ pub fn record_success(&mut self, printer_uri: &str) {
         health.opened_at = None;
         health.last_success = Some(Instant::now());
         health.last_error = None;
+        health.poll_interval.record(true);
     }
 
     /// Record a failed operation for this printer.
@@ -149,6 +365,7 @@ pub fn record_failure(&mut self, printer_uri: &str, error: &str) {
 
         health.consecutive_failures += 1;
         health.last_error = Some(error.to_string());
+        health.poll_interval.record(false);
 
         if health.consecutive_failures >= self.failure_threshold
             && health.state != CircuitState::Open
@@ -171,11 +388,149 @@ pub fn record_failure(&mut self, printer_uri: &str, error: &str) {
         }
     }
 
+    /// Record the result of an operation against this printer that took
+    /// `latency`: updates the circuit breaker like
+    /// [`record_success`](Self::record_success) /
+    /// [`record_failure`](Self::record_failure) and adds it to the history.
+    pub fn record_result<T>(&mut self, printer_uri: &str, result: &Result<T>, latency: Duration) {
+        let error_class = match result {
+            Ok(_) => {
+                self.record_success(printer_uri);
+                None
+            }
+            Err(e) => {
+                self.record_failure(printer_uri, &e.to_string());
+                Some(ErrorClass::classify(e))
+            }
+        };
+        if let Err(e) = self.insert_outcome(printer_uri, Utc::now(), error_class, latency) {
+            warn!(uri = printer_uri, error = %e, "failed to record printer outcome");
+        }
+    }
+
+    /// Add one outcome to the history; `error_class` is `None` for a
+    /// success.  Does nothing without a history.
+    fn insert_outcome(
+        &self,
+        printer_uri: &str,
+        at: DateTime<Utc>,
+        error_class: Option<ErrorClass>,
+        latency: Duration,
+    ) -> Result<()> {
+        let Some(conn) = &self.history else {
+            return Ok(());
+        };
+        let error_class = error_class.map(|c| serde_json::to_string(&c)).transpose()?;
+        conn.execute(
+            "INSERT INTO printer_outcomes
+                 (printer_uri, recorded_at, success, error_class, latency_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                printer_uri,
+                at.timestamp_millis(),
+                error_class.is_none(),
+                error_class,
+                latency.as_millis() as i64,
+            ],
+        )
+        .map_err(|e| PresswerkError::Database(format!("insert printer outcome: {e}")))?;
+        Ok(())
+    }
+
+    /// The printer's success rate and latency over the last `window`.
+    ///
+    /// Empty without a history or when nothing was recorded in the window.
+    pub fn recent_health(&self, printer_uri: &str, window: Duration) -> Result<HealthSummary> {
+        let window = chrono::Duration::from_std(window).unwrap_or(HISTORY_RETENTION);
+        self.health_since(printer_uri, Utc::now() - window)
+    }
+
+    fn health_since(&self, printer_uri: &str, since: DateTime<Utc>) -> Result<HealthSummary> {
+        let mut summary = HealthSummary::default();
+        let Some(conn) = &self.history else {
+            return Ok(summary);
+        };
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT success, error_class, latency_ms, recorded_at FROM printer_outcomes
+                 WHERE printer_uri = ?1 AND recorded_at >= ?2",
+            )
+            .map_err(|e| PresswerkError::Database(format!("prepare health query: {e}")))?;
+        let rows = stmt
+            .query_map(params![printer_uri, since.timestamp_millis()], |row| {
+                Ok((
+                    row.get::<_, bool>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })
+            .map_err(|e| PresswerkError::Database(format!("query health: {e}")))?;
+
+        let mut latencies = Vec::new();
+        for row in rows {
+            let (success, error_class, latency_ms, recorded_at) =
+                row.map_err(|e| PresswerkError::Database(format!("read health row: {e}")))?;
+            summary.attempts += 1;
+            if success {
+                summary.successes += 1;
+                let at = DateTime::from_timestamp_millis(recorded_at);
+                summary.last_success = summary.last_success.max(at);
+            } else if let Some(class) =
+                error_class.and_then(|json| serde_json::from_str::<ErrorClass>(&json).ok())
+            {
+                *summary.failures_by_class.entry(class).or_default() += 1;
+            }
+            latencies.push(latency_ms.max(0) as u64);
+        }
+
+        latencies.sort_unstable();
+        summary.median_latency = latencies
+            .get(latencies.len() / 2)
+            .map(|&ms| Duration::from_millis(ms));
+        Ok(summary)
+    }
+
+    /// The healthiest of `candidates` over the last [`SELECTION_WINDOW`],
+    /// for choosing between printers that could all take the job.
+    ///
+    /// Ranks by success rate, then lower median latency, then the most
+    /// recent success; a printer with no history counts as half reliable.
+    /// Equally ranked candidates keep their order, so the first wins.
+    /// `None` only when `candidates` is empty.
+    pub fn best_of(&self, candidates: &[String]) -> Option<String> {
+        let mut best: Option<(&String, HealthSummary)> = None;
+        for uri in candidates {
+            let summary = self
+                .recent_health(uri, SELECTION_WINDOW)
+                .unwrap_or_else(|e| {
+                    warn!(uri = %uri, error = %e, "failed to read printer health");
+                    HealthSummary::default()
+                });
+            let better = best
+                .as_ref()
+                .is_none_or(|(_, leader)| summary.rank(leader) == Ordering::Greater);
+            if better {
+                best = Some((uri, summary));
+            }
+        }
+        best.map(|(uri, _)| uri.clone())
+    }
+
     /// Get the health status for a printer (if tracked).
     pub fn get_health(&self, printer_uri: &str) -> Option<&PrinterHealth> {
         self.printers.get(printer_uri)
     }
 
+    /// How long to wait before polling this printer's health again.
+    pub fn poll_interval(&self, printer_uri: &str) -> Duration {
+        self.printers
+            .get(printer_uri)
+            .map(|health| health.poll_interval.current())
+            .unwrap_or(DEFAULT_POLL_INTERVAL)
+    }
+
     /// Get a human-readable status message for the printer.
     pub fn status_message(&self, printer_uri: &str) -> Option<String> {
         let health = self.printers.get(printer_uri)?;
@@ -277,4 +632,185 @@ fn no_status_message_when_healthy() {
         tracker.record_success(uri);
         assert!(tracker.status_message(uri).is_none());
     }
+
+    #[test]
+    fn stable_printer_is_polled_less_often() {
+        let mut tracker = HealthTracker::new();
+        let uri = "ipp://test:631/";
+        assert_eq!(tracker.poll_interval(uri), DEFAULT_POLL_INTERVAL);
+
+        let mut previous = DEFAULT_POLL_INTERVAL;
+        for _ in 0..4 {
+            for _ in 0..STABLE_CHECKS {
+                tracker.record_success(uri);
+            }
+            assert!(tracker.poll_interval(uri) >= previous);
+            previous = tracker.poll_interval(uri);
+        }
+        assert!(previous > DEFAULT_POLL_INTERVAL);
+
+        for _ in 0..50 {
+            tracker.record_success(uri);
+        }
+        assert_eq!(tracker.poll_interval(uri), MAX_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn flapping_printer_is_polled_more_often() {
+        let mut interval = PollInterval::default();
+        for _ in 0..10 {
+            interval.record(true);
+        }
+        assert!(interval.current() > DEFAULT_POLL_INTERVAL);
+
+        for healthy in [false, true, false, true, false, true] {
+            assert_eq!(interval.record(healthy), MIN_POLL_INTERVAL);
+        }
+    }
+
+    // -- Outcome history ------------------------------------------------------
+
+    #[test]
+    fn recent_health_summarises_outcomes_in_the_window() {
+        let tracker = HealthTracker::open_in_memory().unwrap();
+        let uri = "ipp://office:631/ipp/print";
+        let now = Utc::now();
+        let ms = Duration::from_millis;
+
+        for latency in [100, 200, 300] {
+            tracker.insert_outcome(uri, now, None, ms(latency)).unwrap();
+        }
+        tracker
+            .insert_outcome(uri, now, Some(ErrorClass::Transient), ms(5000))
+            .unwrap();
+        tracker
+            .insert_outcome(uri, now, Some(ErrorClass::UserAction), ms(400))
+            .unwrap();
+        // Outside the window, and another printer: not counted.
+        tracker
+            .insert_outcome(uri, now - chrono::Duration::days(2), None, ms(1))
+            .unwrap();
+        tracker
+            .insert_outcome("ipp://home:631/ipp/print", now, None, ms(1))
+            .unwrap();
+
+        let summary = tracker
+            .recent_health(uri, Duration::from_secs(24 * 60 * 60))
+            .unwrap();
+        assert_eq!(summary.attempts, 5);
+        assert_eq!(summary.successes, 3);
+        assert_eq!(summary.success_rate(), Some(0.6));
+        assert_eq!(summary.median_latency, Some(ms(300)));
+        assert_eq!(summary.failures_by_class[&ErrorClass::Transient], 1);
+        assert_eq!(summary.failures_by_class[&ErrorClass::UserAction], 1);
+        assert_eq!(summary.reliability(), Reliability::Fair);
+
+        let unknown = tracker
+            .recent_health("ipp://nowhere:631/", Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(unknown.success_rate(), None);
+        assert_eq!(unknown.reliability(), Reliability::Unknown);
+    }
+
+    #[test]
+    fn history_survives_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("health.db");
+        let uri = "ipp://office:631/ipp/print";
+
+        let mut tracker = HealthTracker::open(&path).unwrap();
+        tracker.record_result(uri, &Ok(()), Duration::from_millis(120));
+        tracker.record_result::<()>(
+            uri,
+            &Err(PresswerkError::IppRequest("timed out after 60s".into())),
+            Duration::from_secs(60),
+        );
+        drop(tracker);
+
+        let tracker = HealthTracker::open(&path).unwrap();
+        let summary = tracker.recent_health(uri, Duration::from_secs(60)).unwrap();
+        assert_eq!(summary.attempts, 2);
+        assert_eq!(summary.successes, 1);
+        assert_eq!(summary.failures_by_class[&ErrorClass::Transient], 1);
+    }
+
+    // -- Printer selection ----------------------------------------------------
+
+    #[test]
+    fn best_of_prefers_the_printer_without_recent_failures() {
+        let tracker = HealthTracker::open_in_memory().unwrap();
+        let flaky = "ipp://office-1:631/ipp/print".to_string();
+        let steady = "ipp://office-2:631/ipp/print".to_string();
+        let now = Utc::now();
+        let ms = Duration::from_millis;
+
+        for _ in 0..4 {
+            tracker.insert_outcome(&flaky, now, None, ms(50)).unwrap();
+            tracker.insert_outcome(&steady, now, None, ms(200)).unwrap();
+        }
+        for _ in 0..3 {
+            tracker
+                .insert_outcome(&flaky, now, Some(ErrorClass::Transient), ms(50))
+                .unwrap();
+        }
+
+        let candidates = [flaky.clone(), steady.clone()];
+        assert_eq!(tracker.best_of(&candidates), Some(steady.clone()));
+        let reversed = [steady.clone(), flaky];
+        assert_eq!(tracker.best_of(&reversed), Some(steady));
+    }
+
+    #[test]
+    fn best_of_breaks_ties_on_latency_then_recency() {
+        let tracker = HealthTracker::open_in_memory().unwrap();
+        let slow = "ipp://slow:631/ipp/print".to_string();
+        let fast = "ipp://fast:631/ipp/print".to_string();
+        let stale = "ipp://stale:631/ipp/print".to_string();
+        let now = Utc::now();
+        let ms = Duration::from_millis;
+
+        tracker.insert_outcome(&slow, now, None, ms(900)).unwrap();
+        tracker.insert_outcome(&fast, now, None, ms(100)).unwrap();
+        assert_eq!(
+            tracker.best_of(&[slow.clone(), fast.clone()]),
+            Some(fast.clone())
+        );
+
+        // Same rate and latency: the more recent success wins.
+        tracker
+            .insert_outcome(&stale, now - chrono::Duration::days(3), None, ms(100))
+            .unwrap();
+        assert_eq!(tracker.best_of(&[stale.clone(), fast.clone()]), Some(fast));
+    }
+
+    #[test]
+    fn best_of_ranks_unknown_printers_between_good_and_failing_ones() {
+        let tracker = HealthTracker::open_in_memory().unwrap();
+        let failing = "ipp://failing:631/ipp/print".to_string();
+        let unknown = "ipp://new:631/ipp/print".to_string();
+        let good = "ipp://good:631/ipp/print".to_string();
+        let now = Utc::now();
+        tracker
+            .insert_outcome(&good, now, None, Duration::from_millis(100))
+            .unwrap();
+        tracker
+            .insert_outcome(
+                &failing,
+                now,
+                Some(ErrorClass::Transient),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+
+        assert_eq!(
+            tracker.best_of(&[failing.clone(), unknown.clone()]),
+            Some(unknown.clone())
+        );
+        assert_eq!(tracker.best_of(&[unknown, good.clone()]), Some(good));
+        assert_eq!(
+            tracker.best_of(std::slice::from_ref(&failing)),
+            Some(failing)
+        );
+        assert_eq!(tracker.best_of(&[]), None);
+    }
 }
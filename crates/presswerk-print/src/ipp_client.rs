@@ -7,17 +7,25 @@
 //   - Get-Printer-Attributes  (RFC 8011 §4.2.5)
 //   - Print-Job               (RFC 8011 §4.2.1)
 //   - Get-Jobs                (RFC 8011 §4.2.6)
+//   - Get-Job-Attributes      (RFC 8011 §4.3.4)
 //   - Cancel-Job              (RFC 8011 §4.2.8)
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::io::Cursor;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use ipp::prelude::*;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
 use presswerk_core::error::{PresswerkError, Result};
-use presswerk_core::types::{DocumentType, PrintSettings};
+use presswerk_core::types::{DocumentType, JobStatus, PrintSettings};
+
+use crate::capabilities::{FormatPlan, PrinterCapabilities};
+use crate::protocol;
+use crate::throttle::{self, ThrottledBody};
 
 /// Attributes returned by a Get-Printer-Attributes response.
 ///
@@ -25,23 +33,115 @@
 /// The raw IPP attribute groups are available via [`get_printer_attributes_raw`].
 pub type PrinterAttributes = HashMap<String, String>;
 
-/// Summary of a remote print job as returned by Get-Jobs.
-#[derive(Debug, Clone)]
-pub struct RemoteJobInfo {
+/// A job in a printer's own queue, as returned by Get-Jobs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteJob {
     /// IPP job-id (integer assigned by the printer).
     pub job_id: i32,
-    /// Human-readable job name (`job-name` attribute).
+    /// Human-readable job name (`job-name` attribute); empty if not given.
     pub job_name: String,
-    /// IPP job-state keyword (e.g. "processing", "completed").
-    pub job_state: String,
+    /// The job's `job-state`, or `None` if the printer did not report one
+    /// within RFC 8011's range.
+    pub job_state: Option<JobStatus>,
+}
+
+/// A printer's verdict on a job it was asked to validate with
+/// [`IppClient::validate_job`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationOutcome {
+    /// The printer would accept the job as it is.
+    Accepted,
+    /// The printer would accept the job but ignore or substitute the named
+    /// attributes.
+    AcceptedWithChanges(Vec<String>),
+    /// The printer would reject the job's settings.  Holds the status it
+    /// answered with and the attributes it named as unsupported, if any.
+    Rejected {
+        status: String,
+        unsupported: Vec<String>,
+    },
+}
+
+impl ValidationOutcome {
+    /// Whether the printer would print the job at all.
+    pub fn is_accepted(&self) -> bool {
+        !matches!(self, Self::Rejected { .. })
+    }
+}
+
+/// Renders documents into raster formats for printers that cannot interpret
+/// them directly.
+///
+/// Rendering lives outside this crate (in `presswerk-document`); the app
+/// passes an implementation to [`IppClient::smart_print`].
+pub trait Rasterizer: Send + Sync {
+    /// Render `document` (of `document_type`) as `target`, which is
+    /// [`DocumentType::PwgRaster`] or [`DocumentType::Jpeg`].
+    fn rasterize(
+        &self,
+        document: &[u8],
+        document_type: DocumentType,
+        target: DocumentType,
+        settings: &PrintSettings,
+    ) -> Result<Vec<u8>>;
 }
 
+/// Job attributes asked for in Get-Jobs; without them printers send only
+/// `job-id` and `job-uri`.
+const GET_JOBS_ATTRIBUTES: [&str; 3] = ["job-id", "job-name", "job-state"];
+
 /// Timeout for print operations (seconds).
 const PRINT_TIMEOUT_SECS: u64 = 60;
 
 /// Timeout for query operations like Get-Printer-Attributes, Get-Jobs (seconds).
 const QUERY_TIMEOUT_SECS: u64 = 15;
 
+/// How many times Print-Job is retried while the printer answers
+/// `server-error-busy`, unless set with [`IppClient::with_busy_retries`].
+pub const DEFAULT_BUSY_RETRIES: u32 = 3;
+
+/// Wait before retrying a busy printer that gave no hint of its own.
+const BUSY_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Longest wait honoured from a printer's retry hint.
+const MAX_BUSY_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Response attributes (integer seconds) that some printers send with
+/// `server-error-busy` to say when to try again.
+const RETRY_HINT_ATTRIBUTES: [&str; 3] = ["retry-after", "retry-interval", "notify-get-interval"];
+
+/// How often [`IppClient::wait_for_completion`] asks for the job's state.
+const COMPLETION_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How long to wait for a printer to finish a job before giving up on
+/// confirmation.
+pub const DEFAULT_COMPLETION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// `client-error-attributes-not-settable` (RFC 3380), which the `ipp` crate
+/// has no status code for.
+const STATUS_ATTRIBUTES_NOT_SETTABLE: u16 = 0x0413;
+
+/// Print-Job statuses with which a printer refuses the request before it
+/// takes the job (see [`not_delivered`]).
+const REFUSED_STATUSES: [StatusCode; 3] = [
+    StatusCode::ClientErrorDocumentFormatNotSupported,
+    StatusCode::ServerErrorOperationNotSupported,
+    StatusCode::ServerErrorVersionNotSupported,
+];
+
+/// How often a Print-Job in progress checks its cancel flag.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Outcome of one Print-Job attempt that did not fail outright.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrintOutcome {
+    /// The printer accepted the job and assigned this job-id.
+    Accepted(i32),
+    /// The printer answered `server-error-busy`, optionally saying how long
+    /// to wait.
+    Busy(Option<Duration>),
+}
+
 /// Async IPP client wrapping the `ipp` crate.
 ///
 /// Each instance is bound to a single printer URI.  All methods are async and
@@ -49,6 +149,12 @@ pub struct RemoteJobInfo {
 pub struct IppClient {
     /// The target printer URI (ipp:// or ipps://).
     uri: Uri,
+    /// Upload speed cap for document bodies, if any.
+    rate_limit_bytes_per_sec: Option<u64>,
+    /// Print-Job retries while the printer reports itself busy.
+    busy_retries: u32,
+    /// Set from elsewhere to abort a Print-Job in progress.
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 impl IppClient {
@@ -60,7 +166,36 @@ pub fn new(uri: &str) -> Result<Self> {
         let parsed: Uri = uri
             .parse()
             .map_err(|e| PresswerkError::IppRequest(format!("invalid URI '{uri}': {e}")))?;
-        Ok(Self { uri: parsed })
+        Ok(Self {
+            uri: parsed,
+            rate_limit_bytes_per_sec: None,
+            busy_retries: DEFAULT_BUSY_RETRIES,
+            cancel: None,
+        })
+    }
+
+    /// Cap the speed at which document bodies are uploaded, e.g. on metered
+    /// mobile data.  `None` (the default) sends at full speed.
+    pub fn with_rate_limit(mut self, bytes_per_sec: Option<u64>) -> Self {
+        self.rate_limit_bytes_per_sec = bytes_per_sec;
+        self
+    }
+
+    /// Retry Print-Job up to `retries` times while the printer answers
+    /// `server-error-busy` (default [`DEFAULT_BUSY_RETRIES`]).  Zero reports
+    /// busy as a failure straight away.
+    pub fn with_busy_retries(mut self, retries: u32) -> Self {
+        self.busy_retries = retries;
+        self
+    }
+
+    /// Abort [`print_job`](Self::print_job) as soon as `flag` is set: the
+    /// upload stops, the connection to the printer is closed, and the call
+    /// fails.  Covers busy-retry waits as well as the send itself, and
+    /// also stops [`wait_for_completion`](Self::wait_for_completion).
+    pub fn with_cancel(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
     }
 
     /// Return the printer URI this client is targeting.
@@ -72,8 +207,18 @@ pub fn uri(&self) -> &Uri {
     ///
     /// Sends a Get-Printer-Attributes operation and returns the response as a
     /// flat map of attribute names to their string representations.
-    #[instrument(skip(self), fields(uri = %self.uri))]
     pub async fn get_printer_attributes(&self) -> Result<PrinterAttributes> {
+        let response = self.get_printer_attributes_response().await?;
+        let attrs = flatten_attributes(response.attributes());
+        debug!(count = attrs.len(), "received printer attributes");
+        Ok(attrs)
+    }
+
+    /// Query the printer's attributes and return the response as received,
+    /// with typed and multi-valued attributes intact (see
+    /// [`PrinterCapabilities::from_ipp_response`]).
+    #[instrument(skip(self), fields(uri = %self.uri))]
+    pub async fn get_printer_attributes_response(&self) -> Result<IppRequestResponse> {
         let operation = IppOperationBuilder::get_printer_attributes(self.uri.clone()).build();
         let client = AsyncIppClient::new(self.uri.clone());
 
@@ -94,20 +239,26 @@ pub async fn get_printer_attributes(&self) -> Result<PrinterAttributes> {
         if !response.header().status_code().is_success() {
             let code = response.header().status_code();
             error!(status = ?code, "Get-Printer-Attributes failed");
-            return Err(PresswerkError::IppRequest(format!(
-                "Get-Printer-Attributes returned status {code:?}"
-            )));
+            return Err(status_error(
+                &response,
+                format!("Get-Printer-Attributes returned status {code:?}"),
+            ));
         }
 
-        let attrs = flatten_attributes(response.attributes());
-        debug!(count = attrs.len(), "received printer attributes");
-        Ok(attrs)
+        Ok(response)
     }
 
     /// Submit a document to the printer as a Print-Job.
     ///
     /// Returns the job-id assigned by the printer on success.
     ///
+    /// A printer that answers `server-error-busy` is asked again after the
+    /// delay it hints at (or [`BUSY_RETRY_DELAY`]), up to the configured
+    /// number of busy retries.  Any other error status fails immediately.
+    ///
+    /// If a cancel flag was given with [`with_cancel`](Self::with_cancel)
+    /// and gets set, the request is dropped mid-upload and an error returned.
+    ///
     /// # Arguments
     ///
     /// * `document_bytes` — raw bytes of the document to print.
@@ -122,45 +273,71 @@ pub async fn print_job(
         job_name: &str,
         settings: &PrintSettings,
     ) -> Result<i32> {
-        let payload = IppPayload::new(Cursor::new(document_bytes));
+        self.print_job_for(document_bytes, document_type, job_name, settings, None)
+            .await
+    }
+
+    /// [`print_job`](Self::print_job), encoding `settings` for the printer
+    /// described by `caps` when known.
+    async fn print_job_for(
+        &self,
+        document_bytes: Vec<u8>,
+        document_type: DocumentType,
+        job_name: &str,
+        settings: &PrintSettings,
+        caps: Option<&PrinterCapabilities>,
+    ) -> Result<i32> {
+        let send = retry_while_busy(self.busy_retries, BUSY_RETRY_DELAY, || {
+            self.send_print_job(
+                document_bytes.clone(),
+                document_type,
+                job_name,
+                settings,
+                caps,
+            )
+        });
+        let job_id = match &self.cancel {
+            // Dropping the losing send future closes its connection.
+            Some(flag) => tokio::select! {
+                result = send => result?,
+                () = cancelled(flag) => {
+                    warn!("Print-Job cancelled, connection closed");
+                    return Err(PresswerkError::IppRequest("Print-Job cancelled".into()));
+                }
+            },
+            None => send.await?,
+        };
+
+        info!(job_id, "print job accepted by printer");
+        Ok(job_id)
+    }
+
+    /// Send one Print-Job request.
+    async fn send_print_job(
+        &self,
+        document_bytes: Vec<u8>,
+        document_type: DocumentType,
+        job_name: &str,
+        settings: &PrintSettings,
+        caps: Option<&PrinterCapabilities>,
+    ) -> Result<PrintOutcome> {
+        // A throttled upload takes longer than the usual timeout allows.
+        let mut timeout = Duration::from_secs(PRINT_TIMEOUT_SECS);
+        let payload = match self.rate_limit_bytes_per_sec {
+            Some(rate) => {
+                timeout += throttle::min_transfer_time(document_bytes.len(), rate);
+                debug!(rate, "throttling document upload");
+                IppPayload::new_async(ThrottledBody::new(document_bytes, rate))
+            }
+            None => IppPayload::new(Cursor::new(document_bytes)),
+        };
 
         let mut builder = IppOperationBuilder::print_job(self.uri.clone(), payload)
             .job_title(job_name)
             .document_format(document_type.mime_type());
 
-        // Inject print settings as IPP job-template attributes.
-        builder = builder.attribute(IppAttribute::new(
-            "copies",
-            IppValue::Integer(settings.copies as i32),
-        ));
-        builder = builder.attribute(IppAttribute::new(
-            "media",
-            IppValue::Keyword(settings.paper_size.ipp_media_keyword().into()),
-        ));
-        builder = builder.attribute(IppAttribute::new(
-            "sides",
-            IppValue::Keyword(settings.duplex.ipp_sides_keyword().into()),
-        ));
-        builder = builder.attribute(IppAttribute::new(
-            "orientation-requested",
-            IppValue::Enum(settings.orientation.ipp_enum_value()),
-        ));
-        builder = builder.attribute(IppAttribute::new(
-            "print-color-mode",
-            IppValue::Keyword(
-                if settings.color { "color" } else { "monochrome" }.into(),
-            ),
-        ));
-
-        // Page ranges (1-indexed, inclusive)
-        if let Some(ref range) = settings.page_range {
-            builder = builder.attribute(IppAttribute::new(
-                "page-ranges",
-                IppValue::RangeOfInteger {
-                    min: range.start as i32,
-                    max: range.end as i32,
-                },
-            ));
+        for attribute in protocol::encode_job_attributes(settings, caps) {
+            builder = builder.attribute(attribute);
         }
 
         let operation = builder.build();
@@ -174,40 +351,162 @@ pub async fn print_job(
             "sending Print-Job with settings"
         );
 
+        let response = tokio::time::timeout(timeout, client.send(operation))
+            .await
+            .map_err(|_| {
+                PresswerkError::IppRequest(format!(
+                    "Print-Job timed out after {}s — printer may be busy or offline",
+                    timeout.as_secs()
+                ))
+            })?
+            .map_err(print_job_transport_error)?;
+
+        let code = response.header().status_code();
+        if matches!(code, StatusCode::ServerErrorBusy) {
+            let hint = retry_hint(response.attributes());
+            warn!(retry_after = ?hint, "printer busy");
+            return Ok(PrintOutcome::Busy(hint));
+        }
+        if !code.is_success() {
+            error!(status = ?code, "Print-Job failed");
+            return Err(status_error(
+                &response,
+                format!("Print-Job returned status {code:?}"),
+            ));
+        }
+
+        // The job-id is in the Job Attributes group.
+        let job_id = extract_job_id(response.attributes()).ok_or_else(|| {
+            PresswerkError::IppRequest("Print-Job response missing job-id attribute".into())
+        })?;
+
+        Ok(PrintOutcome::Accepted(job_id))
+    }
+
+    /// Ask the printer whether it would accept a job of `document_type` with
+    /// `settings`, without sending a document (Validate-Job, operation
+    /// 0x0004).
+    ///
+    /// Settings the printer cannot honour give
+    /// [`ValidationOutcome::Rejected`]; any other error status, or no
+    /// answer, is an error.
+    #[instrument(skip(self, settings), fields(uri = %self.uri))]
+    pub async fn validate_job(
+        &self,
+        document_type: DocumentType,
+        settings: &PrintSettings,
+        caps: Option<&PrinterCapabilities>,
+    ) -> Result<ValidationOutcome> {
+        let request = validate_job_request(self.uri.clone(), document_type, settings, caps);
+        let client = AsyncIppClient::new(self.uri.clone());
+
+        debug!(mime = document_type.mime_type(), "sending Validate-Job");
         let response = tokio::time::timeout(
-            Duration::from_secs(PRINT_TIMEOUT_SECS),
-            client.send(operation),
+            Duration::from_secs(QUERY_TIMEOUT_SECS),
+            client.send(request),
         )
         .await
         .map_err(|_| {
             PresswerkError::IppRequest(format!(
-                "Print-Job timed out after {}s — printer may be busy or offline",
-                PRINT_TIMEOUT_SECS
+                "Validate-Job timed out after {QUERY_TIMEOUT_SECS}s"
             ))
         })?
-        .map_err(|e| PresswerkError::IppRequest(format!("Print-Job: {e}")))?;
+        .map_err(|e| PresswerkError::IppRequest(format!("Validate-Job: {e}")))?;
 
-        if !response.header().status_code().is_success() {
-            let code = response.header().status_code();
-            error!(status = ?code, "Print-Job failed");
-            return Err(PresswerkError::IppRequest(format!(
-                "Print-Job returned status {code:?}"
-            )));
-        }
+        let outcome = validation_outcome(&response)?;
+        info!(?outcome, "Validate-Job answered");
+        Ok(outcome)
+    }
 
-        // The job-id is in the Job Attributes group.
-        let job_id = extract_job_id(response.attributes()).ok_or_else(|| {
-            PresswerkError::IppRequest("Print-Job response missing job-id attribute".into())
-        })?;
+    /// Print a document in a format the printer can actually handle.
+    ///
+    /// Queries the printer's capabilities first and, if it cannot interpret
+    /// the document (see [`negotiate`](Self::negotiate)), rasterizes it
+    /// client-side before sending. If the capability query fails the
+    /// document is sent unchanged, as [`print_job`](Self::print_job) would.
+    #[instrument(skip_all, fields(uri = %self.uri, job_name = %job_name))]
+    pub async fn smart_print(
+        &self,
+        document_bytes: Vec<u8>,
+        document_type: DocumentType,
+        job_name: &str,
+        settings: &PrintSettings,
+        rasterizer: &dyn Rasterizer,
+    ) -> Result<i32> {
+        let (document_bytes, document_type, caps) = match self.get_printer_attributes().await {
+            Ok(attrs) => {
+                let caps = PrinterCapabilities::from_attributes(&attrs);
+                let (document_bytes, document_type) =
+                    Self::negotiate(&caps, document_bytes, document_type, settings, rasterizer)?;
+                (document_bytes, document_type, Some(caps))
+            }
+            Err(e) => {
+                warn!(error = %e, "capability query failed, sending document unchanged");
+                (document_bytes, document_type, None)
+            }
+        };
 
-        info!(job_id, "print job accepted by printer");
-        Ok(job_id)
+        self.print_job_for(
+            document_bytes,
+            document_type,
+            job_name,
+            settings,
+            caps.as_ref(),
+        )
+        .await
+    }
+
+    /// Convert a document into a format the printer described by `caps`
+    /// accepts.
+    ///
+    /// Documents in a supported format, or for printers that attempt any
+    /// format (`pdl-override-supported: attempted`), are returned unchanged.
+    /// Otherwise the document is rendered to PWG Raster or JPEG, whichever
+    /// the printer lists. Returns the bytes to send and their type.
+    pub fn negotiate(
+        caps: &PrinterCapabilities,
+        document_bytes: Vec<u8>,
+        document_type: DocumentType,
+        settings: &PrintSettings,
+        rasterizer: &dyn Rasterizer,
+    ) -> Result<(Vec<u8>, DocumentType)> {
+        match caps.format_plan(document_type)? {
+            FormatPlan::SendAsIs => Ok((document_bytes, document_type)),
+            FormatPlan::Rasterize(target) => {
+                info!(
+                    from = document_type.mime_type(),
+                    to = target.mime_type(),
+                    "printer cannot interpret document, rasterizing client-side"
+                );
+                let raster =
+                    rasterizer.rasterize(&document_bytes, document_type, target, settings)?;
+                debug!(
+                    original_bytes = document_bytes.len(),
+                    raster_bytes = raster.len(),
+                    "rasterization complete"
+                );
+                Ok((raster, target))
+            }
+        }
     }
 
-    /// Retrieve the list of jobs currently known to the printer.
+    /// List the jobs the printer has not finished yet, via Get-Jobs
+    /// (operation 0x000A).
+    ///
+    /// Jobs the printer reports without a `job-id` are left out.
     #[instrument(skip(self), fields(uri = %self.uri))]
-    pub async fn get_jobs(&self) -> Result<Vec<RemoteJobInfo>> {
-        let operation = IppOperationBuilder::get_jobs(self.uri.clone()).build();
+    pub async fn get_jobs(&self) -> Result<Vec<RemoteJob>> {
+        let mut operation: IppRequestResponse = IppOperationBuilder::get_jobs(self.uri.clone())
+            .build()
+            .into();
+        let requested = GET_JOBS_ATTRIBUTES
+            .iter()
+            .map(|name| IppValue::Keyword((*name).into()))
+            .collect();
+        operation.attributes_mut().add(
+            DelimiterTag::OperationAttributes,
+            IppAttribute::new("requested-attributes", IppValue::Array(requested)),
+        );
         let client = AsyncIppClient::new(self.uri.clone());
 
         debug!("sending Get-Jobs");
@@ -227,9 +526,10 @@ pub async fn get_jobs(&self) -> Result<Vec<RemoteJobInfo>> {
         if !response.header().status_code().is_success() {
             let code = response.header().status_code();
             error!(status = ?code, "Get-Jobs failed");
-            return Err(PresswerkError::IppRequest(format!(
-                "Get-Jobs returned status {code:?}"
-            )));
+            return Err(status_error(
+                &response,
+                format!("Get-Jobs returned status {code:?}"),
+            ));
         }
 
         let jobs = parse_jobs(response.attributes());
@@ -237,7 +537,92 @@ pub async fn get_jobs(&self) -> Result<Vec<RemoteJobInfo>> {
         Ok(jobs)
     }
 
-    /// Cancel a specific job on the printer.
+    /// Ask the printer for the state of its job `printer_job_id` (the id
+    /// returned by [`print_job`](Self::print_job)), via Get-Job-Attributes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, or the response has no
+    /// `job-state` or one outside RFC 8011's range.
+    #[instrument(skip(self), fields(uri = %self.uri, printer_job_id))]
+    pub async fn job_state(&self, printer_job_id: i32) -> Result<JobStatus> {
+        let operation =
+            IppOperationBuilder::get_job_attributes(self.uri.clone(), printer_job_id).build();
+        let client = AsyncIppClient::new(self.uri.clone());
+
+        debug!("sending Get-Job-Attributes");
+        let response = tokio::time::timeout(
+            Duration::from_secs(QUERY_TIMEOUT_SECS),
+            client.send(operation),
+        )
+        .await
+        .map_err(|_| {
+            PresswerkError::IppRequest(format!(
+                "Get-Job-Attributes({printer_job_id}) timed out after {QUERY_TIMEOUT_SECS}s"
+            ))
+        })?
+        .map_err(|e| {
+            PresswerkError::IppRequest(format!("Get-Job-Attributes({printer_job_id}): {e}"))
+        })?;
+
+        if !response.header().status_code().is_success() {
+            let code = response.header().status_code();
+            error!(status = ?code, printer_job_id, "Get-Job-Attributes failed");
+            return Err(status_error(
+                &response,
+                format!("Get-Job-Attributes({printer_job_id}) returned status {code:?}"),
+            ));
+        }
+
+        let state = extract_job_state(response.attributes()).ok_or_else(|| {
+            PresswerkError::IppRequest(format!(
+                "Get-Job-Attributes({printer_job_id}) response missing job-state"
+            ))
+        })?;
+        let status = job_status_from_ipp_state(state).ok_or_else(|| {
+            PresswerkError::IppRequest(format!("printer reported unknown job-state {state}"))
+        })?;
+        debug!(state, ?status, "received job state");
+        Ok(status)
+    }
+
+    /// Poll the state of the printer's job `printer_job_id` until it is
+    /// completed, aborted or cancelled, or `timeout` passes.
+    ///
+    /// Returns the final state, which after a timeout is the last one the
+    /// printer reported (pending, held or still processing).
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from [`job_state`](Self::job_state), or an
+    /// error once the cancel flag given with [`with_cancel`](Self::with_cancel)
+    /// is set.
+    #[instrument(skip(self), fields(uri = %self.uri, printer_job_id))]
+    pub async fn wait_for_completion(
+        &self,
+        printer_job_id: i32,
+        timeout: Duration,
+    ) -> Result<JobStatus> {
+        let poll = poll_until_finished(COMPLETION_POLL_INTERVAL, timeout, || {
+            self.job_state(printer_job_id)
+        });
+        match &self.cancel {
+            Some(flag) => tokio::select! {
+                result = poll => result,
+                () = cancelled(flag) => {
+                    debug!("stopped polling job state, cancelled");
+                    Err(PresswerkError::IppRequest(format!(
+                        "waiting for job {printer_job_id} cancelled"
+                    )))
+                }
+            },
+            None => poll.await,
+        }
+    }
+
+    /// Cancel a specific job on the printer via Cancel-Job (operation
+    /// 0x0008).  `job_id` is the printer's id, as listed by
+    /// [`get_jobs`](Self::get_jobs).
     ///
     /// Returns `Ok(())` if the printer accepted the cancellation.
     #[instrument(skip(self), fields(uri = %self.uri, job_id))]
@@ -262,9 +647,10 @@ pub async fn cancel_job(&self, job_id: i32) -> Result<()> {
         if !response.header().status_code().is_success() {
             let code = response.header().status_code();
             error!(status = ?code, job_id, "Cancel-Job failed");
-            return Err(PresswerkError::IppRequest(format!(
-                "Cancel-Job({job_id}) returned status {code:?}"
-            )));
+            return Err(status_error(
+                &response,
+                format!("Cancel-Job({job_id}) returned status {code:?}"),
+            ));
         }
 
         info!(job_id, "job cancelled");
@@ -272,6 +658,137 @@ pub async fn cancel_job(&self, job_id: i32) -> Result<()> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Print-Job delivery
+// ---------------------------------------------------------------------------
+
+/// Whether `err`, from [`IppClient::print_job`], was raised before the
+/// printer took the job: it could not be reached, answered HTTP but not IPP,
+/// or refused the IPP version, the operation or the document format.
+///
+/// Only then can the document be sent another way without risking a second
+/// copy; after a timeout or a lost answer the job may already be printing.
+pub fn not_delivered(err: &PresswerkError) -> bool {
+    match err {
+        PresswerkError::IppUnreachable(_) => true,
+        PresswerkError::IppStatus { code, .. } => REFUSED_STATUSES
+            .iter()
+            .any(|status| *status as u16 == *code),
+        _ => false,
+    }
+}
+
+/// The error for a Print-Job request that failed in transport:
+/// [`PresswerkError::IppUnreachable`] when no IPP server received it.
+fn print_job_transport_error(e: IppError) -> PresswerkError {
+    let unreached = match &e {
+        IppError::AsyncClientError(e) => e.is_connect(),
+        IppError::RequestError(_) => true,
+        _ => false,
+    };
+    if unreached {
+        PresswerkError::IppUnreachable(format!("Print-Job not delivered: {e}"))
+    } else {
+        PresswerkError::IppRequest(format!("Print-Job: {e}"))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Busy printers
+// ---------------------------------------------------------------------------
+
+/// Run `attempt` until it is accepted, waiting and retrying up to `retries`
+/// times while it reports the printer busy.
+///
+/// Each wait is the printer's hint (capped at [`MAX_BUSY_RETRY_DELAY`]) or
+/// `default_delay`.  Errors from `attempt` are returned at once.
+async fn retry_while_busy<F, Fut>(
+    retries: u32,
+    default_delay: Duration,
+    mut attempt: F,
+) -> Result<i32>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<PrintOutcome>>,
+{
+    let mut busy_count = 0;
+    loop {
+        match attempt().await? {
+            PrintOutcome::Accepted(job_id) => return Ok(job_id),
+            PrintOutcome::Busy(_) if busy_count >= retries => {
+                return Err(PresswerkError::IppRequest(format!(
+                    "printer still busy (server-error-busy) after {retries} retries"
+                )));
+            }
+            PrintOutcome::Busy(hint) => {
+                busy_count += 1;
+                let delay = hint.map_or(default_delay, |hint| hint.min(MAX_BUSY_RETRY_DELAY));
+                info!(
+                    retry = busy_count,
+                    of = retries,
+                    delay_ms = delay.as_millis() as u64,
+                    "printer busy, retrying Print-Job"
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Cancellation
+// ---------------------------------------------------------------------------
+
+/// Resolve once `flag` is set, checking every [`CANCEL_POLL_INTERVAL`].
+async fn cancelled(flag: &AtomicBool) {
+    while !flag.load(Ordering::Relaxed) {
+        tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Completion
+// ---------------------------------------------------------------------------
+
+/// Run `poll` every `interval` until it reports a finished job (completed,
+/// failed or cancelled) or `timeout` passes, returning the last state seen.
+async fn poll_until_finished<F, Fut>(
+    interval: Duration,
+    timeout: Duration,
+    mut poll: F,
+) -> Result<JobStatus>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<JobStatus>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let status = poll().await?;
+        let finished = matches!(
+            status,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        );
+        if finished || tokio::time::Instant::now() + interval > deadline {
+            debug!(?status, finished, "stopped polling job state");
+            return Ok(status);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// The retry delay a busy printer suggested, if any (see
+/// [`RETRY_HINT_ATTRIBUTES`]).
+fn retry_hint(attrs: &IppAttributes) -> Option<Duration> {
+    attrs.groups().iter().find_map(|group| {
+        RETRY_HINT_ATTRIBUTES
+            .iter()
+            .find_map(|name| match group.attributes().get(*name)?.value() {
+                IppValue::Integer(secs) if *secs >= 0 => Some(Duration::from_secs(*secs as u64)),
+                _ => None,
+            })
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Helper functions for parsing IPP responses
 // ---------------------------------------------------------------------------
@@ -284,12 +801,103 @@ fn flatten_attributes(attrs: &IppAttributes) -> PrinterAttributes {
     let mut map = HashMap::new();
     for group in attrs.groups() {
         for (name, attr) in group.attributes() {
-            map.insert(name.clone(), format!("{}", attr.value()));
+            map.insert(name.clone(), flatten_value(attr.value()));
         }
     }
     map
 }
 
+/// Render one attribute value; an array's members are joined with `", "`
+/// rather than shown in the bracketed form of its `Display` impl.
+fn flatten_value(value: &IppValue) -> String {
+    match value {
+        IppValue::Array(values) => values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Validate-Job
+// ---------------------------------------------------------------------------
+
+/// A Validate-Job request carrying the same attributes a Print-Job for
+/// `document_type` with `settings` would.
+fn validate_job_request(
+    uri: Uri,
+    document_type: DocumentType,
+    settings: &PrintSettings,
+    caps: Option<&PrinterCapabilities>,
+) -> IppRequestResponse {
+    let mut request =
+        IppRequestResponse::new(IppVersion::v1_1(), Operation::ValidateJob, Some(uri));
+    let attrs = request.attributes_mut();
+    attrs.add(
+        DelimiterTag::OperationAttributes,
+        IppAttribute::new(
+            IppAttribute::DOCUMENT_FORMAT,
+            IppValue::MimeMediaType(document_type.mime_type().into()),
+        ),
+    );
+    for attribute in protocol::encode_job_attributes(settings, caps) {
+        attrs.add(DelimiterTag::JobAttributes, attribute);
+    }
+    request
+}
+
+/// The error for a request the printer answered with a failing status,
+/// carrying the status code from `response`; `detail` is the message.
+fn status_error(response: &IppRequestResponse, detail: String) -> PresswerkError {
+    PresswerkError::IppStatus {
+        code: response.header().operation_or_status,
+        status: format!("{:?}", response.header().status_code()),
+        detail,
+    }
+}
+
+/// Interpret a Validate-Job response.
+fn validation_outcome(response: &IppRequestResponse) -> Result<ValidationOutcome> {
+    let raw = response.header().operation_or_status;
+    let code = response.header().status_code();
+    let unsupported = || {
+        let mut names: Vec<String> = response
+            .attributes()
+            .groups_of(DelimiterTag::UnsupportedAttributes)
+            .flat_map(|group| group.attributes().keys().cloned())
+            .collect();
+        names.sort();
+        names
+    };
+
+    match code {
+        StatusCode::SuccessfulOk => Ok(ValidationOutcome::Accepted),
+        StatusCode::SuccessfulOkIgnoredOrSubstitutedAttributes
+        | StatusCode::SuccessfulOkConflictingAttributes => {
+            Ok(ValidationOutcome::AcceptedWithChanges(unsupported()))
+        }
+        StatusCode::ClientErrorAttributesOrValuesNotSupported
+        | StatusCode::ClientErrorConflictingAttributes
+        | StatusCode::ClientErrorDocumentFormatNotSupported => Ok(ValidationOutcome::Rejected {
+            status: code.to_string(),
+            unsupported: unsupported(),
+        }),
+        _ if raw == STATUS_ATTRIBUTES_NOT_SETTABLE => Ok(ValidationOutcome::Rejected {
+            status: "Attributes not settable".into(),
+            unsupported: unsupported(),
+        }),
+        _ => {
+            error!(status = format!("0x{raw:04X}"), "Validate-Job failed");
+            Err(status_error(
+                response,
+                format!("Validate-Job returned status 0x{raw:04X} ({code})"),
+            ))
+        }
+    }
+}
+
 /// Extract the `job-id` integer from a response's Job Attributes group.
 fn extract_job_id(attrs: &IppAttributes) -> Option<i32> {
     for group in attrs.groups_of(DelimiterTag::JobAttributes) {
@@ -302,44 +910,60 @@ fn extract_job_id(attrs: &IppAttributes) -> Option<i32> {
     None
 }
 
-/// Parse the Get-Jobs response into a vec of `RemoteJobInfo`.
-///
-/// Each job is represented as a separate Job Attributes group in the IPP
-/// response.
-fn parse_jobs(attrs: &IppAttributes) -> Vec<RemoteJobInfo> {
-    let mut jobs = Vec::new();
-
-    for group in attrs.groups_of(DelimiterTag::JobAttributes) {
-        let attributes = group.attributes();
-
-        let job_id = attributes.get("job-id").and_then(|a| {
-            if let IppValue::Integer(id) = a.value() {
-                Some(*id)
-            } else {
-                None
-            }
-        });
-
-        let job_name = attributes
-            .get("job-name")
-            .map(|a| format!("{}", a.value()))
-            .unwrap_or_default();
+/// Extract the `job-state` enum from a response's Job Attributes group.
+fn extract_job_state(attrs: &IppAttributes) -> Option<i32> {
+    attrs
+        .groups_of(DelimiterTag::JobAttributes)
+        .find_map(group_job_state)
+}
 
-        let job_state = attributes
-            .get("job-state")
-            .map(|a| format!("{}", a.value()))
-            .unwrap_or_else(|| "unknown".into());
+/// The `job-state` enum of one Job Attributes group.
+fn group_job_state(group: &IppAttributeGroup) -> Option<i32> {
+    match group.attributes().get("job-state")?.value() {
+        IppValue::Enum(state) | IppValue::Integer(state) => Some(*state),
+        _ => None,
+    }
+}
 
-        if let Some(id) = job_id {
-            jobs.push(RemoteJobInfo {
-                job_id: id,
-                job_name,
-                job_state,
-            });
-        }
+/// Map an IPP `job-state` enum (RFC 8011 §5.3.7) to the internal
+/// `JobStatus`.  `None` for values outside the RFC's range.
+///
+/// A job the printer has stopped (`processing-stopped`, e.g. out of paper)
+/// is still in progress, so it maps to `Processing`.
+pub fn job_status_from_ipp_state(state: i32) -> Option<JobStatus> {
+    match state {
+        3 => Some(JobStatus::Pending),
+        4 => Some(JobStatus::Held),
+        5 | 6 => Some(JobStatus::Processing),
+        7 => Some(JobStatus::Cancelled),
+        8 => Some(JobStatus::Failed),
+        9 => Some(JobStatus::Completed),
+        _ => None,
     }
+}
 
-    jobs
+/// Parse a Get-Jobs response into the jobs it lists.
+///
+/// Each job is represented as a separate Job Attributes group in the IPP
+/// response.
+fn parse_jobs(attrs: &IppAttributes) -> Vec<RemoteJob> {
+    attrs
+        .groups_of(DelimiterTag::JobAttributes)
+        .filter_map(|group| {
+            let attributes = group.attributes();
+            let IppValue::Integer(job_id) = attributes.get("job-id")?.value() else {
+                return None;
+            };
+            Some(RemoteJob {
+                job_id: *job_id,
+                job_name: attributes
+                    .get("job-name")
+                    .map(|a| a.value().to_string())
+                    .unwrap_or_default(),
+                job_state: group_job_state(group).and_then(job_status_from_ipp_state),
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -357,4 +981,502 @@ fn new_accepts_valid_ipp_uri() {
         let client = IppClient::new("ipp://192.168.1.100:631/ipp/print");
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn multi_valued_attributes_are_joined_without_brackets() {
+        let value = IppValue::Array(vec![
+            IppValue::Keyword("iso_a4_210x297mm".into()),
+            IppValue::Keyword("na_letter_8.5x11in".into()),
+        ]);
+        assert_eq!(
+            flatten_value(&value),
+            "iso_a4_210x297mm, na_letter_8.5x11in"
+        );
+        assert_eq!(flatten_value(&IppValue::Integer(3)), "3");
+    }
+
+    /// Records what it was asked to render and returns a marker payload.
+    #[derive(Default)]
+    struct RecordingRasterizer {
+        calls: std::sync::Mutex<Vec<(DocumentType, DocumentType)>>,
+    }
+
+    impl Rasterizer for RecordingRasterizer {
+        fn rasterize(
+            &self,
+            _document: &[u8],
+            document_type: DocumentType,
+            target: DocumentType,
+            _settings: &PrintSettings,
+        ) -> Result<Vec<u8>> {
+            self.calls.lock().unwrap().push((document_type, target));
+            Ok(b"RaS2".to_vec())
+        }
+    }
+
+    fn caps_with_formats(formats: &str) -> PrinterCapabilities {
+        let mut attrs = PrinterAttributes::new();
+        attrs.insert("document-format-supported".into(), formats.into());
+        attrs.insert("pdl-override-supported".into(), "not-attempted".into());
+        PrinterCapabilities::from_attributes(&attrs)
+    }
+
+    #[test]
+    fn negotiate_rasterizes_pdf_for_pwg_only_printer() {
+        let caps = caps_with_formats("image/pwg-raster");
+        let rasterizer = RecordingRasterizer::default();
+
+        let (bytes, doc_type) = IppClient::negotiate(
+            &caps,
+            b"%PDF-1.7".to_vec(),
+            DocumentType::Pdf,
+            &PrintSettings::default(),
+            &rasterizer,
+        )
+        .unwrap();
+
+        assert_eq!(doc_type, DocumentType::PwgRaster);
+        assert_eq!(bytes, b"RaS2");
+        assert_eq!(
+            *rasterizer.calls.lock().unwrap(),
+            vec![(DocumentType::Pdf, DocumentType::PwgRaster)]
+        );
+    }
+
+    #[test]
+    fn negotiate_passes_supported_format_through() {
+        let caps = caps_with_formats("application/pdf, image/pwg-raster");
+        let rasterizer = RecordingRasterizer::default();
+
+        let (bytes, doc_type) = IppClient::negotiate(
+            &caps,
+            b"%PDF-1.7".to_vec(),
+            DocumentType::Pdf,
+            &PrintSettings::default(),
+            &rasterizer,
+        )
+        .unwrap();
+
+        assert_eq!(doc_type, DocumentType::Pdf);
+        assert_eq!(bytes, b"%PDF-1.7");
+        assert!(rasterizer.calls.lock().unwrap().is_empty());
+    }
+
+    // -- Busy printers ----------------------------------------------------------
+
+    /// A stub printer answering with `outcomes` in turn, counting calls.
+    fn stub_printer<T>(
+        outcomes: Vec<Result<T>>,
+    ) -> (
+        std::sync::Arc<std::sync::atomic::AtomicU32>,
+        impl FnMut() -> std::future::Ready<Result<T>>,
+    ) {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let counter = std::sync::Arc::clone(&calls);
+        let mut outcomes = outcomes.into_iter();
+        let attempt = move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            std::future::ready(outcomes.next().expect("unexpected extra attempt"))
+        };
+        (calls, attempt)
+    }
+
+    #[tokio::test]
+    async fn busy_printer_is_retried_until_it_accepts() {
+        let (calls, attempt) = stub_printer(vec![
+            Ok(PrintOutcome::Busy(None)),
+            Ok(PrintOutcome::Busy(Some(Duration::from_millis(1)))),
+            Ok(PrintOutcome::Accepted(42)),
+        ]);
+
+        let job_id = retry_while_busy(3, Duration::from_millis(1), attempt)
+            .await
+            .unwrap();
+        assert_eq!(job_id, 42);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn busy_past_the_retry_limit_fails_as_transient() {
+        let (calls, attempt) = stub_printer(vec![
+            Ok(PrintOutcome::Busy(None)),
+            Ok(PrintOutcome::Busy(None)),
+        ]);
+
+        let err = retry_while_busy(1, Duration::from_millis(1), attempt)
+            .await
+            .unwrap_err();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 2);
+        assert_eq!(
+            presswerk_core::types::ErrorClass::classify(&err),
+            presswerk_core::types::ErrorClass::Transient
+        );
+    }
+
+    #[tokio::test]
+    async fn permanent_error_is_not_retried() {
+        let (calls, attempt) = stub_printer(vec![Err(PresswerkError::IppStatus {
+            code: 0x040A,
+            status: "ClientErrorDocumentFormatNotSupported".into(),
+            detail: "Print-Job returned status ClientErrorDocumentFormatNotSupported".into(),
+        })]);
+
+        assert!(
+            retry_while_busy(3, Duration::from_millis(1), attempt)
+                .await
+                .is_err()
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn only_jobs_the_printer_never_took_are_not_delivered() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let client = IppClient::new(&format!("ipp://127.0.0.1:{port}/ipp/print")).unwrap();
+        let settings = PrintSettings::default();
+        let err = client
+            .print_job(
+                b"%PDF-1.7".to_vec(),
+                DocumentType::Pdf,
+                "refused",
+                &settings,
+            )
+            .await
+            .unwrap_err();
+        assert!(not_delivered(&err), "{err}");
+        assert!(not_delivered(&PresswerkError::IppStatus {
+            code: 0x040A,
+            status: "ClientErrorDocumentFormatNotSupported".into(),
+            detail: "Print-Job returned status ClientErrorDocumentFormatNotSupported".into(),
+        }));
+
+        let timed_out = PresswerkError::IppRequest("Print-Job timed out after 60s".into());
+        assert!(!not_delivered(&timed_out));
+        assert!(!not_delivered(&PresswerkError::IppStatus {
+            code: 0x0500,
+            status: "ServerErrorInternalError".into(),
+            detail: "Print-Job returned status ServerErrorInternalError".into(),
+        }));
+    }
+
+    #[test]
+    fn retry_hint_read_from_response_attributes() {
+        let mut attrs = IppAttributes::new();
+        assert_eq!(retry_hint(&attrs), None);
+
+        attrs.add(
+            DelimiterTag::OperationAttributes,
+            IppAttribute::new("retry-after", IppValue::Integer(30)),
+        );
+        assert_eq!(retry_hint(&attrs), Some(Duration::from_secs(30)));
+    }
+
+    // -- Cancellation -----------------------------------------------------------
+
+    #[tokio::test]
+    async fn cancel_closes_connection_mid_upload() {
+        use tokio::io::AsyncReadExt;
+
+        const BODY_LEN: usize = 32 * 1024 * 1024;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        // A printer that takes the upload in at a trickle, then drains
+        // whatever is left once the client has cancelled.
+        let flag = Arc::clone(&cancel);
+        let printer = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 64 * 1024];
+            let mut received = 0;
+            loop {
+                let limit = if flag.load(Ordering::Relaxed) {
+                    buf.len()
+                } else {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    4096
+                };
+                match socket.read(&mut buf[..limit]).await {
+                    Ok(0) | Err(_) => return received,
+                    Ok(n) => received += n,
+                }
+            }
+        });
+
+        let client = IppClient::new(&format!("ipp://127.0.0.1:{port}/ipp/print"))
+            .unwrap()
+            .with_cancel(Arc::clone(&cancel));
+        let settings = PrintSettings::default();
+        let upload = client.print_job(vec![b'x'; BODY_LEN], DocumentType::Pdf, "big", &settings);
+
+        let trigger = Arc::clone(&cancel);
+        let (result, ()) = tokio::join!(upload, async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            trigger.store(true, Ordering::Relaxed);
+        });
+        assert!(result.unwrap_err().to_string().contains("cancelled"));
+
+        let received = tokio::time::timeout(Duration::from_secs(10), printer)
+            .await
+            .expect("connection was not closed")
+            .unwrap();
+        assert!(received > 0);
+        assert!(received < BODY_LEN, "whole body sent: {received} bytes");
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_waiting_for_completion() {
+        // A printer that takes the connection but never answers.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let _printer = tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let client = IppClient::new(&format!("ipp://127.0.0.1:{port}/ipp/print"))
+            .unwrap()
+            .with_cancel(Arc::clone(&cancel));
+        let wait = client.wait_for_completion(7, DEFAULT_COMPLETION_TIMEOUT);
+        let trigger = Arc::clone(&cancel);
+        let (result, ()) = tokio::join!(
+            tokio::time::timeout(Duration::from_secs(5), wait),
+            async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                trigger.store(true, Ordering::Relaxed);
+            }
+        );
+        let err = result.expect("polling did not stop").unwrap_err();
+        assert!(err.to_string().contains("cancelled"), "{err}");
+    }
+
+    // -- Job state --------------------------------------------------------------
+
+    #[test]
+    fn ipp_job_states_map_to_job_status() {
+        let expected = [
+            (3, JobStatus::Pending),
+            (4, JobStatus::Held),
+            (5, JobStatus::Processing),
+            (6, JobStatus::Processing),
+            (7, JobStatus::Cancelled),
+            (8, JobStatus::Failed),
+            (9, JobStatus::Completed),
+        ];
+        for (state, status) in expected {
+            assert_eq!(
+                job_status_from_ipp_state(state),
+                Some(status),
+                "job-state {state}"
+            );
+        }
+        assert_eq!(job_status_from_ipp_state(0), None);
+        assert_eq!(job_status_from_ipp_state(10), None);
+    }
+
+    #[test]
+    fn job_state_read_from_job_attributes_group() {
+        let mut attrs = IppAttributes::new();
+        attrs.add(
+            DelimiterTag::OperationAttributes,
+            IppAttribute::new("job-state", IppValue::Enum(9)),
+        );
+        assert_eq!(extract_job_state(&attrs), None);
+
+        attrs.add(
+            DelimiterTag::JobAttributes,
+            IppAttribute::new("job-state", IppValue::Enum(5)),
+        );
+        assert_eq!(extract_job_state(&attrs), Some(5));
+    }
+
+    // -- Validate-Job -----------------------------------------------------------
+
+    /// A response with `status` naming `unsupported` attributes.
+    fn validate_response(status: u16, unsupported: &[&str]) -> IppRequestResponse {
+        let mut response =
+            IppRequestResponse::new_response(IppVersion::v1_1(), StatusCode::SuccessfulOk, 1);
+        response.header_mut().operation_or_status = status;
+        for name in unsupported {
+            response.attributes_mut().add(
+                DelimiterTag::UnsupportedAttributes,
+                IppAttribute::new(*name, IppValue::Keyword("two-sided-long-edge".into())),
+            );
+        }
+        response
+    }
+
+    #[test]
+    fn validate_job_request_carries_the_job_attributes() {
+        let settings = PrintSettings {
+            copies: 2,
+            ..PrintSettings::default()
+        };
+        let uri: Uri = "ipp://printer.local/ipp/print".parse().unwrap();
+        let request = validate_job_request(uri, DocumentType::Pdf, &settings, None);
+
+        assert_eq!(request.header().operation_or_status, 0x0004);
+        let operation = request
+            .attributes()
+            .groups_of(DelimiterTag::OperationAttributes)
+            .next()
+            .unwrap();
+        assert_eq!(
+            operation.attributes()["document-format"].value(),
+            &IppValue::MimeMediaType("application/pdf".into())
+        );
+        let job = request
+            .attributes()
+            .groups_of(DelimiterTag::JobAttributes)
+            .next()
+            .unwrap();
+        assert_eq!(job.attributes()["copies"].value(), &IppValue::Integer(2));
+    }
+
+    #[test]
+    fn validate_job_accepted() {
+        assert_eq!(
+            validation_outcome(&validate_response(0x0000, &[])).unwrap(),
+            ValidationOutcome::Accepted
+        );
+        let substituted = validation_outcome(&validate_response(0x0001, &["sides"])).unwrap();
+        assert_eq!(
+            substituted,
+            ValidationOutcome::AcceptedWithChanges(vec!["sides".into()])
+        );
+        assert!(substituted.is_accepted());
+    }
+
+    #[test]
+    fn validate_job_rejected() {
+        let rejected = validation_outcome(&validate_response(0x040B, &["sides", "media"])).unwrap();
+        assert_eq!(
+            rejected,
+            ValidationOutcome::Rejected {
+                status: "Attributes or values not supported".into(),
+                unsupported: vec!["media".into(), "sides".into()],
+            }
+        );
+        assert!(!rejected.is_accepted());
+
+        let not_settable = validation_outcome(&validate_response(0x0413, &[])).unwrap();
+        assert!(matches!(
+            not_settable,
+            ValidationOutcome::Rejected { ref status, .. } if status == "Attributes not settable"
+        ));
+
+        // Errors that say nothing about the settings are errors, with the
+        // status the printer sent.
+        let err = validation_outcome(&validate_response(0x0506, &[])).unwrap_err();
+        assert!(
+            matches!(err, PresswerkError::IppStatus { code: 0x0506, .. }),
+            "{err:?}"
+        );
+    }
+
+    // -- Get-Jobs ---------------------------------------------------------------
+
+    /// A Job Attributes group holding `attributes`.
+    fn job_group(attributes: Vec<IppAttribute>) -> IppAttributeGroup {
+        let mut group = IppAttributeGroup::new(DelimiterTag::JobAttributes);
+        for attribute in attributes {
+            group
+                .attributes_mut()
+                .insert(attribute.name().to_owned(), attribute);
+        }
+        group
+    }
+
+    #[test]
+    fn get_jobs_response_parsed_into_jobs() {
+        let mut attrs = IppAttributes::new();
+        attrs.add(
+            DelimiterTag::OperationAttributes,
+            IppAttribute::new("attributes-charset", IppValue::Charset("utf-8".into())),
+        );
+        attrs.groups_mut().push(job_group(vec![
+            IppAttribute::new("job-id", IppValue::Integer(12)),
+            IppAttribute::new(
+                "job-name",
+                IppValue::NameWithoutLanguage("report.pdf".into()),
+            ),
+            IppAttribute::new("job-state", IppValue::Enum(5)),
+        ]));
+        attrs.groups_mut().push(job_group(vec![
+            IppAttribute::new("job-id", IppValue::Integer(13)),
+            IppAttribute::new("job-state", IppValue::Enum(4)),
+        ]));
+
+        assert_eq!(
+            parse_jobs(&attrs),
+            [
+                RemoteJob {
+                    job_id: 12,
+                    job_name: "report.pdf".into(),
+                    job_state: Some(JobStatus::Processing),
+                },
+                RemoteJob {
+                    job_id: 13,
+                    job_name: String::new(),
+                    job_state: Some(JobStatus::Held),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn get_jobs_skips_jobs_without_an_id() {
+        let mut attrs = IppAttributes::new();
+        attrs.groups_mut().push(job_group(vec![IppAttribute::new(
+            "job-name",
+            IppValue::NameWithoutLanguage("orphan".into()),
+        )]));
+        attrs.groups_mut().push(job_group(vec![
+            IppAttribute::new("job-id", IppValue::Integer(7)),
+            IppAttribute::new("job-state", IppValue::Enum(42)),
+        ]));
+
+        let jobs = parse_jobs(&attrs);
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].job_id, 7);
+        assert_eq!(jobs[0].job_state, None);
+    }
+
+    #[test]
+    fn get_jobs_empty_queue() {
+        assert!(parse_jobs(&IppAttributes::new()).is_empty());
+    }
+
+    // -- Completion -------------------------------------------------------------
+
+    #[tokio::test]
+    async fn job_aborted_while_processing_ends_failed() {
+        let (calls, poll) = stub_printer(vec![
+            Ok(JobStatus::Processing),
+            Ok(JobStatus::Processing),
+            Ok(JobStatus::Failed),
+        ]);
+
+        let status = poll_until_finished(Duration::from_millis(1), Duration::from_secs(5), poll)
+            .await
+            .unwrap();
+        assert_eq!(status, JobStatus::Failed);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn polling_stops_at_the_timeout_with_the_last_state() {
+        let (calls, poll) = stub_printer(vec![Ok(JobStatus::Processing)]);
+
+        let status = poll_until_finished(Duration::from_secs(60), Duration::from_secs(1), poll)
+            .await
+            .unwrap();
+        assert_eq!(status, JobStatus::Processing);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
 }
@@ -24,39 +24,58 @@
 //   - Get-Jobs          (0x000A)  RFC 8011 SS4.2.6
 //   - Get-Printer-Attrs (0x000B)  RFC 8011 SS4.2.5
 //
+// # Large jobs
+//
+// Requests up to `ServerConfig::spool_threshold` are read into memory.  For
+// larger ones the attributes are parsed from the start of the request and
+// the document data is streamed into a temp file in the document store,
+// which the job then takes over.  Requests over
+// `ServerConfig::max_request_bytes` are refused with
+// client-error-request-entity-too-large.
+//
 // # mDNS advertisement
 //
 // On start the server registers `_ipp._tcp.local.` via mDNS-SD so other
 // devices on the LAN can discover it automatically.
+//
+// # Certificate
+//
+// The self-signed key pair for IPPS lives in `{data_dir}/server-cert.json`.
+// It is created on first start and renewed on any start within
+// `CERT_RENEWAL_DAYS` of its expiry.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 
 use sha2::{Digest, Sha256};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
 use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 use presswerk_core::error::{PresswerkError, Result};
-use presswerk_core::types::{DocumentType, JobId, JobSource, JobStatus, PrintJob, ServerStatus};
+use presswerk_core::types::{
+    DocumentType, JobSource, JobStatus, PrintJob, SNIFF_LEN, ServerConfig, ServerStatus,
+    resolve_document_type,
+};
+use presswerk_security::{EncryptedStorage, SelfSignedCert};
 
+use crate::document_store::DocumentStore;
 use crate::queue::JobQueue;
 
 // ---------------------------------------------------------------------------
 // Constants
 // ---------------------------------------------------------------------------
 
-/// Default port for the IPP print server (IANA-assigned for IPP).
-const DEFAULT_PORT: u16 = 631;
+/// Size of each read once a request is being spooled.
+const SPOOL_READ_CHUNK: usize = 64 * 1024;
 
-/// Maximum bytes to read from a connection before rejecting it.
-/// Prevents unbounded memory consumption from misbehaving clients.
-const MAX_REQUEST_BYTES: usize = 64 * 1024 * 1024; // 64 MiB
+/// Longest chunk-size or trailer line accepted in a chunked body.
+const MAX_CHUNK_LINE: usize = 4096;
 
 /// IPP version 1.1 major byte.
 pub const IPP_VERSION_MAJOR: u8 = 0x01;
@@ -64,12 +83,15 @@
 /// IPP version 1.1 minor byte.
 pub const IPP_VERSION_MINOR: u8 = 0x01;
 
-/// Default printer name advertised via mDNS and returned in attributes.
-const PRINTER_NAME: &str = "Presswerk Virtual Printer";
-
 /// mDNS service type for plain IPP.
 const IPP_SERVICE_TYPE: &str = "_ipp._tcp.local.";
 
+/// File in the data directory holding the server's certificate.
+const CERT_FILENAME: &str = "server-cert.json";
+
+/// Renew the certificate on start once it is this close to expiring.
+const CERT_RENEWAL_DAYS: i64 = 30;
+
 // ---------------------------------------------------------------------------
 // IPP delimiter tags (RFC 8010 SS3.5.1)
 // ---------------------------------------------------------------------------
@@ -130,6 +152,9 @@
 /// Cancel-Job operation identifier.
 pub const OP_CANCEL_JOB: u16 = 0x0008;
 
+/// Get-Job-Attributes operation identifier.
+pub const OP_GET_JOB_ATTRIBUTES: u16 = 0x0009;
+
 /// Get-Jobs operation identifier.
 pub const OP_GET_JOBS: u16 = 0x000A;
 
@@ -149,6 +174,9 @@
 /// The requested job was not found.
 const STATUS_CLIENT_ERROR_NOT_FOUND: u16 = 0x0406;
 
+/// The request is larger than the server accepts.
+const STATUS_CLIENT_ERROR_REQUEST_ENTITY: u16 = 0x0408;
+
 /// The requested operation is not supported.
 const STATUS_SERVER_ERROR_OPERATION_NOT_SUPPORTED: u16 = 0x0501;
 
@@ -249,11 +277,22 @@ pub struct IppRequest {
     pub request_id: u32,
     /// All attribute groups in order.
     pub attribute_groups: Vec<IppAttributeGroup>,
-    /// Document data (everything after the end-of-attributes tag).
+    /// Document data (everything after the end-of-attributes tag).  Empty
+    /// when the data was spooled to disk instead.
     pub document_data: Vec<u8>,
+    /// Document data too large to keep in memory, if it was spooled.
+    pub spooled_document: Option<SpooledDocument>,
 }
 
 impl IppRequest {
+    /// Size of the document data, wherever it is held.
+    pub fn document_len(&self) -> u64 {
+        match &self.spooled_document {
+            Some(document) => document.len,
+            None => self.document_data.len() as u64,
+        }
+    }
+
     /// Get the first operation-attributes group.
     pub fn operation_attributes(&self) -> Option<&IppAttributeGroup> {
         self.attribute_groups
@@ -270,6 +309,27 @@ pub fn job_attributes(&self) -> Option<&IppAttributeGroup> {
     }
 }
 
+/// Document data written to a temp file in the document store while the
+/// request was read.
+///
+/// The file is deleted when this is dropped, unless the store has taken it
+/// over with [`DocumentStore::put_file_for_job`].
+#[derive(Debug)]
+pub struct SpooledDocument {
+    /// The temp file, from [`DocumentStore::temp_path`].
+    pub path: PathBuf,
+    /// Length of the document in bytes.
+    pub len: u64,
+    /// SHA-256 hex digest of the document, computed while writing it.
+    pub sha256: String,
+}
+
+impl Drop for SpooledDocument {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // IPP binary parser
 // ---------------------------------------------------------------------------
@@ -294,6 +354,21 @@ pub fn job_attributes(&self) -> Option<&IppAttributeGroup> {
 /// document-data: remainder
 /// ```
 pub fn parse_ipp_request(data: &[u8]) -> std::result::Result<IppRequest, String> {
+    let (mut request, attributes_end) = parse_ipp_head(data)?;
+
+    // Everything remaining is document data.
+    let pos = attributes_end.unwrap_or(data.len());
+    request.document_data = data[pos..].to_vec();
+    Ok(request)
+}
+
+/// Parse the header and attribute groups of an IPP message, leaving the
+/// document data alone.
+///
+/// Also returns the offset just past the end-of-attributes tag, or `None`
+/// if `data` ended without one — so a prefix of a longer message can be
+/// parsed and the caller can tell whether it held all the attributes.
+fn parse_ipp_head(data: &[u8]) -> std::result::Result<(IppRequest, Option<usize>), String> {
     if data.len() < 8 {
         return Err(format!(
             "IPP request too short: {} bytes (minimum 8)",
@@ -309,6 +384,7 @@ pub fn parse_ipp_request(data: &[u8]) -> std::result::Result<IppRequest, String>
     let mut pos = 8;
     let mut attribute_groups: Vec<IppAttributeGroup> = Vec::new();
     let mut current_group: Option<IppAttributeGroup> = None;
+    let mut attributes_end = None;
 
     while pos < data.len() {
         let tag = data[pos];
@@ -322,6 +398,7 @@ pub fn parse_ipp_request(data: &[u8]) -> std::result::Result<IppRequest, String>
 
             if tag == TAG_END_OF_ATTRIBUTES {
                 pos += 1;
+                attributes_end = Some(pos);
                 break;
             }
 
@@ -381,21 +458,18 @@ pub fn parse_ipp_request(data: &[u8]) -> std::result::Result<IppRequest, String>
         attribute_groups.push(group);
     }
 
-    // Everything remaining is document data.
-    let document_data = if pos < data.len() {
-        data[pos..].to_vec()
-    } else {
-        Vec::new()
-    };
-
-    Ok(IppRequest {
-        version_major,
-        version_minor,
-        operation_id,
-        request_id,
-        attribute_groups,
-        document_data,
-    })
+    Ok((
+        IppRequest {
+            version_major,
+            version_minor,
+            operation_id,
+            request_id,
+            attribute_groups,
+            document_data: Vec::new(),
+            spooled_document: None,
+        },
+        attributes_end,
+    ))
 }
 
 // ---------------------------------------------------------------------------
@@ -520,8 +594,10 @@ pub fn build(mut self) -> Vec<u8> {
 /// Result of parsing a minimal HTTP POST request for IPP.
 struct HttpRequest {
     /// The Content-Length value, if present.
-    #[allow(dead_code)]
     content_length: Option<usize>,
+    /// Whether the body is sent with `Transfer-Encoding: chunked` and has to
+    /// be reassembled with [`dechunk`].
+    chunked: bool,
     /// The offset where the HTTP body (IPP payload) begins.
     body_offset: usize,
 }
@@ -529,8 +605,8 @@ struct HttpRequest {
 /// Parse the bare minimum of an HTTP/1.1 POST request to find the body.
 ///
 /// IPP over HTTP uses `Content-Type: application/ipp`.  We only need to
-/// find where the headers end (double CRLF) and extract Content-Length.
-/// Returns `None` if the data doesn't look like an HTTP request (in which
+/// find where the headers end (double CRLF) and extract Content-Length, or
+/// notice a chunked body.  Returns `None` if the data doesn't look like an HTTP request (in which
 /// case we treat the entire payload as raw IPP).
 fn parse_http_envelope(data: &[u8]) -> Option<HttpRequest> {
     // Look for the end of headers: \r\n\r\n
@@ -546,12 +622,143 @@ fn parse_http_envelope(data: &[u8]) -> Option<HttpRequest> {
         .and_then(|line| line.split(':').nth(1))
         .and_then(|val| val.trim().parse::<usize>().ok());
 
+    // Chunked wins over Content-Length when both are sent (RFC 9112 §6.3).
+    let chunked = headers_str.lines().any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value
+                    .split(',')
+                    .any(|coding| coding.trim().eq_ignore_ascii_case("chunked"))
+        })
+    });
+
     Some(HttpRequest {
         content_length,
+        chunked,
         body_offset,
     })
 }
 
+/// Reassemble a complete `Transfer-Encoding: chunked` body held in memory.
+/// See [`ChunkedDecoder`].
+fn dechunk(body: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    let mut decoder = ChunkedDecoder::default();
+    let mut out = Vec::with_capacity(body.len());
+    decoder.feed(body, &mut out)?;
+    if !decoder.is_done() {
+        return Err("chunked body not terminated".into());
+    }
+    Ok(out)
+}
+
+/// Incremental decoder for `Transfer-Encoding: chunked` bodies
+/// (RFC 9112 §7.1), fed the body as it arrives.
+///
+/// Each chunk is a hex size line (extensions after `;` are ignored), the
+/// data, and a CRLF; a zero-size chunk ends the body, followed by optional
+/// trailer fields and a blank line.  Anything else — bad sizes, missing
+/// CRLFs, overlong lines — is an error.  Bytes after the end are ignored.
+#[derive(Debug, Default)]
+struct ChunkedDecoder {
+    state: ChunkState,
+    /// The size or trailer line read so far.
+    line: Vec<u8>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ChunkState {
+    /// Reading a chunk-size line.
+    #[default]
+    Size,
+    /// Inside a chunk, with this many data bytes to go.
+    Data(usize),
+    /// After a chunk's data, with this many bytes of its CRLF seen.
+    DataEnd(usize),
+    /// Reading trailer fields after the last chunk.
+    Trailer,
+    /// The blank line after the last chunk has been read.
+    Done,
+}
+
+impl ChunkedDecoder {
+    /// Decode `input`, appending chunk data to `out`.
+    fn feed(&mut self, input: &[u8], out: &mut Vec<u8>) -> std::result::Result<(), String> {
+        let mut rest = input;
+        while !rest.is_empty() {
+            match self.state {
+                ChunkState::Size | ChunkState::Trailer => {
+                    let Some(newline) = rest.iter().position(|&b| b == b'\n') else {
+                        self.push_line(rest)?;
+                        return Ok(());
+                    };
+                    self.push_line(&rest[..=newline])?;
+                    rest = &rest[newline + 1..];
+                    let line = std::mem::take(&mut self.line);
+                    let line = line
+                        .strip_suffix(b"\r\n")
+                        .ok_or("chunk size line not terminated by CRLF")?;
+                    self.state = if self.state == ChunkState::Trailer {
+                        if line.is_empty() {
+                            ChunkState::Done
+                        } else {
+                            ChunkState::Trailer
+                        }
+                    } else {
+                        match parse_chunk_size(line)? {
+                            0 => ChunkState::Trailer,
+                            size => ChunkState::Data(size),
+                        }
+                    };
+                }
+                ChunkState::Data(remaining) => {
+                    let taken = remaining.min(rest.len());
+                    out.extend_from_slice(&rest[..taken]);
+                    rest = &rest[taken..];
+                    self.state = match remaining - taken {
+                        0 => ChunkState::DataEnd(0),
+                        left => ChunkState::Data(left),
+                    };
+                }
+                ChunkState::DataEnd(seen) => {
+                    if rest[0] != b"\r\n"[seen] {
+                        return Err("chunk data not followed by CRLF".into());
+                    }
+                    rest = &rest[1..];
+                    self.state = match seen {
+                        0 => ChunkState::DataEnd(1),
+                        _ => ChunkState::Size,
+                    };
+                }
+                ChunkState::Done => return Ok(()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the whole body, trailer included, has been decoded.
+    fn is_done(&self) -> bool {
+        self.state == ChunkState::Done
+    }
+
+    fn push_line(&mut self, bytes: &[u8]) -> std::result::Result<(), String> {
+        if self.line.len() + bytes.len() > MAX_CHUNK_LINE {
+            return Err(format!("chunk line longer than {MAX_CHUNK_LINE} bytes"));
+        }
+        self.line.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Parse a chunk-size line without its CRLF.
+fn parse_chunk_size(line: &[u8]) -> std::result::Result<usize, String> {
+    let line = std::str::from_utf8(line).map_err(|_| "chunk size line is not ASCII".to_string())?;
+    let size_field = line.split(';').next().unwrap_or("").trim();
+    if size_field.is_empty() || !size_field.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("invalid chunk size {size_field:?}"));
+    }
+    usize::from_str_radix(size_field, 16).map_err(|_| format!("chunk size {size_field} too large"))
+}
+
 /// Find the first occurrence of `needle` in `haystack`.
 fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     haystack
@@ -559,24 +766,352 @@ fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         .position(|window| window == needle)
 }
 
+// ---------------------------------------------------------------------------
+// Request reading
+// ---------------------------------------------------------------------------
+
+/// What [`read_ipp_request`] got from a client.
+enum ReceivedRequest {
+    /// The client sent nothing.
+    Empty,
+    /// A parsed request, its document in memory or spooled to disk.
+    Request(IppRequest),
+    /// The request was refused; this IPP error response says why.
+    Rejected(Vec<u8>),
+}
+
+/// Read one IPP request from `reader`, with or without HTTP framing.
+///
+/// Requests of up to `spool_threshold` bytes are read into memory and
+/// parsed whole.  For larger ones the attributes are parsed from the first
+/// `spool_threshold` bytes and the document data is streamed to a temp file
+/// in `documents` (see [`IppRequest::spooled_document`]).  A request of more
+/// than `max_bytes`, or announcing as much in `Content-Length`, is refused
+/// with `client-error-request-entity-too-large`.
+async fn read_ipp_request<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    documents: &DocumentStore,
+    spool_threshold: usize,
+    max_bytes: usize,
+) -> Result<ReceivedRequest> {
+    let buf = read_request_start(reader, spool_threshold + 1).await?;
+    debug!(bytes = buf.len(), "received IPP request data");
+
+    if buf.is_empty() {
+        return Ok(ReceivedRequest::Empty);
+    }
+
+    // Strip HTTP envelope if present.  Some IPP clients send raw IPP
+    // over TCP (especially in test environments), others wrap it in HTTP.
+    let envelope = parse_http_envelope(&buf);
+    match &envelope {
+        Some(http_req) => debug!(
+            body_offset = http_req.body_offset,
+            content_length = ?http_req.content_length,
+            chunked = http_req.chunked,
+            "HTTP envelope detected"
+        ),
+        None => debug!("no HTTP envelope -- treating as raw IPP"),
+    }
+    let body = &buf[envelope.as_ref().map_or(0, |http_req| http_req.body_offset)..];
+    let mut decoder = envelope
+        .as_ref()
+        .filter(|http_req| http_req.chunked)
+        .map(|_| ChunkedDecoder::default());
+
+    let malformed_chunked = |e: String| {
+        warn!(error = %e, "malformed chunked body");
+        ReceivedRequest::Rejected(build_error_response(
+            STATUS_CLIENT_ERROR_BAD_REQUEST,
+            0, // no valid request-id
+            &format!("Malformed chunked body: {e}"),
+        ))
+    };
+    let malformed_ipp = |e: String| {
+        warn!(error = %e, "malformed IPP request");
+        ReceivedRequest::Rejected(build_error_response(
+            STATUS_CLIENT_ERROR_BAD_REQUEST,
+            0, // no valid request-id
+            &format!("Malformed IPP request: {e}"),
+        ))
+    };
+    let too_large = |request_id: u32| {
+        warn!(max_bytes, "request too large");
+        ReceivedRequest::Rejected(build_error_response(
+            STATUS_CLIENT_ERROR_REQUEST_ENTITY,
+            request_id,
+            &format!("Request larger than {max_bytes} bytes"),
+        ))
+    };
+
+    // The whole request fitted in memory.
+    if buf.len() <= spool_threshold {
+        let body = match decoder {
+            Some(_) => match dechunk(body) {
+                Ok(data) => Cow::Owned(data),
+                Err(e) => return Ok(malformed_chunked(e)),
+            },
+            None => Cow::Borrowed(body),
+        };
+        return Ok(match parse_ipp_request(&body) {
+            Ok(request) => ReceivedRequest::Request(request),
+            Err(e) => malformed_ipp(e),
+        });
+    }
+
+    let head = match decoder.as_mut() {
+        Some(decoder) => {
+            let mut decoded = Vec::with_capacity(body.len());
+            if let Err(e) = decoder.feed(body, &mut decoded) {
+                return Ok(malformed_chunked(e));
+            }
+            Cow::Owned(decoded)
+        }
+        None => Cow::Borrowed(body),
+    };
+
+    // Too big to keep: parse the attributes from what has been read and
+    // stream the document data after them to disk.
+    let (mut request, document_start) = match parse_ipp_head(&head) {
+        Ok((request, Some(end))) => (request, end),
+        Ok((_, None)) => {
+            return Ok(malformed_ipp(format!(
+                "attributes do not end within the first {spool_threshold} bytes"
+            )));
+        }
+        Err(e) => return Ok(malformed_ipp(e)),
+    };
+    if envelope
+        .as_ref()
+        .and_then(|http_req| http_req.content_length)
+        .is_some_and(|length| length > max_bytes)
+    {
+        return Ok(too_large(request.request_id));
+    }
+
+    let mut spool = Spool::create(documents.temp_path()).await?;
+    spool.write(&head[document_start..]).await?;
+
+    // Where a Content-Length body ends, so the read stops there rather than
+    // waiting for a client that keeps the connection open.
+    let request_end = envelope
+        .as_ref()
+        .filter(|http_req| !http_req.chunked)
+        .and_then(|http_req| Some(http_req.body_offset + http_req.content_length?));
+
+    let mut received = buf.len();
+    let mut chunk = vec![0; SPOOL_READ_CHUNK];
+    let mut decoded = Vec::new();
+    loop {
+        if request_end.is_some_and(|end| received >= end)
+            || decoder.as_ref().is_some_and(ChunkedDecoder::is_done)
+        {
+            break;
+        }
+        let n = reader
+            .read(&mut chunk)
+            .await
+            .map_err(|e| PresswerkError::PrintServer(format!("read request: {e}")))?;
+        if n == 0 {
+            break;
+        }
+        received += n;
+        if received > max_bytes {
+            return Ok(too_large(request.request_id));
+        }
+        match decoder.as_mut() {
+            Some(decoder) => {
+                decoded.clear();
+                if let Err(e) = decoder.feed(&chunk[..n], &mut decoded) {
+                    return Ok(malformed_chunked(e));
+                }
+                spool.write(&decoded).await?;
+            }
+            None => spool.write(&chunk[..n]).await?,
+        }
+    }
+    if decoder.as_ref().is_some_and(|decoder| !decoder.is_done()) {
+        return Ok(malformed_chunked("chunked body not terminated".into()));
+    }
+
+    let document = spool.finish().await?;
+    info!(
+        request_bytes = received,
+        document_bytes = document.len,
+        "document data spooled to disk"
+    );
+    request.spooled_document = Some(document);
+    Ok(ReceivedRequest::Request(request))
+}
+
+/// Read up to `limit` bytes of a request, stopping as soon as an HTTP
+/// request is complete: HTTP/1.1 clients keep the connection open while
+/// they wait for the response.  Raw IPP, and HTTP bodies with neither a
+/// Content-Length nor chunked framing, end when the client closes its side.
+async fn read_request_start<R: AsyncRead + Unpin>(reader: &mut R, limit: usize) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(8192);
+    let mut chunk = vec![0; SPOOL_READ_CHUNK];
+    let mut envelope: Option<HttpRequest> = None;
+    let mut decoder = ChunkedDecoder::default();
+    let mut decoded = Vec::new();
+    // How much of the body has been fed to `decoder`.
+    let mut fed = 0;
+
+    while buf.len() < limit {
+        let want = chunk.len().min(limit - buf.len());
+        let n = reader
+            .read(&mut chunk[..want])
+            .await
+            .map_err(|e| PresswerkError::PrintServer(format!("read request: {e}")))?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        if envelope.is_none() {
+            envelope = parse_http_envelope(&buf);
+            fed = envelope.as_ref().map_or(0, |http_req| http_req.body_offset);
+        }
+        let complete = match &envelope {
+            None => false,
+            Some(http_req) if http_req.chunked => {
+                decoded.clear();
+                // A framing error is reported once the body is decoded.
+                let framing = decoder.feed(&buf[fed..], &mut decoded);
+                fed = buf.len();
+                framing.is_err() || decoder.is_done()
+            }
+            Some(http_req) => http_req
+                .content_length
+                .is_some_and(|length| buf.len() >= http_req.body_offset + length),
+        };
+        if complete {
+            break;
+        }
+    }
+    Ok(buf)
+}
+
+/// A [`SpooledDocument`] being written, hashed as it goes.  Dropping it
+/// unfinished deletes the file.
+struct Spool {
+    file: tokio::fs::File,
+    hasher: Sha256,
+    document: SpooledDocument,
+}
+
+impl Spool {
+    async fn create(path: PathBuf) -> Result<Self> {
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+            .map_err(|e| {
+                PresswerkError::PrintServer(format!("create spool file {}: {e}", path.display()))
+            })?;
+        Ok(Self {
+            file,
+            hasher: Sha256::new(),
+            document: SpooledDocument {
+                path,
+                len: 0,
+                sha256: String::new(),
+            },
+        })
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.file
+            .write_all(data)
+            .await
+            .map_err(|e| PresswerkError::PrintServer(format!("write spool file: {e}")))?;
+        self.hasher.update(data);
+        self.document.len += data.len() as u64;
+        Ok(())
+    }
+
+    /// Flush the file to stable storage and record its hash.
+    async fn finish(self) -> Result<SpooledDocument> {
+        let Self {
+            mut file,
+            hasher,
+            mut document,
+        } = self;
+        let synced = match file.flush().await {
+            Ok(()) => file.sync_all().await,
+            Err(e) => Err(e),
+        };
+        synced.map_err(|e| PresswerkError::PrintServer(format!("sync spool file: {e}")))?;
+        document.sha256 = hex::encode(hasher.finalize());
+        Ok(document)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Shared state passed to connection handlers
 // ---------------------------------------------------------------------------
 
 /// State shared across all connection-handling tasks.
 struct SharedState {
-    /// The job queue for persisting incoming print jobs.
+    /// The job queue for persisting incoming print jobs.  It also holds the
+    /// IPP integer job-id for each job, so ids survive a server restart.
     job_queue: Arc<Mutex<JobQueue>>,
     /// Counter of active connections (for the UI).
     active_connections: Arc<AtomicU32>,
-    /// The port we are listening on (used to build printer-uri).
-    port: u16,
-    /// Internal job ID counter (IPP uses sequential integers, not UUIDs).
-    next_ipp_job_id: Arc<AtomicU32>,
-    /// Map from IPP integer job-id to our internal UUID-based JobId.
-    ipp_to_internal: Arc<Mutex<HashMap<i32, JobId>>>,
-    /// Directory for persisting document data files.
-    data_dir: PathBuf,
+    /// The server's settings (port for printer-uri, name, limits).
+    config: ServerConfig,
+    /// Content-addressed storage for received document data.
+    documents: Arc<DocumentStore>,
+}
+
+// ---------------------------------------------------------------------------
+// Certificate
+// ---------------------------------------------------------------------------
+
+/// Load the certificate at `path`, creating it if missing or unreadable and
+/// renewing it (same subject and SANs) if it expires within
+/// [`CERT_RENEWAL_DAYS`].  Any new certificate is written back to `path`,
+/// with its key encrypted by `key_storage` if given; so is a current one
+/// whose key was still in the clear.
+fn load_or_renew_certificate(
+    path: &Path,
+    name: &str,
+    key_storage: Option<&EncryptedStorage>,
+) -> Result<SelfSignedCert> {
+    let existing = if path.exists() {
+        SelfSignedCert::load(path, key_storage)
+            .map_err(
+                |e| warn!(path = %path.display(), error = %e, "discarding unreadable certificate"),
+            )
+            .ok()
+    } else {
+        None
+    };
+
+    let cert = match existing {
+        Some(cert) if !cert.is_expiring_within(chrono::Duration::days(CERT_RENEWAL_DAYS)) => {
+            debug!(expires_at = %cert.expires_at(), "server certificate is current");
+            if key_storage.is_some() && !cert.is_key_encrypted() {
+                cert.save(path, key_storage)?;
+            }
+            return Ok(cert);
+        }
+        Some(cert) => {
+            info!(expires_at = %cert.expires_at(), "server certificate expires soon, renewing");
+            cert.renew()?
+        }
+        None => {
+            let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "presswerk".into());
+            SelfSignedCert::generate_for(
+                name,
+                &[format!("{hostname}.local"), "localhost".to_string()],
+                chrono::Duration::days(presswerk_security::certificates::DEFAULT_VALIDITY_DAYS),
+            )?
+        }
+    };
+    cert.save(path, key_storage)?;
+    Ok(cert)
 }
 
 // ---------------------------------------------------------------------------
@@ -589,8 +1124,8 @@ struct SharedState {
 /// to print to this phone/tablet.  Incoming print jobs are placed into the
 /// local job queue for user review.
 pub struct IppServer {
-    /// The TCP port to listen on.
-    port: u16,
+    /// Port, name, limits and the rest of the server's settings.
+    config: ServerConfig,
     /// Current lifecycle state of the server.
     status: ServerStatus,
     /// Notification handle used to signal a graceful shutdown.
@@ -603,22 +1138,43 @@ pub struct IppServer {
     mdns_daemon: Option<mdns_sd::ServiceDaemon>,
     /// The mDNS service fullname (for unregistration on stop).
     mdns_fullname: Option<String>,
-    /// Root directory for persistent data (documents subdirectory lives here).
+    /// Root directory for persistent data (documents subdirectory lives here),
+    /// from the config or the default.
     data_dir: PathBuf,
+    /// Key pair for IPPS, loaded (or created) on start.
+    certificate: Option<SelfSignedCert>,
+    /// Store shared with the rest of the app, if given; otherwise one is
+    /// opened in `data_dir` on start.
+    documents: Option<Arc<DocumentStore>>,
+    /// Encrypts the certificate's private key on disk, if given.
+    key_storage: Option<Arc<EncryptedStorage>>,
 }
 
 impl IppServer {
-    /// Create a new server bound to the given port.
-    ///
-    /// The server is created in `Stopped` state.  Call [`start`] to begin
-    /// accepting connections.
+    /// Create a new server bound to the given port, with the default
+    /// [`ServerConfig`] otherwise.
     ///
     /// `data_dir` specifies the root directory where document data is persisted.
     /// If `None`, a temporary directory is used (suitable for tests).
     pub fn new(port: Option<u16>, data_dir: Option<PathBuf>) -> Self {
-        let data_dir = data_dir.unwrap_or_else(|| std::env::temp_dir().join("presswerk"));
+        Self::with_config(ServerConfig {
+            port: port.unwrap_or(ServerConfig::DEFAULT_PORT),
+            data_dir,
+            ..ServerConfig::default()
+        })
+    }
+
+    /// Create a new server with the given settings.
+    ///
+    /// The server is created in `Stopped` state.  Call [`start`] to begin
+    /// accepting connections.
+    pub fn with_config(config: ServerConfig) -> Self {
+        let data_dir = config
+            .data_dir
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("presswerk"));
         Self {
-            port: port.unwrap_or(DEFAULT_PORT),
+            config,
             status: ServerStatus::Stopped,
             shutdown_signal: Arc::new(Notify::new()),
             task_handle: None,
@@ -626,12 +1182,35 @@ pub fn new(port: Option<u16>, data_dir: Option<PathBuf>) -> Self {
             mdns_daemon: None,
             mdns_fullname: None,
             data_dir,
+            certificate: None,
+            documents: None,
+            key_storage: None,
         }
     }
 
+    /// Keep received documents in `documents` rather than a store of the
+    /// server's own, so its reference bookkeeping is shared with the other
+    /// users of the same data directory.
+    pub fn with_documents(mut self, documents: Arc<DocumentStore>) -> Self {
+        self.documents = Some(documents);
+        self
+    }
+
+    /// Encrypt the certificate's private key with `storage` when it is
+    /// written to disk, rather than keeping it in the clear.
+    pub fn with_key_storage(mut self, storage: Arc<EncryptedStorage>) -> Self {
+        self.key_storage = Some(storage);
+        self
+    }
+
     /// Return the port this server will bind to (or is bound to).
     pub fn port(&self) -> u16 {
-        self.port
+        self.config.port
+    }
+
+    /// The settings the server was created with.
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
     }
 
     /// Return the current server status.
@@ -639,6 +1218,11 @@ pub fn status(&self) -> ServerStatus {
         self.status
     }
 
+    /// The server's certificate, once [`start`](Self::start) has run.
+    pub fn certificate(&self) -> Option<&SelfSignedCert> {
+        self.certificate.as_ref()
+    }
+
     /// Return the number of currently active client connections.
     pub fn active_connections(&self) -> u32 {
         self.active_connections.load(Ordering::Relaxed)
@@ -681,44 +1265,47 @@ pub fn retrieve_document(&self, hash: &str) -> Result<Vec<u8>> {
     /// be created.
     pub async fn start(&mut self, job_queue: Arc<Mutex<JobQueue>>) -> Result<()> {
         if self.status == ServerStatus::Running {
-            debug!(port = self.port, "IPP server already running");
+            debug!(port = self.config.port, "IPP server already running");
             return Ok(());
         }
 
         self.status = ServerStatus::Starting;
 
-        let bind_addr: SocketAddr = ([0, 0, 0, 0], self.port).into();
+        let bind_addr: SocketAddr = ([0, 0, 0, 0], self.config.port).into();
         let listener = TcpListener::bind(bind_addr)
             .await
             .map_err(|e| PresswerkError::PrintServer(format!("bind {bind_addr}: {e}")))?;
 
-        info!(port = self.port, "IPP print server listening");
+        info!(port = self.config.port, "IPP print server listening");
 
         // Register via mDNS so other devices discover us.
         self.register_mdns();
 
-        // Ensure the documents subdirectory exists for persisting print data.
-        let documents_dir = self.data_dir.join("documents");
-        std::fs::create_dir_all(&documents_dir).map_err(|e| {
-            PresswerkError::PrintServer(format!(
-                "create documents directory {}: {e}",
-                documents_dir.display()
-            ))
-        })?;
-        info!(path = %documents_dir.display(), "document storage directory ready");
+        // A missing certificate only matters for IPPS, so do not fail the
+        // start over it.
+        match load_or_renew_certificate(
+            &self.data_dir.join(CERT_FILENAME),
+            &self.config.name,
+            self.key_storage.as_deref(),
+        ) {
+            Ok(cert) => self.certificate = Some(cert),
+            Err(e) => warn!(error = %e, "server certificate unavailable"),
+        }
 
-        let shutdown = Arc::clone(&self.shutdown_signal);
-        let connections = Arc::clone(&self.active_connections);
-        let port = self.port;
+        // The document store for persisting print data.
+        let documents = match &self.documents {
+            Some(documents) => Arc::clone(documents),
+            None => Arc::new(DocumentStore::open(&self.data_dir).map_err(|e| {
+                PresswerkError::PrintServer(format!(
+                    "open document store in {}: {e}",
+                    self.data_dir.display()
+                ))
+            })?),
+        };
 
-        let shared = Arc::new(SharedState {
-            job_queue,
-            active_connections: connections,
-            port,
-            next_ipp_job_id: Arc::new(AtomicU32::new(1)),
-            ipp_to_internal: Arc::new(Mutex::new(HashMap::new())),
-            data_dir: self.data_dir.clone(),
-        });
+        let shutdown = Arc::clone(&self.shutdown_signal);
+        let port = self.config.port;
+        let shared = Arc::new(self.shared_state(job_queue, documents));
 
         let handle = tokio::spawn(async move {
             Self::accept_loop(listener, shutdown, port, shared).await;
@@ -729,6 +1316,20 @@ pub async fn start(&mut self, job_queue: Arc<Mutex<JobQueue>>) -> Result<()> {
         Ok(())
     }
 
+    /// The state handed to every connection.
+    fn shared_state(
+        &self,
+        job_queue: Arc<Mutex<JobQueue>>,
+        documents: Arc<DocumentStore>,
+    ) -> SharedState {
+        SharedState {
+            job_queue,
+            active_connections: Arc::clone(&self.active_connections),
+            config: self.config.clone(),
+            documents,
+        }
+    }
+
     /// Gracefully stop the server.
     ///
     /// Signals the accept loop to exit and awaits its completion.  Existing
@@ -739,7 +1340,7 @@ pub async fn stop(&mut self) -> Result<()> {
             return Ok(());
         }
 
-        info!(port = self.port, "stopping IPP print server");
+        info!(port = self.config.port, "stopping IPP print server");
 
         // Unregister mDNS service.
         self.unregister_mdns();
@@ -753,7 +1354,7 @@ pub async fn stop(&mut self) -> Result<()> {
         }
 
         self.status = ServerStatus::Stopped;
-        info!(port = self.port, "IPP print server stopped");
+        info!(port = self.config.port, "IPP print server stopped");
         Ok(())
     }
 
@@ -775,7 +1376,7 @@ fn register_mdns(&mut self) {
             ("txtvers", "1"),
             ("qtotal", "1"),
             ("rp", "ipp/print"),
-            ("ty", PRINTER_NAME),
+            ("ty", self.config.name.as_str()),
             ("pdl", "application/pdf,image/jpeg,image/png,text/plain"),
             ("Color", "T"),
             ("Duplex", "T"),
@@ -784,14 +1385,14 @@ fn register_mdns(&mut self) {
 
         let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "presswerk".into());
 
-        let service_name = PRINTER_NAME.to_string();
+        let service_name = self.config.name.clone();
 
         match mdns_sd::ServiceInfo::new(
             IPP_SERVICE_TYPE,
             &service_name,
             &format!("{hostname}.local."),
             "", // empty = auto-detect IP
-            self.port,
+            self.config.port,
             &properties[..],
         ) {
             Ok(service_info) => {
@@ -801,7 +1402,7 @@ fn register_mdns(&mut self) {
                         info!(
                             service_type = IPP_SERVICE_TYPE,
                             name = %service_name,
-                            port = self.port,
+                            port = self.config.port,
                             "mDNS service registered"
                         );
                         self.mdns_fullname = Some(fullname);
@@ -860,10 +1461,16 @@ async fn accept_loop(
                 accept_result = listener.accept() => {
                     match accept_result {
                         Ok((stream, peer_addr)) => {
+                            let max_connections = shared.config.max_connections;
+                            if shared.active_connections.load(Ordering::Relaxed) >= max_connections {
+                                warn!(peer = %peer_addr, max_connections, "too many connections -- closing");
+                                drop(stream);
+                                continue;
+                            }
                             info!(peer = %peer_addr, "incoming IPP connection");
                             let state = Arc::clone(&shared);
+                            state.active_connections.fetch_add(1, Ordering::Relaxed);
                             tokio::spawn(async move {
-                                state.active_connections.fetch_add(1, Ordering::Relaxed);
                                 if let Err(e) = Self::handle_connection(stream, peer_addr, state.clone()).await {
                                     warn!(
                                         peer = %peer_addr,
@@ -885,62 +1492,40 @@ async fn accept_loop(
 
     /// Handle a single incoming TCP connection.
     ///
-    /// Reads the full request, strips HTTP framing if present, parses the
-    /// IPP binary payload, dispatches to the appropriate operation handler,
-    /// and writes back an IPP response wrapped in a minimal HTTP response.
+    /// Reads the request (see [`read_ipp_request`]), dispatches it to the
+    /// appropriate operation handler, and writes back an IPP response
+    /// wrapped in a minimal HTTP response.
     async fn handle_connection(
         mut stream: tokio::net::TcpStream,
         peer_addr: SocketAddr,
         state: Arc<SharedState>,
     ) -> Result<()> {
-        let mut buf = Vec::with_capacity(8192);
-
-        // Read up to MAX_REQUEST_BYTES.
-        let mut limited = (&mut stream).take(MAX_REQUEST_BYTES as u64);
-        let bytes_read = limited
-            .read_to_end(&mut buf)
-            .await
-            .map_err(|e| PresswerkError::PrintServer(format!("read from {peer_addr}: {e}")))?;
-
-        debug!(
-            peer = %peer_addr,
-            bytes = bytes_read,
-            "received IPP request data"
-        );
-
-        if bytes_read == 0 {
-            debug!(peer = %peer_addr, "empty request -- closing connection");
-            return Ok(());
-        }
+        let config = &state.config;
+        let received = tokio::time::timeout(
+            config.read_timeout,
+            read_ipp_request(
+                &mut stream,
+                &state.documents,
+                config.spool_threshold,
+                config.max_request_bytes,
+            ),
+        )
+        .await
+        .map_err(|_| {
+            PresswerkError::PrintServer(format!(
+                "no complete request within {}s",
+                config.read_timeout.as_secs()
+            ))
+        })??;
 
-        // Strip HTTP envelope if present.  Some IPP clients send raw IPP
-        // over TCP (especially in test environments), others wrap it in HTTP.
-        let ipp_body = match parse_http_envelope(&buf) {
-            Some(http_req) => {
-                debug!(
-                    peer = %peer_addr,
-                    body_offset = http_req.body_offset,
-                    content_length = ?http_req.content_length,
-                    "HTTP envelope detected"
-                );
-                &buf[http_req.body_offset..]
-            }
-            None => {
-                debug!(peer = %peer_addr, "no HTTP envelope -- treating as raw IPP");
-                &buf[..]
+        let ipp_request = match received {
+            ReceivedRequest::Request(request) => request,
+            ReceivedRequest::Empty => {
+                debug!(peer = %peer_addr, "empty request -- closing connection");
+                return Ok(());
             }
-        };
-
-        // Parse the IPP request.
-        let ipp_request = match parse_ipp_request(ipp_body) {
-            Ok(req) => req,
-            Err(e) => {
-                warn!(peer = %peer_addr, error = %e, "malformed IPP request");
-                let response = build_error_response(
-                    STATUS_CLIENT_ERROR_BAD_REQUEST,
-                    0, // no valid request-id
-                    &format!("Malformed IPP request: {e}"),
-                );
+            ReceivedRequest::Rejected(response) => {
+                debug!(peer = %peer_addr, "request rejected");
                 send_response(&mut stream, &response).await?;
                 return Ok(());
             }
@@ -952,7 +1537,7 @@ async fn handle_connection(
             operation_id = %format!("0x{:04X}", ipp_request.operation_id),
             request_id = ipp_request.request_id,
             groups = ipp_request.attribute_groups.len(),
-            doc_bytes = ipp_request.document_data.len(),
+            doc_bytes = ipp_request.document_len(),
             "parsed IPP request"
         );
 
@@ -982,6 +1567,7 @@ fn dispatch_operation(request: &IppRequest, peer_addr: SocketAddr, state: &Share
         OP_PRINT_JOB => handle_print_job(request, peer_addr, state),
         OP_VALIDATE_JOB => handle_validate_job(request),
         OP_CANCEL_JOB => handle_cancel_job(request, state),
+        OP_GET_JOB_ATTRIBUTES => handle_get_job_attributes(request, state),
         OP_GET_JOBS => handle_get_jobs(request, state),
         OP_GET_PRINTER_ATTRIBUTES => handle_get_printer_attributes(request, state),
         _ => {
@@ -1020,20 +1606,39 @@ fn handle_print_job(request: &IppRequest, peer_addr: SocketAddr, state: &SharedS
         .and_then(|g| g.get_string("document-format"))
         .unwrap_or_else(|| "application/octet-stream".into());
 
-    let document_type = mime_to_document_type(&document_format);
+    // Clients often send application/octet-stream or a wrong format, so the
+    // declared type is checked against the document's magic bytes.
+    let declared = mime_to_document_type(&document_format);
+    let document_type = match document_head(request) {
+        Ok(head) => resolve_document_type(declared, &head),
+        Err(e) => {
+            warn!(error = %e, "failed to read spooled document for type sniffing");
+            declared
+        }
+    };
+    if document_type != declared {
+        debug!(
+            declared = %document_format,
+            sniffed = ?document_type,
+            "document content overrides declared format"
+        );
+    }
 
-    // Compute SHA-256 hash of the document data.
-    let document_hash = if request.document_data.is_empty() {
-        "empty".into()
-    } else {
-        let mut hasher = Sha256::new();
-        hasher.update(&request.document_data);
-        hex::encode(hasher.finalize())
+    // SHA-256 hash of the document data; a spooled document was hashed as
+    // it was written.
+    let document_hash = match &request.spooled_document {
+        Some(spooled) => spooled.sha256.clone(),
+        None if request.document_data.is_empty() => "empty".into(),
+        None => {
+            let mut hasher = Sha256::new();
+            hasher.update(&request.document_data);
+            hex::encode(hasher.finalize())
+        }
     };
 
     // Create the internal print job.
     let ip = peer_addr.ip();
-    let job = PrintJob::new(
+    let mut job = PrintJob::new(
         JobSource::Network { remote_addr: ip },
         document_type,
         document_name.clone(),
@@ -1042,18 +1647,30 @@ fn handle_print_job(request: &IppRequest, peer_addr: SocketAddr, state: &SharedS
 
     let internal_job_id = job.id;
 
-    // Assign an IPP integer job-id.
-    let ipp_job_id = state.next_ipp_job_id.fetch_add(1, Ordering::Relaxed) as i32;
-
-    // Map IPP job-id to internal JobId.
-    if let Ok(mut map) = state.ipp_to_internal.lock() {
-        map.insert(ipp_job_id, internal_job_id);
-    }
-
-    // Insert into the job queue.
-    match state.job_queue.lock() {
-        Ok(queue) => {
-            if let Err(e) = queue.insert_job(&job) {
+    // Insert the job together with its document data and a persistent IPP
+    // integer job-id in one transaction.  Identical content is stored once;
+    // the job holds a reference so the blob outlives any other job that
+    // shares it.
+    let ipp_job_id = match state.job_queue.lock() {
+        Ok(queue) => match queue.insert_ipp_job_tx(&mut job, || {
+            let documents = &state.documents;
+            let stored = match &request.spooled_document {
+                Some(spooled) => Some(documents.put_file_for_job(
+                    &internal_job_id,
+                    &spooled.path,
+                    &spooled.sha256,
+                )?),
+                None if request.document_data.is_empty() => None,
+                None => Some(documents.put_for_job(&internal_job_id, &request.document_data)?),
+            };
+            Ok(move || {
+                if let Some(hash) = stored {
+                    let _ = documents.release(&hash, &internal_job_id);
+                }
+            })
+        }) {
+            Ok(id) => id,
+            Err(e) => {
                 error!(error = %e, "failed to insert job into queue");
                 return build_error_response(
                     STATUS_SERVER_ERROR_INTERNAL,
@@ -1061,7 +1678,7 @@ fn handle_print_job(request: &IppRequest, peer_addr: SocketAddr, state: &SharedS
                     &format!("Failed to enqueue job: {e}"),
                 );
             }
-        }
+        },
         Err(e) => {
             error!(error = %e, "job queue lock poisoned");
             return build_error_response(
@@ -1070,60 +1687,18 @@ fn handle_print_job(request: &IppRequest, peer_addr: SocketAddr, state: &SharedS
                 "Internal server error: queue lock poisoned",
             );
         }
-    }
-
-    // Persist document data to disk using content-addressed storage.
-    // If the file already exists we skip the write -- same hash means
-    // identical content, so the existing file is already correct.
-    if !request.document_data.is_empty() {
-        let doc_path = state
-            .data_dir
-            .join("documents")
-            .join(format!("{document_hash}.dat"));
-
-        if doc_path.exists() {
-            info!(
-                hash = %document_hash,
-                path = %doc_path.display(),
-                "document already on disk (content-addressed); skipping write"
-            );
-        } else {
-            match std::fs::write(&doc_path, &request.document_data) {
-                Ok(()) => {
-                    info!(
-                        hash = %document_hash,
-                        path = %doc_path.display(),
-                        bytes = request.document_data.len(),
-                        "document data persisted to disk"
-                    );
-                }
-                Err(e) => {
-                    error!(
-                        hash = %document_hash,
-                        path = %doc_path.display(),
-                        error = %e,
-                        "failed to persist document data to disk"
-                    );
-                    return build_error_response(
-                        STATUS_SERVER_ERROR_INTERNAL,
-                        request.request_id,
-                        &format!("Failed to store document data: {e}"),
-                    );
-                }
-            }
-        }
-    }
+    };
 
     info!(
         ipp_job_id = ipp_job_id,
         internal_id = %internal_job_id,
         doc_name = %document_name,
-        doc_bytes = request.document_data.len(),
+        doc_bytes = request.document_len(),
         "Print-Job accepted"
     );
 
     // Build a successful response.
-    let printer_uri = format!("ipp://localhost:{}/ipp/print", state.port);
+    let printer_uri = format!("ipp://localhost:{}/ipp/print", state.config.port);
 
     let mut resp = IppResponseBuilder::new(STATUS_OK, request.request_id);
     resp.begin_group(TAG_OPERATION_ATTRIBUTES)
@@ -1177,10 +1752,11 @@ fn handle_cancel_job(request: &IppRequest, state: &SharedState) -> Vec<u8> {
 
     // Look up the internal JobId.
     let internal_id = state
-        .ipp_to_internal
+        .job_queue
         .lock()
         .ok()
-        .and_then(|map| map.get(&ipp_job_id).copied());
+        .and_then(|queue| queue.get_job_by_ipp_id(ipp_job_id).ok().flatten())
+        .map(|job| job.id);
 
     let internal_id = match internal_id {
         Some(id) => id,
@@ -1253,14 +1829,7 @@ fn handle_get_jobs(request: &IppRequest, state: &SharedState) -> Vec<u8> {
         }
     };
 
-    // We need the reverse mapping from internal JobId to IPP integer id.
-    let id_map: HashMap<JobId, i32> = state
-        .ipp_to_internal
-        .lock()
-        .map(|map| map.iter().map(|(&k, &v)| (v, k)).collect())
-        .unwrap_or_default();
-
-    let printer_uri = format!("ipp://localhost:{}/ipp/print", state.port);
+    let printer_uri = format!("ipp://localhost:{}/ipp/print", state.config.port);
 
     let mut resp = IppResponseBuilder::new(STATUS_OK, request.request_id);
     resp.begin_group(TAG_OPERATION_ATTRIBUTES)
@@ -1269,15 +1838,7 @@ fn handle_get_jobs(request: &IppRequest, state: &SharedState) -> Vec<u8> {
         .text("status-message", "successful-ok");
 
     for job in &jobs {
-        let ipp_id = id_map.get(&job.id).copied().unwrap_or(0);
-        let job_state = job_status_to_ipp_state(job.status);
-
-        resp.begin_group(TAG_JOB_ATTRIBUTES)
-            .integer("job-id", ipp_id)
-            .uri("job-uri", &format!("{printer_uri}/jobs/{ipp_id}"))
-            .name_attr("job-name", &job.document_name)
-            .enum_attr("job-state", job_state)
-            .keyword("job-state-reasons", job_state_reason(job.status));
+        write_job_attributes(&mut resp, job, &printer_uri);
     }
 
     debug!(count = jobs.len(), "Get-Jobs: returning job list");
@@ -1285,11 +1846,87 @@ fn handle_get_jobs(request: &IppRequest, state: &SharedState) -> Vec<u8> {
     resp.build()
 }
 
+/// Handle a Get-Job-Attributes (0x0009) request.
+///
+/// Looks the job up by the IPP integer job-id the client was given.
+fn handle_get_job_attributes(request: &IppRequest, state: &SharedState) -> Vec<u8> {
+    let ipp_job_id = match request
+        .operation_attributes()
+        .and_then(|g| g.get_integer("job-id"))
+    {
+        Some(id) => id,
+        None => {
+            warn!("Get-Job-Attributes: missing job-id attribute");
+            return build_error_response(
+                STATUS_CLIENT_ERROR_BAD_REQUEST,
+                request.request_id,
+                "Missing required job-id attribute",
+            );
+        }
+    };
+
+    let job = match state.job_queue.lock() {
+        Ok(queue) => match queue.get_job_by_ipp_id(ipp_job_id) {
+            Ok(Some(job)) => job,
+            Ok(None) => {
+                warn!(ipp_job_id, "Get-Job-Attributes: job not found");
+                return build_error_response(
+                    STATUS_CLIENT_ERROR_NOT_FOUND,
+                    request.request_id,
+                    &format!("Job {ipp_job_id} not found"),
+                );
+            }
+            Err(e) => {
+                error!(error = %e, "Get-Job-Attributes: failed to retrieve job");
+                return build_error_response(
+                    STATUS_SERVER_ERROR_INTERNAL,
+                    request.request_id,
+                    &format!("Failed to retrieve job: {e}"),
+                );
+            }
+        },
+        Err(e) => {
+            error!(error = %e, "job queue lock poisoned");
+            return build_error_response(
+                STATUS_SERVER_ERROR_INTERNAL,
+                request.request_id,
+                "Internal server error: queue lock poisoned",
+            );
+        }
+    };
+
+    let printer_uri = format!("ipp://localhost:{}/ipp/print", state.config.port);
+
+    let mut resp = IppResponseBuilder::new(STATUS_OK, request.request_id);
+    resp.begin_group(TAG_OPERATION_ATTRIBUTES)
+        .charset("attributes-charset", "utf-8")
+        .natural_language("attributes-natural-language", "en")
+        .text("status-message", "successful-ok");
+    write_job_attributes(&mut resp, &job, &printer_uri);
+
+    debug!(ipp_job_id, internal_id = %job.id, "Get-Job-Attributes: returning job");
+
+    resp.build()
+}
+
+/// Append a job-attributes group describing `job`.  Jobs that did not
+/// arrive over IPP report job-id 0.
+fn write_job_attributes(resp: &mut IppResponseBuilder, job: &PrintJob, printer_uri: &str) {
+    let ipp_id = job.ipp_job_id.unwrap_or(0);
+
+    resp.begin_group(TAG_JOB_ATTRIBUTES)
+        .integer("job-id", ipp_id)
+        .uri("job-uri", &format!("{printer_uri}/jobs/{ipp_id}"))
+        .name_attr("job-name", &job.document_name)
+        .enum_attr("job-state", job_status_to_ipp_state(job.status))
+        .keyword("job-state-reasons", job_state_reason(job.status));
+}
+
 /// Handle a Get-Printer-Attributes (0x000B) request.
 ///
 /// Returns the printer's capabilities and current state.
 fn handle_get_printer_attributes(request: &IppRequest, state: &SharedState) -> Vec<u8> {
-    let printer_uri = format!("ipp://localhost:{}/ipp/print", state.port);
+    let printer_uri = format!("ipp://localhost:{}/ipp/print", state.config.port);
 
     let mut resp = IppResponseBuilder::new(STATUS_OK, request.request_id);
     resp.begin_group(TAG_OPERATION_ATTRIBUTES)
@@ -1300,7 +1937,7 @@ fn handle_get_printer_attributes(request: &IppRequest, state: &SharedState) -> V
     resp.begin_group(TAG_PRINTER_ATTRIBUTES)
         // Identification
         .uri("printer-uri-supported", &printer_uri)
-        .name_attr("printer-name", PRINTER_NAME)
+        .name_attr("printer-name", &state.config.name)
         .text("printer-info", "Presswerk mobile print router")
         .text("printer-make-and-model", "Presswerk Virtual Printer 1.0")
         .text("printer-location", "Mobile Device")
@@ -1312,6 +1949,7 @@ fn handle_get_printer_attributes(request: &IppRequest, state: &SharedState) -> V
         .keyword("operations-supported", "Print-Job")
         .keyword_additional("Validate-Job")
         .keyword_additional("Cancel-Job")
+        .keyword_additional("Get-Job-Attributes")
         .keyword_additional("Get-Jobs")
         .keyword_additional("Get-Printer-Attributes")
         // Supported document formats
@@ -1396,6 +2034,26 @@ async fn send_response(stream: &mut tokio::net::TcpStream, ipp_body: &[u8]) -> R
     Ok(())
 }
 
+/// The first [`SNIFF_LEN`] bytes of the request's document, read from the
+/// spool file when the document was spooled to disk.
+fn document_head(request: &IppRequest) -> std::io::Result<Cow<'_, [u8]>> {
+    match &request.spooled_document {
+        Some(spooled) => {
+            use std::io::Read;
+
+            let mut head = Vec::with_capacity(SNIFF_LEN);
+            std::fs::File::open(&spooled.path)?
+                .take(SNIFF_LEN as u64)
+                .read_to_end(&mut head)?;
+            Ok(Cow::Owned(head))
+        }
+        None => {
+            let len = request.document_data.len().min(SNIFF_LEN);
+            Ok(Cow::Borrowed(&request.document_data[..len]))
+        }
+    }
+}
+
 /// Map a MIME type string to a `DocumentType`.
 fn mime_to_document_type(mime: &str) -> DocumentType {
     match mime {
@@ -1690,6 +2348,79 @@ fn parse_http_envelope_returns_none_for_raw_ipp() {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn parse_http_envelope_detects_chunked_body() {
+        let http = b"POST /ipp/print HTTP/1.1\r\n\
+                     Content-Type: application/ipp\r\n\
+                     Transfer-Encoding: gzip, Chunked\r\n\
+                     \r\n";
+        let req = parse_http_envelope(http).unwrap();
+        assert!(req.chunked);
+        assert_eq!(req.body_offset, http.len());
+
+        let plain = b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc";
+        assert!(!parse_http_envelope(plain).unwrap().chunked);
+    }
+
+    #[test]
+    fn two_chunk_ipp_body_reassembles() {
+        let ipp = build_test_ipp_request(
+            OP_PRINT_JOB,
+            7,
+            &[(VALUE_TAG_NAME, "job-name", b"chunked.pdf" as &[u8])],
+            b"%PDF-1.4 fake document",
+        );
+        let (first, second) = ipp.split_at(20);
+
+        let mut http = b"POST /ipp/print HTTP/1.1\r\n\
+                         Content-Type: application/ipp\r\n\
+                         Transfer-Encoding: chunked\r\n\
+                         \r\n"
+            .to_vec();
+        http.extend_from_slice(format!("{:x}\r\n", first.len()).as_bytes());
+        http.extend_from_slice(first);
+        http.extend_from_slice(format!("\r\n{:X};name=value\r\n", second.len()).as_bytes());
+        http.extend_from_slice(second);
+        http.extend_from_slice(b"\r\n0\r\n\r\n");
+
+        let envelope = parse_http_envelope(&http).unwrap();
+        assert!(envelope.chunked);
+        let body = dechunk(&http[envelope.body_offset..]).unwrap();
+        assert_eq!(body, ipp);
+
+        let request = parse_ipp_request(&body).unwrap();
+        assert_eq!(request.operation_id, OP_PRINT_JOB);
+        assert_eq!(request.request_id, 7);
+        assert_eq!(request.document_data, b"%PDF-1.4 fake document");
+    }
+
+    #[test]
+    fn dechunk_skips_trailer_fields() {
+        assert_eq!(
+            dechunk(b"3\r\nabc\r\n0\r\nExpires: never\r\n\r\n").unwrap(),
+            b"abc"
+        );
+    }
+
+    #[test]
+    fn dechunk_rejects_malformed_framing() {
+        for body in [
+            &b"zz\r\nabc\r\n0\r\n\r\n"[..], // size not hex
+            b"\r\nabc\r\n0\r\n\r\n",        // size missing
+            b"5\r\nabc\r\n0\r\n\r\n",       // chunk shorter than its size
+            b"3\r\nabcd\r\n0\r\n\r\n",      // chunk longer than its size
+            b"3\r\nabc\r\n",                // no terminating chunk
+            b"3\r\nabc\r\n0\r\n",           // no blank line after it
+            b"ffffffffffffffffff\r\n",      // size overflows
+        ] {
+            assert!(
+                dechunk(body).is_err(),
+                "{:?}",
+                String::from_utf8_lossy(body)
+            );
+        }
+    }
+
     // -- MIME type mapping --------------------------------------------------
 
     #[test]
@@ -1749,15 +2480,16 @@ fn make_test_data_dir() -> tempfile::TempDir {
 
     fn make_shared_state_with_dir(data_dir: &std::path::Path) -> SharedState {
         let queue = JobQueue::open_in_memory().expect("open in-memory queue");
-        let documents_dir = data_dir.join("documents");
-        std::fs::create_dir_all(&documents_dir).expect("create documents dir");
+        make_shared_state_with_queue(data_dir, queue)
+    }
+
+    fn make_shared_state_with_queue(data_dir: &std::path::Path, queue: JobQueue) -> SharedState {
+        let documents = DocumentStore::open(data_dir).expect("open document store");
         SharedState {
             job_queue: Arc::new(Mutex::new(queue)),
             active_connections: Arc::new(AtomicU32::new(0)),
-            port: 9100,
-            next_ipp_job_id: Arc::new(AtomicU32::new(1)),
-            ipp_to_internal: Arc::new(Mutex::new(HashMap::new())),
-            data_dir: data_dir.to_path_buf(),
+            config: ServerConfig::default().with_port(9100),
+            documents: Arc::new(documents),
         }
     }
 
@@ -1795,7 +2527,48 @@ fn dispatch_get_printer_attributes_returns_ok() {
 
         assert_eq!(
             printer_group.get_string("printer-name").as_deref(),
-            Some(PRINTER_NAME)
+            Some(ServerConfig::DEFAULT_NAME)
+        );
+    }
+
+    #[test]
+    fn with_config_applies_each_field() {
+        let tmp = make_test_data_dir();
+        let config = ServerConfig::default()
+            .with_port(8631)
+            .with_name("Office Phone")
+            .with_data_dir(tmp.path())
+            .with_max_connections(2)
+            .with_read_timeout(std::time::Duration::from_secs(5))
+            .with_request_limits(1024 * 1024, 4096);
+        let server = IppServer::with_config(config.clone());
+
+        assert_eq!(server.port(), 8631);
+        assert_eq!(server.config(), &config);
+        assert!(server.document_path("abc").starts_with(tmp.path()));
+
+        let queue = JobQueue::open_in_memory().expect("in-memory queue");
+        let documents = DocumentStore::open(tmp.path()).expect("open document store");
+        let state = server.shared_state(Arc::new(Mutex::new(queue)), Arc::new(documents));
+        assert_eq!(state.config, config);
+
+        let data = build_test_ipp_request(OP_GET_PRINTER_ATTRIBUTES, 51, &[], &[]);
+        let req = parse_ipp_request(&data).unwrap();
+        let peer: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        let response = dispatch_operation(&req, peer, &state);
+        let parsed = parse_ipp_request(&response).unwrap();
+        let printer_group = parsed
+            .attribute_groups
+            .iter()
+            .find(|g| g.delimiter == TAG_PRINTER_ATTRIBUTES)
+            .expect("should have printer attributes group");
+        assert_eq!(
+            printer_group.get_string("printer-name").as_deref(),
+            Some("Office Phone")
+        );
+        assert_eq!(
+            printer_group.get_string("printer-uri-supported").as_deref(),
+            Some("ipp://localhost:8631/ipp/print")
         );
     }
 
@@ -1901,6 +2674,98 @@ fn dispatch_cancel_nonexistent_job_returns_not_found() {
         assert_eq!(parsed.operation_id, STATUS_CLIENT_ERROR_NOT_FOUND);
     }
 
+    #[test]
+    fn ipp_job_id_resolves_after_restart() {
+        let tmp = make_test_data_dir();
+        let db_path = tmp.path().join("jobs.db");
+        let peer: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        let restart = || {
+            let queue = JobQueue::open(&db_path).expect("open queue");
+            make_shared_state_with_queue(tmp.path(), queue)
+        };
+        let print = |state: &SharedState| {
+            let data = build_test_ipp_request(OP_PRINT_JOB, 70, &[], b"data");
+            let response = dispatch_operation(&parse_ipp_request(&data).unwrap(), peer, state);
+            parse_ipp_request(&response)
+                .unwrap()
+                .attribute_groups
+                .iter()
+                .find(|g| g.delimiter == TAG_JOB_ATTRIBUTES)
+                .and_then(|g| g.get_integer("job-id"))
+                .expect("job-id")
+        };
+
+        let state = restart();
+        let ipp_job_id = print(&state);
+        let internal_id = state.job_queue.lock().unwrap().get_all_jobs().unwrap()[0].id;
+        drop(state);
+
+        // The old id still maps to the same job, and new jobs do not reuse it.
+        let state = restart();
+        let resolved = state
+            .job_queue
+            .lock()
+            .unwrap()
+            .job_id_for_ipp_id(ipp_job_id)
+            .unwrap();
+        assert_eq!(resolved, Some(internal_id));
+        assert!(print(&state) > ipp_job_id);
+
+        let job_id_bytes = ipp_job_id.to_be_bytes();
+        let attrs = vec![(VALUE_TAG_INTEGER, "job-id", &job_id_bytes[..])];
+        let data = build_test_ipp_request(OP_CANCEL_JOB, 71, &attrs, &[]);
+        let response = dispatch_operation(&parse_ipp_request(&data).unwrap(), peer, &state);
+        let parsed = parse_ipp_request(&response).unwrap();
+        assert_eq!(parsed.operation_id, STATUS_OK);
+    }
+
+    #[test]
+    fn get_job_attributes_finds_job_by_ipp_id_after_reload() {
+        let tmp = make_test_data_dir();
+        let db_path = tmp.path().join("jobs.db");
+        let peer: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+        let state = make_shared_state_with_queue(tmp.path(), JobQueue::open(&db_path).unwrap());
+        let attrs = vec![(VALUE_TAG_NAME, "job-name", b"Report" as &[u8])];
+        let data = build_test_ipp_request(OP_PRINT_JOB, 80, &attrs, b"data");
+        let response = dispatch_operation(&parse_ipp_request(&data).unwrap(), peer, &state);
+        let parsed = parse_ipp_request(&response).unwrap();
+        let ipp_job_id = parsed
+            .attribute_groups
+            .iter()
+            .find(|g| g.delimiter == TAG_JOB_ATTRIBUTES)
+            .and_then(|g| g.get_integer("job-id"))
+            .expect("job-id");
+        drop(state);
+
+        let state = make_shared_state_with_queue(tmp.path(), JobQueue::open(&db_path).unwrap());
+        let stored = state.job_queue.lock().unwrap().get_all_jobs().unwrap();
+        assert_eq!(stored[0].ipp_job_id, Some(ipp_job_id));
+
+        let job_id_bytes = ipp_job_id.to_be_bytes();
+        let attrs = vec![(VALUE_TAG_INTEGER, "job-id", &job_id_bytes[..])];
+        let data = build_test_ipp_request(OP_GET_JOB_ATTRIBUTES, 81, &attrs, &[]);
+        let response = dispatch_operation(&parse_ipp_request(&data).unwrap(), peer, &state);
+        let parsed = parse_ipp_request(&response).unwrap();
+
+        assert_eq!(parsed.operation_id, STATUS_OK);
+        let job_group = parsed
+            .attribute_groups
+            .iter()
+            .find(|g| g.delimiter == TAG_JOB_ATTRIBUTES)
+            .expect("should have job attributes group");
+        assert_eq!(job_group.get_integer("job-id"), Some(ipp_job_id));
+        assert_eq!(job_group.get_string("job-name").as_deref(), Some("Report"));
+
+        // An unknown id is reported as not found.
+        let unknown = (ipp_job_id + 1).to_be_bytes();
+        let attrs = vec![(VALUE_TAG_INTEGER, "job-id", &unknown[..])];
+        let data = build_test_ipp_request(OP_GET_JOB_ATTRIBUTES, 82, &attrs, &[]);
+        let response = dispatch_operation(&parse_ipp_request(&data).unwrap(), peer, &state);
+        let parsed = parse_ipp_request(&response).unwrap();
+        assert_eq!(parsed.operation_id, STATUS_CLIENT_ERROR_NOT_FOUND);
+    }
+
     #[test]
     fn dispatch_get_jobs_returns_empty_list() {
         let state = make_shared_state();
@@ -2045,6 +2910,53 @@ fn print_job_persists_document_to_disk() {
         assert_eq!(stored, doc, "stored content should match original");
     }
 
+    #[test]
+    fn print_job_sniffs_document_type_from_content() {
+        let state = make_shared_state();
+        let peer: SocketAddr = "10.0.0.1:9999".parse().unwrap();
+        let cases: [(&str, &[u8], &[u8], DocumentType); 3] = [
+            (
+                "pdf",
+                b"application/octet-stream",
+                b"%PDF-1.7",
+                DocumentType::Pdf,
+            ),
+            (
+                "jpeg",
+                b"application/pdf",
+                b"\xFF\xD8\xFF\xE0",
+                DocumentType::Jpeg,
+            ),
+            // Plain text never overrides a specific declared format.
+            (
+                "text",
+                b"application/pdf",
+                b"just some words",
+                DocumentType::Pdf,
+            ),
+        ];
+
+        for (i, (name, format, doc, _)) in cases.iter().enumerate() {
+            let attrs = vec![
+                (VALUE_TAG_NAME, "job-name", name.as_bytes()),
+                (VALUE_TAG_KEYWORD, "document-format", *format),
+            ];
+            let data = build_test_ipp_request(OP_PRINT_JOB, 400 + i as u32, &attrs, doc);
+            let req = parse_ipp_request(&data).unwrap();
+            let parsed = parse_ipp_request(&dispatch_operation(&req, peer, &state)).unwrap();
+            assert_eq!(parsed.operation_id, STATUS_OK);
+        }
+
+        let jobs = state.job_queue.lock().unwrap().get_all_jobs().unwrap();
+        for (name, _, _, expected) in cases {
+            let job = jobs
+                .iter()
+                .find(|j| j.document_name == name)
+                .expect("job enqueued");
+            assert_eq!(job.document_type, expected, "{name}");
+        }
+    }
+
     #[test]
     fn print_job_skips_write_for_duplicate_hash() {
         let tmp = make_test_data_dir();
@@ -2169,4 +3081,221 @@ fn retrieve_document_roundtrip_via_print_job() {
             .expect("should retrieve document");
         assert_eq!(retrieved, doc, "retrieved content must match original");
     }
+
+    // -- Large requests -----------------------------------------------------
+
+    /// Deterministic document bytes that are not all the same.
+    fn test_document(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn spool_files(data_dir: &std::path::Path) -> Vec<PathBuf> {
+        std::fs::read_dir(data_dir.join("documents"))
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "tmp"))
+            .collect()
+    }
+
+    fn http_post(body: &[u8]) -> Vec<u8> {
+        let mut http = format!(
+            "POST /ipp/print HTTP/1.1\r\n\
+             Content-Type: application/ipp\r\n\
+             Content-Length: {}\r\n\
+             \r\n",
+            body.len()
+        )
+        .into_bytes();
+        http.extend_from_slice(body);
+        http
+    }
+
+    #[tokio::test]
+    async fn body_past_spool_threshold_is_streamed_to_disk() {
+        let tmp = make_test_data_dir();
+        let state = make_shared_state_with_dir(tmp.path());
+        let limits = ServerConfig::default();
+        let doc = test_document(limits.spool_threshold + 1024 * 1024);
+        let attrs = [(VALUE_TAG_NAME, "job-name", b"Big scan" as &[u8])];
+        let http = http_post(&build_test_ipp_request(OP_PRINT_JOB, 600, &attrs, &doc));
+
+        let received = read_ipp_request(
+            &mut &http[..],
+            &state.documents,
+            limits.spool_threshold,
+            limits.max_request_bytes,
+        )
+        .await
+        .unwrap();
+        let ReceivedRequest::Request(request) = received else {
+            panic!("expected a request");
+        };
+        assert_eq!(request.request_id, 600);
+        assert!(request.document_data.is_empty());
+        let spooled = request.spooled_document.as_ref().expect("spooled document");
+        assert_eq!(request.document_len(), doc.len() as u64);
+        assert_eq!(spooled.sha256, hex::encode(Sha256::digest(&doc)));
+        assert_eq!(std::fs::read(&spooled.path).unwrap(), doc);
+
+        let peer: SocketAddr = "10.0.0.1:9999".parse().unwrap();
+        let response = parse_ipp_request(&dispatch_operation(&request, peer, &state)).unwrap();
+        assert_eq!(response.operation_id, STATUS_OK);
+        assert_eq!(state.documents.get(&spooled.sha256).unwrap(), doc);
+        assert!(spool_files(tmp.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn chunked_body_is_spooled_as_it_arrives() {
+        let tmp = make_test_data_dir();
+        let state = make_shared_state_with_dir(tmp.path());
+        let doc = test_document(5000);
+        let ipp = build_test_ipp_request(OP_PRINT_JOB, 601, &[], &doc);
+
+        let mut http = b"POST /ipp/print HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        for chunk in ipp.chunks(700) {
+            http.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+            http.extend_from_slice(chunk);
+            http.extend_from_slice(b"\r\n");
+        }
+        http.extend_from_slice(b"0\r\n\r\n");
+
+        let received = read_ipp_request(&mut &http[..], &state.documents, 256, 1024 * 1024)
+            .await
+            .unwrap();
+        let ReceivedRequest::Request(request) = received else {
+            panic!("expected a request");
+        };
+        let spooled = request.spooled_document.as_ref().expect("spooled document");
+        assert_eq!(std::fs::read(&spooled.path).unwrap(), doc);
+
+        // Cut off before the last chunk: refused, and nothing left behind.
+        let truncated = &http[..http.len() - 5];
+        let received = read_ipp_request(&mut &truncated[..], &state.documents, 256, 1024 * 1024)
+            .await
+            .unwrap();
+        assert!(matches!(received, ReceivedRequest::Rejected(_)));
+        drop(request);
+        assert!(spool_files(tmp.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn oversized_request_is_refused_not_truncated() {
+        let tmp = make_test_data_dir();
+        let state = make_shared_state_with_dir(tmp.path());
+        let ipp = build_test_ipp_request(OP_PRINT_JOB, 602, &[], &test_document(8192));
+
+        // Raw IPP without a length: refused once the limit is passed.
+        let received = read_ipp_request(&mut &ipp[..], &state.documents, 256, 4096)
+            .await
+            .unwrap();
+        let ReceivedRequest::Rejected(response) = received else {
+            panic!("expected a rejection");
+        };
+        let response = parse_ipp_request(&response).unwrap();
+        assert_eq!(response.operation_id, STATUS_CLIENT_ERROR_REQUEST_ENTITY);
+        assert_eq!(response.request_id, 602);
+        assert!(spool_files(tmp.path()).is_empty());
+
+        // An announced Content-Length over the limit is refused up front.
+        let http = http_post(&ipp);
+        let received = read_ipp_request(&mut &http[..1024], &state.documents, 256, 4096)
+            .await
+            .unwrap();
+        let ReceivedRequest::Rejected(response) = received else {
+            panic!("expected a rejection");
+        };
+        assert_eq!(
+            parse_ipp_request(&response).unwrap().operation_id,
+            STATUS_CLIENT_ERROR_REQUEST_ENTITY
+        );
+    }
+
+    /// HTTP/1.1 clients keep the connection open for the response, so the
+    /// request ends where its framing says, not at end of stream.
+    #[tokio::test]
+    async fn request_ends_without_the_client_closing() {
+        let tmp = make_test_data_dir();
+        let state = make_shared_state_with_dir(tmp.path());
+        let ipp = build_test_ipp_request(OP_PRINT_JOB, 603, &[], &test_document(3000));
+        let mut chunked =
+            b"POST /ipp/print HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        chunked.extend_from_slice(format!("{:x}\r\n", ipp.len()).as_bytes());
+        chunked.extend_from_slice(&ipp);
+        chunked.extend_from_slice(b"\r\n0\r\n\r\n");
+
+        for (spool_threshold, http) in [
+            (1024 * 1024, http_post(&ipp)),
+            (1024 * 1024, chunked.clone()),
+            (256, http_post(&ipp)),
+            (256, chunked),
+        ] {
+            let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+            client.write_all(&http).await.unwrap();
+
+            let received = tokio::time::timeout(
+                std::time::Duration::from_secs(5),
+                read_ipp_request(&mut server, &state.documents, spool_threshold, 1024 * 1024),
+            )
+            .await
+            .expect("request read without waiting for end of stream")
+            .unwrap();
+            let ReceivedRequest::Request(request) = received else {
+                panic!("expected a request");
+            };
+            assert_eq!(request.request_id, 603);
+            assert_eq!(request.document_len(), 3000);
+            drop(client);
+        }
+    }
+
+    // -- Certificate --------------------------------------------------------
+
+    #[test]
+    fn certificate_near_expiry_is_renewed_on_start() {
+        let tmp = make_test_data_dir();
+        let path = tmp.path().join(CERT_FILENAME);
+
+        let first =
+            load_or_renew_certificate(&path, ServerConfig::DEFAULT_NAME, None).expect("create");
+        assert!(path.exists());
+        let again =
+            load_or_renew_certificate(&path, ServerConfig::DEFAULT_NAME, None).expect("reload");
+        assert_eq!(again.private_key_pkcs8_der(), first.private_key_pkcs8_der());
+
+        let sans = vec!["printer.local".to_string()];
+        SelfSignedCert::generate_for("Short Lived", &sans, chrono::Duration::days(10))
+            .unwrap()
+            .save(&path, None)
+            .unwrap();
+        let renewed =
+            load_or_renew_certificate(&path, ServerConfig::DEFAULT_NAME, None).expect("renew");
+        assert_eq!(renewed.subject(), "Short Lived");
+        assert_eq!(renewed.subject_alt_names(), sans.as_slice());
+        assert!(!renewed.is_expiring_within(chrono::Duration::days(CERT_RENEWAL_DAYS)));
+        assert_eq!(
+            SelfSignedCert::load(&path, None).unwrap().expires_at(),
+            renewed.expires_at()
+        );
+    }
+
+    #[test]
+    fn clear_certificate_key_is_encrypted_once_storage_is_given() {
+        let tmp = make_test_data_dir();
+        let path = tmp.path().join(CERT_FILENAME);
+        let storage = EncryptedStorage::new("server-key");
+
+        let first =
+            load_or_renew_certificate(&path, ServerConfig::DEFAULT_NAME, None).expect("create");
+        let again = load_or_renew_certificate(&path, ServerConfig::DEFAULT_NAME, Some(&storage))
+            .expect("reload");
+        assert_eq!(again.private_key_pkcs8_der(), first.private_key_pkcs8_der());
+
+        let reloaded = SelfSignedCert::load(&path, Some(&storage)).unwrap();
+        assert!(reloaded.is_key_encrypted());
+        assert_eq!(
+            reloaded.private_key_pkcs8_der(),
+            first.private_key_pkcs8_der()
+        );
+    }
 }
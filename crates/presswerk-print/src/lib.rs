@@ -5,24 +5,39 @@
 // job queue.  This crate bridges between the core domain types defined in
 // `presswerk-core` and the actual network printing infrastructure.
 
+pub mod address;
+mod archive;
 pub mod capabilities;
 pub mod diagnostics;
 pub mod discovery;
+pub mod document_store;
 pub mod health;
 pub mod ipp_client;
 pub mod ipp_server;
+pub mod lpd_server;
 pub mod lpr_client;
+pub mod printer_cache;
 pub mod protocol;
 pub mod queue;
 pub mod raw_client;
+pub mod readiness;
 pub mod resilience;
 pub mod retry;
 pub mod revival;
+pub mod scan_session;
+pub mod throttle;
 
-pub use capabilities::PrinterCapabilities;
-pub use discovery::PrinterDiscovery;
-pub use health::HealthTracker;
-pub use ipp_client::IppClient;
+pub use capabilities::{PrintWarning, PrinterCapabilities, VendorOption};
+pub use discovery::{PrinterDiscovery, ReadyPrinter};
+pub use document_store::DocumentStore;
+pub use health::{HealthSummary, HealthTracker, PollInterval, Reliability};
+pub use ipp_client::{IppClient, ValidationOutcome};
 pub use ipp_server::IppServer;
+pub use lpd_server::LpdServer;
+pub use lpr_client::{LprClient, LprJobOptions};
+pub use printer_cache::PrinterCache;
 pub use queue::JobQueue;
+pub use raw_client::{PjlStatus, RawClient};
 pub use retry::RetryConfig;
+pub use revival::Revival;
+pub use scan_session::ScanSession;
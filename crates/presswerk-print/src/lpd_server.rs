@@ -0,0 +1,890 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Embedded LPD print server (RFC 1179) -- the receiving end of `lpr_client`.
+//
+// Legacy hosts that only speak LPR send to port 515.  Received jobs go into
+// the same `JobQueue` and `DocumentStore` as the IPP server's, so the user
+// reviews and forwards them the same way.
+//
+// # Protocol implementation
+//
+// Only the "receive a printer job" daemon command (0x02) is accepted.  Within
+// it the client sends any number of subcommands:
+//
+//   - 0x01 `LF`                    abort: forget the files received so far
+//   - 0x02 count SP name `LF`      control file of `count` bytes
+//   - 0x03 count SP name `LF`      data file of `count` bytes
+//
+// The server acknowledges each subcommand line with a zero byte, and each
+// file (which the client ends with a zero byte of its own) with another.
+// The job is complete when the client closes the connection; each data file
+// named by a print command in the control file then becomes one `PrintJob`.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::{
+    DocumentType, JobId, JobSource, PrintJob, ServerStatus, resolve_document_type,
+};
+
+use crate::document_store::DocumentStore;
+use crate::lpr_client::LPR_PORT;
+use crate::queue::JobQueue;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Maximum size of a single data file, and of all of a job's data files
+/// together.
+const MAX_FILE_BYTES: usize = 64 * 1024 * 1024; // 64 MiB
+
+/// Maximum size of a control file; real ones are a few hundred bytes.
+const MAX_CONTROL_BYTES: usize = 64 * 1024;
+
+/// Connections served at once; more are closed on accept.
+const MAX_CONNECTIONS: u32 = 8;
+
+/// Maximum length of a command line (daemon command or subcommand).
+const MAX_LINE_BYTES: u64 = 1024;
+
+/// A connection that takes longer than this to deliver its job is dropped.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Daemon command: receive a printer job.
+const CMD_RECEIVE_JOB: u8 = 0x02;
+
+/// Receive-job subcommand: abort the job.
+const SUBCMD_ABORT: u8 = 0x01;
+
+/// Receive-job subcommand: control file follows.
+const SUBCMD_CONTROL_FILE: u8 = 0x02;
+
+/// Receive-job subcommand: data file follows.
+const SUBCMD_DATA_FILE: u8 = 0x03;
+
+/// Positive acknowledgement.
+const ACK: u8 = 0x00;
+
+/// Negative acknowledgement.
+const NACK: u8 = 0x01;
+
+// ---------------------------------------------------------------------------
+// Control file
+// ---------------------------------------------------------------------------
+
+/// A data file the control file asks to print.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintFile {
+    /// The print command letter: `l` (literal), `f` (formatted text),
+    /// `o` (PostScript), `p` (text with `pr` headers), ...
+    pub format: char,
+    /// Name of the data file, as sent in its subcommand line.
+    pub name: String,
+    /// Name of the source file (`N` line next to the print command).
+    pub source_name: Option<String>,
+}
+
+/// The parsed contents of an LPD control file (RFC 1179 SS7).
+///
+/// Parsing is lenient: unknown and malformed lines are skipped, as other
+/// LPD servers do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ControlFile {
+    /// Host name of the client (`H`).
+    pub host: Option<String>,
+    /// User identification (`P`).
+    pub user: Option<String>,
+    /// Job name for the banner page (`J`).
+    pub job_name: Option<String>,
+    /// Title for `pr` (`T`).
+    pub title: Option<String>,
+    /// Data files to print, in order, without repeats (repeated print
+    /// commands ask for copies).
+    pub files: Vec<PrintFile>,
+}
+
+impl ControlFile {
+    /// Parse the text of a control file.
+    ///
+    /// RFC 1179 puts a file's `N` line before its print command, but BSD
+    /// `lpr` and CUPS write it after; an `N` line names the last file
+    /// printed if that file has no name yet, and the next one otherwise.
+    pub fn parse(text: &str) -> Self {
+        let mut control = Self::default();
+        let mut source_name = None;
+        // Index in `files` of the file the last print command named.
+        let mut last_printed: Option<usize> = None;
+
+        for line in text.lines() {
+            let mut chars = line.chars();
+            let Some(command) = chars.next() else {
+                continue;
+            };
+            let operand = chars.as_str().trim_end_matches('\r');
+            let value = (!operand.is_empty()).then(|| operand.to_string());
+
+            match command {
+                'H' => control.host = value,
+                'P' => control.user = value,
+                'J' => control.job_name = value,
+                'T' => control.title = value,
+                'N' => match last_printed.map(|i| &mut control.files[i]) {
+                    Some(file) if file.source_name.is_none() => file.source_name = value,
+                    _ => source_name = value,
+                },
+                // Print commands (the `p`, `f`, `l` and `o` of RFC 1179 and
+                // the rarer plotter/typesetter formats).
+                'c' | 'd' | 'f' | 'g' | 'l' | 'n' | 'o' | 'p' | 'r' | 't' | 'v' => {
+                    let Some(name) = value else {
+                        continue;
+                    };
+                    match control.files.iter().position(|f| f.name == name) {
+                        Some(i) => last_printed = Some(i),
+                        None => {
+                            control.files.push(PrintFile {
+                                format: command,
+                                name,
+                                source_name: source_name.take(),
+                            });
+                            last_printed = Some(control.files.len() - 1);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        control
+    }
+
+    /// The name to show for `file`: its source name, the job name, or the
+    /// data file name, in that order.
+    pub fn document_name(&self, file: &PrintFile) -> String {
+        file.source_name
+            .clone()
+            .or_else(|| self.job_name.clone())
+            .unwrap_or_else(|| file.name.clone())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Receive-job sequence
+// ---------------------------------------------------------------------------
+
+/// A job as delivered by an LPD client.
+#[derive(Debug, Default)]
+pub struct ReceivedJob {
+    /// Queue the client addressed.
+    pub queue: String,
+    /// The control file (default if the client sent none).
+    pub control: ControlFile,
+    /// Data files by name.
+    pub data_files: HashMap<String, Vec<u8>>,
+}
+
+impl ReceivedJob {
+    /// The documents to enqueue: each data file the control file prints,
+    /// with its name and type.
+    ///
+    /// Without a control file that names them, every data file is printed
+    /// as a literal file under its own name.
+    pub fn documents(&self) -> Vec<(String, DocumentType, &[u8])> {
+        let printed: Vec<PrintFile> = if self.control.files.is_empty() {
+            let mut names: Vec<&String> = self.data_files.keys().collect();
+            names.sort();
+            names
+                .into_iter()
+                .map(|name| PrintFile {
+                    format: 'l',
+                    name: name.clone(),
+                    source_name: None,
+                })
+                .collect()
+        } else {
+            self.control.files.clone()
+        };
+
+        printed
+            .iter()
+            .filter_map(|file| {
+                let Some(data) = self.data_files.get(&file.name) else {
+                    warn!(file = %file.name, "control file names a data file that was not sent");
+                    return None;
+                };
+                Some((
+                    self.control.document_name(file),
+                    resolve_document_type(declared_type(file.format), data),
+                    data.as_slice(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Run the server side of one LPD connection over `stream`.
+///
+/// Returns `None` when the client sent no data file, or aborted the job.
+///
+/// # Errors
+///
+/// Returns an error on I/O failure, on a daemon command other than
+/// receive-job, or on a malformed or oversized subcommand.
+pub async fn receive_job<S>(stream: S) -> Result<Option<ReceivedJob>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut stream = BufReader::new(stream);
+
+    let Some(command) = read_line(&mut stream).await? else {
+        return Ok(None);
+    };
+    if command.first() != Some(&CMD_RECEIVE_JOB) {
+        return Err(PresswerkError::PrintServer(format!(
+            "unsupported LPD command 0x{:02X}",
+            command.first().copied().unwrap_or_default()
+        )));
+    }
+
+    let mut job = ReceivedJob {
+        queue: String::from_utf8_lossy(&command[1..]).into_owned(),
+        ..ReceivedJob::default()
+    };
+    debug!(queue = %job.queue, "LPD receive job");
+    send_ack(&mut stream, ACK).await?;
+
+    while let Some(line) = read_line(&mut stream).await? {
+        let Some((&subcommand, operand)) = line.split_first() else {
+            continue;
+        };
+
+        if subcommand == SUBCMD_ABORT {
+            debug!("LPD job aborted by client");
+            job.control = ControlFile::default();
+            job.data_files.clear();
+            continue;
+        }
+
+        if subcommand != SUBCMD_CONTROL_FILE && subcommand != SUBCMD_DATA_FILE {
+            send_ack(&mut stream, NACK).await?;
+            return Err(PresswerkError::PrintServer(format!(
+                "unsupported LPD subcommand 0x{subcommand:02X}"
+            )));
+        }
+
+        let operand = String::from_utf8_lossy(operand);
+        let Some((count, name)) = operand
+            .split_once(' ')
+            .and_then(|(count, name)| Some((count.parse::<usize>().ok()?, name.to_string())))
+        else {
+            send_ack(&mut stream, NACK).await?;
+            return Err(PresswerkError::PrintServer(format!(
+                "malformed LPD file header {operand:?}"
+            )));
+        };
+        let limit = if subcommand == SUBCMD_CONTROL_FILE {
+            MAX_CONTROL_BYTES
+        } else {
+            MAX_FILE_BYTES - job.data_files.values().map(Vec::len).sum::<usize>()
+        };
+        if count > limit {
+            send_ack(&mut stream, NACK).await?;
+            return Err(PresswerkError::PrintServer(format!(
+                "LPD file {name} too large ({count} bytes)"
+            )));
+        }
+        send_ack(&mut stream, ACK).await?;
+
+        // The file, then the client's zero byte.  The buffer grows with what
+        // actually arrives rather than with what the header claims.
+        let mut contents = Vec::new();
+        (&mut stream)
+            .take(count as u64 + 1)
+            .read_to_end(&mut contents)
+            .await
+            .map_err(|e| PresswerkError::PrintServer(format!("read LPD file {name}: {e}")))?;
+        if contents.len() != count + 1 {
+            return Err(PresswerkError::PrintServer(format!(
+                "LPD file {name} truncated ({} of {count} bytes)",
+                contents.len().saturating_sub(1)
+            )));
+        }
+        if contents.pop() != Some(0) {
+            send_ack(&mut stream, NACK).await?;
+            return Err(PresswerkError::PrintServer(format!(
+                "LPD file {name} not terminated by a zero byte"
+            )));
+        }
+        send_ack(&mut stream, ACK).await?;
+
+        debug!(file = %name, bytes = count, "LPD file received");
+        if subcommand == SUBCMD_CONTROL_FILE {
+            job.control = ControlFile::parse(&String::from_utf8_lossy(&contents));
+        } else {
+            job.data_files.insert(name, contents);
+        }
+    }
+
+    Ok((!job.data_files.is_empty()).then_some(job))
+}
+
+/// Read one `LF`-terminated line, without the `LF`.  `None` at end of stream.
+async fn read_line<S>(stream: &mut BufReader<S>) -> Result<Option<Vec<u8>>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut line = Vec::new();
+    (&mut *stream)
+        .take(MAX_LINE_BYTES)
+        .read_until(b'\n', &mut line)
+        .await
+        .map_err(|e| PresswerkError::PrintServer(format!("read LPD command: {e}")))?;
+
+    if line.is_empty() {
+        return Ok(None);
+    }
+    if line.pop() != Some(b'\n') {
+        return Err(PresswerkError::PrintServer(
+            "LPD command line too long or truncated".into(),
+        ));
+    }
+    Ok(Some(line))
+}
+
+/// Write a one-byte acknowledgement.
+async fn send_ack<S>(stream: &mut BufReader<S>, ack: u8) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream
+        .write_all(&[ack])
+        .await
+        .map_err(|e| PresswerkError::PrintServer(format!("write LPD ack: {e}")))?;
+    stream
+        .flush()
+        .await
+        .map_err(|e| PresswerkError::PrintServer(format!("flush: {e}")))
+}
+
+/// The document type a data file printed with `format` is declared as.
+///
+/// Literal (`l`) files are passed through untouched and declare nothing, so
+/// [`resolve_document_type`] takes their type from the content.
+fn declared_type(format: char) -> DocumentType {
+    match format {
+        'o' => DocumentType::PostScript,
+        'f' | 'p' => DocumentType::PlainText,
+        _ => DocumentType::NativeDelegate,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shared state passed to connection handlers
+// ---------------------------------------------------------------------------
+
+/// State shared across all connection-handling tasks.
+struct SharedState {
+    /// The job queue that receives incoming print jobs.
+    job_queue: Arc<Mutex<JobQueue>>,
+    /// Counter of active connections (for the UI).
+    active_connections: Arc<AtomicU32>,
+    /// Content-addressed storage for received document data.
+    documents: Arc<DocumentStore>,
+}
+
+impl SharedState {
+    /// Receive one job over `stream` and enqueue its documents.
+    ///
+    /// Returns the ids of the jobs created.
+    async fn serve<S>(&self, stream: S, peer: IpAddr) -> Result<Vec<JobId>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let Some(received) = receive_job(stream).await? else {
+            debug!(peer = %peer, "LPD connection closed without a job");
+            return Ok(Vec::new());
+        };
+
+        received
+            .documents()
+            .into_iter()
+            .map(|(name, document_type, data)| self.enqueue(peer, name, document_type, data))
+            .collect()
+    }
+
+    /// Create a `PrintJob` for `data` and insert it, with its document, the
+    /// way the IPP server's Print-Job does.
+    fn enqueue(
+        &self,
+        peer: IpAddr,
+        document_name: String,
+        document_type: DocumentType,
+        data: &[u8],
+    ) -> Result<JobId> {
+        let document_hash = if data.is_empty() {
+            "empty".into()
+        } else {
+            let mut hasher = Sha256::new();
+            hasher.update(data);
+            hex::encode(hasher.finalize())
+        };
+
+        let mut job = PrintJob::new(
+            JobSource::Network { remote_addr: peer },
+            document_type,
+            document_name.clone(),
+            document_hash,
+        );
+        let job_id = job.id;
+
+        let queue = self
+            .job_queue
+            .lock()
+            .map_err(|_| PresswerkError::PrintServer("job queue lock poisoned".into()))?;
+        queue.insert_ipp_job_tx(&mut job, || {
+            let documents = &self.documents;
+            let stored = if data.is_empty() {
+                None
+            } else {
+                Some(documents.put_for_job(&job_id, data)?)
+            };
+            Ok(move || {
+                if let Some(hash) = stored {
+                    let _ = documents.release(&hash, &job_id);
+                }
+            })
+        })?;
+
+        info!(
+            internal_id = %job_id,
+            doc_name = %document_name,
+            doc_bytes = data.len(),
+            "LPD job accepted"
+        );
+        Ok(job_id)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LpdServer
+// ---------------------------------------------------------------------------
+
+/// Embedded LPD print server.
+///
+/// Accepts jobs from hosts that print over LPR and places them into the
+/// local job queue for user review, alongside those from the [`IppServer`].
+///
+/// [`IppServer`]: crate::ipp_server::IppServer
+pub struct LpdServer {
+    /// The TCP port to listen on.
+    port: u16,
+    /// Current lifecycle state of the server.
+    status: ServerStatus,
+    /// Notification handle used to signal a graceful shutdown.
+    shutdown_signal: Arc<Notify>,
+    /// Handle to the Tokio task running the accept loop.
+    task_handle: Option<JoinHandle<()>>,
+    /// Counter of currently active TCP connections.
+    active_connections: Arc<AtomicU32>,
+    /// Root directory for persistent data (documents subdirectory lives here).
+    data_dir: PathBuf,
+    /// Store shared with the rest of the app, if given; otherwise one is
+    /// opened in `data_dir` on start.
+    documents: Option<Arc<DocumentStore>>,
+}
+
+impl LpdServer {
+    /// Create a new server bound to the given port (default 515).
+    ///
+    /// The server is created in `Stopped` state.  Call [`start`] to begin
+    /// accepting connections.
+    ///
+    /// `data_dir` specifies the root directory where document data is persisted.
+    /// If `None`, a temporary directory is used (suitable for tests).
+    ///
+    /// [`start`]: Self::start
+    pub fn new(port: Option<u16>, data_dir: Option<PathBuf>) -> Self {
+        let data_dir = data_dir.unwrap_or_else(|| std::env::temp_dir().join("presswerk"));
+        Self {
+            port: port.unwrap_or(LPR_PORT),
+            status: ServerStatus::Stopped,
+            shutdown_signal: Arc::new(Notify::new()),
+            task_handle: None,
+            active_connections: Arc::new(AtomicU32::new(0)),
+            data_dir,
+            documents: None,
+        }
+    }
+
+    /// Keep received documents in `documents`, e.g. the store the
+    /// [`IppServer`] and the app use, rather than one of the server's own.
+    ///
+    /// [`IppServer`]: crate::ipp_server::IppServer
+    pub fn with_documents(mut self, documents: Arc<DocumentStore>) -> Self {
+        self.documents = Some(documents);
+        self
+    }
+
+    /// Return the port this server will bind to (or is bound to).
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Return the current server status.
+    pub fn status(&self) -> ServerStatus {
+        self.status
+    }
+
+    /// Return the number of currently active client connections.
+    pub fn active_connections(&self) -> u32 {
+        self.active_connections.load(Ordering::Relaxed)
+    }
+
+    /// Start the LPD server.
+    ///
+    /// Binds a TCP listener on `0.0.0.0:{port}` and spawns a Tokio task that
+    /// accepts incoming connections, each handled in its own task.  Received
+    /// jobs go into `job_queue`.
+    ///
+    /// Port 515 is privileged on most systems; pass another port to
+    /// [`new`](Self::new) where binding it is not allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port is already in use or the listener cannot
+    /// be created.
+    pub async fn start(&mut self, job_queue: Arc<Mutex<JobQueue>>) -> Result<()> {
+        if self.status == ServerStatus::Running {
+            debug!(port = self.port, "LPD server already running");
+            return Ok(());
+        }
+
+        self.status = ServerStatus::Starting;
+
+        let bind_addr: SocketAddr = ([0, 0, 0, 0], self.port).into();
+        let listener = match TcpListener::bind(bind_addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                self.status = ServerStatus::Error;
+                return Err(PresswerkError::PrintServer(format!(
+                    "bind {bind_addr}: {e}"
+                )));
+            }
+        };
+
+        info!(port = self.port, "LPD print server listening");
+
+        let documents = match &self.documents {
+            Some(documents) => Arc::clone(documents),
+            None => Arc::new(DocumentStore::open(&self.data_dir).map_err(|e| {
+                PresswerkError::PrintServer(format!(
+                    "open document store in {}: {e}",
+                    self.data_dir.display()
+                ))
+            })?),
+        };
+
+        let shutdown = Arc::clone(&self.shutdown_signal);
+        let port = self.port;
+        let shared = Arc::new(SharedState {
+            job_queue,
+            active_connections: Arc::clone(&self.active_connections),
+            documents,
+        });
+
+        let handle = tokio::spawn(async move {
+            Self::accept_loop(listener, shutdown, port, shared).await;
+        });
+
+        self.task_handle = Some(handle);
+        self.status = ServerStatus::Running;
+        Ok(())
+    }
+
+    /// Gracefully stop the server.
+    ///
+    /// Signals the accept loop to exit and awaits its completion.  Jobs that
+    /// are mid-transfer will be allowed to finish.
+    pub async fn stop(&mut self) -> Result<()> {
+        if self.status != ServerStatus::Running {
+            return Ok(());
+        }
+
+        info!(port = self.port, "stopping LPD print server");
+        self.shutdown_signal.notify_one();
+
+        if let Some(handle) = self.task_handle.take() {
+            handle
+                .await
+                .map_err(|e| PresswerkError::PrintServer(format!("task join: {e}")))?;
+        }
+
+        self.status = ServerStatus::Stopped;
+        info!(port = self.port, "LPD print server stopped");
+        Ok(())
+    }
+
+    /// The main accept loop.
+    ///
+    /// Runs until the shutdown signal is received.  Each incoming connection
+    /// is served in a separate task.
+    async fn accept_loop(
+        listener: TcpListener,
+        shutdown: Arc<Notify>,
+        port: u16,
+        shared: Arc<SharedState>,
+    ) {
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => {
+                    debug!(port, "LPD accept loop received shutdown signal");
+                    break;
+                }
+
+                accept_result = listener.accept() => {
+                    match accept_result {
+                        Ok((stream, peer_addr)) => {
+                            if shared.active_connections.load(Ordering::Relaxed) >= MAX_CONNECTIONS {
+                                warn!(peer = %peer_addr, max_connections = MAX_CONNECTIONS, "too many LPD connections -- closing");
+                                drop(stream);
+                                continue;
+                            }
+                            info!(peer = %peer_addr, "incoming LPD connection");
+                            let state = Arc::clone(&shared);
+                            state.active_connections.fetch_add(1, Ordering::Relaxed);
+                            tokio::spawn(async move {
+                                let served = tokio::time::timeout(
+                                    CONNECTION_TIMEOUT,
+                                    state.serve(stream, peer_addr.ip()),
+                                )
+                                .await;
+                                match served {
+                                    Ok(Ok(_)) => {}
+                                    Ok(Err(e)) => warn!(
+                                        peer = %peer_addr,
+                                        error = %e,
+                                        "LPD connection handler error"
+                                    ),
+                                    Err(_) => warn!(peer = %peer_addr, "LPD connection timed out"),
+                                }
+                                state.active_connections.fetch_sub(1, Ordering::Relaxed);
+                            });
+                        }
+                        Err(e) => {
+                            error!(error = %e, "failed to accept LPD connection");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Play the client side of a receive-job sequence, checking each ack.
+    async fn send_job(
+        mut client: tokio::io::DuplexStream,
+        files: &[(u8, &str, &[u8])],
+    ) -> tokio::io::DuplexStream {
+        let mut ack = [0u8; 1];
+        client.write_all(b"\x02lp\n").await.unwrap();
+        client.read_exact(&mut ack).await.unwrap();
+        assert_eq!(ack[0], ACK);
+
+        for (subcommand, name, contents) in files {
+            let header = format!("{} {name}\n", contents.len());
+            client.write_all(&[*subcommand]).await.unwrap();
+            client.write_all(header.as_bytes()).await.unwrap();
+            client.read_exact(&mut ack).await.unwrap();
+            assert_eq!(ack[0], ACK, "header of {name}");
+
+            client.write_all(contents).await.unwrap();
+            client.write_all(&[0]).await.unwrap();
+            client.read_exact(&mut ack).await.unwrap();
+            assert_eq!(ack[0], ACK, "contents of {name}");
+        }
+        client
+    }
+
+    #[test]
+    fn control_file_parses_job_metadata_and_print_commands() {
+        let control = ControlFile::parse(
+            "Hworkstation\nPalice\nJQuarterly report\nTReport\n\
+             Nreport.pdf\nldfA001workstation\nldfA001workstation\n\
+             UdfA001workstation\nNnotes.txt\nfdfB001workstation\nX\n\nq\n",
+        );
+
+        assert_eq!(control.host.as_deref(), Some("workstation"));
+        assert_eq!(control.user.as_deref(), Some("alice"));
+        assert_eq!(control.job_name.as_deref(), Some("Quarterly report"));
+        assert_eq!(control.title.as_deref(), Some("Report"));
+        assert_eq!(
+            control.files,
+            [
+                PrintFile {
+                    format: 'l',
+                    name: "dfA001workstation".into(),
+                    source_name: Some("report.pdf".into()),
+                },
+                PrintFile {
+                    format: 'f',
+                    name: "dfB001workstation".into(),
+                    source_name: Some("notes.txt".into()),
+                },
+            ]
+        );
+        assert_eq!(control.document_name(&control.files[1]), "notes.txt");
+
+        let bare = PrintFile {
+            format: 'l',
+            name: "dfA002host".into(),
+            source_name: None,
+        };
+        assert_eq!(control.document_name(&bare), "Quarterly report");
+        assert_eq!(ControlFile::default().document_name(&bare), "dfA002host");
+    }
+
+    #[test]
+    fn source_names_after_print_commands_are_matched_up() {
+        // As BSD lpr and CUPS write it: print command, unlink, then name.
+        let control = ControlFile::parse(
+            "Hhost\nPbob\nldfA001host\nldfA001host\nUdfA001host\nNreport.pdf\n\
+             ldfB001host\nUdfB001host\nNnotes.txt\n",
+        );
+
+        let names: Vec<_> = control
+            .files
+            .iter()
+            .map(|f| (f.name.as_str(), f.source_name.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("dfA001host", Some("report.pdf")),
+                ("dfB001host", Some("notes.txt")),
+            ]
+        );
+    }
+
+    #[test]
+    fn documents_are_typed_by_their_format_and_content() {
+        let typed = |format: char, data: &[u8]| resolve_document_type(declared_type(format), data);
+        assert_eq!(typed('l', b"\xEF\xBB\xBF%PDF-1.7\n"), DocumentType::Pdf);
+        assert_eq!(typed('l', b"RaS2PwgRaster\0"), DocumentType::PwgRaster);
+        assert_eq!(typed('l', b"plain words\n"), DocumentType::PlainText);
+        assert_eq!(typed('l', b"\x1bE\x1b&l0O"), DocumentType::NativeDelegate);
+        assert_eq!(typed('o', b"%!PS-Adobe-3.0\n"), DocumentType::PostScript);
+        // A declared text file stays text even if it starts like PostScript,
+        // but a mislabelled image does not.
+        assert_eq!(typed('f', b"%!not really\n"), DocumentType::PlainText);
+        assert_eq!(typed('f', &[0xFF, 0xD8, 0xFF, 0xE0]), DocumentType::Jpeg);
+    }
+
+    #[tokio::test]
+    async fn receive_job_sequence_enqueues_the_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = SharedState {
+            job_queue: Arc::new(Mutex::new(JobQueue::open_in_memory().unwrap())),
+            active_connections: Arc::new(AtomicU32::new(0)),
+            documents: Arc::new(DocumentStore::open(dir.path()).unwrap()),
+        };
+        let pdf: &[u8] = b"%PDF-1.4 test document";
+        let control: &[u8] = b"Hhost\nPbob\nJinvoice.pdf\nldfA001host\nUdfA001host\n";
+
+        let (client, server) = tokio::io::duplex(1024);
+        let client = tokio::spawn(async move {
+            let client = send_job(
+                client,
+                &[
+                    (SUBCMD_CONTROL_FILE, "cfA001host", control),
+                    (SUBCMD_DATA_FILE, "dfA001host", pdf),
+                ],
+            )
+            .await;
+            // Closing the connection ends the job.
+            drop(client);
+        });
+
+        let peer: IpAddr = "192.168.1.50".parse().unwrap();
+        let ids = state.serve(server, peer).await.unwrap();
+        client.await.unwrap();
+        assert_eq!(ids.len(), 1);
+
+        let queue = state.job_queue.lock().unwrap();
+        let job = queue.get_job(&ids[0]).unwrap().unwrap();
+        assert_eq!(job.document_name, "invoice.pdf");
+        assert_eq!(job.document_type, DocumentType::Pdf);
+        assert_eq!(job.source, JobSource::Network { remote_addr: peer });
+        assert!(job.ipp_job_id.is_some());
+        assert_eq!(state.documents.get(&job.document_hash).unwrap(), pdf);
+    }
+
+    #[tokio::test]
+    async fn aborted_job_is_not_received() {
+        let (client, server) = tokio::io::duplex(1024);
+        let client = tokio::spawn(async move {
+            let mut client = send_job(client, &[(SUBCMD_DATA_FILE, "dfA001host", b"hello")]).await;
+            client.write_all(b"\x01\n").await.unwrap();
+        });
+
+        assert!(receive_job(server).await.unwrap().is_none());
+        client.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn oversized_file_is_refused() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let header = format!("\x02lp\n\x03{} dfA001host\n", MAX_FILE_BYTES + 1);
+        client.write_all(header.as_bytes()).await.unwrap();
+
+        assert!(receive_job(server).await.is_err());
+        let mut acks = Vec::new();
+        client.read_to_end(&mut acks).await.unwrap();
+        assert_eq!(acks, [ACK, NACK]);
+    }
+
+    #[tokio::test]
+    async fn oversized_control_file_is_refused() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let header = format!("\x02lp\n\x02{} cfA001host\n", MAX_CONTROL_BYTES + 1);
+        client.write_all(header.as_bytes()).await.unwrap();
+
+        assert!(receive_job(server).await.is_err());
+        let mut acks = Vec::new();
+        client.read_to_end(&mut acks).await.unwrap();
+        assert_eq!(acks, [ACK, NACK]);
+    }
+
+    #[tokio::test]
+    async fn truncated_file_is_an_error() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"\x02lp\n\x031000 dfA001host\nonly a few bytes")
+            .await
+            .unwrap();
+        client.shutdown().await.unwrap();
+
+        let err = receive_job(server).await.unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{err}");
+    }
+}
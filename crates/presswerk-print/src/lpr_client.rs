@@ -6,12 +6,18 @@
 // This is the fallback for printers that don't speak IPP but accept LPR
 // on port 515. The protocol is simple: open connection, send a control
 // file (metadata), then send the data file (document bytes).
+//
+// The control file names the sending host and user, the job name for the
+// banner page, and one print command per copy.  A banner page is only
+// printed when the control file has an `L` line, so leaving it out
+// suppresses the banner.
 
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Duration;
 
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tracing::{info, warn};
+use tracing::info;
 
 use presswerk_core::error::{PresswerkError, Result};
 
@@ -21,126 +27,341 @@
 /// Timeout for LPR operations.
 const LPR_TIMEOUT_SECS: u64 = 60;
 
-/// Send a document via LPR/LPD protocol.
-///
-/// Implements a minimal RFC 1179 client:
-/// 1. Send "receive job" command (0x02)
-/// 2. Send control file with job metadata
-/// 3. Send data file with document bytes
-pub async fn send_lpr(
-    ip: &str,
-    port: u16,
-    document_bytes: &[u8],
-    job_name: &str,
-) -> Result<()> {
-    let addr = format!("{}:{}", ip, port);
-    info!(addr = %addr, job = job_name, "connecting via LPR");
-
-    let mut stream = tokio::time::timeout(
-        Duration::from_secs(LPR_TIMEOUT_SECS),
-        TcpStream::connect(&addr),
-    )
-    .await
-    .map_err(|_| {
-        PresswerkError::IppRequest(format!(
-            "LPR connection to {} timed out after {}s",
-            addr, LPR_TIMEOUT_SECS
-        ))
-    })?
-    .map_err(|e| PresswerkError::IppRequest(format!("LPR connect to {}: {}", addr, e)))?;
-
-    // RFC 1179: Send "receive a printer job" command
-    // Format: 0x02 <queue-name> LF
-    let queue = "lp"; // default queue
-    let cmd = format!("\x02{}\n", queue);
-    stream
-        .write_all(cmd.as_bytes())
-        .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR command: {e}")))?;
+/// Most copies requested in one control file.
+pub const MAX_LPR_COPIES: u32 = 99;
 
-    // Wait for ACK (0x00)
-    let mut ack = [0u8; 1];
-    tokio::io::AsyncReadExt::read_exact(&mut stream, &mut ack)
-        .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR ack: {e}")))?;
+/// Daemon command: receive a printer job.
+const CMD_RECEIVE_JOB: u8 = 0x02;
 
-    if ack[0] != 0 {
-        return Err(PresswerkError::IppRequest(
-            "LPR printer rejected the job request".into(),
-        ));
+/// Receive-job subcommand: control file follows.
+const SUBCMD_CONTROL_FILE: u8 = 0x02;
+
+/// Receive-job subcommand: data file follows.
+const SUBCMD_DATA_FILE: u8 = 0x03;
+
+/// Longest host and user names RFC 1179 allows in a control file.
+const MAX_NAME_LEN: usize = 31;
+
+/// Longest job and source file names RFC 1179 allows in a control file.
+const MAX_JOB_NAME_LEN: usize = 99;
+
+/// Job numbers handed out to [`LprClient::print`], kept below 1000 as the
+/// file names leave three digits for them.
+static NEXT_JOB_NUMBER: AtomicU32 = AtomicU32::new(1);
+
+/// What an LPR job's control file says about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LprJobOptions {
+    /// Printer queue to send to.
+    pub queue: String,
+    /// Name of the sending host (`H`); also part of the file names.
+    pub host: String,
+    /// Name of the sending user (`P`).
+    pub user: String,
+    /// Job name (`J`), shown on the banner page, and the document's source
+    /// file name (`N`).
+    pub job_name: String,
+    /// Number of copies, clamped to `1..=`[`MAX_LPR_COPIES`].
+    pub copies: u32,
+    /// Whether to print a banner page before the document.
+    pub banner: bool,
+}
+
+impl Default for LprJobOptions {
+    fn default() -> Self {
+        Self {
+            queue: "lp".into(),
+            host: "presswerk".into(),
+            user: "presswerk".into(),
+            job_name: String::new(),
+            copies: 1,
+            banner: false,
+        }
     }
+}
 
-    // Send control file
-    let job_num = 1; // simplified — a real client would track this
-    let hostname = "presswerk";
-    let control_file = format!("H{hostname}\nP{hostname}\nJ{job_name}\nldfA{job_num:03}{hostname}\nUdfA{job_num:03}{hostname}\nN{job_name}\n");
-    let cf_header = format!(
-        "\x02{} cfA{:03}{}\n",
-        control_file.len(),
-        job_num,
-        hostname
-    );
+impl LprJobOptions {
+    /// Options for a single copy of `job_name` without a banner page.
+    pub fn new(job_name: impl Into<String>) -> Self {
+        Self {
+            job_name: job_name.into(),
+            ..Self::default()
+        }
+    }
 
-    stream
-        .write_all(cf_header.as_bytes())
-        .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR control header: {e}")))?;
+    /// The control file for job number `job_number`, as sent.
+    ///
+    /// Operands are cut to the lengths RFC 1179 allows and stripped of
+    /// control characters, which would break the line structure.
+    pub fn control_file(&self, job_number: u32) -> String {
+        let host = operand(&self.host, MAX_NAME_LEN);
+        let user = operand(&self.user, MAX_NAME_LEN);
+        let job_name = operand(&self.job_name, MAX_JOB_NAME_LEN);
+        let data_file = data_file_name(job_number, &host);
 
-    let mut ack = [0u8; 1];
-    tokio::io::AsyncReadExt::read_exact(&mut stream, &mut ack)
-        .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR control ack: {e}")))?;
+        let mut control = format!("H{host}\nP{user}\n");
+        if !job_name.is_empty() {
+            control.push_str(&format!("J{job_name}\n"));
+        }
+        if self.banner {
+            control.push_str(&format!("C{host}\nL{user}\n"));
+        }
+        if !job_name.is_empty() {
+            control.push_str(&format!("N{job_name}\n"));
+        }
+        for _ in 0..self.copies.clamp(1, MAX_LPR_COPIES) {
+            control.push_str(&format!("l{data_file}\n"));
+        }
+        control.push_str(&format!("U{data_file}\n"));
+        control
+    }
+}
 
-    stream
-        .write_all(control_file.as_bytes())
-        .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR control file: {e}")))?;
-    stream
-        .write_all(&[0])
-        .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR control term: {e}")))?;
+/// `value` without control characters, cut to `max` bytes.
+fn operand(value: &str, max: usize) -> String {
+    let mut cleaned: String = value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    if cleaned.len() > max {
+        let mut end = max;
+        while !cleaned.is_char_boundary(end) {
+            end -= 1;
+        }
+        cleaned.truncate(end);
+    }
+    cleaned
+}
 
-    let mut ack = [0u8; 1];
-    tokio::io::AsyncReadExt::read_exact(&mut stream, &mut ack)
+fn control_file_name(job_number: u32, host: &str) -> String {
+    format!("cfA{:03}{host}", job_number % 1000)
+}
+
+fn data_file_name(job_number: u32, host: &str) -> String {
+    format!("dfA{:03}{host}", job_number % 1000)
+}
+
+/// Client for LPR/LPD printing.
+#[derive(Debug, Clone)]
+pub struct LprClient {
+    /// Timeout for connecting and for the whole job transfer.
+    timeout: Duration,
+}
+
+impl Default for LprClient {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(LPR_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl LprClient {
+    /// Create a client with the default timeout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `timeout` for connecting and for sending the job.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send `document_bytes` to `host`:`port` (usually [`LPR_PORT`]) as
+    /// one job described by `options`.
+    pub async fn print(
+        &self,
+        host: &str,
+        port: u16,
+        document_bytes: &[u8],
+        options: &LprJobOptions,
+    ) -> Result<()> {
+        info!(host, port, job = %options.job_name, "connecting via LPR");
+        let mut stream = tokio::time::timeout(self.timeout, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| {
+                PresswerkError::IppRequest(format!(
+                    "LPR connection to {host}:{port} timed out after {}s",
+                    self.timeout.as_secs()
+                ))
+            })?
+            .map_err(|e| {
+                PresswerkError::IppRequest(format!("LPR connect to {host}:{port}: {e}"))
+            })?;
+
+        let job_number = NEXT_JOB_NUMBER.fetch_add(1, Ordering::Relaxed) % 1000;
+        tokio::time::timeout(
+            self.timeout,
+            send_job(&mut stream, document_bytes, options, job_number),
+        )
         .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR data ack: {e}")))?;
+        .map_err(|_| {
+            PresswerkError::IppRequest(format!(
+                "LPR job to {host}:{port} timed out after {}s",
+                self.timeout.as_secs()
+            ))
+        })??;
+        stream
+            .shutdown()
+            .await
+            .map_err(|e| PresswerkError::IppRequest(format!("LPR shutdown: {e}")))?;
+
+        info!(job = %options.job_name, copies = options.copies, "LPR job sent successfully");
+        Ok(())
+    }
+}
+
+/// Run the client side of a receive-job sequence over `stream`: the
+/// daemon command, the control file, then the data file, each acknowledged
+/// by the server with a zero byte.
+async fn send_job<S>(
+    stream: &mut S,
+    document_bytes: &[u8],
+    options: &LprJobOptions,
+    job_number: u32,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let host = operand(&options.host, MAX_NAME_LEN);
+    let control = options.control_file(job_number);
 
-    // Send data file
-    let df_header = format!(
-        "\x03{} dfA{:03}{}\n",
+    let mut command = vec![CMD_RECEIVE_JOB];
+    command.extend_from_slice(operand(&options.queue, MAX_JOB_NAME_LEN).as_bytes());
+    command.push(b'\n');
+    write(stream, &command, "command").await?;
+    read_ack(stream, "printer rejected the job request").await?;
+
+    let header = format!(
+        "{} {}\n",
+        control.len(),
+        control_file_name(job_number, &host)
+    );
+    write(stream, &[SUBCMD_CONTROL_FILE], "control header").await?;
+    write(stream, header.as_bytes(), "control header").await?;
+    read_ack(stream, "printer rejected the control file").await?;
+    write(stream, control.as_bytes(), "control file").await?;
+    write(stream, &[0], "control file").await?;
+    read_ack(stream, "printer did not accept the control file").await?;
+
+    let header = format!(
+        "{} {}\n",
         document_bytes.len(),
-        job_num,
-        hostname
+        data_file_name(job_number, &host)
     );
+    write(stream, &[SUBCMD_DATA_FILE], "data header").await?;
+    write(stream, header.as_bytes(), "data header").await?;
+    read_ack(stream, "printer rejected the data file").await?;
+    write(stream, document_bytes, "data file").await?;
+    write(stream, &[0], "data file").await?;
+    read_ack(stream, "printer did not accept the data file").await?;
 
     stream
-        .write_all(df_header.as_bytes())
-        .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR data header: {e}")))?;
-
-    let mut ack = [0u8; 1];
-    tokio::io::AsyncReadExt::read_exact(&mut stream, &mut ack)
+        .flush()
         .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR data file ack: {e}")))?;
+        .map_err(|e| PresswerkError::IppRequest(format!("LPR flush: {e}")))
+}
 
+async fn write<S: AsyncWrite + Unpin>(stream: &mut S, bytes: &[u8], what: &str) -> Result<()> {
     stream
-        .write_all(document_bytes)
+        .write_all(bytes)
         .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR data send: {e}")))?;
+        .map_err(|e| PresswerkError::IppRequest(format!("LPR {what}: {e}")))
+}
+
+/// Read the server's one-byte acknowledgement; anything but zero fails
+/// with `refusal`.
+async fn read_ack<S: AsyncRead + Unpin>(stream: &mut S, refusal: &str) -> Result<()> {
+    let mut ack = [0u8; 1];
     stream
-        .write_all(&[0])
+        .read_exact(&mut ack)
         .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR data term: {e}")))?;
+        .map_err(|e| PresswerkError::IppRequest(format!("LPR ack: {e}")))?;
+    if ack[0] != 0 {
+        return Err(PresswerkError::IppRequest(format!("LPR {refusal}")));
+    }
+    Ok(())
+}
 
-    let mut ack = [0u8; 1];
-    tokio::io::AsyncReadExt::read_exact(&mut stream, &mut ack)
+/// Send a document via LPR/LPD protocol.
+///
+/// A single copy under `job_name`, without a banner page; see
+/// [`LprClient::print`] for the other options.
+pub async fn send_lpr(ip: &str, port: u16, document_bytes: &[u8], job_name: &str) -> Result<()> {
+    LprClient::new()
+        .print(ip, port, document_bytes, &LprJobOptions::new(job_name))
         .await
-        .map_err(|e| PresswerkError::IppRequest(format!("LPR final ack: {e}")))?;
+}
 
-    if ack[0] != 0 {
-        warn!("LPR printer returned non-zero ack after data transfer");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lpd_server::receive_job;
+
+    fn report_options() -> LprJobOptions {
+        LprJobOptions {
+            queue: "office".into(),
+            host: "phone".into(),
+            user: "alice".into(),
+            job_name: "Report.pdf".into(),
+            copies: 2,
+            banner: false,
+        }
     }
 
-    info!(job = job_name, "LPR job sent successfully");
-    Ok(())
+    #[test]
+    fn control_file_layout() {
+        assert_eq!(
+            report_options().control_file(7),
+            "Hphone\nPalice\nJReport.pdf\nNReport.pdf\nldfA007phone\nldfA007phone\nUdfA007phone\n"
+        );
+
+        let banner = LprJobOptions {
+            banner: true,
+            copies: 1,
+            ..report_options()
+        };
+        assert_eq!(
+            banner.control_file(1042),
+            "Hphone\nPalice\nJReport.pdf\nCphone\nLalice\nNReport.pdf\nldfA042phone\nUdfA042phone\n"
+        );
+    }
+
+    #[test]
+    fn control_file_operands_cannot_break_lines() {
+        let options = LprJobOptions {
+            job_name: format!("evil\nLroot\n{}", "x".repeat(200)),
+            copies: 0,
+            ..report_options()
+        };
+        let control = options.control_file(1);
+        let lines: Vec<&str> = control.lines().collect();
+        assert_eq!(lines[2].len(), 1 + MAX_JOB_NAME_LEN);
+        assert!(lines[2].starts_with("Jevil Lroot x"));
+        assert!(!lines.iter().any(|line| line.starts_with('L')));
+        assert_eq!(lines.iter().filter(|line| line.starts_with('l')).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn job_is_received_by_the_lpd_server() {
+        let (mut client, server) = tokio::io::duplex(64 * 1024);
+        let receiver = tokio::spawn(receive_job(server));
+
+        let document = b"%!PS-Adobe-3.0\nshowpage\n";
+        send_job(&mut client, document, &report_options(), 7)
+            .await
+            .unwrap();
+        drop(client);
+
+        let job = receiver.await.unwrap().unwrap().expect("job received");
+        assert_eq!(job.queue, "office");
+        assert_eq!(job.control.host.as_deref(), Some("phone"));
+        assert_eq!(job.control.user.as_deref(), Some("alice"));
+        assert_eq!(job.control.job_name.as_deref(), Some("Report.pdf"));
+        assert_eq!(job.data_files["dfA007phone"], document);
+        let documents = job.documents();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].0, "Report.pdf");
+    }
 }
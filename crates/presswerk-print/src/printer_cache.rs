@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Last-seen printers, kept across restarts.
+//
+// mDNS needs a few seconds before the first printer resolves, so a fresh
+// launch would otherwise show an empty list even when the user prints to the
+// same printer every day.  Every printer discovery resolves is remembered in
+// `{data_dir}/printers.json` with the time it was last seen; on the next
+// launch those entries are listed straight away and replaced by live results
+// as they come in.
+//
+// Discovery saves the cache whenever `record` says so: straight away when an
+// advertisement changes, and at most every `SAVE_INTERVAL` for plain
+// `last_seen` refreshes, so the file stays current without a shutdown hook.
+//
+// A cached entry not seen for `STALE_AFTER` is reported with `stale` set so
+// the UI can show it as possibly gone, and one not seen for `FORGET_AFTER` is
+// dropped from the cache altogether.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Duration, Utc};
+use tracing::{debug, warn};
+
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::DiscoveredPrinter;
+
+/// File name of the cache, under the data dir.
+pub const CACHE_FILE: &str = "printers.json";
+
+/// How long after it was last seen a cached printer is reported as stale.
+pub const STALE_AFTER: Duration = Duration::hours(1);
+
+/// How long after it was last seen a printer is dropped from the cache.
+pub const FORGET_AFTER: Duration = Duration::days(30);
+
+/// How long refreshed `last_seen` times may stay unsaved.
+pub const SAVE_INTERVAL: Duration = Duration::minutes(5);
+
+/// Tells apart the temp files of saves running at the same time.
+static SAVE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Persistent map of last-seen printers, keyed by mDNS full name.
+///
+/// `PrinterCache` is `Send + Sync`; share it behind an `Arc`.
+pub struct PrinterCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, DiscoveredPrinter>>,
+    /// When the file last matched `entries`; held while saving so saves
+    /// land in order.
+    saved_at: Mutex<DateTime<Utc>>,
+}
+
+impl PrinterCache {
+    /// Load the cache at `path`.
+    ///
+    /// A missing file gives an empty cache.  So does an unreadable one: the
+    /// cache only saves time, so a corrupt file is logged and overwritten on
+    /// the next save rather than failing discovery.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = match read_entries(&path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "ignoring unreadable printer cache");
+                HashMap::new()
+            }
+        };
+        let cache = Self {
+            path,
+            entries: Mutex::new(entries),
+            saved_at: Mutex::new(Utc::now()),
+        };
+        cache.forget_expired(Utc::now());
+        debug!(path = %cache.path.display(), count = cache.len(), "printer cache loaded");
+        cache
+    }
+
+    /// Where the cache is saved.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of cached printers.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no printers are cached.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Remember a live `printer`.
+    ///
+    /// Returns `true` when the cache is worth [saving](Self::save) now: the
+    /// printer is new or its advertisement changed (a new URI or address),
+    /// or the last save is more than [`SAVE_INTERVAL`] old.  Other plain
+    /// refreshes of `last_seen` wait for a later save.
+    pub fn record(&self, printer: &DiscoveredPrinter) -> bool {
+        let changed = {
+            let mut entries = self.lock();
+            let changed = entries.get(&printer.name).is_none_or(|cached| {
+                cached.uri != printer.uri || cached.addresses != printer.addresses
+            });
+            let mut printer = printer.clone();
+            printer.stale = false;
+            entries.insert(printer.name.clone(), printer);
+            changed
+        };
+        changed || Utc::now() - *lock_saved_at(&self.saved_at) >= SAVE_INTERVAL
+    }
+
+    /// Combine `live` discovery results with the cached printers.
+    ///
+    /// Live entries win over cached ones of the same name.  Cached printers
+    /// missing from `live` are added with `stale` set once they are older
+    /// than [`STALE_AFTER`] at `now`; ones older than [`FORGET_AFTER`] are
+    /// left out.
+    pub fn merge(
+        &self,
+        live: Vec<DiscoveredPrinter>,
+        now: DateTime<Utc>,
+    ) -> Vec<DiscoveredPrinter> {
+        let entries = self.lock();
+        let mut cached: Vec<DiscoveredPrinter> = entries
+            .values()
+            .filter(|cached| !live.iter().any(|printer| printer.name == cached.name))
+            .filter(|cached| now - cached.last_seen < FORGET_AFTER)
+            .cloned()
+            .map(|mut cached| {
+                cached.stale = now - cached.last_seen >= STALE_AFTER;
+                cached
+            })
+            .collect();
+        // Stable order for the cached tail, most recently seen first.
+        cached.sort_by_key(|printer| std::cmp::Reverse(printer.last_seen));
+
+        let mut merged = live;
+        merged.extend(cached);
+        merged
+    }
+
+    /// Write the cache to disk.
+    ///
+    /// The file is replaced atomically, so a crash mid-save leaves the
+    /// previous version in place.  Each save writes its own temp file, and
+    /// saves from several threads run one at a time, so the last one to
+    /// finish holds the newest entries.
+    pub fn save(&self) -> Result<()> {
+        let mut saved_at = lock_saved_at(&self.saved_at);
+        let now = Utc::now();
+        let mut printers: Vec<DiscoveredPrinter> = self.lock().values().cloned().collect();
+        printers.sort_by(|a, b| a.name.cmp(&b.name));
+        let json = serde_json::to_vec_pretty(&printers)?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = self.path.with_extension(format!(
+            "json.{}.{}.tmp",
+            std::process::id(),
+            SAVE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        if let Err(e) = fs::write(&temp, json).and_then(|()| fs::rename(&temp, &self.path)) {
+            let _ = fs::remove_file(&temp);
+            return Err(PresswerkError::Io(e));
+        }
+        *saved_at = now;
+
+        debug!(path = %self.path.display(), count = printers.len(), "printer cache saved");
+        Ok(())
+    }
+
+    /// Drop entries last seen longer than [`FORGET_AFTER`] before `now`.
+    fn forget_expired(&self, now: DateTime<Utc>) {
+        self.lock()
+            .retain(|_, printer| now - printer.last_seen < FORGET_AFTER);
+    }
+
+    /// Acquire the entry map, recovering from poison (every update leaves it
+    /// consistent).
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, DiscoveredPrinter>> {
+        self.entries.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+/// Lock `saved_at`, recovering from poison (it is only ever overwritten).
+fn lock_saved_at(saved_at: &Mutex<DateTime<Utc>>) -> std::sync::MutexGuard<'_, DateTime<Utc>> {
+    saved_at.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+/// Read the cache file, keyed by full name.  A missing file is empty.
+fn read_entries(path: &Path) -> Result<HashMap<String, DiscoveredPrinter>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(PresswerkError::Io(e)),
+    };
+    let printers: Vec<DiscoveredPrinter> = serde_json::from_slice(&bytes)?;
+    Ok(printers
+        .into_iter()
+        .map(|printer| (printer.name.clone(), printer))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printer(name: &str, ip: &str, last_seen: DateTime<Utc>) -> DiscoveredPrinter {
+        let ip = ip.parse().unwrap();
+        DiscoveredPrinter {
+            name: name.into(),
+            uri: format!("ipp://{ip}:631/ipp/print"),
+            ip,
+            port: 631,
+            supports_color: false,
+            supports_duplex: false,
+            supports_tls: false,
+            paper_sizes: Vec::new(),
+            make_and_model: None,
+            location: None,
+            last_seen,
+            stale: false,
+            manually_added: false,
+            addresses: vec![ip],
+            uuid: None,
+            document_formats: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn saved_printers_load_back_and_expired_ones_are_forgotten() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join(CACHE_FILE);
+        let now = Utc::now();
+
+        let cache = PrinterCache::load(&path);
+        assert!(cache.is_empty());
+        assert!(cache.record(&printer("Office._ipp._tcp.local.", "192.168.1.20", now)));
+        assert!(cache.record(&printer(
+            "Old._ipp._tcp.local.",
+            "192.168.1.21",
+            now - FORGET_AFTER - Duration::hours(1),
+        )));
+        cache.save().unwrap();
+
+        let reloaded = PrinterCache::load(&path);
+        assert_eq!(reloaded.len(), 1);
+        let merged = reloaded.merge(Vec::new(), now);
+        assert_eq!(merged[0].name, "Office._ipp._tcp.local.");
+        assert_eq!(merged[0].uri, "ipp://192.168.1.20:631/ipp/print");
+    }
+
+    #[test]
+    fn unreadable_cache_starts_empty() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join(CACHE_FILE);
+        fs::write(&path, b"{ not json").unwrap();
+
+        let cache = PrinterCache::load(&path);
+        assert!(cache.is_empty());
+        cache.save().unwrap();
+        assert!(PrinterCache::load(&path).is_empty());
+    }
+
+    #[test]
+    fn live_results_replace_cached_ones_and_old_entries_go_stale() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cache = PrinterCache::load(tmp.path().join(CACHE_FILE));
+        let now = Utc::now();
+
+        cache.record(&printer(
+            "Office._ipp._tcp.local.",
+            "192.168.1.20",
+            now - Duration::days(2),
+        ));
+        cache.record(&printer(
+            "Lab._ipp._tcp.local.",
+            "192.168.1.30",
+            now - Duration::days(3),
+        ));
+        cache.record(&printer(
+            "Desk._ipp._tcp.local.",
+            "192.168.1.40",
+            now - Duration::minutes(5),
+        ));
+
+        // The office printer is back on a new address.
+        let live = vec![printer("Office._ipp._tcp.local.", "192.168.1.25", now)];
+        let merged = cache.merge(live, now);
+
+        let names: Vec<&str> = merged.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "Office._ipp._tcp.local.",
+                "Desk._ipp._tcp.local.",
+                "Lab._ipp._tcp.local."
+            ]
+        );
+        assert_eq!(merged[0].uri, "ipp://192.168.1.25:631/ipp/print");
+        assert!(!merged[0].stale);
+        assert!(!merged[1].stale, "seen minutes ago");
+        assert!(merged[2].stale, "not seen for days");
+
+        // A refresh of the same advertisement is not a change.
+        assert!(!cache.record(&printer("Desk._ipp._tcp.local.", "192.168.1.40", now)));
+        assert!(cache.record(&printer("Desk._ipp._tcp.local.", "192.168.1.41", now)));
+
+        // Until the last save is old enough to be worth refreshing.
+        *lock_saved_at(&cache.saved_at) = now - SAVE_INTERVAL;
+        assert!(cache.record(&printer("Desk._ipp._tcp.local.", "192.168.1.41", now)));
+        cache.save().unwrap();
+        assert!(!cache.record(&printer("Desk._ipp._tcp.local.", "192.168.1.41", now)));
+    }
+
+    #[test]
+    fn concurrent_saves_do_not_collide() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join(CACHE_FILE);
+        let cache = PrinterCache::load(&path);
+        let now = Utc::now();
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let cache = &cache;
+                scope.spawn(move || {
+                    let name = format!("Printer{i}._ipp._tcp.local.");
+                    cache.record(&printer(&name, &format!("192.168.1.{}", 50 + i), now));
+                    cache.save().unwrap();
+                });
+            }
+        });
+
+        assert_eq!(PrinterCache::load(&path).len(), 8);
+        let leftovers = fs::read_dir(tmp.path()).unwrap().count();
+        assert_eq!(leftovers, 1, "only the cache file remains");
+    }
+}
@@ -9,58 +9,18 @@
 //
 // Chain: IPPS (TLS) → IPP/1.1 → IPP/1.0 → LPR/LPD (port 515) → Raw TCP (port 9100)
 
-use tracing::{debug, info, warn};
-
-use presswerk_core::error::Result;
-use presswerk_core::types::{DocumentType, PrintSettings};
-
-/// Supported print protocols, ordered from most secure to least.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PrintProtocol {
-    /// IPP over TLS (port 631, ipps://).
-    Ipps,
-    /// IPP/1.1 plain (port 631, ipp://).
-    Ipp11,
-    /// IPP/1.0 plain (legacy printers).
-    Ipp10,
-    /// LPR/LPD (RFC 1179, port 515).
-    Lpr,
-    /// Raw TCP socket (port 9100, JetDirect).
-    RawTcp,
-}
+use std::future::Future;
 
-impl PrintProtocol {
-    /// All protocols in security-preferred order.
-    pub fn chain() -> &'static [PrintProtocol] {
-        &[
-            PrintProtocol::Ipps,
-            PrintProtocol::Ipp11,
-            PrintProtocol::Ipp10,
-            PrintProtocol::Lpr,
-            PrintProtocol::RawTcp,
-        ]
-    }
-
-    /// Human-readable name for UI display.
-    pub fn display_name(&self) -> &'static str {
-        match self {
-            Self::Ipps => "Secure IPP (TLS)",
-            Self::Ipp11 => "IPP 1.1",
-            Self::Ipp10 => "IPP 1.0",
-            Self::Lpr => "LPR/LPD",
-            Self::RawTcp => "Direct TCP",
-        }
-    }
+use ipp::prelude::{IppAttribute, IppValue};
+use tracing::{debug, info, instrument, warn};
 
-    /// Default port for this protocol.
-    pub fn default_port(&self) -> u16 {
-        match self {
-            Self::Ipps | Self::Ipp11 | Self::Ipp10 => 631,
-            Self::Lpr => 515,
-            Self::RawTcp => 9100,
-        }
-    }
-}
+use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::{AttemptRecord, DocumentType, PrintSettings};
+
+use crate::capabilities::PrinterCapabilities;
+use crate::ipp_client::{IppClient, Rasterizer};
+
+pub use presswerk_core::types::PrintProtocol;
 
 /// Result of a protocol probe — can we talk to the printer this way?
 pub struct ProbeResult {
@@ -80,11 +40,7 @@ pub async fn probe_all_protocols(
     let mut results = Vec::new();
 
     for protocol in PrintProtocol::chain() {
-        let port = if base_port != 631 {
-            base_port
-        } else {
-            protocol.default_port()
-        };
+        let port = port_for(*protocol, base_port);
         let result = probe_protocol(ip, port, *protocol).await;
         results.push(result);
     }
@@ -102,11 +58,7 @@ pub async fn find_best_protocol(
     base_port: u16,
 ) -> Option<PrintProtocol> {
     for protocol in PrintProtocol::chain() {
-        let port = if base_port != 631 {
-            base_port
-        } else {
-            protocol.default_port()
-        };
+        let port = port_for(*protocol, base_port);
         let result = probe_protocol(ip, port, *protocol).await;
         if result.success {
             info!(
@@ -156,7 +108,13 @@ pub async fn send_via_protocol(
             Ok(())
         }
         PrintProtocol::Lpr => {
-            crate::lpr_client::send_lpr(ip, port, &document_bytes, job_name).await
+            let options = crate::lpr_client::LprJobOptions {
+                copies: settings.copies,
+                ..crate::lpr_client::LprJobOptions::new(job_name)
+            };
+            crate::lpr_client::LprClient::new()
+                .print(ip, port, &document_bytes, &options)
+                .await
         }
         PrintProtocol::RawTcp => {
             crate::raw_client::send_raw(ip, port, &document_bytes).await
@@ -164,6 +122,183 @@ pub async fn send_via_protocol(
     }
 }
 
+/// Protocols to try for a job, in order: only the forced one when
+/// `settings.forced_protocol` is set, otherwise the whole
+/// [`PrintProtocol::chain`].
+pub fn protocols_to_try(settings: &PrintSettings) -> Vec<PrintProtocol> {
+    match settings.forced_protocol {
+        Some(protocol) => vec![protocol],
+        None => PrintProtocol::chain().to_vec(),
+    }
+}
+
+/// IPP job-template attributes for `settings` (RFC 8011 §5.2): `copies`,
+/// `print-color-mode`, `sides`, `media`, `orientation-requested` and, when a
+/// page range is set, `page-ranges`.
+///
+/// Every IPP sender encodes settings through this, so a job prints the same
+/// whether it is sent first time or retried.  With the printer's `caps` the
+/// paper size is sent as the printer's own keyword for that sheet (see
+/// [`PrinterCapabilities::media_keyword`]).
+pub fn encode_job_attributes(
+    settings: &PrintSettings,
+    caps: Option<&PrinterCapabilities>,
+) -> Vec<IppAttribute> {
+    let color_mode = if settings.color {
+        "color"
+    } else {
+        "monochrome"
+    };
+    let media = caps
+        .and_then(|caps| caps.media_keyword(&settings.paper_size))
+        .unwrap_or_else(|| settings.paper_size.to_ipp_media_keyword());
+    let mut attributes = vec![
+        IppAttribute::new("copies", IppValue::Integer(settings.copies as i32)),
+        IppAttribute::new("print-color-mode", IppValue::Keyword(color_mode.into())),
+        IppAttribute::new(
+            "sides",
+            IppValue::Keyword(settings.duplex.ipp_sides_keyword().into()),
+        ),
+        IppAttribute::new("media", IppValue::Keyword(media)),
+        IppAttribute::new(
+            "orientation-requested",
+            IppValue::Enum(settings.orientation.ipp_enum_value()),
+        ),
+    ];
+
+    // Page ranges (1-indexed, inclusive; open-ended spans run to i32::MAX)
+    if let Some(ref range) = settings.page_range {
+        let spans: Vec<IppValue> = range
+            .spans()
+            .iter()
+            .map(|span| IppValue::RangeOfInteger {
+                min: span.start as i32,
+                max: span.end.map_or(i32::MAX, |end| end as i32),
+            })
+            .collect();
+        attributes.push(IppAttribute::new("page-ranges", IppValue::Array(spans)));
+    }
+
+    attributes
+}
+
+/// Print a document, stepping down the protocol chain until one delivers it.
+///
+/// IPP goes through [`IppClient::smart_print`], which rasterizes documents
+/// the printer cannot interpret; LPR and raw TCP send the bytes unchanged.
+/// When `settings.forced_protocol` is set only that protocol is used and its
+/// failure is returned rather than falling back. Returns the protocol that
+/// delivered the job; every protocol tried is appended to `attempts`.
+///
+/// IPP is sent to `printer_uri` when known, with only its scheme changed
+/// for the protocol, so the printer's resource path is kept.
+#[instrument(
+    skip(document_bytes, printer_uri, settings, rasterizer, attempts),
+    fields(bytes = document_bytes.len())
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn smart_print(
+    ip: &str,
+    base_port: u16,
+    printer_uri: Option<&str>,
+    document_bytes: Vec<u8>,
+    document_type: DocumentType,
+    job_name: &str,
+    settings: &PrintSettings,
+    rasterizer: &dyn Rasterizer,
+    attempts: &mut Vec<AttemptRecord>,
+) -> Result<PrintProtocol> {
+    if let Some(forced) = settings.forced_protocol {
+        info!(
+            protocol = forced.display_name(),
+            "protocol forced by settings"
+        );
+    }
+
+    send_with_fallback(&protocols_to_try(settings), attempts, |protocol| {
+        let port = port_for(protocol, base_port);
+        let bytes = document_bytes.clone();
+        async move {
+            match protocol {
+                PrintProtocol::Lpr | PrintProtocol::RawTcp => {
+                    send_via_protocol(protocol, ip, port, bytes, document_type, job_name, settings)
+                        .await
+                }
+                PrintProtocol::Ipps | PrintProtocol::Ipp11 | PrintProtocol::Ipp10 => {
+                    let client = IppClient::new(&ipp_uri(protocol, printer_uri, ip, port))?;
+                    client
+                        .smart_print(bytes, document_type, job_name, settings, rasterizer)
+                        .await
+                        .map(|_| ())
+                }
+            }
+        }
+    })
+    .await
+}
+
+/// The URI to send IPP to over `protocol`: `printer_uri` with its scheme
+/// swapped when known, otherwise the usual `/ipp/print` resource on `ip`.
+fn ipp_uri(protocol: PrintProtocol, printer_uri: Option<&str>, ip: &str, port: u16) -> String {
+    let scheme = if protocol == PrintProtocol::Ipps {
+        "ipps"
+    } else {
+        "ipp"
+    };
+    if let Some((_, rest)) = printer_uri.and_then(|uri| uri.split_once("://")) {
+        return format!("{scheme}://{rest}");
+    }
+    if ip.contains(':') {
+        format!("{scheme}://[{ip}]:{port}/ipp/print")
+    } else {
+        format!("{scheme}://{ip}:{port}/ipp/print")
+    }
+}
+
+/// Try `send` with each protocol in turn; returns the first that succeeds,
+/// or the last error.  Each try is appended to `attempts`.
+async fn send_with_fallback<F, Fut>(
+    protocols: &[PrintProtocol],
+    attempts: &mut Vec<AttemptRecord>,
+    mut send: F,
+) -> Result<PrintProtocol>
+where
+    F: FnMut(PrintProtocol) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut last_error = None;
+    for &protocol in protocols {
+        match send(protocol).await {
+            Ok(()) => {
+                info!(protocol = protocol.display_name(), "print job delivered");
+                attempts.push(AttemptRecord::delivered(protocol));
+                return Ok(protocol);
+            }
+            Err(e) => {
+                warn!(
+                    protocol = protocol.display_name(),
+                    error = %e,
+                    "protocol failed"
+                );
+                attempts.push(AttemptRecord::failed(protocol, &e));
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| PresswerkError::IppRequest("no print protocol to try".into())))
+}
+
+/// Port to use for `protocol`: a non-standard `base_port` (the printer was
+/// configured with an explicit port) wins over the protocol default.
+fn port_for(protocol: PrintProtocol, base_port: u16) -> u16 {
+    if base_port != 631 {
+        base_port
+    } else {
+        protocol.default_port()
+    }
+}
+
 /// Probe a specific protocol.
 async fn probe_protocol(ip: &str, port: u16, protocol: PrintProtocol) -> ProbeResult {
     let success = match protocol {
@@ -204,3 +339,317 @@ async fn probe_tcp(ip: &str, port: u16) -> std::result::Result<(), String> {
         .map_err(|e| e.to_string())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    /// Fails the test if rasterization (and so IPP) is ever reached.
+    struct NoRasterizer;
+
+    impl Rasterizer for NoRasterizer {
+        fn rasterize(
+            &self,
+            _document: &[u8],
+            _document_type: DocumentType,
+            _target: DocumentType,
+            _settings: &PrintSettings,
+        ) -> Result<Vec<u8>> {
+            panic!("rasterizer must not be used");
+        }
+    }
+
+    fn forced(protocol: PrintProtocol) -> PrintSettings {
+        PrintSettings {
+            forced_protocol: Some(protocol),
+            ..PrintSettings::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn forced_raw_skips_ipp_even_when_it_would_work() {
+        let mut attempts = Vec::new();
+        // Every protocol would succeed; only the forced one may be tried.
+        let sent = send_with_fallback(
+            &protocols_to_try(&forced(PrintProtocol::RawTcp)),
+            &mut Vec::new(),
+            |p| {
+                attempts.push(p);
+                async { Ok(()) }
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(sent, PrintProtocol::RawTcp);
+        assert_eq!(attempts, vec![PrintProtocol::RawTcp]);
+    }
+
+    #[tokio::test]
+    async fn forced_protocol_failure_does_not_fall_back() {
+        let mut attempts = Vec::new();
+        let result = send_with_fallback(
+            &protocols_to_try(&forced(PrintProtocol::Lpr)),
+            &mut Vec::new(),
+            |p| {
+                attempts.push(p);
+                async { Err(PresswerkError::IppRequest("queue refused".into())) }
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, vec![PrintProtocol::Lpr]);
+    }
+
+    #[tokio::test]
+    async fn unforced_print_falls_back_down_the_chain() {
+        let mut attempts = Vec::new();
+        let sent = send_with_fallback(
+            &protocols_to_try(&PrintSettings::default()),
+            &mut Vec::new(),
+            |p| {
+                attempts.push(p);
+                let ok = p == PrintProtocol::Ipp10;
+                async move {
+                    if ok {
+                        Ok(())
+                    } else {
+                        Err(PresswerkError::IppRequest("no".into()))
+                    }
+                }
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(sent, PrintProtocol::Ipp10);
+        assert_eq!(&attempts, &PrintProtocol::chain()[..3]);
+    }
+
+    #[tokio::test]
+    async fn failed_job_keeps_one_attempt_per_protocol() {
+        use crate::queue::JobQueue;
+        use presswerk_core::types::{AttemptOutcome, JobSource, JobStatus, PrintJob};
+
+        let queue = JobQueue::open_in_memory().unwrap();
+        let job = PrintJob::new(
+            JobSource::Local,
+            DocumentType::Pdf,
+            "report.pdf".into(),
+            "abc".into(),
+        );
+        queue.insert_job(&job).unwrap();
+
+        let mut attempts = Vec::new();
+        let result = send_with_fallback(
+            &[PrintProtocol::Ipp11, PrintProtocol::RawTcp],
+            &mut attempts,
+            |p| async move {
+                Err(match p {
+                    PrintProtocol::RawTcp => {
+                        PresswerkError::IppRequest("Raw TCP connect: connection refused".into())
+                    }
+                    _ => PresswerkError::IppStatus {
+                        code: 0x040A,
+                        status: "ClientErrorDocumentFormatNotSupported".into(),
+                        detail: "Print-Job returned status ClientErrorDocumentFormatNotSupported"
+                            .into(),
+                    },
+                })
+            },
+        )
+        .await;
+        assert!(result.is_err());
+
+        queue.record_attempts(&job.id, &attempts).unwrap();
+        queue
+            .update_status(&job.id, JobStatus::Failed, Some("connection refused"))
+            .unwrap();
+
+        let detail = queue
+            .get_job(&job.id)
+            .unwrap()
+            .unwrap()
+            .failure_detail()
+            .expect("failed job has detail");
+        let summary: Vec<_> = detail
+            .attempts
+            .iter()
+            .map(|a| match &a.outcome {
+                AttemptOutcome::Failed { ipp_status, .. } => (a.protocol, ipp_status.clone()),
+                AttemptOutcome::Delivered => panic!("nothing was delivered"),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    PrintProtocol::Ipp11,
+                    Some("ClientErrorDocumentFormatNotSupported".to_string())
+                ),
+                (PrintProtocol::RawTcp, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn forced_ipp_keeps_the_printer_uri() {
+        let uri = "ipp://[fe80::1]:8631/printers/office";
+        assert_eq!(
+            ipp_uri(PrintProtocol::Ipps, Some(uri), "fe80::1", 8631),
+            "ipps://[fe80::1]:8631/printers/office"
+        );
+        let mdns = Some("ipps://printer.local/ipp/print");
+        assert_eq!(
+            ipp_uri(PrintProtocol::Ipp10, mdns, "", 631),
+            "ipp://printer.local/ipp/print"
+        );
+        assert_eq!(
+            ipp_uri(PrintProtocol::Ipp11, None, "2001:db8::5", 631),
+            "ipp://[2001:db8::5]:631/ipp/print"
+        );
+        assert_eq!(
+            ipp_uri(PrintProtocol::Ipps, None, "192.168.1.5", 631),
+            "ipps://192.168.1.5:631/ipp/print"
+        );
+    }
+
+    #[tokio::test]
+    async fn smart_print_sends_forced_raw_over_tcp() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let receiver = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            socket.read_to_end(&mut received).await.unwrap();
+            received
+        });
+
+        let sent = smart_print(
+            "127.0.0.1",
+            port,
+            None,
+            b"%PDF-1.7 test".to_vec(),
+            DocumentType::Pdf,
+            "forced",
+            &forced(PrintProtocol::RawTcp),
+            &NoRasterizer,
+            &mut Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(sent, PrintProtocol::RawTcp);
+        assert_eq!(receiver.await.unwrap(), b"%PDF-1.7 test");
+    }
+
+    // -- Job attributes ---------------------------------------------------------
+
+    /// The value `encode_job_attributes` gives the attribute `name`.
+    fn encoded(settings: &PrintSettings, name: &str) -> Option<IppValue> {
+        encode_job_attributes(settings, None)
+            .into_iter()
+            .find(|attribute| attribute.name() == name)
+            .map(|attribute| attribute.value().clone())
+    }
+
+    fn keyword(value: &str) -> Option<IppValue> {
+        Some(IppValue::Keyword(value.into()))
+    }
+
+    #[test]
+    fn each_setting_is_encoded_as_its_attribute() {
+        use presswerk_core::types::{Orientation, PaperSize};
+
+        let settings = PrintSettings {
+            copies: 3,
+            paper_size: PaperSize::Letter,
+            orientation: Orientation::Landscape,
+            color: false,
+            ..PrintSettings::default()
+        };
+
+        assert_eq!(encoded(&settings, "copies"), Some(IppValue::Integer(3)));
+        assert_eq!(
+            encoded(&settings, "print-color-mode"),
+            keyword("monochrome")
+        );
+        assert_eq!(encoded(&settings, "sides"), keyword("one-sided"));
+        assert_eq!(encoded(&settings, "media"), keyword("na_letter_8.5x11in"));
+        assert_eq!(
+            encoded(&settings, "orientation-requested"),
+            Some(IppValue::Enum(4))
+        );
+        assert_eq!(encoded(&settings, "page-ranges"), None);
+
+        let colour = PrintSettings::default();
+        assert_eq!(encoded(&colour, "print-color-mode"), keyword("color"));
+    }
+
+    #[test]
+    fn media_is_sent_as_the_printers_keyword() {
+        use presswerk_core::types::PaperSize;
+        use std::collections::HashMap;
+
+        let caps = PrinterCapabilities::from_attributes(&HashMap::from([(
+            "media-supported".to_string(),
+            "iso_a4_210x297mm, iso_a6_105x148mm".to_string(),
+        )]));
+        let settings = PrintSettings {
+            paper_size: PaperSize::Custom {
+                width_mm: 105.0,
+                height_mm: 148.0,
+            },
+            ..PrintSettings::default()
+        };
+        let media = |caps| {
+            encode_job_attributes(&settings, caps)
+                .into_iter()
+                .find(|attribute| attribute.name() == "media")
+                .map(|attribute| attribute.value().clone())
+        };
+
+        assert_eq!(media(Some(&caps)), keyword("iso_a6_105x148mm"));
+        assert_eq!(media(None), keyword("custom_105x148mm_105x148mm"));
+    }
+
+    #[test]
+    fn long_and_short_edge_duplex_are_distinct_sides() {
+        use presswerk_core::types::DuplexMode;
+
+        let long = PrintSettings {
+            duplex: DuplexMode::LongEdge,
+            ..PrintSettings::default()
+        };
+        let short = PrintSettings {
+            duplex: DuplexMode::ShortEdge,
+            ..PrintSettings::default()
+        };
+
+        assert_eq!(encoded(&long, "sides"), keyword("two-sided-long-edge"));
+        assert_eq!(encoded(&short, "sides"), keyword("two-sided-short-edge"));
+    }
+
+    #[test]
+    fn page_ranges_are_encoded_with_open_ends() {
+        use presswerk_core::types::PageRange;
+
+        let settings = PrintSettings {
+            page_range: Some(PageRange::parse("1-3,7-").unwrap()),
+            ..PrintSettings::default()
+        };
+
+        assert_eq!(
+            encoded(&settings, "page-ranges"),
+            Some(IppValue::Array(vec![
+                IppValue::RangeOfInteger { min: 1, max: 3 },
+                IppValue::RangeOfInteger {
+                    min: 7,
+                    max: i32::MAX
+                },
+            ]))
+        );
+    }
+}
@@ -8,12 +8,14 @@
 // device reboots.  Document payloads are stored separately on disk and
 // referenced by their SHA-256 hash.
 
+use std::collections::HashSet;
+
 use chrono::{DateTime, Utc};
-use rusqlite::{Connection, params};
-use tracing::{debug, info, instrument};
+use rusqlite::{Connection, OptionalExtension, params};
+use tracing::{debug, info, instrument, warn};
 
 use presswerk_core::error::{PresswerkError, Result};
-use presswerk_core::types::{DocumentType, ErrorClass, JobId, JobSource, JobStatus, PrintJob, PrintSettings};
+use presswerk_core::types::{AttemptOutcome, AttemptRecord, DocumentType, ErrorClass, JobId, JobSource, JobStatus, PrintJob, PrintSettings};
 
 /// SQLite schema for the jobs table.
 const CREATE_TABLE_SQL: &str = r#"
@@ -34,11 +36,26 @@
         error_class TEXT,
         error_history TEXT NOT NULL DEFAULT '[]',
         bytes_sent INTEGER NOT NULL DEFAULT 0,
-        total_bytes INTEGER NOT NULL DEFAULT 0
+        total_bytes INTEGER NOT NULL DEFAULT 0,
+        ipp_job_id INTEGER,
+        attempts TEXT NOT NULL DEFAULT '[]'
     )
 "#;
 
-/// Migration to add retry/resume columns to existing databases.
+/// SQLite schema for the IPP job-id mapping.
+///
+/// IPP identifies jobs by small integers while the queue uses UUIDs.
+/// `AUTOINCREMENT` keeps the counter in `sqlite_sequence`, so an id is never
+/// handed out twice, even after its job is deleted or the process restarts.
+const CREATE_IPP_JOB_IDS_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS ipp_job_ids (
+        ipp_id INTEGER PRIMARY KEY AUTOINCREMENT,
+        job_id TEXT NOT NULL UNIQUE
+    )
+"#;
+
+/// Migration to add retry/resume, IPP job-id and attempt-log columns to
+/// existing databases.
 const MIGRATE_RETRY_COLUMNS_SQL: &str = r#"
     ALTER TABLE jobs ADD COLUMN retry_count INTEGER NOT NULL DEFAULT 0;
     ALTER TABLE jobs ADD COLUMN max_retries INTEGER NOT NULL DEFAULT 5;
@@ -46,6 +63,8 @@
     ALTER TABLE jobs ADD COLUMN error_history TEXT NOT NULL DEFAULT '[]';
     ALTER TABLE jobs ADD COLUMN bytes_sent INTEGER NOT NULL DEFAULT 0;
     ALTER TABLE jobs ADD COLUMN total_bytes INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE jobs ADD COLUMN ipp_job_id INTEGER;
+    ALTER TABLE jobs ADD COLUMN attempts TEXT NOT NULL DEFAULT '[]';
 "#;
 
 /// Persistent job queue backed by a SQLite database.
@@ -61,7 +80,8 @@ impl JobQueue {
     /// Open (or create) the job queue database at the given path.
     ///
     /// Applies WAL journal mode for better concurrent-read performance on
-    /// mobile devices and creates the `jobs` table if it does not exist.
+    /// mobile devices and creates the `jobs` and `ipp_job_ids` tables if they
+    /// do not exist.
     #[instrument(skip_all, fields(path = %path.as_ref().display()))]
     pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
         let conn = Connection::open(path.as_ref())
@@ -74,6 +94,8 @@ pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
 
         conn.execute_batch(CREATE_TABLE_SQL)
             .map_err(|e| PresswerkError::Database(format!("create table: {e}")))?;
+        conn.execute_batch(CREATE_IPP_JOB_IDS_SQL)
+            .map_err(|e| PresswerkError::Database(format!("create ipp_job_ids: {e}")))?;
 
         // Run migration for existing databases that lack retry columns.
         Self::migrate_retry_columns(&conn);
@@ -89,12 +111,15 @@ pub fn open_in_memory() -> Result<Self> {
 
         conn.execute_batch(CREATE_TABLE_SQL)
             .map_err(|e| PresswerkError::Database(format!("create table: {e}")))?;
+        conn.execute_batch(CREATE_IPP_JOB_IDS_SQL)
+            .map_err(|e| PresswerkError::Database(format!("create ipp_job_ids: {e}")))?;
 
         debug!("in-memory job queue database opened");
         Ok(Self { conn })
     }
 
-    /// Apply retry/resume column migration to existing databases.
+    /// Apply retry/resume and IPP job-id column migration to existing
+    /// databases.
     /// Silently skips if columns already exist.
     fn migrate_retry_columns(conn: &Connection) {
         // Each ALTER TABLE is run individually — if the column exists the
@@ -116,52 +141,94 @@ fn migrate_retry_columns(conn: &Connection) {
     /// populated (they are set by `PrintJob::new`).
     #[instrument(skip(self, job), fields(job_id = %job.id))]
     pub fn insert_job(&self, job: &PrintJob) -> Result<()> {
-        let source_json = serde_json::to_string(&job.source)
-            .map_err(|e| PresswerkError::Database(format!("serialize source: {e}")))?;
-        let status_json = serde_json::to_string(&job.status)
-            .map_err(|e| PresswerkError::Database(format!("serialize status: {e}")))?;
-        let doc_type_json = serde_json::to_string(&job.document_type)
-            .map_err(|e| PresswerkError::Database(format!("serialize document_type: {e}")))?;
-        let settings_json = serde_json::to_string(&job.settings)
-            .map_err(|e| PresswerkError::Database(format!("serialize settings: {e}")))?;
+        insert_row(&self.conn, job)?;
+        info!(job_id = %job.id, "job inserted into queue");
+        Ok(())
+    }
 
-        let error_class_json = job
-            .error_class
-            .as_ref()
-            .map(|ec| serde_json::to_string(ec).unwrap_or_default());
-        let error_history_json = serde_json::to_string(&job.error_history)
-            .map_err(|e| PresswerkError::Database(format!("serialize error_history: {e}")))?;
+    /// Insert a new job together with the document it prints, so that
+    /// neither is kept without the other.
+    ///
+    /// `store_fn` writes the document and returns how to undo that write.
+    /// It runs inside the transaction that inserts the row, before the
+    /// insert; if the insert or the commit then fails, the undo runs and the
+    /// error is returned.  If `store_fn` itself fails nothing was written
+    /// and the row is never inserted.
+    ///
+    /// A process killed between the store and the commit leaves the document
+    /// behind with no job; [`DocumentStore::sweep_orphans`] removes it at the
+    /// next startup.
+    ///
+    /// [`DocumentStore::sweep_orphans`]: crate::document_store::DocumentStore::sweep_orphans
+    #[instrument(skip(self, job, store_fn), fields(job_id = %job.id))]
+    pub fn insert_job_tx<S, U>(&self, job: &PrintJob, store_fn: S) -> Result<()>
+    where
+        S: FnOnce() -> Result<U>,
+        U: FnOnce(),
+    {
+        self.store_and_insert(&job.id, store_fn, |tx| insert_row(tx, job))?;
+        info!(job_id = %job.id, "job and document inserted into queue");
+        Ok(())
+    }
 
-        self.conn
-            .execute(
-                "INSERT INTO jobs (id, source, status, document_type, document_name,
-                 document_hash, settings, printer_uri, created_at, updated_at, error_message,
-                 retry_count, max_retries, error_class, error_history, bytes_sent, total_bytes)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
-                params![
-                    job.id.to_string(),
-                    source_json,
-                    status_json,
-                    doc_type_json,
-                    job.document_name,
-                    job.document_hash,
-                    settings_json,
-                    job.printer_uri,
-                    job.created_at.to_rfc3339(),
-                    job.updated_at.to_rfc3339(),
-                    job.error_message,
-                    job.retry_count,
-                    job.max_retries,
-                    error_class_json,
-                    error_history_json,
-                    job.bytes_sent as i64,
-                    job.total_bytes as i64,
-                ],
-            )
-            .map_err(|e| PresswerkError::Database(format!("insert job: {e}")))?;
+    /// Like [`insert_job_tx`](Self::insert_job_tx), for a job submitted over
+    /// IPP or LPD: its IPP job-id is assigned in the same transaction, set on
+    /// `job` and returned.  If the insert fails no id is used up.
+    #[instrument(skip(self, job, store_fn), fields(job_id = %job.id))]
+    pub fn insert_ipp_job_tx<S, U>(&self, job: &mut PrintJob, store_fn: S) -> Result<i32>
+    where
+        S: FnOnce() -> Result<U>,
+        U: FnOnce(),
+    {
+        let job_id = job.id;
+        let previous = job.ipp_job_id;
+        let inserted = self.store_and_insert(&job_id, store_fn, |tx| {
+            let ipp_id = assign_ipp_id(tx, &job_id)?;
+            job.ipp_job_id = Some(ipp_id);
+            insert_row(tx, job).map(|()| ipp_id)
+        });
+        match inserted {
+            Ok(ipp_id) => {
+                info!(job_id = %job_id, ipp_id, "job and document inserted into queue");
+                Ok(ipp_id)
+            }
+            Err(e) => {
+                job.ipp_job_id = previous;
+                Err(e)
+            }
+        }
+    }
 
-        info!(job_id = %job.id, "job inserted into queue");
-        Ok(())
+    /// Run `store_fn`, then `write` and the commit in one transaction,
+    /// undoing the store if either of those fails.
+    fn store_and_insert<S, U, T>(
+        &self,
+        job_id: &JobId,
+        store_fn: S,
+        write: impl FnOnce(&Connection) -> Result<T>,
+    ) -> Result<T>
+    where
+        S: FnOnce() -> Result<U>,
+        U: FnOnce(),
+    {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| PresswerkError::Database(format!("begin insert job: {e}")))?;
+
+        // Dropping `tx` on this early return rolls it back.
+        let undo = store_fn()?;
+
+        let committed = write(&tx).and_then(|value| {
+            tx.commit()
+                .map(|()| value)
+                .map_err(|e| PresswerkError::Database(format!("commit insert job: {e}")))
+        });
+        if let Err(e) = &committed {
+            warn!(job_id = %job_id, error = %e, "job insert failed; undoing document store");
+            undo();
+        }
+        committed
     }
 
     /// Update the status (and optionally the error message) of an existing job.
@@ -195,6 +262,95 @@ pub fn update_status(
         Ok(())
     }
 
+    /// Append delivery attempts to a job's attempt log.
+    ///
+    /// The job's `error_class` follows the last attempt: its class if it
+    /// failed, cleared if it was delivered.
+    #[instrument(skip(self, attempts), fields(job_id = %job_id, count = attempts.len()))]
+    pub fn record_attempts(&self, job_id: &JobId, attempts: &[AttemptRecord]) -> Result<()> {
+        let mut job = self
+            .get_job(job_id)?
+            .ok_or_else(|| PresswerkError::Database(format!("job {job_id} not found")))?;
+        job.attempts.extend_from_slice(attempts);
+        if let Some(last) = attempts.last() {
+            job.error_class = match &last.outcome {
+                AttemptOutcome::Delivered => None,
+                AttemptOutcome::Failed { error_class, .. } => *error_class,
+            };
+        }
+        let attempts_json = serde_json::to_string(&job.attempts)
+            .map_err(|e| PresswerkError::Database(format!("serialize attempts: {e}")))?;
+        let error_class_json = job
+            .error_class
+            .map(|class| serde_json::to_string(&class))
+            .transpose()
+            .map_err(|e| PresswerkError::Database(format!("serialize error_class: {e}")))?;
+
+        self.conn
+            .execute(
+                "UPDATE jobs SET attempts = ?1, error_class = ?2, updated_at = ?3 WHERE id = ?4",
+                params![
+                    attempts_json,
+                    error_class_json,
+                    Utc::now().to_rfc3339(),
+                    job_id.to_string()
+                ],
+            )
+            .map_err(|e| PresswerkError::Database(format!("record attempts: {e}")))?;
+
+        debug!(job_id = %job_id, total = job.attempts.len(), "attempts recorded");
+        Ok(())
+    }
+
+    /// Record how a job failed when it failed before any delivery attempt,
+    /// e.g. because the printer reported itself stopped, so it can still be
+    /// revived or retried by class.
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub fn set_error_class(&self, job_id: &JobId, error_class: Option<ErrorClass>) -> Result<()> {
+        let error_class_json = error_class
+            .map(|class| serde_json::to_string(&class))
+            .transpose()
+            .map_err(|e| PresswerkError::Database(format!("serialize error_class: {e}")))?;
+
+        let rows = self
+            .conn
+            .execute(
+                "UPDATE jobs SET error_class = ?1, updated_at = ?2 WHERE id = ?3",
+                params![error_class_json, Utc::now().to_rfc3339(), job_id.to_string()],
+            )
+            .map_err(|e| PresswerkError::Database(format!("set error_class: {e}")))?;
+
+        if rows == 0 {
+            return Err(PresswerkError::Database(format!("job {job_id} not found")));
+        }
+        Ok(())
+    }
+
+    /// Put a failed or interrupted job back to `Pending` for another try,
+    /// counting the retry and clearing its error message.
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub fn revive_job(&self, job_id: &JobId) -> Result<()> {
+        let pending_json = serde_json::to_string(&JobStatus::Pending)
+            .map_err(|e| PresswerkError::Database(format!("serialize Pending: {e}")))?;
+
+        let rows = self
+            .conn
+            .execute(
+                "UPDATE jobs SET status = ?1, retry_count = retry_count + 1,
+                        error_message = NULL, updated_at = ?2
+                 WHERE id = ?3",
+                params![pending_json, Utc::now().to_rfc3339(), job_id.to_string()],
+            )
+            .map_err(|e| PresswerkError::Database(format!("revive job: {e}")))?;
+
+        if rows == 0 {
+            return Err(PresswerkError::Database(format!("job {job_id} not found")));
+        }
+
+        debug!(job_id = %job_id, "job revived");
+        Ok(())
+    }
+
     /// Retrieve a single job by its ID.
     ///
     /// Returns `None` if the job does not exist.
@@ -206,7 +362,8 @@ pub fn get_job(&self, job_id: &JobId) -> Result<Option<PrintJob>> {
                 "SELECT id, source, status, document_type, document_name,
                         document_hash, settings, printer_uri, created_at,
                         updated_at, error_message, retry_count, max_retries,
-                        error_class, error_history, bytes_sent, total_bytes
+                        error_class, error_history, bytes_sent, total_bytes,
+                        ipp_job_id, attempts
                  FROM jobs WHERE id = ?1",
             )
             .map_err(|e| PresswerkError::Database(format!("prepare get_job: {e}")))?;
@@ -231,7 +388,8 @@ pub fn get_all_jobs(&self) -> Result<Vec<PrintJob>> {
                 "SELECT id, source, status, document_type, document_name,
                         document_hash, settings, printer_uri, created_at,
                         updated_at, error_message, retry_count, max_retries,
-                        error_class, error_history, bytes_sent, total_bytes
+                        error_class, error_history, bytes_sent, total_bytes,
+                        ipp_job_id, attempts
                  FROM jobs ORDER BY created_at DESC",
             )
             .map_err(|e| PresswerkError::Database(format!("prepare get_all_jobs: {e}")))?;
@@ -250,8 +408,44 @@ pub fn get_all_jobs(&self) -> Result<Vec<PrintJob>> {
     /// (oldest first, i.e. FIFO).
     #[instrument(skip(self))]
     pub fn get_pending_jobs(&self) -> Result<Vec<PrintJob>> {
-        let pending_json = serde_json::to_string(&JobStatus::Pending)
-            .map_err(|e| PresswerkError::Database(format!("serialize Pending: {e}")))?;
+        let jobs = self.jobs_with_status(JobStatus::Pending)?;
+        debug!(count = jobs.len(), "retrieved pending jobs");
+        Ok(jobs)
+    }
+
+    /// Recover jobs left in `Processing` by a crash: each goes back to
+    /// `Pending` if it has retries left (counting the interrupted send as
+    /// one) and failed for no permanent reason, and to `Failed` otherwise.
+    /// Returns how many jobs were reset.
+    ///
+    /// Call at startup, before anything starts sending: every job still in
+    /// `Processing` then belongs to a process that is gone, however recently
+    /// it was updated.
+    #[instrument(skip(self))]
+    pub fn reset_stuck_processing(&self) -> Result<usize> {
+        let stuck = self.jobs_with_status(JobStatus::Processing)?;
+
+        for job in &stuck {
+            let retryable =
+                job.retry_count < job.max_retries && job.error_class != Some(ErrorClass::Permanent);
+            if retryable {
+                self.revive_job(&job.id)?;
+            } else {
+                self.update_status(
+                    &job.id,
+                    JobStatus::Failed,
+                    Some("interrupted while printing"),
+                )?;
+            }
+            info!(job_id = %job.id, retryable, "reset job stuck in Processing");
+        }
+        Ok(stuck.len())
+    }
+
+    /// All jobs with `status`, oldest first.
+    fn jobs_with_status(&self, status: JobStatus) -> Result<Vec<PrintJob>> {
+        let status_json = serde_json::to_string(&status)
+            .map_err(|e| PresswerkError::Database(format!("serialize {status:?}: {e}")))?;
 
         let mut stmt = self
             .conn
@@ -259,19 +453,32 @@ pub fn get_pending_jobs(&self) -> Result<Vec<PrintJob>> {
                 "SELECT id, source, status, document_type, document_name,
                         document_hash, settings, printer_uri, created_at,
                         updated_at, error_message, retry_count, max_retries,
-                        error_class, error_history, bytes_sent, total_bytes
+                        error_class, error_history, bytes_sent, total_bytes,
+                        ipp_job_id, attempts
                  FROM jobs WHERE status = ?1 ORDER BY created_at ASC",
             )
-            .map_err(|e| PresswerkError::Database(format!("prepare get_pending: {e}")))?;
+            .map_err(|e| PresswerkError::Database(format!("prepare jobs_with_status: {e}")))?;
 
-        let jobs = stmt
-            .query_map(params![pending_json], row_to_print_job)
-            .map_err(|e| PresswerkError::Database(format!("query get_pending: {e}")))?
+        stmt.query_map(params![status_json], row_to_print_job)
+            .map_err(|e| PresswerkError::Database(format!("query jobs_with_status: {e}")))?
             .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|e| PresswerkError::Database(format!("collect rows: {e}")))?;
+            .map_err(|e| PresswerkError::Database(format!("collect rows: {e}")))
+    }
 
-        debug!(count = jobs.len(), "retrieved pending jobs");
-        Ok(jobs)
+    /// Ids of every job in the queue, whatever its status.
+    #[instrument(skip(self))]
+    pub fn job_ids(&self) -> Result<HashSet<JobId>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM jobs")
+            .map_err(|e| PresswerkError::Database(format!("prepare job_ids: {e}")))?;
+
+        let ids = stmt
+            .query_map([], row_to_job_id)
+            .map_err(|e| PresswerkError::Database(format!("query job_ids: {e}")))?
+            .collect::<std::result::Result<HashSet<_>, _>>()
+            .map_err(|e| PresswerkError::Database(format!("collect rows: {e}")))?;
+        Ok(ids)
     }
 
     /// Delete a job from the queue.
@@ -285,16 +492,171 @@ pub fn delete_job(&self, job_id: &JobId) -> Result<()> {
                 params![job_id.to_string()],
             )
             .map_err(|e| PresswerkError::Database(format!("delete job: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM ipp_job_ids WHERE job_id = ?1",
+                params![job_id.to_string()],
+            )
+            .map_err(|e| PresswerkError::Database(format!("delete ipp job id: {e}")))?;
 
         info!(job_id = %job_id, "job deleted from queue");
         Ok(())
     }
+
+    // -- IPP job ids --------------------------------------------------------
+
+    /// Return the IPP integer job-id for `job_id`, assigning the next free
+    /// one if the job has none yet.
+    ///
+    /// Ids are persisted, so they stay stable across server restarts.
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub fn assign_ipp_job_id(&self, job_id: &JobId) -> Result<i32> {
+        assign_ipp_id(&self.conn, job_id)
+    }
+
+    /// Resolve an IPP integer job-id to the internal `JobId`.
+    ///
+    /// Returns `None` if the id was never assigned.
+    #[instrument(skip(self))]
+    pub fn job_id_for_ipp_id(&self, ipp_id: i32) -> Result<Option<JobId>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT job_id FROM ipp_job_ids WHERE ipp_id = ?1")
+            .map_err(|e| PresswerkError::Database(format!("prepare job_id_for_ipp_id: {e}")))?;
+
+        let mut rows = stmt
+            .query_map(params![ipp_id], row_to_job_id)
+            .map_err(|e| PresswerkError::Database(format!("query job_id_for_ipp_id: {e}")))?;
+
+        match rows.next() {
+            Some(Ok(id)) => Ok(Some(id)),
+            Some(Err(e)) => Err(PresswerkError::Database(format!("row parse: {e}"))),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieve the job submitted over IPP with the given integer job-id.
+    ///
+    /// Returns `None` if no job carries that id.
+    #[instrument(skip(self))]
+    pub fn get_job_by_ipp_id(&self, ipp_id: i32) -> Result<Option<PrintJob>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, source, status, document_type, document_name,
+                        document_hash, settings, printer_uri, created_at,
+                        updated_at, error_message, retry_count, max_retries,
+                        error_class, error_history, bytes_sent, total_bytes,
+                        ipp_job_id, attempts
+                 FROM jobs WHERE ipp_job_id = ?1",
+            )
+            .map_err(|e| PresswerkError::Database(format!("prepare get_job_by_ipp_id: {e}")))?;
+
+        let mut rows = stmt
+            .query_map(params![ipp_id], row_to_print_job)
+            .map_err(|e| PresswerkError::Database(format!("query get_job_by_ipp_id: {e}")))?;
+
+        match rows.next() {
+            Some(Ok(job)) => Ok(Some(job)),
+            Some(Err(e)) => Err(PresswerkError::Database(format!("row parse: {e}"))),
+            None => Ok(None),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Row mapping
 // ---------------------------------------------------------------------------
 
+/// Insert the row for `job` (on a connection or within a transaction).
+fn insert_row(conn: &Connection, job: &PrintJob) -> Result<()> {
+    let source_json = serde_json::to_string(&job.source)
+        .map_err(|e| PresswerkError::Database(format!("serialize source: {e}")))?;
+    let status_json = serde_json::to_string(&job.status)
+        .map_err(|e| PresswerkError::Database(format!("serialize status: {e}")))?;
+    let doc_type_json = serde_json::to_string(&job.document_type)
+        .map_err(|e| PresswerkError::Database(format!("serialize document_type: {e}")))?;
+    let settings_json = serde_json::to_string(&job.settings)
+        .map_err(|e| PresswerkError::Database(format!("serialize settings: {e}")))?;
+
+    let error_class_json = job
+        .error_class
+        .as_ref()
+        .map(|ec| serde_json::to_string(ec).unwrap_or_default());
+    let error_history_json = serde_json::to_string(&job.error_history)
+        .map_err(|e| PresswerkError::Database(format!("serialize error_history: {e}")))?;
+    let attempts_json = serde_json::to_string(&job.attempts)
+        .map_err(|e| PresswerkError::Database(format!("serialize attempts: {e}")))?;
+
+    conn.execute(
+        "INSERT INTO jobs (id, source, status, document_type, document_name,
+         document_hash, settings, printer_uri, created_at, updated_at, error_message,
+         retry_count, max_retries, error_class, error_history, bytes_sent, total_bytes,
+         ipp_job_id, attempts)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                 ?18, ?19)",
+        params![
+            job.id.to_string(),
+            source_json,
+            status_json,
+            doc_type_json,
+            job.document_name,
+            job.document_hash,
+            settings_json,
+            job.printer_uri,
+            job.created_at.to_rfc3339(),
+            job.updated_at.to_rfc3339(),
+            job.error_message,
+            job.retry_count,
+            job.max_retries,
+            error_class_json,
+            error_history_json,
+            job.bytes_sent as i64,
+            job.total_bytes as i64,
+            job.ipp_job_id,
+            attempts_json,
+        ],
+    )
+    .map_err(|e| PresswerkError::Database(format!("insert job: {e}")))?;
+    Ok(())
+}
+
+/// Return the IPP job-id for `job_id`, assigning the next free one if the
+/// job has none yet (on a connection or within a transaction).
+fn assign_ipp_id(conn: &Connection, job_id: &JobId) -> Result<i32> {
+    let existing = conn
+        .query_row(
+            "SELECT ipp_id FROM ipp_job_ids WHERE job_id = ?1",
+            params![job_id.to_string()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| PresswerkError::Database(format!("read ipp job id: {e}")))?;
+    if let Some(ipp_id) = existing {
+        return Ok(ipp_id);
+    }
+
+    // `OR IGNORE` would still consume a sequence number on conflict, so
+    // insert only after the lookup above.
+    conn.execute(
+        "INSERT INTO ipp_job_ids (job_id) VALUES (?1)",
+        params![job_id.to_string()],
+    )
+    .map_err(|e| PresswerkError::Database(format!("assign ipp job id: {e}")))?;
+    let ipp_id = conn.last_insert_rowid() as i32;
+
+    debug!(job_id = %job_id, ipp_id, "IPP job id assigned");
+    Ok(ipp_id)
+}
+
+/// Read a `JobId` stored as text in column 0.
+fn row_to_job_id(row: &rusqlite::Row<'_>) -> rusqlite::Result<JobId> {
+    let id_str: String = row.get(0)?;
+    id_str.parse::<JobId>().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
 /// Map a SQLite row to a `PrintJob`.
 ///
 /// Column indices must match the SELECT order used in the query methods above.
@@ -316,10 +678,12 @@ fn row_to_print_job(row: &rusqlite::Row<'_>) -> rusqlite::Result<PrintJob> {
     let error_history_json: String = row.get::<_, String>(14).unwrap_or_else(|_| "[]".into());
     let bytes_sent: u64 = row.get::<_, i64>(15).unwrap_or(0) as u64;
     let total_bytes: u64 = row.get::<_, i64>(16).unwrap_or(0) as u64;
+    let ipp_job_id: Option<i32> = row.get(17).unwrap_or(None);
+    let attempts_json: String = row.get::<_, String>(18).unwrap_or_else(|_| "[]".into());
 
     // Parse the UUID.  If the stored value is malformed we surface a
     // meaningful error rather than panicking.
-    let uuid = uuid::Uuid::parse_str(&id_str).map_err(|e| {
+    let id = id_str.parse::<JobId>().map_err(|e| {
         rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
     })?;
 
@@ -357,8 +721,10 @@ fn row_to_print_job(row: &rusqlite::Row<'_>) -> rusqlite::Result<PrintJob> {
     let error_history: Vec<String> =
         serde_json::from_str(&error_history_json).unwrap_or_default();
 
+    let attempts: Vec<AttemptRecord> = serde_json::from_str(&attempts_json).unwrap_or_default();
+
     Ok(PrintJob {
-        id: JobId(uuid),
+        id,
         source,
         status,
         document_type,
@@ -375,6 +741,8 @@ fn row_to_print_job(row: &rusqlite::Row<'_>) -> rusqlite::Result<PrintJob> {
         error_history,
         bytes_sent,
         total_bytes,
+        ipp_job_id,
+        attempts,
     })
 }
 
@@ -435,6 +803,83 @@ fn update_status_with_error() {
         assert_eq!(updated.error_message.as_deref(), Some("paper jam"));
     }
 
+    #[test]
+    fn record_attempts_classifies_the_last_failure() {
+        use presswerk_core::types::PrintProtocol;
+
+        let queue = JobQueue::open_in_memory().expect("open in-memory db");
+        let job = test_job();
+        queue.insert_job(&job).expect("insert");
+
+        let jammed = PresswerkError::IppRequest("printer is stopped: media-jam".into());
+        queue
+            .record_attempts(
+                &job.id,
+                &[AttemptRecord::failed(PrintProtocol::Ipp11, &jammed)],
+            )
+            .expect("record");
+        let stored = queue.get_job(&job.id).expect("get_job").expect("found");
+        assert_eq!(stored.error_class, Some(ErrorClass::UserAction));
+
+        queue
+            .record_attempts(&job.id, &[AttemptRecord::delivered(PrintProtocol::Ipp11)])
+            .expect("record");
+        let stored = queue.get_job(&job.id).expect("get_job").expect("found");
+        assert_eq!(stored.error_class, None);
+        assert_eq!(stored.attempts.len(), 2);
+    }
+
+    #[test]
+    fn set_error_class_without_attempts() {
+        let queue = JobQueue::open_in_memory().expect("open in-memory db");
+        let job = test_job();
+        queue.insert_job(&job).expect("insert");
+
+        queue
+            .set_error_class(&job.id, Some(ErrorClass::Transient))
+            .expect("set");
+        let stored = queue.get_job(&job.id).expect("get_job").expect("found");
+        assert_eq!(stored.error_class, Some(ErrorClass::Transient));
+        assert!(stored.attempts.is_empty());
+
+        assert!(queue.set_error_class(&JobId::new(), None).is_err());
+    }
+
+    /// Put `job_id` in `Processing`, last updated `age` ago.
+    fn set_processing_since(queue: &JobQueue, job_id: &JobId, age: chrono::Duration) {
+        let status = serde_json::to_string(&JobStatus::Processing).unwrap();
+        queue
+            .conn
+            .execute(
+                "UPDATE jobs SET status = ?1, updated_at = ?2 WHERE id = ?3",
+                params![status, (Utc::now() - age).to_rfc3339(), job_id.to_string()],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn reset_stuck_processing_requeues_every_processing_job() {
+        let queue = JobQueue::open_in_memory().expect("open in-memory db");
+        let stale = test_job();
+        let fresh = test_job();
+        let mut exhausted = test_job();
+        exhausted.retry_count = exhausted.max_retries;
+        for job in [&stale, &fresh, &exhausted] {
+            queue.insert_job(job).expect("insert");
+        }
+        set_processing_since(&queue, &stale.id, chrono::Duration::hours(1));
+        set_processing_since(&queue, &fresh.id, chrono::Duration::seconds(10));
+        set_processing_since(&queue, &exhausted.id, chrono::Duration::hours(1));
+
+        assert_eq!(queue.reset_stuck_processing().expect("reset"), 3);
+
+        let status = |id: &JobId| queue.get_job(id).unwrap().unwrap().status;
+        assert_eq!(status(&stale.id), JobStatus::Pending);
+        assert_eq!(queue.get_job(&stale.id).unwrap().unwrap().retry_count, 1);
+        assert_eq!(status(&fresh.id), JobStatus::Pending);
+        assert_eq!(status(&exhausted.id), JobStatus::Failed);
+    }
+
     #[test]
     fn get_all_jobs_returns_newest_first() {
         let queue = JobQueue::open_in_memory().expect("open in-memory db");
@@ -497,4 +942,112 @@ fn update_nonexistent_job_returns_error() {
         let result = queue.update_status(&JobId::new(), JobStatus::Cancelled, None);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn ipp_job_ids_survive_reopen_and_are_never_reused() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("jobs.db");
+        let (first, second) = (test_job(), test_job());
+
+        {
+            let queue = JobQueue::open(&path).expect("open");
+            queue.insert_job(&first).expect("insert");
+            assert_eq!(queue.assign_ipp_job_id(&first.id).expect("assign"), 1);
+            // Assigning again returns the existing id.
+            assert_eq!(queue.assign_ipp_job_id(&first.id).expect("assign"), 1);
+        }
+
+        let queue = JobQueue::open(&path).expect("reopen");
+        assert_eq!(queue.job_id_for_ipp_id(1).expect("lookup"), Some(first.id));
+
+        queue.delete_job(&first.id).expect("delete");
+        assert_eq!(queue.job_id_for_ipp_id(1).expect("lookup"), None);
+        assert_eq!(queue.assign_ipp_job_id(&second.id).expect("assign"), 2);
+        assert_eq!(queue.job_id_for_ipp_id(2).expect("lookup"), Some(second.id));
+    }
+
+    #[test]
+    fn ipp_job_id_round_trips_and_finds_the_job() {
+        let queue = JobQueue::open_in_memory().expect("open in-memory db");
+        let mut job = test_job();
+        job.ipp_job_id = Some(queue.assign_ipp_job_id(&job.id).expect("assign"));
+        queue.insert_job(&job).expect("insert");
+        queue.insert_job(&test_job()).expect("insert local job");
+
+        let found = queue
+            .get_job_by_ipp_id(1)
+            .expect("get_job_by_ipp_id")
+            .expect("found");
+        assert_eq!(found.id, job.id);
+        assert_eq!(found.ipp_job_id, Some(1));
+        assert!(queue.get_job_by_ipp_id(2).expect("lookup").is_none());
+    }
+
+    #[test]
+    fn failed_row_insert_removes_the_just_stored_document() {
+        use crate::document_store::DocumentStore;
+
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let store = &DocumentStore::open(dir.path()).expect("open store");
+        let queue = JobQueue::open_in_memory().expect("open in-memory db");
+
+        let job = test_job();
+        queue.insert_job(&job).expect("insert");
+
+        // Same id again: the row insert hits the primary key and fails after
+        // the document has been written.
+        let store_for = |job_id: JobId, data: &'static [u8], hash: &mut Option<String>| {
+            let stored = store.put_for_job(&job_id, data)?;
+            *hash = Some(stored.clone());
+            Ok(move || {
+                let _ = store.release(&stored, &job_id);
+            })
+        };
+
+        let mut hash = None;
+        let result = queue.insert_job_tx(&job, || store_for(job.id, b"second copy", &mut hash));
+        assert!(result.is_err());
+        let hash = hash.expect("document was stored before the insert");
+        assert!(!store.contains(&hash), "orphaned document left behind");
+        assert_eq!(store.ref_count(&hash), 0);
+
+        // A clean insert keeps both.
+        let fresh = test_job();
+        let mut fresh_hash = None;
+        queue
+            .insert_job_tx(&fresh, || store_for(fresh.id, b"fresh", &mut fresh_hash))
+            .expect("insert_job_tx");
+        assert!(queue.get_job(&fresh.id).expect("get_job").is_some());
+        assert_eq!(store.ref_count(&fresh_hash.expect("stored")), 1);
+    }
+
+    #[test]
+    fn failed_ipp_insert_uses_up_no_job_id() {
+        let queue = JobQueue::open_in_memory().expect("open in-memory db");
+        let mut job = test_job();
+        queue.insert_job(&job).expect("insert");
+
+        // The row insert fails on the duplicate id, taking the IPP id with it.
+        let mut undone = false;
+        let result = queue.insert_ipp_job_tx(&mut job, || Ok(|| undone = true));
+        assert!(result.is_err());
+        assert!(undone);
+        assert_eq!(job.ipp_job_id, None);
+        assert_eq!(queue.job_id_for_ipp_id(1).expect("lookup"), None);
+
+        let mut fresh = test_job();
+        assert_eq!(
+            queue
+                .insert_ipp_job_tx(&mut fresh, || Ok(|| {}))
+                .expect("insert_ipp_job_tx"),
+            1
+        );
+        assert_eq!(fresh.ipp_job_id, Some(1));
+        let found = queue
+            .get_job_by_ipp_id(1)
+            .expect("get_job_by_ipp_id")
+            .expect("found");
+        assert_eq!(found.id, fresh.id);
+        assert_eq!(queue.job_ids().expect("job_ids").len(), 2);
+    }
 }
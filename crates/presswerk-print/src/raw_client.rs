@@ -5,16 +5,21 @@
 //
 // The simplest possible print protocol: open a TCP socket and dump bytes.
 // This is the ultimate fallback for printers that don't speak IPP or LPR.
-// No settings, no job tracking, no feedback — just raw data transmission.
-// The printer must be able to interpret the document format natively.
+// No settings, no job tracking — just raw data transmission.  The printer
+// must be able to interpret the document format natively.
+//
+// The only feedback available is PJL (HP's Printer Job Language): after the
+// document a client may send `@PJL INFO STATUS` and read back the printer's
+// status code and panel message.  Printers without PJL simply never answer.
 
 use std::time::Duration;
 
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tracing::{debug, info};
 
 use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::DocumentType;
 
 /// Default raw TCP port (HP JetDirect).
 pub const RAW_PORT: u16 = 9100;
@@ -22,19 +27,181 @@
 /// Timeout for raw TCP operations.
 const RAW_TIMEOUT_SECS: u64 = 60;
 
+/// How long to wait for a PJL status reply before assuming the printer does
+/// not speak PJL.
+const PJL_STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Universal Exit Language: resets the printer into PJL mode.
+const PJL_UEL: &[u8] = b"\x1b%-12345X";
+
+/// Ends a PJL reply (form feed).
+const PJL_REPLY_END: u8 = 0x0C;
+
+/// Largest PJL reply read.
+const MAX_PJL_REPLY_BYTES: usize = 4096;
+
+/// Whether a printer reached over raw TCP can be expected to print
+/// `document_type` as it is: PostScript, PCL and plain text.
+///
+/// Images need rasterizing first, and a printer that takes PDF over a raw
+/// socket almost always speaks IPP too, while a PCL-only one would print the
+/// PDF source as text.
+pub fn is_raw_printable(document_type: DocumentType) -> bool {
+    matches!(
+        document_type,
+        DocumentType::PostScript | DocumentType::Pcl | DocumentType::PlainText
+    )
+}
+
+/// Printer status as reported by `@PJL INFO STATUS`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PjlStatus {
+    /// Numeric status code (`10001` is "ready" on most printers).
+    pub code: Option<u32>,
+    /// The text on the printer's control panel.
+    pub display: Option<String>,
+    /// Whether the printer reports itself online.
+    pub online: Option<bool>,
+}
+
+impl PjlStatus {
+    /// Parse the reply to `@PJL INFO STATUS`.  Returns `None` if it holds no
+    /// status line at all.
+    pub fn parse(reply: &str) -> Option<Self> {
+        let mut status = Self::default();
+        for line in reply.lines() {
+            let Some((key, value)) = line.trim().split_once('=') else {
+                continue;
+            };
+            match key.trim().to_ascii_uppercase().as_str() {
+                "CODE" => status.code = value.trim().parse().ok(),
+                "DISPLAY" => status.display = Some(value.trim().trim_matches('"').to_string()),
+                "ONLINE" => status.online = Some(value.trim().eq_ignore_ascii_case("TRUE")),
+                _ => {}
+            }
+        }
+        (status != Self::default()).then_some(status)
+    }
+}
+
+/// Client for raw socket (JetDirect) printing.
+#[derive(Debug, Clone)]
+pub struct RawClient {
+    /// Timeout for connecting and for sending the document.
+    timeout: Duration,
+}
+
+impl Default for RawClient {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(RAW_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl RawClient {
+    /// Create a client with the default timeout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `timeout` for connecting and for sending the document.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Stream `document_bytes` to `host`:`port` (usually [`RAW_PORT`]).
+    pub async fn print(&self, host: &str, port: u16, document_bytes: &[u8]) -> Result<()> {
+        let mut stream = self.connect(host, port).await?;
+        self.write_document(&mut stream, document_bytes, 0).await?;
+        close(&mut stream).await?;
+        info!(
+            total = document_bytes.len(),
+            "raw TCP print job sent successfully"
+        );
+        Ok(())
+    }
+
+    /// Like [`print`](Self::print), then ask the printer for its PJL status.
+    ///
+    /// Returns `None` when the printer does not answer the query, as
+    /// printers without PJL never do.
+    pub async fn print_with_status(
+        &self,
+        host: &str,
+        port: u16,
+        document_bytes: &[u8],
+    ) -> Result<Option<PjlStatus>> {
+        let mut stream = self.connect(host, port).await?;
+        self.write_document(&mut stream, document_bytes, 0).await?;
+
+        let query = [PJL_UEL, b"@PJL INFO STATUS\r\n", PJL_UEL].concat();
+        stream
+            .write_all(&query)
+            .await
+            .map_err(|e| PresswerkError::IppRequest(format!("Raw TCP PJL query: {e}")))?;
+        let status = read_pjl_status(&mut stream).await;
+        debug!(?status, "PJL status");
+
+        close(&mut stream).await?;
+        info!(
+            total = document_bytes.len(),
+            "raw TCP print job sent successfully"
+        );
+        Ok(status)
+    }
+
+    /// Open the connection to `host`:`port`.
+    async fn connect(&self, host: &str, port: u16) -> Result<TcpStream> {
+        info!(host, port, "connecting via raw TCP");
+        tokio::time::timeout(self.timeout, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| {
+                PresswerkError::IppRequest(format!(
+                    "Raw TCP connection to {host}:{port} timed out after {}s",
+                    self.timeout.as_secs()
+                ))
+            })?
+            .map_err(|e| {
+                PresswerkError::IppRequest(format!("Raw TCP connect to {host}:{port}: {e}"))
+            })
+    }
+
+    /// Write `document_bytes` from `offset` on, in chunks for progress
+    /// tracking.
+    async fn write_document(
+        &self,
+        stream: &mut TcpStream,
+        document_bytes: &[u8],
+        offset: usize,
+    ) -> Result<()> {
+        let chunk_size = 8192; // 8KB chunks for progress tracking
+
+        let mut sent = offset;
+        for chunk in document_bytes[offset..].chunks(chunk_size) {
+            tokio::time::timeout(self.timeout, stream.write_all(chunk))
+                .await
+                .map_err(|_| {
+                    PresswerkError::IppRequest(format!("Raw TCP send timed out at byte {sent}"))
+                })?
+                .map_err(|e| {
+                    PresswerkError::IppRequest(format!("Raw TCP send failed at byte {sent}: {e}"))
+                })?;
+            sent += chunk.len();
+            debug!(sent, total = document_bytes.len(), "raw TCP progress");
+        }
+        Ok(())
+    }
+}
+
 /// Send document bytes directly to a printer via raw TCP (port 9100).
 ///
 /// This is the lowest-level fallback. The printer must natively understand
 /// the document format — there's no protocol negotiation, no settings
 /// transmission, no job tracking.
-///
-/// Returns the number of bytes sent (for progress tracking/resumption).
-pub async fn send_raw(
-    ip: &str,
-    port: u16,
-    document_bytes: &[u8],
-) -> Result<()> {
-    send_raw_with_offset(ip, port, document_bytes, 0).await
+pub async fn send_raw(ip: &str, port: u16, document_bytes: &[u8]) -> Result<()> {
+    RawClient::new().print(ip, port, document_bytes).await
 }
 
 /// Send document bytes starting from a specific offset (for resumption).
@@ -44,47 +211,25 @@ pub async fn send_raw_with_offset(
     document_bytes: &[u8],
     offset: usize,
 ) -> Result<()> {
-    let addr = format!("{}:{}", ip, port);
+    let client = RawClient::new();
+    let mut stream = client.connect(ip, port).await?;
+    debug!(
+        total = document_bytes.len(),
+        offset, "resuming raw TCP send"
+    );
+    client
+        .write_document(&mut stream, document_bytes, offset)
+        .await?;
+    close(&mut stream).await?;
     info!(
-        addr = %addr,
         total = document_bytes.len(),
-        offset,
-        "connecting via raw TCP"
+        "raw TCP print job sent successfully"
     );
+    Ok(())
+}
 
-    let mut stream = tokio::time::timeout(
-        Duration::from_secs(RAW_TIMEOUT_SECS),
-        TcpStream::connect(&addr),
-    )
-    .await
-    .map_err(|_| {
-        PresswerkError::IppRequest(format!(
-            "Raw TCP connection to {} timed out after {}s",
-            addr, RAW_TIMEOUT_SECS
-        ))
-    })?
-    .map_err(|e| PresswerkError::IppRequest(format!("Raw TCP connect to {}: {}", addr, e)))?;
-
-    // Send data from offset (for resumption after partial send)
-    let remaining = &document_bytes[offset..];
-    let chunk_size = 8192; // 8KB chunks for progress tracking
-
-    let mut sent = offset;
-    for chunk in remaining.chunks(chunk_size) {
-        stream
-            .write_all(chunk)
-            .await
-            .map_err(|e| {
-                PresswerkError::IppRequest(format!(
-                    "Raw TCP send failed at byte {}: {}",
-                    sent, e
-                ))
-            })?;
-        sent += chunk.len();
-        debug!(sent, total = document_bytes.len(), "raw TCP progress");
-    }
-
-    // Flush and shutdown cleanly
+/// Flush and shut the connection down cleanly.
+async fn close(stream: &mut TcpStream) -> Result<()> {
     stream
         .flush()
         .await
@@ -92,11 +237,107 @@ pub async fn send_raw_with_offset(
     stream
         .shutdown()
         .await
-        .map_err(|e| PresswerkError::IppRequest(format!("Raw TCP shutdown: {e}")))?;
+        .map_err(|e| PresswerkError::IppRequest(format!("Raw TCP shutdown: {e}")))
+}
 
-    info!(
-        total = document_bytes.len(),
-        "raw TCP print job sent successfully"
-    );
-    Ok(())
+/// Read a PJL reply up to its form feed, giving up after
+/// [`PJL_STATUS_TIMEOUT`].
+async fn read_pjl_status(stream: &mut TcpStream) -> Option<PjlStatus> {
+    let mut reply = Vec::new();
+    let read = async {
+        let mut buf = [0u8; 512];
+        while !reply.contains(&PJL_REPLY_END) && reply.len() < MAX_PJL_REPLY_BYTES {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => reply.extend_from_slice(&buf[..n]),
+            }
+        }
+    };
+    if tokio::time::timeout(PJL_STATUS_TIMEOUT, read)
+        .await
+        .is_err()
+    {
+        debug!("no PJL status reply");
+    }
+    PjlStatus::parse(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn print_streams_the_document_to_the_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let printer = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            socket.read_to_end(&mut received).await.unwrap();
+            received
+        });
+
+        // Larger than one chunk, so it goes out in several writes.
+        let document: Vec<u8> = b"%!PS-Adobe-3.0\n"
+            .iter()
+            .copied()
+            .cycle()
+            .take(20_000)
+            .collect();
+        RawClient::new()
+            .print("127.0.0.1", port, &document)
+            .await
+            .unwrap();
+
+        assert_eq!(printer.await.unwrap(), document);
+    }
+
+    #[tokio::test]
+    async fn print_with_status_reads_the_pjl_reply() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let printer = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !received.ends_with(b"@PJL INFO STATUS\r\n\x1b%-12345X") {
+                let n = socket.read(&mut buf).await.unwrap();
+                assert_ne!(n, 0, "connection closed before the PJL query");
+                received.extend_from_slice(&buf[..n]);
+            }
+            socket
+                .write_all(
+                    b"@PJL INFO STATUS\r\nCODE=10001\r\nDISPLAY=\"Ready\"\r\nONLINE=TRUE\r\n\x0c",
+                )
+                .await
+                .unwrap();
+            received
+        });
+
+        let status = RawClient::new()
+            .print_with_status("127.0.0.1", port, b"\x1bEhello\x1bE")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            status,
+            Some(PjlStatus {
+                code: Some(10001),
+                display: Some("Ready".into()),
+                online: Some(true),
+            })
+        );
+        assert!(printer.await.unwrap().starts_with(b"\x1bEhello\x1bE"));
+    }
+
+    #[test]
+    fn pjl_status_ignores_replies_without_status() {
+        assert_eq!(PjlStatus::parse(""), None);
+        assert_eq!(PjlStatus::parse("@PJL INFO STATUS\r\n"), None);
+        assert_eq!(
+            PjlStatus::parse("CODE=40021\r\nONLINE=FALSE\r\n").and_then(|s| s.code),
+            Some(40021)
+        );
+    }
 }
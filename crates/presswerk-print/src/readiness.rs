@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Short-lived cache of printer readiness.
+//
+// Checking readiness costs a Get-Printer-Attributes round-trip, which makes
+// one-tap printing feel sluggish when the user prints several things in a
+// row.  `ReadinessCache` remembers a printer that was ready for a few
+// seconds so consecutive prints skip the query.  Only ready printers are
+// cached, and any failure (query error or failed print) drops the entry so
+// the next print checks again.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+use presswerk_core::error::Result;
+
+use crate::ipp_client::PrinterAttributes;
+use crate::revival;
+
+/// How long a ready printer is trusted without asking again.
+pub const DEFAULT_READINESS_TTL: Duration = Duration::from_secs(5);
+
+/// A printer's state as of `checked_at`.
+#[derive(Debug, Clone)]
+pub struct PrinterReadiness {
+    /// `printer-state` value (e.g. "idle", "processing", "stopped").
+    pub state: String,
+    /// `printer-state-reasons`, without "none".
+    pub reasons: Vec<String>,
+    /// When the printer was queried.
+    pub checked_at: Instant,
+}
+
+impl PrinterReadiness {
+    /// Query `printer_uri` now.
+    pub async fn check(printer_uri: &str) -> Result<Self> {
+        let (state, reasons) = revival::probe_status(printer_uri).await?;
+        Ok(Self {
+            state,
+            reasons,
+            checked_at: Instant::now(),
+        })
+    }
+
+    /// Read the state from Get-Printer-Attributes results already at hand.
+    pub fn from_attributes(attrs: &PrinterAttributes) -> Self {
+        let (state, reasons) = revival::status_from_attributes(attrs);
+        Self {
+            state,
+            reasons,
+            checked_at: Instant::now(),
+        }
+    }
+
+    /// The parsed `printer-state`, or `None` if the printer sent something
+    /// else.
+    pub fn printer_state(&self) -> Option<PrinterState> {
+        PrinterState::parse(&self.state)
+    }
+
+    /// Whether the printer reported itself stopped (`printer-state` 5).
+    pub fn is_stopped(&self) -> bool {
+        self.printer_state() == Some(PrinterState::Stopped)
+    }
+
+    /// Whether the printer reported itself idle (`printer-state` 3), i.e.
+    /// free to take a job now.
+    pub fn is_idle(&self) -> bool {
+        self.printer_state() == Some(PrinterState::Idle)
+    }
+}
+
+/// IPP `printer-state` (RFC 8011 §5.4.11).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrinterState {
+    Idle,
+    Processing,
+    Stopped,
+}
+
+impl PrinterState {
+    /// Parse a `printer-state` value, given either as its enum number or as
+    /// its keyword.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.parse::<i32>() {
+            Ok(3) => Some(Self::Idle),
+            Ok(4) => Some(Self::Processing),
+            Ok(5) => Some(Self::Stopped),
+            Ok(_) => None,
+            Err(_) if value.eq_ignore_ascii_case("idle") => Some(Self::Idle),
+            Err(_) if value.eq_ignore_ascii_case("processing") => Some(Self::Processing),
+            Err(_) if value.eq_ignore_ascii_case("stopped") => Some(Self::Stopped),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Per-printer readiness results, trusted for a fixed time-to-live.
+pub struct ReadinessCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, PrinterReadiness>>,
+}
+
+impl Default for ReadinessCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_READINESS_TTL)
+    }
+}
+
+impl ReadinessCache {
+    /// Create a cache that trusts results for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached result for `printer_uri`, if still fresh.
+    pub fn cached(&self, printer_uri: &str) -> Option<PrinterReadiness> {
+        self.lock()
+            .get(printer_uri)
+            .filter(|entry| entry.checked_at.elapsed() < self.ttl)
+            .cloned()
+    }
+
+    /// Readiness of `printer_uri`, querying it only if there is no fresh
+    /// cached result.
+    pub async fn readiness(&self, printer_uri: &str) -> Result<PrinterReadiness> {
+        self.get_or_check(printer_uri, || PrinterReadiness::check(printer_uri))
+            .await
+    }
+
+    /// Like [`readiness`](Self::readiness), with `check` standing in for
+    /// the printer query.
+    pub async fn get_or_check<F, Fut>(
+        &self,
+        printer_uri: &str,
+        check: F,
+    ) -> Result<PrinterReadiness>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<PrinterReadiness>>,
+    {
+        if let Some(entry) = self.cached(printer_uri) {
+            debug!(printer = printer_uri, "using cached printer readiness");
+            return Ok(entry);
+        }
+
+        match check().await {
+            Ok(readiness) => {
+                if readiness.is_stopped() {
+                    // Let the user fix the printer and retry straight away.
+                    self.invalidate(printer_uri);
+                } else {
+                    self.lock()
+                        .insert(printer_uri.to_string(), readiness.clone());
+                }
+                Ok(readiness)
+            }
+            Err(e) => {
+                self.invalidate(printer_uri);
+                Err(e)
+            }
+        }
+    }
+
+    /// Forget `printer_uri`, e.g. after a print to it failed.
+    pub fn invalidate(&self, printer_uri: &str) {
+        self.lock().remove(printer_uri);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PrinterReadiness>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presswerk_core::error::PresswerkError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const URI: &str = "ipp://192.168.1.20:631/ipp/print";
+
+    fn idle() -> PrinterReadiness {
+        PrinterReadiness {
+            state: "idle".into(),
+            reasons: Vec::new(),
+            checked_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn printer_state_is_matched_exactly() {
+        assert_eq!(PrinterState::parse("3"), Some(PrinterState::Idle));
+        assert_eq!(PrinterState::parse(" 5 "), Some(PrinterState::Stopped));
+        assert_eq!(
+            PrinterState::parse("Processing"),
+            Some(PrinterState::Processing)
+        );
+        assert_eq!(PrinterState::parse("35"), None);
+        assert_eq!(PrinterState::parse("13"), None);
+        assert_eq!(PrinterState::parse("not-idle"), None);
+        assert_eq!(PrinterState::parse("unknown"), None);
+
+        let state = |value: &str| PrinterReadiness {
+            state: value.into(),
+            ..idle()
+        };
+        assert!(state("5").is_stopped());
+        assert!(!state("15").is_stopped());
+        assert!(!state("unknown").is_idle());
+    }
+
+    #[tokio::test]
+    async fn two_prints_within_ttl_query_once() {
+        let cache = ReadinessCache::new(Duration::from_secs(60));
+        let queries = AtomicUsize::new(0);
+        let check = || async {
+            queries.fetch_add(1, Ordering::SeqCst);
+            Ok(idle())
+        };
+
+        cache.get_or_check(URI, check).await.unwrap();
+        cache.get_or_check(URI, check).await.unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+
+        // A failed print invalidates the entry.
+        cache.invalidate(URI);
+        cache.get_or_check(URI, check).await.unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn errors_and_stopped_printers_are_not_cached() {
+        let cache = ReadinessCache::new(Duration::from_secs(60));
+
+        let failed = cache
+            .get_or_check(URI, || async {
+                Err(PresswerkError::IppRequest("timed out".into()))
+            })
+            .await;
+        assert!(failed.is_err());
+        assert!(cache.cached(URI).is_none());
+
+        let stopped = cache
+            .get_or_check(URI, || async {
+                Ok(PrinterReadiness {
+                    state: "stopped".into(),
+                    reasons: vec!["media-empty".into()],
+                    checked_at: Instant::now(),
+                })
+            })
+            .await
+            .unwrap();
+        assert!(stopped.is_stopped());
+        assert!(cache.cached(URI).is_none());
+
+        let expired = ReadinessCache::new(Duration::ZERO);
+        expired
+            .get_or_check(URI, || async { Ok(idle()) })
+            .await
+            .unwrap();
+        assert!(expired.cached(URI).is_none());
+    }
+}
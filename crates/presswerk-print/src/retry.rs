@@ -3,8 +3,9 @@
 //
 // Retry engine with exponential backoff + jitter for resilient printing.
 //
-// Classifies errors into Transient (auto-retry), UserAction (wait for user),
-// and Permanent (give up). Only transient errors trigger automatic retries.
+// Errors are classified by `ErrorClass::classify` into Transient
+// (auto-retry), UserAction (wait for user), and Permanent (give up). Only
+// transient errors trigger automatic retries.
 
 use std::time::Duration;
 
@@ -43,82 +44,15 @@ pub enum RetryDecision {
 }
 
 /// Classify a `PresswerkError` into an `ErrorClass` for retry decisions.
+#[deprecated(note = "use ErrorClass::classify")]
 pub fn classify_error(err: &PresswerkError) -> ErrorClass {
-    match err {
-        // Transient — network, timeout, temporary server issues
-        PresswerkError::IppRequest(detail) => classify_ipp_detail(detail),
-        PresswerkError::Discovery(_) => ErrorClass::Transient,
-        PresswerkError::PrintServer(_) => ErrorClass::Transient,
-        PresswerkError::Database(_) => ErrorClass::Transient,
-        PresswerkError::Certificate(_) => ErrorClass::Transient,
-        PresswerkError::OcrError(_) => ErrorClass::Transient,
-
-        // User action needed
-        PresswerkError::NoPrinterSelected => ErrorClass::UserAction,
-
-        // Permanent — wrong format, bad data, platform missing
-        PresswerkError::UnsupportedDocument(_) => ErrorClass::Permanent,
-        PresswerkError::PdfError(_) => ErrorClass::Permanent,
-        PresswerkError::ImageError(_) => ErrorClass::Permanent,
-        PresswerkError::Encryption(_) => ErrorClass::Permanent,
-        PresswerkError::Decryption(_) => ErrorClass::Permanent,
-        PresswerkError::IntegrityMismatch { .. } => ErrorClass::Permanent,
-        PresswerkError::PlatformUnavailable => ErrorClass::Permanent,
-        PresswerkError::Bridge(_) => ErrorClass::Permanent,
-        PresswerkError::Serialization(_) => ErrorClass::Permanent,
-
-        // IO errors depend on the kind
-        PresswerkError::Io(io_err) => match io_err.kind() {
-            std::io::ErrorKind::TimedOut
-            | std::io::ErrorKind::ConnectionRefused
-            | std::io::ErrorKind::ConnectionReset
-            | std::io::ErrorKind::ConnectionAborted
-            | std::io::ErrorKind::Interrupted => ErrorClass::Transient,
-            std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied => {
-                ErrorClass::UserAction
-            }
-            _ => ErrorClass::Transient,
-        },
-    }
+    ErrorClass::classify(err)
 }
 
-/// Classify an IPP error detail string.
-fn classify_ipp_detail(detail: &str) -> ErrorClass {
-    let lower = detail.to_ascii_lowercase();
-
-    // Transient network/server errors
-    if lower.contains("timed out")
-        || lower.contains("connection refused")
-        || lower.contains("connection reset")
-        || lower.contains("broken pipe")
-        || lower.contains("server-error")
-    {
-        return ErrorClass::Transient;
-    }
-
-    // User action required (printer physical state)
-    if lower.contains("media-empty")
-        || lower.contains("toner-empty")
-        || lower.contains("ink")
-        || lower.contains("door-open")
-        || lower.contains("cover-open")
-        || lower.contains("paper-jam")
-        || lower.contains("media-jam")
-        || lower.contains("marker-supply")
-    {
-        return ErrorClass::UserAction;
-    }
-
-    // Permanent client errors
-    if lower.contains("client-error-document-format")
-        || lower.contains("client-error-not-possible")
-        || lower.contains("invalid uri")
-    {
-        return ErrorClass::Permanent;
-    }
-
-    // Default to transient (optimistic — retry first, give up later)
-    ErrorClass::Transient
+/// Classify the detail string of an IPP error.
+#[deprecated(note = "use ErrorClass::classify")]
+pub fn classify_ipp_detail(detail: &str) -> ErrorClass {
+    ErrorClass::classify(&PresswerkError::IppRequest(detail.to_string()))
 }
 
 /// Decide whether to retry based on the error class and attempt count.
@@ -127,7 +61,7 @@ pub fn should_retry(
     attempt: u32,
     config: &RetryConfig,
 ) -> RetryDecision {
-    let class = classify_error(err);
+    let class = ErrorClass::classify(err);
 
     match class {
         ErrorClass::Permanent => {
@@ -184,20 +118,31 @@ mod tests {
     #[test]
     fn timeout_is_transient() {
         let err = PresswerkError::IppRequest("timed out after 60s".into());
-        assert_eq!(classify_error(&err), ErrorClass::Transient);
+        assert_eq!(ErrorClass::classify(&err), ErrorClass::Transient);
     }
 
     #[test]
     fn paper_jam_is_user_action() {
         let err = PresswerkError::IppRequest("printer stopped: paper-jam".into());
-        assert_eq!(classify_error(&err), ErrorClass::UserAction);
+        assert_eq!(ErrorClass::classify(&err), ErrorClass::UserAction);
     }
 
     #[test]
     fn bad_format_is_permanent() {
         let err =
             PresswerkError::IppRequest("client-error-document-format-not-supported".into());
-        assert_eq!(classify_error(&err), ErrorClass::Permanent);
+        assert_eq!(ErrorClass::classify(&err), ErrorClass::Permanent);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_classifiers_match_error_class() {
+        let err = PresswerkError::IppRequest("printer stopped: paper-jam".into());
+        assert_eq!(classify_error(&err), ErrorClass::classify(&err));
+        assert_eq!(
+            classify_ipp_detail("client-error-document-format-not-supported"),
+            ErrorClass::Permanent
+        );
     }
 
     #[test]
@@ -5,12 +5,24 @@
 //
 // Wake sleeping printers, clear stuck spoolers, and probe status.
 // Integrated into the Print Doctor: "Your printer seems asleep. [Wake it up]"
+//
+// `Revival` watches discovery for printers coming back and puts the jobs
+// that failed while they were away back in the queue.
 
+use std::collections::HashSet;
 use std::net::UdpSocket;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use tracing::{debug, info, warn};
 
 use presswerk_core::error::{PresswerkError, Result};
+use presswerk_core::types::{DiscoveredPrinter, ErrorClass, JobId, JobStatus, PrintJob};
+
+use crate::queue::JobQueue;
+
+/// How often [`Revival::run`] checks which printers are reachable.
+pub const REVIVAL_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Send a Wake-on-LAN (WoL) magic packet to wake a sleeping printer.
 ///
@@ -101,7 +113,13 @@ pub async fn probe_status(
 ) -> Result<(String, Vec<String>)> {
     let client = crate::ipp_client::IppClient::new(printer_uri)?;
     let attrs = client.get_printer_attributes().await?;
+    Ok(status_from_attributes(&attrs))
+}
 
+/// The (state, reasons) pair from Get-Printer-Attributes results.
+pub(crate) fn status_from_attributes(
+    attrs: &crate::ipp_client::PrinterAttributes,
+) -> (String, Vec<String>) {
     let state = attrs
         .get("printer-state")
         .cloned()
@@ -117,7 +135,7 @@ pub async fn probe_status(
         })
         .unwrap_or_default();
 
-    Ok((state, reasons))
+    (state, reasons)
 }
 
 /// Parse a MAC address string (e.g. "AA:BB:CC:DD:EE:FF") into bytes.
@@ -133,9 +151,121 @@ pub async fn probe_status(
     Some(mac)
 }
 
+// ---------------------------------------------------------------------------
+// Job revival
+// ---------------------------------------------------------------------------
+
+/// Resubmits jobs that failed while their printer was unreachable once the
+/// printer shows up again.
+///
+/// A job is revived when it is `Failed` with a transient error, is aimed at
+/// the returning printer, and has retries left; it goes back to `Pending`,
+/// its retry count one higher, and is handed back to be sent again.
+#[derive(Debug)]
+pub struct Revival {
+    /// URIs of the printers that were reachable at the last check.
+    reachable: HashSet<String>,
+    /// Time between checks in [`run`](Self::run).
+    interval: Duration,
+}
+
+impl Default for Revival {
+    fn default() -> Self {
+        Self {
+            reachable: HashSet::new(),
+            interval: REVIVAL_INTERVAL,
+        }
+    }
+}
+
+impl Revival {
+    /// Create a revival task that checks every [`REVIVAL_INTERVAL`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check every `interval` instead.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Check which printers `discovery` reports every interval, reviving
+    /// jobs for those that have come back and handing their ids to
+    /// `resend`, which sends them again.  Runs until dropped.
+    pub async fn run<F, R>(mut self, queue: Arc<Mutex<JobQueue>>, mut discovery: F, mut resend: R)
+    where
+        F: FnMut() -> Vec<DiscoveredPrinter>,
+        R: FnMut(&[JobId]),
+    {
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            let printers = discovery();
+            let checked = {
+                let queue = queue.lock().unwrap_or_else(|p| p.into_inner());
+                self.check(&queue, &printers)
+            };
+            match checked {
+                Ok(revived) if !revived.is_empty() => {
+                    info!(count = revived.len(), "revived jobs for returning printers");
+                    resend(&revived);
+                }
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "job revival check failed"),
+            }
+        }
+    }
+
+    /// One check: revive the jobs of every printer in `printers` that was
+    /// not reachable last time, returning their ids.
+    ///
+    /// Stale printers count as unreachable.  On the first check every
+    /// reachable printer counts as returning, as it may have been away
+    /// before the app started.
+    pub fn check(
+        &mut self,
+        queue: &JobQueue,
+        printers: &[DiscoveredPrinter],
+    ) -> Result<Vec<JobId>> {
+        let reachable: HashSet<String> = printers
+            .iter()
+            .filter(|p| !p.stale)
+            .map(|p| p.uri.clone())
+            .collect();
+        let returned: HashSet<&String> = reachable.difference(&self.reachable).collect();
+
+        let mut revived = Vec::new();
+        if !returned.is_empty() {
+            for job in queue.get_all_jobs()? {
+                let for_returned = job
+                    .printer_uri
+                    .as_ref()
+                    .is_some_and(|uri| returned.contains(uri));
+                if for_returned && is_revivable(&job) {
+                    queue.revive_job(&job.id)?;
+                    revived.push(job.id);
+                }
+            }
+        }
+
+        self.reachable = reachable;
+        Ok(revived)
+    }
+}
+
+/// Whether `job` failed for a reason that may have gone away with the
+/// printer coming back, and may still be retried.
+fn is_revivable(job: &PrintJob) -> bool {
+    job.status == JobStatus::Failed
+        && job.error_class == Some(ErrorClass::Transient)
+        && job.retry_count < job.max_retries
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use presswerk_core::types::{AttemptRecord, DocumentType, JobSource, PrintProtocol};
 
     #[test]
     fn parse_mac_colon_format() {
@@ -154,4 +284,112 @@ fn parse_mac_invalid() {
         assert!(parse_mac("not-a-mac").is_none());
         assert!(parse_mac("AA:BB:CC").is_none());
     }
+
+    // -- Job revival ------------------------------------------------------------
+
+    const PRINTER_URI: &str = "ipp://192.168.1.20:631/ipp/print";
+
+    fn printer(stale: bool) -> DiscoveredPrinter {
+        let ip = "192.168.1.20".parse().unwrap();
+        DiscoveredPrinter {
+            name: "Office".into(),
+            uri: PRINTER_URI.into(),
+            ip,
+            port: 631,
+            supports_color: false,
+            supports_duplex: false,
+            supports_tls: false,
+            paper_sizes: Vec::new(),
+            make_and_model: None,
+            location: None,
+            last_seen: chrono::Utc::now(),
+            stale,
+            manually_added: false,
+            addresses: vec![ip],
+            uuid: None,
+            document_formats: Vec::new(),
+        }
+    }
+
+    /// Queue a job for the test printer that failed with `error`.
+    fn failed_job(queue: &JobQueue, error: &str) -> JobId {
+        let mut job = PrintJob::new(
+            JobSource::Local,
+            DocumentType::Pdf,
+            "report.pdf".into(),
+            "hash".into(),
+        );
+        job.printer_uri = Some(PRINTER_URI.into());
+        queue.insert_job(&job).unwrap();
+        let error = PresswerkError::IppRequest(error.into());
+        queue
+            .record_attempts(
+                &job.id,
+                &[AttemptRecord::failed(PrintProtocol::Ipp11, &error)],
+            )
+            .unwrap();
+        queue
+            .update_status(&job.id, JobStatus::Failed, Some(&error.to_string()))
+            .unwrap();
+        job.id
+    }
+
+    #[test]
+    fn transient_failure_is_revived_when_the_printer_returns() {
+        let queue = JobQueue::open_in_memory().unwrap();
+        let job_id = failed_job(&queue, "Print-Job timed out after 60s");
+        let mut revival = Revival::new();
+
+        // The printer is still gone.
+        assert!(revival.check(&queue, &[printer(true)]).unwrap().is_empty());
+        assert!(revival.check(&queue, &[]).unwrap().is_empty());
+
+        assert_eq!(
+            revival.check(&queue, &[printer(false)]).unwrap(),
+            vec![job_id]
+        );
+        let job = queue.get_job(&job_id).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert_eq!(job.retry_count, 1);
+        assert_eq!(job.error_message, None);
+
+        // Still there: nothing returned, nothing more to revive.
+        assert!(revival.check(&queue, &[printer(false)]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn permanent_failure_is_not_revived() {
+        let queue = JobQueue::open_in_memory().unwrap();
+        let job_id = failed_job(
+            &queue,
+            "Print-Job returned status ClientErrorDocumentFormatNotSupported",
+        );
+
+        let revived = Revival::new().check(&queue, &[printer(false)]).unwrap();
+        assert!(revived.is_empty());
+        assert_eq!(
+            queue.get_job(&job_id).unwrap().unwrap().status,
+            JobStatus::Failed
+        );
+    }
+
+    #[test]
+    fn job_out_of_retries_is_not_revived() {
+        let queue = JobQueue::open_in_memory().unwrap();
+        let job_id = failed_job(&queue, "connection refused");
+        let mut revival = Revival::new();
+
+        let max_retries = queue.get_job(&job_id).unwrap().unwrap().max_retries;
+        for _ in 0..max_retries {
+            assert_eq!(
+                revival.check(&queue, &[printer(false)]).unwrap(),
+                vec![job_id]
+            );
+            queue
+                .update_status(&job_id, JobStatus::Failed, Some("connection refused"))
+                .unwrap();
+            revival.check(&queue, &[]).unwrap();
+        }
+        assert!(revival.check(&queue, &[printer(false)]).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,445 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Resumable multi-page scan sessions.
+//
+// Every page is written to the `DocumentStore` the moment it is captured, and
+// the ordered list of page hashes is kept in a small manifest at
+// `{data_dir}/scan_sessions/{session_id}.json`.  If the app is killed half way
+// through a long scan, the pages are still on disk and the session can be
+// picked up again with `ScanSession::resume`.  Pages can be reordered or
+// removed before the session is finalized; both only rewrite the manifest.
+//
+// Each page holds a named reference (`scan-{session_id}-{index}`) on its
+// blob, so a page that is also part of a print job is not deleted when the
+// session is discarded.  Moving or removing a page re-points the references
+// of the positions that changed.  Assembling the pages into a PDF needs
+// `presswerk-document`, which this crate does not depend on; the app passes a
+// `PageAssembler` to `finalize`.
+//
+// Sessions live here rather than in `presswerk-document::scan` because their
+// pages are kept in this crate's `DocumentStore`; that module re-exports
+// them, and `ScanSession::load` is another name for `ScanSession::resume`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use presswerk_core::error::{PresswerkError, Result};
+
+use crate::document_store::DocumentStore;
+
+/// Subdirectory (under the data dir) holding session manifests.
+const SESSIONS_DIR: &str = "scan_sessions";
+
+/// Combines captured pages, in order, into a single PDF.
+///
+/// Implemented in the app on top of `presswerk-document`.
+pub trait PageAssembler: Send + Sync {
+    /// Build one PDF from `pages` (encoded images, first page first).
+    fn assemble(&self, pages: &[Vec<u8>]) -> Result<Vec<u8>>;
+}
+
+/// Summary of an unfinished session, for offering to resume it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanSessionInfo {
+    pub id: String,
+    pub page_count: usize,
+    pub started_at: DateTime<Utc>,
+}
+
+/// On-disk manifest of a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    id: String,
+    started_at: DateTime<Utc>,
+    /// Page hashes in capture order.
+    pages: Vec<String>,
+}
+
+/// A multi-page scan whose pages are persisted as they are captured.
+pub struct ScanSession {
+    store: Arc<DocumentStore>,
+    manifest_path: PathBuf,
+    manifest: Manifest,
+}
+
+impl ScanSession {
+    /// Start a new, empty session.
+    pub fn start(data_dir: impl AsRef<Path>, store: Arc<DocumentStore>) -> Result<Self> {
+        let dir = sessions_dir(data_dir.as_ref())?;
+        let id = Uuid::new_v4().to_string();
+        let session = Self {
+            store,
+            manifest_path: dir.join(format!("{id}.json")),
+            manifest: Manifest {
+                id,
+                started_at: Utc::now(),
+                pages: Vec::new(),
+            },
+        };
+        session.save()?;
+
+        info!(session = %session.manifest.id, "scan session started");
+        Ok(session)
+    }
+
+    /// Reopen the session `id`, e.g. after the app was restarted.
+    #[instrument(skip(data_dir, store))]
+    pub fn resume(data_dir: impl AsRef<Path>, store: Arc<DocumentStore>, id: &str) -> Result<Self> {
+        // Session ids are UUIDs; refusing anything else keeps `id` from
+        // naming a file outside the sessions directory.
+        Uuid::parse_str(id).map_err(|_| {
+            PresswerkError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid scan session id: {id}"),
+            ))
+        })?;
+
+        let manifest_path = sessions_dir(data_dir.as_ref())?.join(format!("{id}.json"));
+        let manifest: Manifest = serde_json::from_slice(&fs::read(&manifest_path)?)?;
+
+        if let Some(missing) = manifest.pages.iter().find(|hash| !store.contains(hash)) {
+            warn!(session = id, hash = %missing, "scan session page missing from store");
+        }
+
+        info!(
+            session = id,
+            pages = manifest.pages.len(),
+            "scan session resumed"
+        );
+        Ok(Self {
+            store,
+            manifest_path,
+            manifest,
+        })
+    }
+
+    /// Reopen the session `id`; the same as [`resume`](Self::resume).
+    pub fn load(data_dir: impl AsRef<Path>, store: Arc<DocumentStore>, id: &str) -> Result<Self> {
+        Self::resume(data_dir, store, id)
+    }
+
+    /// Unfinished sessions under `data_dir`, oldest first.
+    ///
+    /// Unreadable manifests are skipped.
+    pub fn list(data_dir: impl AsRef<Path>) -> Result<Vec<ScanSessionInfo>> {
+        let dir = sessions_dir(data_dir.as_ref())?;
+        let mut sessions: Vec<ScanSessionInfo> = fs::read_dir(&dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let manifest: Manifest = serde_json::from_slice(&fs::read(&path).ok()?).ok()?;
+                Some(ScanSessionInfo {
+                    id: manifest.id,
+                    page_count: manifest.pages.len(),
+                    started_at: manifest.started_at,
+                })
+            })
+            .collect();
+        sessions.sort_by_key(|info| info.started_at);
+        Ok(sessions)
+    }
+
+    /// This session's id, for [`resume`](Self::resume).
+    pub fn id(&self) -> &str {
+        &self.manifest.id
+    }
+
+    /// Number of pages captured so far.
+    pub fn page_count(&self) -> usize {
+        self.manifest.pages.len()
+    }
+
+    /// When the session was started.
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.manifest.started_at
+    }
+
+    /// Persist `page` (an enhanced, encoded image) as the next page.
+    ///
+    /// The page is durable once this returns.
+    pub fn add_page(&mut self, page: &[u8]) -> Result<()> {
+        let holder = self.holder(self.manifest.pages.len());
+        let hash = self.store.put_named(page, &holder)?;
+
+        self.manifest.pages.push(hash.clone());
+        if let Err(e) = self.save() {
+            self.manifest.pages.pop();
+            let _ = self.store.release_named(&hash, &holder);
+            return Err(e);
+        }
+
+        info!(session = %self.manifest.id, pages = self.page_count(), "scan page saved");
+        Ok(())
+    }
+
+    /// Move the page at `from` (0-based) to position `to`, shifting the
+    /// pages in between.  `to` past the end moves the page to the end.
+    pub fn move_page(&mut self, from: usize, to: usize) -> Result<()> {
+        self.check_index(from)?;
+        let to = to.min(self.page_count() - 1);
+        if from == to {
+            return Ok(());
+        }
+
+        let mut pages = self.manifest.pages.clone();
+        let page = pages.remove(from);
+        pages.insert(to, page);
+        self.replace_pages(pages)?;
+
+        info!(session = %self.manifest.id, from, to, "scan page moved");
+        Ok(())
+    }
+
+    /// Remove the page at `index` (0-based); later pages move up.
+    pub fn remove_page(&mut self, index: usize) -> Result<()> {
+        self.check_index(index)?;
+
+        let mut pages = self.manifest.pages.clone();
+        pages.remove(index);
+        self.replace_pages(pages)?;
+
+        info!(session = %self.manifest.id, index, pages = self.page_count(), "scan page removed");
+        Ok(())
+    }
+
+    /// Load the pages in session order.
+    pub fn pages(&self) -> Result<Vec<Vec<u8>>> {
+        self.manifest
+            .pages
+            .iter()
+            .map(|hash| self.store.get(hash))
+            .collect()
+    }
+
+    /// Assemble the pages, in session order, into a PDF and close the
+    /// session.
+    ///
+    /// If assembly fails the session stays on disk and can be resumed.
+    pub fn finalize(self, assembler: &dyn PageAssembler) -> Result<Vec<u8>> {
+        if self.manifest.pages.is_empty() {
+            return Err(PresswerkError::PdfError("scan session has no pages".into()));
+        }
+
+        let pdf = assembler.assemble(&self.pages()?)?;
+        info!(session = %self.manifest.id, pages = self.page_count(), "scan session finalized");
+        self.discard()?;
+        Ok(pdf)
+    }
+
+    /// Delete the session and release its pages.
+    pub fn discard(self) -> Result<()> {
+        for (index, hash) in self.manifest.pages.iter().enumerate() {
+            self.store.release_named(hash, &self.holder(index))?;
+        }
+        match fs::remove_file(&self.manifest_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(PresswerkError::Io(e)),
+        }
+
+        info!(session = %self.manifest.id, "scan session discarded");
+        Ok(())
+    }
+
+    /// Error unless there is a page at `index`.
+    fn check_index(&self, index: usize) -> Result<()> {
+        if index < self.page_count() {
+            return Ok(());
+        }
+        Err(PresswerkError::InvalidPageRange(format!(
+            "no page {index} in a scan session of {} pages",
+            self.page_count()
+        )))
+    }
+
+    /// Make `pages` the session's page order and save it.
+    ///
+    /// References for positions whose page changed are added before the old
+    /// ones are released, so a page that merely moved never drops to zero
+    /// references and is never deleted from the store.
+    fn replace_pages(&mut self, pages: Vec<String>) -> Result<()> {
+        let old = std::mem::replace(&mut self.manifest.pages, pages);
+        let changed: Vec<usize> = (0..old.len().max(self.manifest.pages.len()))
+            .filter(|&index| old.get(index) != self.manifest.pages.get(index))
+            .collect();
+
+        let mut added = Vec::new();
+        let mut saved = Ok(());
+        for &index in &changed {
+            if let Some(hash) = self.manifest.pages.get(index) {
+                saved = self.store.add_named_ref(hash, &self.holder(index));
+                if saved.is_err() {
+                    break;
+                }
+                added.push(index);
+            }
+        }
+        if saved.is_ok() {
+            saved = self.save();
+        }
+        if let Err(e) = saved {
+            for index in added {
+                let _ = self
+                    .store
+                    .release_named(&self.manifest.pages[index], &self.holder(index));
+            }
+            self.manifest.pages = old;
+            return Err(e);
+        }
+
+        for index in changed {
+            if let Some(hash) = old.get(index) {
+                self.store.release_named(hash, &self.holder(index))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reference name for page `index` in the document store.
+    fn holder(&self, index: usize) -> String {
+        format!("scan-{}-{index}", self.manifest.id)
+    }
+
+    /// Write the manifest atomically (temp file + rename).
+    fn save(&self) -> Result<()> {
+        let temp = self.manifest_path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec(&self.manifest)?)?;
+        fs::rename(&temp, &self.manifest_path)?;
+        Ok(())
+    }
+}
+
+/// `{data_dir}/scan_sessions`, created if missing.
+fn sessions_dir(data_dir: &Path) -> Result<PathBuf> {
+    let dir = data_dir.join(SESSIONS_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    /// Joins pages with a separator so tests can check order.
+    struct JoiningAssembler;
+
+    impl PageAssembler for JoiningAssembler {
+        fn assemble(&self, pages: &[Vec<u8>]) -> Result<Vec<u8>> {
+            Ok(pages.join(&b'|'))
+        }
+    }
+
+    fn open_store(dir: &Path) -> Arc<DocumentStore> {
+        Arc::new(DocumentStore::open(dir).expect("open store"))
+    }
+
+    #[test]
+    fn pages_survive_restart_and_finalize_in_capture_order() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+
+        let id = {
+            let mut session = ScanSession::start(tmp.path(), open_store(tmp.path())).unwrap();
+            session.add_page(b"page one").unwrap();
+            session.add_page(b"page two").unwrap();
+            session.add_page(b"page one").unwrap();
+            session.id().to_string()
+            // Dropped without finalize: the app was killed.
+        };
+
+        let store = open_store(tmp.path());
+        let pending = ScanSession::list(tmp.path()).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(
+            (pending[0].id.as_str(), pending[0].page_count),
+            (id.as_str(), 3)
+        );
+
+        let mut session = ScanSession::resume(tmp.path(), Arc::clone(&store), &id).unwrap();
+        session.add_page(b"page three").unwrap();
+        let pdf = session.finalize(&JoiningAssembler).unwrap();
+
+        assert_eq!(pdf, b"page one|page two|page one|page three");
+        assert!(ScanSession::list(tmp.path()).unwrap().is_empty());
+        assert!(!store.contains(&hex::encode(Sha256::digest(b"page two"))));
+    }
+
+    #[test]
+    fn discard_keeps_pages_shared_with_jobs() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let store = open_store(tmp.path());
+        let job = presswerk_core::types::JobId::new();
+        let shared = store.put_for_job(&job, b"shared page").unwrap();
+
+        let mut session = ScanSession::start(tmp.path(), Arc::clone(&store)).unwrap();
+        session.add_page(b"shared page").unwrap();
+        session.add_page(b"scratch page").unwrap();
+        session.discard().unwrap();
+
+        assert!(store.contains(&shared));
+        assert_eq!(store.ref_count(&shared), 1);
+        assert!(!store.contains(&hex::encode(Sha256::digest(b"scratch page"))));
+        assert!(ScanSession::resume(tmp.path(), store, "../jobs").is_err());
+    }
+
+    #[test]
+    fn reorder_and_remove_survive_resume_and_set_the_finalized_order() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let store = open_store(tmp.path());
+        let hash = |page: &[u8]| hex::encode(Sha256::digest(page));
+
+        let mut session = ScanSession::start(tmp.path(), Arc::clone(&store)).unwrap();
+        for page in [b"one", b"two", b"six", b"tri"] {
+            session.add_page(page).unwrap();
+        }
+        session.move_page(3, 2).unwrap();
+        session.remove_page(3).unwrap();
+        session.move_page(0, 99).unwrap();
+        assert!(session.remove_page(3).is_err());
+        assert!(session.move_page(3, 0).is_err());
+        let id = session.id().to_string();
+        drop(session);
+
+        // The removed page is gone; the moved ones are still in the store.
+        assert!(!store.contains(&hash(b"six")));
+        for page in [b"one", b"two", b"tri"] {
+            assert_eq!(store.ref_count(&hash(page)), 1);
+        }
+
+        let session = ScanSession::load(tmp.path(), Arc::clone(&store), &id).unwrap();
+        assert_eq!(session.page_count(), 3);
+        let pdf = session.finalize(&JoiningAssembler).unwrap();
+        assert_eq!(pdf, b"two|tri|one");
+        assert!(!store.contains(&hash(b"one")));
+    }
+
+    #[test]
+    fn moving_duplicate_pages_keeps_their_blob() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let store = open_store(tmp.path());
+        let same = hex::encode(Sha256::digest(b"same"));
+
+        let mut session = ScanSession::start(tmp.path(), Arc::clone(&store)).unwrap();
+        session.add_page(b"same").unwrap();
+        session.add_page(b"other").unwrap();
+        session.add_page(b"same").unwrap();
+        session.move_page(2, 0).unwrap();
+        session.move_page(1, 2).unwrap();
+        assert_eq!(store.ref_count(&same), 2);
+
+        session.remove_page(0).unwrap();
+        assert_eq!(store.ref_count(&same), 1);
+        assert_eq!(
+            session.pages().unwrap(),
+            [b"other".to_vec(), b"same".to_vec()]
+        );
+    }
+}
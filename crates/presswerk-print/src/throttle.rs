@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Upload bandwidth throttling for metered connections.
+//
+// On mobile data a large scan uploaded at full speed can eat a noticeable
+// share of the user's data cap in seconds.  `ThrottledBody` paces a document
+// body with a token bucket so the IPP client can hand it to the `ipp` crate
+// as a streaming payload that never exceeds the configured rate.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures_io::AsyncRead;
+use tokio::time::Sleep;
+
+/// Largest burst the bucket allows, regardless of rate.
+const MAX_BURST_BYTES: u64 = 64 * 1024;
+
+/// Token bucket limiting throughput to a fixed number of bytes per second.
+///
+/// The bucket holds at most a quarter of a second's worth of bytes (capped
+/// at 64 KiB), which bounds both the initial burst and the size of each
+/// chunk handed out.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Refill rate in bytes per second.
+    rate: u64,
+    /// Bucket size in bytes.
+    capacity: u64,
+    /// Currently available bytes.
+    tokens: f64,
+    /// When `tokens` was last topped up.
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter for `bytes_per_sec` (at least 1), starting full.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        let rate = bytes_per_sec.max(1);
+        let capacity = (rate / 4).clamp(1, MAX_BURST_BYTES);
+        Self {
+            rate,
+            capacity,
+            tokens: capacity as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Largest number of bytes a single [`take`](Self::take) can grant.
+    pub fn max_chunk(&self) -> usize {
+        self.capacity as usize
+    }
+
+    /// Try to spend `bytes` (at most [`max_chunk`](Self::max_chunk)).
+    ///
+    /// Returns `Ok(())` when the bytes may be sent now, or `Err(wait)` with
+    /// how long until enough tokens have accumulated.
+    pub fn take(&mut self, bytes: usize) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.capacity as f64);
+        self.last_refill = now;
+
+        let wanted = bytes.min(self.max_chunk()) as f64;
+        if self.tokens >= wanted {
+            self.tokens -= wanted;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (wanted - self.tokens) / self.rate as f64,
+            ))
+        }
+    }
+}
+
+/// In-memory document body that reads no faster than its [`RateLimiter`]
+/// allows.
+pub struct ThrottledBody {
+    data: Vec<u8>,
+    position: usize,
+    limiter: RateLimiter,
+    /// Pending wait for tokens, if the last read had to back off.
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl ThrottledBody {
+    /// Wrap `data`, limiting reads to `bytes_per_sec`.
+    ///
+    /// Must be read from within a Tokio runtime, which provides the timer.
+    pub fn new(data: Vec<u8>, bytes_per_sec: u64) -> Self {
+        Self {
+            data,
+            position: 0,
+            limiter: RateLimiter::new(bytes_per_sec),
+            sleep: None,
+        }
+    }
+}
+
+impl AsyncRead for ThrottledBody {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(sleep) = this.sleep.as_mut() {
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.sleep = None;
+            }
+
+            let remaining = &this.data[this.position..];
+            let len = buf.len().min(remaining.len()).min(this.limiter.max_chunk());
+            if len == 0 {
+                return Poll::Ready(Ok(0));
+            }
+
+            match this.limiter.take(len) {
+                Ok(()) => {
+                    buf[..len].copy_from_slice(&remaining[..len]);
+                    this.position += len;
+                    return Poll::Ready(Ok(len));
+                }
+                Err(wait) => this.sleep = Some(Box::pin(tokio::time::sleep(wait))),
+            }
+        }
+    }
+}
+
+/// Minimum time sending `bytes` at `bytes_per_sec` takes, allowing for the
+/// limiter's initial burst.
+pub fn min_transfer_time(bytes: usize, bytes_per_sec: u64) -> Duration {
+    let limiter = RateLimiter::new(bytes_per_sec);
+    let paced = (bytes as u64).saturating_sub(limiter.capacity);
+    Duration::from_secs_f64(paced as f64 / limiter.rate as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::poll_fn;
+
+    async fn read_to_end(body: &mut ThrottledBody) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut buf = [0u8; 8192];
+        loop {
+            let n = poll_fn(|cx| Pin::new(&mut *body).poll_read(cx, &mut buf))
+                .await
+                .expect("read");
+            if n == 0 {
+                return out;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    #[tokio::test]
+    async fn throttled_body_respects_rate_limit() {
+        let payload: Vec<u8> = (0..4000u32).map(|i| i as u8).collect();
+        let rate = 10_000;
+        // 2500 bytes go out as the initial burst, the rest at 10 kB/s.
+        let expected = min_transfer_time(payload.len(), rate);
+        assert!(expected > Duration::from_millis(149), "{expected:?}");
+
+        let started = Instant::now();
+        let mut body = ThrottledBody::new(payload.clone(), rate);
+        let read = read_to_end(&mut body).await;
+
+        assert_eq!(read, payload);
+        assert!(
+            started.elapsed() >= expected,
+            "took {:?}, expected at least {:?}",
+            started.elapsed(),
+            expected
+        );
+    }
+
+    #[test]
+    fn limiter_grants_burst_then_asks_to_wait() {
+        let mut limiter = RateLimiter::new(1000);
+        assert_eq!(limiter.max_chunk(), 250);
+        assert!(limiter.take(250).is_ok());
+        let wait = limiter.take(250).expect_err("bucket is empty");
+        assert!(wait > Duration::from_millis(200), "{wait:?}");
+    }
+}
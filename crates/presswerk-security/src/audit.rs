@@ -10,16 +10,60 @@
 //     action        TEXT    NOT NULL,   -- e.g. "encrypt", "decrypt", "print"
 //     document_hash TEXT    NOT NULL,   -- SHA-256 hex digest
 //     success       INTEGER NOT NULL,   -- 0 = failure, 1 = success
-//     details       TEXT                -- optional free-form context
+//     details       TEXT,               -- optional free-form context
+//     actor         TEXT,               -- who acted, for structured events
+//     job_id        TEXT,               -- print job involved, if any
+//     prev_hash     TEXT    NOT NULL,   -- entry_hash of the previous row
+//     entry_hash    TEXT    NOT NULL    -- SHA-256 over this row and prev_hash
 //   )
+//
+// The last two columns chain the rows together: each entry hashes its own
+// fields together with the hash of the entry before it, so editing, removing
+// or reordering any row breaks every link after it (see `verify_chain`).
 
 use std::path::Path;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use presswerk_core::error::PresswerkError;
-use rusqlite::{Connection, params};
+use presswerk_core::types::JobId;
+use rusqlite::{Connection, OptionalExtension, params};
 use serde::{Deserialize, Serialize};
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
+
+use crate::certificates::SelfSignedCert;
+use crate::integrity::hash_bytes;
+
+/// `prev_hash` of the first entry in the log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// SQLite schema for the audit log.
+const CREATE_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS audit_log (
+        id            INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp     TEXT    NOT NULL,
+        action        TEXT    NOT NULL,
+        document_hash TEXT    NOT NULL,
+        success       INTEGER NOT NULL,
+        details       TEXT,
+        actor         TEXT,
+        job_id        TEXT,
+        prev_hash     TEXT    NOT NULL DEFAULT '',
+        entry_hash    TEXT    NOT NULL DEFAULT ''
+    )
+"#;
+
+/// Migration adding the structured-event and hash-chain columns to logs
+/// created before they existed.
+const MIGRATE_CHAIN_COLUMNS_SQL: &str = r#"
+    ALTER TABLE audit_log ADD COLUMN actor TEXT;
+    ALTER TABLE audit_log ADD COLUMN job_id TEXT;
+    ALTER TABLE audit_log ADD COLUMN prev_hash TEXT NOT NULL DEFAULT '';
+    ALTER TABLE audit_log ADD COLUMN entry_hash TEXT NOT NULL DEFAULT '';
+"#;
+
+/// Columns read back into an [`AuditEntry`], in `row_to_entry` order.
+const SELECT_COLUMNS: &str = "id, timestamp, action, document_hash, success, details, \
+                              actor, job_id, prev_hash, entry_hash";
 
 // ---------------------------------------------------------------------------
 // Local error helpers
@@ -43,6 +87,84 @@ pub struct AuditEntry {
     pub document_hash: String,
     pub success: bool,
     pub details: Option<String>,
+    /// Who acted; set for entries written with [`AuditLog::append`].
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// The print job involved, if any.
+    #[serde(default)]
+    pub job_id: Option<String>,
+    /// `entry_hash` of the previous entry ([`GENESIS_HASH`] for the first).
+    #[serde(default)]
+    pub prev_hash: String,
+    /// SHA-256 hex digest over this entry's fields and `prev_hash`.
+    #[serde(default)]
+    pub entry_hash: String,
+}
+
+impl AuditEntry {
+    /// Recompute the chain hash of this entry from its fields.
+    ///
+    /// Covers everything but `id` and `entry_hash` itself; the fields are
+    /// serialised as a JSON array so no two entries share an encoding.
+    pub fn compute_hash(&self) -> String {
+        let fields = (
+            &self.prev_hash,
+            &self.timestamp,
+            &self.action,
+            &self.document_hash,
+            self.success,
+            &self.details,
+            &self.actor,
+            &self.job_id,
+        );
+        // Serialising strings, bools and options cannot fail.
+        let encoded = serde_json::to_vec(&fields).unwrap_or_default();
+        hash_bytes(&encoded)
+    }
+}
+
+/// A structured audit event: who did what, when, and to which job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Utc>,
+    /// Who acted, e.g. `"user"`, `"ipp-server"` or a remote host.
+    pub actor: String,
+    /// Short verb describing the operation, e.g. `"print"`, `"cancel"`.
+    pub action: String,
+    pub job_id: Option<JobId>,
+}
+
+impl AuditEvent {
+    /// An event happening now.
+    pub fn now(actor: impl Into<String>, action: impl Into<String>, job_id: Option<JobId>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            actor: actor.into(),
+            action: action.into(),
+            job_id,
+        }
+    }
+}
+
+/// A signed copy of the whole audit log, as produced by
+/// [`AuditLog::export_json`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditExport {
+    /// Every entry, oldest first.
+    pub entries: Vec<AuditEntry>,
+    /// `entry_hash` of the last entry ([`GENESIS_HASH`] for an empty log).
+    pub chain_hash: String,
+    /// Hex SEC1 public key of the device that signed the export.
+    pub public_key: String,
+    /// Hex ECDSA P-256 signature over `entries` and `chain_hash`.
+    pub signature: String,
+}
+
+impl AuditExport {
+    /// The bytes covered by `signature`.
+    fn signed_payload(&self) -> Result<Vec<u8>, PresswerkError> {
+        Ok(serde_json::to_vec(&(&self.entries, &self.chain_hash))?)
+    }
 }
 
 /// Append-only audit log backed by a SQLite database.
@@ -68,17 +190,7 @@ pub fn open(path: impl AsRef<Path>) -> Result<Self, PresswerkError> {
         conn.execute_batch("PRAGMA journal_mode = WAL;")
             .map_err(db_err)?;
 
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS audit_log (
-                id            INTEGER PRIMARY KEY AUTOINCREMENT,
-                timestamp     TEXT    NOT NULL,
-                action        TEXT    NOT NULL,
-                document_hash TEXT    NOT NULL,
-                success       INTEGER NOT NULL,
-                details       TEXT
-            );",
-        )
-        .map_err(db_err)?;
+        Self::init(&conn)?;
 
         debug!("audit log opened");
         Ok(Self { conn })
@@ -87,23 +199,68 @@ pub fn open(path: impl AsRef<Path>) -> Result<Self, PresswerkError> {
     /// Open an in-memory audit database (useful for tests).
     pub fn open_in_memory() -> Result<Self, PresswerkError> {
         let conn = Connection::open_in_memory().map_err(db_err)?;
-
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS audit_log (
-                id            INTEGER PRIMARY KEY AUTOINCREMENT,
-                timestamp     TEXT    NOT NULL,
-                action        TEXT    NOT NULL,
-                document_hash TEXT    NOT NULL,
-                success       INTEGER NOT NULL,
-                details       TEXT
-            );",
-        )
-        .map_err(db_err)?;
+        Self::init(&conn)?;
 
         debug!("in-memory audit log opened");
         Ok(Self { conn })
     }
 
+    /// Create the table, add columns missing from older logs and chain any
+    /// entries written before the hash chain existed.
+    fn init(conn: &Connection) -> Result<(), PresswerkError> {
+        conn.execute_batch(CREATE_TABLE_SQL).map_err(db_err)?;
+
+        // Each ALTER TABLE fails harmlessly if its column already exists.
+        for stmt in MIGRATE_CHAIN_COLUMNS_SQL.split(';') {
+            let trimmed = stmt.trim();
+            if !trimmed.is_empty() {
+                let _ = conn.execute_batch(trimmed);
+            }
+        }
+
+        Self::chain_unhashed_entries(conn)
+    }
+
+    /// Fill in `prev_hash`/`entry_hash` for entries that predate the chain.
+    ///
+    /// Such entries were never protected, so this only lets the chain start
+    /// from them; it says nothing about whether they were tampered with
+    /// before the upgrade.
+    fn chain_unhashed_entries(conn: &Connection) -> Result<(), PresswerkError> {
+        let unhashed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM audit_log WHERE entry_hash = ''",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(db_err)?;
+        if unhashed == 0 {
+            return Ok(());
+        }
+
+        let tx = conn.unchecked_transaction().map_err(db_err)?;
+        let mut prev_hash = GENESIS_HASH.to_string();
+        for mut entry in Self::query_entries(&tx, "ORDER BY id ASC", [])? {
+            if entry.entry_hash.is_empty() {
+                entry.prev_hash = prev_hash;
+                entry.entry_hash = entry.compute_hash();
+                tx.execute(
+                    "UPDATE audit_log SET prev_hash = ?1, entry_hash = ?2 WHERE id = ?3",
+                    params![entry.prev_hash, entry.entry_hash, entry.id],
+                )
+                .map_err(db_err)?;
+            }
+            prev_hash = entry.entry_hash;
+        }
+        tx.commit().map_err(db_err)?;
+
+        warn!(
+            count = unhashed,
+            "chained audit entries written before the hash chain"
+        );
+        Ok(())
+    }
+
     /// Record a new audit entry.
     ///
     /// `action` is a short verb describing the operation (e.g. `"encrypt"`,
@@ -117,79 +274,215 @@ pub fn record(
         success: bool,
         details: Option<&str>,
     ) -> Result<(), PresswerkError> {
-        let timestamp = Utc::now().to_rfc3339();
-        let success_int: i32 = if success { 1 } else { 0 };
-
-        self.conn
-            .execute(
-                "INSERT INTO audit_log (timestamp, action, document_hash, success, details)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![timestamp, action, document_hash, success_int, details],
-            )
-            .map_err(db_err)?;
+        self.insert(AuditEntry {
+            id: 0,
+            timestamp: Utc::now().to_rfc3339(),
+            action: action.to_string(),
+            document_hash: document_hash.to_string(),
+            success,
+            details: details.map(str::to_string),
+            actor: None,
+            job_id: None,
+            prev_hash: String::new(),
+            entry_hash: String::new(),
+        })?;
 
         debug!("audit entry recorded");
         Ok(())
     }
 
-    /// Retrieve all entries for a given document hash, ordered by timestamp
-    /// ascending.
-    pub fn entries_for_hash(&self, document_hash: &str) -> Result<Vec<AuditEntry>, PresswerkError> {
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT id, timestamp, action, document_hash, success, details
-                 FROM audit_log
-                 WHERE document_hash = ?1
-                 ORDER BY timestamp ASC",
+    /// Record a structured [`AuditEvent`].
+    ///
+    /// The entry is stored as a successful action with no document hash.
+    #[instrument(skip_all, fields(actor = %event.actor, action = %event.action))]
+    pub fn append(&self, event: AuditEvent) -> Result<(), PresswerkError> {
+        self.insert(AuditEntry {
+            id: 0,
+            timestamp: event.timestamp.to_rfc3339(),
+            action: event.action,
+            document_hash: String::new(),
+            success: true,
+            details: None,
+            actor: Some(event.actor),
+            job_id: event.job_id.map(|id| id.to_string()),
+            prev_hash: String::new(),
+            entry_hash: String::new(),
+        })?;
+
+        debug!("audit event appended");
+        Ok(())
+    }
+
+    /// Link `entry` to the current end of the chain and insert it.
+    ///
+    /// Reading the last hash and inserting happen in one transaction so two
+    /// writers cannot both link to the same predecessor.
+    fn insert(&self, mut entry: AuditEntry) -> Result<(), PresswerkError> {
+        let tx = self.conn.unchecked_transaction().map_err(db_err)?;
+
+        entry.prev_hash = tx
+            .query_row(
+                "SELECT entry_hash FROM audit_log ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
             )
-            .map_err(db_err)?;
+            .optional()
+            .map_err(db_err)?
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+        entry.entry_hash = entry.compute_hash();
+
+        tx.execute(
+            "INSERT INTO audit_log
+                 (timestamp, action, document_hash, success, details,
+                  actor, job_id, prev_hash, entry_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.timestamp,
+                entry.action,
+                entry.document_hash,
+                entry.success,
+                entry.details,
+                entry.actor,
+                entry.job_id,
+                entry.prev_hash,
+                entry.entry_hash,
+            ],
+        )
+        .map_err(db_err)?;
 
-        let rows = stmt
-            .query_map(params![document_hash], |row| {
-                Ok(AuditEntry {
-                    id: row.get(0)?,
-                    timestamp: row.get(1)?,
-                    action: row.get(2)?,
-                    document_hash: row.get(3)?,
-                    success: row.get::<_, i32>(4)? != 0,
-                    details: row.get(5)?,
-                })
-            })
-            .map_err(db_err)?;
+        tx.commit().map_err(db_err)
+    }
 
-        let mut entries = Vec::new();
-        for row in rows {
-            entries.push(row.map_err(db_err)?);
+    /// Walk the log from the first entry and check every link.
+    ///
+    /// Returns `Ok(false)` as soon as an entry's stored hash does not match
+    /// its contents or its `prev_hash` does not match the entry before it —
+    /// i.e. a row was edited, removed or reordered after it was written.
+    pub fn verify_chain(&self) -> Result<bool, PresswerkError> {
+        let entries = Self::query_entries(&self.conn, "ORDER BY id ASC", [])?;
+        match check_chain(&entries) {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                warn!(error = %e, "audit chain broken");
+                Ok(false)
+            }
         }
-        Ok(entries)
     }
 
-    /// Retrieve the most recent `limit` entries, ordered newest-first.
-    pub fn recent_entries(&self, limit: u32) -> Result<Vec<AuditEntry>, PresswerkError> {
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT id, timestamp, action, document_hash, success, details
-                 FROM audit_log
-                 ORDER BY id DESC
-                 LIMIT ?1",
+    /// Export the whole log as JSON, signed with the device key `cert`.
+    ///
+    /// The result is an [`AuditExport`]: all entries oldest first, the final
+    /// chain hash, the signer's public key and the signature.
+    pub fn export_json(&self, cert: &SelfSignedCert) -> Result<String, PresswerkError> {
+        let entries = Self::query_entries(&self.conn, "ORDER BY id ASC", [])?;
+        let chain_hash = entries
+            .last()
+            .map_or_else(|| GENESIS_HASH.to_string(), |e| e.entry_hash.clone());
+
+        let mut export = AuditExport {
+            entries,
+            chain_hash,
+            public_key: hex::encode(cert.public_key_der()),
+            signature: String::new(),
+        };
+        export.signature = hex::encode(cert.sign(&export.signed_payload()?)?);
+
+        debug!(entries = export.entries.len(), "audit log exported");
+        Ok(serde_json::to_string_pretty(&export)?)
+    }
+
+    /// Load an export produced by [`export_json`](Self::export_json) into a
+    /// new in-memory log, after checking its signature and hash chain.
+    ///
+    /// `trusted_public_key` is the DER public key of the device the export
+    /// is expected to come from, obtained separately (e.g. from
+    /// [`SelfSignedCert::public_key_der`]).  The key carried in the export is
+    /// only informative: an export signed by any other key is rejected.
+    pub fn import_and_verify(
+        json: &str,
+        trusted_public_key: &[u8],
+    ) -> Result<AuditLog, PresswerkError> {
+        let export: AuditExport = serde_json::from_str(json)?;
+
+        let public_key = hex::decode(&export.public_key)
+            .map_err(|e| PresswerkError::Certificate(format!("bad public key: {e}")))?;
+        if public_key != trusted_public_key {
+            return Err(PresswerkError::Certificate(
+                "export is signed by an untrusted key".into(),
+            ));
+        }
+        let signature = hex::decode(&export.signature)
+            .map_err(|e| PresswerkError::Certificate(format!("bad signature: {e}")))?;
+        SelfSignedCert::verify_signature(
+            trusted_public_key,
+            &export.signed_payload()?,
+            &signature,
+        )?;
+
+        let chain_hash = check_chain(&export.entries)?;
+        if chain_hash != export.chain_hash {
+            return Err(PresswerkError::IntegrityMismatch {
+                expected: export.chain_hash,
+                actual: chain_hash,
+            });
+        }
+
+        let log = Self::open_in_memory()?;
+        let tx = log.conn.unchecked_transaction().map_err(db_err)?;
+        for entry in &export.entries {
+            tx.execute(
+                "INSERT INTO audit_log
+                     (id, timestamp, action, document_hash, success, details,
+                      actor, job_id, prev_hash, entry_hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    entry.id,
+                    entry.timestamp,
+                    entry.action,
+                    entry.document_hash,
+                    entry.success,
+                    entry.details,
+                    entry.actor,
+                    entry.job_id,
+                    entry.prev_hash,
+                    entry.entry_hash,
+                ],
             )
             .map_err(db_err)?;
+        }
+        tx.commit().map_err(db_err)?;
+
+        debug!(entries = export.entries.len(), "audit export imported");
+        Ok(log)
+    }
+
+    /// Retrieve all entries for a given document hash, ordered by timestamp
+    /// ascending.
+    pub fn entries_for_hash(&self, document_hash: &str) -> Result<Vec<AuditEntry>, PresswerkError> {
+        Self::query_entries(
+            &self.conn,
+            "WHERE document_hash = ?1 ORDER BY timestamp ASC",
+            params![document_hash],
+        )
+    }
 
-        let rows = stmt
-            .query_map(params![limit], |row| {
-                Ok(AuditEntry {
-                    id: row.get(0)?,
-                    timestamp: row.get(1)?,
-                    action: row.get(2)?,
-                    document_hash: row.get(3)?,
-                    success: row.get::<_, i32>(4)? != 0,
-                    details: row.get(5)?,
-                })
-            })
+    /// Retrieve the most recent `limit` entries, ordered newest-first.
+    pub fn recent_entries(&self, limit: u32) -> Result<Vec<AuditEntry>, PresswerkError> {
+        Self::query_entries(&self.conn, "ORDER BY id DESC LIMIT ?1", params![limit])
+    }
+
+    /// Select entries with the given `WHERE`/`ORDER BY` tail.
+    fn query_entries(
+        conn: &Connection,
+        tail: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<AuditEntry>, PresswerkError> {
+        let mut stmt = conn
+            .prepare(&format!("SELECT {SELECT_COLUMNS} FROM audit_log {tail}"))
             .map_err(db_err)?;
 
+        let rows = stmt.query_map(params, row_to_entry).map_err(db_err)?;
+
         let mut entries = Vec::new();
         for row in rows {
             entries.push(row.map_err(db_err)?);
@@ -205,6 +498,45 @@ pub fn count(&self) -> Result<u64, PresswerkError> {
     }
 }
 
+/// Walk `entries` (oldest first) and return the final chain hash, or the
+/// first link that does not hold.
+fn check_chain(entries: &[AuditEntry]) -> Result<String, PresswerkError> {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for entry in entries {
+        if entry.prev_hash != prev_hash {
+            return Err(PresswerkError::IntegrityMismatch {
+                expected: prev_hash,
+                actual: entry.prev_hash.clone(),
+            });
+        }
+        let computed = entry.compute_hash();
+        if computed != entry.entry_hash {
+            return Err(PresswerkError::IntegrityMismatch {
+                expected: entry.entry_hash.clone(),
+                actual: computed,
+            });
+        }
+        prev_hash = computed;
+    }
+    Ok(prev_hash)
+}
+
+/// Map a row selected with [`SELECT_COLUMNS`] to an [`AuditEntry`].
+fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        action: row.get(2)?,
+        document_hash: row.get(3)?,
+        success: row.get::<_, i32>(4)? != 0,
+        details: row.get(5)?,
+        actor: row.get(6)?,
+        job_id: row.get(7)?,
+        prev_hash: row.get(8)?,
+        entry_hash: row.get(9)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,4 +598,94 @@ fn failure_entry() {
         assert!(!entries[0].success);
         assert_eq!(entries[0].details.as_deref(), Some("bad passphrase"));
     }
+
+    #[test]
+    fn clean_chain_verifies() {
+        let log = make_log();
+        assert!(log.verify_chain().unwrap());
+
+        let job = JobId::new();
+        log.append(AuditEvent::now("user", "print", Some(job)))
+            .unwrap();
+        log.record("encrypt", "aaa", true, None).unwrap();
+        log.append(AuditEvent::now("ipp-server", "cancel", None))
+            .unwrap();
+
+        assert!(log.verify_chain().unwrap());
+
+        let entries = log.recent_entries(3).unwrap();
+        assert_eq!(entries[2].prev_hash, GENESIS_HASH);
+        assert_eq!(entries[1].prev_hash, entries[2].entry_hash);
+        assert_eq!(entries[2].actor.as_deref(), Some("user"));
+        assert_eq!(entries[2].job_id, Some(job.to_string()));
+    }
+
+    #[test]
+    fn tampered_entry_breaks_chain() {
+        let log = make_log();
+        for action in ["encrypt", "print", "decrypt"] {
+            log.append(AuditEvent::now("user", action, None)).unwrap();
+        }
+        assert!(log.verify_chain().unwrap());
+
+        let middle = log.recent_entries(3).unwrap()[1].id;
+        log.conn
+            .execute(
+                "UPDATE audit_log SET action = 'nothing' WHERE id = ?1",
+                params![middle],
+            )
+            .unwrap();
+
+        assert!(!log.verify_chain().unwrap());
+    }
+
+    #[test]
+    fn signed_export_round_trips() {
+        let log = make_log();
+        log.append(AuditEvent::now("user", "print", Some(JobId::new())))
+            .unwrap();
+        log.record("encrypt", "aaa", true, Some("scan.pdf"))
+            .unwrap();
+
+        let cert = SelfSignedCert::generate().unwrap();
+        let json = log.export_json(&cert).unwrap();
+
+        let imported = AuditLog::import_and_verify(&json, cert.public_key_der()).unwrap();
+        assert_eq!(imported.count().unwrap(), 2);
+        assert!(imported.verify_chain().unwrap());
+        assert_eq!(
+            imported.recent_entries(1).unwrap()[0].entry_hash,
+            log.recent_entries(1).unwrap()[0].entry_hash
+        );
+    }
+
+    #[test]
+    fn tampered_export_is_rejected() {
+        let log = make_log();
+        for action in ["encrypt", "print", "decrypt"] {
+            log.append(AuditEvent::now("user", action, None)).unwrap();
+        }
+        let cert = SelfSignedCert::generate().unwrap();
+        let json = log.export_json(&cert).unwrap();
+
+        let mut export: AuditExport = serde_json::from_str(&json).unwrap();
+        export.entries[1].action = "nothing".into();
+        let tampered = serde_json::to_string(&export).unwrap();
+        assert!(AuditLog::import_and_verify(&tampered, cert.public_key_der()).is_err());
+
+        // Re-signing with another key is refused unless the caller trusts
+        // that key, and even then the hash chain shows the edit.
+        let other = SelfSignedCert::generate().unwrap();
+        export.public_key = hex::encode(other.public_key_der());
+        export.signature = hex::encode(other.sign(&export.signed_payload().unwrap()).unwrap());
+        let resigned = serde_json::to_string(&export).unwrap();
+        assert!(matches!(
+            AuditLog::import_and_verify(&resigned, cert.public_key_der()),
+            Err(PresswerkError::Certificate(_))
+        ));
+        assert!(matches!(
+            AuditLog::import_and_verify(&resigned, other.public_key_der()),
+            Err(PresswerkError::IntegrityMismatch { .. })
+        ));
+    }
 }
@@ -13,11 +13,34 @@
 // DER/ASN.1 encoder; that integration belongs in presswerk-print where TLS is
 // actually configured.  The key pair produced here can be fed directly into
 // `rcgen::Certificate::from_params()` or `rustls::PrivateKey`.
+//
+// The subject, subject alternative names and validity window that such a
+// certificate would carry are tracked alongside the key, so the server can
+// tell when it is due for rotation and renew it with the same identity.
+//
+// Saved certificates are replaced atomically, and the private key in them is
+// encrypted with an `EncryptedStorage` key when one is given.  Files from
+// before that, with the key in the clear, still load.
 
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
 use presswerk_core::error::PresswerkError;
 use ring::rand::SystemRandom;
-use ring::signature::{ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair, KeyPair};
-use tracing::{debug, instrument};
+use ring::signature::{
+    ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair, KeyPair,
+    UnparsedPublicKey,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument};
+
+use crate::storage::EncryptedStorage;
+
+/// Subject used by [`SelfSignedCert::generate`].
+pub const DEFAULT_SUBJECT: &str = "Presswerk";
+
+/// Validity period used by [`SelfSignedCert::generate`].
+pub const DEFAULT_VALIDITY_DAYS: i64 = 365;
 
 /// An ECDSA P-256 key pair suitable for TLS server authentication.
 ///
@@ -28,16 +51,57 @@ pub struct SelfSignedCert {
     pkcs8_der: Vec<u8>,
     /// Uncompressed SEC1 public key bytes.
     public_key_der: Vec<u8>,
+    /// Subject common name.
+    subject: String,
+    /// Subject alternative names (host names, `.local` names, addresses).
+    subject_alt_names: Vec<String>,
+    /// Start of the validity period.
+    not_before: DateTime<Utc>,
+    /// End of the validity period.
+    not_after: DateTime<Utc>,
+    /// Whether the key was read from a file that had it encrypted.
+    key_encrypted: bool,
+}
+
+/// On-disk form of a [`SelfSignedCert`]; the public key is re-derived on
+/// load.
+#[derive(Serialize, Deserialize)]
+struct StoredCert {
+    subject: String,
+    subject_alt_names: Vec<String>,
+    not_before: DateTime<Utc>,
+    not_after: DateTime<Utc>,
+    /// Hex-encoded PKCS#8 private key, or hex-encoded
+    /// [`EncryptedStorage`] ciphertext of it when `encrypted` is set.
+    private_key: String,
+    #[serde(default)]
+    encrypted: bool,
 }
 
 impl SelfSignedCert {
-    /// Generate a fresh ECDSA P-256 key pair using the OS CSPRNG.
+    /// Generate a fresh ECDSA P-256 key pair using the OS CSPRNG, valid for
+    /// [`DEFAULT_VALIDITY_DAYS`] for `localhost`.
     ///
     /// This does **not** produce an X.509 certificate — only the raw key
     /// material.  See the module-level docs for how to turn this into a
     /// self-signed cert with `rcgen`.
     #[instrument]
     pub fn generate() -> Result<Self, PresswerkError> {
+        Self::generate_for(
+            DEFAULT_SUBJECT,
+            &["localhost".to_string()],
+            Duration::days(DEFAULT_VALIDITY_DAYS),
+        )
+    }
+
+    /// Generate a fresh key pair for `subject` and `subject_alt_names`,
+    /// valid from now for `validity`.
+    #[instrument(skip(subject_alt_names))]
+    pub fn generate_for(
+        subject: &str,
+        subject_alt_names: &[String],
+        validity: Duration,
+    ) -> Result<Self, PresswerkError> {
         let rng = SystemRandom::new();
 
         let pkcs8_document = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng)
@@ -57,12 +121,145 @@ pub fn generate() -> Result<Self, PresswerkError> {
             "ECDSA P-256 key pair generated"
         );
 
+        let not_before = Utc::now();
         Ok(Self {
             pkcs8_der,
             public_key_der,
+            subject: subject.to_string(),
+            subject_alt_names: subject_alt_names.to_vec(),
+            not_before,
+            not_after: not_before + validity,
+            key_encrypted: false,
         })
     }
 
+    /// Generate a new key pair with the same subject and SANs, valid for
+    /// [`DEFAULT_VALIDITY_DAYS`] from now.
+    pub fn renew(&self) -> Result<Self, PresswerkError> {
+        let renewed = Self::generate_for(
+            &self.subject,
+            &self.subject_alt_names,
+            Duration::days(DEFAULT_VALIDITY_DAYS),
+        )?;
+        info!(
+            subject = %self.subject,
+            expires_at = %renewed.not_after,
+            "certificate renewed"
+        );
+        Ok(renewed)
+    }
+
+    /// Subject common name.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Subject alternative names.
+    pub fn subject_alt_names(&self) -> &[String] {
+        &self.subject_alt_names
+    }
+
+    /// When the certificate stops being valid.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.not_after
+    }
+
+    /// Whether the certificate expires (or has expired) within `window`
+    /// from now.
+    pub fn is_expiring_within(&self, window: Duration) -> bool {
+        self.not_after <= Utc::now() + window
+    }
+
+    // -- Persistence ------------------------------------------------------------
+
+    /// Write the key and its metadata to `path` as JSON, readable only by
+    /// the owner on Unix, with the private key encrypted by `key_storage`
+    /// if given.
+    ///
+    /// The file is written beside `path` and renamed over it, so a crash
+    /// leaves either the old certificate or the new one.
+    pub fn save(
+        &self,
+        path: &Path,
+        key_storage: Option<&EncryptedStorage>,
+    ) -> Result<(), PresswerkError> {
+        let private_key = match key_storage {
+            Some(storage) => storage.encrypt(&self.pkcs8_der)?,
+            None => self.pkcs8_der.clone(),
+        };
+        let stored = StoredCert {
+            subject: self.subject.clone(),
+            subject_alt_names: self.subject_alt_names.clone(),
+            not_before: self.not_before,
+            not_after: self.not_after,
+            private_key: hex::encode(private_key),
+            encrypted: key_storage.is_some(),
+        };
+        let json = serde_json::to_vec_pretty(&stored)?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        std::io::Write::write_all(&mut file, &json)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        debug!(path = %path.display(), encrypted = stored.encrypted, "certificate saved");
+        Ok(())
+    }
+
+    /// Read a certificate written by [`save`](Self::save), decrypting its
+    /// key with `key_storage`.
+    ///
+    /// A key saved in the clear loads with or without `key_storage`; an
+    /// encrypted one needs the storage it was saved with.
+    pub fn load(
+        path: &Path,
+        key_storage: Option<&EncryptedStorage>,
+    ) -> Result<Self, PresswerkError> {
+        let stored: StoredCert = serde_json::from_slice(&std::fs::read(path)?)?;
+        let private_key = hex::decode(&stored.private_key)
+            .map_err(|e| PresswerkError::Certificate(format!("bad private key encoding: {e}")))?;
+        let pkcs8_der = match (stored.encrypted, key_storage) {
+            (false, _) => private_key,
+            (true, Some(storage)) => storage.decrypt(&private_key)?,
+            (true, None) => {
+                return Err(PresswerkError::Certificate(
+                    "private key is encrypted and no key storage was given".into(),
+                ));
+            }
+        };
+
+        let rng = SystemRandom::new();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &pkcs8_der, &rng)
+            .map_err(|e| PresswerkError::Certificate(format!("key parsing failed: {e}")))?;
+        let public_key_der = key_pair.public_key().as_ref().to_vec();
+
+        Ok(Self {
+            pkcs8_der,
+            public_key_der,
+            subject: stored.subject,
+            subject_alt_names: stored.subject_alt_names,
+            not_before: stored.not_before,
+            not_after: stored.not_after,
+            key_encrypted: stored.encrypted,
+        })
+    }
+
+    /// Whether the private key was encrypted in the file this certificate
+    /// was loaded from; `false` for newly generated certificates.
+    pub fn is_key_encrypted(&self) -> bool {
+        self.key_encrypted
+    }
+
     /// The PKCS#8 v1 DER-encoded private key.
     ///
     /// Pass this to `rustls::pki_types::PrivateKeyDer::Pkcs8` or to `rcgen`
@@ -94,12 +291,23 @@ pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, PresswerkError> {
 
         Ok(sig.as_ref().to_vec())
     }
+
+    /// Check a signature made by [`sign`](Self::sign) against the SEC1
+    /// `public_key` of the signer.
+    pub fn verify_signature(
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), PresswerkError> {
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key)
+            .verify(message, signature)
+            .map_err(|_| PresswerkError::Certificate("signature does not verify".into()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ring::signature::{ECDSA_P256_SHA256_ASN1, UnparsedPublicKey};
 
     #[test]
     fn generate_key_pair() {
@@ -131,6 +339,66 @@ fn sign_and_verify() {
             .expect("signature verification failed");
     }
 
+    #[test]
+    fn short_lived_cert_is_expiring_and_renews_with_same_identity() {
+        let sans = vec!["presswerk.local".to_string(), "192.168.1.5".to_string()];
+        let cert = SelfSignedCert::generate_for("Presswerk Test", &sans, Duration::minutes(10))
+            .expect("generate");
+
+        assert!(!cert.is_expiring_within(Duration::minutes(5)));
+        assert!(cert.is_expiring_within(Duration::minutes(15)));
+
+        let renewed = cert.renew().expect("renew");
+        assert_eq!(renewed.subject(), "Presswerk Test");
+        assert_eq!(renewed.subject_alt_names(), sans.as_slice());
+        assert!(!renewed.is_expiring_within(Duration::days(DEFAULT_VALIDITY_DAYS - 1)));
+        assert_ne!(
+            renewed.private_key_pkcs8_der(),
+            cert.private_key_pkcs8_der()
+        );
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("server-cert.json");
+        let cert = SelfSignedCert::generate().expect("generate");
+
+        cert.save(&path, None).expect("save");
+        let loaded = SelfSignedCert::load(&path, None).expect("load");
+        assert_eq!(loaded.private_key_pkcs8_der(), cert.private_key_pkcs8_der());
+        assert_eq!(loaded.public_key_der(), cert.public_key_der());
+        assert_eq!(loaded.expires_at(), cert.expires_at());
+        assert_eq!(loaded.subject_alt_names(), ["localhost".to_string()]);
+        assert!(!loaded.is_key_encrypted());
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn saved_key_is_encrypted_with_the_storage_key() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("server-cert.json");
+        let storage = EncryptedStorage::new("certificate-key");
+        let cert = SelfSignedCert::generate().expect("generate");
+
+        cert.save(&path, Some(&storage)).expect("save");
+        let file = std::fs::read_to_string(&path).unwrap();
+        assert!(!file.contains(&hex::encode(cert.private_key_pkcs8_der())));
+
+        let loaded = SelfSignedCert::load(&path, Some(&storage)).expect("load");
+        assert_eq!(loaded.private_key_pkcs8_der(), cert.private_key_pkcs8_der());
+        assert!(loaded.is_key_encrypted());
+
+        assert!(SelfSignedCert::load(&path, None).is_err());
+        let other = EncryptedStorage::new("some-other-key");
+        assert!(SelfSignedCert::load(&path, Some(&other)).is_err());
+
+        // A key saved in the clear still loads when a storage is given.
+        cert.save(&path, None).expect("save plain");
+        let plain = SelfSignedCert::load(&path, Some(&storage)).expect("load plain");
+        assert!(!plain.is_key_encrypted());
+    }
+
     #[test]
     fn different_keys_each_time() {
         let a = SelfSignedCert::generate().expect("gen a");
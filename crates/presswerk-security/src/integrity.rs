@@ -1,11 +1,18 @@
 // SPDX-License-Identifier: PMPL-1.0-or-later
 // Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
 //
-// Document integrity — SHA-256 hashing for tamper detection.
+// Document integrity — SHA-256 hashing for tamper detection, and HMAC-SHA256
+// for data that has to be authenticated with a shared key.
 
 use presswerk_core::error::PresswerkError;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+/// Domain separator mixed into [`IntegrityProof::signature`] so the seal
+/// cannot be confused with a plain document hash.
+const PROOF_DOMAIN: &str = "presswerk-integrity-proof/v1";
+
 /// Compute the SHA-256 hash of `data` and return it as a lowercase hex string.
 ///
 /// Used throughout Presswerk to fingerprint documents before and after
@@ -21,10 +28,11 @@ pub fn hash_bytes(data: &[u8]) -> String {
 ///
 /// Returns `Ok(())` when the hash matches, or
 /// `Err(PresswerkError::IntegrityMismatch)` with the expected and actual
-/// values when it does not.
+/// values when it does not.  The comparison runs in constant time, so a
+/// caller checking attacker-supplied digests leaks nothing through timing.
 pub fn verify_hash(data: &[u8], expected_hex: &str) -> Result<(), PresswerkError> {
     let actual = hash_bytes(data);
-    if actual == expected_hex {
+    if constant_time_eq(actual.as_bytes(), expected_hex.as_bytes()) {
         Ok(())
     } else {
         Err(PresswerkError::IntegrityMismatch {
@@ -34,6 +42,73 @@ pub fn verify_hash(data: &[u8], expected_hex: &str) -> Result<(), PresswerkError
     }
 }
 
+/// Compute HMAC-SHA256 of `data` under `key`.
+///
+/// For keyed authentication (tokens, signed exports, config seals) where a
+/// plain hash would let anyone recompute the digest.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    let mut tag = [0u8; 32];
+    tag.copy_from_slice(hmac::sign(&key, data).as_ref());
+    tag
+}
+
+/// Whether `tag` is the HMAC-SHA256 of `data` under `key`.
+///
+/// The comparison runs in constant time.
+pub fn verify_hmac(key: &[u8], data: &[u8], tag: &[u8]) -> bool {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::verify(&key, data, tag).is_ok()
+}
+
+/// Compare two byte strings without returning early on the first
+/// difference.  Only the length is allowed to leak.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Proof-of-authenticity embedded into document metadata.
+///
+/// `hash` fingerprints the protected content; `signature` is an HMAC of
+/// that hash under a key only the sealer holds, so a forger who edits the
+/// content can recompute the hash but not the seal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityProof {
+    /// SHA-256 hex digest of the protected content.
+    pub hash: String,
+    /// Hex HMAC-SHA256 of the domain separator and `hash`.
+    pub signature: String,
+}
+
+impl IntegrityProof {
+    /// Compute the proof for `content`, sealed with `key`.
+    pub fn for_content(key: &[u8], content: &[u8]) -> Self {
+        let hash = hash_bytes(content);
+        let signature = hex::encode(seal(key, &hash));
+        Self { hash, signature }
+    }
+
+    /// Whether this proof matches `content` and was sealed with `key`.
+    pub fn verify(&self, key: &[u8], content: &[u8]) -> bool {
+        let Ok(signature) = hex::decode(&self.signature) else {
+            return false;
+        };
+        verify_hmac(key, &sealed_message(&self.hash), &signature)
+            && verify_hash(content, &self.hash).is_ok()
+    }
+}
+
+fn seal(key: &[u8], hash: &str) -> [u8; 32] {
+    hmac_sha256(key, &sealed_message(hash))
+}
+
+fn sealed_message(hash: &str) -> Vec<u8> {
+    format!("{PROOF_DOMAIN}:{hash}").into_bytes()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +147,65 @@ fn verify_mismatched_hash() {
             other => panic!("unexpected error variant: {other}"),
         }
     }
+
+    /// RFC 4231 test cases 1, 2 and 6 (the last with a key longer than the
+    /// block size).
+    #[test]
+    fn hmac_rfc4231_vectors() {
+        let cases: [(&[u8], &[u8], &str); 3] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+
+        for (key, data, expected) in cases {
+            let tag = hmac_sha256(key, data);
+            assert_eq!(hex::encode(tag), expected);
+            assert!(verify_hmac(key, data, &tag));
+
+            let mut forged = tag;
+            forged[31] ^= 1;
+            assert!(!verify_hmac(key, data, &forged));
+            assert!(!verify_hmac(key, data, &tag[..16]));
+        }
+    }
+
+    #[test]
+    fn integrity_proof_roundtrip() {
+        let key = b"sealing key";
+        let proof = IntegrityProof::for_content(key, b"page content");
+        assert_eq!(proof.hash, hash_bytes(b"page content"));
+        assert!(proof.verify(key, b"page content"));
+        assert!(!proof.verify(key, b"page c0ntent"));
+        assert!(!proof.verify(b"other key", b"page content"));
+    }
+
+    #[test]
+    fn integrity_proof_rejects_forged_seal() {
+        let key = b"sealing key";
+        let mut proof = IntegrityProof::for_content(key, b"original");
+        proof.hash = hash_bytes(b"forged");
+        assert!(!proof.verify(key, b"forged"));
+
+        // A forger without the key cannot reseal edited content.
+        let forged = IntegrityProof::for_content(b"forger's key", b"forged");
+        assert!(!forged.verify(key, b"forged"));
+        let garbled = IntegrityProof {
+            signature: "not hex".into(),
+            ..IntegrityProof::for_content(key, b"original")
+        };
+        assert!(!garbled.verify(key, b"original"));
+    }
 }
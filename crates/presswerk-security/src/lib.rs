@@ -16,7 +16,7 @@
 pub mod storage;
 
 // PUBLIC API: Re-export core security primitives
-pub use audit::AuditLog;
+pub use audit::{AuditEvent, AuditExport, AuditLog};
 pub use certificates::SelfSignedCert;
-pub use integrity::{hash_bytes, verify_hash};
-pub use storage::EncryptedStorage;
+pub use integrity::{IntegrityProof, hash_bytes, hmac_sha256, verify_hash, verify_hmac};
+pub use storage::{EncryptedStorage, KdfParams};
@@ -5,22 +5,125 @@
 // byte buffers.  Uses passphrase-based encryption via `age::scrypt` so that
 // the user only needs to remember a single passphrase rather than managing
 // raw key files.
+//
+// A handle opened on a directory also keeps named entries there, one age
+// file per entry under `v<N>/`, where N is the key version recorded in the
+// `key-version` file.  Rotating the key re-encrypts every entry into
+// `v<N+1>.tmp/`, renames that to `v<N+1>/` and only then rewrites
+// `key-version`; that rename is the commit point.  `open` deletes whichever
+// generation directory `key-version` does not name, so a crash at any step
+// leaves either the old entries under the old key or the new entries under
+// the new key, never a mix.
+//
+// Handles opened with `open_with_passphrase` write a different format that
+// needs no platform keychain or age identity: the key is derived from the
+// passphrase with Argon2id and the data sealed with ChaCha20-Poly1305.  Each
+// file starts with a header carrying the Argon2 parameters, salt and nonce
+// (authenticated as associated data), so a backup restores on any device
+// given only the passphrase.  Such handles still read age-format files.
 
+use std::fs;
 use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
-use age::secrecy::SecretString;
+use age::secrecy::{ExposeSecret, SecretString};
+use argon2::{Algorithm, Argon2, Params, Version};
 use presswerk_core::error::PresswerkError;
-use tracing::{debug, instrument};
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use tracing::{debug, info, instrument, warn};
+
+/// File in a storage directory holding the current key version.
+const KEY_VERSION_FILE: &str = "key-version";
+
+/// Extension of entry files.
+const ENTRY_EXTENSION: &str = "age";
+
+/// Magic bytes opening a file in the Argon2id format.
+const ARGON2_MAGIC: &[u8; 8] = b"PWARGON1";
+
+/// Length of the Argon2 salt.
+const SALT_LEN: usize = 16;
+
+/// Length of the derived ChaCha20-Poly1305 key.
+const KEY_LEN: usize = 32;
+
+/// Magic, three `u32` parameters, salt and nonce.
+const ARGON2_HEADER_LEN: usize = ARGON2_MAGIC.len() + 12 + SALT_LEN + NONCE_LEN;
+
+/// Largest Argon2 memory cost accepted from a file header (1 GiB), so a
+/// crafted backup cannot make a restore allocate without bound.
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// Most Argon2 passes accepted from a file header, so a crafted backup
+/// cannot make a restore run for hours.
+const MAX_ITERATIONS: u32 = 16;
+
+/// Most Argon2 lanes accepted from a file header.
+const MAX_PARALLELISM: u32 = 16;
+
+/// Argon2id cost parameters for [`EncryptedStorage::open_with_passphrase`].
+///
+/// They are written into every file, so files keep opening after the
+/// defaults change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB.
+    pub memory_kib: u32,
+    /// Number of passes over the memory.
+    pub iterations: u32,
+    /// Degree of parallelism (lanes).
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// The OWASP-recommended minimum for Argon2id: 19 MiB, two passes, one
+    /// lane.
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// These parameters brought within the limits files are read under:
+    /// 1-16 lanes, 1-16 passes, and 8 KiB per lane up to 1 GiB of memory.
+    pub fn clamped(self) -> Self {
+        let parallelism = self.parallelism.clamp(1, MAX_PARALLELISM);
+        Self {
+            memory_kib: self.memory_kib.clamp(8 * parallelism, MAX_MEMORY_KIB),
+            iterations: self.iterations.clamp(1, MAX_ITERATIONS),
+            parallelism,
+        }
+    }
+}
+
+/// Key derived for writing, with the salt and parameters that produced it.
+struct DerivedKey {
+    params: KdfParams,
+    salt: [u8; SALT_LEN],
+    key: LessSafeKey,
+}
 
 /// Passphrase-based encrypted storage backed by the `age` crate.
 ///
 /// Each encrypt/decrypt call is stateless — the passphrase is held only for
 /// the lifetime of the `EncryptedStorage` value so that callers can drop it
-/// promptly after use.
+/// promptly after use.  Handles made with [`open`](Self::open) additionally
+/// store named entries on disk.
 pub struct EncryptedStorage {
     /// The user-supplied passphrase wrapped in a `SecretString` so that it
     /// is zeroised on drop.
     passphrase: SecretString,
+    /// Directory holding the entries, if this handle stores any.
+    dir: Option<PathBuf>,
+    /// How many times the key has been rotated.
+    key_version: u32,
+    /// Present on handles that write the Argon2id format instead of age.
+    derived: Option<DerivedKey>,
 }
 
 impl EncryptedStorage {
@@ -31,15 +134,128 @@ impl EncryptedStorage {
     pub fn new(passphrase: impl Into<String>) -> Self {
         Self {
             passphrase: SecretString::from(passphrase.into()),
+            dir: None,
+            key_version: 0,
+            derived: None,
+        }
+    }
+
+    /// Like [`new`](Self::new), but encrypting with a key derived from the
+    /// passphrase by Argon2id under `params` (see [`KdfParams::clamped`])
+    /// and a fresh random salt.
+    ///
+    /// The derivation runs once here; decrypting a file written with a
+    /// different salt or parameters derives its key again.
+    pub fn with_kdf_params(
+        passphrase: impl Into<String>,
+        params: KdfParams,
+    ) -> Result<Self, PresswerkError> {
+        let passphrase = SecretString::from(passphrase.into());
+        let params = params.clamped();
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| PresswerkError::Encryption("no secure randomness for a salt".into()))?;
+        let key = derive_key(&passphrase, &params, &salt)?;
+
+        Ok(Self {
+            passphrase,
+            dir: None,
+            key_version: 0,
+            derived: Some(DerivedKey { params, salt, key }),
+        })
+    }
+
+    /// Open (or create) a store of named entries in `dir`.
+    ///
+    /// Cleans up after a key rotation that was interrupted, keeping
+    /// whichever side of it `key-version` records.  The passphrase is not
+    /// checked here; reading an entry with the wrong one fails.
+    pub fn open(
+        dir: impl AsRef<Path>,
+        passphrase: impl Into<String>,
+    ) -> Result<Self, PresswerkError> {
+        Self::new(passphrase).attach(dir.as_ref())
+    }
+
+    /// Open (or create) a store of named entries in `dir` whose entries are
+    /// encrypted with an Argon2id-derived key under the default
+    /// [`KdfParams`].
+    ///
+    /// Needs nothing but the passphrase — no keychain, no key file — so it
+    /// works off-device and for backups restored on a new device.  As with
+    /// [`open`](Self::open) the passphrase is not checked until an entry is
+    /// read.
+    pub fn open_with_passphrase(
+        dir: impl AsRef<Path>,
+        passphrase: impl Into<String>,
+    ) -> Result<Self, PresswerkError> {
+        Self::open_with_kdf_params(dir, passphrase, KdfParams::default())
+    }
+
+    /// [`open_with_passphrase`](Self::open_with_passphrase) with explicit
+    /// Argon2id parameters for newly written entries.
+    pub fn open_with_kdf_params(
+        dir: impl AsRef<Path>,
+        passphrase: impl Into<String>,
+        params: KdfParams,
+    ) -> Result<Self, PresswerkError> {
+        Self::with_kdf_params(passphrase, params)?.attach(dir.as_ref())
+    }
+
+    /// Bind this handle to the store in `dir`, creating it if needed.
+    #[instrument(skip(self), fields(dir = %dir.display()))]
+    fn attach(mut self, dir: &Path) -> Result<Self, PresswerkError> {
+        let dir = dir.to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let key_version = match fs::read_to_string(dir.join(KEY_VERSION_FILE)) {
+            Ok(text) => text.trim().parse().map_err(|_| {
+                PresswerkError::Decryption(format!("corrupt key-version marker: {text:?}"))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        // Only the names a rotation from or to this version creates; anything
+        // else in the directory is not ours to delete.
+        let mut leftovers = vec![
+            generation_name(key_version + 1),
+            staging_name(key_version + 1),
+        ];
+        if let Some(previous) = key_version.checked_sub(1) {
+            leftovers.push(generation_name(previous));
+        }
+        for name in leftovers {
+            let path = dir.join(&name);
+            if path.is_dir() {
+                warn!(generation = %name, "removing leftover from an interrupted key rotation");
+                fs::remove_dir_all(path)?;
+            }
         }
+        fs::create_dir_all(dir.join(generation_name(key_version)))?;
+
+        self.dir = Some(dir);
+        self.key_version = key_version;
+        Ok(self)
+    }
+
+    /// How many times the key has been rotated.
+    pub fn key_version(&self) -> u32 {
+        self.key_version
     }
 
     /// Encrypt `plaintext` and return the ciphertext as a `Vec<u8>`.
     ///
-    /// The output is a complete age file (header + encrypted payload) that
-    /// can be written directly to disk.
+    /// The output is a complete age file (header + encrypted payload), or an
+    /// Argon2id-format file for handles with derived keys, that can be
+    /// written directly to disk.
     #[instrument(skip_all, fields(plaintext_len = plaintext.len()))]
     pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, PresswerkError> {
+        if let Some(derived) = &self.derived {
+            return seal(derived, plaintext);
+        }
+
         let encryptor = age::Encryptor::with_user_passphrase(self.passphrase.clone());
         let mut ciphertext = Vec::new();
 
@@ -59,10 +275,14 @@ pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, PresswerkError> {
         Ok(ciphertext)
     }
 
-    /// Decrypt `ciphertext` (a complete age file) and return the original
-    /// plaintext bytes.
+    /// Decrypt `ciphertext` (a complete age or Argon2id-format file) and
+    /// return the original plaintext bytes.
     #[instrument(skip_all, fields(ciphertext_len = ciphertext.len()))]
     pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, PresswerkError> {
+        if ciphertext.starts_with(ARGON2_MAGIC) {
+            return self.open_sealed(ciphertext);
+        }
+
         let decryptor = age::Decryptor::new(ciphertext)
             .map_err(|e| PresswerkError::Decryption(e.to_string()))?;
 
@@ -80,6 +300,327 @@ pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, PresswerkError> {
         debug!(plaintext_len = plaintext.len(), "decryption complete");
         Ok(plaintext)
     }
+
+    /// Decrypt an Argon2id-format file, reusing this handle's derived key
+    /// when the file was written with the same salt and parameters.
+    fn open_sealed(&self, file: &[u8]) -> Result<Vec<u8>, PresswerkError> {
+        if file.len() < ARGON2_HEADER_LEN {
+            return Err(PresswerkError::Decryption("truncated header".into()));
+        }
+        let (header, sealed) = file.split_at(ARGON2_HEADER_LEN);
+
+        let field = |i: usize| {
+            let at = ARGON2_MAGIC.len() + 4 * i;
+            u32::from_be_bytes(header[at..at + 4].try_into().expect("4-byte field"))
+        };
+        let params = KdfParams {
+            memory_kib: field(0),
+            iterations: field(1),
+            parallelism: field(2),
+        };
+        let salt_at = ARGON2_MAGIC.len() + 12;
+        let salt = &header[salt_at..salt_at + SALT_LEN];
+        let nonce = Nonce::try_assume_unique_for_key(&header[salt_at + SALT_LEN..])
+            .map_err(|_| PresswerkError::Decryption("malformed nonce".into()))?;
+
+        let derived;
+        let key = match &self.derived {
+            Some(own) if own.params == params && own.salt == salt => &own.key,
+            _ => {
+                // Nothing within the limits is ever written, so a header
+                // outside them is corrupt or crafted.
+                if params != params.clamped() {
+                    return Err(PresswerkError::Decryption(format!(
+                        "Argon2 parameters out of range ({} KiB, {} passes, {} lanes)",
+                        params.memory_kib, params.iterations, params.parallelism
+                    )));
+                }
+                derived = derive_key(&self.passphrase, &params, salt)?;
+                &derived
+            }
+        };
+
+        let mut buffer = sealed.to_vec();
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(header), &mut buffer)
+            .map_err(|_| PresswerkError::Decryption("wrong passphrase or corrupted data".into()))?;
+
+        debug!(plaintext_len = plaintext.len(), "decryption complete");
+        Ok(plaintext.to_vec())
+    }
+
+    // -- Stored entries -------------------------------------------------------
+
+    /// Encrypt `plaintext` and store it as `name`, replacing any previous
+    /// value.
+    pub fn put(&self, name: &str, plaintext: &[u8]) -> Result<(), PresswerkError> {
+        let path = self.entry_path(name)?;
+        write_atomically(&path, &self.encrypt(plaintext)?)
+    }
+
+    /// Read and decrypt entry `name`, or `None` if there is no such entry.
+    pub fn get(&self, name: &str) -> Result<Option<Vec<u8>>, PresswerkError> {
+        match fs::read(self.entry_path(name)?) {
+            Ok(ciphertext) => self.decrypt(&ciphertext).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete entry `name`.  Deleting a missing entry is not an error.
+    pub fn remove(&self, name: &str) -> Result<(), PresswerkError> {
+        match fs::remove_file(self.entry_path(name)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move entry `from` to `to`, replacing any entry there, without
+    /// decrypting it; so an entry that no longer decrypts can be kept aside.
+    /// Moving a missing entry is not an error.
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), PresswerkError> {
+        match fs::rename(self.entry_path(from)?, self.entry_path(to)?) {
+            Ok(()) => Ok(sync_dir(&self.generation_dir()?)?),
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Names of all stored entries, sorted.
+    pub fn entries(&self) -> Result<Vec<String>, PresswerkError> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(self.generation_dir()?)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(ENTRY_EXTENSION) {
+                continue;
+            }
+            let decoded = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| hex::decode(stem).ok())
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            if let Some(name) = decoded {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    // -- Key rotation ---------------------------------------------------------
+
+    /// Re-encrypt every stored entry under `new_passphrase` and switch this
+    /// handle to it, bumping [`key_version`](Self::key_version).
+    ///
+    /// All entries are written to a new generation before the switch, so a
+    /// failure (including a wrong current passphrase) or a crash leaves the
+    /// store entirely under the old key until the switch has happened.
+    #[instrument(skip_all, fields(from_version = self.key_version))]
+    pub fn rotate_key(&mut self, new_passphrase: impl Into<String>) -> Result<(), PresswerkError> {
+        // Keep the handle's format: a fresh salt under the same parameters.
+        let new = match &self.derived {
+            Some(derived) => Self::with_kdf_params(new_passphrase, derived.params)?,
+            None => Self::new(new_passphrase),
+        };
+        let next_version = self.key_version + 1;
+
+        let Some(dir) = self.dir.clone() else {
+            // Nothing on disk to re-encrypt.
+            self.passphrase = new.passphrase;
+            self.derived = new.derived;
+            self.key_version = next_version;
+            return Ok(());
+        };
+
+        let current = self.generation_dir()?;
+        let staging = dir.join(staging_name(next_version));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir_all(&staging)?;
+
+        let mut count = 0usize;
+        let reencrypted = fs::read_dir(&current)?.try_for_each(|entry| {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(ENTRY_EXTENSION) {
+                return Ok(());
+            }
+            let plaintext = self.decrypt(&fs::read(&path)?)?;
+            let file_name = path.file_name().expect("entry files have names");
+            write_synced(&staging.join(file_name), &new.encrypt(&plaintext)?)?;
+            count += 1;
+            Ok::<(), PresswerkError>(())
+        });
+        if let Err(e) = reencrypted {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+        // The entries must be on disk before the rename can publish them.
+        sync_dir(&staging)?;
+
+        let next = dir.join(generation_name(next_version));
+        if next.exists() {
+            // Left by an earlier rotation that failed before committing.
+            fs::remove_dir_all(&next)?;
+        }
+        fs::rename(&staging, &next)?;
+        sync_dir(&dir)?;
+        // Commit point: from here on the store is under the new key.
+        write_atomically(
+            &dir.join(KEY_VERSION_FILE),
+            next_version.to_string().as_bytes(),
+        )?;
+
+        self.passphrase = new.passphrase;
+        self.derived = new.derived;
+        self.key_version = next_version;
+        if let Err(e) = fs::remove_dir_all(&current) {
+            warn!(error = %e, "could not remove the previous key generation; open() will retry");
+        }
+
+        info!(
+            entries = count,
+            key_version = next_version,
+            "storage key rotated"
+        );
+        Ok(())
+    }
+
+    // -- Paths ----------------------------------------------------------------
+
+    /// Directory holding the entries of the current key version.
+    fn generation_dir(&self) -> Result<PathBuf, PresswerkError> {
+        let dir = self.dir.as_ref().ok_or_else(|| {
+            PresswerkError::Encryption(
+                "storage has no directory; use EncryptedStorage::open".into(),
+            )
+        })?;
+        Ok(dir.join(generation_name(self.key_version)))
+    }
+
+    /// File for entry `name`.  Names are hex-encoded so any string is a
+    /// safe file name.
+    fn entry_path(&self, name: &str) -> Result<PathBuf, PresswerkError> {
+        Ok(self
+            .generation_dir()?
+            .join(format!("{}.{ENTRY_EXTENSION}", hex::encode(name))))
+    }
+}
+
+/// A random passphrase (256 bits, hex-encoded) for stores whose passphrase
+/// lives in the platform keychain rather than in the user's head.
+pub fn generate_passphrase() -> Result<String, PresswerkError> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| PresswerkError::Encryption("no secure randomness for a passphrase".into()))?;
+    Ok(hex::encode(bytes))
+}
+
+/// Derive the ChaCha20-Poly1305 key for `passphrase` with Argon2id.
+fn derive_key(
+    passphrase: &SecretString,
+    params: &KdfParams,
+    salt: &[u8],
+) -> Result<LessSafeKey, PresswerkError> {
+    let argon2_params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| PresswerkError::Encryption(format!("invalid Argon2 parameters: {e}")))?;
+
+    let mut key = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+        .hash_password_into(passphrase.expose_secret().as_bytes(), salt, &mut key)
+        .map_err(|e| PresswerkError::Encryption(format!("key derivation failed: {e}")))?;
+
+    let unbound = UnboundKey::new(&CHACHA20_POLY1305, &key);
+    key.fill(0);
+    unbound
+        .map(LessSafeKey::new)
+        .map_err(|_| PresswerkError::Encryption("derived key has the wrong length".into()))
+}
+
+/// Encrypt `plaintext` into an Argon2id-format file under `derived`.
+///
+/// Layout: magic, memory cost, iterations, parallelism (big-endian `u32`s),
+/// salt, nonce, then the ciphertext with its tag.  The header is the
+/// associated data, so tampering with the parameters fails decryption.
+fn seal(derived: &DerivedKey, plaintext: &[u8]) -> Result<Vec<u8>, PresswerkError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| PresswerkError::Encryption("no secure randomness for a nonce".into()))?;
+
+    let mut file = Vec::with_capacity(ARGON2_HEADER_LEN + plaintext.len() + 16);
+    file.extend_from_slice(ARGON2_MAGIC);
+    for value in [
+        derived.params.memory_kib,
+        derived.params.iterations,
+        derived.params.parallelism,
+    ] {
+        file.extend_from_slice(&value.to_be_bytes());
+    }
+    file.extend_from_slice(&derived.salt);
+    file.extend_from_slice(&nonce);
+
+    let mut sealed = plaintext.to_vec();
+    derived
+        .key
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&file[..]),
+            &mut sealed,
+        )
+        .map_err(|_| PresswerkError::Encryption("sealing failed".into()))?;
+    file.extend_from_slice(&sealed);
+
+    debug!(ciphertext_len = file.len(), "encryption complete");
+    Ok(file)
+}
+
+/// Name of the directory holding key version `version`'s entries.
+fn generation_name(version: u32) -> String {
+    format!("v{version}")
+}
+
+/// Name of the directory key version `version`'s entries are written to
+/// before a rotation commits.
+fn staging_name(version: u32) -> String {
+    format!("{}.tmp", generation_name(version))
+}
+
+/// Write `bytes` to a sibling temp file and rename it over `path`, syncing
+/// both the file and the directory so the rename survives a power cut.
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), PresswerkError> {
+    let tmp = path.with_extension("tmp");
+    write_synced(&tmp, bytes)?;
+    fs::rename(&tmp, path)?;
+    if let Some(parent) = path.parent() {
+        sync_dir(parent)?;
+    }
+    Ok(())
+}
+
+/// Write `bytes` to `path`, replacing any file there, and flush it to stable
+/// storage.
+fn write_synced(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Flush the entries of directory `path` (creations, renames) to stable
+/// storage.  Directories cannot be opened for syncing on Windows, where
+/// this is a no-op.
+fn sync_dir(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    fs::File::open(path)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -116,6 +657,147 @@ fn wrong_passphrase_fails() {
         );
     }
 
+    #[test]
+    fn rotated_entries_read_back_under_the_new_key_only() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut storage = EncryptedStorage::open(dir.path(), "old-passphrase").expect("open");
+        storage.put("printer/office", b"token-1").expect("put");
+        storage.put("printer/home", b"token-2").expect("put");
+
+        storage.rotate_key("new-passphrase").expect("rotate");
+        assert_eq!(storage.key_version(), 1);
+        assert_eq!(storage.get("printer/office").unwrap().unwrap(), b"token-1");
+
+        let reopened = EncryptedStorage::open(dir.path(), "new-passphrase").expect("reopen");
+        assert_eq!(reopened.key_version(), 1);
+        assert_eq!(
+            reopened.entries().unwrap(),
+            ["printer/home", "printer/office"]
+        );
+        assert_eq!(reopened.get("printer/home").unwrap().unwrap(), b"token-2");
+
+        let stale = EncryptedStorage::open(dir.path(), "old-passphrase").expect("open");
+        assert!(
+            stale.get("printer/home").is_err(),
+            "old key must no longer work"
+        );
+    }
+
+    #[test]
+    fn renamed_entry_keeps_its_ciphertext() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let storage = EncryptedStorage::open(dir.path(), "passphrase").expect("open");
+        storage.put("config", b"damaged").expect("put");
+        let sealed = std::fs::read(storage.entry_path("config").unwrap()).unwrap();
+
+        storage.rename("config", "config.damaged").expect("rename");
+        assert!(storage.get("config").unwrap().is_none());
+        assert_eq!(
+            std::fs::read(storage.entry_path("config.damaged").unwrap()).unwrap(),
+            sealed
+        );
+        storage
+            .rename("missing", "elsewhere")
+            .expect("missing entry");
+    }
+
+    #[test]
+    fn interrupted_rotation_keeps_the_old_generation() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let storage = EncryptedStorage::open(dir.path(), "old-passphrase").expect("open");
+        storage.put("secret", b"value").expect("put");
+
+        // A rotation that died before the key-version marker was rewritten.
+        std::fs::create_dir_all(dir.path().join("v1")).unwrap();
+        std::fs::write(dir.path().join("v1").join("garbage.age"), b"partial").unwrap();
+        std::fs::create_dir_all(dir.path().join("v1.tmp")).unwrap();
+        // Not something a rotation creates, so not removed.
+        std::fs::create_dir_all(dir.path().join("vault")).unwrap();
+
+        let reopened = EncryptedStorage::open(dir.path(), "old-passphrase").expect("reopen");
+        assert_eq!(reopened.key_version(), 0);
+        assert_eq!(reopened.get("secret").unwrap().unwrap(), b"value");
+        assert!(!dir.path().join("v1").exists());
+        assert!(!dir.path().join("v1.tmp").exists());
+        assert!(dir.path().join("vault").exists());
+    }
+
+    /// Cheap parameters so the tests do not spend seconds in Argon2.
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn passphrase_store_round_trips_on_a_new_handle() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let storage = EncryptedStorage::open_with_kdf_params(dir.path(), "restore-me", TEST_KDF)
+            .expect("open");
+        storage.put("config", b"{\"paper\":\"A4\"}").expect("put");
+
+        let raw = std::fs::read(storage.entry_path("config").unwrap()).unwrap();
+        assert!(raw.starts_with(ARGON2_MAGIC));
+
+        // A new device: fresh handle, its own salt, same passphrase.
+        let restored = EncryptedStorage::open_with_kdf_params(dir.path(), "restore-me", TEST_KDF)
+            .expect("reopen");
+        assert_eq!(
+            restored.get("config").unwrap().unwrap(),
+            b"{\"paper\":\"A4\"}"
+        );
+    }
+
+    #[test]
+    fn passphrase_store_rejects_wrong_passphrase_and_tampering() {
+        let storage = EncryptedStorage::with_kdf_params("right", TEST_KDF).expect("storage");
+        let ciphertext = storage.encrypt(b"secret").expect("encrypt");
+
+        let wrong = EncryptedStorage::with_kdf_params("wrong", TEST_KDF).expect("storage");
+        assert!(matches!(
+            wrong.decrypt(&ciphertext),
+            Err(PresswerkError::Decryption(_))
+        ));
+
+        // Raising the memory cost in the header breaks authentication.
+        let mut tampered = ciphertext.clone();
+        tampered[ARGON2_MAGIC.len() + 3] ^= 1;
+        assert!(storage.decrypt(&tampered).is_err());
+
+        assert_eq!(storage.decrypt(&ciphertext).unwrap(), b"secret");
+    }
+
+    #[test]
+    fn kdf_params_are_clamped_on_write_and_checked_on_read() {
+        let wild = KdfParams {
+            memory_kib: 0,
+            iterations: 1000,
+            parallelism: 0,
+        };
+        assert_eq!(
+            wild.clamped(),
+            KdfParams {
+                memory_kib: 8,
+                iterations: MAX_ITERATIONS,
+                parallelism: 1,
+            }
+        );
+        assert_eq!(TEST_KDF.clamped(), TEST_KDF);
+        assert_eq!(KdfParams::default().clamped(), KdfParams::default());
+
+        let storage = EncryptedStorage::with_kdf_params("right", TEST_KDF).expect("storage");
+        let ciphertext = storage.encrypt(b"secret").expect("encrypt");
+        // Memory, passes and lanes each rejected past their limit before
+        // any key is derived.
+        for (field, value) in [(0, MAX_MEMORY_KIB + 1), (1, u32::MAX), (2, 64)] {
+            let mut crafted = ciphertext.clone();
+            let at = ARGON2_MAGIC.len() + 4 * field;
+            crafted[at..at + 4].copy_from_slice(&value.to_be_bytes());
+            let err = storage.decrypt(&crafted).unwrap_err();
+            assert!(err.to_string().contains("out of range"), "{err}");
+        }
+    }
+
     #[test]
     fn empty_plaintext() {
         let storage = EncryptedStorage::new("empty-test");